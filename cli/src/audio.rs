@@ -0,0 +1,228 @@
+use std::{
+    fs,
+    io::{self, Cursor},
+    path::PathBuf,
+};
+
+use clap::Subcommand;
+use format::{
+    game::Game,
+    sound::{wem_path, wwise_id, Bnk, BnkStreamType, SoundError, Wem},
+};
+use indicatif::ParallelProgressIterator;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use souls_vfs::{Vfs, VfsOpenError, VfsReadError};
+
+use crate::{convert::file_stem, progress_bar, GameArgs};
+
+/// Read the games' soundbanks.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    kind: Kind,
+}
+
+#[derive(Subcommand, Debug)]
+enum Kind {
+    Extract(ExtractArgs),
+}
+
+/// Extract the WEMs of soundbanks in the `sd` archive: the media embedded in each bank and the
+/// loose WEMs its sounds stream.
+///
+/// WEMs are written to a directory per bank, named by their ID. With `--to wav`, PCM and ADPCM
+/// WEMs are decoded to WAV. Vorbis and Opus WEMs, which most music and voices are, are written as
+/// is for a converter that has Wwise's codebooks, such as vgmstream.
+#[derive(clap::Args, Debug)]
+pub struct ExtractArgs {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// Soundbanks to extract, by virtual path or by name, e.g. `/cs_c3251.bnk` or `cs_c3251`.
+    #[arg(required = true)]
+    banks: Vec<String>,
+
+    /// Only extract the media played by this event, by name or ID, e.g. `Play_c325106000`. May be
+    /// repeated.
+    #[arg(long)]
+    event: Vec<String>,
+
+    /// Language directory to look for streamed voices in first, e.g. `enus`.
+    #[arg(long)]
+    language: Option<String>,
+
+    #[arg(long, value_enum, default_value_t = AudioFormat::Wem)]
+    to: AudioFormat,
+
+    /// Directory to write to.
+    #[arg(long, short, default_value = ".")]
+    output_dir: PathBuf,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum AudioFormat {
+    Wem,
+    /// WAV where the codec can be decoded, WEM otherwise.
+    Wav,
+}
+
+/// A WEM to extract, embedded in its bank or loose in the archive.
+struct Media<'a> {
+    bank: &'a str,
+    id: u32,
+    embedded: Option<&'a [u8]>,
+}
+
+enum Outcome {
+    Written,
+    KeptAsWem,
+    Missing,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    match args.kind {
+        Kind::Extract(args) => extract(args),
+    }
+}
+
+fn extract(args: ExtractArgs) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+    if !matches!(vfs.game(), Game::EldenRing | Game::ArmoredCore6) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} has no Wwise soundbanks", vfs.game()),
+        ));
+    }
+
+    let events = args
+        .event
+        .iter()
+        .map(|event| event.parse::<u32>().unwrap_or_else(|_| wwise_id(event)))
+        .collect::<Vec<_>>();
+
+    let mut banks = Vec::new();
+    for bank in &args.banks {
+        let path = match bank.starts_with('/') {
+            true => bank.clone(),
+            false => format!("/{bank}.bnk"),
+        };
+        let bytes = vfs
+            .read(&path)
+            .map_err(|e| io::Error::other(format!("Could not read {path}: {e}")))?;
+        let bnk = Bnk::from_reader(&mut Cursor::new(bytes))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {e}")))?;
+
+        banks.push((file_stem(&path), bnk));
+    }
+
+    let mut found_events = vec![false; events.len()];
+    let mut media = Vec::new();
+    for (name, bnk) in &banks {
+        let mut ids = Vec::new();
+        if events.is_empty() {
+            ids.extend(bnk.wems.iter().map(|wem| wem.id));
+            ids.extend(bnk.streamed_wems());
+        }
+        for (event, found) in events.iter().zip(&mut found_events) {
+            if let Some(sources) = bnk.event_sources(*event) {
+                ids.extend(sources.iter().map(|source| source.wem_id));
+                *found = true;
+            }
+        }
+        ids.sort_unstable();
+        ids.dedup();
+
+        // Prefetched media only embeds the start of the WEM, so the loose one is preferred.
+        let streamed = bnk
+            .sources()
+            .filter(|source| source.stream_type != BnkStreamType::Embedded)
+            .map(|source| source.wem_id)
+            .collect::<Vec<_>>();
+        media.extend(ids.into_iter().map(|id| Media {
+            bank: name,
+            id,
+            embedded: bnk.wem(id).filter(|_| !streamed.contains(&id)),
+        }));
+    }
+
+    for (event, found) in args.event.iter().zip(found_events) {
+        if !found {
+            eprintln!("None of the banks have the event {event}");
+        }
+    }
+
+    let outcomes = media
+        .par_iter()
+        .progress_with(progress_bar(media.len()))
+        .map(|media| extract_media(&vfs, &args, media))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let count = |f: fn(&Outcome) -> bool| outcomes.iter().filter(|o| f(o)).count();
+    status!(
+        "Extracted {} WEMs from {} banks to {}",
+        count(|o| !matches!(o, Outcome::Missing)),
+        banks.len(),
+        args.output_dir.display()
+    );
+
+    let missing = count(|o| matches!(o, Outcome::Missing));
+    if missing > 0 {
+        status!("{missing} streamed WEMs aren't in the archive, try another --language");
+    }
+    let kept = count(|o| matches!(o, Outcome::KeptAsWem));
+    if kept > 0 {
+        status!("{kept} WEMs were kept as WEM, since their codec can't be decoded");
+    }
+
+    Ok(())
+}
+
+fn extract_media(vfs: &Vfs, args: &ExtractArgs, media: &Media) -> io::Result<Outcome> {
+    let bytes = match media.embedded {
+        Some(bytes) => bytes.to_vec(),
+        None => match read_loose(vfs, media.id, args.language.as_deref())? {
+            Some(bytes) => bytes,
+            None => return Ok(Outcome::Missing),
+        },
+    };
+
+    let directory = args.output_dir.join(media.bank);
+    fs::create_dir_all(&directory)?;
+
+    if args.to == AudioFormat::Wav {
+        let wem = Wem::parse(&bytes).map_err(io::Error::other)?;
+        match wem.to_wav() {
+            Ok(wav) => {
+                fs::write(directory.join(format!("{}.wav", media.id)), wav)?;
+                return Ok(Outcome::Written);
+            }
+            Err(SoundError::UnsupportedCodec(_)) => {
+                fs::write(directory.join(format!("{}.wem", media.id)), bytes)?;
+                return Ok(Outcome::KeptAsWem);
+            }
+            Err(e) => return Err(io::Error::other(format!("WEM {}: {e}", media.id))),
+        }
+    }
+
+    fs::write(directory.join(format!("{}.wem", media.id)), bytes)?;
+
+    Ok(Outcome::Written)
+}
+
+/// Read a streamed WEM, from the language directory first if there is one.
+fn read_loose(vfs: &Vfs, id: u32, language: Option<&str>) -> io::Result<Option<Vec<u8>>> {
+    let paths = language
+        .map(|language| wem_path(id, Some(language)))
+        .into_iter()
+        .chain([wem_path(id, None)]);
+
+    for path in paths {
+        match vfs.read(&path) {
+            Ok(bytes) => return Ok(Some(bytes)),
+            Err(VfsReadError::Open(VfsOpenError::NotFound)) => {}
+            Err(e) => return Err(io::Error::other(format!("Could not read {path}: {e}"))),
+        }
+    }
+
+    Ok(None)
+}
@@ -1,6 +1,7 @@
 use std::{fs, io::Read, path::PathBuf};
 
 use clap::Parser;
+use format::game::Game;
 use indicatif::{ParallelProgressIterator, ProgressStyle};
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use souls_vfs::{FileKeyProvider, Vfs};
@@ -8,8 +9,12 @@ use souls_vfs::{FileKeyProvider, Vfs};
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
+    #[arg(long, alias = "erpath")]
+    game_path: PathBuf,
+
+    /// The game to extract from, e.g. `ds3`, detected from the game's executable by default.
     #[arg(long)]
-    erpath: PathBuf,
+    game: Option<Game>,
     #[arg(long)]
     archive: Option<String>,
     #[arg(long)]
@@ -17,20 +22,16 @@ struct Args {
 }
 
 fn main() -> Result<(), std::io::Error> {
+    cli::init_tracing();
     let args = Args::parse();
 
-    let er_path = args.erpath;
-
-    let keys = FileKeyProvider::new("keys");
-    let archives = [
-        er_path.join("Data0"),
-        er_path.join("Data1"),
-        er_path.join("Data2"),
-        er_path.join("Data3"),
-        er_path.join("sd/sd"),
-    ];
+    let game = args
+        .game
+        .or_else(|| Game::detect(&args.game_path))
+        .unwrap_or(Game::EldenRing);
 
-    let vfs = Vfs::create(archives.clone(), &keys).expect("unable to create vfs");
+    let keys = FileKeyProvider::for_game("keys", game);
+    let vfs = Vfs::open_game(game, &args.game_path, &keys).expect("unable to create vfs");
 
     let dictionary = std::fs::read_to_string(args.dictionary)?;
     let lines = dictionary
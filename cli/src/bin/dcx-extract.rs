@@ -11,6 +11,7 @@ struct Args {
 }
 
 fn main() -> Result<(), std::io::Error> {
+    cli::init_tracing();
     let args = Args::parse();
     let path = std::path::PathBuf::from(args.file);
 
@@ -0,0 +1,81 @@
+use std::io;
+
+use clap::{CommandFactory, Parser, Subcommand};
+use cli::{
+    audio, browse, convert, dict, diff, emevd, export_map, extract, grep, hash, info, manifest,
+    mods, pack, param, save, stats, text, tree, verify, vfs, GlobalArgs,
+};
+
+#[derive(Parser, Debug)]
+#[command(name = "fstools", version, about, long_about = None)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+
+    #[command(flatten)]
+    global: GlobalArgs,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    Audio(audio::Args),
+    Browse(browse::Args),
+    /// Print a completion script for a shell, e.g. `fstools completions bash > fstools.bash`.
+    Completions {
+        shell: clap_complete::Shell,
+    },
+    Convert(convert::Args),
+    Diff(diff::Args),
+    Dict(dict::Args),
+    Emevd(emevd::Args),
+    ExportMap(export_map::Args),
+    Extract(extract::Args),
+    Grep(grep::Args),
+    Hash(hash::Args),
+    Info(info::Args),
+    Manifest(manifest::Args),
+    Mod(mods::Args),
+    Pack(pack::Args),
+    Param(param::Args),
+    Save(save::Args),
+    Stats(stats::Args),
+    Text(text::Args),
+    Tree(tree::Args),
+    Verify(verify::Args),
+    Vfs(vfs::Args),
+}
+
+fn main() -> Result<(), io::Error> {
+    cli::init_tracing();
+
+    let cli = Cli::parse();
+    cli.global.apply()?;
+
+    match cli.command {
+        Command::Audio(args) => audio::run(args),
+        Command::Browse(args) => browse::run(args),
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Cli::command(), "fstools", &mut io::stdout());
+            Ok(())
+        }
+        Command::Convert(args) => convert::run(args),
+        Command::Diff(args) => diff::run(args),
+        Command::Dict(args) => dict::run(args),
+        Command::Emevd(args) => emevd::run(args),
+        Command::ExportMap(args) => export_map::run(args),
+        Command::Extract(args) => extract::run(args),
+        Command::Grep(args) => grep::run(args),
+        Command::Hash(args) => hash::run(args),
+        Command::Info(args) => info::run(args),
+        Command::Manifest(args) => manifest::run(args),
+        Command::Mod(args) => mods::run(args),
+        Command::Pack(args) => pack::run(args),
+        Command::Param(args) => param::run(args),
+        Command::Save(args) => save::run(args),
+        Command::Stats(args) => stats::run(args),
+        Command::Text(args) => text::run(args),
+        Command::Tree(args) => tree::run(args),
+        Command::Verify(args) => verify::run(args),
+        Command::Vfs(args) => vfs::run(args),
+    }
+}
@@ -0,0 +1,434 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Write,
+    fs,
+    io::{self, Cursor, Stdout},
+    path::PathBuf,
+};
+
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEventKind},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use format::fmg::Fmg;
+use ratatui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout},
+    style::{Modifier, Style},
+    text::Line,
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame, Terminal,
+};
+use souls_vfs::Vfs;
+
+use crate::{info, output_path, read_dictionary, vfs::strip_prefix_ignore_case, GameArgs};
+
+/// How much of a file to show as text or hex before cutting the preview short.
+const PREVIEW_LIMIT: usize = 0x10000;
+
+/// Browse a game's archives in the terminal, previewing files and extracting them.
+///
+/// Use the arrow keys or `hjkl` to move between directories, space to mark files or directories,
+/// `x` to extract the marked files (or the selected one) and `q` to quit. `PageUp` and `PageDown`
+/// scroll the preview. Text, FMGs and the formats `fstools info` knows are shown as such, and
+/// anything else as hex.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// File name dictionary, one virtual path per line.
+    #[arg(long)]
+    dictionary: PathBuf,
+
+    /// Directory to extract files to.
+    #[arg(long = "out", short, value_name = "PATH", default_value = ".")]
+    output: PathBuf,
+}
+
+struct Entry {
+    name: String,
+    path: String,
+    directory: bool,
+}
+
+struct Browser {
+    vfs: Vfs,
+    output: PathBuf,
+
+    /// Every path in the dictionary that the archives have, sorted.
+    paths: Vec<String>,
+
+    /// The directory being shown, with a trailing `/`.
+    directory: String,
+    entries: Vec<Entry>,
+    list: ListState,
+
+    /// Marked files and directories, directories with a trailing `/`.
+    marked: BTreeSet<String>,
+    preview: String,
+    scroll: u16,
+    status: String,
+}
+
+/// Puts the terminal back the way it was when dropped, even when browsing fails.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen);
+    }
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+    let mut paths = read_dictionary(&args.dictionary)?
+        .into_iter()
+        .map(|path| format!("/{}", path.trim_start_matches('/')))
+        .filter(|path| vfs.contains(path))
+        .collect::<Vec<_>>();
+    paths.sort_by_key(|path| path.to_lowercase());
+    paths.dedup_by_key(|path| path.to_lowercase());
+
+    let mut browser = Browser {
+        vfs,
+        output: args.output,
+        paths,
+        directory: "/".to_string(),
+        entries: Vec::new(),
+        list: ListState::default(),
+        marked: BTreeSet::new(),
+        preview: String::new(),
+        scroll: 0,
+        status: String::new(),
+    };
+    browser.open_directory("/".to_string(), None);
+
+    enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+    browse(&mut terminal, &mut browser)
+}
+
+fn browse(
+    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    browser: &mut Browser,
+) -> io::Result<()> {
+    loop {
+        terminal.draw(|frame| browser.draw(frame))?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Up | KeyCode::Char('k') => browser.select_by(-1),
+            KeyCode::Down | KeyCode::Char('j') => browser.select_by(1),
+            KeyCode::Home => browser.select_by(isize::MIN / 2),
+            KeyCode::End => browser.select_by(isize::MAX / 2),
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => browser.enter(),
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => browser.leave(),
+            KeyCode::Char(' ') => browser.toggle_mark(),
+            KeyCode::Char('x') => browser.extract(),
+            KeyCode::PageDown => browser.scroll = browser.scroll.saturating_add(20),
+            KeyCode::PageUp => browser.scroll = browser.scroll.saturating_sub(20),
+            _ => {}
+        }
+    }
+}
+
+impl Browser {
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status] = *Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(frame.size())
+        else {
+            return;
+        };
+        let [list, preview] = *Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+            .split(main)
+        else {
+            return;
+        };
+
+        let items = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let mark = match self.marked.contains(&entry.path) {
+                    true => "* ",
+                    false => "  ",
+                };
+                ListItem::new(format!("{mark}{}", entry.name))
+            })
+            .collect::<Vec<_>>();
+        let items = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(self.directory.as_str()),
+            )
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(items, list, &mut self.list);
+
+        let title = self.selected().map_or("", |entry| entry.name.as_str());
+        let text = self.preview.lines().map(Line::raw).collect::<Vec<_>>();
+        frame.render_widget(
+            Paragraph::new(text)
+                .block(Block::default().borders(Borders::ALL).title(title))
+                .scroll((self.scroll, 0)),
+            preview,
+        );
+
+        let status_text = match self.status.is_empty() {
+            true => format!(
+                "{} marked | space: mark, x: extract to {}, q: quit",
+                self.marked.len(),
+                self.output.display()
+            ),
+            false => self.status.clone(),
+        };
+        frame.render_widget(Paragraph::new(status_text), status);
+    }
+
+    fn selected(&self) -> Option<&Entry> {
+        self.list
+            .selected()
+            .and_then(|index| self.entries.get(index))
+    }
+
+    /// Show the contents of `directory`, selecting the entry named `select` if there is one.
+    fn open_directory(&mut self, directory: String, select: Option<&str>) {
+        let mut directories = BTreeSet::new();
+        let mut files = Vec::new();
+        for path in &self.paths {
+            let Some(relative) = strip_prefix_ignore_case(path, &directory) else {
+                continue;
+            };
+
+            match relative.split_once('/') {
+                Some((child, _)) => {
+                    directories.insert(format!("{child}/"));
+                }
+                None => files.push(Entry {
+                    name: relative.to_string(),
+                    path: path.clone(),
+                    directory: false,
+                }),
+            }
+        }
+
+        self.entries = directories
+            .into_iter()
+            .map(|name| Entry {
+                path: format!("{directory}{name}"),
+                name,
+                directory: true,
+            })
+            .chain(files)
+            .collect();
+        self.directory = directory;
+
+        let index = select
+            .and_then(|name| self.entries.iter().position(|entry| entry.name == name))
+            .unwrap_or(0);
+        self.list
+            .select((!self.entries.is_empty()).then_some(index));
+        self.update_preview();
+    }
+
+    fn select_by(&mut self, offset: isize) {
+        let Some(selected) = self.list.selected() else {
+            return;
+        };
+
+        let last = self.entries.len().saturating_sub(1);
+        let index = selected.saturating_add_signed(offset).min(last);
+        if index != selected {
+            self.list.select(Some(index));
+            self.update_preview();
+        }
+    }
+
+    fn enter(&mut self) {
+        if let Some(entry) = self.selected().filter(|entry| entry.directory) {
+            let path = entry.path.clone();
+            self.open_directory(path, None);
+        }
+    }
+
+    fn leave(&mut self) {
+        let Some(parent) = self.directory.trim_end_matches('/').rsplit_once('/') else {
+            return;
+        };
+
+        let name = format!("{}/", parent.1);
+        self.open_directory(format!("{}/", parent.0), Some(&name));
+    }
+
+    fn toggle_mark(&mut self) {
+        if let Some(path) = self.selected().map(|entry| entry.path.clone()) {
+            if !self.marked.remove(&path) {
+                self.marked.insert(path);
+            }
+            self.select_by(1);
+        }
+    }
+
+    /// Extract the marked files and the files under marked directories, or the selected file when
+    /// nothing is marked.
+    fn extract(&mut self) {
+        let targets = match self.marked.is_empty() {
+            true => self
+                .selected()
+                .map(|entry| entry.path.clone())
+                .into_iter()
+                .collect(),
+            false => std::mem::take(&mut self.marked),
+        };
+
+        let files = self
+            .paths
+            .iter()
+            .filter(|path| {
+                targets.iter().any(|target| match target.ends_with('/') {
+                    true => strip_prefix_ignore_case(path, target).is_some(),
+                    false => path == &target,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let (mut extracted, mut failed) = (0, 0);
+        for path in &files {
+            let result = self
+                .vfs
+                .read_decompressed(path)
+                .map_err(io::Error::other)
+                .and_then(|bytes| {
+                    let output =
+                        output_path(&self.output, path.strip_suffix(".dcx").unwrap_or(path));
+                    if let Some(directory) = output.parent() {
+                        fs::create_dir_all(directory)?;
+                    }
+                    fs::write(output, bytes)
+                });
+
+            match result {
+                Ok(()) => extracted += 1,
+                Err(_) => failed += 1,
+            }
+        }
+
+        self.status = format!(
+            "Extracted {extracted} files to {}, {failed} failed",
+            self.output.display()
+        );
+    }
+
+    fn update_preview(&mut self) {
+        self.scroll = 0;
+        self.status.clear();
+        self.preview = match self.selected() {
+            Some(entry) if entry.directory => {
+                let count = self
+                    .paths
+                    .iter()
+                    .filter(|path| strip_prefix_ignore_case(path, &entry.path).is_some())
+                    .count();
+                format!("{count} files")
+            }
+            Some(entry) => match self.vfs.read_decompressed(&entry.path) {
+                Ok(bytes) => preview(&entry.path, &bytes),
+                Err(error) => format!("Could not read {}: {error}", entry.path),
+            },
+            None => String::new(),
+        };
+    }
+}
+
+/// Describe the decompressed file at `path` as text, as a summary of its format or as hex.
+fn preview(path: &str, bytes: &[u8]) -> String {
+    let extension = path.to_lowercase();
+    let extension = extension.strip_suffix(".dcx").unwrap_or(&extension);
+    if extension.ends_with(".fmg") {
+        if let Ok(fmg) = Fmg::from_reader(&mut Cursor::new(bytes)) {
+            return fmg
+                .entries
+                .iter()
+                .filter_map(|entry| Some(format!("{}: {}", entry.id, entry.text.as_ref()?)))
+                .collect::<Vec<_>>()
+                .join("\n");
+        }
+    }
+
+    if [b"BND4", b"FLVE", b"TPF\0"]
+        .into_iter()
+        .any(|magic| bytes.starts_with(magic))
+    {
+        let mut summary = Vec::new();
+        let described = info::write_summary(&mut summary, &info::describe(bytes), 0);
+        if described.is_ok() {
+            return String::from_utf8_lossy(&summary).into_owned();
+        }
+    }
+
+    let head = &bytes[..bytes.len().min(PREVIEW_LIMIT)];
+    if let Some(text) = text(head) {
+        return text;
+    }
+
+    head.chunks(16)
+        .enumerate()
+        .map(|(row, chunk)| {
+            let mut hex = String::with_capacity(48);
+            for byte in chunk {
+                let _ = write!(hex, "{byte:02x} ");
+            }
+            let ascii = chunk
+                .iter()
+                .map(|&b| match b.is_ascii_graphic() || b == b' ' {
+                    true => b as char,
+                    false => '.',
+                })
+                .collect::<String>();
+            format!("{:08x}  {hex:<48} {ascii}", row * 16)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `bytes` as text if it looks like UTF-8, or UTF-16 with a byte order mark.
+fn text(bytes: &[u8]) -> Option<String> {
+    if let Some(utf16) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        let units = utf16
+            .chunks_exact(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]));
+        return Some(
+            char::decode_utf16(units)
+                .map(|c| c.unwrap_or('\u{FFFD}'))
+                .collect(),
+        );
+    }
+
+    // A cut off multi-byte character at the end of the preview doesn't make it binary.
+    let text = match std::str::from_utf8(bytes) {
+        Ok(text) => text,
+        Err(error) if error.error_len().is_none() => {
+            std::str::from_utf8(&bytes[..error.valid_up_to()]).ok()?
+        }
+        Err(_) => return None,
+    };
+
+    (!text.contains('\0')).then(|| text.to_string())
+}
@@ -0,0 +1,336 @@
+use std::collections::HashMap;
+
+use serde_json::{json, Value};
+
+use crate::convert::model::Model;
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+
+/// Write `model` as a binary glTF.
+pub(crate) fn to_glb(model: &Model) -> Vec<u8> {
+    let (document, buffer) = document(model, None);
+
+    glb(document, buffer)
+}
+
+/// Write `model` as a glTF document and the contents of the buffer it refers to as `buffer_uri`.
+pub(crate) fn to_gltf(model: &Model, buffer_uri: &str) -> (String, Vec<u8>) {
+    let (document, buffer) = document(model, Some(buffer_uri));
+
+    (
+        serde_json::to_string_pretty(&document).unwrap_or_default(),
+        buffer,
+    )
+}
+
+/// A model placed in a scene, in glTF's coordinates.
+pub(crate) struct Instance {
+    pub name: String,
+
+    /// Index of the model in the scene.
+    pub model: usize,
+    pub translation: [f32; 3],
+
+    /// Quaternion as `[x, y, z, w]`.
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+/// Write `models` as a binary glTF with a node for each of `instances`.
+pub(crate) fn scene_to_glb(models: &[Model], instances: &[Instance]) -> Vec<u8> {
+    let (document, buffer) = scene_document(models, instances, None);
+
+    glb(document, buffer)
+}
+
+/// Write `models` as a glTF document with a node for each of `instances`, and the contents of the
+/// buffer it refers to as `buffer_uri`.
+pub(crate) fn scene_to_gltf(
+    models: &[Model],
+    instances: &[Instance],
+    buffer_uri: &str,
+) -> (String, Vec<u8>) {
+    let (document, buffer) = scene_document(models, instances, Some(buffer_uri));
+
+    (
+        serde_json::to_string_pretty(&document).unwrap_or_default(),
+        buffer,
+    )
+}
+
+fn glb(document: Value, mut buffer: Vec<u8>) -> Vec<u8> {
+    let mut document = document.to_string().into_bytes();
+
+    // Chunks are 4 byte aligned, JSON with spaces and binary data with zeroes.
+    document.resize(document.len().next_multiple_of(4), b' ');
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+
+    let length = 12 + 8 + document.len() + 8 + buffer.len();
+    let mut glb = Vec::with_capacity(length);
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2u32.to_le_bytes());
+    glb.extend_from_slice(&(length as u32).to_le_bytes());
+
+    glb.extend_from_slice(&(document.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&document);
+
+    glb.extend_from_slice(&(buffer.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&buffer);
+
+    glb
+}
+
+#[derive(Default)]
+struct Builder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    nodes: Vec<Value>,
+    meshes: Vec<Value>,
+    materials: Vec<Value>,
+    images: Vec<Value>,
+    textures: Vec<Value>,
+
+    /// Index of the texture for each image URI, so models sharing a texture share its image.
+    texture_indices: HashMap<String, usize>,
+}
+
+impl Builder {
+    /// Append `values` to the buffer, returning the index of the accessor for them.
+    fn push<const N: usize>(&mut self, values: &[[f32; N]], bounds: bool) -> usize {
+        let offset = self.buffer.len();
+        for value in values.iter().flatten() {
+            self.buffer.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let kind = match N {
+            2 => "VEC2",
+            3 => "VEC3",
+            _ => "VEC4",
+        };
+        let view = self.view(offset, ARRAY_BUFFER);
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": values.len(),
+            "type": kind,
+        });
+
+        // Required for positions, so that viewers can frame the model.
+        if bounds {
+            let (mut min, mut max) = ([f32::MAX; N], [f32::MIN; N]);
+            for value in values {
+                for i in 0..N {
+                    min[i] = min[i].min(value[i]);
+                    max[i] = max[i].max(value[i]);
+                }
+            }
+            accessor["min"] = json!(min[..]);
+            accessor["max"] = json!(max[..]);
+        }
+
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn push_indices(&mut self, indices: &[u32]) -> usize {
+        let offset = self.buffer.len();
+        for index in indices {
+            self.buffer.extend_from_slice(&index.to_le_bytes());
+        }
+
+        let view = self.view(offset, ELEMENT_ARRAY_BUFFER);
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+        self.accessors.len() - 1
+    }
+
+    fn view(&mut self, offset: usize, target: u32) -> usize {
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": offset,
+            "byteLength": self.buffer.len() - offset,
+            "target": target,
+        }));
+        self.buffer_views.len() - 1
+    }
+
+    /// The primitives of each mesh of `model`, adding its materials to the document.
+    fn push_model(&mut self, model: &Model) -> Vec<Value> {
+        let first_material = self.materials.len();
+        for material in &model.materials {
+            let mut value = json!({
+                "name": material.name,
+                "pbrMetallicRoughness": { "metallicFactor": 0.0 },
+            });
+            if let Some(uri) = &material.base_color {
+                value["pbrMetallicRoughness"]["baseColorTexture"] =
+                    json!({ "index": self.texture(uri) });
+            }
+            if let Some(uri) = &material.normal {
+                value["normalTexture"] = json!({ "index": self.texture(uri) });
+            }
+
+            self.materials.push(value);
+        }
+
+        model
+            .meshes
+            .iter()
+            .map(|mesh| {
+                let mut attributes = json!({ "POSITION": self.push(&mesh.positions, true) });
+                if !mesh.normals.is_empty() {
+                    attributes["NORMAL"] = json!(self.push(&mesh.normals, false));
+                }
+                if !mesh.uvs.is_empty() {
+                    attributes["TEXCOORD_0"] = json!(self.push(&mesh.uvs, false));
+                }
+
+                let mut primitive = json!({
+                    "attributes": attributes,
+                    "indices": self.push_indices(&mesh.indices),
+                });
+                if let Some(material) = mesh.material {
+                    primitive["material"] = json!(first_material + material);
+                }
+
+                primitive
+            })
+            .collect()
+    }
+
+    /// DDS images aren't part of core glTF, so loaders without the extension show the model
+    /// untextured rather than refusing it.
+    fn texture(&mut self, uri: &str) -> usize {
+        if let Some(index) = self.texture_indices.get(uri) {
+            return *index;
+        }
+
+        self.images
+            .push(json!({ "uri": uri, "mimeType": "image/vnd-ms.dds" }));
+        self.textures.push(
+            json!({ "extensions": { "MSFT_texture_dds": { "source": self.images.len() - 1 } } }),
+        );
+        self.texture_indices
+            .insert(uri.to_string(), self.textures.len() - 1);
+
+        self.textures.len() - 1
+    }
+
+    fn finish(self, roots: Vec<usize>, buffer_uri: Option<&str>) -> (Value, Vec<u8>) {
+        let mut buffer = json!({ "byteLength": self.buffer.len() });
+        if let Some(uri) = buffer_uri {
+            buffer["uri"] = json!(uri);
+        }
+
+        let mut document = json!({
+            "asset": { "version": "2.0", "generator": "fstools" },
+            "scene": 0,
+            "scenes": [{ "nodes": roots }],
+            "nodes": self.nodes,
+            "meshes": self.meshes,
+            "materials": self.materials,
+            "accessors": self.accessors,
+            "bufferViews": self.buffer_views,
+            "buffers": [buffer],
+        });
+        if !self.textures.is_empty() {
+            document["images"] = json!(self.images);
+            document["textures"] = json!(self.textures);
+            document["extensionsUsed"] = json!(["MSFT_texture_dds"]);
+        }
+
+        (document, self.buffer)
+    }
+}
+
+fn document(model: &Model, buffer_uri: Option<&str>) -> (Value, Vec<u8>) {
+    let mut builder = Builder::default();
+
+    for primitive in builder.push_model(model) {
+        builder.nodes.push(json!({ "mesh": builder.meshes.len() }));
+        builder.meshes.push(json!({ "primitives": [primitive] }));
+    }
+
+    let mut roots = (0..builder.nodes.len()).collect::<Vec<_>>();
+    let first_bone = builder.nodes.len();
+    for (index, bone) in model.bones.iter().enumerate() {
+        let children = model
+            .bones
+            .iter()
+            .enumerate()
+            .filter(|(_, child)| child.parent == Some(index))
+            .map(|(child, _)| first_bone + child)
+            .collect::<Vec<_>>();
+
+        let mut node = json!({
+            "name": bone.name,
+            "translation": bone.translation,
+            "rotation": bone.rotation,
+            "scale": bone.scale,
+        });
+        if !children.is_empty() {
+            node["children"] = json!(children);
+        }
+        if bone
+            .parent
+            .map_or(true, |parent| parent >= model.bones.len())
+        {
+            roots.push(first_bone + index);
+        }
+
+        builder.nodes.push(node);
+    }
+
+    builder.finish(roots, buffer_uri)
+}
+
+/// Each model becomes one mesh with a primitive per mesh of the model, which every instance of it
+/// refers to. Bones are left out.
+fn scene_document(
+    models: &[Model],
+    instances: &[Instance],
+    buffer_uri: Option<&str>,
+) -> (Value, Vec<u8>) {
+    let mut builder = Builder::default();
+
+    // Models without any meshes get no mesh, and instances of them no node.
+    let meshes = models
+        .iter()
+        .map(|model| {
+            let primitives = builder.push_model(model);
+            if primitives.is_empty() {
+                return None;
+            }
+
+            builder.meshes.push(json!({ "primitives": primitives }));
+            Some(builder.meshes.len() - 1)
+        })
+        .collect::<Vec<_>>();
+
+    for instance in instances {
+        let Some(Some(mesh)) = meshes.get(instance.model) else {
+            continue;
+        };
+
+        builder.nodes.push(json!({
+            "name": instance.name,
+            "mesh": mesh,
+            "translation": instance.translation,
+            "rotation": instance.rotation,
+            "scale": instance.scale,
+        }));
+    }
+
+    let roots = (0..builder.nodes.len()).collect();
+    builder.finish(roots, buffer_uri)
+}
@@ -0,0 +1,331 @@
+use std::io;
+
+use ddsfile::{Caps2, D3DFormat, Dds, DxgiFormat};
+
+const IDENTIFIER: [u8; 12] = [
+    0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n',
+];
+const HEADER_SIZE: usize = 80;
+const LEVEL_INDEX_SIZE: usize = 24;
+
+// Data format descriptor colour models, transfer functions and channel qualifiers.
+const MODEL_RGBSDA: u8 = 1;
+const MODEL_BC1A: u8 = 128;
+const MODEL_BC2: u8 = 129;
+const MODEL_BC3: u8 = 130;
+const MODEL_BC4: u8 = 131;
+const MODEL_BC5: u8 = 132;
+const MODEL_BC6H: u8 = 133;
+const MODEL_BC7: u8 = 134;
+const PRIMARIES_BT709: u8 = 1;
+const TRANSFER_LINEAR: u8 = 1;
+const TRANSFER_SRGB: u8 = 2;
+const ALPHA: u8 = 15;
+const QUALIFIER_LINEAR: u8 = 0x10;
+const QUALIFIER_SIGNED: u8 = 0x40;
+const QUALIFIER_FLOAT: u8 = 0x80;
+
+/// How a texture format is described to KTX2 readers.
+struct Format {
+    vk_format: u32,
+    model: u8,
+    srgb: bool,
+
+    /// Width and height of a block of texels, 4 for block compression and 1 otherwise.
+    block: u32,
+    block_bytes: u32,
+
+    /// Bit offset, bit length, channel and qualifiers, lower and upper value of each sample.
+    samples: &'static [(u16, u8, u8, u32, u32)],
+}
+
+impl Format {
+    const fn compressed(vk_format: u32, model: u8, srgb: bool, block_bytes: u32) -> Self {
+        Self {
+            vk_format,
+            model,
+            srgb,
+            block: 4,
+            block_bytes,
+            samples: &[],
+        }
+    }
+
+    fn from_dds(dds: &Dds) -> Option<Self> {
+        const UNORM: (u32, u32) = (0, u32::MAX);
+        const SNORM: (u32, u32) = (i32::MIN as u32, i32::MAX as u32);
+        const RGBA8: &[(u16, u8, u8, u32, u32)] = &[
+            (0, 8, 0, 0, 255),
+            (8, 8, 1, 0, 255),
+            (16, 8, 2, 0, 255),
+            (24, 8, ALPHA, 0, 255),
+        ];
+        const BGRA8: &[(u16, u8, u8, u32, u32)] = &[
+            (0, 8, 2, 0, 255),
+            (8, 8, 1, 0, 255),
+            (16, 8, 0, 0, 255),
+            (24, 8, ALPHA, 0, 255),
+        ];
+
+        let uncompressed = |vk_format, srgb, samples| Self {
+            vk_format,
+            model: MODEL_RGBSDA,
+            srgb,
+            block: 1,
+            block_bytes: 4,
+            samples,
+        };
+
+        let format = match dds.get_dxgi_format() {
+            Some(DxgiFormat::BC1_UNorm) => Self {
+                samples: &[(0, 64, 1, UNORM.0, UNORM.1)],
+                ..Self::compressed(133, MODEL_BC1A, false, 8)
+            },
+            Some(DxgiFormat::BC1_UNorm_sRGB) => Self {
+                samples: &[(0, 64, 1, UNORM.0, UNORM.1)],
+                ..Self::compressed(134, MODEL_BC1A, true, 8)
+            },
+            Some(format @ (DxgiFormat::BC2_UNorm | DxgiFormat::BC2_UNorm_sRGB)) => Self {
+                samples: &[
+                    (0, 64, ALPHA, UNORM.0, UNORM.1),
+                    (64, 64, 0, UNORM.0, UNORM.1),
+                ],
+                ..Self::compressed(
+                    if format == DxgiFormat::BC2_UNorm {
+                        135
+                    } else {
+                        136
+                    },
+                    MODEL_BC2,
+                    format == DxgiFormat::BC2_UNorm_sRGB,
+                    16,
+                )
+            },
+            Some(format @ (DxgiFormat::BC3_UNorm | DxgiFormat::BC3_UNorm_sRGB)) => Self {
+                samples: &[
+                    (0, 64, ALPHA, UNORM.0, UNORM.1),
+                    (64, 64, 0, UNORM.0, UNORM.1),
+                ],
+                ..Self::compressed(
+                    if format == DxgiFormat::BC3_UNorm {
+                        137
+                    } else {
+                        138
+                    },
+                    MODEL_BC3,
+                    format == DxgiFormat::BC3_UNorm_sRGB,
+                    16,
+                )
+            },
+            Some(DxgiFormat::BC4_UNorm) => Self {
+                samples: &[(0, 64, 0, UNORM.0, UNORM.1)],
+                ..Self::compressed(139, MODEL_BC4, false, 8)
+            },
+            Some(DxgiFormat::BC4_SNorm) => Self {
+                samples: &[(0, 64, QUALIFIER_SIGNED, SNORM.0, SNORM.1)],
+                ..Self::compressed(140, MODEL_BC4, false, 8)
+            },
+            Some(DxgiFormat::BC5_UNorm) => Self {
+                samples: &[(0, 64, 0, UNORM.0, UNORM.1), (64, 64, 1, UNORM.0, UNORM.1)],
+                ..Self::compressed(141, MODEL_BC5, false, 16)
+            },
+            Some(DxgiFormat::BC5_SNorm) => Self {
+                samples: &[
+                    (0, 64, QUALIFIER_SIGNED, SNORM.0, SNORM.1),
+                    (64, 64, 1 | QUALIFIER_SIGNED, SNORM.0, SNORM.1),
+                ],
+                ..Self::compressed(142, MODEL_BC5, false, 16)
+            },
+            // Float sample bounds are the bit patterns of -1.0 and 1.0.
+            Some(DxgiFormat::BC6H_UF16) => Self {
+                samples: &[(0, 128, QUALIFIER_FLOAT, 0, 0x3F80_0000)],
+                ..Self::compressed(143, MODEL_BC6H, false, 16)
+            },
+            Some(DxgiFormat::BC6H_SF16) => Self {
+                samples: &[(
+                    0,
+                    128,
+                    QUALIFIER_FLOAT | QUALIFIER_SIGNED,
+                    0xBF80_0000,
+                    0x3F80_0000,
+                )],
+                ..Self::compressed(144, MODEL_BC6H, false, 16)
+            },
+            Some(format @ (DxgiFormat::BC7_UNorm | DxgiFormat::BC7_UNorm_sRGB)) => Self {
+                samples: &[(0, 128, 0, UNORM.0, UNORM.1)],
+                ..Self::compressed(
+                    if format == DxgiFormat::BC7_UNorm {
+                        145
+                    } else {
+                        146
+                    },
+                    MODEL_BC7,
+                    format == DxgiFormat::BC7_UNorm_sRGB,
+                    16,
+                )
+            },
+            Some(DxgiFormat::R8G8B8A8_UNorm) => uncompressed(37, false, RGBA8),
+            Some(DxgiFormat::R8G8B8A8_UNorm_sRGB) => uncompressed(43, true, RGBA8),
+            Some(DxgiFormat::B8G8R8A8_UNorm) => uncompressed(44, false, BGRA8),
+            Some(DxgiFormat::B8G8R8A8_UNorm_sRGB) => uncompressed(50, true, BGRA8),
+            Some(_) => return None,
+            None => match dds.get_d3d_format()? {
+                D3DFormat::A8B8G8R8 => uncompressed(37, false, RGBA8),
+                D3DFormat::A8R8G8B8 => uncompressed(44, false, BGRA8),
+                _ => return None,
+            },
+        };
+
+        Some(format)
+    }
+
+    fn level_size(&self, width: u32, height: u32, level: u32) -> usize {
+        let blocks = |size: u32| (size >> level).max(1).div_ceil(self.block) as usize;
+        blocks(width) * blocks(height) * self.block_bytes as usize
+    }
+
+    /// The basic data format descriptor block, preceded by the total size of the descriptor.
+    fn descriptor(&self) -> Vec<u8> {
+        let block_size = 24 + 16 * self.samples.len();
+        let mut dfd = Vec::with_capacity(4 + block_size);
+        dfd.extend(((4 + block_size) as u32).to_le_bytes());
+
+        // Khronos vendor, basic descriptor type and version 2 of the specification.
+        dfd.extend(0u32.to_le_bytes());
+        dfd.extend(2u16.to_le_bytes());
+        dfd.extend((block_size as u16).to_le_bytes());
+        let transfer = if self.srgb {
+            TRANSFER_SRGB
+        } else {
+            TRANSFER_LINEAR
+        };
+        dfd.extend([self.model, PRIMARIES_BT709, transfer, 0]);
+
+        let dimension = self.block as u8 - 1;
+        dfd.extend([dimension, dimension, 0, 0]);
+        let mut planes = [0; 8];
+        planes[0] = self.block_bytes as u8;
+        dfd.extend(planes);
+
+        for &(offset, length, mut channel, lower, upper) in self.samples {
+            // Alpha stays linear in sRGB textures.
+            if self.srgb && channel & 0xF == ALPHA {
+                channel |= QUALIFIER_LINEAR;
+            }
+
+            dfd.extend(offset.to_le_bytes());
+            dfd.extend([length - 1, channel, 0, 0, 0, 0]);
+            dfd.extend(lower.to_le_bytes());
+            dfd.extend(upper.to_le_bytes());
+        }
+
+        dfd
+    }
+}
+
+/// Repackage a DDS texture as KTX2, keeping its mipmaps, array layers and cubemap faces as they
+/// are. DDS stores each image with its mipmaps in turn while KTX2 stores each mipmap level with
+/// all of its images, smallest level first.
+pub(crate) fn from_dds(dds: &Dds) -> io::Result<Vec<u8>> {
+    let format = Format::from_dds(dds).ok_or_else(|| {
+        io::Error::other(format!("KTX2 has no equivalent for {}", format_name(dds)))
+    })?;
+
+    let (width, height) = (dds.get_width(), dds.get_height());
+    let levels = dds.get_num_mipmap_levels().max(1);
+    let faces = match dds.header.caps2.contains(Caps2::CUBEMAP) {
+        true => 6,
+        false => 1,
+    };
+    // Without a DX10 header the layer count is the number of cubemap faces.
+    let layers = match dds.header10 {
+        Some(_) => dds.get_num_array_layers().max(1),
+        None => 1,
+    };
+
+    let level_sizes = (0..levels)
+        .map(|level| format.level_size(width, height, level))
+        .collect::<Vec<_>>();
+    let image_size = level_sizes.iter().sum::<usize>();
+    let images = (layers * faces) as usize;
+    if dds.data.len() < image_size * images {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "DDS has less data than its header describes",
+        ));
+    }
+
+    let mut key_values = Vec::new();
+    let entry = b"KTXwriter\0fstools\0";
+    key_values.extend((entry.len() as u32).to_le_bytes());
+    key_values.extend(entry);
+    key_values.resize(key_values.len().next_multiple_of(4), 0);
+
+    let descriptor = format.descriptor();
+    let descriptor_offset = HEADER_SIZE + LEVEL_INDEX_SIZE * levels as usize;
+    let key_values_offset = descriptor_offset + descriptor.len();
+
+    // Levels are aligned to both their blocks and 4 bytes, and every block is a multiple of 4.
+    let alignment = format.block_bytes as usize;
+    let mut data = Vec::new();
+    let mut data_offset = key_values_offset + key_values.len();
+    let mut level_index = vec![(0, 0); levels as usize];
+    for level in (0..levels as usize).rev() {
+        let padding = data_offset.next_multiple_of(alignment) - data_offset;
+        data.resize(data.len() + padding, 0);
+        data_offset += padding;
+
+        let start = data.len();
+        let level_offset = level_sizes[..level].iter().sum::<usize>();
+        for image in 0..images {
+            let offset = image * image_size + level_offset;
+            data.extend(&dds.data[offset..offset + level_sizes[level]]);
+        }
+
+        level_index[level] = (data_offset, data.len() - start);
+        data_offset += data.len() - start;
+    }
+
+    let mut ktx = Vec::with_capacity(data_offset);
+    ktx.extend(IDENTIFIER);
+    for value in [
+        format.vk_format,
+        1,
+        width,
+        height,
+        0,
+        if layers > 1 { layers } else { 0 },
+        faces,
+        levels,
+        0,
+    ] {
+        ktx.extend(value.to_le_bytes());
+    }
+
+    ktx.extend((descriptor_offset as u32).to_le_bytes());
+    ktx.extend((descriptor.len() as u32).to_le_bytes());
+    ktx.extend((key_values_offset as u32).to_le_bytes());
+    ktx.extend((key_values.len() as u32).to_le_bytes());
+    ktx.extend([0; 16]);
+
+    for (offset, length) in level_index {
+        ktx.extend((offset as u64).to_le_bytes());
+        ktx.extend((length as u64).to_le_bytes());
+        ktx.extend((length as u64).to_le_bytes());
+    }
+
+    ktx.extend(descriptor);
+    ktx.extend(key_values);
+    ktx.extend(data);
+
+    Ok(ktx)
+}
+
+/// The DXGI or Direct3D name of the format of `dds`, for error messages.
+pub(crate) fn format_name(dds: &Dds) -> String {
+    match (dds.get_dxgi_format(), dds.get_d3d_format()) {
+        (Some(format), _) => format!("{format:?}"),
+        (None, Some(format)) => format!("{format:?}"),
+        (None, None) => "an unknown format".to_string(),
+    }
+}
@@ -0,0 +1,69 @@
+use std::{fs, io};
+
+use clap::Subcommand;
+use format::bnd4::BND4;
+use souls_vfs::{undo_container_compression, Vfs};
+
+pub(crate) mod gltf;
+pub(crate) mod ktx2;
+pub mod model;
+mod obj;
+pub mod texture;
+
+/// Convert game files to formats other tools can open.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    kind: Kind,
+}
+
+#[derive(Subcommand, Debug)]
+enum Kind {
+    Model(model::Args),
+    Texture(texture::Args),
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    match args.kind {
+        Kind::Model(args) => model::run(args),
+        Kind::Texture(args) => texture::run(args),
+    }
+}
+
+/// Read a file from the VFS if there is one and `path` is absolute, or from disk otherwise, with
+/// any DCX compression undone.
+pub(crate) fn read_input(vfs: Option<&Vfs>, path: &str) -> io::Result<Vec<u8>> {
+    match vfs {
+        Some(vfs) if path.starts_with('/') => vfs
+            .read_decompressed(path)
+            .map_err(|e| io::Error::other(format!("Could not read {path}: {e}"))),
+        _ => undo_container_compression(fs::read(path)?).map_err(io::Error::other),
+    }
+}
+
+/// The decompressed files of a binder whose names end with `extension`, ignoring DCX.
+pub(crate) fn binder_files(bnd: &BND4, extension: &str) -> io::Result<Vec<(String, Vec<u8>)>> {
+    bnd.files
+        .iter()
+        .filter(|file| {
+            let path = BND4::normalize_path(&file.path);
+            path.strip_suffix(".dcx")
+                .unwrap_or(&path)
+                .ends_with(extension)
+        })
+        .map(|file| {
+            let bytes = undo_container_compression(bnd.file_bytes(file).to_vec())
+                .map_err(io::Error::other)?;
+            Ok((BND4::normalize_path(&file.path), bytes))
+        })
+        .collect()
+}
+
+/// The lowercase file name of a path without its directory or extensions, e.g. `c3251` for
+/// `/chr/c3251.chrbnd.dcx` or `N:\...\c3251_a.tif`.
+pub(crate) fn file_stem(path: &str) -> String {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let stem = file_name.split('.').next().unwrap_or(file_name);
+
+    stem.to_lowercase()
+}
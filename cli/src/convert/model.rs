@@ -0,0 +1,524 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+};
+
+use byteorder::LE;
+use format::{
+    bnd4::BND4,
+    flver::{
+        accessor::VertexAttributeAccessor,
+        attribute::VertexAttributeSemantic,
+        face_set::{FaceSet, FaceSetIndices},
+        mesh::Mesh,
+        Flver,
+    },
+    hkx::CollisionMesh,
+    tpf::TPF,
+};
+use souls_vfs::{Vfs, VfsOpenError, VfsReadError};
+use tracing::{debug, warn};
+
+use crate::{
+    convert::{binder_files, file_stem, gltf, obj, read_input},
+    watch, VfsArgs,
+};
+
+/// Convert a FLVER model to glTF or OBJ, along with the textures of its materials as DDS files.
+///
+/// Bones are exported as a node hierarchy when asked for, but vertices aren't bound to them.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// A FLVER, or a binder holding one, on disk or in the game's archives when `--game-dir` is
+    /// given, e.g. `/chr/c3251.chrbnd.dcx`.
+    input: String,
+
+    #[arg(long, value_enum, default_value_t = ModelFormat::Glb)]
+    to: ModelFormat,
+
+    /// Where to write the model, named after the input in the current directory by default.
+    /// Textures are written next to it.
+    #[arg(long = "out", short, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// Level of detail to export, from 0 for the most detailed to 2. Meshes without the level
+    /// fall back to the most detailed.
+    #[arg(long, default_value_t = 0)]
+    lod: u8,
+
+    /// Include the skeleton as a hierarchy of nodes. Not supported by OBJ.
+    #[arg(long)]
+    skeleton: bool,
+
+    /// Keep running, and convert again whenever the input changes on disk.
+    #[arg(long)]
+    watch: bool,
+
+    #[command(flatten)]
+    vfs: VfsArgs,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ModelFormat {
+    /// Binary glTF, with everything but the textures in one file.
+    Glb,
+    /// glTF with its buffer in a separate `.bin`.
+    Gltf,
+    Obj,
+}
+
+impl ModelFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Glb => "glb",
+            Self::Gltf => "gltf",
+            Self::Obj => "obj",
+        }
+    }
+}
+
+/// A model decoded from a FLVER, in glTF's right-handed coordinates.
+pub(crate) struct Model {
+    pub meshes: Vec<ModelMesh>,
+    pub materials: Vec<ModelMaterial>,
+    pub bones: Vec<ModelBone>,
+}
+
+pub(crate) struct ModelMesh {
+    pub positions: Vec<[f32; 3]>,
+
+    /// Empty when the FLVER has no normals in a supported format, likewise for the UVs.
+    pub normals: Vec<[f32; 3]>,
+    pub uvs: Vec<[f32; 2]>,
+
+    /// Triangle list, wound counter-clockwise.
+    pub indices: Vec<u32>,
+    pub material: Option<usize>,
+}
+
+pub(crate) struct ModelMaterial {
+    pub name: String,
+
+    /// File names of the textures written alongside the model.
+    pub base_color: Option<String>,
+    pub normal: Option<String>,
+}
+
+pub(crate) struct ModelBone {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub translation: [f32; 3],
+
+    /// Quaternion as `[x, y, z, w]`.
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let vfs = args.vfs.open_vfs()?;
+
+    watch(args.watch, &[Path::new(&args.input)], || {
+        run_with(&args, vfs.as_ref())
+    })
+}
+
+/// Run with archives that are already open, ignoring the ones `args` name.
+pub(crate) fn run_with(args: &Args, vfs: Option<&Vfs>) -> io::Result<()> {
+    let bytes = read_input(vfs, &args.input)?;
+    let name = file_stem(&args.input);
+
+    let mut tpfs = Vec::new();
+    let flver = if bytes.starts_with(b"BND4") {
+        let bnd = BND4::from_reader(&mut Cursor::new(bytes))?;
+        tpfs.extend(binder_files(&bnd, ".tpf")?);
+
+        let mut flvers = binder_files(&bnd, ".flver")?;
+        let index = flvers
+            .iter()
+            .position(|(path, _)| file_stem(path) == name)
+            .unwrap_or_default();
+        if flvers.is_empty() {
+            return Err(io::Error::other(format!("{} has no FLVER", args.input)));
+        }
+
+        flvers.swap_remove(index).1
+    } else {
+        bytes
+    };
+
+    // Textures not in the model's binder are usually in a `texbnd` of the same name.
+    if let Some(vfs) = vfs {
+        let directory = args
+            .input
+            .rsplit_once('/')
+            .map_or("", |(directory, _)| directory);
+        match vfs.read_decompressed(&format!("{directory}/{name}.texbnd.dcx")) {
+            Ok(bytes) => {
+                let bnd = BND4::from_reader(&mut Cursor::new(bytes))?;
+                tpfs.extend(binder_files(&bnd, ".tpf")?);
+            }
+            Err(VfsReadError::Open(VfsOpenError::NotFound)) => {}
+            Err(e) => warn!(error = %e, "could not read texbnd"),
+        }
+    }
+
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(format!("{name}.{}", args.to.extension())));
+    let directory = output.parent().unwrap_or(Path::new(""));
+
+    let flver = Flver::parse(&flver).map_err(io::Error::other)?;
+    let textures = write_textures(&flver, &tpfs, directory)?;
+    let mut model = Model::from_flver(&flver, args.lod, &textures);
+    if !args.skeleton {
+        model.bones.clear();
+    }
+
+    match args.to {
+        ModelFormat::Glb => fs::write(&output, gltf::to_glb(&model))?,
+        ModelFormat::Gltf => {
+            let buffer = output.with_extension("bin");
+            let buffer_name = buffer.file_name().unwrap_or_default().to_string_lossy();
+            let (document, data) = gltf::to_gltf(&model, &buffer_name);
+            fs::write(&output, document)?;
+            fs::write(buffer, data)?;
+        }
+        ModelFormat::Obj => {
+            if args.skeleton {
+                warn!("OBJ has no skeletons, leaving the bones out");
+            }
+
+            let materials = output.with_extension("mtl");
+            let materials_name = materials.file_name().unwrap_or_default().to_string_lossy();
+            let (document, library) = obj::to_obj(&model, &materials_name);
+            fs::write(&output, document)?;
+            fs::write(materials, library)?;
+        }
+    }
+
+    status!(
+        "Wrote {} meshes and {} textures to {}",
+        model.meshes.len(),
+        textures.len(),
+        output.display()
+    );
+
+    Ok(())
+}
+
+/// Write the textures used by the materials of `flver` to `directory` as DDS files, returning the
+/// file name written for each texture name.
+pub(crate) fn write_textures<'a>(
+    flver: &Flver,
+    tpfs: impl IntoIterator<Item = &'a (String, Vec<u8>)>,
+    directory: &Path,
+) -> io::Result<HashMap<String, String>> {
+    let used = texture_names(flver);
+
+    let mut written = HashMap::new();
+    for (path, bytes) in tpfs {
+        let mut r = Cursor::new(bytes);
+        let tpf = match TPF::from_reader(&mut r) {
+            Ok(tpf) => tpf,
+            Err(error) => {
+                warn!(path, %error, "could not read TPF");
+                continue;
+            }
+        };
+
+        for texture in tpf.textures {
+            let name = texture.name.to_lowercase();
+            if !used.contains(&name) || written.contains_key(&name) {
+                continue;
+            }
+
+            let file_name = format!("{name}.dds");
+            fs::write(directory.join(&file_name), texture.bytes(&mut r)?)?;
+            written.insert(name, file_name);
+        }
+    }
+
+    for name in used.iter().filter(|name| !written.contains_key(*name)) {
+        debug!(name, "texture not found");
+    }
+
+    Ok(written)
+}
+
+/// The lowercase names of the textures used by the materials of `flver`, e.g. `c3251_a`.
+pub(crate) fn texture_names(flver: &Flver) -> Vec<String> {
+    flver
+        .materials()
+        .iter()
+        .flat_map(|material| flver.material_textures(material))
+        .filter_map(|texture| flver.texture_path(texture))
+        .map(|path| file_stem(&path))
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+impl Model {
+    /// Decode the face sets of level of detail `lod` along with the vertices they use. FLVERs are
+    /// left-handed, so X is mirrored and triangles are wound the other way round.
+    pub(crate) fn from_flver(flver: &Flver, lod: u8, textures: &HashMap<String, String>) -> Self {
+        let meshes = flver
+            .meshes
+            .iter()
+            .enumerate()
+            .filter_map(|(index, mesh)| {
+                let model_mesh = ModelMesh::from_flver(flver, mesh, lod);
+                if model_mesh.is_none() {
+                    warn!(index, "skipping mesh without positions or faces");
+                }
+
+                model_mesh
+            })
+            .collect();
+
+        let materials = flver
+            .materials()
+            .iter()
+            .map(|material| {
+                let texture = |kinds: &[&str]| {
+                    flver
+                        .material_textures(material)
+                        .iter()
+                        .find(|texture| {
+                            let kind = flver.texture_type(texture).unwrap_or_default();
+                            let kind = kind.to_lowercase();
+                            kinds.iter().any(|candidate| kind.contains(candidate))
+                        })
+                        .and_then(|texture| flver.texture_path(texture))
+                        .and_then(|path| textures.get(&file_stem(&path)).cloned())
+                };
+
+                ModelMaterial {
+                    name: flver.material_name(material).unwrap_or_default(),
+                    base_color: texture(&["albedo", "diffuse"]),
+                    normal: texture(&["normal", "bumpmap"]),
+                }
+            })
+            .collect();
+
+        let bones = flver
+            .bones()
+            .iter()
+            .map(|bone| {
+                let [x, y, z] = bone.translation.map(|v| v.get());
+                let [rx, ry, rz] = bone.rotation.map(|v| v.get());
+                let [qx, qy, qz, qw] = euler_to_quaternion(rx, ry, rz);
+
+                ModelBone {
+                    name: flver
+                        .string(bone.name_offset.get())
+                        .map(|name| name.to_string())
+                        .unwrap_or_default(),
+                    parent: usize::try_from(bone.parent_index.get() as i16).ok(),
+                    translation: [-x, y, z],
+                    rotation: [qx, -qy, -qz, qw],
+                    scale: bone.scale.map(|v| v.get()),
+                }
+            })
+            .collect();
+
+        Self {
+            meshes,
+            materials,
+            bones,
+        }
+    }
+
+    /// Collision meshes as an untextured model, mirrored like FLVERs.
+    pub(crate) fn from_collision(meshes: &[CollisionMesh]) -> Self {
+        let meshes = meshes
+            .iter()
+            .filter(|mesh| !mesh.vertices.is_empty() && !mesh.indices.is_empty())
+            .map(|mesh| ModelMesh {
+                positions: mesh.vertices.iter().map(|[x, y, z]| [-x, *y, *z]).collect(),
+                normals: Vec::new(),
+                uvs: Vec::new(),
+                indices: mesh
+                    .indices
+                    .chunks_exact(3)
+                    .flat_map(|triangle| [triangle[0], triangle[2], triangle[1]])
+                    .collect(),
+                material: None,
+            })
+            .collect();
+
+        Self {
+            meshes,
+            materials: Vec::new(),
+            bones: Vec::new(),
+        }
+    }
+}
+
+impl ModelMesh {
+    fn from_flver(flver: &Flver, mesh: &Mesh<LE>, lod: u8) -> Option<Self> {
+        let face_set = flver
+            .mesh_face_sets(mesh)
+            .find(|face_set| face_set.lod() == Some(lod))
+            .or_else(|| {
+                flver
+                    .mesh_face_sets(mesh)
+                    .find(|face_set| face_set.is_lod0())
+            })?;
+
+        let mut positions = Vec::new();
+        let mut normals = Vec::new();
+        let mut uvs = Vec::new();
+
+        for buffer in flver.mesh_buffers(mesh) {
+            let Some(layout) = flver
+                .vertex_buffer_layouts
+                .get(buffer.layout_index.get() as usize)
+            else {
+                continue;
+            };
+            let Ok(attributes) = flver.vertex_attributes(layout) else {
+                continue;
+            };
+
+            for attribute in attributes {
+                let Ok(semantic) = VertexAttributeSemantic::try_from(attribute.semantic_id.get())
+                else {
+                    continue;
+                };
+                let accessor = match flver.vertex_attribute_accessor(buffer, attribute) {
+                    Ok(accessor) => accessor,
+                    Err(error) => {
+                        debug!(?semantic, %error, "skipping vertex attribute");
+                        continue;
+                    }
+                };
+
+                use VertexAttributeAccessor as A;
+                match (semantic, accessor) {
+                    (VertexAttributeSemantic::Position, A::Float3(it)) if positions.is_empty() => {
+                        positions = it.map(|[x, y, z]| [-x, y, z]).collect();
+                    }
+                    (VertexAttributeSemantic::Position, A::Float4(it)) if positions.is_empty() => {
+                        positions = it.map(|[x, y, z, _]| [-x, y, z]).collect();
+                    }
+                    (VertexAttributeSemantic::Normal, accessor) if normals.is_empty() => {
+                        normals = decode_normals(accessor)
+                            .into_iter()
+                            .map(|[x, y, z]| [-x, y, z])
+                            .collect();
+                    }
+                    (VertexAttributeSemantic::UV, A::UV(it) | A::UVPair(it) | A::Float2(it))
+                        if uvs.is_empty() && attribute.index.get() == 0 =>
+                    {
+                        uvs = it.collect();
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let indices = triangles(flver, face_set)?;
+        if positions.is_empty() || indices.is_empty() {
+            return None;
+        }
+
+        // Attributes that don't cover every vertex would make for an invalid mesh.
+        if normals.len() != positions.len() {
+            normals.clear();
+        }
+        if uvs.len() != positions.len() {
+            uvs.clear();
+        }
+        let vertex_count = positions.len() as u32;
+
+        Some(Self {
+            positions,
+            normals,
+            uvs,
+            indices: indices
+                .into_iter()
+                .filter(|triangle| triangle.iter().all(|index| *index < vertex_count))
+                .flat_map(|[a, b, c]| [a, c, b])
+                .collect(),
+            material: Some(mesh.material_index.get() as usize)
+                .filter(|index| *index < flver.materials().len()),
+        })
+    }
+}
+
+fn decode_normals(accessor: VertexAttributeAccessor) -> Vec<[f32; 3]> {
+    use VertexAttributeAccessor as A;
+
+    let byte = |b: u8| (b as f32 - 127.0) / 127.0;
+    match accessor {
+        A::Float3(it) => it.collect(),
+        A::Float4(it) => it.map(|[x, y, z, _]| [x, y, z]).collect(),
+        A::Byte4A(it) | A::Byte4B(it) | A::Byte4C(it) => {
+            it.map(|[x, y, z, _]| [byte(x), byte(y), byte(z)]).collect()
+        }
+        A::Short4ToFloat4A(it) => it
+            .map(|[x, y, z, _]| [x, y, z].map(|v| v as i16 as f32 / 32767.0))
+            .collect(),
+        A::Short4ToFloat4B(it) => it
+            .map(|[x, y, z, _]| [x, y, z].map(|v| (v as f32 - 32767.0) / 32767.0))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// The triangles of a face set, unrolling triangle strips.
+fn triangles(flver: &Flver, face_set: &FaceSet<LE>) -> Option<Vec<[u32; 3]>> {
+    let (indices, restart) = match flver.face_set_indices(face_set)? {
+        FaceSetIndices::None => return None,
+        FaceSetIndices::U8(data) => (data.iter().map(|i| *i as u32).collect::<Vec<_>>(), 0xFF),
+        FaceSetIndices::U16(data) => (data.iter().map(|i| i.get() as u32).collect(), 0xFFFF),
+        FaceSetIndices::U32(data) => (data.iter().map(|i| i.get()).collect(), u32::MAX),
+    };
+
+    if face_set.triangle_strip == 0 {
+        return Some(
+            indices
+                .chunks_exact(3)
+                .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+                .collect(),
+        );
+    }
+
+    let mut triangles = Vec::new();
+    for strip in indices.split(|index| *index == restart) {
+        for (i, window) in strip.windows(3).enumerate() {
+            let [a, b, c] = [window[0], window[1], window[2]];
+            if a == b || b == c || a == c {
+                continue;
+            }
+
+            // Every other triangle of a strip is wound the other way round.
+            triangles.push(if i % 2 == 0 { [a, b, c] } else { [b, a, c] });
+        }
+    }
+
+    Some(triangles)
+}
+
+/// Bones and the parts of maps rotate about X, then Z, then Y.
+pub(crate) fn euler_to_quaternion(x: f32, y: f32, z: f32) -> [f32; 4] {
+    let axis = |axis: usize, angle: f32| {
+        let mut q = [0.0, 0.0, 0.0, (angle / 2.0).cos()];
+        q[axis] = (angle / 2.0).sin();
+        q
+    };
+
+    multiply(multiply(axis(1, y), axis(2, z)), axis(0, x))
+}
+
+fn multiply([ax, ay, az, aw]: [f32; 4], [bx, by, bz, bw]: [f32; 4]) -> [f32; 4] {
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
@@ -0,0 +1,70 @@
+use std::fmt::Write;
+
+use crate::convert::model::Model;
+
+/// Write `model` as a Wavefront OBJ and the material library it refers to as `materials_uri`.
+pub(crate) fn to_obj(model: &Model, materials_uri: &str) -> (String, String) {
+    let mut obj = String::new();
+    let _ = writeln!(obj, "mtllib {materials_uri}");
+
+    // Indices are 1-based and count every vertex before them, across objects.
+    let mut base = 1;
+    for (index, mesh) in model.meshes.iter().enumerate() {
+        let _ = writeln!(obj, "o mesh{index}");
+        if let Some(material) = mesh.material {
+            let _ = writeln!(obj, "usemtl {}", material_name(model, material));
+        }
+
+        for [x, y, z] in &mesh.positions {
+            let _ = writeln!(obj, "v {x} {y} {z}");
+        }
+        // OBJ texture coordinates start at the bottom of the image rather than the top.
+        for [u, v] in &mesh.uvs {
+            let _ = writeln!(obj, "vt {u} {}", 1.0 - v);
+        }
+        for [x, y, z] in &mesh.normals {
+            let _ = writeln!(obj, "vn {x} {y} {z}");
+        }
+
+        for triangle in mesh.indices.chunks_exact(3) {
+            obj.push('f');
+            for index in triangle {
+                let index = base + index;
+                let _ = match (mesh.uvs.is_empty(), mesh.normals.is_empty()) {
+                    (false, false) => write!(obj, " {index}/{index}/{index}"),
+                    (false, true) => write!(obj, " {index}/{index}"),
+                    (true, false) => write!(obj, " {index}//{index}"),
+                    (true, true) => write!(obj, " {index}"),
+                };
+            }
+            obj.push('\n');
+        }
+
+        base += mesh.positions.len() as u32;
+    }
+
+    let mut mtl = String::new();
+    for (index, material) in model.materials.iter().enumerate() {
+        let _ = writeln!(mtl, "newmtl {}", material_name(model, index));
+        if let Some(texture) = &material.base_color {
+            let _ = writeln!(mtl, "map_Kd {texture}");
+        }
+        if let Some(texture) = &material.normal {
+            let _ = writeln!(mtl, "norm {texture}");
+        }
+        mtl.push('\n');
+    }
+
+    (obj, mtl)
+}
+
+/// Material names aren't unique and may contain spaces, neither of which OBJ allows.
+fn material_name(model: &Model, index: usize) -> String {
+    let name = model
+        .materials
+        .get(index)
+        .map(|material| material.name.replace(char::is_whitespace, "_"))
+        .unwrap_or_default();
+
+    format!("{index}_{name}")
+}
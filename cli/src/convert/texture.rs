@@ -0,0 +1,335 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+};
+
+use ddsfile::{D3DFormat, Dds, DxgiFormat};
+use format::{bnd4::BND4, bxf4::BXF4, dcx::DCX, tpf::TPF};
+use image::{DynamicImage, ImageOutputFormat, RgbaImage};
+use souls_vfs::{undo_container_compression, Vfs};
+use tracing::warn;
+
+use crate::{
+    convert::{binder_files, ktx2, read_input},
+    files_under, watch, VfsArgs,
+};
+
+/// Convert the textures of TPFs to DDS, PNG or KTX2, or pack DDS files back into a TPF.
+///
+/// The input can be a TPF, a binder of them such as a `.texbnd`, a `.tpfbhd` with its `.tpfbdt`
+/// next to it, or a directory to convert every one of those under it. Only PC TPFs are supported,
+/// console textures are swizzled in ways this can't undo yet.
+///
+/// Packing with `--to tpf` takes a directory of DDS files and uses the original TPF as the
+/// template, like `fstools pack`. Each texture is replaced by the DDS file with its name, which has
+/// to be in the same format as the original.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// A TPF, binder, split binder or directory, on disk or in the game's archives when
+    /// `--game-dir` is given, e.g. `/parts/am_m_1000.partsbnd.dcx`. A directory of DDS files with
+    /// `--to tpf`.
+    input: String,
+
+    #[arg(long, value_enum, default_value_t = TextureFormat::Dds)]
+    to: TextureFormat,
+
+    /// Directory to write textures to, the current directory by default. The TPF to write with
+    /// `--to tpf`.
+    #[arg(long = "out", short, value_name = "PATH", required_if_eq("to", "tpf"))]
+    output: Option<PathBuf>,
+
+    /// The original TPF to pack textures into with `--to tpf`, optionally DCX compressed.
+    #[arg(long, required_if_eq("to", "tpf"))]
+    template: Option<PathBuf>,
+
+    /// Keep running, and convert again whenever the input or template changes on disk.
+    #[arg(long)]
+    watch: bool,
+
+    #[command(flatten)]
+    vfs: VfsArgs,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum TextureFormat {
+    /// DDS files, exactly as they're stored in the TPF.
+    Dds,
+    /// The top mipmap of each texture. Only BC1 to BC3 and uncompressed textures can be decoded.
+    Png,
+    /// KTX2 files with the same compression, mipmaps and cubemap faces as the DDS.
+    Ktx2,
+    /// Pack DDS files into a TPF.
+    Tpf,
+}
+
+impl TextureFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Dds => "dds",
+            Self::Png => "png",
+            Self::Ktx2 => "ktx2",
+            Self::Tpf => "tpf",
+        }
+    }
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let vfs = match args.to {
+        TextureFormat::Tpf => None,
+        _ => args.vfs.open_vfs()?,
+    };
+
+    let mut watched = vec![Path::new(&args.input)];
+    watched.extend(args.template.as_deref());
+
+    watch(args.watch, &watched, || run_with(&args, vfs.as_ref()))
+}
+
+/// Run with archives that are already open, ignoring the ones `args` name.
+pub(crate) fn run_with(args: &Args, vfs: Option<&Vfs>) -> io::Result<()> {
+    if args.to == TextureFormat::Tpf {
+        let (Some(template), Some(output)) = (&args.template, &args.output) else {
+            unreachable!("clap requires both with --to tpf");
+        };
+
+        return pack(Path::new(&args.input), template, output);
+    }
+
+    let inputs = match Path::new(&args.input).is_dir() {
+        true => files_under(Path::new(&args.input))?
+            .into_iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .filter(|path| is_texture_container(path))
+            .collect(),
+        false => vec![args.input.clone()],
+    };
+
+    let directory = args.output.clone().unwrap_or_default();
+    if !directory.as_os_str().is_empty() {
+        fs::create_dir_all(&directory)?;
+    }
+
+    let (mut written, mut failed) = (0, 0);
+    for input in &inputs {
+        let tpfs = match tpfs(vfs, input) {
+            Ok(tpfs) => tpfs,
+            Err(error) => {
+                warn!(input, %error, "could not read textures");
+                failed += 1;
+                continue;
+            }
+        };
+
+        for (path, bytes) in tpfs {
+            let mut r = Cursor::new(bytes);
+            let tpf = TPF::from_reader(&mut r)?;
+            if tpf.platform != 0 {
+                warn!(
+                    path,
+                    platform = tpf.platform,
+                    "console TPFs aren't supported"
+                );
+                failed += tpf.textures.len();
+                continue;
+            }
+
+            for texture in &tpf.textures {
+                let output = directory.join(format!("{}.{}", texture.name, args.to.extension()));
+                let result = texture
+                    .bytes(&mut r)
+                    .and_then(|dds| convert(dds, args.to))
+                    .and_then(|bytes| fs::write(&output, bytes));
+
+                match result {
+                    Ok(()) => written += 1,
+                    Err(error) => {
+                        warn!(path, texture = texture.name, %error, "could not convert texture");
+                        failed += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    status!(
+        "Wrote {written} textures from {} files to {}, {failed} failed",
+        inputs.len(),
+        directory.display()
+    );
+
+    Ok(())
+}
+
+/// Whether a file name is one this command reads textures from when given a directory.
+fn is_texture_container(path: &str) -> bool {
+    let path = path.to_lowercase();
+    let path = path.strip_suffix(".dcx").unwrap_or(&path);
+
+    [".tpf", ".texbnd", ".tpfbhd"]
+        .iter()
+        .any(|extension| path.ends_with(extension))
+}
+
+/// The decompressed TPFs of a file, which is either a TPF itself or a binder of them.
+fn tpfs(vfs: Option<&Vfs>, path: &str) -> io::Result<Vec<(String, Vec<u8>)>> {
+    let bytes = read_input(vfs, path)?;
+    if bytes.starts_with(b"TPF\0") {
+        return Ok(vec![(path.to_string(), bytes)]);
+    }
+    if bytes.starts_with(b"BND4") {
+        return binder_files(&BND4::from_reader(&mut Cursor::new(bytes))?, ".tpf");
+    }
+    if !bytes.starts_with(b"BHF4") {
+        return Err(io::Error::other("not a TPF or a binder"));
+    }
+
+    // Split binders keep their data in a file of the same name, e.g. `.tpfbhd` and `.tpfbdt`.
+    let Some(data_path) = path.strip_suffix("bhd") else {
+        return Err(io::Error::other("split binder header doesn't end with bhd"));
+    };
+    let bxf = BXF4::from_bytes(&bytes, read_input(vfs, &format!("{data_path}bdt"))?)?;
+
+    bxf.files
+        .iter()
+        .filter(|file| {
+            let path = BND4::normalize_path(&file.path);
+            path.strip_suffix(".dcx").unwrap_or(&path).ends_with(".tpf")
+        })
+        .map(|file| {
+            let bytes = undo_container_compression(bxf.file_bytes(file).to_vec())
+                .map_err(io::Error::other)?;
+            Ok((BND4::normalize_path(&file.path), bytes))
+        })
+        .collect()
+}
+
+fn convert(dds: Vec<u8>, to: TextureFormat) -> io::Result<Vec<u8>> {
+    match to {
+        TextureFormat::Dds | TextureFormat::Tpf => Ok(dds),
+        TextureFormat::Png => to_png(&read_dds(&dds)?),
+        TextureFormat::Ktx2 => ktx2::from_dds(&read_dds(&dds)?),
+    }
+}
+
+fn read_dds(bytes: &[u8]) -> io::Result<Dds> {
+    Dds::read(bytes).map_err(|e| io::Error::other(format!("Could not read DDS: {e}")))
+}
+
+/// Decode the top mipmap of the first image of `dds` as a PNG.
+// image's DXT decoder is deprecated until it's reworked, but it's still the only one it has.
+#[allow(deprecated)]
+fn to_png(dds: &Dds) -> io::Result<Vec<u8>> {
+    use image::codecs::dxt::{DxtDecoder, DxtVariant};
+
+    let (width, height) = (dds.get_width(), dds.get_height());
+    let variant = match dds.get_dxgi_format() {
+        Some(DxgiFormat::BC1_UNorm | DxgiFormat::BC1_UNorm_sRGB) => Some(DxtVariant::DXT1),
+        Some(DxgiFormat::BC2_UNorm | DxgiFormat::BC2_UNorm_sRGB) => Some(DxtVariant::DXT3),
+        Some(DxgiFormat::BC3_UNorm | DxgiFormat::BC3_UNorm_sRGB) => Some(DxtVariant::DXT5),
+        _ => None,
+    };
+
+    let image = if let Some(variant) = variant {
+        let decoder = DxtDecoder::new(dds.data.as_slice(), width, height, variant)
+            .map_err(io::Error::other)?;
+        DynamicImage::from_decoder(decoder).map_err(io::Error::other)?
+    } else {
+        let bgra = match (dds.get_dxgi_format(), dds.get_d3d_format()) {
+            (Some(DxgiFormat::R8G8B8A8_UNorm | DxgiFormat::R8G8B8A8_UNorm_sRGB), _)
+            | (None, Some(D3DFormat::A8B8G8R8)) => false,
+            (Some(DxgiFormat::B8G8R8A8_UNorm | DxgiFormat::B8G8R8A8_UNorm_sRGB), _)
+            | (None, Some(D3DFormat::A8R8G8B8)) => true,
+            _ => {
+                return Err(io::Error::other(format!(
+                    "can't decode {} to PNG, convert it to DDS or KTX2 instead",
+                    ktx2::format_name(dds)
+                )))
+            }
+        };
+
+        let mut pixels = dds
+            .data
+            .get(..width as usize * height as usize * 4)
+            .ok_or_else(|| io::Error::other("DDS has less data than its header describes"))?
+            .to_vec();
+        if bgra {
+            pixels
+                .chunks_exact_mut(4)
+                .for_each(|pixel| pixel.swap(0, 2));
+        }
+
+        DynamicImage::ImageRgba8(
+            RgbaImage::from_raw(width, height, pixels).expect("pixels fit the image"),
+        )
+    };
+
+    let mut png = Cursor::new(Vec::new());
+    image
+        .write_to(&mut png, ImageOutputFormat::Png)
+        .map_err(io::Error::other)?;
+
+    Ok(png.into_inner())
+}
+
+/// Replace the textures of the TPF at `template` with the DDS files of the same name under
+/// `directory`, compressing the result the way the template was.
+fn pack(directory: &Path, template: &Path, output: &Path) -> io::Result<()> {
+    let mut r = Cursor::new(fs::read(template)?);
+    let dcx = match DCX::has_magic(&mut r)? {
+        true => Some(DCX::from_reader(&mut r).map_err(io::Error::other)?),
+        false => None,
+    };
+    let mut r = match &dcx {
+        Some(dcx) => Cursor::new(dcx.decompressed.clone()),
+        None => r,
+    };
+    let mut tpf = TPF::from_reader(&mut r)?;
+
+    let mut loose = HashMap::new();
+    for file in files_under(directory)? {
+        let name = file.file_name().unwrap_or_default().to_string_lossy();
+        if let Some(stem) = name.to_lowercase().strip_suffix(".dds") {
+            loose.insert(stem.to_string(), file);
+        }
+    }
+
+    let mut data = Vec::with_capacity(tpf.textures.len());
+    let mut replaced = 0;
+    for texture in &mut tpf.textures {
+        let Some(file) = loose.remove(&texture.name.to_lowercase()) else {
+            data.push(texture.bytes(&mut r)?);
+            continue;
+        };
+
+        let dds = fs::read(&file)?;
+        texture.mipmaps = read_dds(&dds)?.get_num_mipmap_levels().max(1) as u8;
+        data.push(dds);
+        replaced += 1;
+    }
+
+    for file in loose.values() {
+        warn!(path = %file.display(), "TPF has no such texture, skipping");
+    }
+
+    let bytes = tpf.to_bytes(&data).map_err(io::Error::other)?;
+    let bytes = match dcx {
+        Some(mut dcx) => {
+            dcx.decompressed = bytes;
+            let mut output = Vec::new();
+            dcx.write(&mut output).map_err(io::Error::other)?;
+            output
+        }
+        None => bytes,
+    };
+    fs::write(output, bytes)?;
+
+    status!(
+        "Replaced {replaced} of {} textures in {}",
+        tpf.textures.len(),
+        template.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,335 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use clap::Subcommand;
+use format::game::Game;
+use serde::Serialize;
+use souls_vfs::Name;
+
+use crate::{dictionary_paths, hash::format_hash, read_dictionary, GameArgs, OutputArgs};
+
+/// Comment a fetched dictionary starts with, so that it can be refreshed from the same place.
+const SOURCE_PREFIX: &str = "# source: ";
+
+/// Maintain file name dictionaries and see how much of a game they name.
+///
+/// The archives only store hashes of their paths, so a dictionary of known paths is what names
+/// their files. Names are found by the community over time, so dictionaries are worth refreshing.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    kind: Kind,
+}
+
+#[derive(Subcommand, Debug)]
+enum Kind {
+    Fetch(FetchArgs),
+    Refresh(RefreshArgs),
+    Coverage(CoverageArgs),
+    Unknown(UnknownArgs),
+}
+
+/// Download a dictionary, one virtual path per line, and remember where it came from for `dict
+/// refresh`.
+///
+/// URLs are downloaded with `curl`, which has to be installed. Local paths and `file://` URLs are
+/// copied.
+#[derive(clap::Args, Debug)]
+pub struct FetchArgs {
+    /// Where to download the dictionary from.
+    url: String,
+
+    /// Where to write the dictionary.
+    #[arg(long = "out", short, value_name = "PATH")]
+    output: PathBuf,
+
+    /// Keep the paths of the dictionary being replaced, e.g. names found locally.
+    #[arg(long)]
+    merge: bool,
+}
+
+/// Download a fetched dictionary again, from where it was fetched from.
+#[derive(clap::Args, Debug)]
+pub struct RefreshArgs {
+    /// Dictionaries to refresh.
+    #[arg(required = true)]
+    dictionaries: Vec<PathBuf>,
+
+    /// Keep the paths the new version of a dictionary doesn't have.
+    #[arg(long)]
+    merge: bool,
+}
+
+/// Report how many of the files in each archive a dictionary names.
+#[derive(clap::Args, Debug)]
+pub struct CoverageArgs {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// File name dictionary, one virtual path per line. May be repeated.
+    #[arg(long, required = true)]
+    dictionary: Vec<PathBuf>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// List the hashes of the files no dictionary names, one per line, for name hunting.
+#[derive(clap::Args, Debug)]
+pub struct UnknownArgs {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// File name dictionary, one virtual path per line. May be repeated.
+    #[arg(long, required = true)]
+    dictionary: Vec<PathBuf>,
+
+    /// Only list the files of these archives, e.g. `Data0`. May be repeated.
+    #[arg(long)]
+    archive: Vec<String>,
+
+    /// Write the list here rather than to stdout.
+    #[arg(long = "out", short, value_name = "PATH")]
+    output: Option<PathBuf>,
+}
+
+/// The `--output json` of `dict coverage`.
+#[derive(Serialize, Default)]
+struct Coverage {
+    files: usize,
+    named: usize,
+
+    /// Paths of the dictionary that aren't in this install, e.g. from other games or versions.
+    absent: usize,
+    archives: BTreeMap<String, ArchiveCoverage>,
+}
+
+#[derive(Serialize, Default)]
+struct ArchiveCoverage {
+    files: usize,
+    named: usize,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    match args.kind {
+        Kind::Fetch(args) => fetch(args),
+        Kind::Refresh(args) => refresh(args),
+        Kind::Coverage(args) => coverage(args),
+        Kind::Unknown(args) => unknown(args),
+    }
+}
+
+fn fetch(args: FetchArgs) -> io::Result<()> {
+    update(&args.url, &args.output, args.merge)
+}
+
+fn refresh(args: RefreshArgs) -> io::Result<()> {
+    for dictionary in &args.dictionaries {
+        let contents = fs::read_to_string(dictionary)?;
+        let url = contents
+            .lines()
+            .next()
+            .and_then(|line| line.strip_prefix(SOURCE_PREFIX))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "{} wasn't fetched with dict fetch, so where it came from isn't known",
+                        dictionary.display()
+                    ),
+                )
+            })?;
+
+        update(url.trim(), dictionary, args.merge)?;
+    }
+
+    Ok(())
+}
+
+/// Download the dictionary at `url` to `output`, reporting what changed.
+fn update(url: &str, output: &Path, merge: bool) -> io::Result<()> {
+    let downloaded = download(url)?;
+    let new = path_set(&downloaded);
+    if new.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{url} has no paths, is it a dictionary?"),
+        ));
+    }
+
+    let old = match fs::read_to_string(output) {
+        Ok(old) => path_set(&old),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeSet::new(),
+        Err(e) => return Err(e),
+    };
+    let added = new.difference(&old).count();
+    let removed = old.difference(&new).count();
+
+    let paths = match merge {
+        true => new.union(&old).collect::<Vec<_>>(),
+        false => new.iter().collect(),
+    };
+
+    let mut contents = format!("{SOURCE_PREFIX}{url}\n");
+    for path in &paths {
+        contents.push_str(path);
+        contents.push('\n');
+    }
+    fs::write(output, contents)?;
+
+    match merge {
+        true => status!(
+            "Wrote {} paths to {}, {added} new",
+            paths.len(),
+            output.display()
+        ),
+        false => status!(
+            "Wrote {} paths to {}, {added} new and {removed} removed",
+            paths.len(),
+            output.display()
+        ),
+    }
+
+    Ok(())
+}
+
+fn download(url: &str) -> io::Result<String> {
+    let local = url
+        .strip_prefix("file://")
+        .or((!url.contains("://")).then_some(url));
+    if let Some(path) = local {
+        return fs::read_to_string(path);
+    }
+
+    let output = process::Command::new("curl")
+        .args(["--fail", "--silent", "--show-error", "--location", url])
+        .output()
+        .map_err(|e| io::Error::other(format!("Could not run curl to download {url}: {e}")))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "Could not download {url}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    String::from_utf8(output.stdout).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{url} is not a text file"),
+        )
+    })
+}
+
+/// The paths of a dictionary, sorted to compare versions of it.
+fn path_set(contents: &str) -> BTreeSet<String> {
+    dictionary_paths(contents).map(str::to_string).collect()
+}
+
+/// The names of every path in `dictionaries`.
+fn named(game: Game, dictionaries: &[PathBuf]) -> io::Result<HashSet<Name>> {
+    let mut names = HashSet::new();
+    for dictionary in dictionaries {
+        names.extend(
+            read_dictionary(dictionary)?
+                .iter()
+                .map(|path| Name::new(game, path)),
+        );
+    }
+
+    Ok(names)
+}
+
+fn coverage(args: CoverageArgs) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+    let named = named(vfs.game(), &args.dictionary)?;
+
+    let mut coverage = Coverage::default();
+    for name in vfs.names() {
+        let Ok(stat) = vfs.stat_name(name) else {
+            continue;
+        };
+        let is_named = named.contains(name);
+
+        let archive = coverage
+            .archives
+            .entry(stat.archive.to_string())
+            .or_default();
+        archive.files += 1;
+        coverage.files += 1;
+        if is_named {
+            archive.named += 1;
+            coverage.named += 1;
+        }
+    }
+    coverage.absent = named
+        .iter()
+        .filter(|name| vfs.stat_name(name).is_err())
+        .count();
+
+    if args.output.is_json() {
+        return args.output.write_json(&coverage);
+    }
+
+    let percent = |named: usize, files: usize| named as f64 * 100.0 / files.max(1) as f64;
+    let mut stdout = io::stdout().lock();
+    for (archive, totals) in &coverage.archives {
+        writeln!(
+            stdout,
+            "{archive}: {} of {} files named ({:.1}%)",
+            totals.named,
+            totals.files,
+            percent(totals.named, totals.files)
+        )?;
+    }
+    writeln!(
+        stdout,
+        "Total: {} of {} files named ({:.1}%)",
+        coverage.named,
+        coverage.files,
+        percent(coverage.named, coverage.files)
+    )?;
+    writeln!(
+        stdout,
+        "{} paths of the dictionary aren't in this install",
+        coverage.absent
+    )
+}
+
+fn unknown(args: UnknownArgs) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+    let named = named(vfs.game(), &args.dictionary)?;
+
+    let mut unknown = vfs
+        .names()
+        .filter(|name| !named.contains(name))
+        .filter(|name| {
+            args.archive.is_empty()
+                || vfs.stat_name(name).is_ok_and(|stat| {
+                    args.archive
+                        .iter()
+                        .any(|archive| archive.eq_ignore_ascii_case(stat.archive))
+                })
+        })
+        .map(|name| name.0)
+        .collect::<Vec<_>>();
+    unknown.sort_unstable();
+
+    let mut list = String::new();
+    for hash in &unknown {
+        list.push_str(&format_hash(vfs.game(), *hash));
+        list.push('\n');
+    }
+
+    match &args.output {
+        Some(output) => fs::write(output, list)?,
+        None => io::stdout().lock().write_all(list.as_bytes())?,
+    }
+    status!("{} files aren't named by the dictionary", unknown.len());
+
+    Ok(())
+}
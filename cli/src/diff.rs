@@ -0,0 +1,423 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fs,
+    io::{self, Cursor, Read, Write},
+    path::PathBuf,
+};
+
+use format::{
+    bnd4::BND4,
+    fmg::Fmg,
+    game::Game,
+    param::{self, Param, Paramdex},
+};
+use indicatif::ParallelProgressIterator;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
+use souls_vfs::{undo_container_compression, Name, Vfs};
+use tracing::warn;
+
+use crate::{
+    extract::glob_set,
+    open_vfs,
+    param::{def_for, RegulationFile},
+    progress_bar, read_dictionary, OutputArgs,
+};
+
+/// How deep to look into binders inside binders.
+const MAX_DEPTH: usize = 4;
+
+/// Compare the archives of two versions of a game and report which files were added, removed or
+/// changed.
+///
+/// The loose `regulation.bin` is compared too. Changed binders are compared file by file, params
+/// row by row when `--def-dir` is given and FMGs entry by entry. Other formats, MSBs included, are
+/// only reported as changed.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// The install directory of the older version.
+    old_dir: PathBuf,
+
+    /// The install directory of the newer version.
+    new_dir: PathBuf,
+
+    /// The game, e.g. `ds3`, detected from the newer version's executable by default.
+    #[arg(long)]
+    game: Option<Game>,
+
+    /// Directory of archive keys, searched for a directory named after the game first.
+    #[arg(long, default_value = "keys")]
+    keys: PathBuf,
+
+    /// File name dictionary, one virtual path per line. Files not in it are reported by hash.
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+
+    /// Only compare paths matching this glob, e.g. `msg/**`. May be repeated. Files not in the
+    /// dictionary are skipped when given.
+    #[arg(long)]
+    filter: Vec<String>,
+
+    /// Compare sizes rather than contents. Much faster, but misses changes that keep a file's
+    /// size.
+    #[arg(long)]
+    quick: bool,
+
+    /// Paramdex directory for the game, e.g. `Paramdex/ER`, to compare params row by row.
+    #[arg(long)]
+    def_dir: Option<PathBuf>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Change {
+    Added,
+    Removed,
+    Changed,
+}
+
+/// How a file differs between the versions, also the `--output json` of the command.
+#[derive(Serialize)]
+struct Report {
+    path: String,
+    change: Change,
+
+    /// Sizes in the archives, `null` for the version without the file.
+    old_size: Option<u32>,
+    new_size: Option<u32>,
+
+    /// How the contents changed, one line each, indented by how deep in binders they are.
+    details: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Reports<'a> {
+    files: &'a [Report],
+}
+
+struct Context {
+    old: Vfs,
+    new: Vfs,
+    paramdex: Option<Paramdex>,
+    quick: bool,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let game = args
+        .game
+        .or_else(|| Game::detect(&args.new_dir))
+        .unwrap_or(Game::EldenRing);
+    let filter = glob_set(&args.filter)?;
+    let context = Context {
+        old: open_vfs(&args.old_dir, game, &args.keys)?,
+        new: open_vfs(&args.new_dir, game, &args.keys)?,
+        paramdex: args
+            .def_dir
+            .as_ref()
+            .map(Paramdex::load)
+            .transpose()
+            .map_err(io::Error::other)?,
+        quick: args.quick,
+    };
+
+    let mut labels = HashMap::new();
+    if let Some(dictionary) = &args.dictionary {
+        for path in read_dictionary(dictionary)? {
+            labels.insert(Name::new(game, &path), path);
+        }
+    }
+
+    let names = context
+        .old
+        .names()
+        .chain(context.new.names())
+        .cloned()
+        .collect::<HashSet<_>>();
+    let mut files = names
+        .into_iter()
+        .filter_map(|name| match labels.get(&name) {
+            Some(path) if filter.is_empty() || filter.is_match(path.trim_start_matches('/')) => {
+                Some((path.clone(), name))
+            }
+            Some(_) => None,
+            None if filter.is_empty() => Some((format!("#{:016x}", name.0), name)),
+            None => None,
+        })
+        .collect::<Vec<_>>();
+    // Named files first, then the ones only known by hash.
+    files.sort_by(|(a, _), (b, _)| (a.starts_with('#'), a).cmp(&(b.starts_with('#'), b)));
+
+    let mut reports = files
+        .par_iter()
+        .progress_with(progress_bar(files.len()))
+        .filter_map(|(label, name)| {
+            compare(&context, label, name).unwrap_or_else(|error| {
+                warn!(path = label, %error, "could not compare file");
+                None
+            })
+        })
+        .collect::<Vec<_>>();
+
+    if filter.is_empty() {
+        match compare_regulations(&context, &args) {
+            Ok(report) => reports.extend(report),
+            Err(error) => warn!(%error, "could not compare regulations"),
+        }
+    }
+
+    if args.output.is_json() {
+        args.output.write_json(&Reports { files: &reports })?;
+    } else {
+        let mut stdout = io::stdout().lock();
+        for report in &reports {
+            match (report.change, report.old_size, report.new_size) {
+                (Change::Added, ..) => writeln!(stdout, "+ {}", report.path)?,
+                (Change::Removed, ..) => writeln!(stdout, "- {}", report.path)?,
+                (Change::Changed, Some(old), Some(new)) => {
+                    writeln!(stdout, "~ {} ({old} -> {new} bytes)", report.path)?
+                }
+                (Change::Changed, ..) => writeln!(stdout, "~ {}", report.path)?,
+            }
+            for line in &report.details {
+                writeln!(stdout, "    {line}")?;
+            }
+        }
+    }
+
+    let count = |change| reports.iter().filter(|r| r.change == change).count();
+    status!(
+        "{} added, {} removed and {} changed of {} files",
+        count(Change::Added),
+        count(Change::Removed),
+        count(Change::Changed),
+        files.len()
+    );
+
+    Ok(())
+}
+
+/// Compare the file identified by `name` in both versions, or `None` if it's the same in both.
+fn compare(context: &Context, label: &str, name: &Name) -> io::Result<Option<Report>> {
+    let report = |change, old_size, new_size| Report {
+        path: label.to_string(),
+        change,
+        old_size,
+        new_size,
+        details: Vec::new(),
+    };
+
+    let (old, new) = match (context.old.stat_name(name), context.new.stat_name(name)) {
+        (Ok(old), Err(_)) => return Ok(Some(report(Change::Removed, Some(old.size), None))),
+        (Err(_), Ok(new)) => return Ok(Some(report(Change::Added, None, Some(new.size)))),
+        (Ok(old), Ok(new)) => (old.size, new.size),
+        (Err(_), Err(_)) => return Ok(None),
+    };
+
+    let unchanged = match context.quick {
+        true => old == new,
+        false => old == new && read_raw(&context.old, name)? == read_raw(&context.new, name)?,
+    };
+    if unchanged {
+        return Ok(None);
+    }
+
+    let mut report = report(Change::Changed, Some(old), Some(new));
+    if !context.quick {
+        let old = context
+            .old
+            .read_decompressed_name(name)
+            .map_err(io::Error::other)?;
+        let new = context
+            .new
+            .read_decompressed_name(name)
+            .map_err(io::Error::other)?;
+        compare_contents(context, label, &old, &new, 0, &mut report.details);
+    }
+
+    Ok(Some(report))
+}
+
+/// Compare the `regulation.bin` next to each version's executable, which isn't in the archives.
+fn compare_regulations(context: &Context, args: &Args) -> io::Result<Option<Report>> {
+    let (old, new) = (
+        args.old_dir.join("regulation.bin"),
+        args.new_dir.join("regulation.bin"),
+    );
+    let change = match (old.is_file(), new.is_file()) {
+        (false, false) => return Ok(None),
+        (true, false) => Change::Removed,
+        (false, true) => Change::Added,
+        (true, true) if fs::read(&old)? == fs::read(&new)? => return Ok(None),
+        (true, true) => Change::Changed,
+    };
+
+    let size = |path: &PathBuf| match path.is_file() {
+        true => fs::metadata(path).map(|metadata| Some(metadata.len() as u32)),
+        false => Ok(None),
+    };
+    let mut report = Report {
+        path: "regulation.bin".to_string(),
+        change,
+        old_size: size(&old)?,
+        new_size: size(&new)?,
+        details: Vec::new(),
+    };
+
+    if change == Change::Changed {
+        let old = RegulationFile::read(&old)?.regulation.into_bytes();
+        let new = RegulationFile::read(&new)?.regulation.into_bytes();
+        compare_contents(
+            context,
+            "regulation.bin",
+            &old,
+            &new,
+            0,
+            &mut report.details,
+        );
+    }
+
+    Ok(Some(report))
+}
+
+/// Describe how the decompressed contents of the file at `path` changed, for the formats that can
+/// be compared in more detail than as bytes.
+fn compare_contents(
+    context: &Context,
+    path: &str,
+    old: &[u8],
+    new: &[u8],
+    depth: usize,
+    lines: &mut Vec<String>,
+) {
+    let indent = "    ".repeat(depth);
+    let result = if old.starts_with(b"BND4") && new.starts_with(b"BND4") && depth < MAX_DEPTH {
+        compare_binders(context, old, new, depth, lines)
+    } else {
+        let extension = path.to_lowercase();
+        let extension = extension.strip_suffix(".dcx").unwrap_or(&extension);
+        let changes = if extension.ends_with(".param") {
+            compare_params(context, path, old, new)
+        } else if extension.ends_with(".fmg") {
+            compare_fmgs(old, new)
+        } else {
+            Ok(Vec::new())
+        };
+
+        changes.map(|changes| {
+            lines.extend(changes.into_iter().map(|line| format!("{indent}{line}")));
+        })
+    };
+
+    if let Err(error) = result {
+        lines.push(format!("{indent}! {error}"));
+    }
+}
+
+fn compare_binders(
+    context: &Context,
+    old: &[u8],
+    new: &[u8],
+    depth: usize,
+    lines: &mut Vec<String>,
+) -> Result<(), String> {
+    let indent = "    ".repeat(depth);
+    let (old, new) = (binder_files(old)?, binder_files(new)?);
+
+    for key in old.keys().chain(new.keys()).collect::<BTreeSet<_>>() {
+        match (old.get(key), new.get(key)) {
+            (Some((path, _)), None) => lines.push(format!("{indent}- {path}")),
+            (None, Some((path, _))) => lines.push(format!("{indent}+ {path}")),
+            (Some((_, old)), Some((path, new))) if old != new => {
+                lines.push(format!("{indent}~ {path}"));
+                compare_contents(context, path, old, new, depth + 1, lines);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// The decompressed files of a binder by their normalized path, along with their path as stored.
+fn binder_files(bytes: &[u8]) -> Result<BTreeMap<String, (String, Vec<u8>)>, String> {
+    let bnd = BND4::parse(bytes.to_vec()).map_err(|e| format!("Could not read binder: {e}"))?;
+
+    bnd.files
+        .iter()
+        .map(|file| {
+            let bytes = undo_container_compression(bnd.file_bytes(file).to_vec())
+                .map_err(|e| format!("Could not decompress {}: {e}", file.path))?;
+            let path = file.path.replace("N:\\", "").replace('\\', "/");
+
+            Ok((BND4::normalize_path(&file.path), (path, bytes)))
+        })
+        .collect()
+}
+
+fn compare_params(
+    context: &Context,
+    path: &str,
+    old: &[u8],
+    new: &[u8],
+) -> Result<Vec<String>, String> {
+    let Some(paramdex) = &context.paramdex else {
+        return Ok(Vec::new());
+    };
+
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let name = file_name.split('.').next().unwrap_or(file_name);
+    let read = |bytes| {
+        let mut param = Param::parse(bytes).map_err(|e| format!("Could not read param: {e}"))?;
+        paramdex.apply_row_names(name, &mut param);
+        Ok::<_, String>(param)
+    };
+    let (old, new) = (read(old)?, read(new)?);
+    let Some(def) = def_for(paramdex, name, &new) else {
+        return Ok(Vec::new());
+    };
+
+    let diff = param::diff(&old, &new, def).map_err(|e| format!("Could not compare rows: {e}"))?;
+
+    Ok(diff.to_string().lines().map(str::to_string).collect())
+}
+
+fn compare_fmgs(old: &[u8], new: &[u8]) -> Result<Vec<String>, String> {
+    let read = |bytes: &[u8]| {
+        Fmg::from_reader(&mut Cursor::new(bytes)).map_err(|e| format!("Could not read FMG: {e}"))
+    };
+    let (old, new) = (read(old)?, read(new)?);
+    let texts = |fmg: &Fmg| {
+        fmg.entries
+            .iter()
+            .filter_map(|entry| Some((entry.id, entry.text.clone()?)))
+            .collect::<BTreeMap<_, _>>()
+    };
+    let (old, new) = (texts(&old), texts(&new));
+
+    let mut lines = Vec::new();
+    for id in old.keys().chain(new.keys()).collect::<BTreeSet<_>>() {
+        match (old.get(id), new.get(id)) {
+            (Some(text), None) => lines.push(format!("- {id} {text:?}")),
+            (None, Some(text)) => lines.push(format!("+ {id} {text:?}")),
+            (Some(old), Some(new)) if old != new => {
+                lines.push(format!("~ {id} {old:?} -> {new:?}"))
+            }
+            _ => {}
+        }
+    }
+
+    Ok(lines)
+}
+
+/// Read the file identified by `name` as stored, decrypted but still compressed.
+fn read_raw(vfs: &Vfs, name: &Name) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    vfs.open_name(name)
+        .map_err(io::Error::other)?
+        .read_to_end(&mut bytes)?;
+
+    Ok(bytes)
+}
@@ -0,0 +1,205 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Cursor, Write},
+    path::PathBuf,
+};
+
+use clap::Subcommand;
+use format::emevd::{
+    decompile::{decompile, decompile_with_names},
+    emedf::Emedf,
+    emeld::Emeld,
+    Emevd,
+};
+use souls_vfs::{undo_container_compression, Vfs};
+
+use crate::VfsArgs;
+
+/// Read event scripts.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    kind: Kind,
+}
+
+#[derive(Subcommand, Debug)]
+enum Kind {
+    Decompile(DecompileArgs),
+}
+
+/// Print the events of an EMEVD as a readable script, naming instructions, arguments and enum
+/// values with the game's EMEDF.
+///
+/// With `--diff`, print only the events that changed from another version of the file instead:
+/// added events with `+`, removed events with `-` and changed events with their lines marked the
+/// same way.
+#[derive(clap::Args, Debug)]
+pub struct DecompileArgs {
+    /// The EMEVD, optionally DCX compressed, on disk or in the game's archives when `--game-dir`
+    /// is given, e.g. `/event/m10_00_00_00.emevd.dcx`.
+    input: String,
+
+    /// Instruction definitions for the game, e.g. `er-common.emedf.json`.
+    #[arg(long)]
+    emedf: PathBuf,
+
+    /// The map's EMELD, to name its events, e.g. `/event/m10_00_00_00.emeld.dcx`.
+    #[arg(long)]
+    emeld: Option<String>,
+
+    /// An older version of the EMEVD to compare with, read the same way as the input.
+    #[arg(long)]
+    diff: Option<String>,
+
+    /// Write the script to this file rather than stdout.
+    #[arg(long = "out", short, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    #[command(flatten)]
+    vfs: VfsArgs,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    match args.kind {
+        Kind::Decompile(args) => decompile_command(args),
+    }
+}
+
+fn decompile_command(args: DecompileArgs) -> io::Result<()> {
+    let vfs = args.vfs.open_vfs()?;
+    let emedf = Emedf::from_json(&fs::read_to_string(&args.emedf)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    let emeld = match &args.emeld {
+        Some(path) => Some(
+            Emeld::from_reader(&mut Cursor::new(read(vfs.as_ref(), path)?))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?,
+        ),
+        None => None,
+    };
+
+    let listing = |path: &str| {
+        let emevd = Emevd::from_reader(&mut Cursor::new(read(vfs.as_ref(), path)?))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{path}: {e}")))?;
+
+        Ok::<_, io::Error>(match &emeld {
+            Some(emeld) => decompile_with_names(&emevd, &emedf, emeld),
+            None => decompile(&emevd, &emedf),
+        })
+    };
+
+    let new = listing(&args.input)?;
+    let output = match &args.diff {
+        Some(old) => diff(&listing(old)?, &new),
+        None => new,
+    };
+
+    match &args.output {
+        Some(path) => fs::write(path, output),
+        None => io::stdout().lock().write_all(output.as_bytes()),
+    }
+}
+
+/// Read a file from the archives if it's a virtual path and they're open, from disk otherwise,
+/// undoing its DCX compression.
+fn read(vfs: Option<&Vfs>, path: &str) -> io::Result<Vec<u8>> {
+    let bytes = match vfs {
+        Some(vfs) if path.starts_with('/') => vfs
+            .read(path)
+            .map_err(|e| io::Error::other(format!("Could not read {path}: {e}")))?,
+        _ => fs::read(path)?,
+    };
+
+    undo_container_compression(bytes).map_err(io::Error::other)
+}
+
+/// The events of a listing by ID, each with the comments before it, and the file's other lines
+/// such as its linked files under `None`.
+fn events(listing: &str) -> BTreeMap<Option<i64>, Vec<&str>> {
+    let mut events = BTreeMap::<_, Vec<_>>::new();
+    let mut pending = Vec::new();
+    let mut current = None;
+
+    for line in listing.lines().filter(|line| !line.is_empty()) {
+        if let Some(rest) = line.strip_prefix("Event(") {
+            let id = rest.split([',', ')']).next().and_then(|id| id.parse().ok());
+            current = Some(id);
+            events.entry(id).or_default().append(&mut pending);
+        }
+
+        match current {
+            Some(id) => events.entry(id).or_default().push(line),
+            None if line.starts_with("// linked:") => events.entry(None).or_default().push(line),
+            // Names of the next event.
+            None => pending.push(line),
+        }
+
+        if line == "}" {
+            current = None;
+        }
+    }
+
+    events
+}
+
+fn diff(old: &str, new: &str) -> String {
+    let (old, mut new) = (events(old), events(new));
+    let mut out = String::new();
+
+    let mut push = |marker: char, lines: &[&str]| {
+        for line in lines {
+            out.push(marker);
+            out.push(' ');
+            out.push_str(line);
+            out.push('\n');
+        }
+    };
+
+    for (id, old_lines) in &old {
+        match new.remove(id) {
+            None => push('-', old_lines),
+            Some(new_lines) if new_lines != *old_lines => {
+                for (marker, line) in diff_lines(old_lines, &new_lines) {
+                    push(marker, &[line]);
+                }
+            }
+            Some(_) => {}
+        }
+    }
+    for new_lines in new.values() {
+        push('+', new_lines);
+    }
+
+    out
+}
+
+/// Mark each line as kept (` `), removed (`-`) or added (`+`) using their longest common
+/// subsequence.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(char, &'a str)> {
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = match old[i] == new[j] {
+                true => lengths[i + 1][j + 1] + 1,
+                false => lengths[i + 1][j].max(lengths[i][j + 1]),
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push((' ', old[i]));
+            (i, j) = (i + 1, j + 1);
+        } else if j < new.len() && (i == old.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            lines.push(('+', new[j]));
+            j += 1;
+        } else {
+            lines.push(('-', old[i]));
+            i += 1;
+        }
+    }
+
+    lines
+}
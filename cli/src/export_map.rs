@@ -0,0 +1,331 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+};
+
+use format::{
+    bnd4::BND4,
+    flver::Flver,
+    game::Game,
+    hkxbhd::MapCollision,
+    msb::{Msb, MsbModel, MsbModelType, MsbPart, MsbPartType},
+};
+use indicatif::ParallelProgressIterator;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use souls_vfs::{Vfs, VfsOpenError, VfsReadError};
+
+use crate::{
+    convert::{
+        binder_files, file_stem,
+        gltf::{self, Instance},
+        model::{euler_to_quaternion, texture_names, write_textures, Model},
+    },
+    progress_bar, GameArgs,
+};
+
+/// Export the map pieces, assets and enemies placed by an Elden Ring map as one glTF scene, with
+/// the textures of their materials as DDS files.
+///
+/// Each model is written once and placed by a node per part, with the part's position, rotation
+/// and scale. Textures are found in the models' binders and in the asset texture archives, so map
+/// pieces that use the map's own texture archives are left untextured.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// The map to export, e.g. `m60_42_36_00`.
+    map: String,
+
+    #[arg(long, value_enum, default_value_t = SceneFormat::Glb)]
+    to: SceneFormat,
+
+    /// Where to write the scene, named after the map in the current directory by default.
+    /// Textures are written next to it.
+    #[arg(long = "out", short, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// Level of detail to export, from 0 for the most detailed to 2.
+    #[arg(long, default_value_t = 0)]
+    lod: u8,
+
+    /// Include the map's collision, as untextured meshes.
+    #[arg(long)]
+    collision: bool,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum SceneFormat {
+    /// Binary glTF, with everything but the textures in one file.
+    Glb,
+    /// glTF with its buffer in a separate `.bin`.
+    Gltf,
+}
+
+impl SceneFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Glb => "glb",
+            Self::Gltf => "gltf",
+        }
+    }
+}
+
+/// The files of a model read from the archives, before they're decoded.
+enum Source {
+    Flver {
+        flver: Vec<u8>,
+        tpfs: Vec<(String, Vec<u8>)>,
+    },
+    Collision(Model),
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+    if vfs.game() != Game::EldenRing {
+        return Err(invalid(&format!(
+            "Only Elden Ring maps can be exported, not {}",
+            vfs.game()
+        )));
+    }
+
+    let map = args.map.to_lowercase();
+    let area = map.get(..3).unwrap_or_default();
+    let is_map_name = map.len() == 12
+        && map.starts_with('m')
+        && map[1..]
+            .split('_')
+            .all(|part| part.len() == 2 && part.bytes().all(|b| b.is_ascii_digit()));
+    if !is_map_name {
+        return Err(invalid(&format!(
+            "{} is not a map name like m60_42_36_00",
+            args.map
+        )));
+    }
+
+    let msb_path = format!("/map/mapstudio/{map}.msb.dcx");
+    let msb = vfs
+        .read_decompressed(&msb_path)
+        .map_err(|e| io::Error::other(format!("Could not read {msb_path}: {e}")))?;
+    let msb = Msb::from_reader(&mut Cursor::new(msb))
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{msb_path}: {e}")))?;
+
+    // Dummies and connect collisions are placeholders the game doesn't draw.
+    let parts = msb
+        .parts
+        .iter()
+        .filter(|part| match part.part_type {
+            MsbPartType::MapPiece | MsbPartType::Asset | MsbPartType::Enemy => true,
+            MsbPartType::Collision => args.collision,
+            _ => false,
+        })
+        .filter(|part| msb.part_model(part).is_some())
+        .collect::<Vec<_>>();
+    let mut used = parts
+        .iter()
+        .filter_map(|part| part.model)
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect::<Vec<_>>();
+    used.sort();
+
+    let collision = match args.collision {
+        true => Some(read_collision(&vfs, area, &map)?),
+        false => None,
+    };
+
+    let sources = used
+        .par_iter()
+        .progress_with(progress_bar(used.len()))
+        .map(|index| {
+            let model = &msb.models[*index];
+            let source = read_model(&vfs, area, &map, model, collision.as_ref());
+
+            (*index, model, source)
+        })
+        .collect::<Vec<_>>();
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("{map}.{}", args.to.extension())));
+    let directory = output.parent().unwrap_or(Path::new(""));
+
+    // Asset textures are shared between models, so each archive is only read once.
+    let mut asset_tpfs = HashMap::new();
+    let mut models = Vec::new();
+    let mut scene_indices = HashMap::new();
+    let mut texture_count = 0;
+    for (index, model, source) in sources {
+        let decoded = match source {
+            Ok(Source::Flver { flver, tpfs }) => {
+                let flver = Flver::parse(&flver).map_err(io::Error::other);
+                flver.and_then(|flver| {
+                    let mut archives = Vec::new();
+                    for name in texture_names(&flver) {
+                        let Some(path) = asset_texture_path(&name) else {
+                            continue;
+                        };
+                        if !asset_tpfs.contains_key(&path) {
+                            let tpf =
+                                read_optional(&vfs, &path)?.map(|bytes| (path.clone(), bytes));
+                            asset_tpfs.insert(path.clone(), tpf);
+                        }
+                        if !archives.contains(&path) {
+                            archives.push(path);
+                        }
+                    }
+
+                    let archives = archives.iter().filter_map(|path| asset_tpfs[path].as_ref());
+                    let textures = write_textures(&flver, tpfs.iter().chain(archives), directory)?;
+                    texture_count += textures.len();
+
+                    Ok(Model::from_flver(&flver, args.lod, &textures))
+                })
+            }
+            Ok(Source::Collision(model)) => Ok(model),
+            Err(e) => Err(e),
+        };
+
+        match decoded {
+            Ok(decoded) => {
+                scene_indices.insert(index, models.len());
+                models.push(decoded);
+            }
+            Err(e) => eprintln!("{}: {e}", model.name),
+        }
+    }
+
+    let instances = parts
+        .iter()
+        .filter_map(|part| {
+            let model = *scene_indices.get(&part.model?)?;
+            Some(instance(part, model))
+        })
+        .collect::<Vec<_>>();
+
+    match args.to {
+        SceneFormat::Glb => fs::write(&output, gltf::scene_to_glb(&models, &instances))?,
+        SceneFormat::Gltf => {
+            let buffer = output.with_extension("bin");
+            let buffer_name = buffer.file_name().unwrap_or_default().to_string_lossy();
+            let (document, data) = gltf::scene_to_gltf(&models, &instances, &buffer_name);
+            fs::write(&output, document)?;
+            fs::write(buffer, data)?;
+        }
+    }
+
+    status!(
+        "Wrote {} parts of {} models and {texture_count} textures to {}",
+        instances.len(),
+        models.len(),
+        output.display()
+    );
+    if models.len() < used.len() {
+        status!("{} models couldn't be read", used.len() - models.len());
+    }
+
+    Ok(())
+}
+
+/// Read the files of a model: the FLVER and textures in its binder, or its cell of the map's
+/// collision.
+fn read_model(
+    vfs: &Vfs,
+    area: &str,
+    map: &str,
+    model: &MsbModel,
+    collision: Option<&MapCollision>,
+) -> io::Result<Source> {
+    let name = model.name.to_lowercase();
+    let (binder, stem) = match model.model_type {
+        // Map pieces are named after their map, e.g. `m000000` of `m60_42_36_00`.
+        MsbModelType::MapPiece => {
+            let stem = format!("{map}_{}", name.get(1..).unwrap_or_default());
+            (format!("/map/{area}/{map}/{stem}.mapbnd.dcx"), stem)
+        }
+        MsbModelType::Asset => {
+            let category = name.get(..6).unwrap_or_default();
+            (format!("/asset/aeg/{category}/{name}.geombnd.dcx"), name)
+        }
+        MsbModelType::Enemy => (format!("/chr/{name}.chrbnd.dcx"), name),
+        MsbModelType::Collision => {
+            let cell = format!("h{}_{}", &map[1..], name.get(1..).unwrap_or_default());
+            let meshes = collision
+                .and_then(|collision| collision.cells.iter().find(|c| c.name == cell))
+                .map(|cell| &cell.meshes[..])
+                .ok_or_else(|| io::Error::other(format!("the map's collision has no {cell}")))?;
+
+            return Ok(Source::Collision(Model::from_collision(meshes)));
+        }
+        _ => return Err(io::Error::other("models of this type aren't supported")),
+    };
+
+    let bytes = vfs
+        .read_decompressed(&binder)
+        .map_err(|e| io::Error::other(format!("Could not read {binder}: {e}")))?;
+    let bnd = BND4::from_reader(&mut Cursor::new(bytes))?;
+
+    let flver = binder_files(&bnd, ".flver")?
+        .into_iter()
+        .find(|(path, _)| file_stem(path) == stem)
+        .map(|(_, flver)| flver)
+        .ok_or_else(|| io::Error::other(format!("{binder} has no FLVER")))?;
+
+    let mut tpfs = binder_files(&bnd, ".tpf")?;
+    if model.model_type == MsbModelType::Enemy {
+        if let Some(bytes) = read_optional(vfs, &format!("/chr/{stem}.texbnd.dcx"))? {
+            let bnd = BND4::from_reader(&mut Cursor::new(bytes))?;
+            tpfs.extend(binder_files(&bnd, ".tpf")?);
+        }
+    }
+
+    Ok(Source::Flver { flver, tpfs })
+}
+
+fn read_collision(vfs: &Vfs, area: &str, map: &str) -> io::Result<MapCollision> {
+    let path = format!("/map/{area}/{map}/h{}", &map[1..]);
+    let read = |extension: &str| {
+        let path = format!("{path}.{extension}");
+        vfs.read(&path)
+            .map_err(|e| io::Error::other(format!("Could not read {path}: {e}")))
+    };
+
+    MapCollision::from_bytes(&read("hkxbhd")?, read("hkxbdt")?).map_err(io::Error::other)
+}
+
+/// The archive holding an asset texture, e.g. `/asset/aet/aet007/aet007_077.tpf.dcx` for
+/// `aet007_077_a`.
+fn asset_texture_path(name: &str) -> Option<String> {
+    let archive = name.get(..10).filter(|_| name.starts_with("aet"))?;
+
+    Some(format!("/asset/aet/{}/{archive}.tpf.dcx", &archive[..6]))
+}
+
+fn read_optional(vfs: &Vfs, path: &str) -> io::Result<Option<Vec<u8>>> {
+    match vfs.read_decompressed(path) {
+        Ok(bytes) => Ok(Some(bytes)),
+        Err(VfsReadError::Open(VfsOpenError::NotFound)) => Ok(None),
+        Err(e) => Err(io::Error::other(format!("Could not read {path}: {e}"))),
+    }
+}
+
+/// Place `part` in glTF's coordinates, mirroring X like the models themselves.
+fn instance(part: &MsbPart, model: usize) -> Instance {
+    let [x, y, z] = part.position;
+    let [rx, ry, rz] = part.rotation.map(f32::to_radians);
+    let [qx, qy, qz, qw] = euler_to_quaternion(rx, ry, rz);
+
+    Instance {
+        name: part.name.clone(),
+        model,
+        translation: [-x, y, z],
+        rotation: [qx, -qy, -qz, qw],
+        scale: part.scale,
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.to_string())
+}
@@ -0,0 +1,134 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use souls_vfs::Vfs;
+use tracing::warn;
+
+use crate::{output_path, progress_bar, progress_bars, read_dictionary, GameArgs};
+
+/// Extract the files of a game's archives named in a dictionary, keeping their virtual directory
+/// layout.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// Directory to write the files to.
+    out_dir: PathBuf,
+
+    /// File name dictionary, one virtual path per line.
+    #[arg(long)]
+    dictionary: PathBuf,
+
+    /// Only extract paths matching this glob, e.g. `map/m60/**/*.msb*`. May be repeated.
+    #[arg(long)]
+    filter: Vec<String>,
+
+    /// Write files as stored rather than undoing their DCX compression.
+    #[arg(long)]
+    raw: bool,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+
+    run_with(args, &vfs)
+}
+
+/// Run with archives that are already open, ignoring the ones `args` name.
+pub(crate) fn run_with(args: Args, vfs: &Vfs) -> io::Result<()> {
+    let filter = glob_set(&args.filter)?;
+
+    let paths = read_dictionary(&args.dictionary)?
+        .into_iter()
+        .filter(|path| filter.is_empty() || filter.is_match(path.trim_start_matches('/')))
+        .collect::<Vec<_>>();
+
+    // Dictionaries name files of every game and version, most of which aren't here.
+    let files = paths
+        .iter()
+        .filter_map(|path| Some((path, vfs.stat(path).ok()?.archive)))
+        .collect::<Vec<_>>();
+
+    // One bar for each archive below the overall one.
+    let bars = progress_bars();
+    let overall = bars.add(progress_bar(files.len()).with_message("total"));
+    let mut archives = BTreeMap::<&str, usize>::new();
+    for (_, archive) in &files {
+        *archives.entry(archive).or_default() += 1;
+    }
+    let archives = archives
+        .into_iter()
+        .map(|(archive, len)| {
+            (
+                archive,
+                bars.add(progress_bar(len).with_message(archive.to_string())),
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let extracted = AtomicUsize::new(0);
+
+    files.par_iter().try_for_each(|(path, archive)| {
+        let result = if args.raw {
+            vfs.read(path)
+        } else {
+            vfs.read_decompressed(path)
+        };
+        overall.inc(1);
+        archives[archive].inc(1);
+
+        let bytes = match result {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                warn!(path, %error, "could not extract file");
+                return Ok(());
+            }
+        };
+
+        let path = match args.raw {
+            true => path.as_str(),
+            false => path.strip_suffix(".dcx").unwrap_or(path),
+        };
+        let output = output_path(&args.out_dir, path);
+        if let Some(directory) = output.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        fs::write(output, bytes)?;
+        extracted.fetch_add(1, Ordering::Relaxed);
+
+        Ok::<_, io::Error>(())
+    })?;
+
+    status!(
+        "Extracted {} of {} matching paths",
+        extracted.into_inner(),
+        paths.len()
+    );
+
+    Ok(())
+}
+
+/// Match virtual paths without their leading `/` and ignoring case, with `*` stopping at
+/// directory separators and `**` crossing them.
+pub(crate) fn glob_set(patterns: &[String]) -> io::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let glob = GlobBuilder::new(pattern.trim_start_matches('/'))
+            .case_insensitive(true)
+            .literal_separator(true)
+            .build()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        builder.add(glob);
+    }
+
+    builder
+        .build()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))
+}
@@ -0,0 +1,241 @@
+use std::{
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use format::bnd4::BND4;
+use indicatif::ParallelProgressIterator;
+use memchr::memmem;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
+use souls_vfs::{undo_container_compression, VfsOpenError, VfsReadError};
+use tracing::warn;
+
+use crate::{extract::glob_set, progress_bar, read_dictionary, GameArgs, OutputArgs};
+
+/// How deep to look into binders inside binders.
+const MAX_DEPTH: usize = 4;
+
+/// Search the files of a game's archives for a string, bytes or an integer.
+///
+/// Files are searched with their DCX compression undone, and binders are searched file by file
+/// rather than as a whole, so offsets are into the decompressed file that matched.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// What to search for, interpreted according to `--type`.
+    pattern: String,
+
+    /// File name dictionary, one virtual path per line.
+    #[arg(long)]
+    dictionary: PathBuf,
+
+    /// How to interpret the pattern.
+    #[arg(long = "type", value_name = "TYPE", value_enum, default_value_t = PatternType::Text)]
+    kind: PatternType,
+
+    /// Only search paths matching this glob, e.g. `msg/**`. May be repeated.
+    #[arg(long)]
+    filter: Vec<String>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq)]
+pub enum PatternType {
+    /// A string, encoded as both UTF-8 and UTF-16 since the games use both.
+    Text,
+    /// Bytes in hex, e.g. `44 43 58 00` or `44435800`.
+    Hex,
+    /// A 32-bit little-endian integer such as a row ID, e.g. `1000` or `-1`.
+    Int,
+}
+
+/// An encoding of the pattern to look for, and how to describe matches of it.
+struct Needle {
+    bytes: Vec<u8>,
+    label: Option<&'static str>,
+}
+
+/// The `--output json` of the command.
+#[derive(Serialize)]
+struct Matches<'a> {
+    matches: Vec<&'a Match>,
+}
+
+#[derive(Serialize)]
+struct Match {
+    path: String,
+
+    /// The files of binders the match is in, outermost first.
+    entries: Vec<String>,
+    offset: usize,
+
+    /// How the pattern was encoded, for text patterns.
+    encoding: Option<&'static str>,
+}
+
+impl Match {
+    fn location(&self) -> String {
+        [self.path.as_str()]
+            .into_iter()
+            .chain(self.entries.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" > ")
+    }
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let needles = needles(&args.pattern, args.kind)?;
+    let filter = glob_set(&args.filter)?;
+    let vfs = args.game.open_vfs()?;
+
+    let paths = read_dictionary(&args.dictionary)?
+        .into_iter()
+        .filter(|path| filter.is_empty() || filter.is_match(path.trim_start_matches('/')))
+        .filter(|path| vfs.contains(path))
+        .collect::<Vec<_>>();
+
+    let results = paths
+        .par_iter()
+        .progress_with(progress_bar(paths.len()))
+        .map(|path| {
+            let mut matches = Vec::new();
+            match vfs.read_decompressed(path) {
+                Ok(bytes) => search(path, &[], &bytes, &needles, &mut matches),
+                Err(VfsReadError::Open(VfsOpenError::NotFound)) => {}
+                Err(error) => warn!(path, %error, "could not read file"),
+            }
+
+            matches
+        })
+        .collect::<Vec<_>>();
+
+    let total = results.iter().map(Vec::len).sum::<usize>();
+    if args.output.is_json() {
+        args.output.write_json(&Matches {
+            matches: results.iter().flatten().collect(),
+        })?;
+    } else {
+        let mut stdout = io::stdout().lock();
+        for m in results.iter().flatten() {
+            match m.encoding {
+                Some(encoding) => {
+                    writeln!(stdout, "{}: {:#x} ({encoding})", m.location(), m.offset)?
+                }
+                None => writeln!(stdout, "{}: {:#x}", m.location(), m.offset)?,
+            }
+        }
+    }
+
+    status!(
+        "Found {total} matches in {} of {} files",
+        results.iter().filter(|matches| !matches.is_empty()).count(),
+        paths.len()
+    );
+
+    Ok(())
+}
+
+/// The byte sequences to look for `pattern` as.
+fn needles(pattern: &str, kind: PatternType) -> io::Result<Vec<Needle>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidInput, message);
+
+    let needles = match kind {
+        PatternType::Text => {
+            let utf16 = pattern
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes)
+                .collect::<Vec<_>>();
+
+            vec![
+                Needle {
+                    bytes: pattern.as_bytes().to_vec(),
+                    label: Some("utf-8"),
+                },
+                Needle {
+                    bytes: utf16,
+                    label: Some("utf-16"),
+                },
+            ]
+        }
+        PatternType::Hex => {
+            let digits = pattern
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>();
+            let digits = digits.strip_prefix("0x").unwrap_or(&digits);
+            if digits.len() % 2 != 0 {
+                return Err(invalid(format!(
+                    "{pattern} has an odd number of hex digits"
+                )));
+            }
+
+            let bytes = (0..digits.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&digits[i..i + 2], 16))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid(format!("{pattern} is not hex")))?;
+
+            vec![Needle { bytes, label: None }]
+        }
+        PatternType::Int => {
+            let value = pattern
+                .parse::<i64>()
+                .map_err(|_| invalid(format!("{pattern} is not an integer")))?;
+            let bytes = match (i32::try_from(value), u32::try_from(value)) {
+                (Ok(value), _) => value.to_le_bytes(),
+                (_, Ok(value)) => value.to_le_bytes(),
+                _ => return Err(invalid(format!("{pattern} doesn't fit in 32 bits"))),
+            };
+
+            vec![Needle {
+                bytes: bytes.to_vec(),
+                label: None,
+            }]
+        }
+    };
+
+    if needles.iter().any(|needle| needle.bytes.is_empty()) {
+        return Err(invalid("The pattern is empty".to_string()));
+    }
+
+    Ok(needles)
+}
+
+/// Find every occurrence of `needles` in `bytes`, or in the files of `bytes` if it's a binder.
+/// `entries` are the binder files that `bytes` was found in.
+fn search(path: &str, entries: &[String], bytes: &[u8], needles: &[Needle], out: &mut Vec<Match>) {
+    if entries.len() < MAX_DEPTH && bytes.starts_with(b"BND4") {
+        match BND4::parse(bytes.to_vec()) {
+            Ok(bnd) => {
+                for file in &bnd.files {
+                    let mut entries = entries.to_vec();
+                    entries.push(BND4::normalize_path(&file.path));
+                    match undo_container_compression(bnd.file_bytes(file).to_vec()) {
+                        Ok(bytes) => search(path, &entries, &bytes, needles, out),
+                        Err(error) => warn!(path, ?entries, %error, "could not decompress file"),
+                    }
+                }
+
+                return;
+            }
+            // Search it as bytes like any other file.
+            Err(error) => warn!(path, ?entries, %error, "could not read binder"),
+        }
+    }
+
+    let start = out.len();
+    for needle in needles {
+        out.extend(memmem::find_iter(bytes, &needle.bytes).map(|offset| Match {
+            path: path.to_string(),
+            entries: entries.to_vec(),
+            offset,
+            encoding: needle.label,
+        }));
+    }
+    out[start..].sort_by_key(|m| m.offset);
+}
@@ -0,0 +1,86 @@
+use std::io::{self, BufRead, Write};
+
+use format::{bnd4::BND4, game::Game};
+use serde::Serialize;
+
+use crate::OutputArgs;
+
+/// Print the hashes the archives and binders know a path by, to check candidate names against
+/// the hashes of files that aren't named yet.
+///
+/// Each path is printed after its archive hash, the binder hash of the whole path and the binder
+/// hash of its file name alone. Paths are read from stdin, one per line, when none are given.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Virtual paths to hash, e.g. `/chr/c3251.chrbnd.dcx`.
+    paths: Vec<String>,
+
+    /// The game whose archive hash to use, e.g. `ds3`.
+    #[arg(long, default_value = "er")]
+    game: Game,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// The `--output json` of the command, per path.
+#[derive(Serialize)]
+struct Hashes {
+    path: String,
+    archive: u64,
+    binder: u32,
+    binder_file_name: u32,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let paths = match args.paths.is_empty() {
+        true => io::stdin()
+            .lock()
+            .lines()
+            .map(|line| line.map(|line| line.trim().to_string()))
+            .filter(|line| !matches!(line, Ok(line) if line.is_empty()))
+            .collect::<io::Result<Vec<_>>>()?,
+        false => args.paths,
+    };
+
+    let hashes = paths
+        .into_iter()
+        .map(|path| {
+            let file_name = path.rsplit(['/', '\\']).next().unwrap_or(&path);
+
+            Hashes {
+                archive: args.game.hash_path(&path),
+                binder: BND4::hash_name(&path),
+                binder_file_name: BND4::hash_name(file_name),
+                path,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if args.output.is_json() {
+        return args.output.write_json(&hashes);
+    }
+
+    let mut stdout = io::stdout().lock();
+    for hashes in &hashes {
+        writeln!(
+            stdout,
+            "{} {:08x} {:08x} {}",
+            format_hash(args.game, hashes.archive),
+            hashes.binder,
+            hashes.binder_file_name,
+            hashes.path
+        )?;
+    }
+
+    Ok(())
+}
+
+/// An archive hash in hex, as wide as the game's hashes are.
+pub(crate) fn format_hash(game: Game, hash: u64) -> String {
+    // Hashes widened to 64 bits with Elden Ring.
+    match game {
+        Game::EldenRing | Game::ArmoredCore6 => format!("{hash:016x}"),
+        _ => format!("{hash:08x}"),
+    }
+}
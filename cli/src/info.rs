@@ -0,0 +1,273 @@
+use std::{
+    fs,
+    io::{self, Cursor, Write},
+};
+
+use byteorder::{ByteOrder, BE};
+use format::{bnd4::BND4, dcx::DCX, flver::Flver, tpf::TPF};
+use serde_json::{json, Map, Value};
+
+use crate::{OutputArgs, VfsArgs};
+
+/// Show what a file is and what's in it: the algorithm and sizes of DCX compression, the files of
+/// a binder, the meshes, materials and bones of a FLVER or the textures of a TPF.
+///
+/// Compressed files and the files of binders are described in turn, so e.g. a `.chrbnd.dcx` shows
+/// its compression, the files it holds and what's in each of them.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// The file to describe, on disk or in the game's archives when `--game-dir` is given, e.g.
+    /// `/chr/c0000.chrbnd.dcx`.
+    input: String,
+
+    #[command(flatten)]
+    vfs: VfsArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let vfs = args.vfs.open_vfs()?;
+    let bytes = match &vfs {
+        Some(vfs) if args.input.starts_with('/') => vfs
+            .read(&args.input)
+            .map_err(|e| io::Error::other(format!("Could not read {}: {e}", args.input)))?,
+        _ => fs::read(&args.input)?,
+    };
+
+    let summary = describe(&bytes);
+    match args.output.is_json() {
+        true => args.output.write_json(&summary),
+        false => write_summary(&mut io::stdout().lock(), &summary, 0),
+    }
+}
+
+/// Summarize `bytes` according to the format its magic identifies.
+pub(crate) fn describe(bytes: &[u8]) -> Value {
+    let result = match bytes.get(..4).unwrap_or_default() {
+        b"DCX\0" => describe_dcx(bytes),
+        b"BND4" => describe_bnd4(bytes),
+        b"FLVE" => describe_flver(bytes),
+        b"TPF\0" => describe_tpf(bytes),
+        magic => Ok(json!({
+            "format": "unknown",
+            "magic": String::from_utf8_lossy(magic).escape_debug().to_string(),
+            "size": bytes.len(),
+        })),
+    };
+
+    result.unwrap_or_else(|error| json!({ "error": error }))
+}
+
+fn describe_dcx(bytes: &[u8]) -> Result<Value, String> {
+    let header = bytes.get(..0x34).ok_or("DCX header is truncated")?;
+    let mut summary = json!({
+        "format": "DCX",
+        "algorithm": String::from_utf8_lossy(&header[0x28..0x2C]),
+        "level": header[0x30],
+        "compressed_size": BE::read_u32(&header[0x20..]),
+        "uncompressed_size": BE::read_u32(&header[0x1C..]),
+    });
+
+    // Other algorithms can still be described, just not decompressed.
+    summary["contents"] = match DCX::from_reader(&mut Cursor::new(bytes)) {
+        Ok(dcx) => describe(&dcx.decompressed),
+        Err(error) => json!({ "error": format!("Could not decompress: {error}") }),
+    };
+
+    Ok(summary)
+}
+
+fn describe_bnd4(bytes: &[u8]) -> Result<Value, String> {
+    let bnd = BND4::parse(bytes.to_vec()).map_err(|e| e.to_string())?;
+    let files = bnd
+        .files
+        .iter()
+        .map(|file| {
+            json!({
+                "id": file.id,
+                "path": file.path,
+                "size": file.uncompressed_size,
+                "contents": describe(bnd.file_bytes(file)),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "format": "BND4",
+        "version": String::from_utf8_lossy(&bnd.version.to_le_bytes()).trim_end_matches('\0'),
+        "unicode": bnd.unicode,
+        "files": files,
+    }))
+}
+
+fn describe_flver(bytes: &[u8]) -> Result<Value, String> {
+    let flver = Flver::parse(bytes).map_err(|e| e.to_string())?;
+    let header = flver.header();
+
+    let materials = flver
+        .materials()
+        .iter()
+        .map(|material| {
+            let textures = flver
+                .material_textures(material)
+                .iter()
+                .map(|texture| {
+                    json!({
+                        "type": flver.texture_type(texture),
+                        "path": flver.texture_path(texture),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            json!({
+                "name": flver.material_name(material),
+                "definition": flver.material_path(material),
+                "textures": textures,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let meshes = flver
+        .meshes
+        .iter()
+        .map(|mesh| {
+            json!({
+                "material": flver.mesh_material(mesh).and_then(|m| flver.material_name(m)),
+                "vertices": flver
+                    .mesh_buffers(mesh)
+                    .map(|buffer| buffer.vertex_count.get())
+                    .max()
+                    .unwrap_or_default(),
+                "face_sets": mesh.face_set_count.get(),
+                "bones": mesh.bone_count.get(),
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let bones = flver
+        .bones()
+        .iter()
+        .map(|bone| {
+            flver
+                .string(bone.name_offset.get())
+                .map(|name| name.to_string())
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "format": "FLVER",
+        "version": format!("{:#x}", header.version.get()),
+        "bounding_box": {
+            "min": header.bounding_box_min.map(|v| v.get()),
+            "max": header.bounding_box_max.map(|v| v.get()),
+        },
+        "meshes": meshes,
+        "materials": materials,
+        "bones": bones,
+        "dummies": flver.dummies().len(),
+    }))
+}
+
+fn describe_tpf(bytes: &[u8]) -> Result<Value, String> {
+    let tpf = TPF::from_reader(&mut Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    let textures = tpf
+        .textures
+        .iter()
+        .map(|texture| {
+            // The DDS header says more about the format than the TPF's own format byte.
+            let dds = bytes
+                .get(texture.data_offset as usize..)
+                .and_then(|data| ddsfile::Dds::read(data).ok());
+
+            json!({
+                "name": texture.name,
+                "format": dds.as_ref().map_or_else(
+                    || texture.format.to_string(),
+                    crate::convert::ktx2::format_name,
+                ),
+                "width": dds.as_ref().map(|dds| dds.get_width()),
+                "height": dds.as_ref().map(|dds| dds.get_height()),
+                "mipmaps": texture.mipmaps,
+                "cubemap": texture.cubemap != 0,
+                "size": texture.data_size,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    Ok(json!({
+        "format": "TPF",
+        "platform": tpf.platform,
+        "textures": textures,
+    }))
+}
+
+/// Write `value` as indented `key: value` lines, with the items of lists on lines starting with
+/// `-`.
+pub(crate) fn write_summary(w: &mut impl Write, value: &Value, depth: usize) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    match value {
+        Value::Object(fields) => {
+            for (key, value) in fields {
+                match value {
+                    Value::Object(_) | Value::Array(_) if !is_empty(value) => {
+                        writeln!(w, "{indent}{key}:")?;
+                        write_summary(w, value, depth + 1)?;
+                    }
+                    _ => writeln!(w, "{indent}{key}: {}", scalar(value))?,
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                match item {
+                    Value::Object(fields) => write_item(w, fields, depth)?,
+                    _ => writeln!(w, "{indent}- {}", scalar(item))?,
+                }
+            }
+        }
+        _ => writeln!(w, "{indent}{}", scalar(value))?,
+    }
+
+    Ok(())
+}
+
+/// Write an object in a list, with its scalar fields on one line and the rest nested under it.
+fn write_item(w: &mut impl Write, fields: &Map<String, Value>, depth: usize) -> io::Result<()> {
+    let indent = "  ".repeat(depth);
+    let (nested, scalars): (Vec<_>, Vec<_>) = fields.iter().partition(|(_, value)| {
+        matches!(value, Value::Object(_) | Value::Array(_)) && !is_empty(value)
+    });
+
+    let line = scalars
+        .iter()
+        .map(|(key, value)| format!("{key}: {}", scalar(value)))
+        .collect::<Vec<_>>();
+    writeln!(w, "{indent}- {}", line.join(", "))?;
+
+    for (key, value) in nested {
+        writeln!(w, "{indent}  {key}:")?;
+        write_summary(w, value, depth + 2)?;
+    }
+
+    Ok(())
+}
+
+fn scalar(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Null => "-".to_string(),
+        Value::Array(items) if items.is_empty() => "none".to_string(),
+        value => value.to_string(),
+    }
+}
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Object(fields) => fields.is_empty(),
+        Value::Array(items) => items.is_empty(),
+        _ => false,
+    }
+}
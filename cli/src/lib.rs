@@ -1 +1,329 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Component, Path, PathBuf},
+    sync::atomic::{AtomicBool, Ordering},
+    thread,
+    time::{Duration, SystemTime},
+};
 
+use format::game::Game;
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle};
+use serde::Serialize;
+use souls_vfs::{FileKeyProvider, Vfs};
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+/// Print a summary or other status to stderr, unless `--quiet` was given.
+macro_rules! status {
+    ($($arg:tt)*) => {
+        if !$crate::is_quiet() {
+            eprintln!($($arg)*);
+        }
+    };
+}
+
+pub mod audio;
+pub mod browse;
+pub mod convert;
+pub mod dict;
+pub mod diff;
+pub mod emevd;
+pub mod export_map;
+pub mod extract;
+pub mod grep;
+pub mod hash;
+pub mod info;
+pub mod manifest;
+pub mod mods;
+pub mod pack;
+pub mod param;
+pub mod save;
+pub mod stats;
+pub mod text;
+pub mod tree;
+pub mod verify;
+pub mod vfs;
+
+/// Log to stderr as filtered by `RUST_LOG`, e.g. `RUST_LOG=format=debug`, including how long each
+/// archive open and parse took.
+pub fn init_tracing() {
+    tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::from_default_env())
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .init();
+}
+
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+// Arguments every command takes.
+#[derive(clap::Args, Debug)]
+pub struct GlobalArgs {
+    /// How many files to work on at once, the number of CPUs by default.
+    #[arg(long, short, global = true)]
+    pub jobs: Option<usize>,
+
+    /// Print only results, warnings and errors, without progress bars or summaries.
+    #[arg(long, short, global = true)]
+    pub quiet: bool,
+}
+
+impl GlobalArgs {
+    /// Size the thread pool and set whether progress is shown, before running a command.
+    pub fn apply(&self) -> io::Result<()> {
+        QUIET.store(self.quiet, Ordering::Relaxed);
+
+        if let Some(jobs) = self.jobs {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(jobs)
+                .build_global()
+                .map_err(io::Error::other)?;
+        }
+
+        Ok(())
+    }
+}
+
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// A progress bar for `len` items, hidden when `--quiet` was given.
+pub fn progress_bar(len: usize) -> ProgressBar {
+    let style = ProgressStyle::with_template("[{elapsed_precise}] {bar:40} {pos:>7}/{len:7} {msg}")
+        .expect("Could not create progress bar style");
+
+    ProgressBar::with_draw_target(Some(len as u64), progress_target()).with_style(style)
+}
+
+/// A set of progress bars drawn together, hidden when `--quiet` was given.
+pub fn progress_bars() -> MultiProgress {
+    MultiProgress::with_draw_target(progress_target())
+}
+
+fn progress_target() -> ProgressDrawTarget {
+    match is_quiet() {
+        true => ProgressDrawTarget::hidden(),
+        false => ProgressDrawTarget::stderr(),
+    }
+}
+
+/// Arguments shared by the commands that read a game's archives.
+#[derive(clap::Args, Debug)]
+pub struct GameArgs {
+    /// The game's install directory.
+    pub game_dir: PathBuf,
+
+    /// The game, e.g. `ds3`, detected from the game's executable by default.
+    #[arg(long)]
+    pub game: Option<Game>,
+
+    /// Directory of archive keys, searched for a directory named after the game first.
+    #[arg(long, default_value = "keys")]
+    pub keys: PathBuf,
+}
+
+impl GameArgs {
+    pub fn game(&self) -> Game {
+        self.game
+            .or_else(|| Game::detect(&self.game_dir))
+            .unwrap_or(Game::EldenRing)
+    }
+
+    pub fn open_vfs(&self) -> io::Result<Vfs> {
+        open_vfs(&self.game_dir, self.game(), &self.keys)
+    }
+}
+
+/// Arguments for the commands that can read from a game's archives, but don't have to.
+#[derive(clap::Args, Debug)]
+pub struct VfsArgs {
+    /// The game's install directory, to read files from its archives.
+    #[arg(long)]
+    pub game_dir: Option<PathBuf>,
+
+    /// The game, e.g. `ds3`, detected from the game's executable by default.
+    #[arg(long)]
+    pub game: Option<Game>,
+
+    /// Directory of archive keys, searched for a directory named after the game first.
+    #[arg(long, default_value = "keys")]
+    pub keys: PathBuf,
+}
+
+impl VfsArgs {
+    pub fn open_vfs(&self) -> io::Result<Option<Vfs>> {
+        let Some(game_dir) = &self.game_dir else {
+            return Ok(None);
+        };
+        let game = self
+            .game
+            .or_else(|| Game::detect(game_dir))
+            .unwrap_or(Game::EldenRing);
+
+        open_vfs(game_dir, game, &self.keys).map(Some)
+    }
+}
+
+/// How the commands that list or describe things print them.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq)]
+pub enum OutputFormat {
+    /// Text for people to read, which may change between releases.
+    #[default]
+    Text,
+    /// A single JSON document, whose fields are only ever added to.
+    Json,
+}
+
+/// Arguments shared by the commands that can print their results as JSON.
+#[derive(clap::Args, Debug)]
+pub struct OutputArgs {
+    /// How to print the results.
+    #[arg(long, value_enum, default_value_t)]
+    pub output: OutputFormat,
+}
+
+impl OutputArgs {
+    pub fn is_json(&self) -> bool {
+        self.output == OutputFormat::Json
+    }
+
+    /// Print `value` to stdout as pretty JSON.
+    pub fn write_json(&self, value: &impl Serialize) -> io::Result<()> {
+        let mut stdout = io::stdout().lock();
+        serde_json::to_writer_pretty(&mut stdout, value)?;
+        writeln!(stdout)
+    }
+}
+
+fn open_vfs(game_dir: &Path, game: Game, keys: &Path) -> io::Result<Vfs> {
+    let keys = FileKeyProvider::for_game(keys, game);
+
+    Vfs::open_game(game, game_dir, &keys)
+}
+
+/// Read a file name dictionary, one path per line, skipping blank lines and `#` comments.
+pub fn read_dictionary(path: impl AsRef<Path>) -> io::Result<Vec<String>> {
+    Ok(dictionary_paths(&fs::read_to_string(path)?)
+        .map(str::to_string)
+        .collect())
+}
+
+/// The paths of a dictionary's contents, see [`read_dictionary`].
+pub fn dictionary_paths(contents: &str) -> impl Iterator<Item = &str> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Where to write the file at the virtual `path` under `directory`. Anything that could lead
+/// outside of `directory`, like `..` or a drive prefix, is dropped.
+pub fn output_path(directory: &Path, path: &str) -> PathBuf {
+    let relative = path.replace('\\', "/");
+    let relative = Path::new(&relative)
+        .components()
+        .filter(|component| matches!(component, Component::Normal(_)));
+
+    directory.join(relative.collect::<PathBuf>())
+}
+
+/// Bytes in binary units, e.g. `1.5 GiB`.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    match unit {
+        0 => format!("{bytes} B"),
+        _ => format!("{size:.1} {}", UNITS[unit]),
+    }
+}
+
+/// Every file under `directory`, recursively.
+pub fn files_under(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut directories = vec![directory.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        for entry in fs::read_dir(directory)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                directories.push(entry.path());
+            } else {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    Ok(files)
+}
+
+/// How often `--watch` checks its inputs for changes.
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Run `f`, then with `watch` keep running it whenever a file at or under `paths` changes, until
+/// interrupted. Failures are reported rather than ending the watch, so a half-written file only
+/// fails one run.
+///
+/// Changes are found by polling modification times, and a run waits for the inputs to settle for
+/// a moment first, since editors and image tools often save in several writes.
+pub fn watch(
+    watch: bool,
+    paths: &[&Path],
+    mut f: impl FnMut() -> io::Result<()>,
+) -> io::Result<()> {
+    if !watch {
+        return f();
+    }
+    if let Some(path) = paths.iter().find(|path| !path.exists()) {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{} doesn't exist on disk to be watched", path.display()),
+        ));
+    }
+
+    loop {
+        if let Err(e) = f() {
+            eprintln!("{e}");
+        }
+
+        // Taken after the run, so outputs written next to the inputs don't start another one.
+        let mut seen = modified_times(paths);
+        let mut changed = false;
+        status!("Watching for changes, press Ctrl+C to stop");
+        loop {
+            thread::sleep(WATCH_INTERVAL);
+            let times = modified_times(paths);
+            match times == seen {
+                true if changed => break,
+                true => {}
+                false => {
+                    seen = times;
+                    changed = true;
+                }
+            }
+        }
+    }
+}
+
+/// The modification time of every file at or under `paths`. Files that can't be read, e.g. while
+/// they're being replaced, are left out.
+fn modified_times(paths: &[&Path]) -> BTreeMap<PathBuf, SystemTime> {
+    paths
+        .iter()
+        .flat_map(|path| match path.is_dir() {
+            true => files_under(path).unwrap_or_default(),
+            false => vec![path.to_path_buf()],
+        })
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect()
+}
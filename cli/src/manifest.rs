@@ -0,0 +1,293 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+use clap::{ArgAction, FromArgMatches};
+use format::game::Game;
+use serde::Deserialize;
+use souls_vfs::Vfs;
+use toml::{Table, Value};
+use tracing::warn;
+
+use crate::{
+    convert::{model, texture},
+    extract, open_vfs, pack, param,
+};
+
+/// Run the extraction and conversion jobs described by a TOML manifest, opening the game's
+/// archives once for all of them.
+///
+/// The manifest names the game and lists its jobs as `[[job]]` tables. Each job has a `command`,
+/// one of `extract`, `convert model`, `convert texture`, `pack`, `param export` or `param import`,
+/// and the arguments and options of that command as keys, e.g. `filter = ["msg/**"]` for
+/// `--filter`. The commands in `after` are run once the job is done, e.g. to post-process its
+/// output. Paths are relative to the manifest.
+///
+/// ```toml
+/// game_dir = "C:/Program Files (x86)/Steam/steamapps/common/ELDEN RING/Game"
+/// dictionary = "er.txt"
+///
+/// [[job]]
+/// name = "messages"
+/// command = "extract"
+/// out_dir = "build/msg"
+/// filter = ["msg/engus/*"]
+///
+/// [[job]]
+/// command = "convert texture"
+/// input = "/parts/am_m_1000.partsbnd.dcx"
+/// to = "png"
+/// output = "build/textures"
+/// after = [["optipng", "build/textures/am_m_1000_a.png"]]
+/// ```
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// The manifest to run, e.g. `mod.toml`.
+    manifest: PathBuf,
+
+    /// Only run the jobs with these names. May be repeated.
+    #[arg(long)]
+    job: Vec<String>,
+
+    /// Run the remaining jobs after one fails, rather than stopping.
+    #[arg(long)]
+    keep_going: bool,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+struct Manifest {
+    /// The game's install directory, for the jobs that read its archives.
+    game_dir: Option<PathBuf>,
+    game: Option<String>,
+    keys: Option<PathBuf>,
+
+    /// File name dictionary for the jobs that take one, unless they name their own.
+    dictionary: Option<PathBuf>,
+
+    #[serde(default, rename = "job")]
+    jobs: Vec<Job>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Job {
+    name: Option<String>,
+    command: String,
+
+    /// Commands to run after the job, each a program followed by its arguments.
+    #[serde(default)]
+    after: Vec<Vec<String>>,
+
+    /// Arguments of the command, by the name of the field or option.
+    #[serde(flatten)]
+    arguments: Table,
+}
+
+impl Job {
+    fn name(&self, index: usize) -> String {
+        self.name
+            .clone()
+            .unwrap_or_else(|| format!("#{} ({})", index + 1, self.command))
+    }
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let manifest: Manifest = toml::from_str(&fs::read_to_string(&args.manifest)?)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    // Every path in the manifest is relative to it, including the ones passed on to commands.
+    if let Some(directory) = args.manifest.parent().filter(|d| !d.as_os_str().is_empty()) {
+        env::set_current_dir(directory)?;
+    }
+
+    let game = match &manifest.game {
+        Some(game) => Some(
+            game.parse::<Game>()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?,
+        ),
+        None => None,
+    };
+    let mut vfs = None;
+
+    let (mut ran, mut failed) = (0, 0);
+    for (index, job) in manifest.jobs.iter().enumerate() {
+        let name = job.name(index);
+        if !args.job.is_empty() && !args.job.iter().any(|job| *job == name) {
+            continue;
+        }
+
+        status!("Running {name}");
+        let result = run_job(job, &manifest, game, &mut vfs)
+            .and_then(|()| job.after.iter().try_for_each(|command| run_after(command)));
+        ran += 1;
+
+        if let Err(error) = result {
+            failed += 1;
+            if !args.keep_going {
+                return Err(io::Error::new(error.kind(), format!("{name}: {error}")));
+            }
+            warn!(job = name, %error, "job failed");
+        }
+    }
+
+    status!("Ran {ran} jobs, {failed} failed");
+
+    Ok(())
+}
+
+fn run_job(
+    job: &Job,
+    manifest: &Manifest,
+    game: Option<Game>,
+    vfs: &mut Option<Vfs>,
+) -> io::Result<()> {
+    // Jobs share the manifest's game, so they don't have to repeat it.
+    let mut arguments = job.arguments.clone();
+    let shared = [
+        ("game_dir", manifest.game_dir.as_ref()),
+        ("keys", manifest.keys.as_ref()),
+        ("dictionary", manifest.dictionary.as_ref()),
+    ];
+    for (key, value) in shared {
+        if let Some(value) = value {
+            let value = Value::String(value.to_string_lossy().into_owned());
+            arguments.entry(key).or_insert(value);
+        }
+    }
+    if let Some(game) = &manifest.game {
+        arguments
+            .entry("game")
+            .or_insert(Value::String(game.clone()));
+    }
+
+    match job.command.split_whitespace().collect::<Vec<_>>()[..] {
+        ["extract"] => {
+            let args = parse::<extract::Args>("extract", &arguments)?;
+            let vfs = shared_vfs(vfs, manifest, game)?
+                .ok_or_else(|| invalid("extract needs the manifest's game_dir"))?;
+            extract::run_with(args, vfs)
+        }
+        ["convert", "model"] => model::run_with(
+            &parse("convert model", &arguments)?,
+            shared_vfs(vfs, manifest, game)?,
+        ),
+        ["convert", "texture"] => {
+            let args = parse::<texture::Args>("convert texture", &arguments)?;
+            texture::run_with(&args, shared_vfs(vfs, manifest, game)?)
+        }
+        ["pack"] => pack::run(parse("pack", &arguments)?),
+        ["param", "export"] => param::export(parse("param export", &arguments)?),
+        ["param", "import"] => param::import(parse("param import", &arguments)?),
+        _ => Err(invalid(&format!("unknown command {:?}", job.command))),
+    }
+}
+
+/// The archives of the manifest's game, opened by the first job that needs them.
+fn shared_vfs<'a>(
+    vfs: &'a mut Option<Vfs>,
+    manifest: &Manifest,
+    game: Option<Game>,
+) -> io::Result<Option<&'a Vfs>> {
+    let Some(game_dir) = &manifest.game_dir else {
+        return Ok(None);
+    };
+
+    if vfs.is_none() {
+        let game = game
+            .or_else(|| Game::detect(game_dir))
+            .unwrap_or(Game::EldenRing);
+        let keys = manifest
+            .keys
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("keys"));
+        *vfs = Some(open_vfs(game_dir, game, &keys)?);
+    }
+
+    Ok(vfs.as_ref())
+}
+
+/// Parse the arguments of a command from the keys of a job, as if they were given on the command
+/// line. Keys the command doesn't have are an error, except for the ones shared by every job.
+fn parse<T: clap::Args + FromArgMatches>(name: &'static str, arguments: &Table) -> io::Result<T> {
+    let mut command = T::augment_args(clap::Command::new(name).no_binary_name(true));
+    // Positionals only get their index once built.
+    command.build();
+
+    let mut positionals = Vec::new();
+    let mut argv = Vec::new();
+    for (key, value) in arguments {
+        let id = key.replace('-', "_");
+        let Some(arg) = command
+            .get_arguments()
+            .find(|arg| arg.get_id() == id.as_str())
+        else {
+            if matches!(id.as_str(), "game_dir" | "game" | "keys" | "dictionary") {
+                continue;
+            }
+            return Err(invalid(&format!("{name} has no argument {key:?}")));
+        };
+
+        let values = match value {
+            Value::Array(values) => values.iter().map(scalar).collect::<Result<Vec<_>, _>>()?,
+            value => vec![scalar(value)?],
+        };
+
+        if arg.is_positional() {
+            positionals.push((arg.get_index().unwrap_or_default(), values));
+            continue;
+        }
+
+        let long = format!("--{}", arg.get_long().unwrap_or(arg.get_id().as_str()));
+        for value in values {
+            match arg.get_action() {
+                ArgAction::SetTrue if value == "true" => argv.push(long.clone()),
+                ArgAction::SetTrue if value == "false" => {}
+                _ => argv.extend([long.clone(), value]),
+            }
+        }
+    }
+
+    // Positionals go first, so options taking any number of values don't swallow them.
+    positionals.sort_by_key(|(index, _)| *index);
+    let argv = positionals
+        .into_iter()
+        .flat_map(|(_, values)| values)
+        .chain(argv);
+
+    let matches = command
+        .try_get_matches_from(argv)
+        .map_err(|e| invalid(&e.render().to_string()))?;
+
+    T::from_arg_matches(&matches).map_err(|e| invalid(&e.to_string()))
+}
+
+fn scalar(value: &Value) -> io::Result<String> {
+    match value {
+        Value::String(value) => Ok(value.clone()),
+        Value::Integer(value) => Ok(value.to_string()),
+        Value::Float(value) => Ok(value.to_string()),
+        Value::Boolean(value) => Ok(value.to_string()),
+        value => Err(invalid(&format!("{value} can't be an argument"))),
+    }
+}
+
+/// Run a post-processing command, failing if it does.
+fn run_after(command: &[String]) -> io::Result<()> {
+    let [program, arguments @ ..] = command else {
+        return Err(invalid("after has an empty command"));
+    };
+
+    let status = process::Command::new(Path::new(program))
+        .args(arguments)
+        .status()?;
+    match status.success() {
+        true => Ok(()),
+        false => Err(io::Error::other(format!("{program} failed with {status}"))),
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.to_string())
+}
@@ -0,0 +1,220 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Cursor},
+    path::PathBuf,
+};
+
+use clap::Subcommand;
+use format::{dcx::DCX, game::Game};
+use souls_vfs::Vfs;
+use tracing::warn;
+
+use crate::{files_under, output_path, read_dictionary, VfsArgs};
+
+/// Lay out mods for Mod Engine 2.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    kind: Kind,
+}
+
+#[derive(Subcommand, Debug)]
+enum Kind {
+    Package(PackageArgs),
+}
+
+/// Copy modified files into a Mod Engine 2 mod directory, checking that the game has a file at
+/// each of their paths.
+///
+/// The input directory holds the files at their virtual paths, e.g. `parts/am_m_1000.partsbnd.dcx`
+/// or `regulation.bin`. With `--game-dir`, files missing the `.dcx` the game's copy has are renamed
+/// and compressed the way the game's copy is, and files the game doesn't have are rejected. With
+/// only a dictionary, paths are checked against it instead. Nothing is written if any file is
+/// rejected.
+///
+/// Mod Engine 2's default config loads the `mod` directory next to it. Patched archives can't be
+/// written instead, since the games only accept archive headers signed with FromSoftware's keys.
+#[derive(clap::Args, Debug)]
+pub struct PackageArgs {
+    /// Directory of modified files, laid out by virtual path.
+    input_dir: PathBuf,
+
+    /// The mod directory to write to.
+    #[arg(long = "out", short, value_name = "PATH", default_value = "mod")]
+    output: PathBuf,
+
+    /// File name dictionary, one virtual path per line, to check paths against without the game.
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+
+    #[command(flatten)]
+    vfs: VfsArgs,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    match args.kind {
+        Kind::Package(args) => package(args),
+    }
+}
+
+/// A file to write to the mod directory.
+struct Packaged {
+    source: PathBuf,
+    path: String,
+
+    /// The file compressed the way the game's copy is, if it wasn't already.
+    compressed: Option<Vec<u8>>,
+}
+
+fn package(args: PackageArgs) -> io::Result<()> {
+    let game = args
+        .vfs
+        .game
+        .or_else(|| args.vfs.game_dir.as_ref().and_then(Game::detect))
+        .ok_or_else(|| invalid("--game or --game-dir is needed to check paths"))?;
+    if !matches!(
+        game,
+        Game::DarkSouls3 | Game::Sekiro | Game::EldenRing | Game::ArmoredCore6
+    ) {
+        return Err(invalid(&format!("Mod Engine 2 doesn't support {game}")));
+    }
+
+    let vfs = args.vfs.open_vfs()?;
+    let dictionary = match &args.dictionary {
+        Some(dictionary) => Some(
+            read_dictionary(dictionary)?
+                .into_iter()
+                .map(|path| format!("/{}", path.trim_start_matches('/').to_lowercase()))
+                .collect::<HashSet<_>>(),
+        ),
+        None => None,
+    };
+    if vfs.is_none() && dictionary.is_none() {
+        warn!("neither --game-dir nor --dictionary given, paths won't be checked");
+    }
+
+    let mut packaged = Vec::new();
+    let mut rejected = 0;
+    for source in files_under(&args.input_dir)? {
+        let relative = source
+            .strip_prefix(&args.input_dir)
+            .map_err(io::Error::other)?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let path = format!("/{relative}");
+
+        let bytes = fs::read(&source)?;
+        let result = match &vfs {
+            Some(vfs) => check_game(vfs, &args, &path, &bytes),
+            None => check_dictionary(dictionary.as_ref(), &path),
+        };
+
+        match result {
+            Ok((path, compressed)) => packaged.push(Packaged {
+                source,
+                path,
+                compressed,
+            }),
+            Err(reason) => {
+                eprintln!("{path}: {reason}");
+                rejected += 1;
+            }
+        }
+    }
+
+    if rejected > 0 {
+        return Err(invalid(&format!(
+            "{rejected} files have paths {game} doesn't have, nothing was written"
+        )));
+    }
+
+    for file in &packaged {
+        let output = output_path(&args.output, &file.path);
+        if let Some(directory) = output.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        match &file.compressed {
+            Some(bytes) => fs::write(output, bytes)?,
+            None => fs::copy(&file.source, output).map(|_| ())?,
+        }
+    }
+
+    status!(
+        "Packaged {} files into {}",
+        packaged.len(),
+        args.output.display()
+    );
+
+    Ok(())
+}
+
+/// Check `path` against the game's archives and loose files, returning the path to write the file
+/// to and the file compressed like the game's copy if it has to be.
+fn check_game(
+    vfs: &Vfs,
+    args: &PackageArgs,
+    path: &str,
+    bytes: &[u8],
+) -> Result<(String, Option<Vec<u8>>), String> {
+    let compressed = bytes.starts_with(b"DCX\0");
+
+    if vfs.contains(path) {
+        let original = vfs.read(path).map_err(|e| e.to_string())?;
+        return match (original.starts_with(b"DCX\0"), compressed) {
+            (true, false) => Ok((path.to_string(), Some(compress_like(&original, bytes)?))),
+            (false, true) => Err("is DCX compressed, but the game's copy isn't".to_string()),
+            _ => Ok((path.to_string(), None)),
+        };
+    }
+
+    let with_dcx = format!("{path}.dcx");
+    if !compressed && vfs.contains(&with_dcx) {
+        let original = vfs.read(&with_dcx).map_err(|e| e.to_string())?;
+        return Ok((with_dcx, Some(compress_like(&original, bytes)?)));
+    }
+
+    // Like regulation.bin, some files are read from the install directory rather than archives.
+    let game_dir = args.vfs.game_dir.as_ref().expect("archives are open");
+    if output_path(game_dir, path).is_file() {
+        return Ok((path.to_string(), None));
+    }
+
+    Err("the game has no such file".to_string())
+}
+
+fn check_dictionary(
+    dictionary: Option<&HashSet<String>>,
+    path: &str,
+) -> Result<(String, Option<Vec<u8>>), String> {
+    let Some(dictionary) = dictionary else {
+        return Ok((path.to_string(), None));
+    };
+
+    let lowercase = path.to_lowercase();
+    if dictionary.contains(&lowercase) {
+        Ok((path.to_string(), None))
+    } else if dictionary.contains(&format!("{lowercase}.dcx")) {
+        Err(
+            "the game's copy is DCX compressed, give --game-dir to compress it the same"
+                .to_string(),
+        )
+    } else {
+        Err("the dictionary has no such file".to_string())
+    }
+}
+
+/// Compress `bytes` with the same DCX algorithm and parameters as the compressed `original`.
+fn compress_like(original: &[u8], bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let mut dcx = DCX::from_reader(&mut Cursor::new(original)).map_err(|e| e.to_string())?;
+    dcx.decompressed = bytes.to_vec();
+
+    let mut output = Vec::new();
+    dcx.write(&mut output).map_err(|e| e.to_string())?;
+
+    Ok(output)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.to_string())
+}
@@ -0,0 +1,121 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+};
+
+use format::{bnd4::BND4, dcx::DCX};
+use tracing::warn;
+
+use crate::{files_under, watch};
+
+/// Rebuild a binder with the loose files of a directory, the reverse of extracting it.
+///
+/// The original binder is the template: each of its files is replaced by the loose file with the
+/// same path or file name, and it is compressed again the way the original was. Files the
+/// template doesn't have can't be added.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// Directory of loose files to pack.
+    input_dir: PathBuf,
+
+    /// The original binder, optionally DCX compressed.
+    binder: PathBuf,
+
+    /// Where to write the rebuilt binder.
+    #[arg(long = "out", short, value_name = "PATH")]
+    output: PathBuf,
+
+    /// Keep running, and pack again whenever a loose file or the original binder changes.
+    #[arg(long)]
+    watch: bool,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    watch(args.watch, &[&args.input_dir, &args.binder], || pack(&args))
+}
+
+fn pack(args: &Args) -> io::Result<()> {
+    let bytes = fs::read(&args.binder)?;
+    let mut r = Cursor::new(bytes);
+    let dcx = match DCX::has_magic(&mut r)? {
+        true => Some(DCX::from_reader(&mut r).map_err(io::Error::other)?),
+        false => None,
+    };
+    let data = match &dcx {
+        Some(dcx) => dcx.decompressed.clone(),
+        None => r.into_inner(),
+    };
+    let mut bnd = BND4::from_reader(&mut Cursor::new(data))?;
+
+    let files = files_under(&args.input_dir)?;
+    let loose = by_path(&args.input_dir, &files);
+
+    let mut used = HashSet::new();
+    let mut replaced = 0;
+    for index in 0..bnd.files.len() {
+        let path = BND4::normalize_path(&bnd.files[index].path);
+        let file_name = path.rsplit('/').next().unwrap_or_default();
+
+        let Some(source) = loose.get(&path).or_else(|| loose.get(file_name)) else {
+            continue;
+        };
+        bnd.replace_file(index, &fs::read(source)?)?;
+        used.insert(*source);
+        replaced += 1;
+    }
+
+    for file in files.iter().filter(|file| !used.contains(file)) {
+        warn!(path = %file.display(), "binder has no such file, skipping");
+    }
+
+    let output = match dcx {
+        Some(mut dcx) => {
+            dcx.decompressed = bnd.data;
+            let mut output = Vec::new();
+            dcx.write(&mut output).map_err(io::Error::other)?;
+            output
+        }
+        None => bnd.data,
+    };
+    fs::write(&args.output, output)?;
+
+    status!(
+        "Replaced {replaced} of {} files in {}",
+        bnd.files.len(),
+        args.binder.display()
+    );
+
+    Ok(())
+}
+
+/// Look up `files` under `directory` by their lowercase path relative to it, or by their file name
+/// where no other file has the same name.
+fn by_path<'a>(directory: &Path, files: &'a [PathBuf]) -> HashMap<String, &'a PathBuf> {
+    let mut by_name = HashMap::<String, Option<&PathBuf>>::new();
+    let mut by_path = HashMap::new();
+    for file in files {
+        let relative = file
+            .strip_prefix(directory)
+            .unwrap_or(file)
+            .to_string_lossy()
+            .replace('\\', "/")
+            .to_lowercase();
+        let file_name = relative.rsplit('/').next().unwrap_or_default().to_string();
+
+        by_name
+            .entry(file_name)
+            .and_modify(|unique| *unique = None)
+            .or_insert(Some(file));
+        by_path.insert(relative, file);
+    }
+
+    by_path.extend(
+        by_name
+            .into_iter()
+            .filter_map(|(name, file)| Some((name, file?))),
+    );
+
+    by_path
+}
@@ -0,0 +1,495 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+};
+
+use clap::Subcommand;
+use format::{
+    dcx::DCX,
+    game::Game,
+    param::{
+        regulation::{decrypt_regulation, encrypt_regulation},
+        Param, ParamDef, ParamRow, ParamValue, Paramdex, Regulation,
+    },
+};
+use serde_json::{json, Value};
+use tracing::warn;
+
+/// Export the params of a regulation to CSV or JSON tables, and import edited tables back.
+///
+/// Each param becomes a table named after it, e.g. `EquipParamWeapon.csv`, with a row per param row
+/// and a column per field of its paramdef. Regulations are decrypted and decompressed as needed,
+/// and imports are written back the same way.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    kind: Kind,
+}
+
+#[derive(Subcommand, Debug)]
+enum Kind {
+    Export(ExportArgs),
+    Import(ImportArgs),
+}
+
+/// Write a table for every param of a regulation that has a paramdef.
+#[derive(clap::Args, Debug)]
+pub struct ExportArgs {
+    /// Paramdex directory of the game, with a `Defs` and optionally a `Names` folder.
+    #[arg(long)]
+    def_dir: PathBuf,
+
+    /// The regulation, e.g. `regulation.bin` or the `Data0.bdt` of Dark Souls III.
+    regulation: PathBuf,
+
+    /// Directory to write the tables to.
+    output_dir: PathBuf,
+
+    #[arg(long, value_enum, default_value_t = TableFormat::Csv)]
+    format: TableFormat,
+
+    /// Only export these params, e.g. `--param EquipParamWeapon`.
+    #[arg(long = "param")]
+    params: Vec<String>,
+}
+
+/// Replace the params of a regulation with the tables in a directory.
+///
+/// A table holds every row of its param: rows are matched to the original by ID, rows missing from
+/// the table are removed and new rows start out zeroed. Params without a table are left as they
+/// are.
+#[derive(clap::Args, Debug)]
+pub struct ImportArgs {
+    /// Paramdex directory of the game, with a `Defs` and optionally a `Names` folder.
+    #[arg(long)]
+    def_dir: PathBuf,
+
+    /// The original regulation.
+    regulation: PathBuf,
+
+    /// Directory of tables, as written by `fstools param export`.
+    input_dir: PathBuf,
+
+    #[arg(long, value_enum, default_value_t = TableFormat::Csv)]
+    format: TableFormat,
+
+    /// Where to write the new regulation.
+    #[arg(long = "out", short, value_name = "PATH")]
+    output: PathBuf,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum TableFormat {
+    Csv,
+    /// An object with a `columns` array and a `rows` array of arrays.
+    Json,
+}
+
+impl TableFormat {
+    pub(crate) fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Json => "json",
+        }
+    }
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    match args.kind {
+        Kind::Export(args) => export(args),
+        Kind::Import(args) => import(args),
+    }
+}
+
+pub(crate) fn export(args: ExportArgs) -> io::Result<()> {
+    let paramdex = Paramdex::load(&args.def_dir).map_err(io::Error::other)?;
+    let file = RegulationFile::read(&args.regulation)?;
+    fs::create_dir_all(&args.output_dir)?;
+
+    let mut exported = 0;
+    for name in file.regulation.param_names() {
+        if !args.params.is_empty() && !args.params.iter().any(|p| p.eq_ignore_ascii_case(name)) {
+            continue;
+        }
+
+        let param = file.regulation.param(name).map_err(io::Error::other)?;
+        let Some(def) = def_for(&paramdex, name, &param) else {
+            continue;
+        };
+
+        let mut table = Table {
+            columns: ["ID", "Name"]
+                .into_iter()
+                .map(str::to_string)
+                .chain(def.fields.iter().map(|field| field.name.clone()))
+                .collect(),
+            rows: Vec::with_capacity(param.rows.len()),
+        };
+        for row in &param.rows {
+            let values = param.read_row(def, &row.data).map_err(io::Error::other)?;
+            let name = row
+                .name
+                .as_deref()
+                .or_else(|| paramdex.row_name(name, row.id))
+                .unwrap_or_default();
+
+            table.rows.push(
+                [row.id.to_string(), name.to_string()]
+                    .into_iter()
+                    .chain(values.iter().map(ParamValue::to_string))
+                    .collect(),
+            );
+        }
+
+        let path = args
+            .output_dir
+            .join(format!("{name}.{}", args.format.extension()));
+        fs::write(path, table.write(args.format))?;
+        exported += 1;
+    }
+
+    status!(
+        "Exported {exported} params to {}",
+        args.output_dir.display()
+    );
+
+    Ok(())
+}
+
+pub(crate) fn import(args: ImportArgs) -> io::Result<()> {
+    let paramdex = Paramdex::load(&args.def_dir).map_err(io::Error::other)?;
+    let mut file = RegulationFile::read(&args.regulation)?;
+
+    let names = file
+        .regulation
+        .param_names()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    let mut changed = 0;
+    for name in &names {
+        let path = args
+            .input_dir
+            .join(format!("{name}.{}", args.format.extension()));
+        if !path.is_file() {
+            continue;
+        }
+
+        let mut param = file.regulation.param(name).map_err(io::Error::other)?;
+        let def = def_for(&paramdex, name, &param)
+            .ok_or_else(|| io::Error::other(format!("{name} has no usable paramdef")))?;
+        let table = Table::read(&fs::read_to_string(&path)?, args.format)
+            .map_err(|e| io::Error::other(format!("Could not read {}: {e}", path.display())))?;
+
+        let rows = table_rows(&paramdex, name, &param, def, &table)
+            .map_err(|e| io::Error::other(format!("{name}: {e}")))?;
+        if rows != param.rows {
+            param.rows = rows;
+            file.regulation
+                .replace_param(name, &param)
+                .map_err(io::Error::other)?;
+            changed += 1;
+        }
+    }
+
+    file.write(&args.output)?;
+    status!(
+        "Imported {changed} changed params into {}",
+        args.output.display()
+    );
+
+    Ok(())
+}
+
+/// The paramdef for the param named `name`, if there is one that fits its rows.
+pub(crate) fn def_for<'a>(
+    paramdex: &'a Paramdex,
+    name: &str,
+    param: &Param,
+) -> Option<&'a ParamDef> {
+    let Some(def) = paramdex.def(&param.param_type) else {
+        warn!(name, param_type = param.param_type, "no paramdef, skipping");
+        return None;
+    };
+
+    // Rows may be padded past the end of the paramdef, but never shorter than it.
+    if param.row_size < def.row_size() {
+        warn!(
+            name,
+            row_size = param.row_size,
+            def_row_size = def.row_size(),
+            "paramdef doesn't match the param, skipping"
+        );
+        return None;
+    }
+
+    Some(def)
+}
+
+/// Build the rows of `param` from a table exported from it.
+fn table_rows(
+    paramdex: &Paramdex,
+    name: &str,
+    param: &Param,
+    def: &ParamDef,
+    table: &Table,
+) -> Result<Vec<ParamRow>, String> {
+    let mut columns = Vec::with_capacity(table.columns.len());
+    for column in table.columns.iter().skip(2) {
+        let field = def
+            .fields
+            .iter()
+            .position(|field| field.name == *column)
+            .ok_or_else(|| format!("{} has no field {column}", def.param_type))?;
+        columns.push(field);
+    }
+
+    // Rows sharing an ID are told apart by the order they appear in.
+    let mut originals = HashMap::<i32, Vec<&ParamRow>>::new();
+    for row in param.rows.iter().rev() {
+        originals.entry(row.id).or_default().push(row);
+    }
+
+    let layout = def.layout();
+    let mut rows = Vec::with_capacity(table.rows.len());
+    for cells in &table.rows {
+        let [id, row_name, values @ ..] = &cells[..] else {
+            return Err(format!("row {cells:?} has no ID or name"));
+        };
+        let id = id
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| format!("invalid row ID {id:?}"))?;
+        let original = originals.get_mut(&id).and_then(Vec::pop);
+
+        let mut data = original
+            .map(|row| row.data.clone())
+            .unwrap_or_else(|| vec![0; param.row_size]);
+        for (&field, value) in columns.iter().zip(values) {
+            let field_def = &def.fields[field];
+            let value =
+                ParamValue::parse(field_def, value).map_err(|e| format!("row {id}: {e}"))?;
+            param
+                .write_field(field_def, layout[field], &value, &mut data)
+                .map_err(|e| format!("row {id}: {e}"))?;
+        }
+
+        // Names that only came from Paramdex on export stay out of the regulation.
+        let known_name = paramdex.row_name(name, id);
+        let name = match original.and_then(|row| row.name.as_deref()) {
+            None if Some(row_name.as_str()) == known_name => None,
+            _ => Some(row_name.clone()).filter(|name| !name.is_empty()),
+        };
+
+        rows.push(ParamRow { id, name, data });
+    }
+
+    Ok(rows)
+}
+
+/// A regulation, along with how it was compressed and encrypted so it can be written back the
+/// same way.
+pub(crate) struct RegulationFile {
+    pub(crate) regulation: Regulation,
+    dcx: Option<DCX>,
+    encryption: Option<([u8; 32], [u8; 16])>,
+}
+
+impl RegulationFile {
+    pub(crate) fn read(path: &Path) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+
+        let mut encryption = None;
+        let bytes = if bytes.starts_with(b"BND4") || bytes.starts_with(b"DCX\0") {
+            bytes
+        } else {
+            let decrypted = Game::ALL
+                .into_iter()
+                .filter_map(Game::regulation_key)
+                .find_map(|key| {
+                    let decrypted = decrypt_regulation(&bytes, &key).ok()?;
+                    decrypted.starts_with(b"DCX\0").then_some((key, decrypted))
+                });
+            let Some((key, decrypted)) = decrypted else {
+                return Err(io::Error::other(format!(
+                    "{} isn't a regulation binder, or is encrypted with an unknown key",
+                    path.display()
+                )));
+            };
+
+            let mut iv = [0; 16];
+            iv.copy_from_slice(&bytes[..16]);
+            encryption = Some((key, iv));
+            decrypted
+        };
+
+        let mut r = Cursor::new(bytes);
+        let dcx = match DCX::has_magic(&mut r)? {
+            true => Some(DCX::from_reader(&mut r).map_err(io::Error::other)?),
+            false => None,
+        };
+        let data = match &dcx {
+            Some(dcx) => dcx.decompressed.clone(),
+            None => r.into_inner(),
+        };
+        let regulation = Regulation::from_bytes(data).map_err(io::Error::other)?;
+
+        Ok(Self {
+            regulation,
+            dcx,
+            encryption,
+        })
+    }
+
+    fn write(self, path: &Path) -> io::Result<()> {
+        let data = self.regulation.into_bytes();
+        let data = match self.dcx {
+            Some(mut dcx) => {
+                dcx.decompressed = data;
+                let mut output = Vec::new();
+                dcx.write(&mut output).map_err(io::Error::other)?;
+                output
+            }
+            None => data,
+        };
+        let data = match self.encryption {
+            Some((key, iv)) => encrypt_regulation(&data, &key, iv),
+            None => data,
+        };
+
+        fs::write(path, data)
+    }
+}
+
+/// The cells of an exported param, as text.
+struct Table {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn write(&self, format: TableFormat) -> String {
+        match format {
+            TableFormat::Csv => {
+                let mut csv = String::new();
+                for record in [&self.columns].into_iter().chain(&self.rows) {
+                    let cells = record.iter().map(|cell| csv_cell(cell)).collect::<Vec<_>>();
+                    csv.push_str(&cells.join(","));
+                    csv.push('\n');
+                }
+
+                csv
+            }
+            TableFormat::Json => {
+                let rows = self
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        let mut cells = row.iter();
+                        let id = cells.next().and_then(|id| id.parse::<i32>().ok());
+                        let name = cells.next();
+
+                        [json!(id), json!(name)]
+                            .into_iter()
+                            .chain(cells.map(|cell| json_cell(cell)))
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+
+                serde_json::to_string_pretty(&json!({
+                    "columns": self.columns,
+                    "rows": rows,
+                }))
+                .unwrap_or_default()
+            }
+        }
+    }
+
+    fn read(text: &str, format: TableFormat) -> Result<Self, String> {
+        let mut records = match format {
+            TableFormat::Csv => parse_csv(text)?,
+            TableFormat::Json => {
+                let value = serde_json::from_str::<Value>(text).map_err(|e| e.to_string())?;
+                [&value["columns"]]
+                    .into_iter()
+                    .chain(value["rows"].as_array().into_iter().flatten())
+                    .map(|record| {
+                        let cells = record.as_array().ok_or("rows aren't arrays")?;
+                        Ok(cells
+                            .iter()
+                            .map(|cell| match cell {
+                                Value::String(cell) => cell.clone(),
+                                Value::Null => String::new(),
+                                cell => cell.to_string(),
+                            })
+                            .collect())
+                    })
+                    .collect::<Result<Vec<_>, &str>>()?
+            }
+        };
+
+        if records.is_empty() {
+            return Err("table has no columns".to_string());
+        }
+        let columns = records.remove(0);
+
+        Ok(Self {
+            columns,
+            rows: records,
+        })
+    }
+}
+
+/// Quote a CSV cell if it would otherwise be read back differently.
+pub(crate) fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) || cell.trim() != cell {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+pub(crate) fn parse_csv(text: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match (ch, quoted) {
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            ('"', true) => quoted = false,
+            ('"', false) if cell.is_empty() => quoted = true,
+            (',', false) => record.push(std::mem::take(&mut cell)),
+            ('\r', false) => {}
+            ('\n', false) => {
+                record.push(std::mem::take(&mut cell));
+                records.push(std::mem::take(&mut record));
+            }
+            (ch, _) => cell.push(ch),
+        }
+    }
+
+    if quoted {
+        return Err("CSV ends inside a quoted cell".to_string());
+    }
+    if !cell.is_empty() || !record.is_empty() {
+        record.push(cell);
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+/// Numbers, booleans and arrays of them as JSON values, anything else as a string.
+fn json_cell(cell: &str) -> Value {
+    match serde_json::from_str::<Value>(cell) {
+        Ok(value @ (Value::Number(_) | Value::Bool(_) | Value::Array(_))) => value,
+        _ => Value::String(cell.to_string()),
+    }
+}
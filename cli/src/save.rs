@@ -0,0 +1,283 @@
+use std::{
+    fs,
+    io::{self, Cursor, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::Subcommand;
+use format::{
+    game::Game,
+    save::{SaveGame, Sl2, SlotSummary},
+};
+use serde::Serialize;
+
+use crate::OutputArgs;
+
+/// Inspect and repair save files (`.sl2`).
+///
+/// The game is detected from the file name, e.g. `ER0000.sl2`, unless `--game` is given. Slot
+/// summaries and copying slots are only supported for Elden Ring.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    kind: Kind,
+}
+
+#[derive(Subcommand, Debug)]
+enum Kind {
+    Info(InfoArgs),
+    CopySlot(CopySlotArgs),
+    FixChecksum(FixChecksumArgs),
+}
+
+/// Print the character slots of a save with their levels and playtime, and the entries whose
+/// checksums don't match.
+#[derive(clap::Args, Debug)]
+pub struct InfoArgs {
+    save: PathBuf,
+
+    /// The game the save is from, e.g. `er`.
+    #[arg(long)]
+    game: Option<Game>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// Copy a character into another slot, of the same save or from another one of the same account.
+///
+/// The save is overwritten unless `--out` is given, after keeping a copy of it as `.bak`.
+#[derive(clap::Args, Debug)]
+pub struct CopySlotArgs {
+    save: PathBuf,
+
+    /// The slot to copy, from 0.
+    from: usize,
+
+    /// The slot to copy it into.
+    to: usize,
+
+    /// Copy the character from this save rather than the one being written.
+    #[arg(long)]
+    from_save: Option<PathBuf>,
+
+    /// Replace the character in the target slot, if there is one.
+    #[arg(long)]
+    force: bool,
+
+    /// Where to write the save.
+    #[arg(long = "out", short, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// The game the save is from, e.g. `er`.
+    #[arg(long)]
+    game: Option<Game>,
+}
+
+/// Rewrite a save with the checksum of every entry fixed, e.g. after editing it by hand.
+///
+/// The save is overwritten unless `--out` is given, after keeping a copy of it as `.bak`.
+#[derive(clap::Args, Debug)]
+pub struct FixChecksumArgs {
+    save: PathBuf,
+
+    /// Where to write the save.
+    #[arg(long = "out", short, value_name = "PATH")]
+    output: Option<PathBuf>,
+
+    /// The game the save is from, e.g. `er`.
+    #[arg(long)]
+    game: Option<Game>,
+}
+
+/// The `--output json` of `save info`.
+#[derive(Serialize)]
+struct Info {
+    game: SaveGame,
+
+    /// Empty for games whose slot summaries aren't known.
+    slots: Vec<SlotSummary>,
+    invalid_checksums: Vec<String>,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    match args.kind {
+        Kind::Info(args) => info(args),
+        Kind::CopySlot(args) => copy_slot(args),
+        Kind::FixChecksum(args) => fix_checksum(args),
+    }
+}
+
+fn info(args: InfoArgs) -> io::Result<()> {
+    let save = read_save(&args.save, args.game)?;
+    let info = Info {
+        game: save.game(),
+        slots: match save.game() {
+            SaveGame::EldenRing => save.slot_summaries().map_err(io::Error::other)?,
+            _ => Vec::new(),
+        },
+        invalid_checksums: save
+            .invalid_entries()
+            .map(|entry| entry.name.clone())
+            .collect(),
+    };
+
+    if args.output.is_json() {
+        return args.output.write_json(&info);
+    }
+
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{:?} save", info.game)?;
+    for slot in &info.slots {
+        match slot.active {
+            true => writeln!(
+                stdout,
+                "Slot {}: {}, level {}, {} played",
+                slot.index,
+                slot.name,
+                slot.level,
+                playtime(slot.seconds_played)
+            )?,
+            false => writeln!(stdout, "Slot {}: empty", slot.index)?,
+        }
+    }
+    if info.slots.is_empty() {
+        let slots = save.slots().count();
+        writeln!(
+            stdout,
+            "{slots} character slots, their summaries can't be read"
+        )?;
+    }
+
+    match &info.invalid_checksums[..] {
+        [] => writeln!(stdout, "Every entry's checksum is valid"),
+        names => writeln!(stdout, "Invalid checksums: {}", names.join(", ")),
+    }
+}
+
+fn copy_slot(args: CopySlotArgs) -> io::Result<()> {
+    let mut save = read_save(&args.save, args.game)?;
+    let source = read_save(args.from_save.as_ref().unwrap_or(&args.save), args.game)?;
+    if source.game() != save.game() {
+        return Err(invalid(
+            "Characters can only be copied between saves of the same game",
+        ));
+    }
+
+    let summaries = save.slot_summaries().map_err(io::Error::other)?;
+    if let Some(target) = summaries.iter().find(|slot| slot.index == args.to) {
+        if target.active && !args.force {
+            return Err(invalid(&format!(
+                "Slot {} holds {}, give --force to replace it",
+                args.to, target.name
+            )));
+        }
+    }
+    let name = source
+        .slot_summaries()
+        .ok()
+        .and_then(|slots| slots.into_iter().find(|slot| slot.index == args.from))
+        .map(|slot| slot.name)
+        .unwrap_or_default();
+
+    save.copy_slot(&source, args.from, args.to)
+        .map_err(io::Error::other)?;
+    let output = write_save(&mut save, &args.save, args.output.as_deref())?;
+
+    status!(
+        "Copied {name} from slot {} to slot {}, written to {}",
+        args.from,
+        args.to,
+        output.display()
+    );
+
+    Ok(())
+}
+
+fn fix_checksum(args: FixChecksumArgs) -> io::Result<()> {
+    let mut save = read_save(&args.save, args.game)?;
+    let invalid = save
+        .invalid_entries()
+        .map(|entry| entry.name.clone())
+        .collect::<Vec<_>>();
+
+    let output = write_save(&mut save, &args.save, args.output.as_deref())?;
+    match &invalid[..] {
+        [] => status!(
+            "Every checksum was already valid, written to {}",
+            output.display()
+        ),
+        names => status!(
+            "Fixed the checksums of {}, written to {}",
+            names.join(", "),
+            output.display()
+        ),
+    }
+
+    Ok(())
+}
+
+fn read_save(path: &Path, game: Option<Game>) -> io::Result<Sl2> {
+    let file_name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_uppercase();
+    let game = match game {
+        Some(Game::DarkSouls3) => SaveGame::DarkSouls3,
+        Some(Game::Sekiro) => SaveGame::Sekiro,
+        Some(Game::EldenRing) => SaveGame::EldenRing,
+        Some(game) => return Err(invalid(&format!("Saves of {game} aren't supported"))),
+        None if file_name.starts_with("DS3") => SaveGame::DarkSouls3,
+        None if file_name.starts_with("ER") => SaveGame::EldenRing,
+        None if file_name.starts_with('S') => SaveGame::Sekiro,
+        None => {
+            return Err(invalid(&format!(
+                "Could not tell which game {} is from, give --game",
+                path.display()
+            )))
+        }
+    };
+
+    let bytes = fs::read(path)?;
+    Sl2::from_reader(&mut Cursor::new(bytes), game).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+/// Write `save` to `output`, or over `path` after keeping a copy of it as `.bak`.
+fn write_save(save: &mut Sl2, path: &Path, output: Option<&Path>) -> io::Result<PathBuf> {
+    let mut bytes = Vec::new();
+    save.write(&mut bytes).map_err(io::Error::other)?;
+
+    let output = match output {
+        Some(output) => output.to_path_buf(),
+        None => {
+            let mut backup = path.as_os_str().to_owned();
+            backup.push(".bak");
+            fs::copy(path, backup)?;
+
+            path.to_path_buf()
+        }
+    };
+    fs::write(&output, bytes)?;
+
+    Ok(output)
+}
+
+/// Seconds as `hours:minutes:seconds`, like the load menu.
+fn playtime(seconds: u32) -> String {
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.to_string())
+}
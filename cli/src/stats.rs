@@ -0,0 +1,245 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Read, Write},
+    path::PathBuf,
+};
+
+use byteorder::{ByteOrder, BE};
+use indicatif::ParallelProgressIterator;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
+use souls_vfs::{Name, Vfs};
+
+use crate::{human_size, progress_bar, read_dictionary, GameArgs, OutputArgs};
+
+/// Label of the files whose hash isn't in the dictionary, so whose extension isn't known.
+const UNNAMED: &str = "(unnamed)";
+
+/// Summarize a game install: how many files of each type it holds, how large they are, how well
+/// they compress and how many of their hashes the dictionary doesn't name.
+///
+/// Files are grouped by their extensions without `.dcx`, e.g. `chrbnd` for `c0000.chrbnd.dcx`.
+/// Compression is read from the DCX header of each file, without decompressing it.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// File name dictionary, one virtual path per line, to name files by. Without one, every file
+    /// counts as unnamed.
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// The `--output json` of the command.
+#[derive(Serialize, Default)]
+struct Report {
+    #[serde(flatten)]
+    total: Totals,
+
+    /// Files whose hash isn't in the dictionary.
+    unnamed: usize,
+    archives: BTreeMap<String, ArchiveTotals>,
+
+    /// Largest first.
+    extensions: Vec<ExtensionTotals>,
+}
+
+#[derive(Serialize, Default, Clone, Copy)]
+struct Totals {
+    files: usize,
+
+    /// Size of the files as stored, compressed or not.
+    size: u64,
+    compressed_files: usize,
+
+    /// Stored and uncompressed size of the compressed files.
+    compressed_size: u64,
+    uncompressed_size: u64,
+}
+
+#[derive(Serialize, Default)]
+struct ArchiveTotals {
+    files: usize,
+    unnamed: usize,
+    size: u64,
+}
+
+#[derive(Serialize)]
+struct ExtensionTotals {
+    extension: String,
+
+    #[serde(flatten)]
+    total: Totals,
+
+    /// Number of compressed files by DCX algorithm, e.g. `KRAK`.
+    algorithms: BTreeMap<String, usize>,
+}
+
+/// What's known about one file without reading more than its header.
+struct FileInfo<'a> {
+    archive: &'a str,
+    extension: Option<String>,
+    size: u64,
+
+    /// The DCX algorithm and uncompressed size, if the file is compressed.
+    compression: Option<(String, u64)>,
+}
+
+impl Totals {
+    fn add(&mut self, file: &FileInfo) {
+        self.files += 1;
+        self.size += file.size;
+        if let Some((_, uncompressed_size)) = &file.compression {
+            self.compressed_files += 1;
+            self.compressed_size += file.size;
+            self.uncompressed_size += uncompressed_size;
+        }
+    }
+
+    /// Compressed size as a share of the uncompressed size.
+    fn ratio(&self) -> Option<f64> {
+        (self.uncompressed_size > 0)
+            .then(|| self.compressed_size as f64 / self.uncompressed_size as f64)
+    }
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+
+    let mut extensions = HashMap::new();
+    if let Some(dictionary) = &args.dictionary {
+        for path in read_dictionary(dictionary)? {
+            if let Some(extension) = extension(&path) {
+                extensions.insert(Name::new(vfs.game(), &path), extension);
+            }
+        }
+    }
+
+    let names = vfs.names().collect::<Vec<_>>();
+    let files = names
+        .par_iter()
+        .progress_with(progress_bar(names.len()))
+        .filter_map(|name| file_info(&vfs, name, extensions.get(*name).cloned()))
+        .collect::<Vec<_>>();
+
+    let mut report = Report::default();
+    let mut by_extension = HashMap::<_, (Totals, BTreeMap<String, usize>)>::new();
+    for file in &files {
+        report.total.add(file);
+
+        let archive = report.archives.entry(file.archive.to_string()).or_default();
+        archive.files += 1;
+        archive.size += file.size;
+        if file.extension.is_none() {
+            archive.unnamed += 1;
+            report.unnamed += 1;
+        }
+
+        let label = file.extension.as_deref().unwrap_or(UNNAMED);
+        let (totals, algorithms) = by_extension.entry(label).or_default();
+        totals.add(file);
+        if let Some((algorithm, _)) = &file.compression {
+            *algorithms.entry(algorithm.clone()).or_default() += 1;
+        }
+    }
+
+    report.extensions = by_extension
+        .into_iter()
+        .map(|(extension, (total, algorithms))| ExtensionTotals {
+            extension: extension.to_string(),
+            total,
+            algorithms,
+        })
+        .collect();
+    report
+        .extensions
+        .sort_by(|a, b| (b.total.size, &a.extension).cmp(&(a.total.size, &b.extension)));
+
+    if args.output.is_json() {
+        return args.output.write_json(&report);
+    }
+
+    let mut stdout = io::stdout().lock();
+    writeln!(
+        stdout,
+        "{:<16} {:>9} {:>11} {:>10} {:>6}",
+        "Extension", "Files", "Size", "Compressed", "Ratio"
+    )?;
+    let row = |stdout: &mut io::StdoutLock, label: &str, total: &Totals| {
+        writeln!(
+            stdout,
+            "{label:<16} {:>9} {:>11} {:>10} {:>6}",
+            total.files,
+            human_size(total.size),
+            total.compressed_files,
+            total
+                .ratio()
+                .map_or("-".to_string(), |ratio| format!("{:.0}%", ratio * 100.0))
+        )
+    };
+    for extension in &report.extensions {
+        row(&mut stdout, &extension.extension, &extension.total)?;
+    }
+    row(&mut stdout, "Total", &report.total)?;
+
+    writeln!(stdout)?;
+    for (archive, totals) in &report.archives {
+        writeln!(
+            stdout,
+            "{archive}: {} files, {}, {} unnamed",
+            totals.files,
+            human_size(totals.size),
+            totals.unnamed
+        )?;
+    }
+    if report.total.files > 0 {
+        writeln!(
+            stdout,
+            "{} of {} files ({:.1}%) aren't named by the dictionary",
+            report.unnamed,
+            report.total.files,
+            report.unnamed as f64 * 100.0 / report.total.files as f64
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The extensions of a path without `.dcx`, e.g. `chrbnd` for `/chr/c0000.chrbnd.dcx`.
+fn extension(path: &str) -> Option<String> {
+    let file_name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+    let (_, extension) = file_name.split_once('.')?;
+
+    Some(
+        extension
+            .strip_suffix(".dcx")
+            .unwrap_or(extension)
+            .to_string(),
+    )
+}
+
+/// Read the DCX header of a file, skipping files that can't be opened.
+fn file_info<'a>(vfs: &'a Vfs, name: &Name, extension: Option<String>) -> Option<FileInfo<'a>> {
+    let stat = vfs.stat_name(name).ok()?;
+
+    let mut header = [0u8; 0x2C];
+    let compressed = stat.size as usize >= header.len()
+        && vfs.open_name(name).ok()?.read_exact(&mut header).is_ok()
+        && header.starts_with(b"DCX\0");
+
+    Some(FileInfo {
+        archive: stat.archive,
+        extension,
+        size: stat.size as u64,
+        compression: compressed.then(|| {
+            (
+                String::from_utf8_lossy(&header[0x28..0x2C]).into_owned(),
+                BE::read_u32(&header[0x1C..]) as u64,
+            )
+        }),
+    })
+}
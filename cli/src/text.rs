@@ -0,0 +1,268 @@
+use std::{
+    fs,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+};
+
+use clap::Subcommand;
+use format::msgbnd::MsgBnd;
+use serde_json::{Map, Value};
+
+use crate::{
+    files_under,
+    param::{csv_cell, parse_csv, TableFormat},
+};
+
+/// Export the text of message binders to CSV or JSON tables, and import edited tables back, e.g.
+/// to translate the game.
+///
+/// Each FMG becomes a table in a directory named after its binder, e.g. `item/WeaponName.csv`
+/// for the `WeaponName.fmg` of `item.msgbnd.dcx`, with an `ID` and a `Text` column. DLC FMGs such
+/// as `WeaponName_dlc01` get tables of their own.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    kind: Kind,
+}
+
+#[derive(Subcommand, Debug)]
+enum Kind {
+    Export(ExportArgs),
+    Import(ImportArgs),
+}
+
+/// Write a table for every FMG of the message binders in a directory.
+#[derive(clap::Args, Debug)]
+pub struct ExportArgs {
+    /// Directory of message binders for one language, e.g. an extracted `msg/engus`.
+    msg_dir: PathBuf,
+
+    /// Directory to write the tables to.
+    output_dir: PathBuf,
+
+    #[arg(long, value_enum, default_value_t = TableFormat::Csv)]
+    format: TableFormat,
+}
+
+/// Replace the text of message binders with the tables in a directory.
+///
+/// Entries missing from a table are left as they are, so tables can hold only the translated
+/// entries. Only the binders with changed text are written, compressed like the originals.
+#[derive(clap::Args, Debug)]
+pub struct ImportArgs {
+    /// Directory of the original message binders.
+    msg_dir: PathBuf,
+
+    /// Directory of tables, as written by `fstools text export`.
+    input_dir: PathBuf,
+
+    #[arg(long, value_enum, default_value_t = TableFormat::Csv)]
+    format: TableFormat,
+
+    /// Directory to write the changed binders to.
+    #[arg(long, short)]
+    output_dir: PathBuf,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    match args.kind {
+        Kind::Export(args) => export(args),
+        Kind::Import(args) => import(args),
+    }
+}
+
+fn export(args: ExportArgs) -> io::Result<()> {
+    let (mut binders, mut tables) = (0, 0);
+    for (path, stem) in msgbnds(&args.msg_dir)? {
+        let msgbnd = read_msgbnd(&path)?;
+        let directory = args.output_dir.join(&stem);
+        fs::create_dir_all(&directory)?;
+
+        for name in msgbnd.fmg_names() {
+            let Some(fmg) = msgbnd.fmg(name) else {
+                continue;
+            };
+            let entries = fmg
+                .entries
+                .iter()
+                .map(|entry| (entry.id, entry.text.as_deref()));
+
+            let table = directory.join(format!("{name}.{}", args.format.extension()));
+            fs::write(table, write_table(entries, args.format))?;
+            tables += 1;
+        }
+        binders += 1;
+    }
+
+    status!(
+        "Exported {tables} FMGs of {binders} binders to {}",
+        args.output_dir.display()
+    );
+
+    Ok(())
+}
+
+fn import(args: ImportArgs) -> io::Result<()> {
+    let (mut binders, mut changed) = (0, 0);
+    for (path, stem) in msgbnds(&args.msg_dir)? {
+        let directory = args.input_dir.join(&stem);
+        if !directory.is_dir() {
+            continue;
+        }
+
+        let mut msgbnd = read_msgbnd(&path)?;
+        let names = msgbnd.fmg_names().map(str::to_string).collect::<Vec<_>>();
+
+        let mut binder_changed = 0;
+        for name in names {
+            let table = directory.join(format!("{name}.{}", args.format.extension()));
+            if !table.is_file() {
+                continue;
+            }
+
+            let entries = read_table(&fs::read_to_string(&table)?, args.format)
+                .map_err(|e| invalid_data(&table, &e))?;
+
+            // Only FMGs with changed text are marked as edited, so the rest are written as read.
+            let unchanged = msgbnd.fmg(&name).is_some_and(|fmg| {
+                entries
+                    .iter()
+                    .all(|(id, text)| same_text(fmg.get(*id), text.as_deref()))
+            });
+            if unchanged {
+                continue;
+            }
+
+            let Some(fmg) = msgbnd.fmg_mut(&name) else {
+                continue;
+            };
+            for (id, text) in entries {
+                if !same_text(fmg.get(id), text.as_deref()) {
+                    fmg.set(id, text);
+                    binder_changed += 1;
+                }
+            }
+        }
+
+        if binder_changed == 0 {
+            continue;
+        }
+
+        let output = args
+            .output_dir
+            .join(path.strip_prefix(&args.msg_dir).unwrap_or(&path));
+        if let Some(directory) = output.parent() {
+            fs::create_dir_all(directory)?;
+        }
+        let mut bytes = Vec::new();
+        msgbnd.write(&mut bytes).map_err(io::Error::other)?;
+        fs::write(output, bytes)?;
+
+        binders += 1;
+        changed += binder_changed;
+    }
+
+    status!(
+        "Changed {changed} entries in {binders} binders, written to {}",
+        args.output_dir.display()
+    );
+
+    Ok(())
+}
+
+/// The message binders under `directory`, with the names of their table directories, e.g. `item`
+/// for `item.msgbnd.dcx`.
+fn msgbnds(directory: &Path) -> io::Result<Vec<(PathBuf, String)>> {
+    let mut binders = files_under(directory)?
+        .into_iter()
+        .filter_map(|path| {
+            let file_name = path.file_name()?.to_str()?.to_lowercase();
+            let (stem, _) = file_name.split_once(".msgbnd")?;
+            let stem = stem.to_string();
+
+            Some((path, stem))
+        })
+        .collect::<Vec<_>>();
+    binders.sort();
+
+    Ok(binders)
+}
+
+fn read_msgbnd(path: &Path) -> io::Result<MsgBnd> {
+    let bytes = fs::read(path)?;
+
+    MsgBnd::from_reader(&mut Cursor::new(bytes)).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+/// Entries without text are empty cells in CSV and `null` in JSON, which maps IDs to text.
+fn write_table<'a>(
+    entries: impl Iterator<Item = (i32, Option<&'a str>)>,
+    format: TableFormat,
+) -> String {
+    match format {
+        TableFormat::Csv => {
+            let mut csv = "ID,Text\n".to_string();
+            for (id, text) in entries {
+                csv.push_str(&format!("{id},{}\n", csv_cell(text.unwrap_or_default())));
+            }
+
+            csv
+        }
+        TableFormat::Json => {
+            let object = entries
+                .map(|(id, text)| (id.to_string(), text.map_or(Value::Null, Value::from)))
+                .collect::<Map<_, _>>();
+
+            serde_json::to_string_pretty(&object).unwrap_or_default()
+        }
+    }
+}
+
+fn read_table(text: &str, format: TableFormat) -> Result<Vec<(i32, Option<String>)>, String> {
+    let parse_id = |id: &str| {
+        id.trim()
+            .parse::<i32>()
+            .map_err(|_| format!("{id:?} is not an ID"))
+    };
+
+    match format {
+        TableFormat::Csv => parse_csv(text)?
+            .into_iter()
+            .skip(1)
+            .map(|record| match &record[..] {
+                [id] => Ok((parse_id(id)?, None)),
+                [id, text] => Ok((parse_id(id)?, Some(text.clone()).filter(|t| !t.is_empty()))),
+                _ => Err(format!("expected an ID and a text, got {record:?}")),
+            })
+            .collect(),
+        TableFormat::Json => {
+            let object =
+                serde_json::from_str::<Map<String, Value>>(text).map_err(|e| e.to_string())?;
+            object
+                .iter()
+                .map(|(id, text)| match text {
+                    Value::String(text) => Ok((parse_id(id)?, Some(text.clone()))),
+                    Value::Null => Ok((parse_id(id)?, None)),
+                    _ => Err(format!("text of {id} isn't a string")),
+                })
+                .collect()
+        }
+    }
+}
+
+/// Empty text and no text at all are the same in a table, and to the games.
+fn same_text(a: Option<&str>, b: Option<&str>) -> bool {
+    a.unwrap_or_default() == b.unwrap_or_default()
+}
+
+fn invalid_data(path: &Path, message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {message}", path.display()),
+    )
+}
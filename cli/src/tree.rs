@@ -0,0 +1,278 @@
+use std::{
+    fs,
+    io::{self, Cursor, Write},
+};
+
+use byteorder::{ByteOrder, BE};
+use format::{
+    bnd4::{BND4Entry, BND4},
+    bxf4::BXF4,
+    dcx::DCX,
+    flver::Flver,
+    tpf::TPF,
+};
+use serde::Serialize;
+
+use crate::{convert::ktx2, human_size, OutputArgs, VfsArgs};
+
+/// Show how a file is wrapped: its DCX compression, the files of the binders in it and the
+/// textures of their TPFs, down to the files that hold no others.
+///
+/// Each node shows its size and what it is, and compressed nodes how far they compress. A split
+/// binder header (`.*bhd`) is opened with the `.*bdt` next to it, on disk or in the same binder.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    /// The file to show, on disk or in the game's archives when `--game-dir` is given, e.g.
+    /// `/chr/c3251.chrbnd.dcx`.
+    input: String,
+
+    /// How many levels of nodes to show below the file, all of them by default.
+    #[arg(long)]
+    depth: Option<usize>,
+
+    #[command(flatten)]
+    vfs: VfsArgs,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// A file and the files it holds, which is also the `--output json` of the command.
+#[derive(Serialize)]
+struct Node {
+    /// The file's name, or empty for the contents of a compressed file.
+    name: String,
+    format: String,
+    size: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    detail: Option<String>,
+
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    children: Vec<Node>,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let vfs = args.vfs.open_vfs()?;
+    let read = |path: &str| match &vfs {
+        Some(vfs) if path.starts_with('/') => vfs
+            .read(path)
+            .map_err(|e| io::Error::other(format!("Could not read {path}: {e}"))),
+        _ => fs::read(path),
+    };
+
+    let bytes = read(&args.input)?;
+    let bdt = match args.input.strip_suffix("bhd") {
+        Some(data_path) if bytes.starts_with(b"BHF4") => Some(read(&format!("{data_path}bdt"))?),
+        _ => None,
+    };
+
+    let name = args.input.rsplit(['/', '\\']).next().unwrap_or_default();
+    let mut tree = node(name, &bytes, bdt);
+    if let Some(depth) = args.depth {
+        prune(&mut tree, depth);
+    }
+
+    if args.output.is_json() {
+        return args.output.write_json(&tree);
+    }
+
+    let mut stdout = io::stdout().lock();
+    writeln!(stdout, "{}", label(&tree))?;
+    write_children(&mut stdout, &tree, "")
+}
+
+/// Describe `bytes` according to the format its magic identifies, with the files it holds.
+/// `bdt` is the data of a split binder, when `bytes` is its header.
+fn node(name: &str, bytes: &[u8], bdt: Option<Vec<u8>>) -> Node {
+    let mut node = Node {
+        name: name.to_string(),
+        format: String::new(),
+        size: bytes.len() as u64,
+        detail: None,
+        children: Vec::new(),
+    };
+
+    let result = match bytes.get(..4).unwrap_or_default() {
+        b"DCX\0" => dcx(&mut node, bytes),
+        b"BND4" => bnd4(&mut node, bytes),
+        b"BHF4" => bhf4(&mut node, bytes, bdt),
+        b"TPF\0" => tpf(&mut node, bytes),
+        b"FLVE" => flver(&mut node, bytes),
+        magic => {
+            node.format = match magic.iter().all(|b| b.is_ascii_alphanumeric()) {
+                true => String::from_utf8_lossy(magic).into_owned(),
+                false => "unknown".to_string(),
+            };
+            Ok(())
+        }
+    };
+
+    if let Err(error) = result {
+        node.detail = Some(error);
+    }
+
+    node
+}
+
+fn dcx(node: &mut Node, bytes: &[u8]) -> Result<(), String> {
+    node.format = "DCX".to_string();
+
+    let header = bytes.get(..0x2C).ok_or("DCX header is truncated")?;
+    let algorithm = String::from_utf8_lossy(&header[0x28..0x2C]).into_owned();
+    let uncompressed_size = BE::read_u32(&header[0x1C..]) as u64;
+    node.detail = Some(format!(
+        "{algorithm}, {} uncompressed ({:.0}%)",
+        human_size(uncompressed_size),
+        node.size as f64 * 100.0 / uncompressed_size.max(1) as f64
+    ));
+
+    // Other algorithms can still be shown, just not opened.
+    let dcx = DCX::from_reader(&mut Cursor::new(bytes))
+        .map_err(|e| format!("{algorithm}, could not decompress: {e}"))?;
+    node.children.push(self::node("", &dcx.decompressed, None));
+
+    Ok(())
+}
+
+fn bnd4(node: &mut Node, bytes: &[u8]) -> Result<(), String> {
+    node.format = "BND4".to_string();
+
+    let bnd = BND4::parse(bytes.to_vec()).map_err(|e| e.to_string())?;
+    node.detail = Some(files(bnd.files.len()));
+    node.children = binder_nodes(&bnd.files, |file| bnd.file_bytes(file));
+
+    Ok(())
+}
+
+fn bhf4(node: &mut Node, bytes: &[u8], bdt: Option<Vec<u8>>) -> Result<(), String> {
+    node.format = "BHF4".to_string();
+
+    let bdt = bdt.ok_or("split binder header, its BDT wasn't found")?;
+    let bxf = BXF4::from_bytes(bytes, bdt).map_err(|e| e.to_string())?;
+    node.detail = Some(format!("{} in its BDT", files(bxf.files.len())));
+    node.children = binder_nodes(&bxf.files, |file| bxf.file_bytes(file));
+
+    Ok(())
+}
+
+/// The files of a binder, with each split binder header opened with the BDT of the same name.
+fn binder_nodes<'a>(
+    files: &'a [BND4Entry],
+    file_bytes: impl Fn(&'a BND4Entry) -> &'a [u8],
+) -> Vec<Node> {
+    files
+        .iter()
+        .map(|file| {
+            let path = BND4::normalize_path(&file.path);
+            let bdt = path.strip_suffix("bhd").and_then(|data_path| {
+                let data_path = format!("{data_path}bdt");
+                files
+                    .iter()
+                    .find(|other| BND4::normalize_path(&other.path) == data_path)
+                    .map(|other| file_bytes(other).to_vec())
+            });
+
+            let name = path.rsplit('/').next().unwrap_or_default();
+            node(name, file_bytes(file), bdt)
+        })
+        .collect()
+}
+
+fn tpf(node: &mut Node, bytes: &[u8]) -> Result<(), String> {
+    node.format = "TPF".to_string();
+
+    let tpf = TPF::from_reader(&mut Cursor::new(bytes)).map_err(|e| e.to_string())?;
+    node.detail = Some(match tpf.textures.len() {
+        1 => "1 texture".to_string(),
+        count => format!("{count} textures"),
+    });
+    node.children = tpf
+        .textures
+        .iter()
+        .map(|texture| {
+            // The DDS header says more about the format than the TPF's own format byte.
+            let dds = bytes
+                .get(texture.data_offset as usize..)
+                .and_then(|data| ddsfile::Dds::read(data).ok());
+
+            Node {
+                name: texture.name.clone(),
+                format: dds
+                    .as_ref()
+                    .map_or_else(|| format!("format {}", texture.format), ktx2::format_name),
+                size: texture.data_size as u64,
+                detail: dds.map(|dds| {
+                    format!(
+                        "{}x{}, {} mipmaps",
+                        dds.get_width(),
+                        dds.get_height(),
+                        texture.mipmaps
+                    )
+                }),
+                children: Vec::new(),
+            }
+        })
+        .collect();
+
+    Ok(())
+}
+
+fn flver(node: &mut Node, bytes: &[u8]) -> Result<(), String> {
+    node.format = "FLVER".to_string();
+
+    let flver = Flver::parse(bytes).map_err(|e| e.to_string())?;
+    node.detail = Some(format!(
+        "{} meshes, {} materials, {} bones",
+        flver.meshes.len(),
+        flver.materials().len(),
+        flver.bones().len()
+    ));
+
+    Ok(())
+}
+
+fn files(count: usize) -> String {
+    match count {
+        1 => "1 file".to_string(),
+        count => format!("{count} files"),
+    }
+}
+
+/// Drop the nodes more than `depth` levels below `node`.
+fn prune(node: &mut Node, depth: usize) {
+    match depth {
+        0 => node.children.clear(),
+        _ => node
+            .children
+            .iter_mut()
+            .for_each(|child| prune(child, depth - 1)),
+    }
+}
+
+fn label(node: &Node) -> String {
+    let mut label = match node.name.is_empty() {
+        true => format!("{} {}", node.format, human_size(node.size)),
+        false => format!("{} {} {}", node.name, node.format, human_size(node.size)),
+    };
+    if let Some(detail) = &node.detail {
+        label.push_str(&format!(" ({detail})"));
+    }
+
+    label
+}
+
+fn write_children(w: &mut impl Write, node: &Node, prefix: &str) -> io::Result<()> {
+    for (index, child) in node.children.iter().enumerate() {
+        let last = index == node.children.len() - 1;
+        let (branch, indent) = match last {
+            true => ("└── ", "    "),
+            false => ("├── ", "│   "),
+        };
+
+        writeln!(w, "{prefix}{branch}{}", label(child))?;
+        write_children(w, child, &format!("{prefix}{indent}"))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use indicatif::ParallelProgressIterator;
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+use serde::Serialize;
+use souls_vfs::{Name, VfsReadError, VfsVerifyError};
+
+use crate::{progress_bar, read_dictionary, GameArgs, OutputArgs};
+
+/// Check a game install for damage: files that run past the end of a truncated BDT, files that
+/// don't match the SHA-256 digests in the archive headers and files that fail to decompress.
+///
+/// A file that matches its digest but still fails to decompress was read correctly, so the
+/// problem is more likely in fstools than in the install. Fails when any problems are found.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// File name dictionary, one virtual path per line, to name files in the report rather than
+    /// giving their hashes.
+    #[arg(long)]
+    dictionary: Option<PathBuf>,
+
+    /// Only check that files are within their archives and match their digests, without
+    /// decompressing them.
+    #[arg(long)]
+    quick: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum ProblemKind {
+    Truncated,
+    Digest,
+    Decompress,
+}
+
+/// The `--output json` of the command.
+#[derive(Serialize)]
+struct Report {
+    files: usize,
+
+    /// How many of the files had a digest to check.
+    digests: usize,
+
+    /// Archives whose BDT is shorter than their header says, with how many bytes are missing.
+    truncated_archives: BTreeMap<String, u64>,
+    problems: Vec<Problem>,
+}
+
+#[derive(Serialize)]
+struct Problem {
+    path: String,
+    archive: String,
+    kind: ProblemKind,
+    message: String,
+
+    /// How many bytes of a truncated file are missing.
+    missing: Option<u64>,
+
+    /// Whether the file matched its digest before failing to decompress, `null` if it has none.
+    digest_matched: Option<bool>,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+    let game = vfs.game();
+
+    let mut labels = HashMap::new();
+    if let Some(dictionary) = &args.dictionary {
+        for path in read_dictionary(dictionary)? {
+            labels.insert(Name::new(game, &path), path);
+        }
+    }
+
+    let mut names = vfs.names().collect::<Vec<_>>();
+    // Named files first, then the ones only known by hash.
+    names.sort_by_key(|name| {
+        let path = labels.get(*name).map(String::as_str);
+        (path.is_none(), path, name.0)
+    });
+
+    let results = names
+        .par_iter()
+        .progress_with(progress_bar(names.len()))
+        .map(|name| {
+            let problem = |kind, message: String, missing, digest_matched| Problem {
+                path: labels
+                    .get(name)
+                    .cloned()
+                    .unwrap_or_else(|| format!("#{:016x}", name.0)),
+                archive: vfs
+                    .stat_name(name)
+                    .map(|stat| stat.archive.to_string())
+                    .unwrap_or_default(),
+                kind,
+                message,
+                missing,
+                digest_matched,
+            };
+
+            let digest = match vfs.verify_name(name) {
+                Ok(digest) => digest,
+                Err(error @ VfsVerifyError::Truncated { missing }) => {
+                    let message = error.to_string();
+                    let problem = problem(ProblemKind::Truncated, message, Some(missing), None);
+                    return (false, Some(problem));
+                }
+                Err(error) => {
+                    let problem = problem(ProblemKind::Digest, error.to_string(), None, None);
+                    return (true, Some(problem));
+                }
+            };
+            if args.quick {
+                return (digest, None);
+            }
+
+            match vfs.read_decompressed_name(name) {
+                Ok(_) => (digest, None),
+                Err(error @ (VfsReadError::Dcx(_) | VfsReadError::Io(_))) => (
+                    digest,
+                    Some(problem(
+                        ProblemKind::Decompress,
+                        error.to_string(),
+                        None,
+                        digest.then_some(true),
+                    )),
+                ),
+                Err(VfsReadError::Open(_)) => (digest, None),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut report = Report {
+        files: names.len(),
+        digests: results.iter().filter(|(digest, _)| *digest).count(),
+        truncated_archives: BTreeMap::new(),
+        problems: results
+            .into_iter()
+            .filter_map(|(_, problem)| problem)
+            .collect(),
+    };
+    // The file furthest past the end of an archive says how much of it is missing.
+    for problem in &report.problems {
+        if let Some(missing) = problem.missing {
+            let most = report
+                .truncated_archives
+                .entry(problem.archive.clone())
+                .or_default();
+            *most = (*most).max(missing);
+        }
+    }
+
+    if args.output.is_json() {
+        args.output.write_json(&report)?;
+    } else {
+        let mut stdout = io::stdout().lock();
+        for (archive, missing) in &report.truncated_archives {
+            writeln!(
+                stdout,
+                "{archive}: truncated, at least {missing} bytes are missing"
+            )?;
+        }
+        for problem in &report.problems {
+            match problem.digest_matched {
+                Some(true) => writeln!(
+                    stdout,
+                    "{}: {} (but it matches its digest, so this is likely a bug)",
+                    problem.path, problem.message
+                )?,
+                _ => writeln!(stdout, "{}: {}", problem.path, problem.message)?,
+            }
+        }
+    }
+
+    status!(
+        "Checked {} files, {} with digests, and found {} problems",
+        report.files,
+        report.digests,
+        report.problems.len()
+    );
+
+    match report.problems.len() {
+        0 => Ok(()),
+        problems => Err(io::Error::other(format!(
+            "{problems} files failed verification"
+        ))),
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    collections::BTreeMap,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use byteorder::{ByteOrder, BE};
+use clap::Subcommand;
+use serde::Serialize;
+use souls_vfs::{Vfs, VfsOpenError, VfsReadError};
+
+use crate::{read_dictionary, GameArgs, OutputArgs};
+
+/// Explore a game's archives as if they were a directory tree.
+#[derive(clap::Args, Debug)]
+pub struct Args {
+    #[command(subcommand)]
+    kind: Kind,
+}
+
+#[derive(Subcommand, Debug)]
+enum Kind {
+    Ls(LsArgs),
+    Cat(CatArgs),
+    Stat(StatArgs),
+}
+
+/// List the files and directories in a virtual directory.
+///
+/// The archives only store hashes of their paths, so only the paths in the dictionary that the
+/// archives have are listed.
+#[derive(clap::Args, Debug)]
+pub struct LsArgs {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// Virtual directory to list, e.g. `/chr`.
+    #[arg(default_value = "/")]
+    path: String,
+
+    /// File name dictionary, one virtual path per line.
+    #[arg(long)]
+    dictionary: PathBuf,
+
+    /// List every file under the directory rather than only its direct children.
+    #[arg(long, short)]
+    recursive: bool,
+
+    /// Show the size and archive of each file.
+    #[arg(long, short)]
+    long: bool,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// Write the contents of a file to stdout.
+#[derive(clap::Args, Debug)]
+pub struct CatArgs {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// Virtual path of the file, e.g. `/regulation.bin`.
+    path: String,
+
+    /// Undo the file's DCX compression, if it has any.
+    #[arg(long, short)]
+    decompress: bool,
+}
+
+/// Show where files are stored and how they're compressed.
+#[derive(clap::Args, Debug)]
+pub struct StatArgs {
+    #[command(flatten)]
+    game: GameArgs,
+
+    /// Virtual paths of the files, e.g. `/chr/c0000.anibnd.dcx`.
+    #[arg(required = true)]
+    paths: Vec<String>,
+
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// The `--output json` of `ls`.
+#[derive(Serialize)]
+struct Listing {
+    directory: String,
+    entries: Vec<ListEntry>,
+}
+
+#[derive(Serialize)]
+struct ListEntry {
+    /// Relative to the listed directory, with a trailing `/` for directories.
+    name: String,
+    path: String,
+    directory: bool,
+
+    /// Both `null` for directories.
+    size: Option<u32>,
+    archive: Option<String>,
+}
+
+/// The `--output json` of `stat`, one for each path.
+#[derive(Serialize)]
+struct FileStat {
+    path: String,
+    archive: String,
+    offset: u64,
+    size: u32,
+    padded_size: u32,
+    encrypted_ranges: usize,
+    compression: Option<Compression>,
+}
+
+#[derive(Serialize)]
+struct Compression {
+    format: String,
+    uncompressed_size: u32,
+}
+
+pub fn run(args: Args) -> io::Result<()> {
+    match args.kind {
+        Kind::Ls(args) => ls(args),
+        Kind::Cat(args) => cat(args),
+        Kind::Stat(args) => stat(args),
+    }
+}
+
+fn ls(args: LsArgs) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+    let directory = format!("/{}/", args.path.trim_matches('/').to_lowercase()).replace("//", "/");
+
+    // Directories are listed once however many files they hold, files keep their own case.
+    let mut entries = BTreeMap::<String, Option<String>>::new();
+    for path in read_dictionary(&args.dictionary)? {
+        let normalized = format!("/{}", path.trim_start_matches('/'));
+        let Some(relative) = strip_prefix_ignore_case(&normalized, &directory) else {
+            continue;
+        };
+        if !vfs.contains(&normalized) {
+            continue;
+        }
+
+        match relative.split_once('/') {
+            Some((child, _)) if !args.recursive => {
+                entries.entry(format!("{child}/")).or_insert(None);
+            }
+            _ => {
+                entries.insert(relative.to_string(), Some(normalized));
+            }
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("No files in the dictionary are under {directory}"),
+        ));
+    }
+
+    let entries = entries
+        .into_iter()
+        .map(|(name, path)| {
+            let stat = path.as_ref().and_then(|path| vfs.stat(path).ok());
+            ListEntry {
+                directory: path.is_none(),
+                path: path.unwrap_or_else(|| format!("{directory}{name}")),
+                size: stat.as_ref().map(|stat| stat.size),
+                archive: stat.map(|stat| stat.archive.to_string()),
+                name,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if args.output.is_json() {
+        return args.output.write_json(&Listing { directory, entries });
+    }
+
+    let mut stdout = io::stdout().lock();
+    for entry in entries {
+        match (args.long, entry.size, entry.archive) {
+            (true, Some(size), Some(archive)) => {
+                writeln!(stdout, "{size:>12} {archive:<8} {}", entry.name)?
+            }
+            (true, _, _) => writeln!(stdout, "{:>12} {:<8} {}", "-", "", entry.name)?,
+            (false, _, _) => writeln!(stdout, "{}", entry.name)?,
+        }
+    }
+
+    Ok(())
+}
+
+fn cat(args: CatArgs) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+    let bytes = match args.decompress {
+        true => vfs.read_decompressed(&args.path),
+        false => vfs.read(&args.path),
+    }
+    .map_err(|e| read_error(&args.path, e))?;
+
+    io::stdout().lock().write_all(&bytes)
+}
+
+fn stat(args: StatArgs) -> io::Result<()> {
+    let vfs = args.game.open_vfs()?;
+
+    let mut stats = Vec::with_capacity(args.paths.len());
+    for path in &args.paths {
+        let stat = vfs
+            .stat(path)
+            .map_err(|e| read_error(path, VfsReadError::Open(e)))?;
+
+        stats.push(FileStat {
+            path: path.clone(),
+            archive: stat.archive.to_string(),
+            offset: stat.offset,
+            size: stat.size,
+            padded_size: stat.padded_size,
+            encrypted_ranges: stat.encrypted_ranges,
+            compression: compression(&vfs, path, stat.size),
+        });
+    }
+
+    if args.output.is_json() {
+        return args.output.write_json(&stats);
+    }
+
+    let mut stdout = io::stdout().lock();
+    for (index, stat) in stats.iter().enumerate() {
+        if index > 0 {
+            writeln!(stdout)?;
+        }
+        writeln!(stdout, "{}", stat.path)?;
+        writeln!(stdout, "  Archive:     {}", stat.archive)?;
+        writeln!(stdout, "  Offset:      {:#x}", stat.offset)?;
+        writeln!(
+            stdout,
+            "  Size:        {} ({} with padding)",
+            stat.size, stat.padded_size
+        )?;
+        match stat.encrypted_ranges {
+            0 => writeln!(stdout, "  Encrypted:   no")?,
+            ranges => writeln!(stdout, "  Encrypted:   {ranges} ranges")?,
+        }
+        match &stat.compression {
+            Some(compression) => writeln!(
+                stdout,
+                "  Compression: DCX {}, {} bytes uncompressed",
+                compression.format, compression.uncompressed_size
+            )?,
+            None => writeln!(stdout, "  Compression: none")?,
+        }
+    }
+
+    Ok(())
+}
+
+/// The DCX header of the file at `path`, if it's compressed at all.
+fn compression(vfs: &Vfs, path: &str, size: u32) -> Option<Compression> {
+    let header = vfs.read_range(path, 0..0x2C.min(size as usize)).ok()?;
+    if header.len() < 0x2C || !header.starts_with(b"DCX\0") {
+        return None;
+    }
+
+    Some(Compression {
+        format: String::from_utf8_lossy(&header[0x28..0x2C]).into_owned(),
+        uncompressed_size: BE::read_u32(&header[0x1C..]),
+    })
+}
+
+pub(crate) fn strip_prefix_ignore_case<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+    let head = path.get(..prefix.len())?;
+
+    head.eq_ignore_ascii_case(prefix)
+        .then(|| &path[prefix.len()..])
+}
+
+fn read_error(path: &str, error: VfsReadError) -> io::Error {
+    match error {
+        VfsReadError::Open(VfsOpenError::NotFound) => io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{path} is not in the archives"),
+        ),
+        error => io::Error::other(format!("Could not read {path}: {error}")),
+    }
+}
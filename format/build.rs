@@ -1,8 +1,19 @@
 use std::env;
 
 fn main() {
+    // Only DCX needs Oodle, leave it out for consumers that don't decompress anything.
+    if env::var_os("CARGO_FEATURE_DCX").is_none() {
+        return;
+    }
+
     let project_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
 
     println!("cargo:rustc-link-search={}", project_dir); // the "-L" flag
     println!("cargo:rustc-link-lib=oo2corelinux64"); // the "-l" flag
+
+    // The static library's compressors are C++, so anything that recompresses a DCX needs the C++
+    // runtime as well.
+    if env::var("CARGO_CFG_TARGET_OS").as_deref() == Ok("linux") {
+        println!("cargo:rustc-link-lib=stdc++");
+    }
 }
@@ -0,0 +1,6 @@
+#![no_main]
+
+use format::fuzz::{check_bnd4, Bnd4Input};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: Bnd4Input| check_bnd4(&input.to_bytes()));
@@ -0,0 +1,6 @@
+#![no_main]
+
+use format::fuzz::{check_dcx, DcxInput};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: DcxInput| check_dcx(&input.to_bytes()));
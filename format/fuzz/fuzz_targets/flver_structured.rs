@@ -0,0 +1,6 @@
+#![no_main]
+
+use format::fuzz::{check_flver, FlverInput};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: FlverInput| check_flver(&input.to_bytes()));
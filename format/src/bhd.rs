@@ -7,6 +7,7 @@ use byteorder::{BigEndian, ByteOrder, LittleEndian, ReadBytesExt};
 use rayon::prelude::*;
 use rsa::{pkcs1::DecodeRsaPublicKey, traits::PublicKeyParts, RsaPublicKey};
 use rug::{integer::Order, Integer};
+use tracing::{debug, instrument};
 
 use crate::io_ext::ReadFormatsExt;
 
@@ -33,11 +34,36 @@ impl BhdKey {
     }
 }
 
+/// The revisions of the BHD5 layout, named for the first game to use them.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BhdFormat {
+    /// Dark Souls and its remaster. Headers are neither encrypted nor salted.
+    DarkSouls1,
+
+    /// Dark Souls 2, which adds encryption, a salt and per-file digests.
+    DarkSouls2,
+
+    /// Dark Souls 3 and Sekiro, which add the unpadded size of files.
+    DarkSouls3,
+
+    /// Elden Ring and Armored Core 6, whose path hashes are 64 bits wide.
+    EldenRing,
+}
+
+impl BhdFormat {
+    pub fn is_encrypted(self) -> bool {
+        self >= Self::DarkSouls2
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Bhd {
     pub toc: Vec<BhdTocEntry>,
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BhdTocEntry {
     pub hash: u64,
     pub padded_size: u32,
@@ -45,9 +71,23 @@ pub struct BhdTocEntry {
     pub offset: u64,
     pub aes_key: [u8; 16],
     pub encrypted_ranges: Vec<(i64, i64)>,
+
+    /// SHA-256 digest of the file as stored, if the archive has one for it.
+    pub digest: Option<BhdDigest>,
 }
 
+/// A SHA-256 digest of the ranges of a file, as stored in its BDT.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BhdDigest {
+    pub sha256: [u8; 32],
+
+    /// Offsets into the file of the hashed ranges, or `(-1, -1)` for unused ranges.
+    pub ranges: Vec<(i64, i64)>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BhdHeader {
     pub is_big_endian: bool,
     pub file_size: u32,
@@ -58,65 +98,95 @@ pub struct BhdHeader {
 }
 
 impl Bhd {
-    pub fn read<R: Read + Seek>(mut file: R, key: BhdKey) -> Result<Self, std::io::Error> {
-        let key_size = key.size;
-        let file_len = file.seek(SeekFrom::End(0))? as usize;
-        let decrypted_file_len = file_len - file_len / key_size;
-        file.seek(SeekFrom::Start(0))?;
-
-        let mut decrypted_data = vec![MaybeUninit::uninit(); decrypted_file_len];
-        let mut encrypted_data = Vec::with_capacity(file_len);
-        file.read_to_end(&mut encrypted_data)?;
-
-        let decrypted_len = encrypted_data
-            .par_chunks(key_size)
-            .zip(decrypted_data.par_chunks_mut(key_size - 1))
-            .map(|(encrypted_block, decrypted_block)| {
-                let mut decrypted = Integer::from_digits(encrypted_block, Order::Msf);
-                decrypted
-                    .pow_mod_mut(&key.exponent, &key.modulus)
-                    .expect("failed to decrypt");
-
-                let mut decrypted_with_padding = vec![MaybeUninit::<u8>::uninit(); key_size];
-                decrypted.write_digits(
-                    unsafe { transmute::<_, &mut [u8]>(&mut decrypted_with_padding[..]) },
-                    Order::Msf,
-                );
-                decrypted_block.copy_from_slice(&decrypted_with_padding[1..]);
-
-                Ok::<_, std::io::Error>(key_size)
-            })
-            .try_reduce(|| 0, |len, block_len| Ok(len + block_len))?;
+    /// Read an Elden Ring style header, see [`Bhd::read_format`].
+    pub fn read<R: Read + Seek>(file: R, key: BhdKey) -> Result<Self, std::io::Error> {
+        Self::read_format(file, Some(key), BhdFormat::EldenRing)
+    }
 
-        // SAFETY: all elements from [0,decrypted_len) have been initialized.
-        let decrypted_data: Vec<u8> = unsafe {
-            decrypted_data.set_len(decrypted_len);
-            transmute(decrypted_data)
+    /// Read a header, decrypting it with `key` when there is one.
+    #[instrument(name = "bhd", skip_all, fields(?format))]
+    pub fn read_format<R: Read + Seek>(
+        mut file: R,
+        key: Option<BhdKey>,
+        format: BhdFormat,
+    ) -> Result<Self, std::io::Error> {
+        let data = match key {
+            Some(key) => decrypt(&mut file, key)?,
+            None => {
+                let mut data = Vec::new();
+                file.read_to_end(&mut data)?;
+                data
+            }
         };
 
-        let mut reader = Cursor::new(&decrypted_data[..]);
-        let header = read_header(&mut reader)?;
+        let mut reader = Cursor::new(&data[..]);
+        let header = read_header(&mut reader, format)?;
 
         let toc = if header.is_big_endian {
-            read_toc::<_, BigEndian>(header.buckets as usize, reader)
+            read_toc::<_, BigEndian>(header.buckets as usize, reader, format)
         } else {
-            read_toc::<_, LittleEndian>(header.buckets as usize, reader)
+            read_toc::<_, LittleEndian>(header.buckets as usize, reader, format)
         }?;
+        debug!(size = data.len(), entries = toc.len(), "read");
 
         Ok(Bhd { toc })
     }
 }
 
+fn decrypt<R: Read + Seek>(file: &mut R, key: BhdKey) -> Result<Vec<u8>, std::io::Error> {
+    let key_size = key.size;
+    let file_len = file.seek(SeekFrom::End(0))? as usize;
+    let decrypted_file_len = file_len - file_len / key_size;
+    file.seek(SeekFrom::Start(0))?;
+
+    let mut decrypted_data = vec![MaybeUninit::uninit(); decrypted_file_len];
+    let mut encrypted_data = Vec::with_capacity(file_len);
+    file.read_to_end(&mut encrypted_data)?;
+
+    let decrypted_len = encrypted_data
+        .par_chunks(key_size)
+        .zip(decrypted_data.par_chunks_mut(key_size - 1))
+        .map(|(encrypted_block, decrypted_block)| {
+            let mut decrypted = Integer::from_digits(encrypted_block, Order::Msf);
+            decrypted
+                .pow_mod_mut(&key.exponent, &key.modulus)
+                .map_err(|_| std::io::Error::other("BHD key has no modular inverse"))?;
+
+            let mut decrypted_with_padding = vec![MaybeUninit::<u8>::uninit(); key_size];
+            decrypted.write_digits(
+                unsafe { transmute::<_, &mut [u8]>(&mut decrypted_with_padding[..]) },
+                Order::Msf,
+            );
+            decrypted_block.copy_from_slice(&decrypted_with_padding[1..]);
+
+            Ok::<_, std::io::Error>(key_size)
+        })
+        .try_reduce(|| 0, |len, block_len| Ok(len + block_len))?;
+
+    // SAFETY: all elements from [0,decrypted_len) have been initialized.
+    let decrypted_data: Vec<u8> = unsafe {
+        decrypted_data.set_len(decrypted_len);
+        transmute(decrypted_data)
+    };
+
+    Ok(decrypted_data)
+}
+
 pub fn read_header_data<R: Read, O: ByteOrder>(
     mut reader: R,
     is_big_endian: bool,
+    format: BhdFormat,
 ) -> Result<BhdHeader, std::io::Error> {
     reader.read_padding(7)?;
 
     let file_size = reader.read_u32::<O>()?;
     let toc_buckets = reader.read_i32::<O>()?;
     let toc_offset = reader.read_i32::<O>()?;
-    let salt_length = reader.read_u32::<O>()?;
+    let salt_length = if format >= BhdFormat::DarkSouls2 {
+        reader.read_u32::<O>()?
+    } else {
+        0
+    };
 
     let mut salt = vec![0u8; salt_length as usize];
     reader.read_exact(&mut salt)?;
@@ -131,20 +201,21 @@ pub fn read_header_data<R: Read, O: ByteOrder>(
     })
 }
 
-pub fn read_header<R: Read>(mut reader: R) -> Result<BhdHeader, std::io::Error> {
+pub fn read_header<R: Read>(mut reader: R, format: BhdFormat) -> Result<BhdHeader, std::io::Error> {
     reader.read_magic(b"BHD5")?;
 
     let endianness = reader.read_i8()?;
     if endianness == -1 {
-        read_header_data::<_, LittleEndian>(reader, false)
+        read_header_data::<_, LittleEndian>(reader, false, format)
     } else {
-        read_header_data::<_, BigEndian>(reader, true)
+        read_header_data::<_, BigEndian>(reader, true, format)
     }
 }
 
 pub fn read_toc<R: Read + Seek, O: ByteOrder>(
     buckets: usize,
     mut reader: R,
+    format: BhdFormat,
 ) -> Result<Vec<BhdTocEntry>, std::io::Error> {
     let mut entries = Vec::new();
 
@@ -157,13 +228,32 @@ pub fn read_toc<R: Read + Seek, O: ByteOrder>(
         reader.seek(SeekFrom::Start(entry_data_offset as u64))?;
 
         for _ in 0..entry_count {
-            let hash = reader.read_u64::<O>()?;
+            let hash = if format >= BhdFormat::EldenRing {
+                reader.read_u64::<O>()?
+            } else {
+                reader.read_u32::<O>()? as u64
+            };
             let padded_size = reader.read_u32::<O>()?;
-            let size = reader.read_u32::<O>()?;
+            let mut size = if format >= BhdFormat::EldenRing {
+                reader.read_u32::<O>()?
+            } else {
+                padded_size
+            };
             let offset = reader.read_u64::<O>()?;
 
-            let _digest_offset = reader.read_u64::<O>()?;
-            let encryption_offset = reader.read_u64::<O>()?;
+            let (digest_offset, encryption_offset) = if format >= BhdFormat::DarkSouls2 {
+                (reader.read_u64::<O>()?, reader.read_u64::<O>()?)
+            } else {
+                (0, 0)
+            };
+
+            // Zero when the file isn't compressed or encrypted, in which case it isn't padded.
+            if format == BhdFormat::DarkSouls3 {
+                size = match reader.read_u64::<O>()? {
+                    0 => padded_size,
+                    unpadded => unpadded as u32,
+                };
+            }
 
             let next_file_pos = reader.stream_position()?;
             let mut aes_key = [0u8; 16];
@@ -182,6 +272,21 @@ pub fn read_toc<R: Read + Seek, O: ByteOrder>(
                 }
             }
 
+            let mut digest = None;
+            if digest_offset != 0 {
+                reader.seek(SeekFrom::Start(digest_offset))?;
+
+                let mut sha256 = [0u8; 32];
+                reader.read_exact(&mut sha256)?;
+
+                let range_count = reader.read_u32::<O>()?;
+                let ranges = (0..range_count)
+                    .map(|_| Ok((reader.read_i64::<O>()?, reader.read_i64::<O>()?)))
+                    .collect::<Result<_, std::io::Error>>()?;
+
+                digest = Some(BhdDigest { sha256, ranges });
+            }
+
             reader.seek(SeekFrom::Start(next_file_pos))?;
 
             entries.push(BhdTocEntry {
@@ -191,6 +296,7 @@ pub fn read_toc<R: Read + Seek, O: ByteOrder>(
                 offset,
                 aes_key,
                 encrypted_ranges,
+                digest,
             })
         }
 
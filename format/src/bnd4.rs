@@ -1,12 +1,29 @@
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+#[cfg(feature = "std")]
 use std::io::{self, Read, Seek, SeekFrom};
 
-use byteorder::{ReadBytesExt, LE};
+use byteorder::LE;
+use tracing::{debug, instrument};
 
-use crate::io_ext::ReadFormatsExt;
+use crate::{diagnostics::Diagnostics, error::FormatError, io_ext::ByteReader};
 
+#[cfg(feature = "std")]
 type BND4Reader = std::io::Cursor<Vec<u8>>;
 
+/// Offsets of the fields in a file header that change when file data is moved.
+#[cfg(feature = "std")]
+const FILE_HEADER_COMPRESSED_SIZE: usize = 0x8;
+#[cfg(feature = "std")]
+const FILE_HEADER_UNCOMPRESSED_SIZE: usize = 0x10;
+#[cfg(feature = "std")]
+const FILE_HEADER_DATA_OFFSET: usize = 0x18;
+#[cfg(feature = "std")]
+const FILE_DATA_ALIGNMENT: usize = 0x10;
+
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BND4 {
     pub unk04: u8,
     pub unk05: u8,
@@ -21,21 +38,57 @@ pub struct BND4 {
     pub extended: u8,
     pub buckets_offset: u64,
     pub files: Vec<BND4Entry>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub data: Vec<u8>,
 }
 
 impl BND4 {
+    #[cfg(feature = "std")]
     pub fn from_reader(r: &mut BND4Reader) -> io::Result<Self> {
+        Self::from_reader_with(r, &mut Diagnostics::default())
+    }
+
+    /// Read a binder, recovering from non-zero padding, compressed entries, file counts that don't
+    /// fit in the binder and truncation when `diagnostics` is lenient.
+    #[cfg(feature = "std")]
+    pub fn from_reader_with(r: &mut BND4Reader, diagnostics: &mut Diagnostics) -> io::Result<Self> {
+        let bnd = Self::parse_with(r.get_ref().clone(), diagnostics)?;
+        r.seek(SeekFrom::End(0))?;
+
+        Ok(bnd)
+    }
+
+    pub fn parse(data: Vec<u8>) -> Result<Self, FormatError> {
+        Self::parse_with(data, &mut Diagnostics::default())
+    }
+
+    /// Parse a binder from its bytes, which works without `std`. See [`BND4::from_reader_with`]
+    /// for what is recovered from in lenient mode.
+    #[instrument(name = "bnd4", skip_all, fields(size = data.len()))]
+    pub fn parse_with(data: Vec<u8>, diagnostics: &mut Diagnostics) -> Result<Self, FormatError> {
+        let mut r = ByteReader::new("BND4", &data);
         r.read_magic(b"BND4")?;
 
         let unk04 = r.read_u8()?;
         let unk05 = r.read_u8()?;
-        r.read_padding(3)?;
+        read_padding::<3>(&mut r, diagnostics)?;
 
-        assert!(r.read_u8()? == 0x0, "BND4 is not little endian");
+        if r.read_u8()? != 0x0 {
+            let offset = r.position() - 1;
+            return Err(FormatError::malformed(
+                "BND4",
+                "header",
+                offset,
+                "not little endian",
+            ));
+        }
 
         let unk0a = r.read_u8()?;
-        r.read_padding(1)?;
+        read_padding::<1>(&mut r, diagnostics)?;
+        diagnostics.observe("BND4", "unk04", unk04);
+        diagnostics.observe("BND4", "unk05", unk05);
+        diagnostics.observe("BND4", "unk0a", unk0a);
+        let file_count_offset = r.position();
         let file_count = r.read_u32::<LE>()?;
 
         let file_headers_offset = r.read_u64::<LE>()?;
@@ -46,18 +99,55 @@ impl BND4 {
         let raw_format = r.read_u8()?;
         let extended = r.read_u8()?;
 
-        r.read_padding(5)?;
+        read_padding::<5>(&mut r, diagnostics)?;
 
         let buckets_offset = r.read_u64::<LE>()?;
 
-        let mut files = vec![];
+        let headers_length = data.len() as u64 - r.position();
+        let fitting_count = headers_length / file_header_size.max(1);
+        let file_count = if file_count as u64 > fitting_count {
+            diagnostics.report(FormatError::malformed(
+                "BND4",
+                "header",
+                file_count_offset,
+                format!(
+                    "{file_count} file headers don't fit in the binder, reading {fitting_count}"
+                ),
+            ))?;
+
+            fitting_count as u32
+        } else {
+            file_count
+        };
+
+        r.set_section("file header");
+        let mut files = Vec::new();
         for _ in 0..file_count {
-            files.push(BND4Entry::from_reader(r)?);
+            let offset = r.position();
+            match BND4Entry::parse_with(&mut r, diagnostics) {
+                Ok(file) => files.push(file),
+                Err(FormatError::Truncated { .. }) => {
+                    diagnostics.truncated("BND4", "file header", offset)?;
+                    break;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        // Files whose data was cut off are left out.
+        let length = data.len() as u64;
+        let mut complete = Vec::with_capacity(files.len());
+        for file in files {
+            let end = (file.data_offset as u64).checked_add(file.compressed_size);
+            if end.map_or(true, |end| end > length) {
+                diagnostics.truncated("BND4", "file data", file.data_offset as u64)?;
+            } else {
+                complete.push(file);
+            }
         }
+        let files = complete;
 
-        let mut data = vec![];
-        r.seek(SeekFrom::Start(0))?;
-        r.read_to_end(&mut data)?;
+        debug!(file_count, "read file headers");
 
         Ok(Self {
             unk04,
@@ -77,13 +167,79 @@ impl BND4 {
         })
     }
 
+    /// The data of a file, empty if `handle` points outside of the binder's data.
     pub fn file_bytes(&self, handle: &BND4Entry) -> &[u8] {
         let start = handle.data_offset as usize;
-        let end = start + handle.compressed_size as usize;
+        usize::try_from(handle.compressed_size)
+            .ok()
+            .and_then(|size| start.checked_add(size))
+            .and_then(|end| self.data.get(start..end))
+            .unwrap_or_default()
+    }
+
+    /// Replace the contents of the file at `index`, re-laying out the data of every file in the
+    /// binder.
+    #[cfg(feature = "std")]
+    pub fn replace_file(&mut self, index: usize, bytes: &[u8]) -> io::Result<()> {
+        if index >= self.files.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("BND4 has no file at index {index}"),
+            ));
+        }
+
+        if (self.file_header_size as usize) < FILE_HEADER_DATA_OFFSET + 4 {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                format!(
+                    "BND4 file headers of {:#x} bytes are not supported",
+                    self.file_header_size
+                ),
+            ));
+        }
+
+        let mut order = (0..self.files.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| self.files[i].data_offset);
 
-        &self.data[start..end]
+        let contents = self
+            .files
+            .iter()
+            .enumerate()
+            .map(|(i, file)| {
+                if i == index {
+                    bytes.to_vec()
+                } else {
+                    self.file_bytes(file).to_vec()
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let data_start = self.files[order[0]].data_offset as usize;
+        self.data.truncate(data_start);
+
+        for i in order {
+            let aligned = self.data.len().next_multiple_of(FILE_DATA_ALIGNMENT);
+            self.data.resize(aligned, 0);
+
+            let file = &mut self.files[i];
+            file.data_offset = aligned as u32;
+            file.compressed_size = contents[i].len() as u64;
+            file.uncompressed_size = contents[i].len() as u64;
+            self.data.extend_from_slice(&contents[i]);
+
+            let header = (self.file_headers_offset + i as u64 * self.file_header_size) as usize;
+            let header = &mut self.data[header..header + self.file_header_size as usize];
+            header[FILE_HEADER_COMPRESSED_SIZE..][..8]
+                .copy_from_slice(&file.compressed_size.to_le_bytes());
+            header[FILE_HEADER_UNCOMPRESSED_SIZE..][..8]
+                .copy_from_slice(&file.uncompressed_size.to_le_bytes());
+            header[FILE_HEADER_DATA_OFFSET..][..4].copy_from_slice(&file.data_offset.to_le_bytes());
+        }
+
+        Ok(())
     }
 
+    #[cfg(feature = "std")]
     pub fn file_descriptor_by_stem(&self, path: &str) -> Option<&BND4Entry> {
         let lookup = std::path::PathBuf::from(Self::normalize_path(path));
 
@@ -97,9 +253,25 @@ impl BND4 {
     pub fn normalize_path(path: &str) -> String {
         path.replace("N:\\", "").to_lowercase().replace('\\', "/")
     }
+
+    /// Hash a file name the way the binder's hash buckets index their files: trimmed, lowercased,
+    /// with forward slashes and a leading slash.
+    pub fn hash_name(name: &str) -> u32 {
+        let name = name.trim();
+        let prefix = (!name.starts_with(['/', '\\'])).then_some('/');
+
+        prefix
+            .into_iter()
+            .chain(name.chars().map(|ch| ch.to_ascii_lowercase()))
+            .map(|ch| if ch == '\\' { '/' } else { ch })
+            .fold(0u32, |hash, next| {
+                hash.wrapping_mul(37).wrapping_add(next as u32)
+            })
+    }
 }
 
 #[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct BND4Entry {
     pub flags: u8,
     pub unk4: i32,
@@ -111,26 +283,55 @@ pub struct BND4Entry {
 }
 
 impl BND4Entry {
+    #[cfg(feature = "std")]
     pub fn from_reader(r: &mut BND4Reader) -> Result<Self, io::Error> {
+        Self::from_reader_with(r, &mut Diagnostics::default())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_reader_with(
+        r: &mut BND4Reader,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self, io::Error> {
+        let mut bytes = ByteReader::new("BND4", r.get_ref());
+        bytes.set_section("file header");
+        bytes.set_position(r.position());
+        let entry = Self::parse_with(&mut bytes, diagnostics)?;
+        r.set_position(bytes.position());
+
+        Ok(entry)
+    }
+
+    pub fn parse_with(
+        r: &mut ByteReader<'_>,
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self, FormatError> {
+        let offset = r.position();
         let flags = r.read_u8()?;
-        r.read_padding(3)?;
+        read_padding::<3>(r, diagnostics)?;
 
         let unk4 = r.read_i32::<LE>()?;
+        diagnostics.observe("BND4", "file unk4", unk4);
         let compressed_size = r.read_u64::<LE>()?;
         let uncompressed_size = r.read_u64::<LE>()?;
         let data_offset = r.read_u32::<LE>()?;
         let id = r.read_u32::<LE>()?;
         let name_offset = r.read_u32::<LE>()?;
 
-        let current = r.stream_position()?;
-        r.seek(SeekFrom::Start(name_offset as u64))?;
+        let current = r.position();
+        r.set_position(name_offset as u64);
         let path = r.read_utf16::<LE>()?;
-        r.seek(SeekFrom::Start(current))?;
+        r.set_position(current);
 
-        assert!(
-            compressed_size == uncompressed_size,
-            "BND4 entry compression detected"
-        );
+        // Leniently read, the compressed bytes are exposed as is.
+        if compressed_size != uncompressed_size {
+            diagnostics.report(FormatError::malformed(
+                "BND4",
+                "file header",
+                offset,
+                format!("compressed entry {path}, which is unsupported"),
+            ))?;
+        }
 
         Ok(Self {
             flags,
@@ -143,6 +344,7 @@ impl BND4Entry {
         })
     }
 
+    #[cfg(feature = "std")]
     pub fn bytes(&self, r: &mut BND4Reader) -> Result<Vec<u8>, io::Error> {
         let mut buffer = vec![0x0u8; self.compressed_size as usize];
         r.seek(SeekFrom::Start(self.data_offset as u64))?;
@@ -151,3 +353,13 @@ impl BND4Entry {
         Ok(buffer)
     }
 }
+
+fn read_padding<const N: usize>(
+    r: &mut ByteReader<'_>,
+    diagnostics: &mut Diagnostics,
+) -> Result<(), FormatError> {
+    let offset = r.position();
+    let padding = r.read_array::<N>()?;
+
+    diagnostics.check_padding("BND4", r.section(), offset, &padding)
+}
@@ -0,0 +1,166 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use byteorder::{ByteOrder, ReadBytesExt, LE};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum BtabError {
+    #[error("Could not read BTAB: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unknown BTAB version {0}")]
+    UnknownVersion(u32),
+
+    #[error("Unknown BTAB entry size {0:#x}")]
+    UnknownEntrySize(u32),
+}
+
+const BTAB_VERSION: u32 = 1;
+const HEADER_SIZE: u64 = 0x28;
+
+/// Entry sizes with 32-bit (Dark Souls 3) and 64-bit (Sekiro and Elden Ring) name offsets.
+const ENTRY_SIZE_SHORT: u32 = 0x1C;
+const ENTRY_SIZE_LONG: u32 = 0x28;
+
+/// A map's lightmap atlas bindings (`mXX_XX_XX_XX.btab`), mapping each map piece material to the
+/// region of a lightmap atlas page its baked lighting is stored in.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Btab {
+    pub entries: Vec<BtabEntry>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BtabEntry {
+    /// Name of the MSB part, e.g. `m4000B0_0000`.
+    pub part_name: String,
+
+    /// Name of the FLVER material within the part's model.
+    pub material_name: String,
+
+    /// Index of the lightmap atlas page, as in the `_lit_XXXX` texture name suffix.
+    pub atlas_id: i32,
+
+    /// Transform from the mesh's lightmap UVs into the atlas page, `uv * scale + offset`.
+    pub atlas_offset: [f32; 2],
+    pub atlas_scale: [f32; 2],
+}
+
+impl Btab {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, BtabError> {
+        let version = r.read_u32::<LE>()?;
+        if version != BTAB_VERSION {
+            return Err(BtabError::UnknownVersion(version));
+        }
+
+        let _unk04 = r.read_u32::<LE>()?;
+        let entry_count = r.read_u32::<LE>()?;
+        let strings_length = r.read_u32::<LE>()?;
+        let _unk10 = r.read_u32::<LE>()?;
+        let entry_size = r.read_u32::<LE>()?;
+        if entry_size != ENTRY_SIZE_SHORT && entry_size != ENTRY_SIZE_LONG {
+            return Err(BtabError::UnknownEntrySize(entry_size));
+        }
+
+        r.seek(SeekFrom::Start(HEADER_SIZE))?;
+        let mut strings = vec![0u8; strings_length as usize];
+        r.read_exact(&mut strings)?;
+
+        let mut entries = Vec::with_capacity(entry_count as usize);
+        for _ in 0..entry_count {
+            let mut entry = vec![0u8; entry_size as usize];
+            r.read_exact(&mut entry)?;
+
+            let (name_offsets, rest) = if entry_size == ENTRY_SIZE_LONG {
+                let offsets = [LE::read_u64(&entry), LE::read_u64(&entry[8..])];
+                (offsets, &entry[0x10..])
+            } else {
+                let offsets = [
+                    LE::read_u32(&entry) as u64,
+                    LE::read_u32(&entry[4..]) as u64,
+                ];
+                (offsets, &entry[0x8..])
+            };
+
+            let [part_name, material_name] =
+                name_offsets.map(|offset| utf16_at(&strings, offset as usize));
+            let float = |index: usize| LE::read_f32(&rest[4 + index * 4..]);
+
+            entries.push(BtabEntry {
+                part_name,
+                material_name,
+                atlas_id: LE::read_i32(rest),
+                atlas_offset: [float(0), float(1)],
+                atlas_scale: [float(2), float(3)],
+            });
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Find the atlas binding of a part's material.
+    pub fn entry(&self, part_name: &str, material_name: &str) -> Option<&BtabEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.part_name == part_name && entry.material_name == material_name)
+    }
+}
+
+impl BtabEntry {
+    /// Map a lightmap UV of the mesh into the atlas page.
+    pub fn atlas_uv(&self, uv: [f32; 2]) -> [f32; 2] {
+        [
+            uv[0] * self.atlas_scale[0] + self.atlas_offset[0],
+            uv[1] * self.atlas_scale[1] + self.atlas_offset[1],
+        ]
+    }
+}
+
+fn utf16_at(strings: &[u8], offset: usize) -> String {
+    let units = strings
+        .get(offset..)
+        .unwrap_or_default()
+        .chunks_exact(2)
+        .map(LE::read_u16)
+        .take_while(|unit| *unit != 0)
+        .collect::<Vec<_>>();
+
+    String::from_utf16_lossy(&units)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::btab::Btab;
+
+    #[test]
+    pub fn reads_long_entries() {
+        let strings = "m4000B0_0000\0Stone\0"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+
+        let mut bytes = Vec::new();
+        for value in [1u32, 0, 1, strings.len() as u32, 0, 0x28] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.resize(0x28, 0);
+        bytes.extend(&strings);
+
+        bytes.extend(0u64.to_le_bytes());
+        bytes.extend(26u64.to_le_bytes());
+        bytes.extend(3i32.to_le_bytes());
+        for value in [0.5f32, 0.25, 0.5, 0.5] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(0u32.to_le_bytes());
+
+        let btab = Btab::from_reader(&mut Cursor::new(bytes)).unwrap();
+        let entry = btab.entry("m4000B0_0000", "Stone").unwrap();
+
+        assert_eq!(entry.atlas_id, 3);
+        assert_eq!(entry.atlas_uv([1.0, 1.0]), [1.0, 0.75]);
+    }
+}
@@ -0,0 +1,365 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use byteorder::{ByteOrder, ReadBytesExt, LE};
+use thiserror::Error;
+
+use crate::io_ext::ReadFormatsExt;
+
+#[derive(Debug, Error)]
+pub enum BtlError {
+    #[error("Could not read BTL: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unknown BTL version {0}")]
+    UnknownVersion(u32),
+
+    #[error("Light {index} has an unknown type {value}")]
+    UnknownLightType { index: usize, value: u32 },
+}
+
+const BTL_MAGIC: u32 = 2;
+const HEADER_SIZE: u64 = 0x40;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BtlLightType {
+    Point,
+    Spot,
+    Directional,
+}
+
+impl BtlLightType {
+    fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Point),
+            1 => Some(Self::Spot),
+            2 => Some(Self::Directional),
+            _ => None,
+        }
+    }
+
+    fn as_u32(self) -> u32 {
+        match self {
+            Self::Point => 0,
+            Self::Spot => 1,
+            Self::Directional => 2,
+        }
+    }
+}
+
+/// Field offsets of a light record, which move with the width of the name offset and grow with
+/// each version.
+struct LightLayout {
+    size: usize,
+    name: usize,
+    long_offsets: bool,
+
+    /// Offset of the light type, fields from here on follow one another in every version.
+    fields: usize,
+}
+
+impl LightLayout {
+    fn for_version(version: u32) -> Option<Self> {
+        let (size, long_offsets) = match version {
+            1 | 2 | 5 => (0xC0, false),
+            6 => (0xC8, false),
+            16 => (0xC8, true),
+            18 => (0xE8, true),
+            _ => return None,
+        };
+
+        Some(Self {
+            size,
+            name: 0x10,
+            long_offsets,
+            fields: if long_offsets { 0x18 } else { 0x14 },
+        })
+    }
+
+    fn light_type(&self) -> usize {
+        self.fields
+    }
+
+    fn diffuse_color(&self) -> usize {
+        self.fields + 0x8
+    }
+
+    fn diffuse_power(&self) -> usize {
+        self.fields + 0xC
+    }
+
+    fn specular_color(&self) -> usize {
+        self.fields + 0x10
+    }
+
+    fn cast_shadows(&self) -> usize {
+        self.fields + 0x14
+    }
+
+    fn specular_power(&self) -> usize {
+        self.fields + 0x18
+    }
+
+    fn cone_angle(&self) -> usize {
+        self.fields + 0x1C
+    }
+
+    fn position(&self) -> usize {
+        self.fields + 0x28
+    }
+
+    fn rotation(&self) -> usize {
+        self.fields + 0x34
+    }
+
+    fn radius(&self) -> usize {
+        self.fields + 0x48
+    }
+}
+
+/// A map light file (`mXX_XX_XX_XX_XXXX.btl`), holding the point, spot and directional lights of
+/// an area.
+///
+/// The commonly edited fields of each light are decoded, and the rest of its record is kept as is
+/// so that lights round-trip without loss.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Btl {
+    pub version: u32,
+    pub lights: Vec<BtlLight>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BtlLight {
+    pub name: String,
+    pub light_type: BtlLightType,
+
+    /// RGBA colors.
+    pub diffuse_color: [u8; 4],
+    pub diffuse_power: f32,
+    pub specular_color: [u8; 4],
+    pub specular_power: f32,
+    pub cast_shadows: bool,
+
+    /// Angle of a spot light's cone, in degrees.
+    pub cone_angle: f32,
+    pub position: [f32; 3],
+
+    /// Euler angles in degrees.
+    pub rotation: [f32; 3],
+    pub radius: f32,
+
+    /// The full record, which fields not decoded above are written back from.
+    pub raw: Vec<u8>,
+}
+
+impl Btl {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, BtlError> {
+        let magic = r.read_u32::<LE>()?;
+        if magic != BTL_MAGIC {
+            return Err(BtlError::UnknownVersion(magic));
+        }
+
+        let version = r.read_u32::<LE>()?;
+        let layout = LightLayout::for_version(version).ok_or(BtlError::UnknownVersion(version))?;
+        let light_count = r.read_u32::<LE>()?;
+        let names_length = r.read_u32::<LE>()?;
+        r.read_padding(4)?;
+        let _light_size = r.read_u32::<LE>()?;
+
+        r.seek(SeekFrom::Start(HEADER_SIZE))?;
+        let mut names = vec![0u8; names_length as usize];
+        r.read_exact(&mut names)?;
+
+        let mut lights = Vec::with_capacity(light_count as usize);
+        for index in 0..light_count as usize {
+            let mut raw = vec![0u8; layout.size];
+            r.read_exact(&mut raw)?;
+
+            lights.push(BtlLight::from_raw(raw, &names, &layout, index)?);
+        }
+
+        Ok(Self { version, lights })
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> Result<(), BtlError> {
+        let layout =
+            LightLayout::for_version(self.version).ok_or(BtlError::UnknownVersion(self.version))?;
+
+        let mut names = Vec::new();
+        let mut name_offsets = Vec::with_capacity(self.lights.len());
+        for light in &self.lights {
+            name_offsets.push(names.len() as u64);
+            names.extend(
+                light
+                    .name
+                    .encode_utf16()
+                    .chain([0])
+                    .flat_map(u16::to_le_bytes),
+            );
+        }
+        names.resize(names.len().next_multiple_of(0x10), 0);
+
+        let mut header = [0u8; HEADER_SIZE as usize];
+        LE::write_u32(&mut header[0x0..], BTL_MAGIC);
+        LE::write_u32(&mut header[0x4..], self.version);
+        LE::write_u32(&mut header[0x8..], self.lights.len() as u32);
+        LE::write_u32(&mut header[0xC..], names.len() as u32);
+        LE::write_u32(&mut header[0x14..], layout.size as u32);
+
+        w.write_all(&header)?;
+        w.write_all(&names)?;
+        for (light, name_offset) in self.lights.iter().zip(name_offsets) {
+            w.write_all(&light.to_raw(name_offset, &layout))?;
+        }
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, BtlError> {
+        let mut out = Vec::new();
+        self.write(&mut out)?;
+
+        Ok(out)
+    }
+
+    pub fn light(&self, name: &str) -> Option<&BtlLight> {
+        self.lights.iter().find(|light| light.name == name)
+    }
+
+    pub fn light_mut(&mut self, name: &str) -> Option<&mut BtlLight> {
+        self.lights.iter_mut().find(|light| light.name == name)
+    }
+}
+
+impl BtlLight {
+    fn from_raw(
+        raw: Vec<u8>,
+        names: &[u8],
+        layout: &LightLayout,
+        index: usize,
+    ) -> Result<Self, BtlError> {
+        let name_offset = if layout.long_offsets {
+            LE::read_u64(&raw[layout.name..]) as usize
+        } else {
+            LE::read_u32(&raw[layout.name..]) as usize
+        };
+        let name = names
+            .get(name_offset..)
+            .unwrap_or_default()
+            .chunks_exact(2)
+            .map(LE::read_u16)
+            .take_while(|unit| *unit != 0)
+            .collect::<Vec<_>>();
+
+        let type_value = LE::read_u32(&raw[layout.light_type()..]);
+        let light_type = BtlLightType::from_u32(type_value).ok_or(BtlError::UnknownLightType {
+            index,
+            value: type_value,
+        })?;
+
+        let float = |offset: usize| LE::read_f32(&raw[offset..]);
+        let vector = |offset: usize| [float(offset), float(offset + 4), float(offset + 8)];
+        let color = |offset: usize| {
+            [
+                raw[offset],
+                raw[offset + 1],
+                raw[offset + 2],
+                raw[offset + 3],
+            ]
+        };
+
+        Ok(Self {
+            name: String::from_utf16_lossy(&name),
+            light_type,
+            diffuse_color: color(layout.diffuse_color()),
+            diffuse_power: float(layout.diffuse_power()),
+            specular_color: color(layout.specular_color()),
+            specular_power: float(layout.specular_power()),
+            cast_shadows: raw[layout.cast_shadows()] != 0,
+            cone_angle: float(layout.cone_angle()),
+            position: vector(layout.position()),
+            rotation: vector(layout.rotation()),
+            radius: float(layout.radius()),
+            raw,
+        })
+    }
+
+    fn to_raw(&self, name_offset: u64, layout: &LightLayout) -> Vec<u8> {
+        let mut raw = self.raw.clone();
+        raw.resize(layout.size, 0);
+
+        if layout.long_offsets {
+            LE::write_u64(&mut raw[layout.name..], name_offset);
+        } else {
+            LE::write_u32(&mut raw[layout.name..], name_offset as u32);
+        }
+
+        LE::write_u32(&mut raw[layout.light_type()..], self.light_type.as_u32());
+        raw[layout.diffuse_color()..][..4].copy_from_slice(&self.diffuse_color);
+        LE::write_f32(&mut raw[layout.diffuse_power()..], self.diffuse_power);
+        raw[layout.specular_color()..][..4].copy_from_slice(&self.specular_color);
+        LE::write_f32(&mut raw[layout.specular_power()..], self.specular_power);
+        raw[layout.cast_shadows()] = self.cast_shadows as u8;
+        LE::write_f32(&mut raw[layout.cone_angle()..], self.cone_angle);
+        LE::write_f32(&mut raw[layout.radius()..], self.radius);
+
+        for (offset, vector) in [
+            (layout.position(), self.position),
+            (layout.rotation(), self.rotation),
+        ] {
+            for (i, value) in vector.iter().enumerate() {
+                LE::write_f32(&mut raw[offset + i * 4..], *value);
+            }
+        }
+
+        raw
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::btl::{Btl, BtlLight, BtlLightType};
+
+    #[test]
+    pub fn round_trips_each_layout() {
+        for (version, size) in [(5, 0xC0), (16, 0xC8), (18, 0xE8)] {
+            let mut raw = vec![0u8; size];
+            raw[size - 1] = 0xAB;
+
+            let btl = Btl {
+                version,
+                lights: vec![BtlLight {
+                    name: "Light_0001".to_string(),
+                    light_type: BtlLightType::Spot,
+                    diffuse_color: [255, 128, 64, 255],
+                    diffuse_power: 2.0,
+                    specular_color: [255, 255, 255, 255],
+                    specular_power: 1.5,
+                    cast_shadows: true,
+                    cone_angle: 45.0,
+                    position: [1.0, 2.0, 3.0],
+                    rotation: [0.0, 90.0, 0.0],
+                    radius: 10.0,
+                    raw,
+                }],
+            };
+
+            let bytes = btl.to_bytes().unwrap();
+            let mut read = Btl::from_reader(&mut Cursor::new(&bytes)).unwrap();
+
+            read.light_mut("Light_0001").unwrap().radius = 20.0;
+            let light = read.light("Light_0001").unwrap();
+            assert_eq!(light.position, [1.0, 2.0, 3.0]);
+            assert_eq!(light.light_type, BtlLightType::Spot);
+            assert_eq!(light.raw[size - 1], 0xAB);
+
+            let reread = Btl::from_reader(&mut Cursor::new(read.to_bytes().unwrap())).unwrap();
+            assert_eq!(reread.lights[0].radius, 20.0);
+            assert!(reread.lights[0].cast_shadows);
+        }
+    }
+}
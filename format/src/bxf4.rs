@@ -0,0 +1,56 @@
+use std::io::{self, Cursor};
+
+use tracing::instrument;
+
+use crate::bnd4::{BND4Entry, BND4};
+
+/// A split binder: a `BHF4` header (e.g. a `.hkxbhd` or `.chrtpfbhd`) describing files whose data
+/// is stored in a separate `BDT`.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BXF4 {
+    pub files: Vec<BND4Entry>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    data: Vec<u8>,
+}
+
+impl BXF4 {
+    #[instrument(name = "bxf4", skip_all, fields(bdt_size = bdt.len()))]
+    pub fn from_bytes(bhd: &[u8], bdt: Vec<u8>) -> io::Result<Self> {
+        if bhd.get(..4) != Some(b"BHF4") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "split binder header is not a BHF4",
+            ));
+        }
+
+        // The header is laid out like a BND4 whose file offsets point into the BDT instead.
+        let mut header = b"BND4".to_vec();
+        header.extend(&bhd[4..]);
+        let files = BND4::from_reader(&mut Cursor::new(header))?.files;
+
+        if let Some(file) = files
+            .iter()
+            .find(|file| file.data_offset as u64 + file.compressed_size > bdt.len() as u64)
+        {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!("{} lies outside of its BDT", file.path),
+            ));
+        }
+
+        Ok(Self { files, data: bdt })
+    }
+
+    pub fn file_bytes(&self, handle: &BND4Entry) -> &[u8] {
+        let start = handle.data_offset as usize;
+        let end = start + handle.compressed_size as usize;
+
+        &self.data[start..end]
+    }
+
+    /// The BDT the files' offsets point into.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
@@ -0,0 +1,28 @@
+use crate::hkx::{cloth::SimCloth, Hkx, HkxError};
+
+/// A character cloth file (`cXXXX.clm2`), which marks the parts of a model that are driven by
+/// cloth physics.
+///
+/// The simulation itself is Havok cloth data, so this only identifies the simulated cloths and
+/// their particles and constraints. Cloth stored in packfiles by earlier games is not supported.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Clm2 {
+    pub cloths: Vec<SimCloth>,
+}
+
+impl Clm2 {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HkxError> {
+        match Hkx::from_bytes(bytes)? {
+            Hkx::Tagfile(tagfile) => Ok(Self {
+                cloths: SimCloth::from_tagfile(&tagfile)?,
+            }),
+            Hkx::Packfile(_) => Err(HkxError::NotTagfile),
+        }
+    }
+
+    /// Find a simulated cloth by name.
+    pub fn cloth(&self, name: &str) -> Option<&SimCloth> {
+        self.cloths.iter().find(|cloth| cloth.name == name)
+    }
+}
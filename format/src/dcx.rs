@@ -1,7 +1,11 @@
-use std::{io, mem};
+use std::{
+    io::{self, Read},
+    mem,
+};
 
-use byteorder::{ReadBytesExt, BE};
+use byteorder::{ReadBytesExt, WriteBytesExt, BE};
 use thiserror::Error;
+use tracing::{debug, instrument};
 
 use crate::io_ext::ReadFormatsExt;
 
@@ -12,9 +16,16 @@ pub enum DCXError {
 
     #[error("Got error from oodle decompression: {0}")]
     Decompress(u32),
+
+    #[error("Got error from oodle compression: {0}")]
+    Compress(u32),
+
+    #[error("Unsupported DCX compression {0:#x}, only KRAKEN is supported")]
+    UnsupportedFormat(u32),
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct DCX {
     pub unk04: u32,
     pub dcs_offset: u32,
@@ -37,10 +48,12 @@ pub struct DCX {
     pub unk40: u32,
     pub dca: u32,
     pub dca_size: u32,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub decompressed: Vec<u8>,
 }
 
 impl DCX {
+    #[instrument(name = "dcx", skip_all)]
     pub fn from_reader(r: &mut impl io::Read) -> Result<Self, DCXError> {
         r.read_magic(b"DCX\0")?;
 
@@ -54,7 +67,9 @@ impl DCX {
         let compressed_size = r.read_u32::<BE>()?;
         let dcp = r.read_u32::<BE>()?;
         let format = r.read_u32::<BE>()?;
-        assert!(format == 0x4b52414b, "Format was not KRAKEN");
+        if format != 0x4b52414b {
+            return Err(DCXError::UnsupportedFormat(format));
+        }
 
         let unk2c = r.read_u32::<BE>()?;
         let compression_level = r.read_u8()?;
@@ -68,9 +83,15 @@ impl DCX {
         let dca = r.read_u32::<BE>()?;
         let dca_size = r.read_u32::<BE>()?;
 
-        let mut compressed = vec![0x0u8; compressed_size as usize];
-        r.read_exact(&mut compressed)?;
+        // Read through `take` so that sizes claimed by corrupt headers aren't allocated up front.
+        let mut compressed = Vec::new();
+        r.take(compressed_size as u64)
+            .read_to_end(&mut compressed)?;
+        if compressed.len() != compressed_size as usize {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
 
+        debug!(compressed_size, uncompressed_size, "decompressing");
         let mut decompressed = vec![0x0u8; uncompressed_size as usize];
 
         oodle_safe::decompress(&compressed, &mut decompressed, None, None, None, None)
@@ -102,6 +123,51 @@ impl DCX {
         })
     }
 
+    /// Recompress [DCX::decompressed] with the compression level of this DCX and write it out.
+    pub fn write(&self, w: &mut impl io::Write) -> Result<(), DCXError> {
+        // Worst case expansion of an incompressible buffer, per
+        // `OodleLZ_GetCompressedBufferSizeNeeded`.
+        let bound = self.decompressed.len() + 274 * (self.decompressed.len() / 0x40000 + 1);
+        let mut compressed = vec![0x0u8; bound];
+
+        let compressed_size = oodle_safe::compress(
+            oodle_safe::Compressor::Kraken,
+            &self.decompressed,
+            &mut compressed,
+            compression_level(self.compression_level),
+            None,
+            None,
+            None,
+        )
+        .map_err(DCXError::Compress)?;
+
+        w.write_all(b"DCX\0")?;
+        w.write_u32::<BE>(self.unk04)?;
+        w.write_u32::<BE>(self.dcs_offset)?;
+        w.write_u32::<BE>(self.dcp_offset)?;
+        w.write_u32::<BE>(self.unk10)?;
+        w.write_u32::<BE>(self.unk14)?;
+        w.write_u32::<BE>(self.dcs)?;
+        w.write_u32::<BE>(self.decompressed.len() as u32)?;
+        w.write_u32::<BE>(compressed_size as u32)?;
+        w.write_u32::<BE>(self.dcp)?;
+        w.write_u32::<BE>(self.format)?;
+        w.write_u32::<BE>(self.unk2c)?;
+        w.write_u8(self.compression_level)?;
+        w.write_u8(self.unk31)?;
+        w.write_u8(self.unk32)?;
+        w.write_u8(self.unk33)?;
+        w.write_u32::<BE>(self.unk34)?;
+        w.write_u32::<BE>(self.unk38)?;
+        w.write_u32::<BE>(self.unk3c)?;
+        w.write_u32::<BE>(self.unk40)?;
+        w.write_u32::<BE>(self.dca)?;
+        w.write_u32::<BE>(self.dca_size)?;
+        w.write_all(&compressed[..compressed_size])?;
+
+        Ok(())
+    }
+
     pub fn has_magic(r: &mut (impl io::Read + io::Seek)) -> Result<bool, io::Error> {
         // Read magic and check if it's DCX
         let result = r.read_u32::<BE>()? == 0x44435800;
@@ -112,3 +178,20 @@ impl DCX {
         Ok(result)
     }
 }
+
+fn compression_level(level: u8) -> oodle_safe::CompressionLevel {
+    use oodle_safe::CompressionLevel;
+
+    match level {
+        0 => CompressionLevel::None,
+        1 => CompressionLevel::SuperFast,
+        2 => CompressionLevel::VeryFast,
+        3 => CompressionLevel::Fast,
+        4 => CompressionLevel::Normal,
+        5 => CompressionLevel::Optimal1,
+        7 => CompressionLevel::Optimal3,
+        8 => CompressionLevel::Optimal4,
+        9 => CompressionLevel::Optimal5,
+        _ => CompressionLevel::Optimal2,
+    }
+}
@@ -0,0 +1,172 @@
+use std::io::{self, Read, Write};
+
+use byteorder::{ByteOrder, LE};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum DesignError {
+    #[error("Could not read design: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Design is {0:#x} bytes, expected at least {DESIGN_SIZE:#x}")]
+    TooShort(usize),
+
+    #[error("Design name {0} is too long")]
+    NameTooLong(String),
+}
+
+/// Part IDs are stored first, one per [`AcPartSlot`], followed by the names.
+const PARTS_SIZE: usize = AcPartSlot::ALL.len() * 4;
+const NAME_LENGTH: usize = 0x18;
+const DESIGN_NAME: usize = PARTS_SIZE;
+const AC_NAME: usize = DESIGN_NAME + NAME_LENGTH * 2;
+const DESIGN_SIZE: usize = AC_NAME + NAME_LENGTH * 2;
+
+/// ID stored in a slot with no part equipped, e.g. an empty back unit.
+pub const NO_PART: i32 = -1;
+
+/// The slots of an Armored Core 6 assembly, in the order their parts are stored.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum AcPartSlot {
+    RightArmUnit,
+    LeftArmUnit,
+    RightBackUnit,
+    LeftBackUnit,
+    Head,
+    Core,
+    Arms,
+    Legs,
+    Booster,
+    Fcs,
+    Generator,
+    Expansion,
+}
+
+impl AcPartSlot {
+    pub const ALL: [Self; 12] = [
+        Self::RightArmUnit,
+        Self::LeftArmUnit,
+        Self::RightBackUnit,
+        Self::LeftBackUnit,
+        Self::Head,
+        Self::Core,
+        Self::Arms,
+        Self::Legs,
+        Self::Booster,
+        Self::Fcs,
+        Self::Generator,
+        Self::Expansion,
+    ];
+}
+
+/// An Armored Core 6 AC design, the assembly of parts making up a build.
+///
+/// Part IDs refer to rows of `EquipParamWeapon` for units and `EquipParamProtector`,
+/// `EquipParamBooster`, `EquipParamFcs` and `EquipParamGenerator` for the frame and inner parts.
+/// Anything following the parts and names, such as paint and decals, is kept as is so that
+/// designs can be written back unchanged.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Design {
+    pub name: String,
+
+    /// Name of the AC itself, as shown to other players.
+    pub ac_name: String,
+    pub parts: [i32; 12],
+    pub remainder: Vec<u8>,
+}
+
+impl Design {
+    pub fn from_reader(r: &mut impl Read) -> Result<Self, DesignError> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+        if bytes.len() < DESIGN_SIZE {
+            return Err(DesignError::TooShort(bytes.len()));
+        }
+
+        let mut parts = [0; 12];
+        LE::read_i32_into(&bytes[..PARTS_SIZE], &mut parts);
+
+        Ok(Self {
+            name: read_name(&bytes[DESIGN_NAME..AC_NAME]),
+            ac_name: read_name(&bytes[AC_NAME..DESIGN_SIZE]),
+            parts,
+            remainder: bytes[DESIGN_SIZE..].to_vec(),
+        })
+    }
+
+    pub fn part(&self, slot: AcPartSlot) -> Option<i32> {
+        let id = self.parts[slot as usize];
+
+        (id != NO_PART).then_some(id)
+    }
+
+    pub fn set_part(&mut self, slot: AcPartSlot, id: Option<i32>) {
+        self.parts[slot as usize] = id.unwrap_or(NO_PART);
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> Result<(), DesignError> {
+        let mut out = vec![0u8; DESIGN_SIZE];
+        LE::write_i32_into(&self.parts, &mut out[..PARTS_SIZE]);
+        write_name(&self.name, &mut out[DESIGN_NAME..AC_NAME])?;
+        write_name(&self.ac_name, &mut out[AC_NAME..DESIGN_SIZE])?;
+        out.extend(&self.remainder);
+
+        Ok(w.write_all(&out)?)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, DesignError> {
+        let mut out = Vec::new();
+        self.write(&mut out)?;
+
+        Ok(out)
+    }
+}
+
+fn read_name(bytes: &[u8]) -> String {
+    let name = bytes
+        .chunks_exact(2)
+        .map(LE::read_u16)
+        .take_while(|c| *c != 0)
+        .collect::<Vec<_>>();
+
+    String::from_utf16_lossy(&name)
+}
+
+fn write_name(name: &str, out: &mut [u8]) -> Result<(), DesignError> {
+    let encoded = name.encode_utf16().collect::<Vec<_>>();
+
+    // Keep room for the terminator.
+    if encoded.len() >= NAME_LENGTH {
+        return Err(DesignError::NameTooLong(name.to_string()));
+    }
+
+    LE::write_u16_into(&encoded, &mut out[..encoded.len() * 2]);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::design::{AcPartSlot, Design, NO_PART};
+
+    #[test]
+    pub fn round_trips_parts() {
+        let mut design = Design {
+            name: "Rusty".to_string(),
+            ac_name: "STEEL HAZE".to_string(),
+            parts: [NO_PART; 12],
+            remainder: vec![1, 2, 3],
+        };
+        design.set_part(AcPartSlot::Head, Some(2010000));
+        design.set_part(AcPartSlot::Booster, Some(3060000));
+
+        let read = Design::from_reader(&mut Cursor::new(design.to_bytes().unwrap())).unwrap();
+        assert_eq!(read, design);
+        assert_eq!(read.part(AcPartSlot::Head), Some(2010000));
+        assert_eq!(read.part(AcPartSlot::LeftBackUnit), None);
+    }
+}
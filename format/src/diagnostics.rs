@@ -0,0 +1,219 @@
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use tracing::warn;
+
+use crate::{
+    error::FormatError,
+    telemetry::{UnknownFields, UnknownValue},
+};
+
+/// How parsers react to irregularities they know how to recover from, such as non-zero padding,
+/// unknown enum values, counts that don't fit in the file or files that were cut short.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum ParseMode {
+    /// Reject the file on the first irregularity.
+    #[default]
+    Strict,
+
+    /// Recover and record a warning, so that slightly modded or hacked files still load.
+    Lenient,
+}
+
+/// Irregularities found while parsing a file, see [`ParseMode`], and optionally the values of its
+/// unknown fields.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    mode: ParseMode,
+    warnings: Vec<FormatError>,
+    unknown_fields: Option<UnknownFields>,
+}
+
+impl Diagnostics {
+    pub fn new(mode: ParseMode) -> Self {
+        Self {
+            mode,
+            warnings: Vec::new(),
+            unknown_fields: None,
+        }
+    }
+
+    pub fn lenient() -> Self {
+        Self::new(ParseMode::Lenient)
+    }
+
+    pub fn mode(&self) -> ParseMode {
+        self.mode
+    }
+
+    /// Report a recoverable irregularity, which fails parsing in strict mode. The parser goes on
+    /// with its fallback when this returns `Ok`.
+    pub fn report(&mut self, error: FormatError) -> Result<(), FormatError> {
+        match self.mode {
+            ParseMode::Strict => Err(error),
+            ParseMode::Lenient => {
+                warn!(%error, "recovered from malformed data");
+                self.warnings.push(error);
+                Ok(())
+            }
+        }
+    }
+
+    /// Check that padding read at `offset` is zeroed.
+    pub fn check_padding(
+        &mut self,
+        format: &'static str,
+        section: &'static str,
+        offset: u64,
+        padding: &[u8],
+    ) -> Result<(), FormatError> {
+        if padding.iter().all(|byte| *byte == 0) {
+            return Ok(());
+        }
+
+        self.report(FormatError::malformed(
+            format,
+            section,
+            offset,
+            format!("non-zero padding {padding:02x?}"),
+        ))
+    }
+
+    /// Report that the file ends before a part starting at `offset`. When this returns `Ok`, the
+    /// parser skips the part and returns everything it read up to that point.
+    pub fn truncated(
+        &mut self,
+        format: &'static str,
+        section: &'static str,
+        offset: u64,
+    ) -> Result<(), FormatError> {
+        self.report(FormatError::Truncated {
+            format,
+            section,
+            offset,
+        })
+    }
+
+    /// The first part that was missing from a truncated file, if any.
+    pub fn truncation(&self) -> Option<&FormatError> {
+        self.warnings
+            .iter()
+            .find(|warning| matches!(warning, FormatError::Truncated { .. }))
+    }
+
+    /// Start recording the values of unknown fields. The same diagnostics can be passed to the
+    /// parsers of many files to survey a whole corpus.
+    pub fn record_unknown_fields(&mut self) {
+        self.unknown_fields
+            .get_or_insert_with(UnknownFields::default);
+    }
+
+    /// Record the value of an unknown field, if recording is enabled.
+    pub fn observe(&mut self, format: &'static str, field: &'static str, value: impl UnknownValue) {
+        if let Some(unknown_fields) = &mut self.unknown_fields {
+            unknown_fields.record(format, field, value);
+        }
+    }
+
+    pub fn unknown_fields(&self) -> Option<&UnknownFields> {
+        self.unknown_fields.as_ref()
+    }
+
+    pub fn warnings(&self) -> &[FormatError] {
+        &self.warnings
+    }
+
+    pub fn into_warnings(self) -> Vec<FormatError> {
+        self.warnings
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::diagnostics::{Diagnostics, ParseMode};
+
+    #[test]
+    pub fn only_fails_in_strict_mode() {
+        let mut strict = Diagnostics::default();
+        assert!(strict.check_padding("BND4", "header", 0x5, &[0, 0]).is_ok());
+        assert!(strict
+            .check_padding("BND4", "header", 0x5, &[0, 1])
+            .is_err());
+
+        let mut lenient = Diagnostics::new(ParseMode::Lenient);
+        assert!(lenient
+            .check_padding("BND4", "header", 0x5, &[0, 1])
+            .is_ok());
+        assert_eq!(lenient.warnings().len(), 1);
+    }
+
+    #[test]
+    pub fn only_observes_unknown_fields_when_recording() {
+        let mut diagnostics = Diagnostics::default();
+        diagnostics.observe("BND4", "unk04", 0u8);
+        assert!(diagnostics.unknown_fields().is_none());
+
+        diagnostics.record_unknown_fields();
+        diagnostics.observe("BND4", "unk04", 0u8);
+        diagnostics.observe("BND4", "unk04", 0u8);
+        let unknown_fields = diagnostics.unknown_fields().unwrap();
+        assert_eq!(unknown_fields.field("BND4", "unk04").unwrap().count(), 2);
+    }
+
+    #[cfg(feature = "tpf")]
+    #[test]
+    pub fn recovers_tpf_with_unknown_encoding() {
+        use std::io::Cursor;
+
+        use crate::tpf::TPF;
+
+        let mut bytes = b"TPF\0".to_vec();
+        bytes.extend(0u32.to_le_bytes());
+
+        // One texture is claimed, but none follow the header.
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend([0, 0, 2, 0]);
+
+        assert!(TPF::from_reader(&mut Cursor::new(&bytes)).is_err());
+
+        let mut diagnostics = Diagnostics::lenient();
+        let tpf = TPF::from_reader_with(&mut Cursor::new(&bytes), &mut diagnostics).unwrap();
+        assert!(tpf.textures.is_empty());
+        assert_eq!(diagnostics.warnings().len(), 2);
+    }
+
+    #[cfg(feature = "tpf")]
+    #[test]
+    pub fn recovers_textures_of_truncated_tpf() {
+        use std::io::Cursor;
+
+        use crate::{error::FormatError, tpf::TPF};
+
+        let mut bytes = b"TPF\0".to_vec();
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend([0, 0, 1, 0]);
+
+        // The data of the second texture was cut off.
+        for (data_offset, data_size) in [(0x38u32, 4u32), (0x100, 0x10)] {
+            bytes.extend(data_offset.to_le_bytes());
+            bytes.extend(data_size.to_le_bytes());
+            bytes.extend([0, 0, 1, 0]);
+            bytes.extend(0x3Cu32.to_le_bytes());
+            bytes.extend(0u32.to_le_bytes());
+        }
+        bytes.extend([0; 4]);
+        bytes.extend([b'a', 0, 0, 0]);
+
+        assert!(TPF::from_reader(&mut Cursor::new(&bytes)).is_err());
+
+        let mut diagnostics = Diagnostics::lenient();
+        let tpf = TPF::from_reader_with(&mut Cursor::new(&bytes), &mut diagnostics).unwrap();
+        assert_eq!(tpf.textures.len(), 1);
+        assert!(matches!(
+            diagnostics.truncation(),
+            Some(FormatError::Truncated { offset: 0x100, .. })
+        ));
+    }
+}
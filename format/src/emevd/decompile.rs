@@ -0,0 +1,299 @@
+use std::fmt::{self, Write};
+
+use byteorder::{ByteOrder, BE, LE};
+
+use crate::emevd::{
+    emedf::{Emedf, EmedfArgType, EmedfInstruction},
+    emeld::Emeld,
+    Emevd, Event, Instruction, RestBehavior,
+};
+
+/// A decoded instruction argument.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ArgValue {
+    U8(u8),
+    U16(u16),
+    U32(u32),
+    S8(i8),
+    S16(i16),
+    S32(i32),
+    F32(f32),
+    String(String),
+}
+
+impl ArgValue {
+    fn as_i64(&self) -> Option<i64> {
+        Some(match *self {
+            Self::U8(v) => v as i64,
+            Self::U16(v) => v as i64,
+            Self::U32(v) => v as i64,
+            Self::S8(v) => v as i64,
+            Self::S16(v) => v as i64,
+            Self::S32(v) => v as i64,
+            Self::F32(_) | Self::String(_) => return None,
+        })
+    }
+}
+
+impl fmt::Display for ArgValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::U8(v) => write!(f, "{v}"),
+            Self::U16(v) => write!(f, "{v}"),
+            Self::U32(v) => write!(f, "{v}"),
+            Self::S8(v) => write!(f, "{v}"),
+            Self::S16(v) => write!(f, "{v}"),
+            Self::S32(v) => write!(f, "{v}"),
+            Self::F32(v) => write!(f, "{v:?}"),
+            Self::String(v) => write!(f, "{v:?}"),
+        }
+    }
+}
+
+/// Decode the arguments of an instruction according to its definition.
+///
+/// Each argument is returned with its offset into the argument data. Decoding stops early if the
+/// argument data is shorter than the definition expects.
+pub fn decode_args(
+    emevd: &Emevd,
+    instruction: &Instruction,
+    def: &EmedfInstruction,
+) -> Vec<(usize, ArgValue)> {
+    if emevd.big_endian {
+        decode_args_with::<BE>(emevd, instruction, def)
+    } else {
+        decode_args_with::<LE>(emevd, instruction, def)
+    }
+}
+
+fn decode_args_with<O: ByteOrder>(
+    emevd: &Emevd,
+    instruction: &Instruction,
+    def: &EmedfInstruction,
+) -> Vec<(usize, ArgValue)> {
+    let data = &instruction.args;
+    let mut offset = 0usize;
+    let mut values = Vec::with_capacity(def.args.len());
+
+    for arg in &def.args {
+        let size = arg.arg_type.size();
+        offset = offset.next_multiple_of(size);
+
+        let Some(bytes) = data.get(offset..offset + size) else {
+            break;
+        };
+
+        let value = match arg.arg_type {
+            EmedfArgType::U8 => ArgValue::U8(bytes[0]),
+            EmedfArgType::U16 => ArgValue::U16(O::read_u16(bytes)),
+            EmedfArgType::U32 => ArgValue::U32(O::read_u32(bytes)),
+            EmedfArgType::S8 => ArgValue::S8(bytes[0] as i8),
+            EmedfArgType::S16 => ArgValue::S16(O::read_i16(bytes)),
+            EmedfArgType::S32 => ArgValue::S32(O::read_i32(bytes)),
+            EmedfArgType::F32 => ArgValue::F32(O::read_f32(bytes)),
+            EmedfArgType::StringOffset => {
+                ArgValue::String(emevd.string(O::read_u32(bytes) as usize))
+            }
+        };
+
+        values.push((offset, value));
+        offset += size;
+    }
+
+    values
+}
+
+/// Produce a readable listing of every event in an EMEVD, naming instructions and arguments
+/// using the given EMEDF.
+///
+/// Arguments that are substituted from the event's initialization parameters are shown as
+/// `X<source byte>_<byte count>`, matching the convention of other event script tooling.
+pub fn decompile(emevd: &Emevd, emedf: &Emedf) -> String {
+    decompile_inner(emevd, emedf, None)
+}
+
+/// Like [`decompile`], with each event preceded by its name from the map's EMELD.
+pub fn decompile_with_names(emevd: &Emevd, emedf: &Emedf, emeld: &Emeld) -> String {
+    decompile_inner(emevd, emedf, Some(emeld))
+}
+
+fn decompile_inner(emevd: &Emevd, emedf: &Emedf, emeld: Option<&Emeld>) -> String {
+    let mut out = String::new();
+
+    for linked_file in &emevd.linked_files {
+        let _ = writeln!(out, "// linked: {linked_file}");
+    }
+
+    for event in &emevd.events {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+
+        if let Some(name) = emeld.and_then(|emeld| emeld.name(event.id)) {
+            let _ = writeln!(out, "// {name}");
+        }
+
+        decompile_event(&mut out, emevd, emedf, event);
+    }
+
+    out
+}
+
+fn decompile_event(out: &mut String, emevd: &Emevd, emedf: &Emedf, event: &Event) {
+    let rest_behavior = match event.rest_behavior {
+        RestBehavior::Default => "Default",
+        RestBehavior::Restart => "Restart",
+        RestBehavior::End => "End",
+    };
+
+    let _ = writeln!(out, "Event({}, {rest_behavior}) {{", event.id);
+
+    for (index, instruction) in event.instructions.iter().enumerate() {
+        let parameters = event
+            .parameters
+            .iter()
+            .filter(|param| param.instruction_index == index as i64)
+            .collect::<Vec<_>>();
+
+        let parameter_at = |offset: usize| {
+            parameters
+                .iter()
+                .find(|param| param.target_start_byte == offset as i64)
+                .map(|param| format!("X{}_{}", param.source_start_byte, param.byte_count))
+        };
+
+        out.push_str("    ");
+
+        match emedf.instruction(instruction.bank, instruction.id) {
+            Some(def) => {
+                let _ = write!(out, "{}(", instruction_name(&def.name));
+
+                let values = decode_args(emevd, instruction, def);
+                for (arg_index, (arg, (offset, value))) in def.args.iter().zip(values).enumerate() {
+                    if arg_index > 0 {
+                        out.push_str(", ");
+                    }
+
+                    let enum_member = arg
+                        .enum_name
+                        .as_deref()
+                        .zip(value.as_i64())
+                        .and_then(|(enum_name, value)| emedf.enum_member(enum_name, value));
+
+                    let _ = match (parameter_at(offset), enum_member) {
+                        (Some(parameter), _) => write!(out, "{}: {parameter}", arg.name),
+                        (None, Some(member)) => write!(out, "{}: {member}", arg.name),
+                        (None, None) => write!(out, "{}: {value}", arg.name),
+                    };
+                }
+
+                out.push(')');
+            }
+            None => {
+                let _ = write!(out, "Unknown{}_{:02}(", instruction.bank, instruction.id);
+                for (i, byte) in instruction.args.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+
+                    let _ = write!(out, "{byte:02x}");
+                }
+                out.push(')');
+
+                for param in parameters {
+                    let _ = write!(
+                        out,
+                        " [{}: X{}_{}]",
+                        param.target_start_byte, param.source_start_byte, param.byte_count
+                    );
+                }
+            }
+        }
+
+        if let Some(layer) = instruction.layer {
+            let _ = write!(out, " @layers({layer:#x})");
+        }
+
+        out.push_str(";\n");
+    }
+
+    out.push_str("}\n");
+}
+
+/// Convert an EMEDF instruction name such as `IF Condition Group` into `IfConditionGroup`.
+fn instruction_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+
+    for word in name.split(|ch: char| !ch.is_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            result.extend(first.to_uppercase());
+            result.extend(chars.flat_map(char::to_lowercase));
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use crate::emevd::{decompile, Emedf, Emevd, Event, Instruction, RestBehavior};
+
+    #[test]
+    pub fn decompiles_named_instructions() {
+        let emedf = Emedf::from_json(
+            r#"{
+                "main_classes": [{
+                    "name": "Event",
+                    "index": 2000,
+                    "instrs": [{
+                        "name": "Set Event Flag",
+                        "index": 5,
+                        "args": [
+                            { "name": "Flag ID", "type": 2 },
+                            { "name": "State", "type": 0, "enum_name": "ON/OFF" }
+                        ]
+                    }]
+                }],
+                "enums": [{ "name": "ON/OFF", "values": { "0": "OFF", "1": "ON" } }]
+            }"#,
+        )
+        .unwrap();
+
+        let emevd = Emevd {
+            big_endian: false,
+            long_format: true,
+            unk06: false,
+            unicode: true,
+            version: 0xCD,
+            events: vec![Event {
+                id: 100,
+                rest_behavior: RestBehavior::Restart,
+                instructions: vec![
+                    Instruction {
+                        bank: 2000,
+                        id: 5,
+                        args: vec![0x10, 0x27, 0, 0, 1, 0, 0, 0],
+                        layer: None,
+                    },
+                    Instruction {
+                        bank: 1000,
+                        id: 1,
+                        args: vec![0xab],
+                        layer: Some(2),
+                    },
+                ],
+                parameters: vec![],
+            }],
+            linked_files: vec![],
+            strings: vec![],
+        };
+
+        assert_eq!(
+            decompile(&emevd, &emedf),
+            "Event(100, Restart) {\n    SetEventFlag(Flag ID: 10000, State: ON);\n    \
+             Unknown1000_01(ab) @layers(0x2);\n}\n"
+        );
+    }
+}
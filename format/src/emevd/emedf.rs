@@ -0,0 +1,170 @@
+use std::{collections::HashMap, fmt};
+
+use serde::Deserialize;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EmedfError {
+    #[error("Could not parse EMEDF: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Instruction definitions for a game's event scripts, in the JSON format used by DarkScript3 and
+/// soulstruct (e.g. `er-common.emedf.json`).
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Emedf {
+    #[serde(rename = "main_classes")]
+    pub classes: Vec<EmedfClass>,
+
+    #[serde(default)]
+    pub enums: Vec<EmedfEnum>,
+
+    #[serde(skip)]
+    instructions: HashMap<(i32, i32), (usize, usize)>,
+
+    #[serde(skip)]
+    enum_values: HashMap<String, HashMap<i64, String>>,
+}
+
+/// A bank of instructions, e.g. `Event` for bank 2000.
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EmedfClass {
+    pub name: String,
+    pub index: i32,
+    pub instrs: Vec<EmedfInstruction>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EmedfInstruction {
+    pub name: String,
+    pub index: i32,
+
+    #[serde(default)]
+    pub args: Vec<EmedfArg>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EmedfArg {
+    pub name: String,
+
+    #[serde(rename = "type")]
+    pub arg_type: EmedfArgType,
+
+    #[serde(default)]
+    pub enum_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EmedfEnum {
+    pub name: String,
+
+    /// Enum member names keyed by their value, as a string.
+    pub values: HashMap<String, String>,
+}
+
+/// Storage type of an instruction argument.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+#[serde(try_from = "u8")]
+pub enum EmedfArgType {
+    U8,
+    U16,
+    U32,
+    S8,
+    S16,
+    S32,
+    F32,
+
+    /// An offset into the EMEVD string table.
+    StringOffset,
+}
+
+impl EmedfArgType {
+    /// Size in bytes of the argument, arguments are aligned to their own size.
+    pub fn size(self) -> usize {
+        match self {
+            Self::U8 | Self::S8 => 1,
+            Self::U16 | Self::S16 => 2,
+            Self::U32 | Self::S32 | Self::F32 | Self::StringOffset => 4,
+        }
+    }
+}
+
+impl TryFrom<u8> for EmedfArgType {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0 => Self::U8,
+            1 => Self::U16,
+            2 => Self::U32,
+            3 => Self::S8,
+            4 => Self::S16,
+            5 => Self::S32,
+            6 => Self::F32,
+            8 => Self::StringOffset,
+            _ => return Err(format!("unknown EMEDF argument type {value}")),
+        })
+    }
+}
+
+impl fmt::Display for EmedfArgType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::U8 => "u8",
+            Self::U16 => "u16",
+            Self::U32 => "u32",
+            Self::S8 => "s8",
+            Self::S16 => "s16",
+            Self::S32 => "s32",
+            Self::F32 => "f32",
+            Self::StringOffset => "string",
+        })
+    }
+}
+
+impl Emedf {
+    pub fn from_json(json: &str) -> Result<Self, EmedfError> {
+        let mut emedf: Self = serde_json::from_str(json)?;
+
+        for (class_index, class) in emedf.classes.iter().enumerate() {
+            for (instr_index, instr) in class.instrs.iter().enumerate() {
+                emedf
+                    .instructions
+                    .insert((class.index, instr.index), (class_index, instr_index));
+            }
+        }
+
+        for emedf_enum in &emedf.enums {
+            let values = emedf_enum
+                .values
+                .iter()
+                .filter_map(|(value, name)| Some((value.trim().parse().ok()?, name.clone())))
+                .collect();
+
+            emedf.enum_values.insert(emedf_enum.name.clone(), values);
+        }
+
+        Ok(emedf)
+    }
+
+    /// Find the definition of the instruction `bank[id]`.
+    pub fn instruction(&self, bank: i32, id: i32) -> Option<&EmedfInstruction> {
+        let (class, instr) = self.instructions.get(&(bank, id))?;
+
+        Some(&self.classes[*class].instrs[*instr])
+    }
+
+    /// Find the name of the member of `enum_name` with the given value.
+    pub fn enum_member(&self, enum_name: &str, value: i64) -> Option<&str> {
+        self.enum_values
+            .get(enum_name)?
+            .get(&value)
+            .map(String::as_str)
+    }
+}
@@ -0,0 +1,134 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use byteorder::{ByteOrder, ReadBytesExt, BE, LE};
+
+use crate::{
+    emevd::{read_signed_varint, read_string, read_varint, EmevdError},
+    io_ext::ReadFormatsExt,
+};
+
+/// Version used by Dark Souls 2.
+pub const EMELD_VERSION_DS2: u32 = 0xCC;
+
+/// Version used from Dark Souls 3 onwards.
+pub const EMELD_VERSION_DS3: u32 = 0xCD;
+
+/// The names of the events in an EMEVD (`.emeld`), shipped alongside it for some maps.
+///
+/// Names are developer labels that have no effect on the game, they are only useful to annotate
+/// decompiled events.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Emeld {
+    pub big_endian: bool,
+
+    /// Set from Dark Souls 3 onwards, counts and offsets are 64 bits wide.
+    pub long_format: bool,
+    pub version: u32,
+    pub events: Vec<EmeldEvent>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EmeldEvent {
+    pub id: i64,
+    pub name: String,
+}
+
+impl Emeld {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, EmevdError> {
+        r.read_magic(b"ELD\0")?;
+        let big_endian = r.read_bool()?;
+
+        if big_endian {
+            Self::read::<_, BE>(r)
+        } else {
+            Self::read::<_, LE>(r)
+        }
+    }
+
+    fn read<R: Read + Seek, O: ByteOrder>(r: &mut R) -> Result<Self, EmevdError> {
+        r.seek(SeekFrom::Start(4))?;
+        let big_endian = r.read_bool()?;
+        let long_format = r.read_u8()? == 0xFF;
+        r.read_padding(2)?;
+
+        let version = r.read_u32::<O>()?;
+        if version != EMELD_VERSION_DS2 && version != EMELD_VERSION_DS3 {
+            return Err(EmevdError::UnknownVersion(version));
+        }
+
+        let _file_size = r.read_u32::<O>()?;
+
+        let mut varint = || read_varint::<O>(r, long_format);
+        let event_count = varint()?;
+        let events_offset = varint()?;
+
+        // Tables that are always empty.
+        for _ in 0..6 {
+            varint()?;
+        }
+
+        let strings_length = varint()?;
+        let strings_offset = varint()?;
+
+        r.seek(SeekFrom::Start(strings_offset))?;
+        let mut strings = vec![0u8; strings_length as usize];
+        r.read_exact(&mut strings)?;
+
+        let event_size = if long_format { 0x18 } else { 0xC };
+        let mut events = Vec::with_capacity(event_count as usize);
+        for index in 0..event_count {
+            r.seek(SeekFrom::Start(events_offset + index * event_size))?;
+            let id = read_signed_varint::<O>(r, long_format)?;
+            let name_offset = read_varint::<O>(r, long_format)?;
+
+            events.push(EmeldEvent {
+                id,
+                name: read_string::<O>(&strings, name_offset as usize, true),
+            });
+        }
+
+        Ok(Self {
+            big_endian,
+            long_format,
+            version,
+            events,
+        })
+    }
+
+    pub fn name(&self, id: i64) -> Option<&str> {
+        self.events
+            .iter()
+            .find(|event| event.id == id)
+            .map(|event| event.name.as_str())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::emevd::Emeld;
+
+    #[test]
+    pub fn reads_event_names() {
+        let mut bytes = b"ELD\0\0\xFF\0\0".to_vec();
+        bytes.extend(0xCDu32.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+
+        // Event count and offset, six empty tables, then the string table.
+        let header = [1u64, 0x60, 0, 0, 0, 0, 0, 0, 0x10, 0x78];
+        bytes.extend(header.iter().flat_map(|value| value.to_le_bytes()));
+        assert_eq!(bytes.len(), 0x60);
+
+        bytes.extend([100u64, 0, 0].iter().flat_map(|value| value.to_le_bytes()));
+        bytes.extend("Boss\0".encode_utf16().flat_map(u16::to_le_bytes));
+        bytes.resize(0x88, 0);
+
+        let emeld = Emeld::from_reader(&mut Cursor::new(bytes)).unwrap();
+        assert!(emeld.long_format);
+        assert_eq!(emeld.name(100), Some("Boss"));
+        assert_eq!(emeld.name(200), None);
+    }
+}
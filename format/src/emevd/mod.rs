@@ -0,0 +1,330 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use byteorder::{ByteOrder, ReadBytesExt, BE, LE};
+use thiserror::Error;
+
+use crate::io_ext::ReadFormatsExt;
+
+pub mod decompile;
+pub mod emedf;
+pub mod emeld;
+
+pub use self::{
+    decompile::{decode_args, decompile, decompile_with_names, ArgValue},
+    emedf::{Emedf, EmedfArg, EmedfArgType, EmedfError, EmedfInstruction},
+    emeld::{Emeld, EmeldEvent},
+};
+
+#[derive(Debug, Error)]
+pub enum EmevdError {
+    #[error("Could not read EMEVD: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unknown EMEVD version {0:#x}")]
+    UnknownVersion(u32),
+
+    #[error("Event {event} has unknown rest behavior {value}")]
+    UnknownRestBehavior { event: i64, value: u32 },
+}
+
+/// Version used by Demon's Souls, Dark Souls and Dark Souls 2.
+pub const EMEVD_VERSION_DS1: u32 = 0xCC;
+
+/// Version used from Bloodborne and Dark Souls 3 onwards.
+pub const EMEVD_VERSION_DS3: u32 = 0xCD;
+
+/// An event script, made up of events that each run a list of instructions.
+///
+/// Instruction arguments are kept as raw bytes, their layout is defined per instruction by the
+/// game's EMEDF.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Emevd {
+    pub big_endian: bool,
+
+    /// Set from Dark Souls Remastered onwards, counts and offsets are 64 bits wide.
+    pub long_format: bool,
+
+    pub unk06: bool,
+    pub unicode: bool,
+    pub version: u32,
+    pub events: Vec<Event>,
+
+    /// Other EMEVD files whose events this file can initialize, e.g. `common_func.emevd`.
+    pub linked_files: Vec<String>,
+
+    /// The raw string table, referenced by linked files and some instruction arguments.
+    pub strings: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum RestBehavior {
+    Default,
+    Restart,
+    End,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Event {
+    pub id: i64,
+    pub rest_behavior: RestBehavior,
+    pub instructions: Vec<Instruction>,
+    pub parameters: Vec<EventParameter>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Instruction {
+    /// The group of related instructions this belongs to, e.g. 2003 for event control.
+    pub bank: i32,
+    pub id: i32,
+    pub args: Vec<u8>,
+
+    /// Bitmask of the map layers this instruction runs on, if restricted.
+    pub layer: Option<u32>,
+}
+
+/// A substitution of an event's initialization arguments into the arguments of an instruction.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EventParameter {
+    pub instruction_index: i64,
+    pub target_start_byte: i64,
+    pub source_start_byte: i64,
+    pub byte_count: i64,
+    pub unk_id: i32,
+}
+
+struct EmevdOffsets {
+    events: u64,
+    instructions: u64,
+    layers: u64,
+    parameters: u64,
+    linked_files: u64,
+    arguments: u64,
+    strings: u64,
+}
+
+impl Emevd {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, EmevdError> {
+        r.read_magic(b"EVD\0")?;
+        let big_endian = r.read_bool()?;
+
+        if big_endian {
+            Self::read::<_, BE>(r)
+        } else {
+            Self::read::<_, LE>(r)
+        }
+    }
+
+    fn read<R: Read + Seek, O: ByteOrder>(r: &mut R) -> Result<Self, EmevdError> {
+        r.seek(SeekFrom::Start(4))?;
+        let big_endian = r.read_bool()?;
+        let long_format = r.read_u8()? == 0xFF;
+        let unk06 = r.read_bool()?;
+        let unicode = r.read_u8()? == 0xFF;
+
+        let version = r.read_u32::<O>()?;
+        if version != EMEVD_VERSION_DS1 && version != EMEVD_VERSION_DS3 {
+            return Err(EmevdError::UnknownVersion(version));
+        }
+
+        let _file_size = r.read_u32::<O>()?;
+
+        let mut varint = || read_varint::<O>(r, long_format);
+        let event_count = varint()?;
+        let events_offset = varint()?;
+        let _instruction_count = varint()?;
+        let instructions_offset = varint()?;
+        let _unk_count = varint()?;
+        let _unk_offset = varint()?;
+        let _layer_count = varint()?;
+        let layers_offset = varint()?;
+        let _parameter_count = varint()?;
+        let parameters_offset = varint()?;
+        let linked_file_count = varint()?;
+        let linked_files_offset = varint()?;
+        let _arguments_length = varint()?;
+        let arguments_offset = varint()?;
+        let strings_length = varint()?;
+        let strings_offset = varint()?;
+
+        let offsets = EmevdOffsets {
+            events: events_offset,
+            instructions: instructions_offset,
+            layers: layers_offset,
+            parameters: parameters_offset,
+            linked_files: linked_files_offset,
+            arguments: arguments_offset,
+            strings: strings_offset,
+        };
+
+        r.seek(SeekFrom::Start(offsets.strings))?;
+        let mut strings = vec![0u8; strings_length as usize];
+        r.read_exact(&mut strings)?;
+
+        r.seek(SeekFrom::Start(offsets.linked_files))?;
+        let linked_file_offsets = (0..linked_file_count)
+            .map(|_| read_varint::<O>(r, long_format))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let linked_files = linked_file_offsets
+            .into_iter()
+            .map(|offset| read_string::<O>(&strings, offset as usize, unicode))
+            .collect();
+
+        let mut events = Vec::with_capacity(event_count as usize);
+        for index in 0..event_count {
+            let event_size = if long_format { 0x30 } else { 0x1C };
+            r.seek(SeekFrom::Start(offsets.events + index * event_size))?;
+            events.push(Event::read::<_, O>(r, long_format, &offsets)?);
+        }
+
+        Ok(Self {
+            big_endian,
+            long_format,
+            unk06,
+            unicode,
+            version,
+            events,
+            linked_files,
+            strings,
+        })
+    }
+
+    /// Read a null-terminated string from the string table, such as a string instruction argument.
+    pub fn string(&self, offset: usize) -> String {
+        if self.big_endian {
+            read_string::<BE>(&self.strings, offset, self.unicode)
+        } else {
+            read_string::<LE>(&self.strings, offset, self.unicode)
+        }
+    }
+
+    pub fn event(&self, id: i64) -> Option<&Event> {
+        self.events.iter().find(|event| event.id == id)
+    }
+}
+
+impl Event {
+    fn read<R: Read + Seek, O: ByteOrder>(
+        r: &mut R,
+        long_format: bool,
+        offsets: &EmevdOffsets,
+    ) -> Result<Self, EmevdError> {
+        let id = read_signed_varint::<O>(r, long_format)?;
+        let instruction_count = read_varint::<O>(r, long_format)?;
+        let instructions_offset = read_varint::<O>(r, long_format)?;
+        let parameter_count = read_varint::<O>(r, long_format)?;
+        let parameters_offset = read_varint::<O>(r, long_format)?;
+
+        let rest_behavior = match r.read_u32::<O>()? {
+            0 => RestBehavior::Default,
+            1 => RestBehavior::Restart,
+            2 => RestBehavior::End,
+            value => return Err(EmevdError::UnknownRestBehavior { event: id, value }),
+        };
+
+        let instruction_size = if long_format { 0x20 } else { 0x14 };
+        let mut instructions = Vec::with_capacity(instruction_count as usize);
+        for index in 0..instruction_count {
+            r.seek(SeekFrom::Start(
+                offsets.instructions + instructions_offset + index * instruction_size,
+            ))?;
+            instructions.push(Instruction::read::<_, O>(r, long_format, offsets)?);
+        }
+
+        let parameter_size = if long_format { 0x28 } else { 0x14 };
+        let mut parameters = Vec::with_capacity(parameter_count as usize);
+        for index in 0..parameter_count {
+            r.seek(SeekFrom::Start(
+                offsets.parameters + parameters_offset + index * parameter_size,
+            ))?;
+
+            parameters.push(EventParameter {
+                instruction_index: read_signed_varint::<O>(r, long_format)?,
+                target_start_byte: read_signed_varint::<O>(r, long_format)?,
+                source_start_byte: read_signed_varint::<O>(r, long_format)?,
+                byte_count: read_signed_varint::<O>(r, long_format)?,
+                unk_id: r.read_i32::<O>()?,
+            });
+        }
+
+        Ok(Self {
+            id,
+            rest_behavior,
+            instructions,
+            parameters,
+        })
+    }
+}
+
+impl Instruction {
+    fn read<R: Read + Seek, O: ByteOrder>(
+        r: &mut R,
+        long_format: bool,
+        offsets: &EmevdOffsets,
+    ) -> Result<Self, EmevdError> {
+        let bank = r.read_i32::<O>()?;
+        let id = r.read_i32::<O>()?;
+        let args_length = read_varint::<O>(r, long_format)?;
+        let args_offset = read_varint::<O>(r, long_format)?;
+        let layer_offset = read_signed_varint::<O>(r, long_format)?;
+
+        r.seek(SeekFrom::Start(offsets.arguments + args_offset))?;
+        let mut args = vec![0u8; args_length as usize];
+        r.read_exact(&mut args)?;
+
+        let layer = if layer_offset >= 0 {
+            // Layers are stored as `2, mask, 0, -1, 1`, only the mask varies.
+            r.seek(SeekFrom::Start(offsets.layers + layer_offset as u64 + 4))?;
+            Some(r.read_u32::<O>()?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            bank,
+            id,
+            args,
+            layer,
+        })
+    }
+}
+
+fn read_varint<O: ByteOrder>(r: &mut impl Read, long_format: bool) -> io::Result<u64> {
+    if long_format {
+        r.read_u64::<O>()
+    } else {
+        Ok(r.read_u32::<O>()? as u64)
+    }
+}
+
+fn read_signed_varint<O: ByteOrder>(r: &mut impl Read, long_format: bool) -> io::Result<i64> {
+    if long_format {
+        r.read_i64::<O>()
+    } else {
+        Ok(r.read_i32::<O>()? as i64)
+    }
+}
+
+fn read_string<O: ByteOrder>(strings: &[u8], offset: usize, unicode: bool) -> String {
+    let bytes = strings.get(offset..).unwrap_or_default();
+
+    if unicode {
+        let units = bytes
+            .chunks_exact(2)
+            .map(O::read_u16)
+            .take_while(|unit| *unit != 0)
+            .collect::<Vec<_>>();
+
+        String::from_utf16_lossy(&units)
+    } else {
+        let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+        let (string, _, _) = encoding_rs::SHIFT_JIS.decode(&bytes[..end]);
+
+        string.into_owned()
+    }
+}
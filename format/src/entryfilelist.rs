@@ -0,0 +1,236 @@
+use std::io::{self, Read, Write};
+
+use byteorder::{ByteOrder, LE};
+use flate2::{read::ZlibDecoder, write::ZlibEncoder, Compression};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EntryFileListError {
+    #[error("Could not read ENTRYFILELIST: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not an ENTRYFILELIST file")]
+    InvalidMagic,
+
+    #[error("ENTRYFILELIST body is truncated")]
+    Truncated,
+
+    #[error("Entry refers to path {0}, but there are only {1} paths")]
+    PathOutOfBounds(u16, usize),
+
+    #[error("ENTRYFILELIST can't hold more than {max} paths", max = u16::MAX)]
+    TooManyPaths,
+}
+
+const ENFL_MAGIC: &[u8; 4] = b"ENFL";
+const HEADER_SIZE: usize = 0x10;
+const BODY_HEADER_SIZE: usize = 0x10;
+const ENTRY_SIZE: usize = 0x4;
+const PATH_HASH_SIZE: usize = 0x8;
+
+/// The resources an entity streams in alongside itself (`.entryfilelist`), e.g. the textures and
+/// animations a map piece or character needs.
+///
+/// The body is zlib compressed, and the file itself is usually wrapped in a DCX.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EntryFileList {
+    /// Unknown, `0x10415` in Elden Ring.
+    pub version: u32,
+    pub entries: Vec<EntryFileListEntry>,
+    pub paths: Vec<EntryFileListPath>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EntryFileListEntry {
+    pub unk00: u16,
+
+    /// Index into [`EntryFileList::paths`].
+    pub path_index: u16,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EntryFileListPath {
+    /// Unknown, possibly a hash of the path.
+    pub unk00: u64,
+
+    /// Virtual path of the resource, e.g. `N:\GR\data\INTERROOT_win64\map\...`.
+    pub path: String,
+}
+
+impl EntryFileList {
+    pub fn from_reader(r: &mut impl Read) -> Result<Self, EntryFileListError> {
+        let mut header = [0u8; HEADER_SIZE];
+        r.read_exact(&mut header)?;
+        if &header[..4] != ENFL_MAGIC {
+            return Err(EntryFileListError::InvalidMagic);
+        }
+
+        let version = LE::read_u32(&header[0x4..]);
+        let compressed_size = LE::read_u32(&header[0x8..]) as u64;
+        let decompressed_size = LE::read_u32(&header[0xC..]) as usize;
+
+        let mut body = Vec::with_capacity(decompressed_size);
+        ZlibDecoder::new(r.take(compressed_size)).read_to_end(&mut body)?;
+
+        let body_header = body
+            .get(..BODY_HEADER_SIZE)
+            .ok_or(EntryFileListError::Truncated)?;
+        let entry_count = LE::read_u32(&body_header[0x4..]) as usize;
+        let path_count = LE::read_u32(&body_header[0x8..]) as usize;
+
+        let entries_end = BODY_HEADER_SIZE + entry_count * ENTRY_SIZE;
+        let entries = body
+            .get(BODY_HEADER_SIZE..entries_end)
+            .ok_or(EntryFileListError::Truncated)?
+            .chunks_exact(ENTRY_SIZE)
+            .map(|entry| EntryFileListEntry {
+                unk00: LE::read_u16(entry),
+                path_index: LE::read_u16(&entry[0x2..]),
+            })
+            .collect::<Vec<_>>();
+
+        let hashes_start = entries_end.next_multiple_of(0x10);
+        let hashes_end = hashes_start + path_count * PATH_HASH_SIZE;
+        let hashes = body
+            .get(hashes_start..hashes_end)
+            .ok_or(EntryFileListError::Truncated)?;
+
+        // Paths are preceded by a single empty string.
+        let mut strings = body
+            .get(hashes_end..)
+            .ok_or(EntryFileListError::Truncated)?
+            .chunks_exact(2)
+            .map(LE::read_u16);
+        strings.next();
+
+        let mut paths = Vec::with_capacity(path_count);
+        for hash in hashes.chunks_exact(PATH_HASH_SIZE) {
+            let path = strings.by_ref().take_while(|c| *c != 0).collect::<Vec<_>>();
+
+            paths.push(EntryFileListPath {
+                unk00: LE::read_u64(hash),
+                path: String::from_utf16_lossy(&path),
+            });
+        }
+
+        if let Some(entry) = entries
+            .iter()
+            .find(|entry| entry.path_index as usize >= paths.len())
+        {
+            return Err(EntryFileListError::PathOutOfBounds(
+                entry.path_index,
+                paths.len(),
+            ));
+        }
+
+        Ok(Self {
+            version,
+            entries,
+            paths,
+        })
+    }
+
+    /// Add a resource to the list, reusing its path if it's already listed.
+    pub fn add_path(&mut self, path: &str, unk00: u64) -> Result<(), EntryFileListError> {
+        let path_index = match self.paths.iter().position(|p| p.path == path) {
+            Some(index) => index,
+            None => {
+                self.paths.push(EntryFileListPath {
+                    unk00,
+                    path: path.to_string(),
+                });
+                self.paths.len() - 1
+            }
+        };
+
+        self.entries.push(EntryFileListEntry {
+            unk00: 0,
+            path_index: u16::try_from(path_index).map_err(|_| EntryFileListError::TooManyPaths)?,
+        });
+
+        Ok(())
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> Result<(), EntryFileListError> {
+        if self.paths.len() > u16::MAX as usize {
+            return Err(EntryFileListError::TooManyPaths);
+        }
+
+        let mut body = Vec::new();
+        body.extend(0u32.to_le_bytes());
+        body.extend((self.entries.len() as u32).to_le_bytes());
+        body.extend((self.paths.len() as u32).to_le_bytes());
+        body.extend(0u32.to_le_bytes());
+
+        for entry in &self.entries {
+            body.extend(entry.unk00.to_le_bytes());
+            body.extend(entry.path_index.to_le_bytes());
+        }
+        body.resize(body.len().next_multiple_of(0x10), 0);
+
+        for path in &self.paths {
+            body.extend(path.unk00.to_le_bytes());
+        }
+
+        body.extend(0u16.to_le_bytes());
+        for path in &self.paths {
+            body.extend(
+                path.path
+                    .encode_utf16()
+                    .chain([0])
+                    .flat_map(u16::to_le_bytes),
+            );
+        }
+
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&body)?;
+        let compressed = encoder.finish()?;
+
+        w.write_all(ENFL_MAGIC)?;
+        w.write_all(&self.version.to_le_bytes())?;
+        w.write_all(&(compressed.len() as u32).to_le_bytes())?;
+        w.write_all(&(body.len() as u32).to_le_bytes())?;
+        w.write_all(&compressed)?;
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, EntryFileListError> {
+        let mut out = Vec::new();
+        self.write(&mut out)?;
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::entryfilelist::EntryFileList;
+
+    #[test]
+    pub fn round_trips_added_paths() {
+        let mut list = EntryFileList {
+            version: 0x10415,
+            entries: Vec::new(),
+            paths: Vec::new(),
+        };
+        list.add_path(r"N:\GR\data\INTERROOT_win64\map\m10\m10_00_00_00.tpfbhd", 1)
+            .unwrap();
+        list.add_path(r"N:\GR\data\INTERROOT_win64\map\m10\m10_00_00_00.tpfbhd", 1)
+            .unwrap();
+        list.add_path(r"N:\GR\data\INTERROOT_win64\chr\c0000.anibnd", 2)
+            .unwrap();
+
+        assert_eq!(list.paths.len(), 2);
+        assert_eq!(list.entries[1].path_index, 0);
+        assert_eq!(list.entries[2].path_index, 1);
+
+        let read = EntryFileList::from_reader(&mut Cursor::new(list.to_bytes().unwrap())).unwrap();
+        assert_eq!(read, list);
+    }
+}
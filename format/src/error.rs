@@ -0,0 +1,310 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+use core::fmt;
+#[cfg(feature = "std")]
+use std::io;
+
+use thiserror::Error;
+
+#[cfg(feature = "map")]
+use crate::btab::BtabError;
+#[cfg(feature = "map")]
+use crate::btl::BtlError;
+#[cfg(feature = "dcx")]
+use crate::dcx::DCXError;
+#[cfg(feature = "design")]
+use crate::design::DesignError;
+#[cfg(feature = "emevd")]
+use crate::emevd::emedf::EmedfError;
+#[cfg(feature = "emevd")]
+use crate::emevd::EmevdError;
+#[cfg(feature = "entryfilelist")]
+use crate::entryfilelist::EntryFileListError;
+#[cfg(feature = "esd")]
+use crate::esd::expression::ExpressionError;
+#[cfg(feature = "esd")]
+use crate::esd::EsdError;
+#[cfg(feature = "fmg")]
+use crate::fmg::FmgError;
+#[cfg(feature = "fxr")]
+use crate::fxr::FxrError;
+#[cfg(feature = "gparam")]
+use crate::gparam::GparamError;
+#[cfg(feature = "map")]
+use crate::grass::GrassError;
+#[cfg(feature = "havok")]
+use crate::hkx::HkxError;
+#[cfg(feature = "havok")]
+use crate::hkxbhd::MapCollisionError;
+#[cfg(feature = "lua")]
+use crate::luabnd::LuaBndError;
+#[cfg(feature = "lua")]
+use crate::luagnl::LuaGnlError;
+#[cfg(feature = "lua")]
+use crate::luainfo::LuaInfoError;
+#[cfg(feature = "material")]
+use crate::matbin::MatbinError;
+#[cfg(feature = "map")]
+use crate::mcg::McgError;
+#[cfg(feature = "map")]
+use crate::mcp::McpError;
+#[cfg(feature = "cutscene")]
+use crate::mqb::MqbError;
+#[cfg(feature = "fmg")]
+use crate::msgbnd::MsgBndError;
+#[cfg(feature = "material")]
+use crate::mtd::MtdError;
+#[cfg(feature = "map")]
+use crate::nva::NvaError;
+#[cfg(feature = "map")]
+use crate::nvm::NvmError;
+#[cfg(all(feature = "param", feature = "std"))]
+use crate::param::def::ParamDefError;
+#[cfg(all(feature = "param", feature = "std"))]
+use crate::param::paramdex::ParamdexError;
+#[cfg(all(feature = "param", feature = "std"))]
+use crate::param::regulation::RegulationError;
+#[cfg(feature = "param")]
+use crate::param::ParamError;
+#[cfg(feature = "save")]
+use crate::save::SaveError;
+#[cfg(feature = "sound")]
+use crate::sound::SoundError;
+#[cfg(feature = "tae")]
+use crate::tae::TaeError;
+#[cfg(feature = "tae")]
+use crate::tae::TaeTemplateError;
+#[cfg(feature = "tpf")]
+use crate::tpf::TPFError;
+
+/// Any error produced by the parsers and writers of this crate.
+///
+/// Each format keeps its own error type, which converts into this one, so that applications
+/// handling many formats can propagate them with `?` and still match on what failed.
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[cfg(feature = "std")]
+    #[error("I/O error: {0}")]
+    Io(io::Error),
+
+    #[error("Malformed {format} at {offset:#x} ({section}): {reason}")]
+    Malformed {
+        format: &'static str,
+        section: &'static str,
+        offset: u64,
+        reason: String,
+    },
+
+    #[error("Truncated {format}, the file ends before the {section} at {offset:#x}")]
+    Truncated {
+        format: &'static str,
+        section: &'static str,
+        offset: u64,
+    },
+
+    #[cfg(feature = "map")]
+    #[error(transparent)]
+    Btab(#[from] BtabError),
+
+    #[cfg(feature = "map")]
+    #[error(transparent)]
+    Btl(#[from] BtlError),
+
+    #[cfg(feature = "dcx")]
+    #[error(transparent)]
+    Dcx(#[from] DCXError),
+
+    #[cfg(feature = "design")]
+    #[error(transparent)]
+    Design(#[from] DesignError),
+
+    #[cfg(feature = "emevd")]
+    #[error(transparent)]
+    Emedf(#[from] EmedfError),
+
+    #[cfg(feature = "emevd")]
+    #[error(transparent)]
+    Emevd(#[from] EmevdError),
+
+    #[cfg(feature = "entryfilelist")]
+    #[error(transparent)]
+    EntryFileList(#[from] EntryFileListError),
+
+    #[cfg(feature = "esd")]
+    #[error(transparent)]
+    Esd(#[from] EsdError),
+
+    #[cfg(feature = "esd")]
+    #[error(transparent)]
+    EsdExpression(#[from] ExpressionError),
+
+    #[cfg(feature = "fmg")]
+    #[error(transparent)]
+    Fmg(#[from] FmgError),
+
+    #[cfg(feature = "fxr")]
+    #[error(transparent)]
+    Fxr(#[from] FxrError),
+
+    #[cfg(feature = "gparam")]
+    #[error(transparent)]
+    Gparam(#[from] GparamError),
+
+    #[cfg(feature = "map")]
+    #[error(transparent)]
+    Grass(#[from] GrassError),
+
+    #[cfg(feature = "havok")]
+    #[error(transparent)]
+    Hkx(#[from] HkxError),
+
+    #[cfg(feature = "lua")]
+    #[error(transparent)]
+    LuaBnd(#[from] LuaBndError),
+
+    #[cfg(feature = "lua")]
+    #[error(transparent)]
+    LuaGnl(#[from] LuaGnlError),
+
+    #[cfg(feature = "lua")]
+    #[error(transparent)]
+    LuaInfo(#[from] LuaInfoError),
+
+    #[cfg(feature = "havok")]
+    #[error(transparent)]
+    MapCollision(#[from] MapCollisionError),
+
+    #[cfg(feature = "material")]
+    #[error(transparent)]
+    Matbin(#[from] MatbinError),
+
+    #[cfg(feature = "map")]
+    #[error(transparent)]
+    Mcg(#[from] McgError),
+
+    #[cfg(feature = "map")]
+    #[error(transparent)]
+    Mcp(#[from] McpError),
+
+    #[cfg(feature = "cutscene")]
+    #[error(transparent)]
+    Mqb(#[from] MqbError),
+
+    #[cfg(feature = "fmg")]
+    #[error(transparent)]
+    MsgBnd(#[from] MsgBndError),
+
+    #[cfg(feature = "material")]
+    #[error(transparent)]
+    Mtd(#[from] MtdError),
+
+    #[cfg(feature = "map")]
+    #[error(transparent)]
+    Nva(#[from] NvaError),
+
+    #[cfg(feature = "map")]
+    #[error(transparent)]
+    Nvm(#[from] NvmError),
+
+    #[cfg(feature = "param")]
+    #[error(transparent)]
+    Param(#[from] ParamError),
+
+    #[cfg(all(feature = "param", feature = "std"))]
+    #[error(transparent)]
+    ParamDef(#[from] ParamDefError),
+
+    #[cfg(all(feature = "param", feature = "std"))]
+    #[error(transparent)]
+    Paramdex(#[from] ParamdexError),
+
+    #[cfg(all(feature = "param", feature = "std"))]
+    #[error(transparent)]
+    Regulation(#[from] RegulationError),
+
+    #[cfg(feature = "save")]
+    #[error(transparent)]
+    Save(#[from] SaveError),
+
+    #[cfg(feature = "sound")]
+    #[error(transparent)]
+    Sound(#[from] SoundError),
+
+    #[cfg(feature = "tae")]
+    #[error(transparent)]
+    Tae(#[from] TaeError),
+
+    #[cfg(feature = "tae")]
+    #[error(transparent)]
+    TaeTemplate(#[from] TaeTemplateError),
+
+    #[cfg(feature = "tpf")]
+    #[error(transparent)]
+    Tpf(#[from] TPFError),
+}
+
+impl FormatError {
+    pub fn malformed(
+        format: &'static str,
+        section: &'static str,
+        offset: u64,
+        reason: impl fmt::Display,
+    ) -> Self {
+        Self::Malformed {
+            format,
+            section,
+            offset,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Parsers built on [`io::Read`] report malformed data as an [`io::ErrorKind::InvalidData`] error
+/// wrapping the [`FormatError`], which is unwrapped again when converting back.
+#[cfg(feature = "std")]
+impl From<FormatError> for io::Error {
+    fn from(value: FormatError) -> Self {
+        match value {
+            FormatError::Io(e) => e,
+            e => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<io::Error> for FormatError {
+    fn from(value: io::Error) -> Self {
+        // OS errors have no inner error, and would lose their code through `into_inner`.
+        if value.get_ref().is_none() {
+            return Self::Io(value);
+        }
+
+        let kind = value.kind();
+        match value
+            .into_inner()
+            .map(|inner| inner.downcast::<FormatError>())
+        {
+            Some(Ok(e)) => *e,
+            Some(Err(inner)) => Self::Io(io::Error::new(kind, inner)),
+            None => Self::Io(kind.into()),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use std::io;
+
+    use crate::error::FormatError;
+
+    #[test]
+    pub fn round_trips_through_io_errors() {
+        let error: io::Error = FormatError::malformed("BND4", "header", 0x9, "big endian").into();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        match FormatError::from(error) {
+            FormatError::Malformed { offset, .. } => assert_eq!(offset, 0x9),
+            e => panic!("unexpected error {e}"),
+        }
+    }
+}
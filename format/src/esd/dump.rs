@@ -0,0 +1,158 @@
+use std::fmt::Write;
+
+use crate::esd::{CommandCall, Condition, Esd, Expression, State, StateGroup};
+
+/// Render an ESD as readable text, with its expressions decoded.
+///
+/// Expressions that fail to decode are shown as their raw bytecode.
+pub fn dump(esd: &Esd) -> String {
+    let mut out = String::new();
+    let _ = writeln!(out, "// {}", esd.name);
+
+    for group in &esd.state_groups {
+        out.push('\n');
+        dump_group(&mut out, group);
+    }
+
+    out
+}
+
+fn dump_group(out: &mut String, group: &StateGroup) {
+    let _ = writeln!(out, "StateGroup({:#x}) {{", group.id);
+
+    for (index, state) in group.states.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+
+        dump_state(out, state);
+    }
+
+    out.push_str("}\n");
+}
+
+fn dump_state(out: &mut String, state: &State) {
+    let _ = writeln!(out, "    State({}) {{", state.id);
+
+    for (label, commands) in [
+        ("entry", &state.entry_commands),
+        ("while", &state.while_commands),
+        ("exit", &state.exit_commands),
+    ] {
+        if commands.is_empty() {
+            continue;
+        }
+
+        let _ = writeln!(out, "        {label}:");
+        for command in commands {
+            dump_command(out, command, 3);
+        }
+    }
+
+    for condition in &state.conditions {
+        dump_condition(out, condition, 2);
+    }
+
+    out.push_str("    }\n");
+}
+
+fn dump_condition(out: &mut String, condition: &Condition, depth: usize) {
+    let indent = "    ".repeat(depth);
+    let target = condition
+        .target_state
+        .map(|state| format!(" => State({state})"))
+        .unwrap_or_default();
+
+    let _ = writeln!(
+        out,
+        "{indent}if {}{target}",
+        expression(&condition.evaluator)
+    );
+
+    for command in &condition.pass_commands {
+        dump_command(out, command, depth + 1);
+    }
+
+    for subcondition in &condition.subconditions {
+        dump_condition(out, subcondition, depth + 1);
+    }
+}
+
+fn dump_command(out: &mut String, command: &CommandCall, depth: usize) {
+    let args = command
+        .args
+        .iter()
+        .map(|arg| expression(arg))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let _ = writeln!(
+        out,
+        "{}c{}_{}({args})",
+        "    ".repeat(depth),
+        command.bank,
+        command.id
+    );
+}
+
+fn expression(bytecode: &[u8]) -> String {
+    match Expression::parse(bytecode) {
+        Ok(expression) => expression.to_string(),
+        Err(_) => {
+            let mut raw = String::from("<");
+            for byte in bytecode {
+                let _ = write!(raw, "{byte:02x}");
+            }
+            raw.push('>');
+
+            raw
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::esd::{dump, CommandCall, Condition, Esd, State, StateGroup};
+
+    #[test]
+    pub fn dumps_states() {
+        let esd = Esd {
+            long_format: true,
+            dark_souls_count: 3,
+            unk: [0; 4],
+            name: "t000001000".to_string(),
+            state_groups: vec![StateGroup {
+                id: 0x7FFFFFFF,
+                states: vec![State {
+                    id: 0,
+                    conditions: vec![Condition {
+                        target_state: Some(1),
+                        pass_commands: vec![],
+                        subconditions: vec![],
+                        evaluator: vec![0x45, 0x41, 0x85, 0x42, 0x95, 0xA1],
+                    }],
+                    entry_commands: vec![CommandCall {
+                        bank: 1,
+                        id: 10,
+                        args: vec![vec![0x41, 0xA1], vec![0x95]],
+                    }],
+                    exit_commands: vec![],
+                    while_commands: vec![],
+                }],
+            }],
+        };
+
+        assert_eq!(
+            dump(&esd),
+            "// t000001000\n\
+             \n\
+             StateGroup(0x7fffffff) {\n    \
+                 State(0) {\n        \
+                     entry:\n            \
+                         c1_10(1, <95>)\n        \
+                     if f5(1) == 2 => State(1)\n    \
+                 }\n\
+             }\n"
+        );
+    }
+}
@@ -0,0 +1,283 @@
+use std::fmt;
+
+use byteorder::{ByteOrder, LE};
+use thiserror::Error;
+
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum ExpressionError {
+    #[error("Expression ended in the middle of the operand of {opcode:#x} at {offset:#x}")]
+    Truncated { offset: usize, opcode: u8 },
+
+    #[error("Opcode {opcode:#x} at {offset:#x} has too few operands")]
+    StackUnderflow { offset: usize, opcode: u8 },
+
+    #[error("Expression left {0} values on the stack")]
+    Unbalanced(usize),
+}
+
+const END: u8 = 0xA1;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Le,
+    Ge,
+    Lt,
+    Gt,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    fn from_opcode(opcode: u8) -> Option<Self> {
+        Some(match opcode {
+            0x8C => Self::Add,
+            0x8E => Self::Sub,
+            0x8F => Self::Mul,
+            0x90 => Self::Div,
+            0x91 => Self::Le,
+            0x92 => Self::Ge,
+            0x93 => Self::Lt,
+            0x94 => Self::Gt,
+            0x95 => Self::Eq,
+            0x96 => Self::Ne,
+            0x98 => Self::And,
+            0x99 => Self::Or,
+            _ => return None,
+        })
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Self::Add => "+",
+            Self::Sub => "-",
+            Self::Mul => "*",
+            Self::Div => "/",
+            Self::Le => "<=",
+            Self::Ge => ">=",
+            Self::Lt => "<",
+            Self::Gt => ">",
+            Self::Eq => "==",
+            Self::Ne => "!=",
+            Self::And => "&&",
+            Self::Or => "||",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum UnaryOp {
+    Negate,
+    Not,
+
+    /// Stops evaluating the expression, with a false result, if the operand is false.
+    AbortIfFalse,
+}
+
+/// A decoded EzState expression.
+///
+/// Expressions are stored as bytecode for a stack machine, this is the tree it evaluates.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Expression {
+    Int(i64),
+    Float(f64),
+    String(String),
+
+    /// A call to a function of the game's EzState environment, e.g. `GetEventFlag`.
+    Call {
+        function: Box<Expression>,
+        args: Vec<Expression>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expression>,
+    },
+    Binary {
+        op: BinaryOp,
+        lhs: Box<Expression>,
+        rhs: Box<Expression>,
+    },
+
+    /// Stores a value in one of the 8 registers, the value is also the result.
+    SetRegister {
+        register: u8,
+        value: Box<Expression>,
+    },
+    GetRegister(u8),
+
+    /// An opcode without a known meaning, which is assumed to push a value.
+    Unknown(u8),
+}
+
+impl Expression {
+    /// Decode the bytecode of an expression, up to its end opcode.
+    pub fn parse(bytecode: &[u8]) -> Result<Self, ExpressionError> {
+        let mut stack: Vec<Expression> = Vec::new();
+        let mut offset = 0;
+
+        while let Some(&opcode) = bytecode.get(offset) {
+            offset += 1;
+
+            let underflow = ExpressionError::StackUnderflow {
+                offset: offset - 1,
+                opcode,
+            };
+            let mut operand = |length: usize| {
+                let bytes =
+                    bytecode
+                        .get(offset..offset + length)
+                        .ok_or(ExpressionError::Truncated {
+                            offset: offset - 1,
+                            opcode,
+                        })?;
+                offset += length;
+                Ok(bytes)
+            };
+
+            let expression = match opcode {
+                0x00..=0x7F => Self::Int(opcode as i64 - 0x40),
+                0x80 => Self::Float(LE::read_f32(operand(4)?) as f64),
+                0x81 => Self::Float(LE::read_f64(operand(8)?)),
+                0x82 => Self::Int(LE::read_i32(operand(4)?) as i64),
+                0x84..=0x8A => {
+                    let arg_count = (opcode - 0x84) as usize;
+                    let args_start = stack
+                        .len()
+                        .checked_sub(arg_count)
+                        .ok_or(underflow.clone())?;
+                    let args = stack.split_off(args_start);
+                    let function = stack.pop().ok_or(underflow)?;
+
+                    Self::Call {
+                        function: Box::new(function),
+                        args,
+                    }
+                }
+                0x8D | 0x9A | 0xB7 => Self::Unary {
+                    op: match opcode {
+                        0x8D => UnaryOp::Negate,
+                        0x9A => UnaryOp::Not,
+                        _ => UnaryOp::AbortIfFalse,
+                    },
+                    operand: Box::new(stack.pop().ok_or(underflow)?),
+                },
+                0xA5 => {
+                    let mut units = Vec::new();
+                    loop {
+                        let unit = LE::read_u16(operand(2)?);
+                        if unit == 0 {
+                            break;
+                        }
+                        units.push(unit);
+                    }
+
+                    Self::String(String::from_utf16_lossy(&units))
+                }
+                0xA7..=0xAE => Self::SetRegister {
+                    register: opcode - 0xA7,
+                    value: Box::new(stack.pop().ok_or(underflow)?),
+                },
+                0xAF..=0xB6 => Self::GetRegister(opcode - 0xAF),
+                END => break,
+                _ => match BinaryOp::from_opcode(opcode) {
+                    Some(op) => {
+                        let rhs = stack.pop().ok_or(underflow.clone())?;
+                        let lhs = stack.pop().ok_or(underflow)?;
+
+                        Self::Binary {
+                            op,
+                            lhs: Box::new(lhs),
+                            rhs: Box::new(rhs),
+                        }
+                    }
+                    None => Self::Unknown(opcode),
+                },
+            };
+
+            stack.push(expression);
+        }
+
+        match (stack.pop(), stack.len()) {
+            (Some(expression), 0) => Ok(expression),
+            (None, _) => Err(ExpressionError::Unbalanced(0)),
+            (Some(_), remaining) => Err(ExpressionError::Unbalanced(remaining + 1)),
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Binary { .. } | Self::SetRegister { .. } => write!(f, "({self})"),
+            _ => write!(f, "{self}"),
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value:?}"),
+            Self::String(value) => write!(f, "{value:?}"),
+            Self::Call { function, args } => {
+                match function.as_ref() {
+                    Self::Int(id) => write!(f, "f{id}(")?,
+                    function => write!(f, "{function}(")?,
+                }
+
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{arg}")?;
+                }
+
+                f.write_str(")")
+            }
+            Self::Unary { op, operand } => {
+                match op {
+                    UnaryOp::Negate => f.write_str("-")?,
+                    UnaryOp::Not => f.write_str("!")?,
+                    UnaryOp::AbortIfFalse => f.write_str("abort_if_false ")?,
+                }
+                operand.fmt_operand(f)
+            }
+            Self::Binary { op, lhs, rhs } => {
+                lhs.fmt_operand(f)?;
+                write!(f, " {} ", op.symbol())?;
+                rhs.fmt_operand(f)
+            }
+            Self::SetRegister { register, value } => write!(f, "r{register} = {value}"),
+            Self::GetRegister(register) => write!(f, "r{register}"),
+            Self::Unknown(opcode) => write!(f, "op_{opcode:#04x}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::esd::expression::{Expression, ExpressionError};
+
+    #[test]
+    pub fn formats_calls_and_operators() {
+        // f5(1) == 2 && !r0
+        let bytecode = [0x45, 0x41, 0x85, 0x42, 0x95, 0xAF, 0x9A, 0x98, 0xA1];
+        let expression = Expression::parse(&bytecode).unwrap();
+
+        assert_eq!(expression.to_string(), "(f5(1) == 2) && !r0");
+        assert_eq!(
+            Expression::parse(&[0x41, 0x95, 0xA1]),
+            Err(ExpressionError::StackUnderflow {
+                offset: 1,
+                opcode: 0x95
+            })
+        );
+    }
+}
@@ -0,0 +1,371 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read, Seek, SeekFrom},
+};
+
+use byteorder::{ByteOrder, ReadBytesExt, LE};
+use thiserror::Error;
+
+pub mod dump;
+pub mod expression;
+
+pub use self::{
+    dump::dump,
+    expression::{BinaryOp, Expression, ExpressionError, UnaryOp},
+};
+
+#[derive(Debug, Error)]
+pub enum EsdError {
+    #[error("Could not read ESD: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unknown ESD magic {0:?}")]
+    UnknownMagic([u8; 4]),
+
+    #[error("ESD data at {offset:#x} is out of bounds")]
+    OutOfBounds { offset: i64 },
+
+    #[error("ESD conditions are nested more than {MAX_CONDITION_DEPTH} deep")]
+    TooDeep,
+}
+
+const SHORT_MAGIC: &[u8; 4] = b"fSSL";
+const LONG_MAGIC: &[u8; 4] = b"fsSL";
+
+/// Offsets in the file are relative to the end of the header.
+const HEADER_SIZE: u64 = 0x6C;
+
+/// Subconditions can be shared between conditions, so guard against a malformed file referencing
+/// itself.
+const MAX_CONDITION_DEPTH: usize = 64;
+
+/// An EzState script (`.esd`), the state machines that drive NPC dialogue, menus and some
+/// character behavior.
+///
+/// The machine is made of state groups, whose states run commands on entry, exit and every frame,
+/// and move to other states when a condition's evaluator expression is true. Expressions and
+/// command arguments are kept as bytecode, see [`Expression::parse`] to decode them.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Esd {
+    /// Set from Dark Souls 3 onwards, offsets and counts are 64 bits wide.
+    pub long_format: bool,
+
+    /// 1 for Dark Souls, 2 for Dark Souls 2, 3 for later games.
+    pub dark_souls_count: u32,
+    pub unk: [i32; 4],
+    pub name: String,
+    pub state_groups: Vec<StateGroup>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StateGroup {
+    pub id: i64,
+    pub states: Vec<State>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct State {
+    pub id: i64,
+    pub conditions: Vec<Condition>,
+    pub entry_commands: Vec<CommandCall>,
+    pub exit_commands: Vec<CommandCall>,
+    pub while_commands: Vec<CommandCall>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Condition {
+    /// ID of the state in the same group to move to when this condition passes.
+    pub target_state: Option<i64>,
+    pub pass_commands: Vec<CommandCall>,
+
+    /// Conditions checked after this one passes, when it doesn't have a target itself.
+    pub subconditions: Vec<Condition>,
+    pub evaluator: Vec<u8>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CommandCall {
+    pub bank: i32,
+    pub id: i32,
+
+    /// The bytecode of each argument expression.
+    pub args: Vec<Vec<u8>>,
+}
+
+/// The data region of an ESD, which everything after the header points into.
+struct EsdData<'a> {
+    data: &'a [u8],
+    long_format: bool,
+
+    /// State IDs by offset, to resolve the target state of conditions.
+    state_ids: HashMap<i64, i64>,
+}
+
+impl<'a> EsdData<'a> {
+    fn varint_size(&self) -> usize {
+        if self.long_format {
+            8
+        } else {
+            4
+        }
+    }
+
+    fn bytes(&self, offset: i64, length: usize) -> Result<&'a [u8], EsdError> {
+        usize::try_from(offset)
+            .ok()
+            .and_then(|start| self.data.get(start..start.checked_add(length)?))
+            .ok_or(EsdError::OutOfBounds { offset })
+    }
+
+    fn varint(&self, offset: i64) -> Result<i64, EsdError> {
+        let bytes = self.bytes(offset, self.varint_size())?;
+
+        Ok(if self.long_format {
+            LE::read_i64(bytes)
+        } else {
+            LE::read_i32(bytes) as i64
+        })
+    }
+
+    /// Read consecutive varints starting at an offset.
+    fn varints<const N: usize>(&self, offset: i64) -> Result<[i64; N], EsdError> {
+        let size = self.varint_size() as i64;
+        let mut values = [0; N];
+        for (i, value) in values.iter_mut().enumerate() {
+            *value = self.varint(offset + i as i64 * size)?;
+        }
+
+        Ok(values)
+    }
+
+    /// Read `count` fixed-size records starting at an offset, an offset of -1 means none.
+    fn records<T>(
+        &self,
+        offset: i64,
+        count: i64,
+        size: i64,
+        mut read: impl FnMut(i64) -> Result<T, EsdError>,
+    ) -> Result<Vec<T>, EsdError> {
+        if offset < 0 {
+            return Ok(Vec::new());
+        }
+
+        (0..count).map(|i| read(offset + i * size)).collect()
+    }
+
+    fn state_size(&self) -> i64 {
+        self.varint_size() as i64 * 9
+    }
+
+    fn state_group(&self, offset: i64) -> Result<StateGroup, EsdError> {
+        let [id, states_offset, state_count, _] = self.varints::<4>(offset)?;
+        let states = self.records(states_offset, state_count, self.state_size(), |offset| {
+            self.state(offset)
+        })?;
+
+        Ok(StateGroup { id, states })
+    }
+
+    fn state(&self, offset: i64) -> Result<State, EsdError> {
+        let [conditions_offset, condition_count] = self.list(offset, 0)?;
+        let [entry_offset, entry_count] = self.list(offset, 1)?;
+        let [exit_offset, exit_count] = self.list(offset, 2)?;
+        let [while_offset, while_count] = self.list(offset, 3)?;
+
+        Ok(State {
+            id: self.varint(offset)?,
+            conditions: self.conditions(conditions_offset, condition_count, 0)?,
+            entry_commands: self.commands(entry_offset, entry_count)?,
+            exit_commands: self.commands(exit_offset, exit_count)?,
+            while_commands: self.commands(while_offset, while_count)?,
+        })
+    }
+
+    /// Read the offset and count of one of the lists that follow the leading varint of a state or
+    /// condition.
+    fn list(&self, record_offset: i64, index: i64) -> Result<[i64; 2], EsdError> {
+        let size = self.varint_size() as i64;
+        self.varints::<2>(record_offset + size * (1 + index * 2))
+    }
+
+    /// Read a list of offsets to conditions.
+    fn conditions(
+        &self,
+        offset: i64,
+        count: i64,
+        depth: usize,
+    ) -> Result<Vec<Condition>, EsdError> {
+        if depth > MAX_CONDITION_DEPTH {
+            return Err(EsdError::TooDeep);
+        }
+
+        let size = self.varint_size() as i64;
+        self.records(offset, count, size, |offset| {
+            self.condition(self.varint(offset)?, depth)
+        })
+    }
+
+    fn condition(&self, offset: i64, depth: usize) -> Result<Condition, EsdError> {
+        let [pass_offset, pass_count] = self.list(offset, 0)?;
+        let [subconditions_offset, subcondition_count] = self.list(offset, 1)?;
+        let [evaluator_offset, evaluator_length] = self.list(offset, 2)?;
+
+        Ok(Condition {
+            target_state: self.state_ids.get(&self.varint(offset)?).copied(),
+            pass_commands: self.commands(pass_offset, pass_count)?,
+            subconditions: self.conditions(subconditions_offset, subcondition_count, depth + 1)?,
+            evaluator: self
+                .bytes(evaluator_offset, evaluator_length as usize)?
+                .to_vec(),
+        })
+    }
+
+    fn commands(&self, offset: i64, count: i64) -> Result<Vec<CommandCall>, EsdError> {
+        let size = 8 + self.varint_size() as i64 * 2;
+        self.records(offset, count, size, |offset| {
+            let ids = self.bytes(offset, 8)?;
+            let [args_offset, arg_count] = self.varints::<2>(offset + 8)?;
+
+            let args = self.records(args_offset, arg_count, size - 8, |offset| {
+                let [arg_offset, arg_length] = self.varints::<2>(offset)?;
+                Ok(self.bytes(arg_offset, arg_length as usize)?.to_vec())
+            })?;
+
+            Ok(CommandCall {
+                bank: LE::read_i32(ids),
+                id: LE::read_i32(&ids[4..]),
+                args,
+            })
+        })
+    }
+}
+
+impl Esd {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, EsdError> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        let long_format = match &magic {
+            LONG_MAGIC => true,
+            SHORT_MAGIC => false,
+            _ => return Err(EsdError::UnknownMagic(magic)),
+        };
+
+        let _unk04 = r.read_u32::<LE>()?;
+        let dark_souls_count = r.read_u32::<LE>()?;
+
+        r.seek(SeekFrom::Start(HEADER_SIZE))?;
+        let mut data = Vec::new();
+        r.read_to_end(&mut data)?;
+
+        let mut esd_data = EsdData {
+            data: &data,
+            long_format,
+            state_ids: HashMap::new(),
+        };
+
+        let unk_bytes = esd_data.bytes(4, 0x10)?;
+        let unk = std::array::from_fn(|i| LE::read_i32(&unk_bytes[i * 4..]));
+
+        let main_offset = if long_format { 0x18 } else { 0x14 };
+        let [groups_offset, group_count, name_offset, name_length] =
+            esd_data.varints::<4>(main_offset)?;
+
+        let name = esd_data
+            .bytes(name_offset, name_length as usize * 2)?
+            .chunks_exact(2)
+            .map(LE::read_u16)
+            .collect::<Vec<_>>();
+
+        // Conditions refer to their target by offset, so collect every state's ID first.
+        let group_size = esd_data.varint_size() as i64 * 4;
+        let mut state_ids = HashMap::new();
+        for group in 0..group_count {
+            let [_, states_offset, state_count, _] =
+                esd_data.varints::<4>(groups_offset + group * group_size)?;
+
+            for state in 0..state_count {
+                let offset = states_offset + state * esd_data.state_size();
+                state_ids.insert(offset, esd_data.varint(offset)?);
+            }
+        }
+        esd_data.state_ids = state_ids;
+
+        let state_groups = esd_data.records(groups_offset, group_count, group_size, |offset| {
+            esd_data.state_group(offset)
+        })?;
+
+        Ok(Self {
+            long_format,
+            dark_souls_count,
+            unk,
+            name: String::from_utf16_lossy(&name),
+            state_groups,
+        })
+    }
+
+    pub fn state_group(&self, id: i64) -> Option<&StateGroup> {
+        self.state_groups.iter().find(|group| group.id == id)
+    }
+}
+
+impl StateGroup {
+    pub fn state(&self, id: i64) -> Option<&State> {
+        self.states.iter().find(|state| state.id == id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::esd::{Esd, Expression};
+
+    #[test]
+    pub fn reads_short_format() {
+        let mut bytes = b"fSSL".to_vec();
+        for value in [
+            1i32, 1, 1, 0x54, 0xA4, 6, 0x2C, 1, 0x10, 1, 0x24, 1, 0x1C, 1, 0x10, 1, 0x8, 1, 0x60,
+            1, 0xA0, 1, 0, 0, 0, 0,
+        ] {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        for value in [
+            // Main struct: unknowns, state groups, name and two unused offsets.
+            1i32, 0, 0, 0, 0, 0x2C, 1, 0xA0, 1, 0, 0, // State group 1, with a single state.
+            1, 0x3C, 1, 0x3C, // State 0: one condition and one entry command.
+            0, 0x60, 1, 0x64, 1, -1, 0, -1, 0,    // Condition offsets.
+            0x74, // Entry command 1:10 with one argument.
+            1, 10, 0x90, 1, // Condition back to state 0.
+            0x3C, -1, 0, -1, 0, 0x9E, 2, // Argument.
+            0x98, 6,
+        ] {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        bytes.push(0x82);
+        bytes.extend(1234i32.to_le_bytes());
+        bytes.extend([0xA1, 0x41, 0xA1]);
+        bytes.extend("t".encode_utf16().flat_map(u16::to_le_bytes));
+
+        let esd = Esd::from_reader(&mut Cursor::new(bytes)).unwrap();
+        let state = esd.state_group(1).unwrap().state(0).unwrap();
+
+        assert_eq!(esd.name, "t");
+        assert_eq!(state.conditions[0].target_state, Some(0));
+        assert_eq!(
+            Expression::parse(&state.conditions[0].evaluator).unwrap(),
+            Expression::Int(1)
+        );
+        assert_eq!(state.entry_commands[0].id, 10);
+        assert_eq!(
+            Expression::parse(&state.entry_commands[0].args[0]).unwrap(),
+            Expression::Int(1234)
+        );
+    }
+}
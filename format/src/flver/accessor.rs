@@ -1,4 +1,4 @@
-use std::{marker::PhantomData, mem::size_of};
+use core::{marker::PhantomData, mem::size_of, slice::ChunksExact};
 
 use bytemuck::Pod;
 
@@ -18,10 +18,11 @@ pub enum VertexAttributeAccessor<'a> {
 }
 
 pub struct VertexAttributeIter<'a, T: Pod> {
-    buffer: &'a [u8],
+    vertices: ChunksExact<'a, u8>,
     attribute_data_offset: usize,
-    attribute_data_end: usize,
-    vertex_size: usize,
+
+    /// Vertices left to read, counted down rather than derived from the remaining buffer.
+    remaining: usize,
     _phantom: PhantomData<T>,
 }
 
@@ -32,14 +33,19 @@ impl<'a, T: Pod> VertexAttributeIter<'a, T> {
         vertex_size: usize,
         vertex_offset: usize,
     ) -> VertexAttributeIter<'a, T> {
-        let attribute_data_offset = vertex_offset;
-        let attribute_data_end = attribute_data_offset + size_of::<T>();
+        // Attributes outside of their vertex yield nothing, and a trailing partial vertex is
+        // skipped by `chunks_exact`.
+        let fits = vertex_size > 0 && vertex_offset + size_of::<T>() <= vertex_size;
+        let vertices = if fits {
+            buffer.chunks_exact(vertex_size)
+        } else {
+            buffer[..0].chunks_exact(1)
+        };
 
         Self {
-            buffer,
-            attribute_data_offset,
-            attribute_data_end,
-            vertex_size,
+            remaining: vertices.len(),
+            vertices,
+            attribute_data_offset: vertex_offset,
             _phantom: Default::default(),
         }
     }
@@ -49,21 +55,40 @@ impl<'a, T: Pod> ExactSizeIterator for VertexAttributeIter<'a, T> {}
 impl<'a, T: Pod> Iterator for VertexAttributeIter<'a, T> {
     type Item = T;
 
+    #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        if self.buffer.is_empty() {
-            return None;
-        }
-
-        let attribute_byte_data = &self.buffer[self.attribute_data_offset..self.attribute_data_end];
-        let data: &T = bytemuck::from_bytes(attribute_byte_data);
+        let vertex = self.vertices.next()?;
+        self.remaining -= 1;
 
-        self.buffer = &self.buffer[self.vertex_size..];
-
-        Some(*data)
+        // Buffers aren't necessarily aligned for the attribute type.
+        let attribute = &vertex[self.attribute_data_offset..][..size_of::<T>()];
+        Some(bytemuck::pod_read_unaligned(attribute))
     }
 
+    #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let remaining = self.buffer.len() / self.vertex_size;
-        (remaining, Some(remaining))
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::flver::accessor::VertexAttributeIter;
+
+    #[test]
+    pub fn reads_attribute_of_each_whole_vertex() {
+        let buffer = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+        let attributes = VertexAttributeIter::<[u8; 2]>::new(&buffer, 4, 1);
+        assert_eq!(attributes.len(), 2);
+        assert!(attributes.eq([[2, 3], [6, 7]]));
+
+        assert_eq!(
+            VertexAttributeIter::<[u8; 2]>::new(&buffer, 4, 3).count(),
+            0
+        );
+        assert_eq!(
+            VertexAttributeIter::<[u8; 2]>::new(&buffer, 0, 0).count(),
+            0
+        );
     }
 }
@@ -0,0 +1,140 @@
+#[repr(u32)]
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+// TODO: these come from soulsformats and probably have documented
+// names in dx12
+pub enum VertexAttributeFormat {
+    Float2 = 0x1,
+    Float3 = 0x2,
+    Float4 = 0x3,
+    Byte4A = 0x10,
+    Byte4B = 0x11,
+    Short2ToFloat2 = 0x12,
+
+    // int to float 127
+    Byte4C = 0x13,
+    UV = 0x15,
+
+    // int to float
+    UVPair = 0x16,
+    ShortBoneIndices = 0x18,
+    Short4ToFloat4A = 0x1A,
+    Short4ToFloat4B = 0x2E,
+    Byte4E = 0x2F,
+    EdgeCompressed = 0xF0,
+}
+
+impl VertexAttributeFormat {
+    /// Size of a single component, or `None` for edge compressed attributes.
+    pub fn datum_size(&self) -> Option<usize> {
+        Some(match self {
+            VertexAttributeFormat::Float2
+            | VertexAttributeFormat::Float3
+            | VertexAttributeFormat::Float4
+            | VertexAttributeFormat::UV
+            | VertexAttributeFormat::UVPair => 4,
+            VertexAttributeFormat::Byte4A
+            | VertexAttributeFormat::Byte4B
+            | VertexAttributeFormat::Byte4C
+            | VertexAttributeFormat::Byte4E => 1,
+            VertexAttributeFormat::Short2ToFloat2
+            | VertexAttributeFormat::ShortBoneIndices
+            | VertexAttributeFormat::Short4ToFloat4A
+            | VertexAttributeFormat::Short4ToFloat4B => 2,
+            VertexAttributeFormat::EdgeCompressed => return None,
+        })
+    }
+
+    /// Number of components, or `None` for edge compressed attributes.
+    pub fn dimensions(&self) -> Option<usize> {
+        Some(match self {
+            VertexAttributeFormat::Float2 => 2,
+            VertexAttributeFormat::Float3 => 3,
+            VertexAttributeFormat::Float4 => 4,
+            VertexAttributeFormat::Byte4A => 4,
+            VertexAttributeFormat::Byte4B => 4,
+            VertexAttributeFormat::Short2ToFloat2 => 2,
+            VertexAttributeFormat::Byte4C => 4,
+            VertexAttributeFormat::UV => 2,
+            VertexAttributeFormat::UVPair => 4,
+            VertexAttributeFormat::ShortBoneIndices => 4,
+            VertexAttributeFormat::Short4ToFloat4A => 4,
+            VertexAttributeFormat::Short4ToFloat4B => 4,
+            VertexAttributeFormat::Byte4E => 4,
+            VertexAttributeFormat::EdgeCompressed => return None,
+        })
+    }
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum VertexAttributeDimensions {
+    Scalar,
+    Vec2,
+    Vec3,
+    Vec4,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum VertexAttributeDataType {
+    F32,
+    U32,
+    U16,
+    I16,
+}
+
+/// Fails with the unknown value.
+impl TryFrom<u32> for VertexAttributeFormat {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, u32> {
+        Ok(match value {
+            0x1 => Self::Float2,
+            0x2 => Self::Float3,
+            0x3 => Self::Float4,
+            0x10 => Self::Byte4A,
+            0x11 => Self::Byte4B,
+            0x12 => Self::Short2ToFloat2,
+            0x13 => Self::Byte4C,
+            0x15 => Self::UV,
+            0x16 => Self::UVPair,
+            0x18 => Self::ShortBoneIndices,
+            0x1A => Self::Short4ToFloat4A,
+            0x2E => Self::Short4ToFloat4B,
+            0x2F => Self::Byte4E,
+            0xF0 => Self::EdgeCompressed,
+            _ => return Err(value),
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum VertexAttributeSemantic {
+    Position,
+    BoneWeights,
+    BoneIndices,
+    Normal,
+    UV,
+    Tangent,
+    Bitangent,
+    VertexColor,
+}
+
+/// Fails with the unknown value.
+impl TryFrom<u32> for VertexAttributeSemantic {
+    type Error = u32;
+
+    fn try_from(value: u32) -> Result<Self, u32> {
+        Ok(match value {
+            0x0 => Self::Position,
+            0x1 => Self::BoneWeights,
+            0x2 => Self::BoneIndices,
+            0x3 => Self::Normal,
+            0x5 => Self::UV,
+            0x6 => Self::Tangent,
+            0x7 => Self::Bitangent,
+            0xA => Self::VertexColor,
+            _ => return Err(value),
+        })
+    }
+}
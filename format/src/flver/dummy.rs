@@ -7,15 +7,15 @@ use crate::{flver::header::FlverHeaderPart, io_ext::zerocopy::Padding};
 #[repr(packed)]
 #[allow(unused)]
 pub struct Dummy<O: ByteOrder> {
-    position: [F32<O>; 3],
-    color: [u8; 4],
-    forward: [F32<O>; 3],
-    ref_id: U16<O>,
-    parent_bone_index: U16<O>,
-    up_vector: [F32<O>; 3],
-    attached_bone_index: u16,
-    flag_1: u8,
-    use_up_vector: u8,
+    pub position: [F32<O>; 3],
+    pub color: [u8; 4],
+    pub forward: [F32<O>; 3],
+    pub ref_id: U16<O>,
+    pub parent_bone_index: U16<O>,
+    pub up_vector: [F32<O>; 3],
+    pub attached_bone_index: u16,
+    pub flag_1: u8,
+    pub use_up_vector: u8,
     _padding1: Padding<16>,
 }
 
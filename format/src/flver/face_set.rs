@@ -14,22 +14,42 @@ pub enum FaceSetIndices<'a, O> {
 #[repr(C)]
 #[allow(unused)]
 pub struct FaceSet<O: ByteOrder> {
-    flags: U32<O>,
-    triangle_strip: u8,
-    cull_back_faces: u8,
-    unk06: U16<O>,
-    pub(crate) index_count: U32<O>,
-    pub(crate) index_offset: U32<O>,
-    unk: U32<O>,
+    pub flags: U32<O>,
+    pub triangle_strip: u8,
+    pub cull_back_faces: u8,
+    pub unk06: U16<O>,
+    pub index_count: U32<O>,
+    pub index_offset: U32<O>,
+    pub unk: U32<O>,
     padding0: Padding<4>,
-    pub(crate) index_size: U32<O>,
+    pub index_size: U32<O>,
     padding1: U32<O>,
 }
 
+const LOD1: u32 = 0x0100_0000;
+const LOD2: u32 = 0x0200_0000;
+const MOTION_BLUR: u32 = 0x8000_0000;
+
 impl<O: ByteOrder> FaceSet<O> {
     pub fn is_lod0(&self) -> bool {
         self.flags.get() == 0
     }
+
+    /// The level of detail of this face set, from 0 for the most detailed to 2, or `None` for the
+    /// variants drawn during motion blur.
+    pub fn lod(&self) -> Option<u8> {
+        let flags = self.flags.get();
+        if flags & MOTION_BLUR != 0 {
+            return None;
+        }
+
+        match flags & (LOD1 | LOD2) {
+            0 => Some(0),
+            LOD1 => Some(1),
+            LOD2 => Some(2),
+            _ => None,
+        }
+    }
 }
 
 impl<O: ByteOrder> FlverHeaderPart for FaceSet<O> {}
@@ -1,4 +1,4 @@
-use std::mem::size_of;
+use core::mem::size_of;
 
 use byteorder::ByteOrder;
 use zerocopy::{AsBytes, FromBytes, FromZeroes, F32, U32};
@@ -11,26 +11,26 @@ use crate::io_ext::zerocopy::Padding;
 pub struct FlverHeader<O: ByteOrder> {
     #[doc(hidden)]
     _padding0: Padding<8>,
-    pub(crate) version: U32<O>,
-    pub(crate) data_offset: U32<O>,
-    pub(crate) data_length: U32<O>,
-    pub(crate) dummy_count: U32<O>,
-    pub(crate) material_count: U32<O>,
-    pub(crate) bone_count: U32<O>,
-    pub(crate) mesh_count: U32<O>,
-    pub(crate) vertex_buffer_count: U32<O>,
-    pub(crate) bounding_box_min: [F32<O>; 3],
-    pub(crate) bounding_box_max: [F32<O>; 3],
-    pub(crate) face_count: U32<O>,
-    pub(crate) total_face_count: U32<O>,
-    pub(crate) vertex_index_size: u8,
-    pub(crate) unicode: u8,
+    pub version: U32<O>,
+    pub data_offset: U32<O>,
+    pub data_length: U32<O>,
+    pub dummy_count: U32<O>,
+    pub material_count: U32<O>,
+    pub bone_count: U32<O>,
+    pub mesh_count: U32<O>,
+    pub vertex_buffer_count: U32<O>,
+    pub bounding_box_min: [F32<O>; 3],
+    pub bounding_box_max: [F32<O>; 3],
+    pub face_count: U32<O>,
+    pub total_face_count: U32<O>,
+    pub vertex_index_size: u8,
+    pub unicode: u8,
     pub(crate) _unk4a: u8,
     pub(crate) _unk4b: u8,
     pub(crate) _unk4c: U32<O>,
-    pub(crate) face_set_count: U32<O>,
-    pub(crate) buffer_layout_count: U32<O>,
-    pub(crate) texture_count: U32<O>,
+    pub face_set_count: U32<O>,
+    pub buffer_layout_count: U32<O>,
+    pub texture_count: U32<O>,
     pub(crate) _unk5c: u8,
     pub(crate) _unk5d: u8,
     #[doc(hidden)]
@@ -85,12 +85,14 @@ pub(crate) trait FlverHeaderPart: FromBytes + FromZeroes + Sized {
     where
         I: Into<u32> + FromBytes + FromZeroes + Copy + 'static,
     {
-        let data = &data[indices_offset..indices_offset + (indices_count * size_of::<I>())];
-        let indices = I::slice_from(data).expect("buffer data was not aligned");
+        // Malformed index lists yield nothing and out of bounds indices are skipped.
+        let indices = data
+            .get(indices_offset..indices_offset + (indices_count * size_of::<I>()))
+            .and_then(I::slice_from)
+            .unwrap_or_default();
 
-        indices.iter().map(|index| {
-            let index = *index;
-            &parts[index.into() as usize]
-        })
+        indices
+            .iter()
+            .filter_map(|index| parts.get((*index).into() as usize))
     }
 }
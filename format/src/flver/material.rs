@@ -7,14 +7,14 @@ use crate::flver::header::FlverHeaderPart;
 #[repr(packed)]
 #[allow(unused)]
 pub struct Material<O: ByteOrder> {
-    name_offset: U32<O>,
-    mtd_name_offset: U32<O>,
-    texture_count: U32<O>,
-    texture_index: U32<O>,
-    flags: U32<O>,
-    gx_offset: U32<O>,
-    unk18: U32<O>,
-    unk1c: U32<O>,
+    pub name_offset: U32<O>,
+    pub mtd_name_offset: U32<O>,
+    pub texture_count: U32<O>,
+    pub texture_index: U32<O>,
+    pub flags: U32<O>,
+    pub gx_offset: U32<O>,
+    pub unk18: U32<O>,
+    pub unk1c: U32<O>,
 }
 
 impl<O: ByteOrder> FlverHeaderPart for Material<O> {}
@@ -1,37 +1,45 @@
-use std::{
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::{
     fmt::{Debug, Formatter},
-    io::Read,
     ops::Deref,
 };
 
 use byteorder::{ByteOrder, LE};
 use header::FlverHeader;
+use tracing::instrument;
 use zerocopy::{FromBytes, Ref, U16, U32};
 
 use crate::{
+    error::FormatError,
     flver::{
         accessor::VertexAttributeAccessor,
+        attribute::VertexAttributeFormat,
         bone::Bone,
         dummy::Dummy,
         face_set::{FaceSet, FaceSetIndices},
         header::FlverHeaderPart,
         material::Material,
         mesh::Mesh,
-        reader::VertexAttributeFormat,
+        string::FlverStr,
         texture::Texture,
         vertex_buffer::{VertexBuffer, VertexBufferAttribute, VertexBufferLayout},
     },
-    io_ext::ReadFormatsExt,
+    io_ext::ByteReader,
 };
 
 pub mod accessor;
+pub mod attribute;
 pub mod bone;
 pub mod dummy;
 pub mod face_set;
-mod header;
+pub mod header;
 pub mod material;
 pub mod mesh;
+#[cfg(feature = "std")]
 pub mod reader;
+pub mod string;
 pub mod texture;
 pub mod vertex_buffer;
 
@@ -71,7 +79,9 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
         let index_size = face_set.index_size.get() as usize;
         let index_count = face_set.index_count.get() as usize;
         let index_offset = face_set.index_offset.get() as usize;
-        let index_data = &self.data[index_offset..index_offset + (index_size / 8 * index_count)];
+        let index_data = self
+            .data
+            .get(index_offset..index_offset + (index_size / 8 * index_count))?;
 
         Some(match face_set.index_size.get() {
             8 => FaceSetIndices::U8(index_data),
@@ -99,28 +109,111 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
         )
     }
 
+    pub fn header(&self) -> &'a FlverHeader<O> {
+        self.header
+    }
+
+    /// The data region, which vertex buffers and face set indices are relative to.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    pub fn bones(&self) -> &'a [Bone<O>] {
+        self.bones
+    }
+
+    pub fn dummies(&self) -> &'a [Dummy<O>] {
+        self.dummys
+    }
+
+    pub fn materials(&self) -> &'a [Material<O>] {
+        self.materials
+    }
+
+    pub fn textures(&self) -> &'a [Texture<O>] {
+        self.textures
+    }
+
+    pub fn mesh_material(&self, mesh: &Mesh<O>) -> Option<&'a Material<O>> {
+        self.materials.get(mesh.material_index.get() as usize)
+    }
+
+    pub fn material_name(&self, material: &Material<O>) -> Option<String> {
+        self.string_at(material.name_offset.get())
+    }
+
+    /// The path of the material definition a material uses: an MTD before Elden Ring, and the
+    /// `.matxml` source of a MATBIN since.
+    pub fn material_path(&self, material: &Material<O>) -> Option<String> {
+        self.string_at(material.mtd_name_offset.get())
+    }
+
+    pub fn material_textures(&self, material: &Material<O>) -> &'a [Texture<O>] {
+        let start = material.texture_index.get() as usize;
+        let end = start + material.texture_count.get() as usize;
+
+        self.textures.get(start..end).unwrap_or_default()
+    }
+
+    pub fn texture_path(&self, texture: &Texture<O>) -> Option<String> {
+        self.string_at(texture.path_offset.get())
+    }
+
+    pub fn texture_type(&self, texture: &Texture<O>) -> Option<String> {
+        self.string_at(texture.type_offset.get())
+    }
+
+    /// Borrow the null-terminated string at `offset`, such as a [`Material::name_offset`].
+    pub fn string(&self, offset: u32) -> Option<FlverStr<'a, O>> {
+        let bytes = self.bytes.get(offset as usize..)?;
+
+        if self.header.unicode != 0 {
+            let length = bytes
+                .chunks_exact(2)
+                .position(|unit| unit == [0, 0])
+                .unwrap_or(bytes.len() / 2);
+
+            U16::slice_from(&bytes[..length * 2]).map(FlverStr::Utf16)
+        } else {
+            let length = bytes.iter().position(|byte| *byte == 0)?;
+
+            Some(FlverStr::ShiftJis(&bytes[..length]))
+        }
+    }
+
+    fn string_at(&self, offset: u32) -> Option<String> {
+        self.string(offset)?.decode()
+    }
+
     pub fn vertex_attributes(
         &self,
         vertex_buffer_layout: &'a VertexBufferLayout<O>,
-    ) -> &'a [VertexBufferAttribute<O>] {
+    ) -> Result<&'a [VertexBufferAttribute<O>], FormatError> {
         let attribute_count = vertex_buffer_layout.member_count.get() as usize;
         let attribute_offset = vertex_buffer_layout.member_offset.get() as usize;
-        let attributes_length = std::mem::size_of::<VertexBufferLayout<O>>() * attribute_count;
+        let attributes_length = core::mem::size_of::<VertexBufferLayout<O>>() * attribute_count;
 
-        VertexBufferAttribute::slice_from(
-            &self.bytes[attribute_offset..attribute_offset + attributes_length],
-        )
-        .unwrap()
+        self.bytes
+            .get(attribute_offset..attribute_offset + attributes_length)
+            .and_then(VertexBufferAttribute::slice_from)
+            .ok_or_else(|| {
+                FormatError::malformed(
+                    "FLVER",
+                    "buffer layout",
+                    attribute_offset as u64,
+                    format!("{attribute_count} attributes are out of bounds or unaligned"),
+                )
+            })
     }
 
     pub fn vertex_attribute_accessor(
         &self,
         buffer: &VertexBuffer<O>,
         attribute: &VertexBufferAttribute<O>,
-    ) -> VertexAttributeAccessor<'a> {
+    ) -> Result<VertexAttributeAccessor<'a>, FormatError> {
         use crate::flver::{
             accessor::{VertexAttributeAccessor as Accessor, VertexAttributeIter as Iter},
-            reader::VertexAttributeFormat::{
+            attribute::VertexAttributeFormat::{
                 Byte4A, Byte4B, Byte4C, Float2, Float3, Float4, Short2ToFloat2, Short4ToFloat4A,
                 Short4ToFloat4B, UVPair, UV,
             },
@@ -129,11 +222,26 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
         let buffer_offset = buffer.buffer_offset.get() as usize;
         let buffer_length = buffer.buffer_length.get() as usize;
 
-        let data = &self.data[buffer_offset..buffer_offset + buffer_length];
+        let malformed = |reason: String| {
+            FormatError::malformed("FLVER", "vertex buffer", buffer_offset as u64, reason)
+        };
+
+        let data = self
+            .data
+            .get(buffer_offset..buffer_offset + buffer_length)
+            .ok_or_else(|| malformed(format!("{buffer_length:#x} bytes are out of bounds")))?;
         let vertex_size = buffer.vertex_size.get() as usize;
         let vertex_offset = attribute.struct_offset.get() as usize;
+        if vertex_offset >= vertex_size {
+            return Err(malformed(format!(
+                "attribute at {vertex_offset:#x} is outside of {vertex_size:#x} byte vertices"
+            )));
+        }
 
-        match VertexAttributeFormat::from(attribute.format_id.get()) {
+        let format = VertexAttributeFormat::try_from(attribute.format_id.get())
+            .map_err(|format| malformed(format!("unknown attribute format {format:#x}")))?;
+
+        Ok(match format {
             Float3 => Accessor::Float3(Iter::new(data, vertex_size, vertex_offset)),
             Float2 => Accessor::Float2(Iter::new(data, vertex_size, vertex_offset)),
             Float4 => Accessor::Float4(Iter::new(data, vertex_size, vertex_offset)),
@@ -149,8 +257,12 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
             Short4ToFloat4B => {
                 Accessor::Short4ToFloat4B(Iter::new(data, vertex_size, vertex_offset))
             }
-            _ => unimplemented!(),
-        }
+            format => {
+                return Err(malformed(format!(
+                    "unsupported attribute format {format:?}"
+                )))
+            }
+        })
     }
 
     fn parse_no_verify(bytes: &'a [u8]) -> Option<Self> {
@@ -171,7 +283,7 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
         let (textures, _) = Texture::<O>::slice_from_prefix(next, header.texture_count())?;
         let data_offset = header.data_offset.get() as usize;
         let data_end = data_offset + header.data_length.get() as usize;
-        let data = &bytes[data_offset..data_end];
+        let data = bytes.get(data_offset..data_end)?;
 
         Some(Self {
             header,
@@ -188,26 +300,24 @@ impl<'a, O: ByteOrder + 'static> FlverInner<'a, O> {
         })
     }
 
-    pub fn parse(data: &'a [u8]) -> Result<Self, std::io::Error> {
-        let mut header = &data[..8];
-        header.read_magic(b"FLVER\0")?;
-
-        let mut endianness = vec![0x0u8; 2];
-        header.read_exact(&mut endianness)?;
+    /// View a FLVER in place. Every part is borrowed from `data`, which can be a memory-mapped
+    /// file, so scanning many models copies nothing beyond what is decoded.
+    #[instrument(name = "flver", skip_all, fields(size = data.len()))]
+    pub fn parse(data: &'a [u8]) -> Result<Self, FormatError> {
+        let mut r = ByteReader::new("FLVER", data);
+        r.read_magic(b"FLVER\0")?;
 
-        let is_little_endian = endianness == [0x4c, 0x00];
-        if !is_little_endian {
-            return Err(std::io::Error::other(
-                "only little endian FLVERs are supported",
-            ));
+        if r.read_array::<2>()? != [0x4c, 0x00] {
+            return Err(r.malformed(6, "only little endian FLVERs are supported"));
         }
 
-        Self::parse_no_verify(data).ok_or_else(|| std::io::Error::other("FLVER data is unaligned"))
+        Self::parse_no_verify(data)
+            .ok_or_else(|| r.malformed(0, "FLVER data is truncated or unaligned"))
     }
 }
 
 impl<'a, O: ByteOrder + 'static> Debug for FlverInner<'a, O> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Flver")
             .field("version", &self.version.get())
             .field("data_offset", &self.data_offset.get())
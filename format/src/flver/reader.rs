@@ -1,25 +1,49 @@
 use std::{
+    cell::{Cell, RefCell},
     fmt::{Debug, Formatter},
     io,
     io::SeekFrom,
 };
 
 use byteorder::{ReadBytesExt, LE};
+use tracing::{debug, instrument};
 
-use crate::io_ext::ReadFormatsExt;
+pub use crate::flver::attribute::{
+    VertexAttributeDataType, VertexAttributeDimensions, VertexAttributeFormat,
+    VertexAttributeSemantic,
+};
+use crate::{
+    diagnostics::Diagnostics, error::FormatError, io_ext::ReadFormatsExt, telemetry::UnknownValue,
+};
 
 const _ALLOWED_VERSIONS: [u32; 1] = [
     0x2001A, // Elden Ring
 ];
 
-pub struct FLVERPartContext {
+pub struct FLVERPartContext<'a> {
     pub data_offset: u32,
+    pub diagnostics: RefCell<&'a mut Diagnostics>,
+
+    /// Set once the file was found to end early, after which no more parts are read.
+    pub truncated: Cell<bool>,
+}
+
+impl<'a> FLVERPartContext<'a> {
+    /// Report a recoverable irregularity, see [`Diagnostics::report`].
+    pub fn report(&self, error: FormatError) -> io::Result<()> {
+        Ok(self.diagnostics.borrow_mut().report(error)?)
+    }
+
+    /// Record the value of an unknown field, see [`Diagnostics::observe`].
+    pub fn observe(&self, field: &'static str, value: impl UnknownValue) {
+        self.diagnostics.borrow_mut().observe("FLVER", field, value);
+    }
 }
 
 pub trait FLVERPartReader {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        c: &FLVERPartContext,
+        c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error>
     where
         Self: Sized;
@@ -44,6 +68,7 @@ impl Debug for FLVER {
     }
 }
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVER {
     pub version: u32,
     pub data_offset: u32,
@@ -68,6 +93,17 @@ pub struct FLVER {
 
 impl FLVER {
     pub fn from_reader(r: &mut (impl io::Read + io::Seek)) -> Result<Self, io::Error> {
+        Self::from_reader_with(r, &mut Diagnostics::default())
+    }
+
+    /// Read a FLVER, recovering from unexpected non-zero fields, unknown index sizes, unknown
+    /// vertex attributes and truncation when `diagnostics` is lenient. Unknown attributes are left
+    /// out of their layout, and parts from where the file ends onwards are left out entirely.
+    #[instrument(name = "flver", skip_all)]
+    pub fn from_reader_with(
+        r: &mut (impl io::Read + io::Seek),
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self, io::Error> {
         let mut magic = vec![0x0u8; 6];
         r.read_exact(&mut magic)?;
 
@@ -82,7 +118,11 @@ impl FLVER {
 
         let data_offset = r.read_u32::<LE>()?;
         let data_length = r.read_u32::<LE>()?;
-        let part_context = FLVERPartContext { data_offset };
+        let part_context = FLVERPartContext {
+            data_offset,
+            diagnostics: RefCell::new(diagnostics),
+            truncated: Cell::new(false),
+        };
 
         let dummy_count = r.read_u32::<LE>()?;
         let material_count = r.read_u32::<LE>()?;
@@ -98,38 +138,56 @@ impl FLVER {
         let vertex_index_size = r.read_u8()?;
 
         let _unicode = r.read_u8()?;
-        let _unk4a = r.read_u8()?;
-        let _unk4b = r.read_u8()?;
-        let _unk4c = r.read_u32::<LE>()?;
+        let unk4a = r.read_u8()?;
+        let unk4b = r.read_u8()?;
+        let unk4c = r.read_u32::<LE>()?;
+        part_context.observe("unk4a", unk4a);
+        part_context.observe("unk4b", unk4b);
+        part_context.observe("unk4c", unk4c);
 
         let face_set_count = r.read_u32::<LE>()?;
         let buffer_layout_count = r.read_u32::<LE>()?;
         let texture_count = r.read_u32::<LE>()?;
 
-        let _unk5c = r.read_u8()?;
-        let _unk5d = r.read_u8()?;
+        let unk5c = r.read_u8()?;
+        let unk5d = r.read_u8()?;
         r.read_u8()?;
         r.read_u8()?;
         r.read_u32::<LE>()?;
         r.read_u32::<LE>()?;
-        let _unk68 = r.read_u32::<LE>()?;
-        println!("unk68: {}", _unk68);
+        let unk68 = r.read_u32::<LE>()?;
+        part_context.observe("unk5c", unk5c);
+        part_context.observe("unk5d", unk5d);
+        part_context.observe("unk68", unk68);
         r.read_u32::<LE>()?;
         r.read_u32::<LE>()?;
         r.read_u32::<LE>()?;
         r.read_u32::<LE>()?;
         r.read_u32::<LE>()?;
 
-        let dummies = read_vec::<FLVERDummy>(r, &part_context, dummy_count as usize)?;
-        let materials = read_vec::<FLVERMaterial>(r, &part_context, material_count as usize)?;
-        let bones = read_vec::<FLVERBone>(r, &part_context, bone_count as usize)?;
-        let meshes = read_vec::<FLVERMesh>(r, &part_context, mesh_count as usize)?;
-        let face_sets = read_vec::<FLVERFaceSet>(r, &part_context, face_set_count as usize)?;
-        let vertex_buffers =
-            read_vec::<VertexBuffer>(r, &part_context, vertex_buffer_count as usize)?;
-        let buffer_layouts =
-            read_vec::<VertexBufferLayout>(r, &part_context, buffer_layout_count as usize)?;
-        let textures = read_vec::<FLVERTexture>(r, &part_context, texture_count as usize)?;
+        debug!(version, mesh_count, vertex_buffer_count, "read header");
+
+        let dummies = read_parts::<FLVERDummy>(r, &part_context, dummy_count as usize, "dummy")?;
+        let materials =
+            read_parts::<FLVERMaterial>(r, &part_context, material_count as usize, "material")?;
+        let bones = read_parts::<FLVERBone>(r, &part_context, bone_count as usize, "bone")?;
+        let meshes = read_parts::<FLVERMesh>(r, &part_context, mesh_count as usize, "mesh")?;
+        let face_sets =
+            read_parts::<FLVERFaceSet>(r, &part_context, face_set_count as usize, "face set")?;
+        let vertex_buffers = read_parts::<VertexBuffer>(
+            r,
+            &part_context,
+            vertex_buffer_count as usize,
+            "vertex buffer",
+        )?;
+        let buffer_layouts = read_parts::<VertexBufferLayout>(
+            r,
+            &part_context,
+            buffer_layout_count as usize,
+            "buffer layout",
+        )?;
+        let textures =
+            read_parts::<FLVERTexture>(r, &part_context, texture_count as usize, "texture")?;
 
         Ok(Self {
             version,
@@ -156,6 +214,7 @@ impl FLVER {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERVector3 {
     pub x: f32,
     pub y: f32,
@@ -165,7 +224,7 @@ pub struct FLVERVector3 {
 impl FLVERPartReader for FLVERVector3 {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        _c: &FLVERPartContext,
+        _c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         Ok(Self {
             x: r.read_f32::<LE>()?,
@@ -176,6 +235,7 @@ impl FLVERPartReader for FLVERVector3 {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERVector2 {
     pub x: f32,
     pub y: f32,
@@ -184,7 +244,7 @@ pub struct FLVERVector2 {
 impl FLVERPartReader for FLVERVector2 {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        _c: &FLVERPartContext,
+        _c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         Ok(Self {
             x: r.read_f32::<LE>()?,
@@ -194,6 +254,7 @@ impl FLVERPartReader for FLVERVector2 {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERColor {
     pub r: u8,
     pub g: u8,
@@ -204,7 +265,7 @@ pub struct FLVERColor {
 impl FLVERPartReader for FLVERColor {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        _c: &FLVERPartContext,
+        _c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         Ok(Self {
             r: r.read_u8()?,
@@ -216,6 +277,7 @@ impl FLVERPartReader for FLVERColor {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERDummy {
     pub position: FLVERVector3,
     pub color: FLVERColor,
@@ -237,9 +299,9 @@ pub struct FLVERDummy {
 impl FLVERPartReader for FLVERDummy {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        c: &FLVERPartContext,
+        c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
-        Ok(Self {
+        let dummy = Self {
             position: FLVERVector3::from_reader(r, c)?,
             color: FLVERColor::from_reader(r, c)?,
             forward: FLVERVector3::from_reader(r, c)?,
@@ -253,11 +315,18 @@ impl FLVERPartReader for FLVERDummy {
             unk34: r.read_u32::<LE>()?,
             unk38: r.read_u32::<LE>()?,
             unk3c: r.read_u32::<LE>()?,
-        })
+        };
+        c.observe("dummy unk30", dummy.unk30);
+        c.observe("dummy unk34", dummy.unk34);
+        c.observe("dummy unk38", dummy.unk38);
+        c.observe("dummy unk3c", dummy.unk3c);
+
+        Ok(dummy)
     }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERMaterial {
     pub name: String,
     pub mtd: String,
@@ -272,7 +341,7 @@ pub struct FLVERMaterial {
 impl FLVERPartReader for FLVERMaterial {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        _c: &FLVERPartContext,
+        c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         let name_offset = r.read_u32::<LE>()?;
         let mtd_offset = r.read_u32::<LE>()?;
@@ -290,6 +359,8 @@ impl FLVERPartReader for FLVERMaterial {
         let gx_offset = r.read_u32::<LE>()?;
         let unk18 = r.read_u32::<LE>()?;
         let unk1c = r.read_u32::<LE>()?;
+        c.observe("material unk18", unk18);
+        c.observe("material unk1c", unk1c);
 
         Ok(Self {
             name,
@@ -305,6 +376,7 @@ impl FLVERPartReader for FLVERMaterial {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERBone {
     pub name: String,
     pub bounding_box_min: FLVERVector3,
@@ -322,7 +394,7 @@ pub struct FLVERBone {
 impl FLVERPartReader for FLVERBone {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        c: &FLVERPartContext,
+        c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         let translation = FLVERVector3::from_reader(r, c)?;
         let name_offset = r.read_u32::<LE>()?;
@@ -335,6 +407,7 @@ impl FLVERPartReader for FLVERBone {
         let previous_sibling_index = r.read_u16::<LE>()?;
         let bounding_box_min = FLVERVector3::from_reader(r, c)?;
         let unk3c = r.read_u32::<LE>()?;
+        c.observe("bone unk3c", unk3c);
         let bounding_box_max = FLVERVector3::from_reader(r, c)?;
 
         // Deal with FS garbage zeroes
@@ -362,6 +435,7 @@ impl FLVERPartReader for FLVERBone {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERMesh {
     pub dynamic: bool,
     pub material_index: u32,
@@ -375,16 +449,16 @@ pub struct FLVERMesh {
 impl FLVERPartReader for FLVERMesh {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        c: &FLVERPartContext,
+        c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         let dynamic = r.read_u8()? == 0x1;
-        assert!(r.read_u8()? == 0x0);
-        assert!(r.read_u8()? == 0x0);
-        assert!(r.read_u8()? == 0x0);
+        read_zero_u8(r, c, "mesh")?;
+        read_zero_u8(r, c, "mesh")?;
+        read_zero_u8(r, c, "mesh")?;
 
         let material_index = r.read_u32::<LE>()?;
-        assert!(r.read_u32::<LE>()? == 0x0);
-        assert!(r.read_u32::<LE>()? == 0x0);
+        read_zero_u32(r, c, "mesh")?;
+        read_zero_u32(r, c, "mesh")?;
         let default_bone_index = r.read_u32::<LE>()?;
         let bone_count = r.read_u32::<LE>()?;
         let bounding_box_offset = r.read_u32::<LE>()?;
@@ -420,6 +494,7 @@ impl FLVERPartReader for FLVERMesh {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERFaceSetFlags(u32);
 
 impl From<u32> for FLVERFaceSetFlags {
@@ -440,6 +515,7 @@ impl FLVERFaceSetFlags {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERFaceSet {
     pub flags: FLVERFaceSetFlags,
     pub triangle_strip: bool,
@@ -449,6 +525,7 @@ pub struct FLVERFaceSet {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum FLVERFaceSetIndices {
     Byte0,
     Byte1(Vec<u8>),
@@ -459,18 +536,20 @@ pub enum FLVERFaceSetIndices {
 impl FLVERPartReader for FLVERFaceSet {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        c: &FLVERPartContext,
+        c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
+        let offset = r.stream_position()?;
         let flags = r.read_u32::<LE>()?.into();
         let triangle_strip = r.read_u8()? == 0x1;
         let cull_back_faces = r.read_u8()? == 0x1;
         let unk06 = r.read_u16::<LE>()?;
+        c.observe("face set unk06", unk06);
         let index_count = r.read_u32::<LE>()?;
         let index_offset = r.read_u32::<LE>()?;
         r.read_u32::<LE>()?;
-        assert!(r.read_u32::<LE>()? == 0x0);
+        read_zero_u32(r, c, "face set")?;
         let index_size = r.read_u32::<LE>()?;
-        assert!(r.read_u32::<LE>()? == 0x0);
+        read_zero_u32(r, c, "face set")?;
 
         let current = r.stream_position()?;
         r.seek(SeekFrom::Start(index_offset as u64 + c.data_offset as u64))?;
@@ -479,7 +558,16 @@ impl FLVERPartReader for FLVERFaceSet {
             8 => FLVERFaceSetIndices::Byte1(read_vec::<u8>(r, c, index_count as usize)?),
             16 => FLVERFaceSetIndices::Byte2(read_vec::<u16>(r, c, index_count as usize)?),
             32 => FLVERFaceSetIndices::Byte4(read_vec::<u32>(r, c, index_count as usize)?),
-            _ => panic!("Unhandled index size {}", index_size),
+            _ => {
+                c.report(FormatError::malformed(
+                    "FLVER",
+                    "face set",
+                    offset,
+                    format!("unhandled index size {index_size}"),
+                ))?;
+
+                FLVERFaceSetIndices::Byte0
+            }
         };
         r.seek(SeekFrom::Start(current))?;
 
@@ -494,7 +582,7 @@ impl FLVERPartReader for FLVERFaceSet {
 }
 
 #[derive(Debug)]
-
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct VertexBuffer {
     pub buffer_index: u32,
     pub layout_index: u32,
@@ -507,14 +595,14 @@ pub struct VertexBuffer {
 impl FLVERPartReader for VertexBuffer {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        _c: &FLVERPartContext,
+        c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         let buffer_index = r.read_u32::<LE>()?;
         let layout_index = r.read_u32::<LE>()?;
         let vertex_size = r.read_u32::<LE>()?;
         let vertex_count = r.read_u32::<LE>()?;
-        assert!(r.read_u32::<LE>()? == 0x0);
-        assert!(r.read_u32::<LE>()? == 0x0);
+        read_zero_u32(r, c, "vertex buffer")?;
+        read_zero_u32(r, c, "vertex buffer")?;
         let buffer_length = r.read_u32::<LE>()?;
         let buffer_offset = r.read_u32::<LE>()?;
 
@@ -530,6 +618,7 @@ impl FLVERPartReader for VertexBuffer {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct VertexBufferLayout {
     pub members: Vec<FLVERBufferLayoutMember>,
 }
@@ -537,17 +626,29 @@ pub struct VertexBufferLayout {
 impl FLVERPartReader for VertexBufferLayout {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        c: &FLVERPartContext,
+        c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         let member_count = r.read_u32::<LE>()?;
-        assert!(r.read_u32::<LE>()? == 0x0);
-        assert!(r.read_u32::<LE>()? == 0x0);
+        read_zero_u32(r, c, "buffer layout")?;
+        read_zero_u32(r, c, "buffer layout")?;
         let member_offset = r.read_u32::<LE>()?;
 
         let current = r.stream_position()?;
 
         r.seek(SeekFrom::Start(member_offset as u64))?;
-        let members = read_vec::<FLVERBufferLayoutMember>(r, c, member_count as usize)?;
+        let mut members = Vec::with_capacity(member_count as usize);
+        for index in 0..member_count as u64 {
+            match FLVERBufferLayoutMember::from_reader(r, c) {
+                Ok(member) => members.push(member),
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    c.report(e.into())?;
+                    r.seek(SeekFrom::Start(
+                        member_offset as u64 + (index + 1) * BUFFER_LAYOUT_MEMBER_SIZE,
+                    ))?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
 
         r.seek(SeekFrom::Start(current))?;
 
@@ -564,135 +665,10 @@ impl VertexBufferLayout {
     }
 }
 
-#[repr(u32)]
-#[derive(Debug, PartialEq, Eq)]
-// TODO: these come from soulsformats and probably have documented
-// names in dx12
-pub enum VertexAttributeFormat {
-    Float2 = 0x1,
-    Float3 = 0x2,
-    Float4 = 0x3,
-    Byte4A = 0x10,
-    Byte4B = 0x11,
-    Short2ToFloat2 = 0x12,
-
-    // int to float 127
-    Byte4C = 0x13,
-    UV = 0x15,
-
-    // int to float
-    UVPair = 0x16,
-    ShortBoneIndices = 0x18,
-    Short4ToFloat4A = 0x1A,
-    Short4ToFloat4B = 0x2E,
-    Byte4E = 0x2F,
-    EdgeCompressed = 0xF0,
-}
-
-impl VertexAttributeFormat {
-    pub fn datum_size(&self) -> usize {
-        match self {
-            VertexAttributeFormat::Float2
-            | VertexAttributeFormat::Float3
-            | VertexAttributeFormat::Float4
-            | VertexAttributeFormat::UV
-            | VertexAttributeFormat::UVPair => 4,
-            VertexAttributeFormat::Byte4A
-            | VertexAttributeFormat::Byte4B
-            | VertexAttributeFormat::Byte4C
-            | VertexAttributeFormat::Byte4E => 1,
-            VertexAttributeFormat::Short2ToFloat2
-            | VertexAttributeFormat::ShortBoneIndices
-            | VertexAttributeFormat::Short4ToFloat4A
-            | VertexAttributeFormat::Short4ToFloat4B => 2,
-            _ => unimplemented!(),
-        }
-    }
-    pub fn dimensions(&self) -> usize {
-        match self {
-            VertexAttributeFormat::Float2 => 2,
-            VertexAttributeFormat::Float3 => 3,
-            VertexAttributeFormat::Float4 => 4,
-            VertexAttributeFormat::Byte4A => 4,
-            VertexAttributeFormat::Byte4B => 4,
-            VertexAttributeFormat::Short2ToFloat2 => 2,
-            VertexAttributeFormat::Byte4C => 4,
-            VertexAttributeFormat::UV => 2,
-            VertexAttributeFormat::UVPair => 4,
-            VertexAttributeFormat::ShortBoneIndices => 4,
-            VertexAttributeFormat::Short4ToFloat4A => 4,
-            VertexAttributeFormat::Short4ToFloat4B => 4,
-            VertexAttributeFormat::Byte4E => 4,
-            VertexAttributeFormat::EdgeCompressed => unimplemented!(),
-        }
-    }
-}
-
-pub enum VertexAttributeDimensions {
-    Scalar,
-    Vec2,
-    Vec3,
-    Vec4,
-}
-
-pub enum VertexAttributeDataType {
-    F32,
-    U32,
-    U16,
-    I16,
-}
-
-impl From<u32> for VertexAttributeFormat {
-    fn from(value: u32) -> Self {
-        match value {
-            0x1 => Self::Float2,
-            0x2 => Self::Float3,
-            0x3 => Self::Float4,
-            0x10 => Self::Byte4A,
-            0x11 => Self::Byte4B,
-            0x12 => Self::Short2ToFloat2,
-            0x13 => Self::Byte4C,
-            0x15 => Self::UV,
-            0x16 => Self::UVPair,
-            0x18 => Self::ShortBoneIndices,
-            0x1A => Self::Short4ToFloat4A,
-            0x2E => Self::Short4ToFloat4B,
-            0x2F => Self::Byte4E,
-            0xF0 => Self::EdgeCompressed,
-            _ => panic!("Unknown storage type {}", value),
-        }
-    }
-}
-
-#[derive(Copy, Clone, Debug, PartialEq)]
-pub enum VertexAttributeSemantic {
-    Position,
-    BoneWeights,
-    BoneIndices,
-    Normal,
-    UV,
-    Tangent,
-    Bitangent,
-    VertexColor,
-}
-
-impl From<u32> for VertexAttributeSemantic {
-    fn from(value: u32) -> Self {
-        match value {
-            0x0 => Self::Position,
-            0x1 => Self::BoneWeights,
-            0x2 => Self::BoneIndices,
-            0x3 => Self::Normal,
-            0x5 => Self::UV,
-            0x6 => Self::Tangent,
-            0x7 => Self::Bitangent,
-            0xA => Self::VertexColor,
-            _ => panic!("Unknown member type {}", value),
-        }
-    }
-}
+const BUFFER_LAYOUT_MEMBER_SIZE: u64 = 0x14;
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERBufferLayoutMember {
     pub unk0: u32,
     pub struct_offset: u32,
@@ -704,19 +680,39 @@ pub struct FLVERBufferLayoutMember {
 impl FLVERPartReader for FLVERBufferLayoutMember {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        _c: &FLVERPartContext,
+        c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
+        let offset = r.stream_position()?;
+        let unk0 = r.read_u32::<LE>()?;
+        c.observe("layout member unk0", unk0);
+        let struct_offset = r.read_u32::<LE>()?;
+        let unknown = |kind: &str, value: u32| {
+            FormatError::malformed(
+                "FLVER",
+                "buffer layout member",
+                offset,
+                format!("unknown {kind} {value:#x}"),
+            )
+        };
+
         Ok(Self {
-            unk0: r.read_u32::<LE>()?,
-            struct_offset: r.read_u32::<LE>()?,
-            format: r.read_u32::<LE>()?.into(),
-            semantic: r.read_u32::<LE>()?.into(),
+            unk0,
+            struct_offset,
+            format: r
+                .read_u32::<LE>()?
+                .try_into()
+                .map_err(|value| unknown("format", value))?,
+            semantic: r
+                .read_u32::<LE>()?
+                .try_into()
+                .map_err(|value| unknown("semantic", value))?,
             index: r.read_u32::<LE>()?,
         })
     }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct FLVERTexture {
     pub path: String,
     pub r#type: String,
@@ -731,7 +727,7 @@ pub struct FLVERTexture {
 impl FLVERPartReader for FLVERTexture {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        c: &FLVERPartContext,
+        c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         let path_offset = r.read_u32::<LE>()?;
         let type_offset = r.read_u32::<LE>()?;
@@ -739,11 +735,16 @@ impl FLVERPartReader for FLVERTexture {
         let scale = FLVERVector2::from_reader(r, c)?;
         let unk10 = r.read_u8()?;
         let unk11 = r.read_u8()? == 0x1;
-        assert!(r.read_u8()? == 0x0);
-        assert!(r.read_u8()? == 0x0);
+        read_zero_u8(r, c, "texture")?;
+        read_zero_u8(r, c, "texture")?;
         let unk14 = r.read_f32::<LE>()?;
         let unk18 = r.read_f32::<LE>()?;
         let unk1c = r.read_f32::<LE>()?;
+        c.observe("texture unk10", unk10);
+        c.observe("texture unk11", unk11);
+        c.observe("texture unk14", unk14);
+        c.observe("texture unk18", unk18);
+        c.observe("texture unk1c", unk1c);
 
         let current_pos = r.stream_position()?;
         r.seek(SeekFrom::Start(path_offset as u64))?;
@@ -768,7 +769,7 @@ impl FLVERPartReader for FLVERTexture {
 impl FLVERPartReader for u8 {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        _c: &FLVERPartContext,
+        _c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         r.read_u8()
     }
@@ -777,7 +778,7 @@ impl FLVERPartReader for u8 {
 impl FLVERPartReader for u16 {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        _c: &FLVERPartContext,
+        _c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         r.read_u16::<LE>()
     }
@@ -786,7 +787,7 @@ impl FLVERPartReader for u16 {
 impl FLVERPartReader for u32 {
     fn from_reader(
         r: &mut (impl io::Read + io::Seek),
-        _c: &FLVERPartContext,
+        _c: &FLVERPartContext<'_>,
     ) -> Result<Self, io::Error> {
         r.read_u32::<LE>()
     }
@@ -794,7 +795,7 @@ impl FLVERPartReader for u32 {
 
 fn read_vec<T: FLVERPartReader>(
     r: &mut (impl io::Read + io::Seek),
-    c: &FLVERPartContext,
+    c: &FLVERPartContext<'_>,
     count: usize,
 ) -> Result<Vec<T>, io::Error> {
     let mut results = Vec::new();
@@ -804,3 +805,72 @@ fn read_vec<T: FLVERPartReader>(
 
     Ok(results)
 }
+
+/// Read the top-level parts of a FLVER, returning those read so far if the file ends early and
+/// the truncation is recovered from.
+fn read_parts<T: FLVERPartReader>(
+    r: &mut (impl io::Read + io::Seek),
+    c: &FLVERPartContext<'_>,
+    count: usize,
+    section: &'static str,
+) -> Result<Vec<T>, io::Error> {
+    let mut results = Vec::new();
+    for _ in 0..count {
+        if c.truncated.get() {
+            break;
+        }
+
+        let offset = r.stream_position()?;
+        match T::from_reader(r, c) {
+            Ok(part) => results.push(part),
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                c.diagnostics
+                    .borrow_mut()
+                    .truncated("FLVER", section, offset)?;
+                c.truncated.set(true);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(results)
+}
+
+/// Read a field that is always zero in known FLVERs, reporting it otherwise.
+fn read_zero_u8(
+    r: &mut (impl io::Read + io::Seek),
+    c: &FLVERPartContext<'_>,
+    section: &'static str,
+) -> io::Result<()> {
+    let value = r.read_u8()?;
+    check_zero(r, c, value.into(), 1, section)
+}
+
+fn read_zero_u32(
+    r: &mut (impl io::Read + io::Seek),
+    c: &FLVERPartContext<'_>,
+    section: &'static str,
+) -> io::Result<()> {
+    let value = r.read_u32::<LE>()?;
+    check_zero(r, c, value, 4, section)
+}
+
+fn check_zero(
+    r: &mut impl io::Seek,
+    c: &FLVERPartContext<'_>,
+    value: u32,
+    size: u64,
+    section: &'static str,
+) -> io::Result<()> {
+    if value == 0 {
+        return Ok(());
+    }
+
+    let offset = r.stream_position()? - size;
+    c.report(FormatError::malformed(
+        "FLVER",
+        section,
+        offset,
+        format!("expected 0, got {value:#x}"),
+    ))
+}
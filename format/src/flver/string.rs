@@ -0,0 +1,79 @@
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::{
+    char::REPLACEMENT_CHARACTER,
+    fmt::{self, Display, Formatter, Write},
+};
+
+use byteorder::ByteOrder;
+use zerocopy::U16;
+
+/// A null-terminated string borrowed from a FLVER, without its terminator. Strings are UTF-16 in
+/// unicode FLVERs and Shift-JIS otherwise.
+///
+/// Comparing or displaying a string doesn't allocate, so names can be tallied across many files
+/// cheaply.
+#[derive(Clone, Copy, Debug)]
+pub enum FlverStr<'a, O: ByteOrder> {
+    Utf16(&'a [U16<O>]),
+    ShiftJis(&'a [u8]),
+}
+
+impl<'a, O: ByteOrder> FlverStr<'a, O> {
+    /// Decode the string, or `None` if it isn't valid UTF-16.
+    pub fn decode(&self) -> Option<String> {
+        match self {
+            Self::Utf16(units) => char::decode_utf16(units.iter().map(|unit| unit.get()))
+                .collect::<Result<_, _>>()
+                .ok(),
+            Self::ShiftJis(bytes) => Some(
+                encoding_rs::SHIFT_JIS
+                    .decode_without_bom_handling(bytes)
+                    .0
+                    .into_owned(),
+            ),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Self::Utf16(units) => units.is_empty(),
+            Self::ShiftJis(bytes) => bytes.is_empty(),
+        }
+    }
+}
+
+/// Invalid characters are replaced with U+FFFD.
+impl<'a, O: ByteOrder> Display for FlverStr<'a, O> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Utf16(units) => char::decode_utf16(units.iter().map(|unit| unit.get()))
+                .try_for_each(|ch| f.write_char(ch.unwrap_or(REPLACEMENT_CHARACTER))),
+            Self::ShiftJis(bytes) => {
+                f.write_str(&encoding_rs::SHIFT_JIS.decode_without_bom_handling(bytes).0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::format;
+
+    use byteorder::LE;
+    use zerocopy::{FromBytes, U16};
+
+    use crate::flver::string::FlverStr;
+
+    #[test]
+    pub fn decodes_both_encodings() {
+        let units = U16::<LE>::slice_from(&[0x61, 0, 0x00, 0xD8]).unwrap();
+        let utf16 = FlverStr::Utf16(units);
+        assert_eq!(format!("{utf16}"), "a\u{FFFD}");
+        assert_eq!(utf16.decode(), None);
+
+        let shift_jis = FlverStr::<LE>::ShiftJis(b"\x83\x65\x83\x58\x83\x67");
+        assert_eq!(format!("{shift_jis}"), "テスト");
+        assert_eq!(shift_jis.decode().as_deref(), Some("テスト"));
+    }
+}
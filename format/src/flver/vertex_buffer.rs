@@ -22,9 +22,9 @@ impl<O: ByteOrder> FlverHeaderPart for VertexBuffer<O> {}
 #[repr(packed)]
 #[allow(unused)]
 pub struct VertexBufferLayout<O: ByteOrder> {
-    pub(crate) member_count: U32<O>,
+    pub member_count: U32<O>,
     padding0: Padding<8>,
-    pub(crate) member_offset: U32<O>,
+    pub member_offset: U32<O>,
 }
 
 impl<O: ByteOrder> FlverHeaderPart for VertexBufferLayout<O> {}
@@ -0,0 +1,535 @@
+use std::{
+    collections::HashMap,
+    io::{self, Read, Seek, SeekFrom, Write},
+};
+
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt, BE, LE};
+use thiserror::Error;
+
+use crate::{io_ext::ReadFormatsExt, round_trip::WriteMode};
+
+#[derive(Debug, Error)]
+pub enum FmgError {
+    #[error("Could not read FMG: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unknown FMG version {0}")]
+    UnknownVersion(u8),
+
+    #[error("Group of IDs {first}..={last} is invalid")]
+    InvalidGroup { first: i32, last: i32 },
+}
+
+/// The layout revision of an FMG, which determines the width of its offsets.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FmgVersion {
+    /// Demon's Souls.
+    DemonsSouls,
+
+    /// Dark Souls, Dark Souls 2 and their remasters.
+    DarkSouls1,
+
+    /// Bloodborne, Dark Souls 3, Sekiro, Elden Ring and Armored Core 6. Offsets are 64 bits wide.
+    DarkSouls3,
+}
+
+impl FmgVersion {
+    fn from_u8(value: u8) -> Result<Self, FmgError> {
+        match value {
+            0 => Ok(Self::DemonsSouls),
+            1 => Ok(Self::DarkSouls1),
+            2 => Ok(Self::DarkSouls3),
+            _ => Err(FmgError::UnknownVersion(value)),
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::DemonsSouls => 0,
+            Self::DarkSouls1 => 1,
+            Self::DarkSouls3 => 2,
+        }
+    }
+
+    fn is_wide(self) -> bool {
+        self == Self::DarkSouls3
+    }
+}
+
+/// A message file mapping entry IDs to UTF-16 text, such as item names and descriptions.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Fmg {
+    pub version: FmgVersion,
+    pub big_endian: bool,
+
+    /// Entries ordered by ID. Entries without text are kept so they survive a round-trip.
+    pub entries: Vec<FmgEntry>,
+
+    /// Layout of the file this was read from, reused by [WriteMode::Preserve].
+    #[cfg_attr(feature = "serde", serde(skip))]
+    layout: Option<FmgLayout>,
+}
+
+/// Only the text is compared, not the layout of the files the FMGs were read from.
+impl PartialEq for Fmg {
+    fn eq(&self, other: &Self) -> bool {
+        self.version == other.version
+            && self.big_endian == other.big_endian
+            && self.entries == other.entries
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FmgEntry {
+    pub id: i32,
+    pub text: Option<String>,
+}
+
+/// Header values whose meaning is unknown, along with the values the games write.
+#[derive(Clone, Copy, Debug)]
+struct FmgHeader {
+    unk00: u8,
+    unk03: u8,
+    unk08: u8,
+    unk09: u8,
+    unk14: u32,
+    unk_offset: u64,
+}
+
+impl FmgHeader {
+    fn canonical(version: FmgVersion) -> Self {
+        Self {
+            unk00: 0,
+            unk03: 0,
+            unk08: 1,
+            unk09: if version == FmgVersion::DemonsSouls {
+                0xFF
+            } else {
+                0
+            },
+            unk14: 0xFF,
+            unk_offset: 0,
+        }
+    }
+}
+
+/// Everything about the layout of an FMG that the canonical writer normalizes.
+#[derive(Clone, Debug)]
+struct FmgLayout {
+    header: FmgHeader,
+
+    /// Inclusive ranges of IDs in file order, which may be split or out of order.
+    groups: Vec<(i32, i32)>,
+
+    /// IDs of the entries in the order of their strings, leaving out entries sharing a string.
+    string_order: Vec<i32>,
+
+    /// Entries pointing at the string of another entry, as `(id, source id)`.
+    shared_strings: Vec<(i32, i32)>,
+
+    /// Bytes following the last string, usually alignment padding.
+    trailing: Vec<u8>,
+    size_includes_trailing: bool,
+}
+
+impl FmgLayout {
+    /// The original groups with the index of their first entry, if they still match the entries.
+    fn groups_for(&self, entries: &[FmgEntry]) -> Option<Vec<(usize, i32, i32)>> {
+        let mut ids = entries.iter().map(|entry| entry.id);
+        let mut groups = Vec::with_capacity(self.groups.len());
+        let mut index = 0;
+        for &(first, last) in &self.groups {
+            groups.push((index, first, last));
+            for id in first..=last {
+                if ids.next() != Some(id) {
+                    return None;
+                }
+                index += 1;
+            }
+        }
+
+        ids.next().is_none().then_some(groups)
+    }
+
+    fn shared_source(&self, id: i32) -> Option<i32> {
+        self.shared_strings
+            .iter()
+            .find(|(shared, _)| *shared == id)
+            .map(|(_, source)| *source)
+    }
+}
+
+impl Fmg {
+    pub fn new(version: FmgVersion) -> Self {
+        Self {
+            version,
+            big_endian: false,
+            entries: Vec::new(),
+            layout: None,
+        }
+    }
+
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, FmgError> {
+        r.seek(SeekFrom::Start(1))?;
+        let big_endian = r.read_bool()?;
+
+        if big_endian {
+            Self::read::<_, BE>(r)
+        } else {
+            Self::read::<_, LE>(r)
+        }
+    }
+
+    fn read<R: Read + Seek, O: ByteOrder>(r: &mut R) -> Result<Self, FmgError> {
+        r.seek(SeekFrom::Start(0))?;
+        let unk00 = r.read_u8()?;
+        let big_endian = r.read_bool()?;
+        let version = FmgVersion::from_u8(r.read_u8()?)?;
+        let unk03 = r.read_u8()?;
+        let file_size = r.read_u32::<O>()?;
+        let unk08 = r.read_u8()?;
+        let unk09 = r.read_u8()?;
+        r.read_padding(2)?;
+
+        let group_count = r.read_u32::<O>()?;
+        let string_count = r.read_u32::<O>()?;
+
+        let wide = version.is_wide();
+        let unk14 = if wide { r.read_u32::<O>()? } else { 0xFF };
+
+        let string_offsets_offset = read_offset::<O>(r, wide)?;
+        let unk_offset = read_offset::<O>(r, wide)?;
+
+        let mut groups = Vec::with_capacity(group_count as usize);
+        for _ in 0..group_count {
+            let offset_index = r.read_i32::<O>()?;
+            let first = r.read_i32::<O>()?;
+            let last = r.read_i32::<O>()?;
+            if wide {
+                r.read_padding(4)?;
+            }
+
+            if last < first || offset_index < 0 {
+                return Err(FmgError::InvalidGroup { first, last });
+            }
+
+            groups.push((offset_index, first, last));
+        }
+
+        r.seek(SeekFrom::Start(string_offsets_offset))?;
+        let string_offsets = (0..string_count)
+            .map(|_| read_offset::<O>(r, wide))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut strings_end = r.stream_position()?;
+
+        let mut string_ids = HashMap::new();
+        let mut shared_strings = Vec::new();
+        let mut entries = Vec::new();
+        for &(offset_index, first, last) in &groups {
+            for id in first..=last {
+                let index = (offset_index + (id - first)) as usize;
+                let Some(&offset) = string_offsets.get(index) else {
+                    return Err(FmgError::InvalidGroup { first, last });
+                };
+
+                let text = if offset != 0 {
+                    if let Some(&source) = string_ids.get(&offset) {
+                        shared_strings.push((id, source));
+                    } else {
+                        string_ids.insert(offset, id);
+                    }
+
+                    r.seek(SeekFrom::Start(offset))?;
+                    let text = r.read_utf16::<O>()?;
+                    strings_end = strings_end.max(r.stream_position()?);
+
+                    Some(text)
+                } else {
+                    None
+                };
+
+                entries.push(FmgEntry { id, text });
+            }
+        }
+
+        let mut string_order = string_ids.into_iter().collect::<Vec<_>>();
+        string_order.sort();
+
+        let end = r.seek(SeekFrom::End(0))?;
+        let mut trailing = Vec::new();
+        r.seek(SeekFrom::Start(strings_end))?;
+        r.take(end.saturating_sub(strings_end))
+            .read_to_end(&mut trailing)?;
+
+        let layout = FmgLayout {
+            header: FmgHeader {
+                unk00,
+                unk03,
+                unk08,
+                unk09,
+                unk14,
+                unk_offset,
+            },
+            groups: groups
+                .iter()
+                .map(|(_, first, last)| (*first, *last))
+                .collect(),
+            string_order: string_order.into_iter().map(|(_, id)| id).collect(),
+            shared_strings,
+            trailing,
+            size_includes_trailing: file_size as u64 == end,
+        };
+
+        Ok(Self {
+            version,
+            big_endian,
+            entries,
+            layout: Some(layout),
+        })
+    }
+
+    /// Find the text of an entry, if it exists and isn't empty.
+    pub fn get(&self, id: i32) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.id == id)
+            .and_then(|entry| entry.text.as_deref())
+    }
+
+    /// Set the text of an entry, inserting it in ID order if it doesn't exist yet.
+    pub fn set(&mut self, id: i32, text: Option<String>) {
+        match self.entries.binary_search_by_key(&id, |entry| entry.id) {
+            Ok(index) => self.entries[index].text = text,
+            Err(index) => self.entries.insert(index, FmgEntry { id, text }),
+        }
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        self.write_with(w, WriteMode::Canonical)
+    }
+
+    /// Write the FMG, keeping the layout of the file it was read from when `mode` is
+    /// [WriteMode::Preserve].
+    pub fn write_with(&self, w: &mut impl Write, mode: WriteMode) -> io::Result<()> {
+        if self.big_endian {
+            self.write_endian::<BE>(w, mode)
+        } else {
+            self.write_endian::<LE>(w, mode)
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_bytes_with(WriteMode::Canonical)
+    }
+
+    pub fn to_bytes_with(&self, mode: WriteMode) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_with(&mut bytes, mode)
+            .expect("writing to a Vec is infallible");
+
+        bytes
+    }
+
+    fn write_endian<O: ByteOrder>(&self, w: &mut impl Write, mode: WriteMode) -> io::Result<()> {
+        let layout = self.layout.as_ref().filter(|_| mode == WriteMode::Preserve);
+
+        let (entries, groups) = match layout.and_then(|layout| layout.groups_for(&self.entries)) {
+            Some(groups) => (self.entries.iter().collect::<Vec<_>>(), groups),
+            None => {
+                let mut entries = self.entries.iter().collect::<Vec<_>>();
+                entries.sort_by_key(|entry| entry.id);
+                entries.dedup_by_key(|entry| entry.id);
+
+                // Runs of consecutive IDs share a group, which stores the first index into the
+                // offset table.
+                let mut groups: Vec<(usize, i32, i32)> = Vec::new();
+                for (index, entry) in entries.iter().enumerate() {
+                    match groups.last_mut() {
+                        Some((_, _, last)) if entry.id.checked_sub(1) == Some(*last) => {
+                            *last = entry.id
+                        }
+                        _ => groups.push((index, entry.id, entry.id)),
+                    }
+                }
+
+                (entries, groups)
+            }
+        };
+
+        let wide = self.version.is_wide();
+        let offset_size = if wide { 8 } else { 4 };
+        let header_size = if wide { 0x28 } else { 0x1C };
+        let group_size = if wide { 0x10 } else { 0xC };
+        let string_offsets_offset = header_size + groups.len() * group_size;
+        let strings_offset = string_offsets_offset + entries.len() * offset_size;
+
+        // Strings keep their original order and sharing, with new strings at the end.
+        let indices = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| (entry.id, index))
+            .collect::<HashMap<_, _>>();
+        let mut order = layout
+            .map(|layout| {
+                layout
+                    .string_order
+                    .iter()
+                    .filter_map(|id| indices.get(id).copied())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let mut ordered = vec![false; entries.len()];
+        for &index in &order {
+            ordered[index] = true;
+        }
+        order.extend((0..entries.len()).filter(|&index| !ordered[index]));
+
+        let mut strings = Vec::new();
+        let mut string_offsets = vec![0; entries.len()];
+        for index in order {
+            let entry = entries[index];
+            let Some(text) = &entry.text else {
+                continue;
+            };
+
+            let shared = layout
+                .and_then(|layout| layout.shared_source(entry.id))
+                .and_then(|source| indices.get(&source).copied())
+                .filter(|&source| entries[source].text == entry.text)
+                .map(|source| string_offsets[source])
+                .filter(|&offset| offset != 0);
+            if let Some(offset) = shared {
+                string_offsets[index] = offset;
+                continue;
+            }
+
+            string_offsets[index] = (strings_offset + strings.len()) as u64;
+            for unit in text.encode_utf16().chain([0]) {
+                strings.write_u16::<O>(unit)?;
+            }
+        }
+
+        let header = layout.map_or(FmgHeader::canonical(self.version), |layout| layout.header);
+        let trailing = layout.map_or(&[][..], |layout| &layout.trailing[..]);
+        let mut file_size = strings_offset + strings.len();
+        if layout.is_some_and(|layout| layout.size_includes_trailing) {
+            file_size += trailing.len();
+        }
+
+        w.write_u8(header.unk00)?;
+        w.write_u8(self.big_endian as u8)?;
+        w.write_u8(self.version.as_u8())?;
+        w.write_u8(header.unk03)?;
+        w.write_u32::<O>(file_size as u32)?;
+        w.write_u8(header.unk08)?;
+        w.write_u8(header.unk09)?;
+        w.write_all(&[0; 2])?;
+        w.write_u32::<O>(groups.len() as u32)?;
+        w.write_u32::<O>(entries.len() as u32)?;
+        if wide {
+            w.write_u32::<O>(header.unk14)?;
+        }
+        write_offset::<O>(w, wide, string_offsets_offset as u64)?;
+        write_offset::<O>(w, wide, header.unk_offset)?;
+
+        for (offset_index, first, last) in &groups {
+            w.write_i32::<O>(*offset_index as i32)?;
+            w.write_i32::<O>(*first)?;
+            w.write_i32::<O>(*last)?;
+            if wide {
+                w.write_u32::<O>(0)?;
+            }
+        }
+
+        for offset in string_offsets {
+            write_offset::<O>(w, wide, offset)?;
+        }
+
+        w.write_all(&strings)?;
+        w.write_all(trailing)
+    }
+}
+
+fn read_offset<O: ByteOrder>(r: &mut impl Read, wide: bool) -> io::Result<u64> {
+    if wide {
+        r.read_u64::<O>()
+    } else {
+        Ok(r.read_u32::<O>()? as u64)
+    }
+}
+
+fn write_offset<O: ByteOrder>(w: &mut impl Write, wide: bool, offset: u64) -> io::Result<()> {
+    if wide {
+        w.write_u64::<O>(offset)
+    } else {
+        w.write_u32::<O>(offset as u32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::{
+        fmg::{Fmg, FmgVersion},
+        round_trip::{verify, WriteMode},
+    };
+
+    #[test]
+    pub fn round_trips_each_version() {
+        for version in [
+            FmgVersion::DemonsSouls,
+            FmgVersion::DarkSouls1,
+            FmgVersion::DarkSouls3,
+        ] {
+            for big_endian in [false, true] {
+                let mut fmg = Fmg::new(version);
+                fmg.big_endian = big_endian;
+                fmg.set(100, Some("Dagger".to_string()));
+                fmg.set(101, None);
+                fmg.set(102, Some("ロングソード".to_string()));
+                fmg.set(2000, Some(String::new()));
+
+                let bytes = fmg.to_bytes();
+                let read = Fmg::from_reader(&mut Cursor::new(bytes)).unwrap();
+
+                assert_eq!(read, fmg);
+                assert_eq!(read.get(102), Some("ロングソード"));
+                assert_eq!(read.get(101), None);
+            }
+        }
+    }
+
+    #[test]
+    pub fn preserves_original_layout() {
+        let mut bytes = vec![0, 0, 1, 0];
+        bytes.extend(0x48u32.to_le_bytes());
+        bytes.extend([0, 0, 0, 0]);
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend(0x34u32.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+
+        // Groups out of ID order, with 100 sharing the string of 101 and strings out of order.
+        for (index, first, last) in [(0, 200, 200), (1, 100, 101)] {
+            bytes.extend([index, first, last].map(i32::to_le_bytes).concat());
+        }
+        bytes.extend([0x44u32, 0x40, 0x40].map(u32::to_le_bytes).concat());
+        bytes.extend([b'B', 0, 0, 0, b'A', 0, 0, 0]);
+        bytes.extend([0; 8]);
+
+        let mut fmg = Fmg::from_reader(&mut Cursor::new(&bytes)).unwrap();
+        assert_eq!(fmg.get(100), Some("B"));
+        verify("FMG", &bytes, &fmg.to_bytes_with(WriteMode::Preserve)).unwrap();
+        assert!(verify("FMG", &bytes, &fmg.to_bytes()).is_err());
+
+        fmg.set(101, Some("C".to_string()));
+        let edited = fmg.to_bytes_with(WriteMode::Preserve);
+        let read = Fmg::from_reader(&mut Cursor::new(edited)).unwrap();
+        assert_eq!(read, fmg);
+        assert_eq!(read.get(100), Some("B"));
+    }
+}
@@ -0,0 +1,372 @@
+//! Structured inputs and invariant checks for fuzzing the BND4, DCX and FLVER parsers with
+//! cargo-fuzz.
+//!
+//! Each `check_*` function parses arbitrary bytes and panics when the parser breaks one of its
+//! invariants, while the `*Input` types generate mostly well-formed files so that fuzzing gets past
+//! the magic and header checks.
+
+use std::io::Cursor;
+
+use arbitrary::Arbitrary;
+
+use crate::{
+    bnd4::BND4,
+    dcx::DCX,
+    diagnostics::Diagnostics,
+    flver::{accessor::VertexAttributeAccessor, reader::FLVER, Flver},
+};
+
+/// Anything strict mode accepts is read the same in lenient mode without any warnings, and every
+/// file read leniently has its data within the binder.
+pub fn check_bnd4(bytes: &[u8]) {
+    let strict = BND4::from_reader(&mut Cursor::new(bytes.to_vec()));
+    let mut diagnostics = Diagnostics::lenient();
+    let lenient = BND4::from_reader_with(&mut Cursor::new(bytes.to_vec()), &mut diagnostics);
+
+    if let Ok(strict) = &strict {
+        let lenient = lenient
+            .as_ref()
+            .expect("lenient mode rejected a binder accepted by strict mode");
+        assert_eq!(strict.files, lenient.files);
+        assert!(diagnostics.warnings().is_empty());
+    }
+
+    if let Ok(binder) = &lenient {
+        for file in &binder.files {
+            assert_eq!(binder.file_bytes(file).len() as u64, file.compressed_size);
+        }
+    }
+}
+
+/// The decompressed data is as large as the header claims.
+pub fn check_dcx(bytes: &[u8]) {
+    if let Ok(dcx) = DCX::from_reader(&mut Cursor::new(bytes)) {
+        assert_eq!(dcx.decompressed.len(), dcx.uncompressed_size as usize);
+    }
+}
+
+/// Both FLVER parsers handle the file without panicking, strict and lenient mode agree, and vertex
+/// attributes never yield more vertices than fit in their buffer.
+pub fn check_flver(bytes: &[u8]) {
+    let strict = FLVER::from_reader(&mut Cursor::new(bytes));
+    let mut diagnostics = Diagnostics::lenient();
+    let lenient = FLVER::from_reader_with(&mut Cursor::new(bytes), &mut diagnostics);
+    if strict.is_ok() {
+        assert!(
+            lenient.is_ok(),
+            "lenient mode rejected a FLVER accepted by strict mode"
+        );
+        assert!(diagnostics.warnings().is_empty());
+    }
+
+    let Ok(flver) = Flver::parse(bytes) else {
+        return;
+    };
+
+    for mesh in flver.meshes {
+        if let Some(material) = flver.mesh_material(mesh) {
+            flver.material_name(material);
+            flver.material_path(material);
+            for texture in flver.material_textures(material) {
+                flver.texture_path(texture);
+                flver.texture_type(texture);
+            }
+        }
+
+        for face_set in flver.mesh_face_sets(mesh) {
+            flver.face_set_indices(face_set);
+        }
+
+        for buffer in flver.mesh_buffers(mesh) {
+            let layout_index = buffer.layout_index.get() as usize;
+            let Some(layout) = flver.vertex_buffer_layouts.get(layout_index) else {
+                continue;
+            };
+            let Ok(attributes) = flver.vertex_attributes(layout) else {
+                continue;
+            };
+
+            let fitting = (buffer.buffer_length.get() as usize)
+                .checked_div(buffer.vertex_size.get() as usize)
+                .unwrap_or_default();
+            for attribute in attributes {
+                if let Ok(accessor) = flver.vertex_attribute_accessor(buffer, attribute) {
+                    assert!(vertex_count(accessor) <= fitting);
+                }
+            }
+        }
+    }
+}
+
+fn vertex_count(accessor: VertexAttributeAccessor<'_>) -> usize {
+    use VertexAttributeAccessor::*;
+
+    match accessor {
+        Float2(iter) | UV(iter) | UVPair(iter) => iter.count(),
+        Float3(iter) => iter.count(),
+        Float4(iter) => iter.count(),
+        Byte4A(iter) | Byte4B(iter) | Byte4C(iter) => iter.count(),
+        Short2ToFloat2(iter) => iter.count(),
+        Short4ToFloat4A(iter) | Short4ToFloat4B(iter) => iter.count(),
+    }
+}
+
+/// A BND4 with a well-formed header and file headers.
+#[derive(Arbitrary, Debug)]
+pub struct Bnd4Input {
+    pub unk04: u8,
+    pub unk05: u8,
+    pub unk0a: u8,
+    pub files: Vec<Bnd4FileInput>,
+
+    /// Bytes cut off the end of the binder, to exercise truncation.
+    pub truncate: u16,
+}
+
+#[derive(Arbitrary, Debug)]
+pub struct Bnd4FileInput {
+    pub flags: u8,
+    pub unk4: i32,
+    pub id: u32,
+    pub path: String,
+    pub data: Vec<u8>,
+
+    /// Claim the data is compressed, which isn't supported.
+    pub compressed: bool,
+}
+
+impl Bnd4Input {
+    const HEADER_SIZE: usize = 0x40;
+    const FILE_HEADER_SIZE: usize = 0x24;
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut names = Vec::new();
+        let mut name_offsets = Vec::with_capacity(self.files.len());
+        let names_offset = Self::HEADER_SIZE + self.files.len() * Self::FILE_HEADER_SIZE;
+        for file in &self.files {
+            name_offsets.push(names_offset + names.len());
+            for unit in file.path.encode_utf16().chain([0]) {
+                names.extend(unit.to_le_bytes());
+            }
+        }
+
+        let mut data = Vec::new();
+        let mut data_offsets = Vec::with_capacity(self.files.len());
+        let data_offset = names_offset + names.len();
+        for file in &self.files {
+            data_offsets.push(data_offset + data.len());
+            data.extend_from_slice(&file.data);
+        }
+
+        let mut bytes = Vec::new();
+        bytes.extend(b"BND4");
+        bytes.extend([self.unk04, self.unk05, 0, 0, 0, 0, self.unk0a, 0]);
+        bytes.extend((self.files.len() as u32).to_le_bytes());
+        for value in [
+            Self::HEADER_SIZE as u64,
+            0,
+            Self::FILE_HEADER_SIZE as u64,
+            names_offset as u64,
+        ] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend([1, 0x74, 4, 0, 0, 0, 0, 0]);
+        bytes.extend(0u64.to_le_bytes());
+
+        for ((file, name_offset), data_offset) in
+            self.files.iter().zip(name_offsets).zip(data_offsets)
+        {
+            let size = file.data.len() as u64;
+            bytes.extend([file.flags, 0, 0, 0]);
+            bytes.extend(file.unk4.to_le_bytes());
+            bytes.extend((size + file.compressed as u64).to_le_bytes());
+            bytes.extend(size.to_le_bytes());
+            bytes.extend((data_offset as u32).to_le_bytes());
+            bytes.extend(file.id.to_le_bytes());
+            bytes.extend((name_offset as u32).to_le_bytes());
+        }
+
+        bytes.extend(names);
+        bytes.extend(data);
+        bytes.truncate(bytes.len().saturating_sub(self.truncate as usize));
+
+        bytes
+    }
+}
+
+/// A DCX of any data, compressed with Kraken, optionally with a corrupted byte.
+#[derive(Arbitrary, Debug)]
+pub struct DcxInput {
+    pub data: Vec<u8>,
+    pub compression_level: u8,
+    pub corrupt: Option<(u16, u8)>,
+}
+
+impl DcxInput {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let dcx = DCX {
+            unk04: 0x11000,
+            dcs_offset: 0x18,
+            dcp_offset: 0x24,
+            unk10: 0x24,
+            unk14: 0x2C,
+            dcs: 0x44435300,
+            uncompressed_size: self.data.len() as u32,
+            compressed_size: 0,
+            dcp: 0x44435000,
+            format: 0x4B52414B,
+            unk2c: 0x20,
+            compression_level: self.compression_level % 10,
+            unk31: 0,
+            unk32: 0,
+            unk33: 0,
+            unk34: 0,
+            unk38: 0,
+            unk3c: 0,
+            unk40: 0,
+            dca: 0x44434100,
+            dca_size: 8,
+            decompressed: self.data.clone(),
+        };
+
+        let mut bytes = Vec::new();
+        dcx.write(&mut bytes)
+            .expect("compressing into a Vec can't fail");
+
+        if let Some((index, value)) = self.corrupt {
+            let length = bytes.len();
+            bytes[index as usize % length] ^= value;
+        }
+
+        bytes
+    }
+}
+
+/// A FLVER with a well-formed header with small part counts, followed by arbitrary parts and data.
+#[derive(Arbitrary, Debug)]
+pub struct FlverInput {
+    pub version: u32,
+
+    /// Counts of dummies, materials, bones, meshes, vertex buffers, face sets, buffer layouts and
+    /// textures.
+    pub counts: [u8; 8],
+    pub vertex_index_size: u8,
+    pub unicode: bool,
+
+    /// Start of the data region within the body.
+    pub data_start: u16,
+    pub body: Vec<u8>,
+}
+
+impl FlverInput {
+    const HEADER_SIZE: usize = 0x80;
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let data_start = (self.data_start as usize)
+            .checked_rem(self.body.len() + 1)
+            .unwrap_or_default();
+        let data_offset = Self::HEADER_SIZE + data_start;
+        let data_length = self.body.len() - data_start;
+        let [dummies, materials, bones, meshes, vertex_buffers, face_sets, layouts, textures] =
+            self.counts.map(|count| (count % 16) as u32);
+
+        let mut bytes = Vec::with_capacity(Self::HEADER_SIZE + self.body.len());
+        bytes.extend(b"FLVER\0L\0");
+        bytes.extend(self.version.to_le_bytes());
+        bytes.extend((data_offset as u32).to_le_bytes());
+        bytes.extend((data_length as u32).to_le_bytes());
+        for count in [dummies, materials, bones, meshes, vertex_buffers] {
+            bytes.extend(count.to_le_bytes());
+        }
+        bytes.extend([0; 24]);
+        bytes.extend([0; 8]);
+        bytes.extend([self.vertex_index_size, self.unicode as u8, 0, 0]);
+        bytes.extend([0; 4]);
+        for count in [face_sets, layouts, textures] {
+            bytes.extend(count.to_le_bytes());
+        }
+        bytes.resize(Self::HEADER_SIZE, 0);
+
+        bytes.extend(&self.body);
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use arbitrary::{Arbitrary, Unstructured};
+
+    use crate::{
+        bnd4::BND4,
+        diagnostics::Diagnostics,
+        fuzz::{check_bnd4, check_flver, Bnd4FileInput, Bnd4Input, FlverInput},
+    };
+
+    #[test]
+    pub fn generates_readable_binders() {
+        let input = Bnd4Input {
+            unk04: 0,
+            unk05: 0,
+            unk0a: 0,
+            files: ["a.flver", "b.tpf"]
+                .map(|path| Bnd4FileInput {
+                    flags: 0x40,
+                    unk4: -1,
+                    id: 0,
+                    path: path.to_string(),
+                    data: path.as_bytes().to_vec(),
+                    compressed: false,
+                })
+                .into(),
+            truncate: 0,
+        };
+
+        let bytes = input.to_bytes();
+        check_bnd4(&bytes);
+
+        let binder = BND4::from_reader(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(binder.files.len(), 2);
+        assert_eq!(binder.file_bytes(&binder.files[1]), b"b.tpf");
+    }
+
+    #[test]
+    pub fn leaves_out_files_with_overflowing_sizes() {
+        let input = Bnd4Input {
+            unk04: 0,
+            unk05: 0,
+            unk0a: 0,
+            files: ["a.flver", "b.tpf"]
+                .map(|path| Bnd4FileInput {
+                    flags: 0x40,
+                    unk4: -1,
+                    id: 0,
+                    path: path.to_string(),
+                    data: path.as_bytes().to_vec(),
+                    compressed: false,
+                })
+                .into(),
+            truncate: 0,
+        };
+
+        // The first file's data offset plus its compressed size wraps around.
+        let mut bytes = input.to_bytes();
+        let size_offset = Bnd4Input::HEADER_SIZE + 8;
+        bytes[size_offset..size_offset + 8].copy_from_slice(&u64::MAX.to_le_bytes());
+        check_bnd4(&bytes);
+
+        let mut diagnostics = Diagnostics::lenient();
+        let binder = BND4::from_reader_with(&mut Cursor::new(bytes), &mut diagnostics).unwrap();
+        assert_eq!(binder.files.len(), 1);
+        assert_eq!(binder.file_bytes(&binder.files[0]), b"b.tpf");
+    }
+
+    #[test]
+    pub fn checks_arbitrary_flvers() {
+        let seed = (0..=255).cycle().take(0x400).collect::<Vec<u8>>();
+        let input = FlverInput::arbitrary(&mut Unstructured::new(&seed)).unwrap();
+
+        check_flver(&input.to_bytes());
+    }
+}
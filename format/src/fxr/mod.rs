@@ -0,0 +1,580 @@
+use std::io::{self, Read};
+
+use byteorder::{ByteOrder, LE};
+use thiserror::Error;
+
+pub use self::property::{FxrKeyframe, FxrPropertyValue};
+
+mod property;
+mod write;
+
+#[derive(Debug, Error)]
+pub enum FxrError {
+    #[error("Could not read FXR: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not an FXR file")]
+    InvalidMagic,
+
+    #[error("Unknown FXR version {0}")]
+    UnknownVersion(u16),
+
+    #[error("FXR data at {offset:#x} is out of bounds")]
+    OutOfBounds { offset: usize },
+
+    #[error("FXR containers are nested more than {MAX_CONTAINER_DEPTH} deep")]
+    TooDeep,
+}
+
+const FXR_MAGIC: &[u8; 4] = b"FXR\0";
+
+/// Containers can't reference themselves in a valid file, this guards against ones that do.
+const MAX_CONTAINER_DEPTH: usize = 64;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FxrVersion {
+    DarkSouls3,
+
+    /// Sekiro and Elden Ring, which add sections 12 to 14.
+    Sekiro,
+}
+
+impl FxrVersion {
+    fn from_u16(value: u16) -> Option<Self> {
+        match value {
+            4 => Some(Self::DarkSouls3),
+            5 => Some(Self::Sekiro),
+            _ => None,
+        }
+    }
+
+    fn as_u16(self) -> u16 {
+        match self {
+            Self::DarkSouls3 => 4,
+            Self::Sekiro => 5,
+        }
+    }
+}
+
+/// A visual effect (`fXXXXXXXX.fxr`), usually found in an `ffxbnd`.
+///
+/// An effect is stored as numbered sections of records that point at one another. Sections 1 to 3
+/// are a state machine deciding which of the effect's states is active, and section 4 onwards is
+/// a tree of containers, effects and actions, whose properties hold the actual (often keyframed)
+/// values. Every leaf value lives in section 11 as an untyped 4 byte field.
+///
+/// Scalars whose meaning isn't known are kept by their offset in the record, so that effects can
+/// be written back as they were read.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Fxr {
+    pub version: FxrVersion,
+    pub id: i32,
+
+    /// Section 1.
+    pub states: Vec<FxrState>,
+
+    /// Section 4, the root of the effect tree.
+    pub root: FxrContainer,
+
+    /// Sections 12 to 14, which are only present from Sekiro onwards.
+    pub section12: Vec<i32>,
+    pub section13: Vec<i32>,
+    pub section14: Vec<i32>,
+}
+
+/// A section 11 value, whose type is implied by where it is used.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FxrField(pub u32);
+
+impl FxrField {
+    pub fn from_f32(value: f32) -> Self {
+        Self(value.to_bits())
+    }
+
+    pub fn from_i32(value: i32) -> Self {
+        Self(value as u32)
+    }
+
+    pub fn as_f32(self) -> f32 {
+        f32::from_bits(self.0)
+    }
+
+    pub fn as_i32(self) -> i32 {
+        self.0 as i32
+    }
+}
+
+/// Section 2, a state of the effect's state machine.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FxrState {
+    pub transitions: Vec<FxrTransition>,
+}
+
+/// Section 3, a condition under which the state machine leaves a state.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FxrTransition {
+    pub unk00: i16,
+    pub unk08: i32,
+    pub unk10: i16,
+    pub unk18: i32,
+    pub unk28: i32,
+    pub unk38: i32,
+    pub field1: FxrField,
+    pub field2: FxrField,
+}
+
+/// Section 4, a node of the effect tree grouping effects and further containers.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FxrContainer {
+    pub container_type: i16,
+    pub effects: Vec<FxrEffect>,
+    pub containers: Vec<FxrContainer>,
+    pub actions: Vec<FxrAction>,
+}
+
+/// Section 5, an emitter or other effect made up of actions.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FxrEffect {
+    pub effect_type: i16,
+    pub actions: Vec<FxrAction>,
+}
+
+/// Section 6, a behavior of an effect, such as how its particles spawn, move or are drawn.
+///
+/// What each property and field means is determined by the action type.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FxrAction {
+    pub action_type: i16,
+    pub unk02: u8,
+    pub unk03: u8,
+    pub unk04: i32,
+    pub properties1: Vec<FxrProperty>,
+    pub properties2: Vec<FxrProperty>,
+    pub section10s: Vec<Vec<FxrField>>,
+    pub fields1: Vec<FxrField>,
+    pub fields2: Vec<FxrField>,
+}
+
+/// Section 7, a value of an action that can vary over time.
+///
+/// The property type determines the value's dimensions and how its fields are interpreted, e.g.
+/// as a constant or as keyframes.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FxrProperty {
+    pub property_type: i16,
+    pub unk04: i32,
+    pub modifiers: Vec<FxrModifier>,
+    pub fields: Vec<FxrField>,
+}
+
+/// Section 8, a modifier applied to a property, e.g. randomization.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FxrModifier {
+    pub modifier_type: u16,
+    pub unk02: u8,
+    pub unk03: u8,
+    pub unk04: i32,
+
+    /// Section 9s.
+    pub properties: Vec<FxrModifierProperty>,
+    pub fields: Vec<FxrField>,
+}
+
+/// Section 9, a value of a modifier.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FxrModifierProperty {
+    pub property_type: i16,
+    pub unk04: i32,
+    pub fields: Vec<FxrField>,
+}
+
+/// Sizes of the records in each section.
+const SECTION1_SIZE: usize = 0x10;
+const SECTION2_SIZE: usize = 0x10;
+const SECTION3_SIZE: usize = 0x40;
+const SECTION4_SIZE: usize = 0x30;
+const SECTION5_SIZE: usize = 0x20;
+const SECTION6_SIZE: usize = 0x40;
+const SECTION7_SIZE: usize = 0x28;
+const SECTION8_SIZE: usize = 0x20;
+const SECTION9_SIZE: usize = 0x18;
+const SECTION10_SIZE: usize = 0x10;
+const FIELD_SIZE: usize = 4;
+
+/// Offset of the section 1 offset in the header.
+const HEADER_SECTION1: usize = 0x10;
+
+/// Offset of the section 4 offset in the header.
+const HEADER_SECTION4: usize = 0x28;
+
+/// Offset of the section 12 offset in the header, from Sekiro onwards.
+const HEADER_SECTION12: usize = 0x70;
+
+struct FxrData<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> FxrData<'a> {
+    fn get(&self, offset: usize, length: usize) -> Result<&'a [u8], FxrError> {
+        offset
+            .checked_add(length)
+            .and_then(|end| self.bytes.get(offset..end))
+            .ok_or(FxrError::OutOfBounds { offset })
+    }
+
+    fn i32(&self, offset: usize) -> Result<i32, FxrError> {
+        Ok(LE::read_i32(self.get(offset, 4)?))
+    }
+
+    fn i16(&self, offset: usize) -> Result<i16, FxrError> {
+        Ok(LE::read_i16(self.get(offset, 2)?))
+    }
+
+    fn u8(&self, offset: usize) -> Result<u8, FxrError> {
+        Ok(self.get(offset, 1)?[0])
+    }
+
+    /// Read a count or offset, which are never negative in a valid file.
+    fn index(&self, offset: usize) -> Result<usize, FxrError> {
+        let value = self.i32(offset)?;
+        usize::try_from(value).map_err(|_| FxrError::OutOfBounds { offset })
+    }
+
+    fn records<T>(
+        &self,
+        offset: usize,
+        count: usize,
+        size: usize,
+        read: impl Fn(usize) -> Result<T, FxrError>,
+    ) -> Result<Vec<T>, FxrError> {
+        // Check the whole table up front so a bogus count fails before allocating.
+        self.get(offset, count.saturating_mul(size))?;
+
+        (0..count).map(|i| read(offset + i * size)).collect()
+    }
+
+    fn i32s(&self, offset: usize, count: usize) -> Result<Vec<i32>, FxrError> {
+        self.records(offset, count, 4, |offset| self.i32(offset))
+    }
+
+    fn fields(&self, offset: usize, count: usize) -> Result<Vec<FxrField>, FxrError> {
+        self.records(offset, count, FIELD_SIZE, |offset| {
+            Ok(FxrField(self.i32(offset)? as u32))
+        })
+    }
+
+    fn state(&self, offset: usize) -> Result<FxrState, FxrError> {
+        let count = self.index(offset + 0x4)?;
+        let transitions_offset = self.index(offset + 0x8)?;
+
+        Ok(FxrState {
+            transitions: self.records(transitions_offset, count, SECTION3_SIZE, |offset| {
+                self.transition(offset)
+            })?,
+        })
+    }
+
+    fn transition(&self, offset: usize) -> Result<FxrTransition, FxrError> {
+        Ok(FxrTransition {
+            unk00: self.i16(offset)?,
+            unk08: self.i32(offset + 0x8)?,
+            unk10: self.i16(offset + 0x10)?,
+            unk18: self.i32(offset + 0x18)?,
+            field1: self.fields(self.index(offset + 0x20)?, 1)?[0],
+            unk28: self.i32(offset + 0x28)?,
+            field2: self.fields(self.index(offset + 0x30)?, 1)?[0],
+            unk38: self.i32(offset + 0x38)?,
+        })
+    }
+
+    fn container(&self, offset: usize, depth: usize) -> Result<FxrContainer, FxrError> {
+        if depth > MAX_CONTAINER_DEPTH {
+            return Err(FxrError::TooDeep);
+        }
+
+        let effect_count = self.index(offset + 0x8)?;
+        let container_count = self.index(offset + 0xC)?;
+        let action_count = self.index(offset + 0x10)?;
+
+        Ok(FxrContainer {
+            container_type: self.i16(offset)?,
+            effects: self.records(
+                self.index(offset + 0x18)?,
+                effect_count,
+                SECTION5_SIZE,
+                |offset| self.effect(offset),
+            )?,
+            containers: self.records(
+                self.index(offset + 0x20)?,
+                container_count,
+                SECTION4_SIZE,
+                |offset| self.container(offset, depth + 1),
+            )?,
+            actions: self.records(
+                self.index(offset + 0x28)?,
+                action_count,
+                SECTION6_SIZE,
+                |offset| self.action(offset),
+            )?,
+        })
+    }
+
+    fn effect(&self, offset: usize) -> Result<FxrEffect, FxrError> {
+        let action_count = self.index(offset + 0x10)?;
+
+        Ok(FxrEffect {
+            effect_type: self.i16(offset)?,
+            actions: self.records(
+                self.index(offset + 0x18)?,
+                action_count,
+                SECTION6_SIZE,
+                |offset| self.action(offset),
+            )?,
+        })
+    }
+
+    fn action(&self, offset: usize) -> Result<FxrAction, FxrError> {
+        let fields1_count = self.index(offset + 0x8)?;
+        let section10_count = self.index(offset + 0xC)?;
+        let properties1_count = self.index(offset + 0x10)?;
+        let fields2_count = self.index(offset + 0x14)?;
+        let properties2_count = self.index(offset + 0x1C)?;
+        let fields_offset = self.index(offset + 0x20)?;
+        let section10_offset = self.index(offset + 0x28)?;
+        let properties_offset = self.index(offset + 0x30)?;
+
+        // Both lists of properties and of fields are stored back to back.
+        let property = |offset| self.property(offset);
+        let properties2_offset = properties_offset + properties1_count * SECTION7_SIZE;
+        let fields2_offset = fields_offset + fields1_count * FIELD_SIZE;
+
+        Ok(FxrAction {
+            action_type: self.i16(offset)?,
+            unk02: self.u8(offset + 0x2)?,
+            unk03: self.u8(offset + 0x3)?,
+            unk04: self.i32(offset + 0x4)?,
+            properties1: self.records(
+                properties_offset,
+                properties1_count,
+                SECTION7_SIZE,
+                property,
+            )?,
+            properties2: self.records(
+                properties2_offset,
+                properties2_count,
+                SECTION7_SIZE,
+                property,
+            )?,
+            section10s: self.records(
+                section10_offset,
+                section10_count,
+                SECTION10_SIZE,
+                |offset| self.fields(self.index(offset)?, self.index(offset + 0x8)?),
+            )?,
+            fields1: self.fields(fields_offset, fields1_count)?,
+            fields2: self.fields(fields2_offset, fields2_count)?,
+        })
+    }
+
+    fn property(&self, offset: usize) -> Result<FxrProperty, FxrError> {
+        let field_count = self.index(offset + 0x8)?;
+        let modifier_count = self.index(offset + 0x20)?;
+
+        Ok(FxrProperty {
+            property_type: self.i16(offset)?,
+            unk04: self.i32(offset + 0x4)?,
+            fields: self.fields(self.index(offset + 0x10)?, field_count)?,
+            modifiers: self.records(
+                self.index(offset + 0x18)?,
+                modifier_count,
+                SECTION8_SIZE,
+                |offset| self.modifier(offset),
+            )?,
+        })
+    }
+
+    fn modifier(&self, offset: usize) -> Result<FxrModifier, FxrError> {
+        let field_count = self.index(offset + 0x8)?;
+        let property_count = self.index(offset + 0xC)?;
+
+        Ok(FxrModifier {
+            modifier_type: self.i16(offset)? as u16,
+            unk02: self.u8(offset + 0x2)?,
+            unk03: self.u8(offset + 0x3)?,
+            unk04: self.i32(offset + 0x4)?,
+            fields: self.fields(self.index(offset + 0x10)?, field_count)?,
+            properties: self.records(
+                self.index(offset + 0x18)?,
+                property_count,
+                SECTION9_SIZE,
+                |offset| {
+                    Ok(FxrModifierProperty {
+                        property_type: self.i16(offset)?,
+                        unk04: self.i32(offset + 0x4)?,
+                        fields: self
+                            .fields(self.index(offset + 0x10)?, self.index(offset + 0x8)?)?,
+                    })
+                },
+            )?,
+        })
+    }
+}
+
+impl Fxr {
+    pub fn from_reader(r: &mut impl Read) -> Result<Self, FxrError> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+
+        let data = FxrData { bytes: &bytes };
+        if data.get(0, 4)? != FXR_MAGIC {
+            return Err(FxrError::InvalidMagic);
+        }
+
+        let version = data.i16(0x6)? as u16;
+        let version = FxrVersion::from_u16(version).ok_or(FxrError::UnknownVersion(version))?;
+        let id = data.i32(0xC)?;
+
+        let section1 = data.index(HEADER_SECTION1)?;
+        let states = data.records(
+            data.index(section1 + 0x8)?,
+            data.index(section1 + 0x4)?,
+            SECTION2_SIZE,
+            |offset| data.state(offset),
+        )?;
+
+        let root = data.container(data.index(HEADER_SECTION4)?, 0)?;
+
+        let (section12, section13, section14) = if version == FxrVersion::Sekiro {
+            let list = |index: usize| {
+                let header = HEADER_SECTION12 + index * 8;
+                data.i32s(data.index(header)?, data.index(header + 4)?)
+            };
+
+            (list(0)?, list(1)?, list(2)?)
+        } else {
+            Default::default()
+        };
+
+        Ok(Self {
+            version,
+            id,
+            states,
+            root,
+            section12,
+            section13,
+            section14,
+        })
+    }
+
+    /// Every action in the effect tree, whether it belongs to a container or an effect.
+    pub fn actions(&self) -> Vec<&FxrAction> {
+        let mut actions = Vec::new();
+        self.root.collect_actions(&mut actions);
+
+        actions
+    }
+
+    pub fn actions_mut(&mut self) -> Vec<&mut FxrAction> {
+        let mut actions = Vec::new();
+        self.root.collect_actions_mut(&mut actions);
+
+        actions
+    }
+}
+
+impl FxrContainer {
+    fn collect_actions<'a>(&'a self, out: &mut Vec<&'a FxrAction>) {
+        out.extend(&self.actions);
+        out.extend(self.effects.iter().flat_map(|effect| &effect.actions));
+        for container in &self.containers {
+            container.collect_actions(out);
+        }
+    }
+
+    fn collect_actions_mut<'a>(&'a mut self, out: &mut Vec<&'a mut FxrAction>) {
+        out.extend(&mut self.actions);
+        out.extend(
+            self.effects
+                .iter_mut()
+                .flat_map(|effect| &mut effect.actions),
+        );
+        for container in &mut self.containers {
+            container.collect_actions_mut(out);
+        }
+    }
+}
+
+impl FxrAction {
+    /// Both lists of properties, in the order they are stored.
+    pub fn properties(&self) -> impl Iterator<Item = &FxrProperty> {
+        self.properties1.iter().chain(&self.properties2)
+    }
+
+    pub fn properties_mut(&mut self) -> impl Iterator<Item = &mut FxrProperty> {
+        self.properties1.iter_mut().chain(&mut self.properties2)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::fxr::{Fxr, FxrField, FxrVersion};
+
+    #[test]
+    pub fn reads_action_properties() {
+        let mut bytes = vec![0u8; 0x124];
+        let mut put = |offset: usize, value: i32| {
+            bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+        };
+
+        put(0x4, 4 << 16);
+        put(0xC, 1234);
+        put(0x10, 0x70);
+        put(0x28, 0x80);
+
+        // Root container with a single action.
+        put(0x90, 1);
+        put(0xA8, 0xB0);
+
+        // The action, with one field and one property.
+        put(0xB0, 600);
+        put(0xB8, 1);
+        put(0xC0, 1);
+        put(0xD0, 0x118);
+        put(0xE0, 0xF0);
+
+        // The property, with two fields.
+        put(0xF0, 0x20);
+        put(0xF8, 2);
+        put(0x100, 0x11C);
+
+        put(0x118, 1.5f32.to_bits() as i32);
+        put(0x11C, 7);
+        put(0x120, 8);
+        bytes[..4].copy_from_slice(b"FXR\0");
+
+        let fxr = Fxr::from_reader(&mut Cursor::new(bytes)).unwrap();
+        let action = &fxr.root.actions[0];
+
+        assert_eq!(fxr.version, FxrVersion::DarkSouls3);
+        assert_eq!(fxr.id, 1234);
+        assert_eq!(action.action_type, 600);
+        assert_eq!(action.fields1[0].as_f32(), 1.5);
+        assert_eq!(
+            action.properties1[0].fields,
+            [FxrField::from_i32(7), FxrField::from_i32(8)]
+        );
+    }
+}
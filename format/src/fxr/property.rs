@@ -0,0 +1,196 @@
+use crate::fxr::{FxrField, FxrProperty};
+
+/// Bits of the property type holding the number of components, less one.
+const COMPONENTS_MASK: i16 = 0b11;
+
+/// Bits of the property type holding the function used to evaluate the fields.
+const FUNCTION_SHIFT: u32 = 4;
+const FUNCTION_MASK: i16 = 0xF << FUNCTION_SHIFT;
+
+const FUNCTION_ZERO: i16 = 0;
+const FUNCTION_ONE: i16 = 1;
+const FUNCTION_CONSTANT: i16 = 2;
+const FUNCTION_STEPPED: i16 = 3;
+const FUNCTION_LINEAR: i16 = 4;
+
+/// The decoded value of a property.
+///
+/// Only the functions whose field layout is known are decoded, curves are left as raw fields on
+/// the property.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum FxrPropertyValue {
+    Zero,
+    One,
+    Constant(Vec<f32>),
+
+    /// Holds the value of the last keyframe reached.
+    Stepped(Vec<FxrKeyframe>),
+
+    /// Interpolates linearly between keyframes.
+    Linear(Vec<FxrKeyframe>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FxrKeyframe {
+    pub time: f32,
+    pub value: Vec<f32>,
+}
+
+impl FxrPropertyValue {
+    /// Evaluate the value at a point in time, as `components` values.
+    pub fn sample(&self, time: f32, components: usize) -> Vec<f32> {
+        let keyframes = match self {
+            Self::Zero => return vec![0.0; components],
+            Self::One => return vec![1.0; components],
+            Self::Constant(value) => return value.clone(),
+            Self::Stepped(keyframes) | Self::Linear(keyframes) => keyframes,
+        };
+
+        let next = keyframes.partition_point(|keyframe| keyframe.time <= time);
+        match (
+            next.checked_sub(1).map(|i| &keyframes[i]),
+            keyframes.get(next),
+        ) {
+            (None, None) => vec![0.0; components],
+            (Some(keyframe), None) | (None, Some(keyframe)) => keyframe.value.clone(),
+            (Some(previous), Some(_)) if matches!(self, Self::Stepped(_)) => previous.value.clone(),
+            (Some(previous), Some(next)) => {
+                let t = (time - previous.time) / (next.time - previous.time);
+
+                previous
+                    .value
+                    .iter()
+                    .zip(&next.value)
+                    .map(|(a, b)| a + (b - a) * t)
+                    .collect()
+            }
+        }
+    }
+}
+
+impl FxrProperty {
+    /// Number of components of each value, from 1 for a scalar to 4 for a color.
+    pub fn components(&self) -> usize {
+        (self.property_type & COMPONENTS_MASK) as usize + 1
+    }
+
+    /// Decode the fields according to the property type, if its function is understood and the
+    /// fields fit it.
+    pub fn value(&self) -> Option<FxrPropertyValue> {
+        let components = self.components();
+        let floats = |fields: &[FxrField]| fields.iter().map(|field| field.as_f32()).collect();
+
+        match (self.property_type & FUNCTION_MASK) >> FUNCTION_SHIFT {
+            FUNCTION_ZERO => Some(FxrPropertyValue::Zero),
+            FUNCTION_ONE => Some(FxrPropertyValue::One),
+            FUNCTION_CONSTANT => (self.fields.len() == components)
+                .then(|| FxrPropertyValue::Constant(floats(&self.fields))),
+            function @ (FUNCTION_STEPPED | FUNCTION_LINEAR) => {
+                // A keyframe count, then every time, then every value.
+                let (count, fields) = self.fields.split_first()?;
+                let count = usize::try_from(count.as_i32()).ok()?;
+                if fields.len() != count.checked_mul(components + 1)? {
+                    return None;
+                }
+
+                let (times, values) = fields.split_at(count);
+                let keyframes = times
+                    .iter()
+                    .zip(values.chunks_exact(components))
+                    .map(|(time, value)| FxrKeyframe {
+                        time: time.as_f32(),
+                        value: floats(value),
+                    })
+                    .collect();
+
+                Some(if function == FUNCTION_STEPPED {
+                    FxrPropertyValue::Stepped(keyframes)
+                } else {
+                    FxrPropertyValue::Linear(keyframes)
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Replace the fields with an encoded value, updating the function in the property type but
+    /// keeping its number of components.
+    ///
+    /// Panics if a value doesn't have as many components as the property.
+    pub fn set_value(&mut self, value: &FxrPropertyValue) {
+        let components = self.components();
+        let check = |value: &[f32]| {
+            assert_eq!(
+                value.len(),
+                components,
+                "property value has wrong dimensions"
+            );
+            value
+                .iter()
+                .copied()
+                .map(FxrField::from_f32)
+                .collect::<Vec<_>>()
+        };
+
+        let (function, fields) = match value {
+            FxrPropertyValue::Zero => (FUNCTION_ZERO, vec![]),
+            FxrPropertyValue::One => (FUNCTION_ONE, vec![]),
+            FxrPropertyValue::Constant(value) => (FUNCTION_CONSTANT, check(value)),
+            FxrPropertyValue::Stepped(keyframes) | FxrPropertyValue::Linear(keyframes) => {
+                let function = if matches!(value, FxrPropertyValue::Stepped(_)) {
+                    FUNCTION_STEPPED
+                } else {
+                    FUNCTION_LINEAR
+                };
+
+                let mut fields = vec![FxrField::from_i32(keyframes.len() as i32)];
+                fields.extend(
+                    keyframes
+                        .iter()
+                        .map(|keyframe| FxrField::from_f32(keyframe.time)),
+                );
+                for keyframe in keyframes {
+                    fields.extend(check(&keyframe.value));
+                }
+
+                (function, fields)
+            }
+        };
+
+        self.property_type = (self.property_type & !FUNCTION_MASK) | (function << FUNCTION_SHIFT);
+        self.fields = fields;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fxr::{FxrKeyframe, FxrProperty, FxrPropertyValue};
+
+    #[test]
+    pub fn round_trips_keyframes() {
+        let mut property = FxrProperty {
+            property_type: 0x21,
+            ..Default::default()
+        };
+        let value = FxrPropertyValue::Linear(vec![
+            FxrKeyframe {
+                time: 0.0,
+                value: vec![0.0, 1.0],
+            },
+            FxrKeyframe {
+                time: 2.0,
+                value: vec![4.0, 3.0],
+            },
+        ]);
+
+        property.set_value(&value);
+
+        assert_eq!(property.property_type, 0x41);
+        assert_eq!(property.fields.len(), 1 + 2 + 4);
+        assert_eq!(property.value(), Some(value.clone()));
+        assert_eq!(value.sample(1.0, 2), [2.0, 2.0]);
+        assert_eq!(value.sample(5.0, 2), [4.0, 3.0]);
+    }
+}
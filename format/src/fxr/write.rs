@@ -0,0 +1,382 @@
+use std::io::Write;
+
+use byteorder::{ByteOrder, LE};
+
+use crate::fxr::{
+    Fxr, FxrAction, FxrContainer, FxrEffect, FxrError, FxrField, FxrModifier, FxrModifierProperty,
+    FxrProperty, FxrState, FxrTransition, FxrVersion, FIELD_SIZE, HEADER_SECTION1,
+    HEADER_SECTION12, SECTION10_SIZE, SECTION1_SIZE, SECTION2_SIZE, SECTION3_SIZE, SECTION4_SIZE,
+    SECTION5_SIZE, SECTION6_SIZE, SECTION7_SIZE, SECTION8_SIZE, SECTION9_SIZE,
+};
+
+const HEADER_SIZE_DS3: usize = 0x70;
+const HEADER_SIZE_SEKIRO: usize = 0x90;
+
+/// Number of the section holding fields, the last one shared by every version.
+const FIELDS: usize = 11;
+
+const SECTION_SIZES: [usize; FIELDS + 1] = [
+    0,
+    SECTION1_SIZE,
+    SECTION2_SIZE,
+    SECTION3_SIZE,
+    SECTION4_SIZE,
+    SECTION5_SIZE,
+    SECTION6_SIZE,
+    SECTION7_SIZE,
+    SECTION8_SIZE,
+    SECTION9_SIZE,
+    SECTION10_SIZE,
+    FIELD_SIZE,
+];
+
+/// An offset from one record to another, which can only be written once every section has been
+/// laid out.
+struct Fixup {
+    section: usize,
+    at: usize,
+    target_section: usize,
+    target: usize,
+}
+
+/// Builds each section separately, records are reserved as contiguous tables before their
+/// contents are written so that children of a node always follow one another.
+struct SectionWriter {
+    sections: [Vec<u8>; FIELDS + 1],
+    fixups: Vec<Fixup>,
+}
+
+impl SectionWriter {
+    fn reserve(&mut self, section: usize, count: usize) -> usize {
+        let buffer = &mut self.sections[section];
+        let start = buffer.len();
+        buffer.resize(start + count * SECTION_SIZES[section], 0);
+
+        start
+    }
+
+    fn i32(&mut self, section: usize, at: usize, value: i32) {
+        LE::write_i32(&mut self.sections[section][at..], value);
+    }
+
+    fn i16(&mut self, section: usize, at: usize, value: i16) {
+        LE::write_i16(&mut self.sections[section][at..], value);
+    }
+
+    fn bytes(&mut self, section: usize, at: usize, values: &[u8]) {
+        self.sections[section][at..at + values.len()].copy_from_slice(values);
+    }
+
+    fn count(&mut self, section: usize, at: usize, count: usize) {
+        self.i32(section, at, count as i32);
+    }
+
+    /// Reserve a table of records in another section and point at it, unless it is empty.
+    fn table(&mut self, section: usize, at: usize, target_section: usize, count: usize) -> usize {
+        let target = self.reserve(target_section, count);
+        if count > 0 {
+            self.fixups.push(Fixup {
+                section,
+                at,
+                target_section,
+                target,
+            });
+        }
+
+        target
+    }
+
+    fn fields<'a>(
+        &mut self,
+        section: usize,
+        at: usize,
+        fields: impl IntoIterator<Item = &'a FxrField>,
+    ) {
+        let fields = fields.into_iter().collect::<Vec<_>>();
+        let start = self.table(section, at, FIELDS, fields.len());
+
+        for (i, field) in fields.into_iter().enumerate() {
+            LE::write_u32(
+                &mut self.sections[FIELDS][start + i * FIELD_SIZE..],
+                field.0,
+            );
+        }
+    }
+
+    fn state(&mut self, at: usize, state: &FxrState) {
+        self.count(2, at + 0x4, state.transitions.len());
+        let start = self.table(2, at + 0x8, 3, state.transitions.len());
+
+        for (i, transition) in state.transitions.iter().enumerate() {
+            self.transition(start + i * SECTION3_SIZE, transition);
+        }
+    }
+
+    fn transition(&mut self, at: usize, transition: &FxrTransition) {
+        self.i16(3, at, transition.unk00);
+        self.bytes(3, at + 0x2, &[0, 1]);
+        self.i32(3, at + 0x8, transition.unk08);
+        self.i16(3, at + 0x10, transition.unk10);
+        self.bytes(3, at + 0x12, &[0, 1]);
+        self.i32(3, at + 0x18, transition.unk18);
+        self.fields(3, at + 0x20, [&transition.field1]);
+        self.i32(3, at + 0x28, transition.unk28);
+        self.fields(3, at + 0x30, [&transition.field2]);
+        self.i32(3, at + 0x38, transition.unk38);
+    }
+
+    fn container(&mut self, at: usize, container: &FxrContainer) {
+        self.i16(4, at, container.container_type);
+        self.bytes(4, at + 0x2, &[0, 1]);
+        self.count(4, at + 0x8, container.effects.len());
+        self.count(4, at + 0xC, container.containers.len());
+        self.count(4, at + 0x10, container.actions.len());
+
+        let effects = self.table(4, at + 0x18, 5, container.effects.len());
+        let containers = self.table(4, at + 0x20, 4, container.containers.len());
+        let actions = self.table(4, at + 0x28, 6, container.actions.len());
+
+        for (i, effect) in container.effects.iter().enumerate() {
+            self.effect(effects + i * SECTION5_SIZE, effect);
+        }
+        for (i, child) in container.containers.iter().enumerate() {
+            self.container(containers + i * SECTION4_SIZE, child);
+        }
+        for (i, action) in container.actions.iter().enumerate() {
+            self.action(actions + i * SECTION6_SIZE, action);
+        }
+    }
+
+    fn effect(&mut self, at: usize, effect: &FxrEffect) {
+        self.i16(5, at, effect.effect_type);
+        self.bytes(5, at + 0x2, &[0, 1]);
+        self.count(5, at + 0x10, effect.actions.len());
+
+        let actions = self.table(5, at + 0x18, 6, effect.actions.len());
+        for (i, action) in effect.actions.iter().enumerate() {
+            self.action(actions + i * SECTION6_SIZE, action);
+        }
+    }
+
+    fn action(&mut self, at: usize, action: &FxrAction) {
+        self.i16(6, at, action.action_type);
+        self.bytes(6, at + 0x2, &[action.unk02, action.unk03]);
+        self.i32(6, at + 0x4, action.unk04);
+        self.count(6, at + 0x8, action.fields1.len());
+        self.count(6, at + 0xC, action.section10s.len());
+        self.count(6, at + 0x10, action.properties1.len());
+        self.count(6, at + 0x14, action.fields2.len());
+        self.count(6, at + 0x1C, action.properties2.len());
+
+        self.fields(6, at + 0x20, action.fields1.iter().chain(&action.fields2));
+
+        let section10s = self.table(6, at + 0x28, 10, action.section10s.len());
+        for (i, fields) in action.section10s.iter().enumerate() {
+            let at = section10s + i * SECTION10_SIZE;
+            self.fields(10, at, fields);
+            self.count(10, at + 0x8, fields.len());
+        }
+
+        let property_count = action.properties1.len() + action.properties2.len();
+        let start = self.table(6, at + 0x30, 7, property_count);
+        for (i, property) in action.properties().enumerate() {
+            self.property(start + i * SECTION7_SIZE, property);
+        }
+    }
+
+    fn property(&mut self, at: usize, property: &FxrProperty) {
+        self.i16(7, at, property.property_type);
+        self.bytes(7, at + 0x2, &[0, 1]);
+        self.i32(7, at + 0x4, property.unk04);
+        self.count(7, at + 0x8, property.fields.len());
+        self.fields(7, at + 0x10, &property.fields);
+        self.count(7, at + 0x20, property.modifiers.len());
+
+        let modifiers = self.table(7, at + 0x18, 8, property.modifiers.len());
+        for (i, modifier) in property.modifiers.iter().enumerate() {
+            self.modifier(modifiers + i * SECTION8_SIZE, modifier);
+        }
+    }
+
+    fn modifier(&mut self, at: usize, modifier: &FxrModifier) {
+        self.i16(8, at, modifier.modifier_type as i16);
+        self.bytes(8, at + 0x2, &[modifier.unk02, modifier.unk03]);
+        self.i32(8, at + 0x4, modifier.unk04);
+        self.count(8, at + 0x8, modifier.fields.len());
+        self.count(8, at + 0xC, modifier.properties.len());
+        self.fields(8, at + 0x10, &modifier.fields);
+
+        let properties = self.table(8, at + 0x18, 9, modifier.properties.len());
+        for (i, property) in modifier.properties.iter().enumerate() {
+            self.modifier_property(properties + i * SECTION9_SIZE, property);
+        }
+    }
+
+    fn modifier_property(&mut self, at: usize, property: &FxrModifierProperty) {
+        self.i16(9, at, property.property_type);
+        self.bytes(9, at + 0x2, &[0, 1]);
+        self.i32(9, at + 0x4, property.unk04);
+        self.count(9, at + 0x8, property.fields.len());
+        self.fields(9, at + 0x10, &property.fields);
+    }
+}
+
+impl Fxr {
+    /// Write the effect out, laying out every section afresh.
+    pub fn write(&self, w: &mut impl Write) -> Result<(), FxrError> {
+        let mut writer = SectionWriter {
+            sections: Default::default(),
+            fixups: Vec::new(),
+        };
+
+        let section1 = writer.reserve(1, 1);
+        writer.count(1, section1 + 0x4, self.states.len());
+        let states = writer.table(1, section1 + 0x8, 2, self.states.len());
+        for (i, state) in self.states.iter().enumerate() {
+            writer.state(states + i * SECTION2_SIZE, state);
+        }
+
+        let root = writer.reserve(4, 1);
+        writer.container(root, &self.root);
+
+        let header_size = match self.version {
+            FxrVersion::DarkSouls3 => HEADER_SIZE_DS3,
+            FxrVersion::Sekiro => HEADER_SIZE_SEKIRO,
+        };
+
+        let mut bases = [0; FIELDS + 1];
+        let mut offset = header_size;
+        for (base, section) in bases.iter_mut().zip(&writer.sections).skip(1) {
+            *base = offset;
+            offset += section.len();
+        }
+
+        for fixup in &writer.fixups {
+            let target = (bases[fixup.target_section] + fixup.target) as i32;
+            LE::write_i32(&mut writer.sections[fixup.section][fixup.at..], target);
+        }
+
+        let mut header = vec![0u8; header_size];
+        header[..4].copy_from_slice(b"FXR\0");
+        LE::write_u16(&mut header[0x6..], self.version.as_u16());
+        LE::write_i32(&mut header[0x8..], 1);
+        LE::write_i32(&mut header[0xC..], self.id);
+        for section in 1..=FIELDS {
+            let at = HEADER_SECTION1 + (section - 1) * 8;
+            let count = writer.sections[section].len() / SECTION_SIZES[section];
+
+            LE::write_i32(&mut header[at..], bases[section] as i32);
+            LE::write_i32(&mut header[at + 4..], count as i32);
+        }
+        LE::write_i32(&mut header[0x68..], 1);
+
+        let mut lists = Vec::new();
+        if self.version == FxrVersion::Sekiro {
+            for (i, list) in [&self.section12, &self.section13, &self.section14]
+                .into_iter()
+                .enumerate()
+            {
+                let at = HEADER_SECTION12 + i * 8;
+                LE::write_i32(&mut header[at..], (offset + lists.len()) as i32);
+                LE::write_i32(&mut header[at + 4..], list.len() as i32);
+                lists.extend(list.iter().flat_map(|value| value.to_le_bytes()));
+            }
+        }
+
+        w.write_all(&header)?;
+        for section in &writer.sections[1..] {
+            w.write_all(section)?;
+        }
+        w.write_all(&lists)?;
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, FxrError> {
+        let mut out = Vec::new();
+        self.write(&mut out)?;
+
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::fxr::{
+        Fxr, FxrAction, FxrContainer, FxrEffect, FxrField, FxrModifier, FxrModifierProperty,
+        FxrProperty, FxrState, FxrTransition, FxrVersion,
+    };
+
+    #[test]
+    pub fn round_trips_graph() {
+        let property = |value: f32| FxrProperty {
+            property_type: 0x20,
+            unk04: 0,
+            modifiers: vec![FxrModifier {
+                modifier_type: 0xC000,
+                unk02: 0,
+                unk03: 1,
+                unk04: 2,
+                properties: vec![FxrModifierProperty {
+                    property_type: 0x20,
+                    unk04: 0,
+                    fields: vec![FxrField::from_f32(0.5)],
+                }],
+                fields: vec![FxrField::from_i32(3)],
+            }],
+            fields: vec![FxrField::from_f32(value)],
+        };
+        let action = FxrAction {
+            action_type: 600,
+            unk02: 1,
+            unk03: 0,
+            unk04: 5,
+            properties1: vec![property(1.0), property(2.0)],
+            properties2: vec![property(3.0)],
+            section10s: vec![vec![FxrField::from_i32(4)]],
+            fields1: vec![FxrField::from_i32(10)],
+            fields2: vec![FxrField::from_i32(20), FxrField::from_i32(30)],
+        };
+
+        let fxr = Fxr {
+            version: FxrVersion::Sekiro,
+            id: 302000,
+            states: vec![FxrState {
+                transitions: vec![FxrTransition {
+                    unk00: 11,
+                    unk08: 1,
+                    unk10: 89,
+                    unk18: 1,
+                    field1: FxrField::from_i32(-2),
+                    field2: FxrField::from_f32(0.0),
+                    ..Default::default()
+                }],
+            }],
+            root: FxrContainer {
+                container_type: 2000,
+                effects: vec![],
+                containers: vec![FxrContainer {
+                    container_type: 2001,
+                    effects: vec![FxrEffect {
+                        effect_type: 1002,
+                        actions: vec![action.clone(), action.clone()],
+                    }],
+                    containers: vec![],
+                    actions: vec![],
+                }],
+                actions: vec![action],
+            },
+            section12: vec![1, 2],
+            section13: vec![],
+            section14: vec![3],
+        };
+
+        let bytes = fxr.to_bytes().unwrap();
+        let read = Fxr::from_reader(&mut Cursor::new(&bytes)).unwrap();
+
+        assert_eq!(read, fxr);
+        assert_eq!(read.to_bytes().unwrap(), bytes);
+    }
+}
@@ -0,0 +1,254 @@
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+use core::{fmt, str::FromStr};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "archive")]
+use crate::bhd::BhdFormat;
+#[cfg(feature = "fmg")]
+use crate::fmg::FmgVersion;
+#[cfg(feature = "fxr")]
+use crate::fxr::FxrVersion;
+#[cfg(feature = "gparam")]
+use crate::gparam::GparamGame;
+#[cfg(all(feature = "param", feature = "std"))]
+use crate::param::regulation::{DS3_REGULATION_KEY, ER_REGULATION_KEY};
+#[cfg(feature = "save")]
+use crate::save::SaveGame;
+
+/// The FROMSOFTWARE games whose files this crate knows about.
+///
+/// Formats evolved from game to game, so this selects the revision of a format to expect where the
+/// files themselves don't say, along with the archives and path hashing of each game.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Game {
+    DemonsSouls,
+    DarkSouls,
+    DarkSoulsRemastered,
+    DarkSouls2,
+    DarkSouls3,
+    Bloodborne,
+    Sekiro,
+    EldenRing,
+    ArmoredCore6,
+}
+
+impl Game {
+    pub const ALL: [Self; 9] = [
+        Self::DemonsSouls,
+        Self::DarkSouls,
+        Self::DarkSoulsRemastered,
+        Self::DarkSouls2,
+        Self::DarkSouls3,
+        Self::Bloodborne,
+        Self::Sekiro,
+        Self::EldenRing,
+        Self::ArmoredCore6,
+    ];
+
+    /// Short name used on the command line and for key directories, e.g. `er`.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::DemonsSouls => "des",
+            Self::DarkSouls => "ds1",
+            Self::DarkSoulsRemastered => "ds1r",
+            Self::DarkSouls2 => "ds2",
+            Self::DarkSouls3 => "ds3",
+            Self::Bloodborne => "bb",
+            Self::Sekiro => "sdt",
+            Self::EldenRing => "er",
+            Self::ArmoredCore6 => "ac6",
+        }
+    }
+
+    /// Detect the game installed in `directory` from the name of its executable.
+    #[cfg(feature = "std")]
+    pub fn detect(directory: impl AsRef<Path>) -> Option<Self> {
+        let directory = directory.as_ref();
+
+        [
+            ("DARKSOULS.exe", Self::DarkSouls),
+            ("DarkSoulsRemastered.exe", Self::DarkSoulsRemastered),
+            ("DarkSoulsII.exe", Self::DarkSouls2),
+            ("DarkSoulsIII.exe", Self::DarkSouls3),
+            ("sekiro.exe", Self::Sekiro),
+            ("eldenring.exe", Self::EldenRing),
+            ("armoredcore6.exe", Self::ArmoredCore6),
+        ]
+        .into_iter()
+        .find(|(executable, _)| directory.join(executable).is_file())
+        .map(|(_, game)| game)
+    }
+
+    /// Names of the game's BHD5 archives relative to its install directory, without extensions.
+    /// Empty for console-only games, whose archives aren't supported.
+    pub fn archives(self) -> &'static [&'static str] {
+        match self {
+            Self::DemonsSouls | Self::Bloodborne => &[],
+            Self::DarkSouls | Self::DarkSoulsRemastered => {
+                &["dvdbnd0", "dvdbnd1", "dvdbnd2", "dvdbnd3"]
+            }
+            Self::DarkSouls2 => &[
+                "GameDataEbl",
+                "HqChrEbl",
+                "HqMapEbl",
+                "HqObjEbl",
+                "HqPartsEbl",
+                "LqChrEbl",
+                "LqMapEbl",
+                "LqObjEbl",
+                "LqPartsEbl",
+            ],
+            Self::DarkSouls3 => &["Data1", "Data2", "Data3", "Data4", "Data5", "DLC1", "DLC2"],
+            Self::Sekiro => &["Data1", "Data2", "Data3", "Data4", "Data5"],
+            Self::EldenRing => &["Data0", "Data1", "Data2", "Data3", "sd/sd"],
+            Self::ArmoredCore6 => &["data0", "data1", "data2", "data3", "sd/sd"],
+        }
+    }
+
+    /// Extension of the archive headers, whose data is always in a `.bdt`.
+    pub fn archive_header_extension(self) -> &'static str {
+        match self {
+            Self::DarkSouls | Self::DarkSoulsRemastered => "bhd5",
+            _ => "bhd",
+        }
+    }
+
+    #[cfg(feature = "archive")]
+    pub fn bhd_format(self) -> Option<BhdFormat> {
+        match self {
+            Self::DemonsSouls | Self::Bloodborne => None,
+            Self::DarkSouls | Self::DarkSoulsRemastered => Some(BhdFormat::DarkSouls1),
+            Self::DarkSouls2 => Some(BhdFormat::DarkSouls2),
+            Self::DarkSouls3 | Self::Sekiro => Some(BhdFormat::DarkSouls3),
+            Self::EldenRing | Self::ArmoredCore6 => Some(BhdFormat::EldenRing),
+        }
+    }
+
+    /// Hash a path the way the game's archives index their files: lowercased, with forward
+    /// slashes and a leading slash.
+    pub fn hash_path(self, path: &str) -> u64 {
+        let prefix = (!path.starts_with('/')).then_some('/');
+        let chars = prefix
+            .into_iter()
+            .chain(path.chars().map(|ch| ch.to_ascii_lowercase()))
+            .map(|ch| if ch == '\\' { '/' } else { ch });
+
+        // Hashes widened to 64 bits with Elden Ring.
+        match self {
+            Self::EldenRing | Self::ArmoredCore6 => chars.fold(0u64, |hash, next| {
+                hash.wrapping_mul(0x85).wrapping_add(next as u64)
+            }),
+            _ => chars.fold(0u32, |hash, next| {
+                hash.wrapping_mul(0x25).wrapping_add(next as u32)
+            }) as u64,
+        }
+    }
+
+    #[cfg(feature = "fmg")]
+    pub fn fmg_version(self) -> FmgVersion {
+        match self {
+            Self::DemonsSouls => FmgVersion::DemonsSouls,
+            Self::DarkSouls | Self::DarkSoulsRemastered | Self::DarkSouls2 => {
+                FmgVersion::DarkSouls1
+            }
+            _ => FmgVersion::DarkSouls3,
+        }
+    }
+
+    #[cfg(feature = "fxr")]
+    pub fn fxr_version(self) -> Option<FxrVersion> {
+        match self {
+            Self::DarkSouls3 => Some(FxrVersion::DarkSouls3),
+            Self::Sekiro | Self::EldenRing | Self::ArmoredCore6 => Some(FxrVersion::Sekiro),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "gparam")]
+    pub fn gparam_game(self) -> Option<GparamGame> {
+        match self {
+            Self::DarkSouls2 => Some(GparamGame::DarkSouls2),
+            Self::Bloodborne | Self::DarkSouls3 => Some(GparamGame::DarkSouls3),
+            Self::Sekiro | Self::EldenRing | Self::ArmoredCore6 => Some(GparamGame::Sekiro),
+            _ => None,
+        }
+    }
+
+    /// The key the game's regulation is encrypted with, where it's encrypted with AES-256.
+    #[cfg(all(feature = "param", feature = "std"))]
+    pub fn regulation_key(self) -> Option<[u8; 32]> {
+        match self {
+            Self::DarkSouls3 => Some(DS3_REGULATION_KEY),
+            Self::EldenRing => Some(ER_REGULATION_KEY),
+            _ => None,
+        }
+    }
+
+    #[cfg(feature = "save")]
+    pub fn save_game(self) -> Option<SaveGame> {
+        match self {
+            Self::DarkSouls3 => Some(SaveGame::DarkSouls3),
+            Self::Sekiro => Some(SaveGame::Sekiro),
+            Self::EldenRing => Some(SaveGame::EldenRing),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::DemonsSouls => "Demon's Souls",
+            Self::DarkSouls => "Dark Souls",
+            Self::DarkSoulsRemastered => "Dark Souls Remastered",
+            Self::DarkSouls2 => "Dark Souls 2",
+            Self::DarkSouls3 => "Dark Souls 3",
+            Self::Bloodborne => "Bloodborne",
+            Self::Sekiro => "Sekiro",
+            Self::EldenRing => "Elden Ring",
+            Self::ArmoredCore6 => "Armored Core 6",
+        })
+    }
+}
+
+impl FromStr for Game {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|game| game.id().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let ids = Self::ALL.map(Self::id).join(", ");
+                format!("unknown game {s}, expected one of {ids}")
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::game::Game;
+
+    #[test]
+    pub fn hashes_paths_per_game() {
+        assert_eq!(
+            Game::EldenRing.hash_path("/chr/c0000.anibnd.dcx"),
+            Game::EldenRing.hash_path("CHR\\c0000.anibnd.dcx")
+        );
+        assert!(Game::DarkSouls3.hash_path("/chr/c0000.anibnd.dcx") <= u32::MAX as u64);
+        assert_eq!("ER".parse(), Ok(Game::EldenRing));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    pub fn serializes_to_json() {
+        assert_eq!(
+            serde_json::to_string(&Game::EldenRing).unwrap(),
+            "\"EldenRing\""
+        );
+    }
+}
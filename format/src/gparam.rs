@@ -0,0 +1,710 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use thiserror::Error;
+
+use crate::io_ext::ReadFormatsExt;
+
+#[derive(Debug, Error)]
+pub enum GparamError {
+    #[error("Could not read GPARAM: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unknown GPARAM game {0}")]
+    UnknownGame(i32),
+
+    #[error("Unknown GPARAM value type {0:#x}")]
+    UnknownValueType(u8),
+
+    #[error("Param {param} holds {expected:?} values, not {actual:?}")]
+    TypeMismatch {
+        param: String,
+        expected: GparamValueType,
+        actual: GparamValueType,
+    },
+
+    #[error("Param {param} has no value {index}")]
+    NoSuchValue { param: String, index: usize },
+}
+
+/// The layout revision of a GPARAM, named for the first game to use it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum GparamGame {
+    /// Dark Souls 2. Names are Shift-JIS and there is no display name.
+    DarkSouls2,
+
+    /// Bloodborne and Dark Souls 3.
+    DarkSouls3,
+
+    /// Sekiro and Elden Ring, which store an extra float per value.
+    Sekiro,
+}
+
+impl GparamGame {
+    fn from_i32(value: i32) -> Result<Self, GparamError> {
+        match value {
+            2 => Ok(Self::DarkSouls2),
+            3 => Ok(Self::DarkSouls3),
+            5 => Ok(Self::Sekiro),
+            _ => Err(GparamError::UnknownGame(value)),
+        }
+    }
+
+    fn as_i32(self) -> i32 {
+        match self {
+            Self::DarkSouls2 => 2,
+            Self::DarkSouls3 => 3,
+            Self::Sekiro => 5,
+        }
+    }
+
+    fn header_size(self) -> u32 {
+        match self {
+            Self::DarkSouls2 => 0x40,
+            Self::DarkSouls3 => 0x50,
+            Self::Sekiro => 0x54,
+        }
+    }
+}
+
+/// A graphics param file (`.gparam`), holding draw settings such as lighting, fog and
+/// post-processing for an area.
+///
+/// Params are grouped, and each param holds a small table of values keyed by ID. Data after the
+/// values, such as editor comments, is kept undecoded and written back as is.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Gparam {
+    pub game: GparamGame,
+    pub unk0d: bool,
+    pub unk14: i32,
+    pub unk40: i32,
+    pub unk50: f32,
+    pub groups: Vec<GparamGroup>,
+
+    unk3_count: i32,
+    tail: Vec<u8>,
+
+    /// Offsets into the tail of the unk2, unk3, unk3 value ID, and comment tables.
+    tail_offsets: [u32; 6],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GparamGroup {
+    /// Internal name of the group, e.g. `LightSet ##`.
+    pub name: String,
+
+    /// Display name of the group, absent in Dark Souls 2.
+    pub display_name: Option<String>,
+    pub params: Vec<GparamParam>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GparamParam {
+    pub name: String,
+    pub display_name: Option<String>,
+    pub value_type: GparamValueType,
+    pub values: Vec<GparamValue>,
+
+    /// The ID of each value, usually a time of day or weather variation.
+    pub value_ids: Vec<i32>,
+
+    /// An unknown float stored alongside each value ID by Sekiro and later.
+    pub unk_floats: Vec<f32>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum GparamValueType {
+    Byte,
+    Short,
+    IntA,
+    BoolA,
+    IntB,
+    Float,
+    BoolB,
+    Float2,
+    Float3,
+    Float4,
+    Byte4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum GparamValue {
+    Byte(u8),
+    Short(i16),
+    IntA(i32),
+    BoolA(bool),
+    IntB(i32),
+    Float(f32),
+    BoolB(bool),
+    Float2([f32; 2]),
+    Float3([f32; 3]),
+    Float4([f32; 4]),
+    Byte4([u8; 4]),
+}
+
+impl GparamValueType {
+    fn from_u8(value: u8) -> Result<Self, GparamError> {
+        Ok(match value {
+            0x1 => Self::Byte,
+            0x2 => Self::Short,
+            0x3 => Self::IntA,
+            0x5 => Self::BoolA,
+            0x7 => Self::IntB,
+            0x9 => Self::Float,
+            0xB => Self::BoolB,
+            0xC => Self::Float2,
+            0xD => Self::Float3,
+            0xE => Self::Float4,
+            0xF => Self::Byte4,
+            _ => return Err(GparamError::UnknownValueType(value)),
+        })
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Self::Byte => 0x1,
+            Self::Short => 0x2,
+            Self::IntA => 0x3,
+            Self::BoolA => 0x5,
+            Self::IntB => 0x7,
+            Self::Float => 0x9,
+            Self::BoolB => 0xB,
+            Self::Float2 => 0xC,
+            Self::Float3 => 0xD,
+            Self::Float4 => 0xE,
+            Self::Byte4 => 0xF,
+        }
+    }
+}
+
+impl GparamValue {
+    pub fn value_type(&self) -> GparamValueType {
+        match self {
+            Self::Byte(_) => GparamValueType::Byte,
+            Self::Short(_) => GparamValueType::Short,
+            Self::IntA(_) => GparamValueType::IntA,
+            Self::BoolA(_) => GparamValueType::BoolA,
+            Self::IntB(_) => GparamValueType::IntB,
+            Self::Float(_) => GparamValueType::Float,
+            Self::BoolB(_) => GparamValueType::BoolB,
+            Self::Float2(_) => GparamValueType::Float2,
+            Self::Float3(_) => GparamValueType::Float3,
+            Self::Float4(_) => GparamValueType::Float4,
+            Self::Byte4(_) => GparamValueType::Byte4,
+        }
+    }
+
+    fn read(r: &mut impl Read, value_type: GparamValueType) -> io::Result<Self> {
+        Ok(match value_type {
+            GparamValueType::Byte => Self::Byte(r.read_u8()?),
+            GparamValueType::Short => Self::Short(r.read_i16::<LE>()?),
+            GparamValueType::IntA => Self::IntA(r.read_i32::<LE>()?),
+            GparamValueType::BoolA => Self::BoolA(r.read_bool()?),
+            GparamValueType::IntB => Self::IntB(r.read_i32::<LE>()?),
+            GparamValueType::Float => Self::Float(r.read_f32::<LE>()?),
+            GparamValueType::BoolB => Self::BoolB(r.read_bool()?),
+            GparamValueType::Float2 => {
+                let value = [r.read_f32::<LE>()?, r.read_f32::<LE>()?];
+                r.read_padding(8)?;
+                Self::Float2(value)
+            }
+            GparamValueType::Float3 => {
+                let value = [
+                    r.read_f32::<LE>()?,
+                    r.read_f32::<LE>()?,
+                    r.read_f32::<LE>()?,
+                ];
+                r.read_padding(4)?;
+                Self::Float3(value)
+            }
+            GparamValueType::Float4 => Self::Float4([
+                r.read_f32::<LE>()?,
+                r.read_f32::<LE>()?,
+                r.read_f32::<LE>()?,
+                r.read_f32::<LE>()?,
+            ]),
+            GparamValueType::Byte4 => {
+                let mut value = [0u8; 4];
+                r.read_exact(&mut value)?;
+                Self::Byte4(value)
+            }
+        })
+    }
+
+    fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        match *self {
+            Self::Byte(value) => w.write_u8(value),
+            Self::Short(value) => w.write_i16::<LE>(value),
+            Self::IntA(value) | Self::IntB(value) => w.write_i32::<LE>(value),
+            Self::BoolA(value) | Self::BoolB(value) => w.write_u8(value as u8),
+            Self::Float(value) => w.write_f32::<LE>(value),
+            Self::Float2(value) => {
+                value.iter().try_for_each(|v| w.write_f32::<LE>(*v))?;
+                w.write_all(&[0; 8])
+            }
+            Self::Float3(value) => {
+                value.iter().try_for_each(|v| w.write_f32::<LE>(*v))?;
+                w.write_all(&[0; 4])
+            }
+            Self::Float4(value) => value.iter().try_for_each(|v| w.write_f32::<LE>(*v)),
+            Self::Byte4(value) => w.write_all(&value),
+        }
+    }
+}
+
+/// Offsets of each region of a GPARAM, as stored in its header.
+#[derive(Default)]
+struct Regions {
+    group_headers: u32,
+    param_header_offsets: u32,
+    param_headers: u32,
+    values: u32,
+    value_ids: u32,
+}
+
+impl Gparam {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, GparamError> {
+        r.read_magic(b"f\0i\0l\0t\0")?;
+
+        let game = GparamGame::from_i32(r.read_i32::<LE>()?)?;
+        r.read_padding(1)?;
+        let unk0d = r.read_bool()?;
+        r.read_padding(2)?;
+        let group_count = r.read_u32::<LE>()?;
+        let unk14 = r.read_i32::<LE>()?;
+        let _header_size = r.read_u32::<LE>()?;
+
+        let regions = Regions {
+            group_headers: r.read_u32::<LE>()?,
+            param_header_offsets: r.read_u32::<LE>()?,
+            param_headers: r.read_u32::<LE>()?,
+            values: r.read_u32::<LE>()?,
+            value_ids: r.read_u32::<LE>()?,
+        };
+
+        let unk2 = r.read_u32::<LE>()?;
+        let unk3_count = r.read_i32::<LE>()?;
+        let unk3 = r.read_u32::<LE>()?;
+        let unk3_value_ids = r.read_u32::<LE>()?;
+
+        let mut comments = [unk2; 3];
+        let mut unk40 = 0;
+        let mut unk50 = 0.0;
+        if game != GparamGame::DarkSouls2 {
+            unk40 = r.read_i32::<LE>()?;
+            for offset in &mut comments {
+                *offset = r.read_u32::<LE>()?;
+            }
+        }
+        if game == GparamGame::Sekiro {
+            unk50 = r.read_f32::<LE>()?;
+        }
+
+        let mut group_offsets = vec![0; group_count as usize];
+        r.read_u32_into::<LE>(&mut group_offsets)?;
+
+        let mut groups = Vec::with_capacity(group_offsets.len());
+        for offset in group_offsets {
+            r.seek(SeekFrom::Start((regions.group_headers + offset) as u64))?;
+            groups.push(GparamGroup::read(r, game, &regions)?);
+        }
+
+        r.seek(SeekFrom::Start(unk2 as u64))?;
+        let mut tail = Vec::new();
+        r.read_to_end(&mut tail)?;
+
+        let [comment_offsets_offsets, comment_offsets, comments] = comments;
+        let tail_offsets = [
+            unk2,
+            unk3,
+            unk3_value_ids,
+            comment_offsets_offsets,
+            comment_offsets,
+            comments,
+        ]
+        .map(|offset| offset.saturating_sub(unk2));
+
+        Ok(Self {
+            game,
+            unk0d,
+            unk14,
+            unk40,
+            unk50,
+            groups,
+            unk3_count,
+            tail,
+            tail_offsets,
+        })
+    }
+
+    pub fn group(&self, name: &str) -> Option<&GparamGroup> {
+        self.groups.iter().find(|group| group.name == name)
+    }
+
+    pub fn group_mut(&mut self, name: &str) -> Option<&mut GparamGroup> {
+        self.groups.iter_mut().find(|group| group.name == name)
+    }
+
+    /// Find a param by the names of its group and itself.
+    pub fn param(&self, group: &str, param: &str) -> Option<&GparamParam> {
+        self.group(group)?.param(param)
+    }
+
+    pub fn param_mut(&mut self, group: &str, param: &str) -> Option<&mut GparamParam> {
+        self.group_mut(group)?.param_mut(param)
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(&self.to_bytes())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let unicode = self.game != GparamGame::DarkSouls2;
+        let params = self.groups.iter().flat_map(|group| &group.params);
+
+        // Lay out the regions back to front, as each refers to offsets within the next.
+        let mut values = Vec::new();
+        let mut value_ids = Vec::new();
+        let mut param_headers = Vec::new();
+        let mut header_offsets = Vec::new();
+        for param in params {
+            header_offsets.push(param_headers.len() as u32);
+            param_headers.extend((values.len() as u32).to_le_bytes());
+            param_headers.extend((value_ids.len() as u32).to_le_bytes());
+            param_headers.extend([param.value_type.as_u8(), param.values.len() as u8, 0, 0]);
+            write_name(
+                &mut param_headers,
+                &param.name,
+                &param.display_name,
+                unicode,
+            );
+
+            for value in &param.values {
+                let _ = value.write(&mut values);
+            }
+            pad(&mut values, 4);
+
+            for (index, id) in param.value_ids.iter().enumerate() {
+                value_ids.extend(id.to_le_bytes());
+                if self.game == GparamGame::Sekiro {
+                    let unk = param.unk_floats.get(index).copied().unwrap_or_default();
+                    value_ids.extend(unk.to_le_bytes());
+                }
+            }
+        }
+
+        let mut header_offsets = header_offsets.into_iter();
+        let mut param_header_offsets = Vec::new();
+        let mut group_headers = Vec::new();
+        let mut group_offsets = Vec::new();
+        for group in &self.groups {
+            group_offsets.push(group_headers.len() as u32);
+
+            group_headers.extend((group.params.len() as u32).to_le_bytes());
+            group_headers.extend((param_header_offsets.len() as u32).to_le_bytes());
+            write_name(
+                &mut group_headers,
+                &group.name,
+                &group.display_name,
+                unicode,
+            );
+
+            for offset in header_offsets.by_ref().take(group.params.len()) {
+                param_header_offsets.extend(offset.to_le_bytes());
+            }
+        }
+
+        let header_size = self.game.header_size();
+        let group_headers_start = header_size + group_offsets.len() as u32 * 4;
+        let param_header_offsets_start = group_headers_start + group_headers.len() as u32;
+        let param_headers_start = param_header_offsets_start + param_header_offsets.len() as u32;
+        let values_start = param_headers_start + param_headers.len() as u32;
+        let value_ids_start = values_start + values.len() as u32;
+        let tail_start = value_ids_start + value_ids.len() as u32;
+        let [unk2, unk3, unk3_value_ids, comment_offsets_offsets, comment_offsets, comments] =
+            self.tail_offsets.map(|offset| offset + tail_start);
+
+        let mut out = Vec::with_capacity(tail_start as usize + self.tail.len());
+        out.extend(b"f\0i\0l\0t\0");
+        out.extend(self.game.as_i32().to_le_bytes());
+        out.extend([0, self.unk0d as u8, 0, 0]);
+        out.extend((self.groups.len() as u32).to_le_bytes());
+        out.extend(self.unk14.to_le_bytes());
+        out.extend(header_size.to_le_bytes());
+
+        for offset in [
+            group_headers_start,
+            param_header_offsets_start,
+            param_headers_start,
+            values_start,
+            value_ids_start,
+            unk2,
+        ] {
+            out.extend(offset.to_le_bytes());
+        }
+        out.extend(self.unk3_count.to_le_bytes());
+        out.extend(unk3.to_le_bytes());
+        out.extend(unk3_value_ids.to_le_bytes());
+
+        if self.game != GparamGame::DarkSouls2 {
+            out.extend(self.unk40.to_le_bytes());
+            for offset in [comment_offsets_offsets, comment_offsets, comments] {
+                out.extend(offset.to_le_bytes());
+            }
+        }
+        if self.game == GparamGame::Sekiro {
+            out.extend(self.unk50.to_le_bytes());
+        }
+
+        out.extend(group_offsets.iter().flat_map(|offset| offset.to_le_bytes()));
+        out.extend(group_headers);
+        out.extend(param_header_offsets);
+        out.extend(param_headers);
+        out.extend(values);
+        out.extend(value_ids);
+        out.extend(&self.tail);
+
+        out
+    }
+}
+
+impl GparamGroup {
+    fn read(
+        r: &mut (impl Read + Seek),
+        game: GparamGame,
+        regions: &Regions,
+    ) -> Result<Self, GparamError> {
+        let param_count = r.read_u32::<LE>()?;
+        let param_header_offsets_offset = r.read_u32::<LE>()?;
+        let (name, display_name) = read_name(r, game)?;
+
+        r.seek(SeekFrom::Start(
+            (regions.param_header_offsets + param_header_offsets_offset) as u64,
+        ))?;
+        let mut param_offsets = vec![0; param_count as usize];
+        r.read_u32_into::<LE>(&mut param_offsets)?;
+
+        let mut params = Vec::with_capacity(param_offsets.len());
+        for offset in param_offsets {
+            r.seek(SeekFrom::Start((regions.param_headers + offset) as u64))?;
+            params.push(GparamParam::read(r, game, regions)?);
+        }
+
+        Ok(Self {
+            name,
+            display_name,
+            params,
+        })
+    }
+
+    pub fn param(&self, name: &str) -> Option<&GparamParam> {
+        self.params.iter().find(|param| param.name == name)
+    }
+
+    pub fn param_mut(&mut self, name: &str) -> Option<&mut GparamParam> {
+        self.params.iter_mut().find(|param| param.name == name)
+    }
+}
+
+impl GparamParam {
+    fn read(
+        r: &mut (impl Read + Seek),
+        game: GparamGame,
+        regions: &Regions,
+    ) -> Result<Self, GparamError> {
+        let values_offset = r.read_u32::<LE>()?;
+        let value_ids_offset = r.read_u32::<LE>()?;
+        let value_type = GparamValueType::from_u8(r.read_u8()?)?;
+        let value_count = r.read_u8()? as usize;
+        r.read_padding(2)?;
+        let (name, display_name) = read_name(r, game)?;
+
+        r.seek(SeekFrom::Start((regions.values + values_offset) as u64))?;
+        let values = (0..value_count)
+            .map(|_| GparamValue::read(r, value_type))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        r.seek(SeekFrom::Start(
+            (regions.value_ids + value_ids_offset) as u64,
+        ))?;
+        let mut value_ids = Vec::with_capacity(value_count);
+        let mut unk_floats = Vec::new();
+        for _ in 0..value_count {
+            value_ids.push(r.read_i32::<LE>()?);
+            if game == GparamGame::Sekiro {
+                unk_floats.push(r.read_f32::<LE>()?);
+            }
+        }
+
+        Ok(Self {
+            name,
+            display_name,
+            value_type,
+            values,
+            value_ids,
+            unk_floats,
+        })
+    }
+
+    /// The value with the given ID, if any.
+    pub fn value_by_id(&self, id: i32) -> Option<&GparamValue> {
+        let index = self.value_ids.iter().position(|value_id| *value_id == id)?;
+
+        self.values.get(index)
+    }
+
+    /// Replace the value at `index`, which must be of the param's type.
+    pub fn set_value(&mut self, index: usize, value: GparamValue) -> Result<(), GparamError> {
+        if value.value_type() != self.value_type {
+            return Err(GparamError::TypeMismatch {
+                param: self.name.clone(),
+                expected: self.value_type,
+                actual: value.value_type(),
+            });
+        }
+
+        let slot = self
+            .values
+            .get_mut(index)
+            .ok_or_else(|| GparamError::NoSuchValue {
+                param: self.name.clone(),
+                index,
+            })?;
+        *slot = value;
+
+        Ok(())
+    }
+
+    /// Add a value with a new ID, which must be of the param's type.
+    pub fn push_value(&mut self, id: i32, value: GparamValue) -> Result<(), GparamError> {
+        if value.value_type() != self.value_type {
+            return Err(GparamError::TypeMismatch {
+                param: self.name.clone(),
+                expected: self.value_type,
+                actual: value.value_type(),
+            });
+        }
+
+        self.values.push(value);
+        self.value_ids.push(id);
+        if !self.unk_floats.is_empty() {
+            self.unk_floats.push(0.0);
+        }
+
+        Ok(())
+    }
+}
+
+fn read_name(
+    r: &mut (impl Read + Seek),
+    game: GparamGame,
+) -> Result<(String, Option<String>), GparamError> {
+    if game == GparamGame::DarkSouls2 {
+        let mut bytes = Vec::new();
+        loop {
+            match r.read_u8()? {
+                0 => break,
+                byte => bytes.push(byte),
+            }
+        }
+
+        let (name, _, _) = encoding_rs::SHIFT_JIS.decode(&bytes);
+        Ok((name.into_owned(), None))
+    } else {
+        let name = r.read_utf16::<LE>()?;
+        let display_name = r.read_utf16::<LE>()?;
+
+        Ok((name, Some(display_name)))
+    }
+}
+
+/// Write a null-terminated name, and display name if the format has them, padded to 4 bytes.
+fn write_name(out: &mut Vec<u8>, name: &str, display_name: &Option<String>, unicode: bool) {
+    if unicode {
+        for string in [name, display_name.as_deref().unwrap_or_default()] {
+            out.extend(string.encode_utf16().chain([0]).flat_map(u16::to_le_bytes));
+        }
+    } else {
+        let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode(name);
+        out.extend(bytes.iter());
+        out.push(0);
+    }
+
+    pad(out, 4);
+}
+
+fn pad(out: &mut Vec<u8>, alignment: usize) {
+    out.resize(out.len().next_multiple_of(alignment), 0);
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::gparam::{
+        Gparam, GparamGame, GparamGroup, GparamParam, GparamValue, GparamValueType,
+    };
+
+    #[test]
+    pub fn round_trips_edits() {
+        let mut gparam = Gparam {
+            game: GparamGame::Sekiro,
+            unk0d: true,
+            unk14: 1,
+            unk40: 0,
+            unk50: 2.5,
+            groups: vec![GparamGroup {
+                name: "LightSet ##".to_string(),
+                display_name: Some("Light".to_string()),
+                params: vec![
+                    GparamParam {
+                        name: "DirLightDiffColor0".to_string(),
+                        display_name: Some("Diffuse".to_string()),
+                        value_type: GparamValueType::Float3,
+                        values: vec![GparamValue::Float3([1.0, 0.5, 0.25])],
+                        value_ids: vec![0],
+                        unk_floats: vec![0.0],
+                    },
+                    GparamParam {
+                        name: "Enabled".to_string(),
+                        display_name: Some("Enabled".to_string()),
+                        value_type: GparamValueType::BoolA,
+                        values: vec![GparamValue::BoolA(true), GparamValue::BoolA(false)],
+                        value_ids: vec![0, 1],
+                        unk_floats: vec![0.0, 1.0],
+                    },
+                ],
+            }],
+            unk3_count: 0,
+            tail: vec![1, 2, 3, 4],
+            tail_offsets: [0, 4, 4, 4, 4, 4],
+        };
+
+        gparam
+            .param_mut("LightSet ##", "DirLightDiffColor0")
+            .unwrap()
+            .set_value(0, GparamValue::Float3([0.0, 1.0, 0.0]))
+            .unwrap();
+        assert!(gparam
+            .param_mut("LightSet ##", "Enabled")
+            .unwrap()
+            .set_value(0, GparamValue::Float(1.0))
+            .is_err());
+
+        let bytes = gparam.to_bytes();
+        let read = Gparam::from_reader(&mut Cursor::new(&bytes)).unwrap();
+
+        assert_eq!(read, gparam);
+        assert_eq!(
+            read.param("LightSet ##", "Enabled").unwrap().value_by_id(1),
+            Some(&GparamValue::BoolA(false))
+        );
+        assert_eq!(read.to_bytes(), bytes);
+    }
+}
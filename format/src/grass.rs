@@ -0,0 +1,194 @@
+use std::io::{self, Read};
+
+use byteorder::{ByteOrder, ReadBytesExt, LE};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum GrassError {
+    #[error("Could not read GRASS: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unknown GRASS version {0}")]
+    UnknownVersion(u32),
+
+    #[error("GRASS {what} records are {actual:#x} bytes, expected at least {expected:#x}")]
+    RecordTooSmall {
+        what: &'static str,
+        actual: u32,
+        expected: u32,
+    },
+}
+
+const GRASS_VERSION: u32 = 1;
+
+/// Size of the header fields that are read, the header may be padded beyond this.
+const HEADER_FIELDS_SIZE: u32 = 0x20;
+
+const VOLUME_SIZE: u32 = 0x18;
+const VERTEX_SIZE: u32 = 0xC;
+const FACE_SIZE: u32 = 0x24;
+
+/// Number of grass types a face stores a density for.
+pub const GRASS_TYPE_COUNT: usize = 5;
+
+/// Procedural foliage placement for a map (`.grass`): a triangle mesh laid over the map geometry,
+/// with the density of each grass type painted per face, and a hierarchy of bounding volumes over
+/// it for culling.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Grass {
+    pub volumes: Vec<GrassVolume>,
+    pub vertices: Vec<[f32; 3]>,
+    pub faces: Vec<GrassFace>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GrassVolume {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GrassFace {
+    pub unk00: i32,
+    pub vertices: [u32; 3],
+
+    /// Density of each grass type on this face, from 0 to 1.
+    pub densities: [f32; GRASS_TYPE_COUNT],
+}
+
+impl Grass {
+    pub fn from_reader(r: &mut impl Read) -> Result<Self, GrassError> {
+        let version = r.read_u32::<LE>()?;
+        if version != GRASS_VERSION {
+            return Err(GrassError::UnknownVersion(version));
+        }
+
+        let header_size = r.read_u32::<LE>()?;
+        let volume_size = record_size(r, "volume", VOLUME_SIZE)?;
+        let vertex_size = record_size(r, "vertex", VERTEX_SIZE)?;
+        let face_size = record_size(r, "face", FACE_SIZE)?;
+        let volume_count = r.read_u32::<LE>()?;
+        let vertex_count = r.read_u32::<LE>()?;
+        let face_count = r.read_u32::<LE>()?;
+
+        let mut rest = vec![0u8; header_size.saturating_sub(HEADER_FIELDS_SIZE) as usize];
+        r.read_exact(&mut rest)?;
+
+        let volumes = read_records(r, volume_count, volume_size, |record| {
+            let float = |index: usize| LE::read_f32(&record[index * 4..]);
+
+            GrassVolume {
+                min: [float(0), float(1), float(2)],
+                max: [float(3), float(4), float(5)],
+            }
+        })?;
+
+        let vertices = read_records(r, vertex_count, vertex_size, |record| {
+            std::array::from_fn(|i| LE::read_f32(&record[i * 4..]))
+        })?;
+
+        let faces = read_records(r, face_count, face_size, |record| GrassFace {
+            unk00: LE::read_i32(record),
+            vertices: std::array::from_fn(|i| LE::read_u32(&record[4 + i * 4..])),
+            densities: std::array::from_fn(|i| LE::read_f32(&record[0x10 + i * 4..])),
+        })?;
+
+        Ok(Self {
+            volumes,
+            vertices,
+            faces,
+        })
+    }
+
+    /// The corner positions of a face, if its vertex indices are valid.
+    pub fn face_positions(&self, face: &GrassFace) -> Option<[[f32; 3]; 3]> {
+        let [a, b, c] = face.vertices;
+
+        Some([
+            *self.vertices.get(a as usize)?,
+            *self.vertices.get(b as usize)?,
+            *self.vertices.get(c as usize)?,
+        ])
+    }
+
+    /// Faces where grass of the given type grows.
+    pub fn faces_with_type(&self, grass_type: usize) -> impl Iterator<Item = &GrassFace> {
+        self.faces.iter().filter(move |face| {
+            face.densities
+                .get(grass_type)
+                .is_some_and(|density| *density > 0.0)
+        })
+    }
+}
+
+fn record_size(r: &mut impl Read, what: &'static str, expected: u32) -> Result<u32, GrassError> {
+    let actual = r.read_u32::<LE>()?;
+    if actual < expected {
+        return Err(GrassError::RecordTooSmall {
+            what,
+            actual,
+            expected,
+        });
+    }
+
+    Ok(actual)
+}
+
+fn read_records<T>(
+    r: &mut impl Read,
+    count: u32,
+    size: u32,
+    decode: impl Fn(&[u8]) -> T,
+) -> io::Result<Vec<T>> {
+    let mut record = vec![0u8; size as usize];
+
+    (0..count)
+        .map(|_| {
+            r.read_exact(&mut record)?;
+            Ok(decode(&record))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::grass::Grass;
+
+    #[test]
+    pub fn reads_faces() {
+        let mut bytes = Vec::new();
+        for value in [1u32, 0x28, 0x18, 0xC, 0x24, 1, 3, 1, 0, 0] {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        for value in [0.0f32, 0.0, 0.0, 1.0, 1.0, 1.0] {
+            bytes.extend(value.to_le_bytes());
+        }
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0] {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        bytes.extend(0i32.to_le_bytes());
+        for index in [0u32, 1, 2] {
+            bytes.extend(index.to_le_bytes());
+        }
+        for density in [0.0f32, 0.5, 0.0, 0.0, 1.0] {
+            bytes.extend(density.to_le_bytes());
+        }
+
+        let grass = Grass::from_reader(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(grass.volumes[0].max, [1.0, 1.0, 1.0]);
+        assert_eq!(
+            grass.face_positions(&grass.faces[0]).unwrap()[1],
+            [1.0, 0.0, 0.0]
+        );
+        assert_eq!(grass.faces_with_type(1).count(), 1);
+        assert_eq!(grass.faces_with_type(2).count(), 0);
+    }
+}
@@ -0,0 +1,777 @@
+use crate::hkx::{
+    HkQsTransform, HkxError, HkxPackfile, HkxPointer, HkxTagfile, TagRecord, TagValue,
+};
+
+const SPLINE_CLASS: &str = "hkaSplineCompressedAnimation";
+
+/// Rotation quantization formats, stored in bits 2-5 of a track's quantization types. The 24-bit
+/// and 16-bit formats are not used by any of the games and are unsupported.
+const ROTATION_POLAR32: u8 = 0;
+const ROTATION_THREECOMP40: u8 = 1;
+const ROTATION_THREECOMP48: u8 = 2;
+const ROTATION_UNCOMPRESSED: u8 = 5;
+
+const IDENTITY_ROTATION: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+
+/// An `hkaSplineCompressedAnimation`, the animation format used by every FromSoftware game since
+/// Dark Souls.
+///
+/// The animation is split into blocks of up to `max_frames_per_block` frames, each of which stores
+/// a B-spline or constant value for every component of every transform track. Consecutive blocks
+/// share their boundary frame.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkaSplineAnimation {
+    pub duration: f32,
+    pub frame_duration: f32,
+    pub transform_track_count: usize,
+    pub frame_count: usize,
+    pub block_count: usize,
+    pub max_frames_per_block: usize,
+
+    /// Size of the per-track masks at the start of each block, including float track masks.
+    pub mask_and_quantization_size: usize,
+    pub block_offsets: Vec<u32>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub data: Vec<u8>,
+    pub little_endian: bool,
+}
+
+/// A decompressed animation, bound to the bones of a skeleton.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkaAnimation {
+    /// Name of the skeleton the animation was authored against.
+    pub skeleton_name: Option<String>,
+    pub duration: f32,
+    pub frame_duration: f32,
+
+    /// The bone animated by each transform track, if any.
+    pub track_to_bone: Vec<Option<usize>>,
+
+    /// Local bone transforms for each track, indexed by track and then frame.
+    pub tracks: Vec<Vec<HkQsTransform>>,
+}
+
+/// Field offsets of `hkaSplineCompressedAnimation` and `hkaAnimationBinding`, which depend on the
+/// pointer size.
+struct AnimationLayout {
+    duration: usize,
+    transform_track_count: usize,
+    frame_count: usize,
+    block_count: usize,
+    max_frames_per_block: usize,
+    mask_and_quantization_size: usize,
+    frame_duration: usize,
+    block_offsets: usize,
+    data: usize,
+    binding_skeleton_name: usize,
+    binding_animation: usize,
+    binding_track_to_bone: usize,
+}
+
+impl AnimationLayout {
+    fn new(pointer_size: usize) -> Self {
+        // hkReferencedObject: vtable, memSizeAndFlags and referenceCount, padded to pointer size.
+        let base = (pointer_size + 4).next_multiple_of(pointer_size);
+        let array_size = pointer_size + 8;
+
+        // hkaAnimation: type, duration, track counts, extractedMotion and annotationTracks.
+        let spline = base + 16 + pointer_size + array_size;
+        let block_offsets = (spline + 28).next_multiple_of(pointer_size);
+
+        Self {
+            duration: base + 4,
+            transform_track_count: base + 8,
+            frame_count: spline,
+            block_count: spline + 4,
+            max_frames_per_block: spline + 8,
+            mask_and_quantization_size: spline + 12,
+            frame_duration: spline + 24,
+            block_offsets,
+            data: block_offsets + array_size * 4,
+            binding_skeleton_name: base,
+            binding_animation: base + pointer_size,
+            binding_track_to_bone: base + pointer_size * 2,
+        }
+    }
+}
+
+impl HkaAnimation {
+    /// Read and decompress the animation of every `hkaAnimationBinding` in a packfile.
+    pub fn from_packfile(packfile: &HkxPackfile) -> Result<Vec<Self>, HkxError> {
+        let layout = AnimationLayout::new(packfile.pointer_size as usize);
+
+        packfile
+            .objects_of_class("hkaAnimationBinding")
+            .filter_map(|binding| {
+                let field = |offset: usize| HkxPointer {
+                    section: binding.section,
+                    offset: binding.offset + offset as u32,
+                };
+
+                if packfile.bytes(binding).len() < layout.binding_track_to_bone + 12 {
+                    return Some(Err(HkxError::Truncated {
+                        class: "hkaAnimationBinding",
+                        offset: binding.offset,
+                    }));
+                }
+
+                let animation = packfile.resolve(field(layout.binding_animation))?;
+                let is_spline = packfile.sections[animation.section]
+                    .objects
+                    .iter()
+                    .any(|(offset, class)| *offset == animation.offset && class == SPLINE_CLASS);
+                if !is_spline {
+                    return None;
+                }
+
+                Some(Self::read(packfile, binding, animation, &layout))
+            })
+            .collect()
+    }
+
+    fn read(
+        packfile: &HkxPackfile,
+        binding: HkxPointer,
+        animation: HkxPointer,
+        layout: &AnimationLayout,
+    ) -> Result<Self, HkxError> {
+        let field = |offset: usize| HkxPointer {
+            section: binding.section,
+            offset: binding.offset + offset as u32,
+        };
+
+        let track_to_bone = packfile
+            .array(field(layout.binding_track_to_bone), 2)?
+            .map(|bytes| usize::try_from(packfile.read_u16(bytes) as i16).ok())
+            .collect();
+
+        let spline = HkaSplineAnimation::from_packfile(packfile, animation, layout)?;
+
+        Ok(Self {
+            skeleton_name: packfile.read_string_ptr(field(layout.binding_skeleton_name)),
+            duration: spline.duration,
+            frame_duration: spline.frame_duration,
+            track_to_bone,
+            tracks: spline.decompress()?,
+        })
+    }
+
+    /// Read and decompress the animation of every `hkaAnimationBinding` in a tagfile.
+    pub fn from_tagfile(tagfile: &HkxTagfile) -> Result<Vec<Self>, HkxError> {
+        tagfile
+            .items_of_type("hkaAnimationBinding")
+            .filter_map(|item| {
+                let binding = tagfile.object(item)?;
+                let binding = binding.as_record()?;
+                let animation = tagfile.deref(binding.get("animation")?)?;
+                let animation = animation.as_record()?;
+                if animation.type_name != SPLINE_CLASS {
+                    return None;
+                }
+
+                let track_to_bone = binding
+                    .get("transformTrackToBoneIndices")
+                    .and_then(TagValue::as_array)
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|bone| bone.as_int().and_then(|bone| usize::try_from(bone).ok()))
+                    .collect();
+
+                let spline = HkaSplineAnimation::from_record(animation);
+
+                Some(spline.decompress().map(|tracks| {
+                    Self {
+                        skeleton_name: binding
+                            .get("originalSkeletonName")
+                            .and_then(TagValue::as_str)
+                            .map(str::to_string),
+                        duration: spline.duration,
+                        frame_duration: spline.frame_duration,
+                        track_to_bone,
+                        tracks,
+                    }
+                }))
+            })
+            .collect()
+    }
+
+    pub fn frame_count(&self) -> usize {
+        self.tracks.first().map_or(0, Vec::len)
+    }
+
+    /// The track animating `bone`, if any.
+    pub fn bone_track(&self, bone: usize) -> Option<&[HkQsTransform]> {
+        let track = self.track_to_bone.iter().position(|b| *b == Some(bone))?;
+
+        self.tracks.get(track).map(Vec::as_slice)
+    }
+
+    /// Sample every track at `time` seconds, interpolating between the surrounding frames.
+    pub fn sample(&self, time: f32) -> Vec<HkQsTransform> {
+        let last = self.frame_count().saturating_sub(1);
+        let frame = if self.frame_duration > 0.0 {
+            (time / self.frame_duration).clamp(0.0, last as f32)
+        } else {
+            0.0
+        };
+
+        let index = (frame.floor() as usize).min(last);
+        let next = (index + 1).min(last);
+        let t = frame - index as f32;
+
+        self.tracks
+            .iter()
+            .filter_map(|track| Some(interpolate(track.get(index)?, track.get(next)?, t)))
+            .collect()
+    }
+}
+
+impl HkaSplineAnimation {
+    fn from_packfile(
+        packfile: &HkxPackfile,
+        object: HkxPointer,
+        layout: &AnimationLayout,
+    ) -> Result<Self, HkxError> {
+        let bytes = packfile.bytes(object);
+        if bytes.len() < layout.data + 12 {
+            return Err(HkxError::Truncated {
+                class: SPLINE_CLASS,
+                offset: object.offset,
+            });
+        }
+
+        let field = |offset: usize| HkxPointer {
+            section: object.section,
+            offset: object.offset + offset as u32,
+        };
+        let u32_at = |offset: usize| packfile.read_u32(&bytes[offset..]) as usize;
+
+        Ok(Self {
+            duration: packfile.read_f32(&bytes[layout.duration..]),
+            frame_duration: packfile.read_f32(&bytes[layout.frame_duration..]),
+            transform_track_count: u32_at(layout.transform_track_count),
+            frame_count: u32_at(layout.frame_count),
+            block_count: u32_at(layout.block_count),
+            max_frames_per_block: u32_at(layout.max_frames_per_block),
+            mask_and_quantization_size: u32_at(layout.mask_and_quantization_size),
+            block_offsets: packfile
+                .array(field(layout.block_offsets), 4)?
+                .map(|bytes| packfile.read_u32(bytes))
+                .collect(),
+            data: packfile
+                .array(field(layout.data), 1)?
+                .map(|bytes| bytes[0])
+                .collect(),
+            little_endian: packfile.little_endian,
+        })
+    }
+
+    fn from_record(record: &TagRecord) -> Self {
+        let int = |name: &str| {
+            record
+                .get(name)
+                .and_then(TagValue::as_int)
+                .and_then(|value| usize::try_from(value).ok())
+                .unwrap_or_default()
+        };
+        let float = |name: &str| {
+            record
+                .get(name)
+                .and_then(TagValue::as_float)
+                .unwrap_or_default() as f32
+        };
+        let ints = |name: &str| {
+            record
+                .get(name)
+                .and_then(TagValue::as_array)
+                .unwrap_or_default()
+                .iter()
+                .map(|value| value.as_int().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+
+        Self {
+            duration: float("duration"),
+            frame_duration: float("frameDuration"),
+            transform_track_count: int("numberOfTransformTracks"),
+            frame_count: int("numFrames"),
+            block_count: int("numBlocks"),
+            max_frames_per_block: int("maxFramesPerBlock"),
+            mask_and_quantization_size: int("maskAndQuantizationSize"),
+            block_offsets: ints("blockOffsets").into_iter().map(|v| v as u32).collect(),
+            data: ints("data").into_iter().map(|v| v as u8).collect(),
+            little_endian: true,
+        }
+    }
+
+    /// Evaluate every transform track at every frame, returning keyframes indexed by track and then
+    /// frame.
+    pub fn decompress(&self) -> Result<Vec<Vec<HkQsTransform>>, HkxError> {
+        let mut tracks = vec![Vec::with_capacity(self.frame_count); self.transform_track_count];
+        if self.block_count == 0 {
+            return Ok(tracks);
+        }
+
+        let frames_per_block = self.max_frames_per_block.saturating_sub(1).max(1);
+        let mut current_block = None;
+        let mut curves = Vec::new();
+
+        for frame in 0..self.frame_count {
+            let block = (frame / frames_per_block).min(self.block_count - 1);
+            if current_block != Some(block) {
+                curves = self.read_block(block)?;
+                current_block = Some(block);
+            }
+
+            let local_frame = (frame - block * frames_per_block) as f32;
+            for (track, curves) in tracks.iter_mut().zip(&curves) {
+                track.push(curves.evaluate(local_frame));
+            }
+        }
+
+        Ok(tracks)
+    }
+
+    fn read_block(&self, block: usize) -> Result<Vec<TrackCurves>, HkxError> {
+        let start = *self.block_offsets.get(block).ok_or(HkxError::Truncated {
+            class: SPLINE_CLASS,
+            offset: block as u32,
+        })? as usize;
+
+        let mut reader = BlockReader {
+            data: &self.data,
+            position: start,
+            little_endian: self.little_endian,
+        };
+
+        let masks = (0..self.transform_track_count)
+            .map(|_| Ok([reader.u8()?, reader.u8()?, reader.u8()?, reader.u8()?]))
+            .collect::<Result<Vec<_>, HkxError>>()?;
+
+        reader.position = start + self.mask_and_quantization_size;
+        reader.align(4);
+
+        masks
+            .into_iter()
+            .map(|[quantization, translation, rotation, scale]| {
+                Ok(TrackCurves {
+                    translation: reader.vector(translation, quantization & 0x3, 0.0)?,
+                    rotation: reader.rotation(rotation, (quantization >> 2) & 0xF)?,
+                    scale: reader.vector(scale, (quantization >> 6) & 0x3, 1.0)?,
+                })
+            })
+            .collect()
+    }
+}
+
+struct TrackCurves {
+    translation: Curve<3>,
+    rotation: Curve<4>,
+    scale: Curve<3>,
+}
+
+impl TrackCurves {
+    fn evaluate(&self, frame: f32) -> HkQsTransform {
+        HkQsTransform {
+            translation: self.translation.evaluate(frame),
+            rotation: normalize(self.rotation.evaluate(frame)),
+            scale: self.scale.evaluate(frame),
+        }
+    }
+}
+
+/// The value of an animated component over the frames of a block.
+enum Curve<const N: usize> {
+    Static([f32; N]),
+    Spline {
+        degree: usize,
+        knots: Vec<u8>,
+        points: Vec<[f32; N]>,
+    },
+}
+
+impl<const N: usize> Curve<N> {
+    fn evaluate(&self, frame: f32) -> [f32; N] {
+        let (degree, knots, points) = match self {
+            Self::Static(value) => return *value,
+            Self::Spline {
+                degree,
+                knots,
+                points,
+            } => (*degree, knots, points),
+        };
+
+        // Find the knot span containing the frame, then evaluate the non-zero B-spline basis
+        // functions over it with the Cox-de Boor recurrence.
+        let knot = |index: usize| knots[index] as f32;
+        let span = (degree..points.len())
+            .rev()
+            .find(|span| knot(*span) <= frame)
+            .unwrap_or(degree);
+
+        let mut basis = vec![0.0f32; degree + 1];
+        let mut left = vec![0.0f32; degree + 1];
+        let mut right = vec![0.0f32; degree + 1];
+        basis[0] = 1.0;
+
+        for j in 1..=degree {
+            left[j] = frame - knot(span + 1 - j);
+            right[j] = knot(span + j) - frame;
+
+            let mut saved = 0.0;
+            for r in 0..j {
+                let denominator = right[r + 1] + left[j - r];
+                let temp = if denominator != 0.0 {
+                    basis[r] / denominator
+                } else {
+                    0.0
+                };
+
+                basis[r] = saved + right[r + 1] * temp;
+                saved = left[j - r] * temp;
+            }
+            basis[j] = saved;
+        }
+
+        let mut result = [0.0; N];
+        for (i, weight) in basis.iter().enumerate() {
+            for (component, value) in result.iter_mut().zip(points[span - degree + i]) {
+                *component += value * weight;
+            }
+        }
+
+        result
+    }
+}
+
+/// Cursor over the data of a block, alignment is relative to the start of the animation data.
+struct BlockReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    little_endian: bool,
+}
+
+impl<'a> BlockReader<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], HkxError> {
+        let bytes =
+            self.data
+                .get(self.position..self.position + count)
+                .ok_or(HkxError::Truncated {
+                    class: SPLINE_CLASS,
+                    offset: self.position as u32,
+                })?;
+        self.position += count;
+
+        Ok(bytes)
+    }
+
+    fn align(&mut self, alignment: usize) {
+        self.position = self.position.next_multiple_of(alignment);
+    }
+
+    fn u8(&mut self) -> Result<u8, HkxError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    /// Read an unsigned little or big endian integer of up to 8 bytes.
+    fn uint(&mut self, size: usize) -> Result<u64, HkxError> {
+        let bytes = self.bytes(size)?;
+        let fold = |value: u64, byte: &u8| (value << 8) | *byte as u64;
+
+        Ok(if self.little_endian {
+            bytes.iter().rev().fold(0, fold)
+        } else {
+            bytes.iter().fold(0, fold)
+        })
+    }
+
+    fn f32(&mut self) -> Result<f32, HkxError> {
+        Ok(f32::from_bits(self.uint(4)? as u32))
+    }
+
+    /// Read the control point count, degree and knots that begin every spline.
+    fn spline_header(&mut self) -> Result<(usize, usize, Vec<u8>), HkxError> {
+        let count = self.uint(2)? as usize + 1;
+        let degree = self.u8()? as usize;
+        let knots = self.bytes(count + degree + 1)?.to_vec();
+
+        if count <= degree {
+            return Err(HkxError::Truncated {
+                class: SPLINE_CLASS,
+                offset: self.position as u32,
+            });
+        }
+
+        Ok((count, degree, knots))
+    }
+
+    /// Read a translation or scale, where bits 0-2 of `types` mark static components and bits 4-6
+    /// mark spline components.
+    fn vector(&mut self, types: u8, quantization: u8, default: f32) -> Result<Curve<3>, HkxError> {
+        let is_static = |axis: usize| types & (0x1 << axis) != 0;
+        let is_spline = |axis: usize| types & (0x10 << axis) != 0;
+
+        if types & 0x70 == 0 {
+            let mut value = [default; 3];
+            for (axis, component) in value.iter_mut().enumerate() {
+                if is_static(axis) {
+                    *component = self.f32()?;
+                }
+            }
+
+            return Ok(Curve::Static(value));
+        }
+
+        let (count, degree, knots) = self.spline_header()?;
+        self.align(4);
+
+        let mut ranges = [(default, default); 3];
+        for (axis, range) in ranges.iter_mut().enumerate() {
+            if is_spline(axis) {
+                *range = (self.f32()?, self.f32()?);
+            } else if is_static(axis) {
+                let value = self.f32()?;
+                *range = (value, value);
+            }
+        }
+
+        let (size, max) = match quantization {
+            0 => (1, u8::MAX as f32),
+            1 => (2, u16::MAX as f32),
+            _ => return Err(HkxError::UnsupportedQuantization(quantization)),
+        };
+
+        let mut points = Vec::with_capacity(count);
+        for _ in 0..count {
+            let mut point = ranges.map(|(min, _)| min);
+            for (axis, component) in point.iter_mut().enumerate() {
+                if is_spline(axis) {
+                    let (min, max_value) = ranges[axis];
+                    *component = min + (max_value - min) * (self.uint(size)? as f32 / max);
+                }
+            }
+
+            points.push(point);
+        }
+
+        self.align(4);
+
+        Ok(Curve::Spline {
+            degree,
+            knots,
+            points,
+        })
+    }
+
+    /// Read a rotation, where the low nibble of `types` marks a static rotation and the high nibble
+    /// a spline.
+    fn rotation(&mut self, types: u8, quantization: u8) -> Result<Curve<4>, HkxError> {
+        let alignment = match quantization {
+            ROTATION_POLAR32 | ROTATION_UNCOMPRESSED => 4,
+            ROTATION_THREECOMP48 => 2,
+            ROTATION_THREECOMP40 => 1,
+            _ => return Err(HkxError::UnsupportedQuantization(quantization)),
+        };
+
+        let curve = if types & 0xF0 != 0 {
+            let (count, degree, knots) = self.spline_header()?;
+            self.align(alignment);
+
+            let points = (0..count)
+                .map(|_| self.quaternion(quantization))
+                .collect::<Result<_, _>>()?;
+
+            Curve::Spline {
+                degree,
+                knots,
+                points,
+            }
+        } else if types & 0x0F != 0 {
+            self.align(alignment);
+            Curve::Static(self.quaternion(quantization)?)
+        } else {
+            return Ok(Curve::Static(IDENTITY_ROTATION));
+        };
+
+        self.align(4);
+
+        Ok(curve)
+    }
+
+    fn quaternion(&mut self, quantization: u8) -> Result<[f32; 4], HkxError> {
+        Ok(match quantization {
+            ROTATION_POLAR32 => polar32(self.uint(4)? as u32),
+            ROTATION_THREECOMP40 => {
+                const MASK: u64 = (1 << 12) - 1;
+                const SCALE: f32 = 0.000345436;
+
+                let value = self.uint(5)?;
+                let component =
+                    |shift: u32| (((value >> shift) & MASK) as f32 - (MASK >> 1) as f32) * SCALE;
+
+                from_three_components(
+                    [component(0), component(12), component(24)],
+                    ((value >> 36) & 0x3) as usize,
+                    (value >> 38) & 0x1 != 0,
+                )
+            }
+            ROTATION_THREECOMP48 => {
+                const MASK: u16 = (1 << 15) - 1;
+                const SCALE: f32 = 0.000043161;
+
+                let [x, y, z] = [self.uint(2)?, self.uint(2)?, self.uint(2)?].map(|v| v as u16);
+                let component = |value: u16| ((value & MASK) as f32 - (MASK >> 1) as f32) * SCALE;
+
+                from_three_components(
+                    [component(x), component(y), component(z)],
+                    (((y >> 14) & 0x2) | ((x >> 15) & 0x1)) as usize,
+                    z >> 15 != 0,
+                )
+            }
+            ROTATION_UNCOMPRESSED => [self.f32()?, self.f32()?, self.f32()?, self.f32()?],
+            _ => return Err(HkxError::UnsupportedQuantization(quantization)),
+        })
+    }
+}
+
+/// Decode a quaternion stored as a 10-bit `w` and polar coordinates for the direction of its
+/// vector part, with the sign of each component in the top 4 bits.
+fn polar32(value: u32) -> [f32; 4] {
+    const R_MASK: u32 = (1 << 10) - 1;
+    const PHI_SCALE: f32 = std::f32::consts::FRAC_PI_2 / 511.0;
+
+    let r = ((value >> 18) & R_MASK) as f32 / R_MASK as f32;
+    let r = 1.0 - r * r;
+
+    let phi_theta = (value & 0x3FFFF) as f32;
+    let mut phi = phi_theta.sqrt().floor();
+    let mut theta = 0.0;
+    if phi > 0.0 {
+        theta = std::f32::consts::FRAC_PI_4 * (phi_theta - phi * phi) / phi;
+        phi *= PHI_SCALE;
+    }
+
+    let magnitude = (1.0 - r * r).max(0.0).sqrt();
+    let mut quaternion = [
+        phi.sin() * theta.cos() * magnitude,
+        phi.sin() * theta.sin() * magnitude,
+        phi.cos() * magnitude,
+        r,
+    ];
+
+    for (bit, component) in quaternion.iter_mut().enumerate() {
+        if value & (0x10000000 << bit) != 0 {
+            *component = -*component;
+        }
+    }
+
+    quaternion
+}
+
+/// Rebuild a unit quaternion from three of its components, given the index of the dropped
+/// (largest) component and its sign.
+fn from_three_components(values: [f32; 3], missing: usize, negative: bool) -> [f32; 4] {
+    let dot = values.iter().map(|v| v * v).sum::<f32>();
+    let largest = (1.0 - dot).max(0.0).sqrt();
+
+    let mut values = values.into_iter();
+    let mut quaternion = [0.0; 4];
+    for (index, component) in quaternion.iter_mut().enumerate() {
+        *component = match index == missing {
+            true if negative => -largest,
+            true => largest,
+            false => values.next().unwrap_or_default(),
+        };
+    }
+
+    quaternion
+}
+
+fn normalize(quaternion: [f32; 4]) -> [f32; 4] {
+    let length = quaternion.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if length > 0.0 {
+        quaternion.map(|v| v / length)
+    } else {
+        IDENTITY_ROTATION
+    }
+}
+
+fn interpolate(a: &HkQsTransform, b: &HkQsTransform, t: f32) -> HkQsTransform {
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+
+    // Interpolate along the shortest arc between the two rotations.
+    let dot = a
+        .rotation
+        .iter()
+        .zip(b.rotation)
+        .map(|(a, b)| a * b)
+        .sum::<f32>();
+    let sign = if dot < 0.0 { -1.0 } else { 1.0 };
+
+    HkQsTransform {
+        translation: std::array::from_fn(|i| lerp(a.translation[i], b.translation[i])),
+        rotation: normalize(std::array::from_fn(|i| {
+            lerp(a.rotation[i], b.rotation[i] * sign)
+        })),
+        scale: std::array::from_fn(|i| lerp(a.scale[i], b.scale[i])),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hkx::animation::HkaSplineAnimation;
+
+    #[test]
+    pub fn decompresses_static_and_spline_tracks() {
+        let mut data = vec![
+            // Track 0: 8-bit positions, uncompressed rotation, 8-bit scale. X is a spline, Y is
+            // static and the rotation is static.
+            0x14, 0x12, 0x01, 0x00, //
+            // Track 1: everything at its default.
+            0x00, 0x00, 0x00, 0x00,
+        ];
+
+        // Position spline: two control points of degree 1, knots [0, 0, 2, 2].
+        data.extend([1, 0, 1, 0, 0, 2, 2]);
+        data.resize(data.len().next_multiple_of(4), 0);
+        data.extend(0.0f32.to_le_bytes()); // X min
+        data.extend(10.0f32.to_le_bytes()); // X max
+        data.extend(5.0f32.to_le_bytes()); // Y static
+        data.extend([0, 255]);
+        data.resize(data.len().next_multiple_of(4), 0);
+
+        // Static rotation.
+        for value in [0.0f32, 0.0, 1.0, 0.0] {
+            data.extend(value.to_le_bytes());
+        }
+
+        let animation = HkaSplineAnimation {
+            duration: 2.0 / 30.0,
+            frame_duration: 1.0 / 30.0,
+            transform_track_count: 2,
+            frame_count: 3,
+            block_count: 1,
+            max_frames_per_block: 256,
+            mask_and_quantization_size: 8,
+            block_offsets: vec![0],
+            data,
+            little_endian: true,
+        };
+
+        let tracks = animation.decompress().unwrap();
+        let translations = tracks[0]
+            .iter()
+            .map(|frame| frame.translation)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            translations,
+            vec![[0.0, 5.0, 0.0], [5.0, 5.0, 0.0], [10.0, 5.0, 0.0]]
+        );
+        assert_eq!(tracks[0][1].rotation, [0.0, 0.0, 1.0, 0.0]);
+        assert_eq!(tracks[0][1].scale, [1.0, 1.0, 1.0]);
+        assert_eq!(tracks[1][2].rotation, [0.0, 0.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,211 @@
+use crate::hkx::{HkxError, HkxTagfile, TagRecord, TagValue};
+
+/// The simulated part of an `hclClothData`: the particles a cloth is made of and the constraints
+/// between them.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SimCloth {
+    pub name: String,
+    pub particles: Vec<ClothParticle>,
+
+    /// Particles pinned to the skinned mesh rather than simulated.
+    pub fixed_particles: Vec<usize>,
+    pub constraint_sets: Vec<ClothConstraintSet>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ClothParticle {
+    pub mass: f32,
+    pub inv_mass: f32,
+    pub radius: f32,
+    pub friction: f32,
+}
+
+/// One of the constraint sets of a cloth, e.g. an `hclStandardLinkConstraintSet`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ClothConstraintSet {
+    /// The Havok class of the set, which determines how its constraints behave.
+    pub type_name: String,
+    pub name: String,
+
+    /// Number of constraints in the set, whatever their type.
+    pub constraint_count: usize,
+
+    /// The particle pairs of link constraints, empty for other kinds of constraint.
+    pub links: Vec<ClothLink>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ClothLink {
+    pub particle_a: usize,
+    pub particle_b: usize,
+    pub rest_length: f32,
+    pub stiffness: f32,
+}
+
+impl SimCloth {
+    /// Read every `hclSimClothData` in a tagfile.
+    pub fn from_tagfile(tagfile: &HkxTagfile) -> Result<Vec<Self>, HkxError> {
+        tagfile
+            .items_of_type("hclSimClothData")
+            .map(|item| {
+                let object = tagfile.object(item);
+                let record =
+                    object
+                        .as_ref()
+                        .and_then(TagValue::as_record)
+                        .ok_or(HkxError::Truncated {
+                            class: "hclSimClothData",
+                            offset: tagfile.items[item].offset,
+                        })?;
+
+                Ok(Self::from_record(tagfile, record))
+            })
+            .collect()
+    }
+
+    fn from_record(tagfile: &HkxTagfile, record: &TagRecord) -> Self {
+        let particles = array(record, "particleDatas")
+            .iter()
+            .filter_map(TagValue::as_record)
+            .map(|particle| ClothParticle {
+                mass: float(particle, "mass"),
+                inv_mass: float(particle, "invMass"),
+                radius: float(particle, "radius"),
+                friction: float(particle, "friction"),
+            })
+            .collect();
+
+        let fixed_particles = array(record, "fixedParticles")
+            .iter()
+            .filter_map(|particle| usize::try_from(particle.as_int()?).ok())
+            .collect();
+
+        let constraint_sets = array(record, "staticConstraintSets")
+            .iter()
+            .filter_map(|set| tagfile.deref(set))
+            .filter_map(|set| match set {
+                TagValue::Record(set) => Some(ClothConstraintSet::from_record(&set)),
+                _ => None,
+            })
+            .collect();
+
+        Self {
+            name: string(record, "name"),
+            particles,
+            fixed_particles,
+            constraint_sets,
+        }
+    }
+
+    /// Total number of constraints across every set.
+    pub fn constraint_count(&self) -> usize {
+        self.constraint_sets
+            .iter()
+            .map(|set| set.constraint_count)
+            .sum()
+    }
+}
+
+impl ClothConstraintSet {
+    fn from_record(record: &TagRecord) -> Self {
+        // Each kind of set stores its constraints in a differently named array, so count whichever
+        // array of records the set has.
+        let constraints = record
+            .fields
+            .iter()
+            .filter_map(|(_, value)| value.as_array())
+            .find(|values| {
+                values
+                    .first()
+                    .is_some_and(|value| value.as_record().is_some())
+            })
+            .unwrap_or_default();
+
+        let links = constraints
+            .iter()
+            .filter_map(TagValue::as_record)
+            .filter_map(|link| {
+                let particle = |name: &str| usize::try_from(link.get(name)?.as_int()?).ok();
+
+                Some(ClothLink {
+                    particle_a: particle("particleA")?,
+                    particle_b: particle("particleB")?,
+                    rest_length: float(link, "restLength"),
+                    stiffness: float(link, "stiffness"),
+                })
+            })
+            .collect();
+
+        Self {
+            type_name: record.type_name.clone(),
+            name: string(record, "name"),
+            constraint_count: constraints.len(),
+            links,
+        }
+    }
+}
+
+fn array<'a>(record: &'a TagRecord, name: &str) -> &'a [TagValue] {
+    record
+        .get(name)
+        .and_then(TagValue::as_array)
+        .unwrap_or_default()
+}
+
+fn float(record: &TagRecord, name: &str) -> f32 {
+    record
+        .get(name)
+        .and_then(TagValue::as_float)
+        .unwrap_or_default() as f32
+}
+
+fn string(record: &TagRecord, name: &str) -> String {
+    record
+        .get(name)
+        .and_then(TagValue::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hkx::{cloth::ClothConstraintSet, TagRecord, TagValue};
+
+    #[test]
+    pub fn reads_link_constraints() {
+        let link = |a: i64, b: i64| {
+            TagValue::Record(TagRecord {
+                type_name: "hclStandardLinkConstraintSet::Link".to_string(),
+                fields: vec![
+                    ("particleA".to_string(), TagValue::Int(a)),
+                    ("particleB".to_string(), TagValue::Int(b)),
+                    ("restLength".to_string(), TagValue::Float(0.5)),
+                    ("stiffness".to_string(), TagValue::Float(1.0)),
+                ],
+            })
+        };
+
+        let set = ClothConstraintSet::from_record(&TagRecord {
+            type_name: "hclStandardLinkConstraintSet".to_string(),
+            fields: vec![
+                (
+                    "name".to_string(),
+                    TagValue::String(Some("Cape".to_string())),
+                ),
+                (
+                    "links".to_string(),
+                    TagValue::Array(vec![link(0, 1), link(1, 2)]),
+                ),
+            ],
+        });
+
+        assert_eq!(set.name, "Cape");
+        assert_eq!(set.constraint_count, 2);
+        assert_eq!(set.links[1].particle_a, 1);
+        assert_eq!(set.links[1].rest_length, 0.5);
+    }
+}
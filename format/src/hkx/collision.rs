@@ -0,0 +1,386 @@
+use crate::hkx::{HkxError, HkxPackfile, HkxPointer, HkxTagfile, TagRecord, TagValue};
+
+/// Size of `hkcdStaticMeshTreeBaseSection` and of the `hkcdStaticMeshTreeBasePrimitive` indices.
+const SECTION_SIZE: usize = 0x60;
+const PRIMITIVE_SIZE: usize = 4;
+
+/// Triangle geometry extracted from a compressed collision mesh shape, in the space of the shape.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CollisionMesh {
+    pub vertices: Vec<[f32; 3]>,
+
+    /// Vertex indices, three per triangle.
+    pub indices: Vec<u32>,
+}
+
+/// The quantized geometry of an `hkcdStaticMeshTree`, shared by the Havok 2014
+/// `hkpBvCompressedMeshShape` and the Havok 2018 `hknpCompressedMeshShape`.
+///
+/// Geometry is split into sections, each with a small set of vertices packed into 32 bits relative
+/// to the section's bounds, plus indices into a pool of vertices packed into 64 bits relative to
+/// the bounds of the whole tree.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CompressedMeshTree {
+    /// Minimum and maximum corner of the tree's bounds.
+    pub domain: [[f32; 3]; 2],
+    pub sections: Vec<CompressedMeshSection>,
+
+    /// Vertex indices of each primitive, a quad unless the last two indices are equal.
+    pub primitives: Vec<[u8; 4]>,
+    pub shared_vertices_index: Vec<u16>,
+    pub packed_vertices: Vec<u32>,
+    pub shared_vertices: Vec<u64>,
+}
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CompressedMeshSection {
+    /// Offset and scale applied to the section's packed vertices.
+    pub codec_params: [f32; 6],
+    pub first_packed_vertex: u32,
+    pub packed_vertex_count: u8,
+
+    /// First shared vertex index in the upper 24 bits, and their count in the lower 8.
+    pub shared_vertices: u32,
+
+    /// First primitive in the upper 24 bits, and their count in the lower 8.
+    pub primitives: u32,
+}
+
+/// Field offsets of `hkpBvCompressedMeshShape` and its tree, which depend on the pointer size.
+struct MeshShapeLayout {
+    domain: usize,
+    sections: usize,
+    primitives: usize,
+    shared_vertices_index: usize,
+    packed_vertices: usize,
+    shared_vertices: usize,
+    pointer_size: usize,
+}
+
+impl MeshShapeLayout {
+    fn new(pointer_size: usize) -> Self {
+        // hkReferencedObject: vtable, memSizeAndFlags and referenceCount, padded to pointer size.
+        let base = (pointer_size + 4).next_multiple_of(pointer_size);
+        let array_size = pointer_size + 8;
+
+        // hkpShape: type, dispatch type, bits per key, codec type and user data, then the
+        // hkpBvTreeShape type, convex radius, welding type and two flags, and finally the
+        // collision filter, user data and user string palettes.
+        let user_data = (base + 4).next_multiple_of(pointer_size);
+        let palettes = (user_data + pointer_size + 11).next_multiple_of(pointer_size);
+        let tree = (palettes + array_size * 3).next_multiple_of(16);
+
+        // hkcdStaticTree: the nodes and the domain, then the primitive key fields.
+        let domain = tree + array_size.next_multiple_of(16);
+        let sections = (domain + 0x20 + 12).next_multiple_of(pointer_size);
+
+        Self {
+            domain,
+            sections,
+            primitives: sections + array_size,
+            shared_vertices_index: sections + array_size * 2,
+            packed_vertices: sections + array_size * 3,
+            shared_vertices: sections + array_size * 4,
+            pointer_size,
+        }
+    }
+}
+
+impl CollisionMesh {
+    /// Extract the geometry of every `hkpBvCompressedMeshShape` in a packfile.
+    pub fn from_packfile(packfile: &HkxPackfile) -> Result<Vec<Self>, HkxError> {
+        packfile
+            .objects_of_class("hkpBvCompressedMeshShape")
+            .map(|object| Ok(CompressedMeshTree::from_packfile(packfile, object)?.triangulate()))
+            .collect()
+    }
+
+    /// Extract the geometry of every `hknpCompressedMeshShape` in a tagfile.
+    pub fn from_tagfile(tagfile: &HkxTagfile) -> Result<Vec<Self>, HkxError> {
+        tagfile
+            .items_of_type("hknpCompressedMeshShapeData")
+            .map(|item| {
+                let object = tagfile.object(item);
+                let tree = object
+                    .as_ref()
+                    .and_then(|object| object.as_record()?.get("meshTree")?.as_record())
+                    .ok_or(HkxError::Truncated {
+                        class: "hknpCompressedMeshShapeData",
+                        offset: tagfile.items[item].offset,
+                    })?;
+
+                Ok(CompressedMeshTree::from_record(tree).triangulate())
+            })
+            .collect()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+}
+
+impl CompressedMeshTree {
+    fn from_packfile(packfile: &HkxPackfile, object: HkxPointer) -> Result<Self, HkxError> {
+        let layout = MeshShapeLayout::new(packfile.pointer_size as usize);
+        let bytes = packfile.bytes(object);
+        if bytes.len() < layout.shared_vertices + layout.pointer_size + 4 {
+            return Err(HkxError::Truncated {
+                class: "hkpBvCompressedMeshShape",
+                offset: object.offset,
+            });
+        }
+
+        let field = |offset: usize| HkxPointer {
+            section: object.section,
+            offset: object.offset + offset as u32,
+        };
+        let float = |offset: usize| packfile.read_f32(&bytes[offset..]);
+        let read_u64 = |bytes: &[u8]| {
+            let (low, high) = (packfile.read_u32(bytes), packfile.read_u32(&bytes[4..]));
+            if packfile.little_endian {
+                (high as u64) << 32 | low as u64
+            } else {
+                (low as u64) << 32 | high as u64
+            }
+        };
+
+        let domain = [
+            [0, 4, 8].map(|offset| float(layout.domain + offset)),
+            [16, 20, 24].map(|offset| float(layout.domain + offset)),
+        ];
+
+        let sections = packfile
+            .array(field(layout.sections), SECTION_SIZE)?
+            .map(|section| {
+                let codec_offset = (layout.pointer_size + 8).next_multiple_of(16) + 0x20;
+
+                CompressedMeshSection {
+                    codec_params: std::array::from_fn(|i| {
+                        packfile.read_f32(&section[codec_offset + i * 4..])
+                    }),
+                    first_packed_vertex: packfile.read_u32(&section[codec_offset + 0x18..]),
+                    shared_vertices: packfile.read_u32(&section[codec_offset + 0x1C..]),
+                    primitives: packfile.read_u32(&section[codec_offset + 0x20..]),
+                    packed_vertex_count: section[codec_offset + 0x28],
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            domain,
+            sections,
+            primitives: packfile
+                .array(field(layout.primitives), PRIMITIVE_SIZE)?
+                .map(|primitive| [primitive[0], primitive[1], primitive[2], primitive[3]])
+                .collect(),
+            shared_vertices_index: packfile
+                .array(field(layout.shared_vertices_index), 2)?
+                .map(|bytes| packfile.read_u16(bytes))
+                .collect(),
+            packed_vertices: packfile
+                .array(field(layout.packed_vertices), 4)?
+                .map(|bytes| packfile.read_u32(bytes))
+                .collect(),
+            shared_vertices: packfile
+                .array(field(layout.shared_vertices), 8)?
+                .map(read_u64)
+                .collect(),
+        })
+    }
+
+    fn from_record(record: &TagRecord) -> Self {
+        let array = |record: &TagRecord, name: &str| {
+            record
+                .get(name)
+                .and_then(TagValue::as_array)
+                .unwrap_or_default()
+                .iter()
+                .map(|value| value.as_int().unwrap_or_default())
+                .collect::<Vec<_>>()
+        };
+        let int = |record: &TagRecord, name: &str| {
+            record
+                .get(name)
+                .and_then(TagValue::as_int)
+                .unwrap_or_default()
+        };
+        // Section shared vertex and primitive ranges are records wrapping a single `data` field.
+        let packed_range = |record: &TagRecord, name: &str| {
+            record
+                .get(name)
+                .and_then(TagValue::as_record)
+                .map_or(0, |range| int(range, "data"))
+        };
+
+        let domain = record
+            .get("domain")
+            .map(TagValue::floats)
+            .unwrap_or_default();
+        let corner = |start: usize| {
+            std::array::from_fn(|i| domain.get(start + i).copied().unwrap_or_default())
+        };
+
+        let sections = record
+            .get("sections")
+            .and_then(TagValue::as_array)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(TagValue::as_record)
+            .map(|section| {
+                let codec_params = section
+                    .get("codecParms")
+                    .map(TagValue::floats)
+                    .unwrap_or_default();
+
+                CompressedMeshSection {
+                    codec_params: std::array::from_fn(|i| {
+                        codec_params.get(i).copied().unwrap_or_default()
+                    }),
+                    first_packed_vertex: int(section, "firstPackedVertex") as u32,
+                    packed_vertex_count: int(section, "numPackedVertices") as u8,
+                    shared_vertices: packed_range(section, "sharedVertices") as u32,
+                    primitives: packed_range(section, "primitives") as u32,
+                }
+            })
+            .collect();
+
+        let primitives = record
+            .get("primitives")
+            .and_then(TagValue::as_array)
+            .unwrap_or_default()
+            .iter()
+            .map(|primitive| {
+                let indices = primitive
+                    .as_record()
+                    .map(|primitive| array(primitive, "indices"))
+                    .unwrap_or_default();
+
+                std::array::from_fn(|i| indices.get(i).copied().unwrap_or_default() as u8)
+            })
+            .collect();
+
+        Self {
+            domain: [corner(0), corner(4)],
+            sections,
+            primitives,
+            shared_vertices_index: array(record, "sharedVerticesIndex")
+                .into_iter()
+                .map(|index| index as u16)
+                .collect(),
+            packed_vertices: array(record, "packedVertices")
+                .into_iter()
+                .map(|vertex| vertex as u32)
+                .collect(),
+            shared_vertices: array(record, "sharedVertices")
+                .into_iter()
+                .map(|vertex| vertex as u64)
+                .collect(),
+        }
+    }
+
+    /// Decode the vertices of every section and split its primitives into triangles.
+    ///
+    /// Vertices are not shared between sections, and primitives referring to vertices that don't
+    /// exist are skipped.
+    pub fn triangulate(&self) -> CollisionMesh {
+        let mut mesh = CollisionMesh::default();
+        let [min, max] = self.domain;
+
+        for section in &self.sections {
+            let base = mesh.vertices.len() as u32;
+            let [offset @ .., _, _, _] = section.codec_params;
+            let [_, _, _, scale @ ..] = section.codec_params;
+
+            let first_packed = section.first_packed_vertex as usize;
+            let packed = self
+                .packed_vertices
+                .get(first_packed..first_packed + section.packed_vertex_count as usize)
+                .unwrap_or_default();
+
+            mesh.vertices.extend(packed.iter().map(|vertex| {
+                let quantized = [vertex & 0x7FF, (vertex >> 11) & 0x7FF, vertex >> 22];
+                std::array::from_fn(|i| offset[i] + scale[i] * quantized[i] as f32)
+            }));
+
+            let shared_start = (section.shared_vertices >> 8) as usize;
+            let shared_count = (section.shared_vertices & 0xFF) as usize;
+            let shared = self
+                .shared_vertices_index
+                .get(shared_start..shared_start + shared_count)
+                .unwrap_or_default();
+
+            mesh.vertices.extend(shared.iter().map(|index| {
+                let vertex = self
+                    .shared_vertices
+                    .get(*index as usize)
+                    .copied()
+                    .unwrap_or_default();
+                let quantized = [
+                    (vertex & 0x1FFFFF) as f32 / 0x1FFFFF as f32,
+                    ((vertex >> 21) & 0x1FFFFF) as f32 / 0x1FFFFF as f32,
+                    (vertex >> 42) as f32 / 0x3FFFFF as f32,
+                ];
+
+                std::array::from_fn(|i| min[i] + (max[i] - min[i]) * quantized[i])
+            }));
+
+            let vertex_count = (packed.len() + shared.len()) as u32;
+            let primitive_start = (section.primitives >> 8) as usize;
+            let primitive_count = (section.primitives & 0xFF) as usize;
+            let primitives = self
+                .primitives
+                .get(primitive_start..primitive_start + primitive_count)
+                .unwrap_or_default();
+
+            for [a, b, c, d] in primitives.iter().map(|p| p.map(u32::from)) {
+                if [a, b, c, d].iter().any(|index| *index >= vertex_count) {
+                    continue;
+                }
+
+                mesh.indices.extend([base + a, base + b, base + c]);
+                if c != d {
+                    mesh.indices.extend([base + a, base + c, base + d]);
+                }
+            }
+        }
+
+        mesh
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hkx::collision::{CompressedMeshSection, CompressedMeshTree};
+
+    #[test]
+    pub fn triangulates_packed_and_shared_vertices() {
+        let tree = CompressedMeshTree {
+            domain: [
+                [0.0, 0.0, 0.0],
+                [0x1FFFFF as f32, 0x1FFFFF as f32, 0x3FFFFF as f32],
+            ],
+            sections: vec![CompressedMeshSection {
+                codec_params: [10.0, 20.0, 30.0, 1.0, 2.0, 0.5],
+                first_packed_vertex: 1,
+                packed_vertex_count: 2,
+                shared_vertices: 1,
+                primitives: 2,
+            }],
+            primitives: vec![[0, 1, 2, 2], [0, 1, 2, 0]],
+            shared_vertices_index: vec![0],
+            packed_vertices: vec![u32::MAX, 1 | 2 << 11 | 4 << 22, 0],
+            shared_vertices: vec![5 | 6 << 21 | 7 << 42],
+        };
+
+        let mesh = tree.triangulate();
+
+        assert_eq!(
+            mesh.vertices,
+            vec![[11.0, 24.0, 32.0], [10.0, 20.0, 30.0], [5.0, 6.0, 7.0]]
+        );
+        assert_eq!(mesh.indices, vec![0, 1, 2, 0, 1, 2, 0, 2, 0]);
+    }
+}
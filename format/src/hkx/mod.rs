@@ -0,0 +1,417 @@
+use std::{
+    collections::HashMap,
+    io::{self, Cursor, Read, Seek, SeekFrom},
+};
+
+use byteorder::{ByteOrder, ReadBytesExt, BE, LE};
+use thiserror::Error;
+use tracing::instrument;
+
+use crate::{
+    dcx::{DCXError, DCX},
+    io_ext::ReadFormatsExt,
+};
+
+pub mod animation;
+pub mod cloth;
+pub mod collision;
+pub mod navmesh;
+pub mod skeleton;
+pub mod tagfile;
+
+pub use self::{
+    animation::{HkaAnimation, HkaSplineAnimation},
+    cloth::{ClothConstraintSet, ClothLink, ClothParticle, SimCloth},
+    collision::{CollisionMesh, CompressedMeshSection, CompressedMeshTree},
+    navmesh::{HkaiNavMesh, NavMeshEdge, NavMeshFace},
+    skeleton::{HkQsTransform, HkaBone, HkaSkeleton},
+    tagfile::{HkxCompendium, HkxTagfile, TagRecord, TagValue},
+};
+
+#[derive(Debug, Error)]
+pub enum HkxError {
+    #[error("Could not read HKX: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not a Havok packfile")]
+    NotPackfile,
+
+    #[error("Not a Havok tagfile")]
+    NotTagfile,
+
+    #[error("Could not decompress HKX: {0}")]
+    Dcx(#[from] DCXError),
+
+    #[error("Unsupported packfile version {0}")]
+    UnsupportedVersion(i32),
+
+    #[error("Packfile has no {0} section")]
+    MissingSection(&'static str),
+
+    #[error("Unsupported quantization format {0}")]
+    UnsupportedQuantization(u8),
+
+    #[error("Object of class {class} at {offset:#x} is truncated")]
+    Truncated { class: &'static str, offset: u32 },
+}
+
+const PACKFILE_MAGIC: [u32; 2] = [0x57E0E057, 0x10C0C010];
+
+/// Packfile version written by Havok 2010, used by Dark Souls.
+pub const PACKFILE_VERSION_2010: i32 = 8;
+
+/// Packfile version written by Havok 2014, used by Dark Souls Remastered, Bloodborne and Dark
+/// Souls 3.
+pub const PACKFILE_VERSION_2014: i32 = 11;
+
+/// A Havok file in either of the binary formats shipped by the games.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum Hkx {
+    Packfile(HkxPackfile),
+    Tagfile(HkxTagfile),
+}
+
+impl Hkx {
+    /// Read a packfile or tagfile, undoing the DCX compression Elden Ring wraps them in.
+    #[instrument(name = "hkx", skip_all, fields(size = bytes.len()))]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HkxError> {
+        let mut cursor = Cursor::new(bytes);
+        if DCX::has_magic(&mut cursor)? {
+            let dcx = DCX::from_reader(&mut cursor)?;
+            return Self::from_bytes(&dcx.decompressed);
+        }
+
+        if HkxTagfile::is_tagfile(bytes) {
+            Ok(Self::Tagfile(HkxTagfile::from_bytes(bytes)?))
+        } else {
+            Ok(Self::Packfile(HkxPackfile::from_reader(&mut cursor)?))
+        }
+    }
+
+    pub fn skeletons(&self) -> Result<Vec<HkaSkeleton>, HkxError> {
+        match self {
+            Self::Packfile(packfile) => HkaSkeleton::from_packfile(packfile),
+            Self::Tagfile(tagfile) => HkaSkeleton::from_tagfile(tagfile),
+        }
+    }
+
+    /// Decompress every spline-compressed animation, along with its track to bone mapping.
+    pub fn animations(&self) -> Result<Vec<HkaAnimation>, HkxError> {
+        match self {
+            Self::Packfile(packfile) => HkaAnimation::from_packfile(packfile),
+            Self::Tagfile(tagfile) => HkaAnimation::from_tagfile(tagfile),
+        }
+    }
+
+    /// Extract the triangles of every compressed collision mesh shape.
+    pub fn collision_meshes(&self) -> Result<Vec<CollisionMesh>, HkxError> {
+        match self {
+            Self::Packfile(packfile) => CollisionMesh::from_packfile(packfile),
+            Self::Tagfile(tagfile) => CollisionMesh::from_tagfile(tagfile),
+        }
+    }
+}
+
+/// A Havok binary packfile: sections of raw object data, with pointers between objects described
+/// by fixup tables rather than stored inline.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkxPackfile {
+    pub file_version: i32,
+    pub pointer_size: u8,
+    pub little_endian: bool,
+    pub contents_version: String,
+    pub sections: Vec<HkxSection>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkxSection {
+    /// Name of the section, typically `__classnames__`, `__types__` or `__data__`.
+    pub tag: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub data: Vec<u8>,
+
+    /// Pointers to data within this section, keyed by the offset of the pointer.
+    pub local_fixups: HashMap<u32, u32>,
+
+    /// Pointers to data in another section, keyed by the offset of the pointer.
+    pub global_fixups: HashMap<u32, (u32, u32)>,
+
+    /// Objects in this section, as their offset and class name.
+    pub objects: Vec<(u32, String)>,
+}
+
+/// A reference to a location within a packfile section.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkxPointer {
+    pub section: usize,
+    pub offset: u32,
+}
+
+struct SectionHeader {
+    tag: String,
+    data_start: u32,
+    local_fixups: u32,
+    global_fixups: u32,
+    virtual_fixups: u32,
+    exports: u32,
+    end: u32,
+}
+
+impl HkxPackfile {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, HkxError> {
+        let magic = [r.read_u32::<LE>()?, r.read_u32::<LE>()?];
+        if magic == PACKFILE_MAGIC {
+            Self::read::<_, LE>(r)
+        } else if magic == PACKFILE_MAGIC.map(u32::swap_bytes) {
+            Self::read::<_, BE>(r)
+        } else {
+            Err(HkxError::NotPackfile)
+        }
+    }
+
+    fn read<R: Read + Seek, O: ByteOrder>(r: &mut R) -> Result<Self, HkxError> {
+        let _user_tag = r.read_i32::<O>()?;
+        let file_version = r.read_i32::<O>()?;
+        if file_version != PACKFILE_VERSION_2010 && file_version != PACKFILE_VERSION_2014 {
+            return Err(HkxError::UnsupportedVersion(file_version));
+        }
+
+        let pointer_size = r.read_u8()?;
+        let little_endian = r.read_bool()?;
+        let _reuse_padding_optimization = r.read_u8()?;
+        let _empty_base_class_optimization = r.read_u8()?;
+        let section_count = r.read_i32::<O>()?;
+        let _contents_section_index = r.read_i32::<O>()?;
+        let _contents_section_offset = r.read_i32::<O>()?;
+        let _contents_class_name_section_index = r.read_i32::<O>()?;
+        let _contents_class_name_section_offset = r.read_i32::<O>()?;
+
+        let mut contents_version = [0u8; 16];
+        r.read_exact(&mut contents_version)?;
+        let contents_version = null_terminated(&contents_version);
+
+        let _flags = r.read_i32::<O>()?;
+        let _max_predicate = r.read_i16::<O>()?;
+        let predicate_array_size = r.read_i16::<O>()?;
+        if file_version >= PACKFILE_VERSION_2014 {
+            r.seek(SeekFrom::Current(predicate_array_size as i64))?;
+        }
+
+        let mut headers = Vec::with_capacity(section_count as usize);
+        for _ in 0..section_count {
+            let mut tag = [0u8; 20];
+            r.read_exact(&mut tag)?;
+
+            let data_start = r.read_u32::<O>()?;
+            let local_fixups = r.read_u32::<O>()?;
+            let global_fixups = r.read_u32::<O>()?;
+            let virtual_fixups = r.read_u32::<O>()?;
+            let exports = r.read_u32::<O>()?;
+            let _imports = r.read_u32::<O>()?;
+            let end = r.read_u32::<O>()?;
+
+            headers.push(SectionHeader {
+                tag: null_terminated(&tag),
+                data_start,
+                local_fixups,
+                global_fixups,
+                virtual_fixups,
+                exports,
+                end,
+            });
+
+            if file_version >= PACKFILE_VERSION_2014 {
+                r.seek(SeekFrom::Current(16))?;
+            }
+        }
+
+        let mut raw_sections = Vec::with_capacity(headers.len());
+        for header in &headers {
+            r.seek(SeekFrom::Start(header.data_start as u64))?;
+            let mut data = vec![0u8; header.end as usize];
+            r.read_exact(&mut data)?;
+            raw_sections.push(data);
+        }
+
+        let class_names = headers
+            .iter()
+            .position(|header| header.tag == "__classnames__")
+            .ok_or(HkxError::MissingSection("__classnames__"))?;
+
+        let mut sections = Vec::with_capacity(headers.len());
+        for (header, data) in headers.iter().zip(&raw_sections) {
+            let local = &data[header.local_fixups as usize..header.global_fixups as usize];
+            let local_fixups = local
+                .chunks_exact(8)
+                .map(|fixup| (O::read_u32(fixup), O::read_u32(&fixup[4..])))
+                .filter(|(src, _)| *src != u32::MAX)
+                .collect();
+
+            let global = &data[header.global_fixups as usize..header.virtual_fixups as usize];
+            let global_fixups = global
+                .chunks_exact(12)
+                .map(|fixup| {
+                    let src = O::read_u32(fixup);
+                    (src, (O::read_u32(&fixup[4..]), O::read_u32(&fixup[8..])))
+                })
+                .filter(|(src, _)| *src != u32::MAX)
+                .collect();
+
+            let virtual_fixups = &data[header.virtual_fixups as usize..header.exports as usize];
+            let objects = virtual_fixups
+                .chunks_exact(12)
+                .filter(|fixup| O::read_u32(fixup) != u32::MAX)
+                .map(|fixup| {
+                    let name_section = O::read_u32(&fixup[4..]) as usize;
+                    let name_offset = O::read_u32(&fixup[8..]) as usize;
+                    let names = raw_sections
+                        .get(name_section)
+                        .unwrap_or(&raw_sections[class_names]);
+
+                    (
+                        O::read_u32(fixup),
+                        null_terminated(names.get(name_offset..).unwrap_or_default()),
+                    )
+                })
+                .collect();
+
+            sections.push(HkxSection {
+                tag: header.tag.clone(),
+                data: data[..header.local_fixups as usize].to_vec(),
+                local_fixups,
+                global_fixups,
+                objects,
+            });
+        }
+
+        Ok(Self {
+            file_version,
+            pointer_size,
+            little_endian,
+            contents_version,
+            sections,
+        })
+    }
+
+    /// Every object in the packfile whose class is `class_name`, e.g. `hkaSkeleton`.
+    pub fn objects_of_class<'a>(
+        &'a self,
+        class_name: &'a str,
+    ) -> impl Iterator<Item = HkxPointer> + 'a {
+        self.sections
+            .iter()
+            .enumerate()
+            .flat_map(move |(section, data)| {
+                data.objects
+                    .iter()
+                    .filter(move |(_, class)| class == class_name)
+                    .map(move |(offset, _)| HkxPointer {
+                        section,
+                        offset: *offset,
+                    })
+            })
+    }
+
+    /// Follow the pointer stored at `at`, if it is non-null.
+    pub fn resolve(&self, at: HkxPointer) -> Option<HkxPointer> {
+        let section = self.sections.get(at.section)?;
+
+        if let Some(offset) = section.local_fixups.get(&at.offset) {
+            return Some(HkxPointer {
+                section: at.section,
+                offset: *offset,
+            });
+        }
+
+        section
+            .global_fixups
+            .get(&at.offset)
+            .map(|(section, offset)| HkxPointer {
+                section: *section as usize,
+                offset: *offset,
+            })
+    }
+
+    /// Bytes of a section starting at `at`.
+    pub fn bytes(&self, at: HkxPointer) -> &[u8] {
+        self.sections
+            .get(at.section)
+            .and_then(|section| section.data.get(at.offset as usize..))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn read_u16(&self, bytes: &[u8]) -> u16 {
+        if self.little_endian {
+            LE::read_u16(bytes)
+        } else {
+            BE::read_u16(bytes)
+        }
+    }
+
+    pub(crate) fn read_u32(&self, bytes: &[u8]) -> u32 {
+        if self.little_endian {
+            LE::read_u32(bytes)
+        } else {
+            BE::read_u32(bytes)
+        }
+    }
+
+    pub(crate) fn read_f32(&self, bytes: &[u8]) -> f32 {
+        f32::from_bits(self.read_u32(bytes))
+    }
+
+    /// Iterate over the elements of the `hkArray` at `at`, yielding a pointer to and the bytes of
+    /// each.
+    pub(crate) fn array_elements(
+        &self,
+        at: HkxPointer,
+        element_size: usize,
+    ) -> Result<impl Iterator<Item = (HkxPointer, &[u8])> + '_, HkxError> {
+        let count = self.read_u32(&self.bytes(at)[self.pointer_size as usize..]) as usize;
+        let data = self.resolve(at);
+
+        let available = data.map_or(0, |data| self.bytes(data).len());
+        if count > 0 && (data.is_none() || available < count * element_size) {
+            return Err(HkxError::Truncated {
+                class: "hkArray",
+                offset: at.offset,
+            });
+        }
+
+        Ok((0..count).filter_map(move |index| {
+            let data = data?;
+            let element = HkxPointer {
+                section: data.section,
+                offset: data.offset + (index * element_size) as u32,
+            };
+
+            Some((element, &self.bytes(element)[..element_size]))
+        }))
+    }
+
+    /// Iterate over the bytes of each element of the `hkArray` at `at`.
+    pub(crate) fn array(
+        &self,
+        at: HkxPointer,
+        element_size: usize,
+    ) -> Result<impl Iterator<Item = &[u8]> + '_, HkxError> {
+        Ok(self
+            .array_elements(at, element_size)?
+            .map(|(_, bytes)| bytes))
+    }
+
+    /// Read a null-terminated string pointed to by the `hkStringPtr` or `char*` at `at`.
+    pub fn read_string_ptr(&self, at: HkxPointer) -> Option<String> {
+        self.resolve(at)
+            .map(|target| null_terminated(self.bytes(target)))
+    }
+}
+
+fn null_terminated(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
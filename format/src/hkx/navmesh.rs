@@ -0,0 +1,168 @@
+use crate::hkx::{HkxError, HkxTagfile, TagRecord, TagValue};
+
+/// The walkable geometry of an `hkaiNavMesh`, as stored in the `.nvmhktbnd` of Sekiro and Elden
+/// Ring maps.
+///
+/// Faces are convex polygons described by a run of edges, and each edge links to the face on its
+/// other side, if any.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkaiNavMesh {
+    pub vertices: Vec<[f32; 3]>,
+    pub faces: Vec<NavMeshFace>,
+    pub edges: Vec<NavMeshEdge>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NavMeshFace {
+    pub start_edge: usize,
+    pub edge_count: usize,
+    pub cluster_index: i64,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NavMeshEdge {
+    pub a: usize,
+    pub b: usize,
+
+    /// The matching edge of the neighbouring face, if this edge is not on the boundary.
+    pub opposite_edge: Option<usize>,
+    pub opposite_face: Option<usize>,
+    pub flags: u8,
+}
+
+/// Havok's null index for edges and faces.
+const NULL_INDEX: i64 = 0xFFFFFFFF;
+
+impl HkaiNavMesh {
+    /// Read every `hkaiNavMesh` in a tagfile.
+    pub fn from_tagfile(tagfile: &HkxTagfile) -> Result<Vec<Self>, HkxError> {
+        tagfile
+            .items_of_type("hkaiNavMesh")
+            .map(|item| {
+                let object = tagfile.object(item);
+                let record =
+                    object
+                        .as_ref()
+                        .and_then(TagValue::as_record)
+                        .ok_or(HkxError::Truncated {
+                            class: "hkaiNavMesh",
+                            offset: tagfile.items[item].offset,
+                        })?;
+
+                Ok(Self::from_record(record))
+            })
+            .collect()
+    }
+
+    fn from_record(record: &TagRecord) -> Self {
+        let records = |name: &str| {
+            record
+                .get(name)
+                .and_then(TagValue::as_array)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(TagValue::as_record)
+                .collect::<Vec<_>>()
+        };
+        let int = |record: &TagRecord, name: &str| {
+            record
+                .get(name)
+                .and_then(TagValue::as_int)
+                .unwrap_or_default()
+        };
+        let index = |record: &TagRecord, name: &str| {
+            let value = int(record, name);
+            (value >= 0 && value != NULL_INDEX).then_some(value as usize)
+        };
+
+        let vertices = record
+            .get("vertices")
+            .and_then(TagValue::as_array)
+            .unwrap_or_default()
+            .iter()
+            .map(|vertex| {
+                let floats = vertex.floats();
+                std::array::from_fn(|i| floats.get(i).copied().unwrap_or_default())
+            })
+            .collect();
+
+        let faces = records("faces")
+            .into_iter()
+            .map(|face| NavMeshFace {
+                start_edge: int(face, "startEdgeIndex").max(0) as usize,
+                edge_count: int(face, "numEdges").max(0) as usize,
+                cluster_index: int(face, "clusterIndex"),
+            })
+            .collect();
+
+        let edges = records("edges")
+            .into_iter()
+            .map(|edge| NavMeshEdge {
+                a: int(edge, "a").max(0) as usize,
+                b: int(edge, "b").max(0) as usize,
+                opposite_edge: index(edge, "oppositeEdge"),
+                opposite_face: index(edge, "oppositeFace"),
+                flags: int(edge, "flags") as u8,
+            })
+            .collect();
+
+        Self {
+            vertices,
+            faces,
+            edges,
+        }
+    }
+
+    /// The edges bounding a face, in winding order.
+    pub fn face_edges(&self, face: &NavMeshFace) -> &[NavMeshEdge] {
+        self.edges
+            .get(face.start_edge..face.start_edge + face.edge_count)
+            .unwrap_or_default()
+    }
+
+    /// Fan triangulate every face, returning three vertex indices per triangle.
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        self.faces
+            .iter()
+            .flat_map(|face| {
+                let edges = self.face_edges(face);
+                let first = edges.first().map(|edge| edge.a);
+
+                edges
+                    .iter()
+                    .skip(1)
+                    .take(edges.len().saturating_sub(2))
+                    .filter_map(move |edge| Some([first?, edge.a, edge.b]))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hkx::navmesh::{HkaiNavMesh, NavMeshEdge, NavMeshFace};
+
+    #[test]
+    pub fn triangulates_faces() {
+        let edge = |a, b| NavMeshEdge {
+            a,
+            b,
+            ..Default::default()
+        };
+
+        let navmesh = HkaiNavMesh {
+            vertices: vec![[0.0; 3]; 4],
+            faces: vec![NavMeshFace {
+                start_edge: 0,
+                edge_count: 4,
+                cluster_index: 0,
+            }],
+            edges: vec![edge(0, 1), edge(1, 2), edge(2, 3), edge(3, 0)],
+        };
+
+        assert_eq!(navmesh.triangles(), vec![[0, 1, 2], [0, 2, 3]]);
+    }
+}
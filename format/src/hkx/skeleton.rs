@@ -0,0 +1,206 @@
+use crate::hkx::{HkxError, HkxPackfile, HkxPointer, HkxTagfile, TagRecord};
+
+const QS_TRANSFORM_SIZE: usize = 0x30;
+
+/// The bind pose hierarchy of an animated model, as stored in `skeleton.hkx`.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkaSkeleton {
+    pub name: String,
+    pub bones: Vec<HkaBone>,
+
+    /// The local transform of each bone relative to its parent in the bind pose.
+    pub reference_pose: Vec<HkQsTransform>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkaBone {
+    pub name: String,
+    pub parent: Option<usize>,
+    pub lock_translation: bool,
+}
+
+/// A translation, quaternion rotation and scale.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkQsTransform {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+/// Field offsets of `hkaSkeleton` and the size of `hkaBone`, which depend on the pointer size.
+struct SkeletonLayout {
+    name: usize,
+    parent_indices: usize,
+    bones: usize,
+    reference_pose: usize,
+    bone_size: usize,
+    pointer_size: usize,
+}
+
+impl SkeletonLayout {
+    fn new(pointer_size: usize) -> Self {
+        // hkReferencedObject: vtable, memSizeAndFlags and referenceCount, padded to pointer size.
+        let base = (pointer_size + 4).next_multiple_of(pointer_size);
+        let array_size = pointer_size + 8;
+
+        Self {
+            name: base,
+            parent_indices: base + pointer_size,
+            bones: base + pointer_size + array_size,
+            reference_pose: base + pointer_size + array_size * 2,
+            bone_size: pointer_size * 2,
+            pointer_size,
+        }
+    }
+}
+
+impl HkaSkeleton {
+    /// Read every `hkaSkeleton` in a packfile.
+    pub fn from_packfile(packfile: &HkxPackfile) -> Result<Vec<Self>, HkxError> {
+        packfile
+            .objects_of_class("hkaSkeleton")
+            .map(|object| Self::read(packfile, object))
+            .collect()
+    }
+
+    /// Read every `hkaSkeleton` in a tagfile.
+    pub fn from_tagfile(tagfile: &HkxTagfile) -> Result<Vec<Self>, HkxError> {
+        tagfile
+            .items_of_type("hkaSkeleton")
+            .map(|item| {
+                let object = tagfile.object(item);
+                let record = object
+                    .as_ref()
+                    .and_then(|object| object.as_record())
+                    .ok_or(HkxError::Truncated {
+                        class: "hkaSkeleton",
+                        offset: tagfile.items[item].offset,
+                    })?;
+
+                Ok(Self::from_record(record))
+            })
+            .collect()
+    }
+
+    fn from_record(record: &TagRecord) -> Self {
+        let array = |name: &str| {
+            record
+                .get(name)
+                .and_then(|value| value.as_array())
+                .unwrap_or_default()
+        };
+
+        let parents = array("parentIndices")
+            .iter()
+            .map(|parent| parent.as_int().unwrap_or(-1))
+            .collect::<Vec<_>>();
+
+        let bones = array("bones")
+            .iter()
+            .enumerate()
+            .map(|(index, bone)| {
+                let bone = bone.as_record();
+                let field = |name: &str| bone.and_then(|bone| bone.get(name));
+
+                HkaBone {
+                    name: field("name")
+                        .and_then(|name| name.as_str())
+                        .unwrap_or_default()
+                        .to_string(),
+                    parent: parents
+                        .get(index)
+                        .and_then(|parent| usize::try_from(*parent).ok()),
+                    lock_translation: field("lockTranslation")
+                        .and_then(|lock| lock.as_int())
+                        .is_some_and(|lock| lock != 0),
+                }
+            })
+            .collect();
+
+        let reference_pose = array("referencePose")
+            .iter()
+            .map(|transform| {
+                let floats = transform.floats();
+                let float = |index: usize| floats.get(index).copied().unwrap_or_default();
+
+                HkQsTransform {
+                    translation: [float(0), float(1), float(2)],
+                    rotation: [float(4), float(5), float(6), float(7)],
+                    scale: [float(8), float(9), float(10)],
+                }
+            })
+            .collect();
+
+        Self {
+            name: record
+                .get("name")
+                .and_then(|name| name.as_str())
+                .unwrap_or_default()
+                .to_string(),
+            bones,
+            reference_pose,
+        }
+    }
+
+    fn read(packfile: &HkxPackfile, object: HkxPointer) -> Result<Self, HkxError> {
+        let layout = SkeletonLayout::new(packfile.pointer_size as usize);
+        let field = |offset: usize| HkxPointer {
+            section: object.section,
+            offset: object.offset + offset as u32,
+        };
+
+        if packfile.bytes(object).len() < layout.reference_pose + layout.pointer_size + 4 {
+            return Err(HkxError::Truncated {
+                class: "hkaSkeleton",
+                offset: object.offset,
+            });
+        }
+
+        let name = packfile
+            .read_string_ptr(field(layout.name))
+            .unwrap_or_default();
+
+        let parents = packfile
+            .array(field(layout.parent_indices), 2)?
+            .map(|bytes| packfile.read_u16(bytes) as i16)
+            .collect::<Vec<_>>();
+
+        let bones = packfile
+            .array_elements(field(layout.bones), layout.bone_size)?
+            .enumerate()
+            .map(|(index, (bone, bytes))| HkaBone {
+                name: packfile.read_string_ptr(bone).unwrap_or_default(),
+                parent: parents
+                    .get(index)
+                    .and_then(|parent| usize::try_from(*parent).ok()),
+                lock_translation: bytes[layout.pointer_size] != 0,
+            })
+            .collect();
+
+        let reference_pose = packfile
+            .array(field(layout.reference_pose), QS_TRANSFORM_SIZE)?
+            .map(|bytes| {
+                let float = |index: usize| packfile.read_f32(&bytes[index * 4..]);
+
+                HkQsTransform {
+                    translation: [float(0), float(1), float(2)],
+                    rotation: [float(4), float(5), float(6), float(7)],
+                    scale: [float(8), float(9), float(10)],
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            name,
+            bones,
+            reference_pose,
+        })
+    }
+
+    pub fn bone_index(&self, name: &str) -> Option<usize> {
+        self.bones.iter().position(|bone| bone.name == name)
+    }
+}
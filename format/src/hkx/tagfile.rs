@@ -0,0 +1,724 @@
+use byteorder::{ByteOrder, BE, LE};
+
+use crate::hkx::HkxError;
+
+/// Kinds of data a tagfile type can describe, stored in the low bits of its format.
+const FORMAT_VOID: u64 = 0;
+const FORMAT_OPAQUE: u64 = 1;
+const FORMAT_BOOL: u64 = 2;
+const FORMAT_STRING: u64 = 3;
+const FORMAT_INT: u64 = 4;
+const FORMAT_FLOAT: u64 = 5;
+const FORMAT_POINTER: u64 = 6;
+const FORMAT_RECORD: u64 = 7;
+const FORMAT_ARRAY: u64 = 8;
+
+const FORMAT_KIND_MASK: u64 = 0xF;
+const FORMAT_FIXED_SIZE: u64 = 0x20;
+const FORMAT_SIGNED: u64 = 0x200;
+
+const TYPE_HAS_FORMAT: u64 = 0x1;
+const TYPE_HAS_SUBTYPE: u64 = 0x2;
+const TYPE_HAS_VERSION: u64 = 0x4;
+const TYPE_HAS_SIZE_ALIGN: u64 = 0x8;
+const TYPE_HAS_FLAGS: u64 = 0x10;
+const TYPE_HAS_FIELDS: u64 = 0x20;
+const TYPE_HAS_INTERFACES: u64 = 0x40;
+const TYPE_HAS_ATTRIBUTE: u64 = 0x80;
+
+/// A Havok tagfile, the self-describing binary format used from Havok 2015 onwards (e.g. by
+/// Sekiro and Elden Ring).
+///
+/// Unlike packfiles, tagfiles carry a full description of every type they contain, so objects are
+/// decoded generically into [TagValue]s and then interpreted by name.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkxTagfile {
+    pub sdk_version: String,
+    pub types: Vec<TagType>,
+    pub items: Vec<TagItem>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TagType {
+    pub name: String,
+    pub templates: Vec<(String, u64)>,
+    pub parent: usize,
+    pub format: Option<u64>,
+    pub subtype: Option<usize>,
+    pub version: u64,
+    pub size: Option<u64>,
+    pub alignment: Option<u64>,
+    pub flags: u64,
+    pub fields: Vec<TagField>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TagField {
+    pub name: String,
+    pub flags: u64,
+    pub offset: u64,
+    pub type_index: usize,
+}
+
+/// A block of one or more consecutive values of a type in the data section. Pointers, strings and
+/// arrays refer to their targets by item index.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TagItem {
+    pub type_index: usize,
+    pub flags: u8,
+    pub offset: u32,
+    pub count: u32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TagValue {
+    Void,
+    Opaque(Vec<u8>),
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(Option<String>),
+
+    /// The item index of the pointed to object, if not null.
+    Pointer(Option<usize>),
+    Array(Vec<TagValue>),
+    Record(TagRecord),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TagRecord {
+    pub type_name: String,
+    pub fields: Vec<(String, TagValue)>,
+}
+
+struct Section<'a> {
+    tag: [u8; 4],
+    data: &'a [u8],
+}
+
+/// Maximum depth of nested inline values, guarding against malformed recursive types.
+const MAX_DEPTH: usize = 64;
+
+/// Types shared by a set of tagfiles (`.compendium`), which refer to it by ID rather than
+/// describing their types themselves.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HkxCompendium {
+    pub ids: Vec<u64>,
+    pub types: Vec<TagType>,
+}
+
+impl HkxCompendium {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HkxError> {
+        let root = sections(bytes)?
+            .into_iter()
+            .find(|section| &section.tag == b"TCM0")
+            .ok_or(HkxError::NotTagfile)?;
+
+        let mut ids = Vec::new();
+        let mut types = None;
+        for section in sections(root.data)? {
+            match &section.tag {
+                b"TCID" => ids = section.data.chunks_exact(8).map(LE::read_u64).collect(),
+                b"TYPE" => types = Some(read_types(section.data)?),
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            ids,
+            types: types.ok_or(HkxError::MissingSection("TYPE"))?,
+        })
+    }
+}
+
+impl HkxTagfile {
+    pub fn is_tagfile(bytes: &[u8]) -> bool {
+        bytes.get(4..8) == Some(b"TAG0")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HkxError> {
+        Self::from_bytes_with_compendium(bytes, None)
+    }
+
+    /// Read a tagfile whose types may be stored in a shared compendium, as Elden Ring does for
+    /// the many small collision files of a map.
+    pub fn from_bytes_with_compendium(
+        bytes: &[u8],
+        compendium: Option<&HkxCompendium>,
+    ) -> Result<Self, HkxError> {
+        let root = sections(bytes)?
+            .into_iter()
+            .find(|section| &section.tag == b"TAG0")
+            .ok_or(HkxError::NotTagfile)?;
+
+        let mut sdk_version = String::new();
+        let mut data = Vec::new();
+        let mut types = None;
+        let mut compendium_id = None;
+        let mut item_data = None;
+
+        for section in sections(root.data)? {
+            match &section.tag {
+                b"SDKV" => sdk_version = String::from_utf8_lossy(section.data).into_owned(),
+                b"DATA" => data = section.data.to_vec(),
+                b"TYPE" => types = Some(read_types(section.data)?),
+                b"TCRF" => compendium_id = section.data.get(..8).map(LE::read_u64),
+                b"INDX" => {
+                    for section in sections(section.data)? {
+                        if &section.tag == b"ITEM" {
+                            item_data = Some(section.data);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let types = match (types, compendium_id, compendium) {
+            (Some(types), _, _) => types,
+            (None, Some(id), Some(compendium)) if compendium.ids.contains(&id) => {
+                compendium.types.clone()
+            }
+            (None, Some(_), _) => return Err(HkxError::MissingSection("TCM0")),
+            (None, None, _) => return Err(HkxError::MissingSection("TYPE")),
+        };
+
+        let items = item_data
+            .ok_or(HkxError::MissingSection("ITEM"))?
+            .chunks_exact(12)
+            .map(|item| {
+                let flags_and_type = LE::read_u32(item);
+
+                TagItem {
+                    type_index: (flags_and_type & 0xFFFFFF) as usize,
+                    flags: (flags_and_type >> 24) as u8,
+                    offset: LE::read_u32(&item[4..]),
+                    count: LE::read_u32(&item[8..]),
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            sdk_version,
+            types,
+            items,
+            data,
+        })
+    }
+
+    pub fn type_name(&self, type_index: usize) -> &str {
+        self.types
+            .get(type_index)
+            .map(|ty| ty.name.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Indices of every item whose type is named `type_name`, e.g. `hkaSkeleton`.
+    pub fn items_of_type<'a>(&'a self, type_name: &'a str) -> impl Iterator<Item = usize> + 'a {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(move |(_, item)| self.type_name(item.type_index) == type_name)
+            .map(|(index, _)| index)
+    }
+
+    /// Decode the first value of an item.
+    pub fn object(&self, item_index: usize) -> Option<TagValue> {
+        let item = self.items.get(item_index)?;
+
+        self.value(item.type_index, item.offset as usize, 0)
+    }
+
+    /// Follow a pointer value to the object it points at.
+    pub fn deref(&self, value: &TagValue) -> Option<TagValue> {
+        match value {
+            TagValue::Pointer(Some(item)) => self.object(*item),
+            _ => None,
+        }
+    }
+
+    /// Resolve a property of a type, walking up its parents until one defines it.
+    fn resolve<T>(&self, type_index: usize, get: impl Fn(&TagType) -> Option<T>) -> Option<T> {
+        let mut current = type_index;
+
+        for _ in 0..MAX_DEPTH {
+            let ty = self.types.get(current)?;
+            if let Some(value) = get(ty) {
+                return Some(value);
+            }
+
+            if ty.parent == 0 {
+                return None;
+            }
+            current = ty.parent;
+        }
+
+        None
+    }
+
+    fn size_of(&self, type_index: usize) -> usize {
+        self.resolve(type_index, |ty| ty.size).unwrap_or_default() as usize
+    }
+
+    fn value(&self, type_index: usize, offset: usize, depth: usize) -> Option<TagValue> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+
+        let format = self.resolve(type_index, |ty| ty.format).unwrap_or_default();
+        let size = self.size_of(type_index);
+        let bytes = self.data.get(offset..offset + size)?;
+
+        Some(match format & FORMAT_KIND_MASK {
+            FORMAT_VOID => TagValue::Void,
+            FORMAT_OPAQUE => TagValue::Opaque(bytes.to_vec()),
+            FORMAT_BOOL => TagValue::Bool(bytes.iter().any(|b| *b != 0)),
+            FORMAT_INT => {
+                let signed = format & FORMAT_SIGNED != 0;
+                TagValue::Int(match (bytes.len(), signed) {
+                    (1, true) => bytes[0] as i8 as i64,
+                    (1, false) => bytes[0] as i64,
+                    (2, true) => LE::read_i16(bytes) as i64,
+                    (2, false) => LE::read_u16(bytes) as i64,
+                    (4, true) => LE::read_i32(bytes) as i64,
+                    (4, false) => LE::read_u32(bytes) as i64,
+                    (8, _) => LE::read_i64(bytes),
+                    _ => return Some(TagValue::Opaque(bytes.to_vec())),
+                })
+            }
+            FORMAT_FLOAT => TagValue::Float(match bytes.len() {
+                4 => LE::read_f32(bytes) as f64,
+                8 => LE::read_f64(bytes),
+                _ => return Some(TagValue::Opaque(bytes.to_vec())),
+            }),
+            FORMAT_STRING => {
+                let item = self.pointer_item(bytes);
+                TagValue::String(item.map(|item| {
+                    let item = &self.items[item];
+                    let start = item.offset as usize;
+                    let end = (start + item.count as usize).min(self.data.len());
+                    let chars = &self.data[start.min(end)..end];
+                    let len = chars.iter().position(|b| *b == 0).unwrap_or(chars.len());
+
+                    String::from_utf8_lossy(&chars[..len]).into_owned()
+                }))
+            }
+            FORMAT_POINTER => TagValue::Pointer(self.pointer_item(bytes)),
+            FORMAT_ARRAY if format & FORMAT_FIXED_SIZE != 0 => {
+                let subtype = self.resolve(type_index, |ty| ty.subtype)?;
+                let count = (format >> 8) as usize;
+                let stride = self.size_of(subtype);
+
+                TagValue::Array(
+                    (0..count)
+                        .map(|i| self.value(subtype, offset + i * stride, depth + 1))
+                        .collect::<Option<Vec<_>>>()?,
+                )
+            }
+            FORMAT_ARRAY => match self.pointer_item(bytes) {
+                Some(item) => {
+                    let item = self.items[item];
+                    let stride = self.size_of(item.type_index);
+
+                    TagValue::Array(
+                        (0..item.count as usize)
+                            .map(|i| {
+                                self.value(
+                                    item.type_index,
+                                    item.offset as usize + i * stride,
+                                    depth + 1,
+                                )
+                            })
+                            .collect::<Option<Vec<_>>>()?,
+                    )
+                }
+                None => TagValue::Array(Vec::new()),
+            },
+            FORMAT_RECORD => TagValue::Record(self.record(type_index, offset, depth)?),
+            _ => TagValue::Opaque(bytes.to_vec()),
+        })
+    }
+
+    fn record(&self, type_index: usize, offset: usize, depth: usize) -> Option<TagRecord> {
+        // Collect the type hierarchy so inherited fields come first.
+        let mut hierarchy = Vec::new();
+        let mut current = type_index;
+        while current != 0 && hierarchy.len() < MAX_DEPTH {
+            hierarchy.push(current);
+            current = self.types.get(current)?.parent;
+        }
+
+        let mut fields = Vec::new();
+        for &ty in hierarchy.iter().rev() {
+            for field in &self.types[ty].fields {
+                let value =
+                    self.value(field.type_index, offset + field.offset as usize, depth + 1)?;
+                fields.push((field.name.clone(), value));
+            }
+        }
+
+        Some(TagRecord {
+            type_name: self.type_name(type_index).to_string(),
+            fields,
+        })
+    }
+
+    /// Read the item index stored in a pointer-sized slot.
+    fn pointer_item(&self, bytes: &[u8]) -> Option<usize> {
+        let index = match bytes.len() {
+            4 => LE::read_u32(bytes) as usize,
+            8.. => LE::read_u64(bytes) as usize,
+            _ => return None,
+        };
+
+        (index != 0 && index < self.items.len()).then_some(index)
+    }
+}
+
+impl TagValue {
+    pub fn as_record(&self) -> Option<&TagRecord> {
+        match self {
+            Self::Record(record) => Some(record),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[TagValue]> {
+        match self {
+            Self::Array(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            Self::Int(value) => Some(*value),
+            Self::Bool(value) => Some(*value as i64),
+            _ => None,
+        }
+    }
+
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            Self::Float(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(value) => value.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Every float nested within this value in order, e.g. the components of a `hkQsTransform`.
+    pub fn floats(&self) -> Vec<f32> {
+        let mut floats = Vec::new();
+        self.collect_floats(&mut floats);
+
+        floats
+    }
+
+    fn collect_floats(&self, floats: &mut Vec<f32>) {
+        match self {
+            Self::Float(value) => floats.push(*value as f32),
+            Self::Array(values) => values.iter().for_each(|v| v.collect_floats(floats)),
+            Self::Record(record) => record
+                .fields
+                .iter()
+                .for_each(|(_, v)| v.collect_floats(floats)),
+            Self::Opaque(bytes) if bytes.len() % 4 == 0 => {
+                floats.extend(bytes.chunks_exact(4).map(LE::read_f32))
+            }
+            _ => {}
+        }
+    }
+}
+
+impl TagRecord {
+    pub fn get(&self, name: &str) -> Option<&TagValue> {
+        self.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+    }
+}
+
+/// Split a run of sections, each prefixed by a big endian size (including the header) with two
+/// flag bits, followed by a four character tag.
+fn sections(bytes: &[u8]) -> Result<Vec<Section<'_>>, HkxError> {
+    let mut sections = Vec::new();
+    let mut offset = 0;
+
+    while offset + 8 <= bytes.len() {
+        let size = (BE::read_u32(&bytes[offset..]) & 0x3FFFFFFF) as usize;
+        if size < 8 || offset + size > bytes.len() {
+            return Err(HkxError::Truncated {
+                class: "tagfile section",
+                offset: offset as u32,
+            });
+        }
+
+        let mut tag = [0u8; 4];
+        tag.copy_from_slice(&bytes[offset + 4..offset + 8]);
+
+        sections.push(Section {
+            tag,
+            data: &bytes[offset + 8..offset + size],
+        });
+        offset += size;
+    }
+
+    Ok(sections)
+}
+
+fn strings(bytes: &[u8]) -> Vec<String> {
+    let mut strings = bytes
+        .split(|b| *b == 0)
+        .map(|s| String::from_utf8_lossy(s).into_owned())
+        .collect::<Vec<_>>();
+
+    // The table is null terminated, so splitting leaves a trailing empty string.
+    if bytes.last() == Some(&0) {
+        strings.pop();
+    }
+
+    strings
+}
+
+/// A cursor over the variable-length integers used to encode type information.
+struct PackedReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl PackedReader<'_> {
+    fn is_empty(&self) -> bool {
+        self.offset >= self.bytes.len()
+    }
+
+    fn byte(&mut self) -> Result<u64, HkxError> {
+        let byte = *self.bytes.get(self.offset).ok_or(HkxError::Truncated {
+            class: "tagfile type",
+            offset: self.offset as u32,
+        })?;
+        self.offset += 1;
+
+        Ok(byte as u64)
+    }
+
+    fn bytes(&mut self, first: u64, count: usize) -> Result<u64, HkxError> {
+        let mut value = first;
+        for _ in 0..count {
+            value = (value << 8) | self.byte()?;
+        }
+
+        Ok(value)
+    }
+
+    /// Read an integer whose length is given by the number of leading set bits of its first byte.
+    fn packed(&mut self) -> Result<u64, HkxError> {
+        let first = self.byte()?;
+
+        Ok(match first {
+            0x00..=0x7F => first,
+            0x80..=0xBF => self.bytes(first, 1)? & 0x3FFF,
+            0xC0..=0xDF => self.bytes(first, 2)? & 0x1FFFFF,
+            0xE0..=0xEF => self.bytes(first, 3)? & 0x0FFFFFFF,
+            0xF0..=0xF7 => self.bytes(first, 7)? & 0x07FF_FFFF_FFFF_FFFF,
+            _ => self.bytes(0, 8)?,
+        })
+    }
+
+    fn index(&mut self) -> Result<usize, HkxError> {
+        Ok(self.packed()? as usize)
+    }
+}
+
+/// Read the type names and bodies of a `TYPE` section.
+fn read_types(bytes: &[u8]) -> Result<Vec<TagType>, HkxError> {
+    let mut type_strings = Vec::new();
+    let mut field_strings = Vec::new();
+    let mut type_names = None;
+    let mut type_bodies = None;
+
+    for section in sections(bytes)? {
+        match &section.tag {
+            b"TSTR" | b"TST1" => type_strings = strings(section.data),
+            b"FSTR" | b"FST1" => field_strings = strings(section.data),
+            b"TNAM" | b"TNA1" => type_names = Some(section.data),
+            b"TBOD" | b"TBDY" => type_bodies = Some(section.data),
+            _ => {}
+        }
+    }
+
+    let mut types = read_type_names(
+        type_names.ok_or(HkxError::MissingSection("TNAM"))?,
+        &type_strings,
+    )?;
+    read_type_bodies(
+        type_bodies.ok_or(HkxError::MissingSection("TBOD"))?,
+        &field_strings,
+        &mut types,
+    )?;
+
+    Ok(types)
+}
+
+fn read_type_names(bytes: &[u8], strings: &[String]) -> Result<Vec<TagType>, HkxError> {
+    let mut r = PackedReader { bytes, offset: 0 };
+    let count = r.index()?;
+    let string = |index: usize| strings.get(index).cloned().unwrap_or_default();
+
+    let mut types = vec![TagType::default(); count.max(1)];
+    for ty in types.iter_mut().skip(1) {
+        ty.name = string(r.index()?);
+
+        let template_count = r.index()?;
+        for _ in 0..template_count {
+            let name = string(r.index()?);
+            let value = r.packed()?;
+            ty.templates.push((name, value));
+        }
+    }
+
+    Ok(types)
+}
+
+fn read_type_bodies(
+    bytes: &[u8],
+    field_strings: &[String],
+    types: &mut [TagType],
+) -> Result<(), HkxError> {
+    let mut r = PackedReader { bytes, offset: 0 };
+
+    while !r.is_empty() {
+        let type_index = r.index()?;
+        if type_index == 0 {
+            continue;
+        }
+
+        let mut ty = TagType {
+            parent: r.index()?,
+            ..Default::default()
+        };
+
+        let optionals = r.packed()?;
+        if optionals & TYPE_HAS_FORMAT != 0 {
+            ty.format = Some(r.packed()?);
+        }
+        if optionals & TYPE_HAS_SUBTYPE != 0 {
+            ty.subtype = Some(r.index()?);
+        }
+        if optionals & TYPE_HAS_VERSION != 0 {
+            ty.version = r.packed()?;
+        }
+        if optionals & TYPE_HAS_SIZE_ALIGN != 0 {
+            ty.size = Some(r.packed()?);
+            ty.alignment = Some(r.packed()?);
+        }
+        if optionals & TYPE_HAS_FLAGS != 0 {
+            ty.flags = r.packed()?;
+        }
+        if optionals & TYPE_HAS_FIELDS != 0 {
+            let count = r.packed()? & 0xFFFF;
+            for _ in 0..count {
+                let name = field_strings.get(r.index()?).cloned().unwrap_or_default();
+                let flags = r.packed()?;
+                let offset = r.packed()?;
+                let type_index = r.index()?;
+
+                ty.fields.push(TagField {
+                    name,
+                    flags,
+                    offset,
+                    type_index,
+                });
+            }
+        }
+        if optionals & TYPE_HAS_INTERFACES != 0 {
+            let count = r.packed()?;
+            for _ in 0..count {
+                let _type_index = r.packed()?;
+                let _value = r.packed()?;
+            }
+        }
+        if optionals & TYPE_HAS_ATTRIBUTE != 0 {
+            let _attribute = r.packed()?;
+        }
+
+        if let Some(existing) = types.get_mut(type_index) {
+            ty.name = std::mem::take(&mut existing.name);
+            ty.templates = std::mem::take(&mut existing.templates);
+            *existing = ty;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::hkx::{HkxTagfile, TagValue};
+
+    fn section(tag: &[u8; 4], leaf: bool, data: &[u8]) -> Vec<u8> {
+        let size = (data.len() + 8) as u32 | if leaf { 0x40000000 } else { 0 };
+
+        let mut out = size.to_be_bytes().to_vec();
+        out.extend_from_slice(tag);
+        out.extend_from_slice(data);
+        out
+    }
+
+    #[test]
+    pub fn decodes_records_from_type_info() {
+        let type_section = [
+            section(b"TSTR", true, b"int\0Rec\0"),
+            section(b"TNAM", true, &[3, 0, 0, 1, 0]),
+            section(b"FSTR", true, b"value\0"),
+            section(
+                b"TBOD",
+                true,
+                &[
+                    // int: signed 32-bit integer, with a format needing a 3 byte packed int.
+                    1, 0, 0x09, 0xC0, 0x82, 0x04, 4, 4,
+                    // Rec: record with a single int field at offset 0.
+                    2, 0, 0x29, 7, 4, 4, 1, 0, 0, 0, 1,
+                ],
+            ),
+        ]
+        .concat();
+
+        let mut items = vec![0u8; 12];
+        items.extend_from_slice(&2u32.to_le_bytes());
+        items.extend_from_slice(&0u32.to_le_bytes());
+        items.extend_from_slice(&1u32.to_le_bytes());
+
+        let root = [
+            section(b"SDKV", true, b"20180100"),
+            section(b"DATA", true, &(-5i32).to_le_bytes()),
+            section(b"TYPE", false, &type_section),
+            section(b"INDX", false, &section(b"ITEM", true, &items)),
+        ]
+        .concat();
+
+        let tagfile = HkxTagfile::from_bytes(&section(b"TAG0", false, &root)).unwrap();
+        assert_eq!(tagfile.sdk_version, "20180100");
+        assert_eq!(tagfile.items_of_type("Rec").collect::<Vec<_>>(), [1]);
+
+        let object = tagfile.object(1).unwrap();
+        let record = object.as_record().unwrap();
+        assert_eq!(record.type_name, "Rec");
+        assert_eq!(record.get("value"), Some(&TagValue::Int(-5)));
+    }
+}
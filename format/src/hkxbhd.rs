@@ -0,0 +1,100 @@
+use std::io::{self, Cursor};
+
+use thiserror::Error;
+
+use crate::{
+    bnd4::BND4,
+    bxf4::BXF4,
+    dcx::{DCXError, DCX},
+    hkx::{CollisionMesh, Hkx, HkxCompendium, HkxError, HkxTagfile},
+};
+
+#[derive(Debug, Error)]
+pub enum MapCollisionError {
+    #[error("Could not read collision binder: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Could not decompress collision: {0}")]
+    Dcx(#[from] DCXError),
+
+    #[error("Could not parse collision of {0}: {1}")]
+    Hkx(String, HkxError),
+}
+
+/// The collision of a map (`hXX_XX_XX_XX.hkxbhd` and its `.hkxbdt`), split into one HKX per cell.
+///
+/// In Elden Ring the cells are tagfiles that leave out their types, which are shared through a
+/// compendium stored in the same binder.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MapCollision {
+    pub cells: Vec<CollisionCell>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CollisionCell {
+    /// Name of the cell's file without its extensions, e.g. `h60_44_36_00_443600`.
+    pub name: String,
+    pub meshes: Vec<CollisionMesh>,
+}
+
+impl MapCollision {
+    pub fn from_bytes(bhd: &[u8], bdt: Vec<u8>) -> Result<Self, MapCollisionError> {
+        let binder = BXF4::from_bytes(bhd, bdt)?;
+        let file_name = |path: &str| {
+            BND4::normalize_path(path)
+                .rsplit('/')
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let compendium = binder
+            .files
+            .iter()
+            .find(|file| file_name(&file.path).contains(".compendium"))
+            .map(|file| {
+                let bytes = decompress(binder.file_bytes(file))?;
+                HkxCompendium::from_bytes(&bytes)
+                    .map_err(|e| MapCollisionError::Hkx(file.path.clone(), e))
+            })
+            .transpose()?;
+
+        let mut cells = Vec::new();
+        for file in &binder.files {
+            let name = file_name(&file.path);
+            let Some(stem) = name
+                .strip_suffix(".hkx.dcx")
+                .or_else(|| name.strip_suffix(".hkx"))
+            else {
+                continue;
+            };
+
+            let bytes = decompress(binder.file_bytes(file))?;
+            let meshes = if HkxTagfile::is_tagfile(&bytes) {
+                HkxTagfile::from_bytes_with_compendium(&bytes, compendium.as_ref())
+                    .and_then(|tagfile| CollisionMesh::from_tagfile(&tagfile))
+            } else {
+                Hkx::from_bytes(&bytes).and_then(|hkx| hkx.collision_meshes())
+            }
+            .map_err(|e| MapCollisionError::Hkx(file.path.clone(), e))?;
+
+            cells.push(CollisionCell {
+                name: stem.to_string(),
+                meshes,
+            });
+        }
+
+        Ok(Self { cells })
+    }
+}
+
+fn decompress(bytes: &[u8]) -> Result<Vec<u8>, MapCollisionError> {
+    let mut cursor = Cursor::new(bytes);
+    if DCX::has_magic(&mut cursor)? {
+        Ok(DCX::from_reader(&mut cursor)?.decompressed)
+    } else {
+        Ok(bytes.to_vec())
+    }
+}
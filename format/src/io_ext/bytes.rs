@@ -0,0 +1,178 @@
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use byteorder::ByteOrder;
+
+use crate::error::FormatError;
+
+/// A cursor over a byte slice for the parsers that don't need `std::io`.
+///
+/// Reads past the end fail with [`FormatError::Truncated`], naming the format and the section
+/// last set with [`ByteReader::set_section`].
+#[derive(Clone, Debug)]
+pub struct ByteReader<'a> {
+    format: &'static str,
+    section: &'static str,
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(format: &'static str, bytes: &'a [u8]) -> Self {
+        Self {
+            format,
+            section: "header",
+            bytes,
+            position: 0,
+        }
+    }
+
+    pub fn bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    pub fn position(&self) -> u64 {
+        self.position as u64
+    }
+
+    /// Move to `position`, which may be past the end, in which case the next read fails.
+    pub fn set_position(&mut self, position: u64) {
+        self.position = usize::try_from(position).unwrap_or(usize::MAX);
+    }
+
+    pub fn section(&self) -> &'static str {
+        self.section
+    }
+
+    /// Name the part of the file being read, for errors.
+    pub fn set_section(&mut self, section: &'static str) {
+        self.section = section;
+    }
+
+    pub fn malformed(&self, offset: u64, reason: impl core::fmt::Display) -> FormatError {
+        FormatError::malformed(self.format, self.section, offset, reason)
+    }
+
+    pub fn read_bytes(&mut self, length: usize) -> Result<&'a [u8], FormatError> {
+        let bytes = self
+            .position
+            .checked_add(length)
+            .and_then(|end| self.bytes.get(self.position..end))
+            .ok_or(FormatError::Truncated {
+                format: self.format,
+                section: self.section,
+                offset: self.position as u64,
+            })?;
+        self.position += length;
+
+        Ok(bytes)
+    }
+
+    pub fn read_array<const N: usize>(&mut self) -> Result<[u8; N], FormatError> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.read_bytes(N)?);
+
+        Ok(array)
+    }
+
+    pub fn read_magic<const N: usize>(&mut self, expected: &[u8; N]) -> Result<(), FormatError> {
+        let offset = self.position();
+        let magic = self.read_array::<N>()?;
+        if &magic == expected {
+            return Ok(());
+        }
+
+        Err(self.malformed(
+            offset,
+            format!(
+                "expected {:?}, found {:?}",
+                String::from_utf8_lossy(expected),
+                String::from_utf8_lossy(&magic)
+            ),
+        ))
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, FormatError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u16<O: ByteOrder>(&mut self) -> Result<u16, FormatError> {
+        Ok(O::read_u16(self.read_bytes(2)?))
+    }
+
+    pub fn read_i16<O: ByteOrder>(&mut self) -> Result<i16, FormatError> {
+        Ok(O::read_i16(self.read_bytes(2)?))
+    }
+
+    pub fn read_u32<O: ByteOrder>(&mut self) -> Result<u32, FormatError> {
+        Ok(O::read_u32(self.read_bytes(4)?))
+    }
+
+    pub fn read_i32<O: ByteOrder>(&mut self) -> Result<i32, FormatError> {
+        Ok(O::read_i32(self.read_bytes(4)?))
+    }
+
+    pub fn read_u64<O: ByteOrder>(&mut self) -> Result<u64, FormatError> {
+        Ok(O::read_u64(self.read_bytes(8)?))
+    }
+
+    /// Read up to a null byte, consuming but not returning it.
+    pub fn read_null_terminated(&mut self) -> Result<&'a [u8], FormatError> {
+        let remaining = self.bytes.get(self.position..).unwrap_or_default();
+        let length =
+            remaining
+                .iter()
+                .position(|byte| *byte == 0)
+                .ok_or(FormatError::Truncated {
+                    format: self.format,
+                    section: self.section,
+                    offset: self.bytes.len() as u64,
+                })?;
+
+        let string = self.read_bytes(length)?;
+        self.position += 1;
+
+        Ok(string)
+    }
+
+    /// Read a null-terminated UTF-16 string.
+    pub fn read_utf16<O: ByteOrder>(&mut self) -> Result<String, FormatError> {
+        let offset = self.position();
+        let mut units = Vec::new();
+        loop {
+            match self.read_u16::<O>()? {
+                0 => break,
+                unit => units.push(unit),
+            }
+        }
+
+        String::from_utf16(&units).map_err(|e| self.malformed(offset, e))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use byteorder::LE;
+
+    use crate::{error::FormatError, io_ext::ByteReader};
+
+    #[test]
+    pub fn reports_reads_past_the_end() {
+        let mut r = ByteReader::new("BND4", b"BND4\x01\0a\0\0\0");
+        r.read_magic(b"BND4").unwrap();
+        assert_eq!(r.read_u16::<LE>().unwrap(), 1);
+        assert_eq!(r.read_utf16::<LE>().unwrap(), "a");
+
+        r.set_section("file header");
+        match r.read_u32::<LE>() {
+            Err(FormatError::Truncated {
+                section, offset, ..
+            }) => {
+                assert_eq!(section, "file header");
+                assert_eq!(offset, 0xA);
+            }
+            result => panic!("unexpected result {result:?}"),
+        }
+    }
+}
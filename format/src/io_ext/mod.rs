@@ -1,5 +1,10 @@
-/// Extensions for Rust standard library IO traits.
+/// Extensions for Rust standard library IO traits, and a slice reader for when there is no `std`.
+mod bytes;
+#[cfg(feature = "std")]
 mod read;
+#[cfg(feature = "flver")]
 pub mod zerocopy;
 
+pub use bytes::*;
+#[cfg(feature = "std")]
 pub use read::*;
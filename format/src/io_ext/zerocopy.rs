@@ -1,4 +1,4 @@
-use std::fmt::{Debug, Formatter};
+use core::fmt::{Debug, Formatter};
 
 use zerocopy::{AsBytes, FromBytes, FromZeroes};
 
@@ -7,7 +7,7 @@ use zerocopy::{AsBytes, FromBytes, FromZeroes};
 pub struct Padding<const N: usize>([u8; N]);
 
 impl<const N: usize> Debug for Padding<N> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         f.debug_struct("Padding").field("length", &N).finish()
     }
 }
@@ -1,9 +1,85 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(not(feature = "std"), feature(error_in_core))]
 #![feature(trait_alias)]
 #![feature(ptr_metadata)]
+
+extern crate alloc;
+
+#[cfg(feature = "archive")]
 pub mod bhd;
+#[cfg(feature = "bnd")]
 pub mod bnd4;
+#[cfg(feature = "map")]
+pub mod btab;
+#[cfg(feature = "map")]
+pub mod btl;
+#[cfg(all(feature = "bnd", feature = "std"))]
+pub mod bxf4;
+#[cfg(feature = "map")]
+pub mod clm2;
+#[cfg(feature = "dcx")]
 pub mod dcx;
+#[cfg(feature = "design")]
+pub mod design;
+pub mod diagnostics;
+#[cfg(feature = "emevd")]
+pub mod emevd;
+#[cfg(feature = "entryfilelist")]
+pub mod entryfilelist;
+pub mod error;
+#[cfg(feature = "esd")]
+pub mod esd;
+#[cfg(feature = "flver")]
 pub mod flver;
+#[cfg(feature = "fmg")]
+pub mod fmg;
+#[cfg(feature = "arbitrary")]
+pub mod fuzz;
+#[cfg(feature = "fxr")]
+pub mod fxr;
+pub mod game;
+#[cfg(feature = "gparam")]
+pub mod gparam;
+#[cfg(feature = "map")]
+pub mod grass;
+#[cfg(feature = "havok")]
+pub mod hkx;
+#[cfg(feature = "havok")]
+pub mod hkxbhd;
 pub mod io_ext;
+#[cfg(feature = "lua")]
+pub mod luabnd;
+#[cfg(feature = "lua")]
+pub mod luagnl;
+#[cfg(feature = "lua")]
+pub mod luainfo;
+#[cfg(feature = "material")]
 pub mod matbin;
+#[cfg(feature = "map")]
+pub mod mcg;
+#[cfg(feature = "map")]
+pub mod mcp;
+#[cfg(feature = "cutscene")]
+pub mod mqb;
+#[cfg(feature = "map")]
+pub mod msb;
+#[cfg(feature = "fmg")]
+pub mod msgbnd;
+#[cfg(feature = "material")]
+pub mod mtd;
+#[cfg(feature = "map")]
+pub mod nva;
+#[cfg(feature = "map")]
+pub mod nvm;
+#[cfg(feature = "param")]
+pub mod param;
+pub mod round_trip;
+#[cfg(feature = "save")]
+pub mod save;
+#[cfg(feature = "sound")]
+pub mod sound;
+#[cfg(feature = "tae")]
+pub mod tae;
+pub mod telemetry;
+#[cfg(feature = "tpf")]
 pub mod tpf;
@@ -0,0 +1,199 @@
+use std::io::{self, Cursor, Read, Seek, Write};
+
+use thiserror::Error;
+
+use crate::{
+    bnd4::BND4,
+    dcx::{DCXError, DCX},
+    luagnl::{LuaGnl, LuaGnlError},
+    luainfo::{LuaGoal, LuaInfo, LuaInfoError},
+};
+
+#[derive(Debug, Error)]
+pub enum LuaBndError {
+    #[error("Could not read luabnd: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Could not decompress luabnd: {0}")]
+    Dcx(#[from] DCXError),
+
+    #[error("Could not parse LUAGNL: {0}")]
+    LuaGnl(#[from] LuaGnlError),
+
+    #[error("Could not parse LUAINFO: {0}")]
+    LuaInfo(#[from] LuaInfoError),
+
+    #[error("No script named {0}")]
+    UnknownScript(String),
+}
+
+/// The AI scripts of a character or map (`.luabnd.dcx`), along with the goals and globals they
+/// register.
+///
+/// Scripts are usually compiled Lua and are exposed as raw bytes by name, e.g. `300000_battle`.
+pub struct LuaBnd {
+    dcx: Option<DCX>,
+    bnd: BND4,
+    gnl: Option<LuaBndFile<LuaGnl>>,
+    info: Option<LuaBndFile<LuaInfo>>,
+}
+
+struct LuaBndFile<T> {
+    file_index: usize,
+    value: T,
+    modified: bool,
+}
+
+impl LuaBnd {
+    /// Read an AI script binder, undoing DCX compression if present.
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, LuaBndError> {
+        let (dcx, bytes) = if DCX::has_magic(r)? {
+            let mut dcx = DCX::from_reader(r)?;
+            let bytes = std::mem::take(&mut dcx.decompressed);
+
+            (Some(dcx), bytes)
+        } else {
+            let mut bytes = Vec::new();
+            r.read_to_end(&mut bytes)?;
+
+            (None, bytes)
+        };
+
+        let bnd = BND4::from_reader(&mut Cursor::new(bytes))?;
+
+        let mut gnl = None;
+        let mut info = None;
+        for (file_index, entry) in bnd.files.iter().enumerate() {
+            let mut bytes = Cursor::new(bnd.file_bytes(entry));
+
+            match extension(&entry.path).as_deref() {
+                Some("luagnl") => {
+                    gnl = Some(LuaBndFile {
+                        file_index,
+                        value: LuaGnl::from_reader(&mut bytes)?,
+                        modified: false,
+                    });
+                }
+                Some("luainfo") => {
+                    info = Some(LuaBndFile {
+                        file_index,
+                        value: LuaInfo::from_reader(&mut bytes)?,
+                        modified: false,
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Self {
+            dcx,
+            bnd,
+            gnl,
+            info,
+        })
+    }
+
+    /// Names of the scripts in the binder, without their extension.
+    pub fn script_names(&self) -> impl Iterator<Item = &str> {
+        self.bnd
+            .files
+            .iter()
+            .filter_map(|entry| script_name(&entry.path))
+    }
+
+    pub fn script(&self, name: &str) -> Option<&[u8]> {
+        self.bnd
+            .files
+            .iter()
+            .find(|entry| script_name(&entry.path) == Some(name))
+            .map(|entry| self.bnd.file_bytes(entry))
+    }
+
+    pub fn set_script(&mut self, name: &str, bytes: &[u8]) -> Result<(), LuaBndError> {
+        let index = self
+            .bnd
+            .files
+            .iter()
+            .position(|entry| script_name(&entry.path) == Some(name))
+            .ok_or_else(|| LuaBndError::UnknownScript(name.to_string()))?;
+
+        Ok(self.bnd.replace_file(index, bytes)?)
+    }
+
+    /// Global variable names registered by the binder.
+    pub fn globals(&self) -> &[String] {
+        self.gnl
+            .as_ref()
+            .map(|gnl| gnl.value.globals.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn globals_mut(&mut self) -> Option<&mut Vec<String>> {
+        let gnl = self.gnl.as_mut()?;
+        gnl.modified = true;
+
+        Some(&mut gnl.value.globals)
+    }
+
+    /// Goals registered by the binder.
+    pub fn goals(&self) -> &[LuaGoal] {
+        self.info
+            .as_ref()
+            .map(|info| info.value.goals.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn goals_mut(&mut self) -> Option<&mut Vec<LuaGoal>> {
+        let info = self.info.as_mut()?;
+        info.modified = true;
+
+        Some(&mut info.value.goals)
+    }
+
+    pub fn goal(&self, id: i32) -> Option<&LuaGoal> {
+        self.goals().iter().find(|goal| goal.id == id)
+    }
+
+    /// Write the binder back out, recompressing it if it was read from a DCX.
+    pub fn write(&mut self, w: &mut impl Write) -> Result<(), LuaBndError> {
+        if let Some(gnl) = self.gnl.as_mut().filter(|gnl| gnl.modified) {
+            self.bnd
+                .replace_file(gnl.file_index, &gnl.value.to_bytes()?)?;
+            gnl.modified = false;
+        }
+
+        if let Some(info) = self.info.as_mut().filter(|info| info.modified) {
+            self.bnd
+                .replace_file(info.file_index, &info.value.to_bytes()?)?;
+            info.modified = false;
+        }
+
+        match &mut self.dcx {
+            Some(dcx) => {
+                dcx.decompressed = self.bnd.data.clone();
+                let result = dcx.write(w);
+                dcx.decompressed = Vec::new();
+
+                Ok(result?)
+            }
+            None => Ok(w.write_all(&self.bnd.data)?),
+        }
+    }
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit(['\\', '/']).next().unwrap_or(path)
+}
+
+fn extension(path: &str) -> Option<String> {
+    let (_, extension) = file_name(path).rsplit_once('.')?;
+
+    Some(extension.to_ascii_lowercase())
+}
+
+fn script_name(path: &str) -> Option<&str> {
+    let file_name = file_name(path);
+    let (name, extension) = file_name.rsplit_once('.')?;
+
+    extension.eq_ignore_ascii_case("lua").then_some(name)
+}
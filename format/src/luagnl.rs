@@ -0,0 +1,124 @@
+use std::io::{self, Read, Write};
+
+use byteorder::{ByteOrder, LE};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum LuaGnlError {
+    #[error("Could not read LUAGNL: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("LUAGNL name offset {0:#x} is out of bounds")]
+    OutOfBounds(u64),
+}
+
+/// The global variable names of an AI script binder (`.luagnl`), which the game registers before
+/// loading the binder's scripts.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LuaGnl {
+    /// Set from Dark Souls 3 onwards, name offsets are 64 bits wide.
+    pub long_format: bool,
+    pub globals: Vec<String>,
+}
+
+impl LuaGnl {
+    pub fn from_reader(r: &mut impl Read) -> Result<Self, LuaGnlError> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+
+        // Names follow the offset table, so the upper half of a 64-bit offset is always zero. A
+        // single 32-bit offset is also followed by zeros, but then names start right after them.
+        let first_offset = bytes.get(..4).map(LE::read_u32).unwrap_or_default();
+        let long_format = first_offset > 8
+            && bytes
+                .get(4..8)
+                .is_some_and(|upper| LE::read_u32(upper) == 0);
+        let offset_size = if long_format { 8 } else { 4 };
+
+        let mut globals = Vec::new();
+        for entry in bytes.chunks_exact(offset_size) {
+            let offset = if long_format {
+                LE::read_u64(entry)
+            } else {
+                LE::read_u32(entry) as u64
+            };
+            if offset == 0 {
+                break;
+            }
+
+            globals.push(shift_jis_at(&bytes, offset).ok_or(LuaGnlError::OutOfBounds(offset))?);
+        }
+
+        Ok(Self {
+            long_format,
+            globals,
+        })
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> Result<(), LuaGnlError> {
+        let offset_size = if self.long_format { 8 } else { 4 };
+        let table_size = (self.globals.len() + 1) * offset_size;
+
+        let mut table = Vec::with_capacity(table_size);
+        let mut names = Vec::new();
+        for global in &self.globals {
+            let offset = (table_size + names.len()) as u64;
+            if self.long_format {
+                table.extend(offset.to_le_bytes());
+            } else {
+                table.extend((offset as u32).to_le_bytes());
+            }
+
+            let (name, _, _) = encoding_rs::SHIFT_JIS.encode(global);
+            names.extend(name.iter());
+            names.push(0);
+        }
+        table.resize(table_size, 0);
+        names.resize(names.len().next_multiple_of(0x10), 0);
+
+        w.write_all(&table)?;
+        w.write_all(&names)?;
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, LuaGnlError> {
+        let mut out = Vec::new();
+        self.write(&mut out)?;
+
+        Ok(out)
+    }
+}
+
+/// Read a null-terminated Shift-JIS string at an offset into a file.
+pub(crate) fn shift_jis_at(bytes: &[u8], offset: u64) -> Option<String> {
+    let bytes = bytes.get(usize::try_from(offset).ok()?..)?;
+    let length = bytes.iter().position(|byte| *byte == 0)?;
+    let (string, _, _) = encoding_rs::SHIFT_JIS.decode(&bytes[..length]);
+
+    Some(string.into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::luagnl::LuaGnl;
+
+    #[test]
+    pub fn round_trips_both_formats() {
+        for (long_format, count) in [(false, 1), (false, 2), (true, 1), (true, 2)] {
+            let gnl = LuaGnl {
+                long_format,
+                globals: ["AI_Goal_Attack", "g_LogicTable"][..count]
+                    .iter()
+                    .map(|global| global.to_string())
+                    .collect(),
+            };
+
+            let read = LuaGnl::from_reader(&mut Cursor::new(gnl.to_bytes().unwrap())).unwrap();
+            assert_eq!(read, gnl);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::io::{self, Read, Write};
+
+use byteorder::{ByteOrder, LE};
+use thiserror::Error;
+
+use crate::luagnl::shift_jis_at;
+
+#[derive(Debug, Error)]
+pub enum LuaInfoError {
+    #[error("Could not read LUAINFO: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not a LUAINFO file")]
+    InvalidMagic,
+
+    #[error("Unknown LUAINFO version {0}")]
+    UnknownVersion(u32),
+
+    #[error("Goal {id} has a name offset {offset:#x} that is out of bounds")]
+    OutOfBounds { id: i32, offset: u64 },
+}
+
+const LUAINFO_MAGIC: &[u8; 4] = b"LUAI";
+const LUAINFO_VERSION: u32 = 1;
+const HEADER_SIZE: usize = 0x10;
+
+const GOAL_SIZE_SHORT: usize = 0x10;
+const GOAL_SIZE_LONG: usize = 0x18;
+
+/// The goals registered by an AI script binder (`.luainfo`).
+///
+/// Each goal names the Lua table implementing it, and whether that table also handles battle and
+/// logic interrupts.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LuaInfo {
+    /// Set from Dark Souls 3 onwards, name offsets are 64 bits wide.
+    pub long_format: bool,
+    pub goals: Vec<LuaGoal>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LuaGoal {
+    pub id: i32,
+
+    /// Name of the goal's table, e.g. `Attack`.
+    pub name: String,
+    pub battle_interrupt: bool,
+    pub logic_interrupt: bool,
+
+    /// Name of the logic that handles interrupts for this goal, if it isn't the goal itself.
+    pub logic_interrupt_name: Option<String>,
+}
+
+impl LuaInfo {
+    pub fn from_reader(r: &mut impl Read) -> Result<Self, LuaInfoError> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+
+        let header = bytes.get(..HEADER_SIZE).ok_or(LuaInfoError::InvalidMagic)?;
+        if &header[..4] != LUAINFO_MAGIC {
+            return Err(LuaInfoError::InvalidMagic);
+        }
+
+        let version = LE::read_u32(&header[4..]);
+        if version != LUAINFO_VERSION {
+            return Err(LuaInfoError::UnknownVersion(version));
+        }
+
+        let goal_count = LE::read_u32(&header[8..]) as usize;
+
+        // The short format has a name offset past the goal table where the long format has its
+        // interrupt flags, which are never that large.
+        let long_format = bytes
+            .get(HEADER_SIZE + 4..HEADER_SIZE + 8)
+            .is_some_and(|value| {
+                (LE::read_u32(value) as usize) < HEADER_SIZE + goal_count * GOAL_SIZE_SHORT
+            });
+        let goal_size = if long_format {
+            GOAL_SIZE_LONG
+        } else {
+            GOAL_SIZE_SHORT
+        };
+
+        let table = bytes
+            .get(HEADER_SIZE..HEADER_SIZE + goal_count * goal_size)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+
+        let goals = table
+            .chunks_exact(goal_size)
+            .map(|goal| {
+                let id = LE::read_i32(goal);
+                let (name_offset, interrupt_name_offset, flags) = if long_format {
+                    (
+                        LE::read_u64(&goal[8..]),
+                        LE::read_u64(&goal[0x10..]),
+                        &goal[4..],
+                    )
+                } else {
+                    (
+                        LE::read_u32(&goal[4..]) as u64,
+                        LE::read_u32(&goal[8..]) as u64,
+                        &goal[0xC..],
+                    )
+                };
+
+                let string = |offset: u64| {
+                    shift_jis_at(&bytes, offset).ok_or(LuaInfoError::OutOfBounds { id, offset })
+                };
+
+                Ok(LuaGoal {
+                    id,
+                    name: string(name_offset)?,
+                    battle_interrupt: flags[0] != 0,
+                    logic_interrupt: flags[1] != 0,
+                    logic_interrupt_name: match interrupt_name_offset {
+                        0 => None,
+                        offset => Some(string(offset)?),
+                    },
+                })
+            })
+            .collect::<Result<Vec<_>, LuaInfoError>>()?;
+
+        Ok(Self { long_format, goals })
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> Result<(), LuaInfoError> {
+        let goal_size = if self.long_format {
+            GOAL_SIZE_LONG
+        } else {
+            GOAL_SIZE_SHORT
+        };
+        let strings_offset = HEADER_SIZE + self.goals.len() * goal_size;
+
+        let mut strings = Vec::new();
+        let mut push_string = |value: &str| {
+            let offset = (strings_offset + strings.len()) as u64;
+            let (bytes, _, _) = encoding_rs::SHIFT_JIS.encode(value);
+            strings.extend(bytes.iter());
+            strings.push(0);
+            offset
+        };
+
+        let mut out = Vec::with_capacity(strings_offset);
+        out.extend(LUAINFO_MAGIC);
+        out.extend(LUAINFO_VERSION.to_le_bytes());
+        out.extend((self.goals.len() as u32).to_le_bytes());
+        out.extend(0u32.to_le_bytes());
+
+        for goal in &self.goals {
+            let name_offset = push_string(&goal.name);
+            let interrupt_name_offset = goal
+                .logic_interrupt_name
+                .as_deref()
+                .map(&mut push_string)
+                .unwrap_or_default();
+            let flags = [
+                goal.battle_interrupt as u8,
+                goal.logic_interrupt as u8,
+                0,
+                0,
+            ];
+
+            out.extend(goal.id.to_le_bytes());
+            if self.long_format {
+                out.extend(flags);
+                out.extend(name_offset.to_le_bytes());
+                out.extend(interrupt_name_offset.to_le_bytes());
+            } else {
+                out.extend((name_offset as u32).to_le_bytes());
+                out.extend((interrupt_name_offset as u32).to_le_bytes());
+                out.extend(flags);
+            }
+        }
+
+        strings.resize(strings.len().next_multiple_of(0x10), 0);
+        out.extend(strings);
+
+        Ok(w.write_all(&out)?)
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, LuaInfoError> {
+        let mut out = Vec::new();
+        self.write(&mut out)?;
+
+        Ok(out)
+    }
+
+    pub fn goal(&self, id: i32) -> Option<&LuaGoal> {
+        self.goals.iter().find(|goal| goal.id == id)
+    }
+
+    pub fn goal_mut(&mut self, id: i32) -> Option<&mut LuaGoal> {
+        self.goals.iter_mut().find(|goal| goal.id == id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::luainfo::{LuaGoal, LuaInfo};
+
+    #[test]
+    pub fn round_trips_both_formats() {
+        for long_format in [false, true] {
+            let info = LuaInfo {
+                long_format,
+                goals: vec![
+                    LuaGoal {
+                        id: 2000,
+                        name: "Attack".to_string(),
+                        battle_interrupt: true,
+                        logic_interrupt: false,
+                        logic_interrupt_name: None,
+                    },
+                    LuaGoal {
+                        id: 200000,
+                        name: "Common".to_string(),
+                        battle_interrupt: false,
+                        logic_interrupt: true,
+                        logic_interrupt_name: Some("CommonInterrupt".to_string()),
+                    },
+                ],
+            };
+
+            let read = LuaInfo::from_reader(&mut Cursor::new(info.to_bytes().unwrap())).unwrap();
+            assert_eq!(read, info);
+            assert!(read.goal(200000).unwrap().logic_interrupt);
+        }
+    }
+}
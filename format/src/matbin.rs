@@ -1,15 +1,28 @@
-use std::io::{self, SeekFrom};
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
 
 use byteorder::{ReadBytesExt, LE};
+use thiserror::Error;
 
-use crate::io_ext::ReadFormatsExt;
+use crate::{bnd4::BND4, io_ext::ReadFormatsExt};
 
-#[derive(Debug)]
+#[derive(Debug, Error)]
 pub enum MatbinError {
-    IO(io::Error),
+    #[error("Could not read MATBIN: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unknown MATBIN version {0}")]
+    UnknownVersion(u32),
+
+    #[error("Param {name} has an unknown value type {value_type:#x}")]
+    UnknownValueType { name: String, value_type: u32 },
 }
 
+const MATBIN_VERSION: u32 = 2;
+
+/// An Elden Ring material (`.matbin`), found in `matbinbnd`s and referenced by the `.matxml` path
+/// of a FLVER material.
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Matbin {
     pub unk04: u32,
     pub shader_path: String,
@@ -22,27 +35,23 @@ pub struct Matbin {
 }
 
 impl Matbin {
-    pub fn from_reader(r: &mut (impl io::Read + io::Seek)) -> Result<Self, io::Error> {
-        let _magic = r.read_u32::<LE>()?;
-        // assert!(magic == 0x42414d, "Matbin was not of expected format");
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, MatbinError> {
+        r.read_magic(b"MAB\0")?;
 
         let unk04 = r.read_u32::<LE>()?;
+        if unk04 != MATBIN_VERSION {
+            return Err(MatbinError::UnknownVersion(unk04));
+        }
+
         let shader_path_offset = r.read_u64::<LE>()?;
         let source_path_offset = r.read_u64::<LE>()?;
         let key = r.read_u32::<LE>()?;
         let param_count = r.read_u32::<LE>()?;
         let sampler_count = r.read_u32::<LE>()?;
+        r.read_padding(0x14)?;
 
-        let current_pos = r.stream_position()?;
-        r.seek(SeekFrom::Start(shader_path_offset))?;
-        let shader_path = r.read_utf16::<LE>()?;
-        r.seek(SeekFrom::Start(source_path_offset))?;
-        let source_path = r.read_utf16::<LE>()?;
-        r.seek(SeekFrom::Start(current_pos))?;
-
-        assert!(r.read_u64::<LE>()? == 0x0);
-        assert!(r.read_u64::<LE>()? == 0x0);
-        assert!(r.read_u32::<LE>()? == 0x0);
+        let shader_path = utf16_at(r, shader_path_offset)?;
+        let source_path = utf16_at(r, source_path_offset)?;
 
         let mut params = vec![];
         for _ in 0..param_count {
@@ -65,36 +74,96 @@ impl Matbin {
             samplers,
         })
     }
+
+    /// Find the MATBIN for a FLVER material in a `matbinbnd`, by the file name of the material's
+    /// path.
+    pub fn from_bnd(bnd: &BND4, material_path: &str) -> Option<Result<Self, MatbinError>> {
+        let entry = bnd.file_descriptor_by_stem(material_path)?;
+
+        Some(Self::from_reader(&mut Cursor::new(bnd.file_bytes(entry))))
+    }
+
+    pub fn param(&self, name: &str) -> Option<&MatbinValue> {
+        self.params
+            .iter()
+            .find(|param| param.name == name)
+            .map(|param| &param.value)
+    }
+
+    /// Find the sampler bound to a slot, e.g. `C_DetailBlend__snp_Texture2D_0_AlbedoMap_0`.
+    pub fn sampler(&self, sampler_type: &str) -> Option<&MatbinSampler> {
+        self.samplers
+            .iter()
+            .find(|sampler| sampler.sampler_type == sampler_type)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MatbinValue {
+    Bool(bool),
+    Int(i32),
+    Int2([i32; 2]),
+    Float(f32),
+    Float2([f32; 2]),
+    Float3([f32; 3]),
+    Float4([f32; 4]),
+    Float5([f32; 5]),
+}
+
+impl MatbinValue {
+    fn from_reader(r: &mut impl Read, value_type: u32) -> io::Result<Option<Self>> {
+        fn floats<const N: usize>(r: &mut impl Read) -> io::Result<[f32; N]> {
+            let mut values = [0.0; N];
+            r.read_f32_into::<LE>(&mut values)?;
+            Ok(values)
+        }
+
+        Ok(Some(match value_type {
+            0x0 => Self::Bool(r.read_bool()?),
+            0x4 => Self::Int(r.read_i32::<LE>()?),
+            0x5 => Self::Int2([r.read_i32::<LE>()?, r.read_i32::<LE>()?]),
+            0x8 => Self::Float(r.read_f32::<LE>()?),
+            0x9 => Self::Float2(floats(r)?),
+            0xA => Self::Float3(floats(r)?),
+            0xB => Self::Float4(floats(r)?),
+            0xC => Self::Float5(floats(r)?),
+            _ => return Ok(None),
+        }))
+    }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MatbinParam {
     pub name: String,
-    pub value: u32,
+    pub value: MatbinValue,
     pub key: u32,
     pub value_type: u32,
 }
 
 impl MatbinParam {
-    pub fn from_reader(r: &mut (impl io::Read + io::Seek)) -> Result<Self, io::Error> {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, MatbinError> {
         let name_offset = r.read_u64::<LE>()?;
-
-        // TODO: read values
-        let _value_offset = r.read_u64::<LE>()?;
+        let value_offset = r.read_u64::<LE>()?;
         let key = r.read_u32::<LE>()?;
         let value_type = r.read_u32::<LE>()?;
+        r.read_padding(0x10)?;
 
-        assert!(r.read_u64::<LE>()? == 0x0);
-        assert!(r.read_u64::<LE>()? == 0x0);
+        let name = utf16_at(r, name_offset)?;
 
         let current_pos = r.stream_position()?;
-        r.seek(SeekFrom::Start(name_offset))?;
-        let name = r.read_utf16::<LE>()?;
+        r.seek(SeekFrom::Start(value_offset))?;
+        let value = MatbinValue::from_reader(r, value_type)?;
         r.seek(SeekFrom::Start(current_pos))?;
 
+        let Some(value) = value else {
+            return Err(MatbinError::UnknownValueType { name, value_type });
+        };
+
         Ok(Self {
             name,
-            value: 0x0,
+            value,
             key,
             value_type,
         })
@@ -102,6 +171,7 @@ impl MatbinParam {
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MatbinSampler {
     pub sampler_type: String,
     pub path: String,
@@ -111,31 +181,90 @@ pub struct MatbinSampler {
 }
 
 impl MatbinSampler {
-    pub fn from_reader(r: &mut (impl io::Read + io::Seek)) -> Result<Self, io::Error> {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, MatbinError> {
         let type_offset = r.read_u64::<LE>()?;
         let path_offset = r.read_u64::<LE>()?;
         let key = r.read_u32::<LE>()?;
 
         let unkx = r.read_f32::<LE>()?;
         let unky = r.read_f32::<LE>()?;
-
-        assert!(r.read_u64::<LE>()? == 0x0);
-        assert!(r.read_u64::<LE>()? == 0x0);
-        assert!(r.read_u32::<LE>()? == 0x0);
-
-        let current_pos = r.stream_position()?;
-        r.seek(SeekFrom::Start(type_offset))?;
-        let sampler_type = r.read_utf16::<LE>()?;
-        r.seek(SeekFrom::Start(path_offset))?;
-        let path = r.read_utf16::<LE>()?;
-        r.seek(SeekFrom::Start(current_pos))?;
+        r.read_padding(0x14)?;
 
         Ok(Self {
-            sampler_type,
-            path,
+            sampler_type: utf16_at(r, type_offset)?,
+            path: utf16_at(r, path_offset)?,
             key,
             unkx,
             unky,
         })
     }
 }
+
+fn utf16_at(r: &mut (impl Read + Seek), offset: u64) -> io::Result<String> {
+    let current_pos = r.stream_position()?;
+    r.seek(SeekFrom::Start(offset))?;
+    let value = r.read_utf16::<LE>()?;
+    r.seek(SeekFrom::Start(current_pos))?;
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::matbin::{Matbin, MatbinValue};
+
+    #[test]
+    pub fn reads_params_and_samplers() {
+        let utf16 = |value: &str| {
+            value
+                .encode_utf16()
+                .chain([0])
+                .flat_map(u16::to_le_bytes)
+                .collect::<Vec<_>>()
+        };
+
+        // Header 0x38, one param 0x28, one sampler 0x30, then the data.
+        let data_offset = 0x38 + 0x28 + 0x30u64;
+        let mut data = Vec::new();
+        let mut push = |bytes: Vec<u8>| {
+            let offset = data_offset + data.len() as u64;
+            data.extend(bytes);
+            offset
+        };
+
+        let shader = push(utf16("Shader.fx"));
+        let source = push(utf16("AEG020_000.matxml"));
+        let param_name = push(utf16("Roughness"));
+        let param_value = push(0.5f32.to_le_bytes().to_vec());
+        let sampler_type = push(utf16("AlbedoMap_0"));
+        let sampler_path = push(utf16("AEG020_000_a"));
+
+        let mut bytes = b"MAB\0".to_vec();
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend(shader.to_le_bytes());
+        bytes.extend(source.to_le_bytes());
+        for value in [0u32, 1, 1] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.resize(0x38, 0);
+
+        bytes.extend(param_name.to_le_bytes());
+        bytes.extend(param_value.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(8u32.to_le_bytes());
+        bytes.resize(0x38 + 0x28, 0);
+
+        bytes.extend(sampler_type.to_le_bytes());
+        bytes.extend(sampler_path.to_le_bytes());
+        bytes.resize(data_offset as usize, 0);
+        bytes.extend(data);
+
+        let matbin = Matbin::from_reader(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(matbin.shader_path, "Shader.fx");
+        assert_eq!(matbin.param("Roughness"), Some(&MatbinValue::Float(0.5)));
+        assert_eq!(matbin.sampler("AlbedoMap_0").unwrap().path, "AEG020_000_a");
+    }
+}
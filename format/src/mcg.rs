@@ -0,0 +1,213 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use byteorder::{ByteOrder, ReadBytesExt, BE, LE};
+use thiserror::Error;
+
+use crate::nvm::{Nvm, NvmTriangle};
+
+#[derive(Debug, Error)]
+pub enum McgError {
+    #[error("Could not read MCG: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unknown MCG version {0:#x}")]
+    UnknownVersion(u32),
+}
+
+const MCG_VERSION: u32 = 1;
+
+/// The navigation graph of a Dark Souls map (`.mcg`).
+///
+/// Nodes sit on the gates between navmeshes, and each edge is a path across a single navmesh (an
+/// MCP room) between two gates.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Mcg {
+    pub big_endian: bool,
+    pub unk04: i32,
+    pub nodes: Vec<McgNode>,
+    pub edges: Vec<McgEdge>,
+    pub unk18: i32,
+    pub unk1c: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct McgNode {
+    pub position: [f32; 3],
+
+    /// Indices of the nodes connected to this one, paired with [McgNode::connected_edges].
+    pub connected_nodes: Vec<i32>,
+    pub connected_edges: Vec<i32>,
+    pub unk18: i32,
+    pub unk1c: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct McgEdge {
+    pub node_a: i32,
+
+    /// Gate triangles at node A, as indices into the NVM of the edge's room.
+    pub triangles_a: Vec<i32>,
+    pub node_b: i32,
+
+    /// Gate triangles at node B, as indices into the NVM of the edge's room.
+    pub triangles_b: Vec<i32>,
+
+    /// The room in the map's MCP, and so the navmesh, this edge crosses.
+    pub mcp_room_index: i32,
+
+    /// The map containing the room, packed as one byte per part of `mAA_BB_CC_DD`.
+    pub map_id: [u8; 4],
+    pub weight: f32,
+}
+
+impl Mcg {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, McgError> {
+        let version = r.read_u32::<LE>()?;
+        r.seek(SeekFrom::Start(0))?;
+
+        if version == MCG_VERSION {
+            Self::read::<_, LE>(r, false)
+        } else if version == MCG_VERSION.swap_bytes() {
+            Self::read::<_, BE>(r, true)
+        } else {
+            Err(McgError::UnknownVersion(version))
+        }
+    }
+
+    fn read<R: Read + Seek, O: ByteOrder>(r: &mut R, big_endian: bool) -> Result<Self, McgError> {
+        let _version = r.read_u32::<O>()?;
+        let unk04 = r.read_i32::<O>()?;
+        let node_count = r.read_u32::<O>()?;
+        let node_offset = r.read_u32::<O>()?;
+        let edge_count = r.read_u32::<O>()?;
+        let edge_offset = r.read_u32::<O>()?;
+        let unk18 = r.read_i32::<O>()?;
+        let unk1c = r.read_i32::<O>()?;
+
+        r.seek(SeekFrom::Start(node_offset as u64))?;
+        let mut node_headers = Vec::with_capacity(node_count as usize);
+        for _ in 0..node_count {
+            let position = [r.read_f32::<O>()?, r.read_f32::<O>()?, r.read_f32::<O>()?];
+            let connection_count = r.read_u32::<O>()?;
+            let nodes_offset = r.read_u32::<O>()?;
+            let edges_offset = r.read_u32::<O>()?;
+            let unk18 = r.read_i32::<O>()?;
+            let unk1c = r.read_i32::<O>()?;
+
+            node_headers.push((
+                position,
+                connection_count,
+                nodes_offset,
+                edges_offset,
+                unk18,
+                unk1c,
+            ));
+        }
+
+        r.seek(SeekFrom::Start(edge_offset as u64))?;
+        let mut edge_headers = Vec::with_capacity(edge_count as usize);
+        for _ in 0..edge_count {
+            let node_a = r.read_i32::<O>()?;
+            let triangles_a = (r.read_u32::<O>()?, r.read_u32::<O>()?);
+            let node_b = r.read_i32::<O>()?;
+            let triangles_b = (r.read_u32::<O>()?, r.read_u32::<O>()?);
+            let mcp_room_index = r.read_i32::<O>()?;
+
+            let mut map_id = [0u8; 4];
+            r.read_exact(&mut map_id)?;
+            if !big_endian {
+                map_id.reverse();
+            }
+
+            let weight = r.read_f32::<O>()?;
+
+            edge_headers.push((
+                node_a,
+                triangles_a,
+                node_b,
+                triangles_b,
+                mcp_room_index,
+                map_id,
+                weight,
+            ));
+        }
+
+        let mut nodes = Vec::with_capacity(node_headers.len());
+        for (position, count, nodes_offset, edges_offset, unk18, unk1c) in node_headers {
+            nodes.push(McgNode {
+                position,
+                connected_nodes: read_indices::<_, O>(r, nodes_offset, count)?,
+                connected_edges: read_indices::<_, O>(r, edges_offset, count)?,
+                unk18,
+                unk1c,
+            });
+        }
+
+        let mut edges = Vec::with_capacity(edge_headers.len());
+        for (node_a, (a_count, a_offset), node_b, (b_count, b_offset), room, map_id, weight) in
+            edge_headers
+        {
+            edges.push(McgEdge {
+                node_a,
+                triangles_a: read_indices::<_, O>(r, a_offset, a_count)?,
+                node_b,
+                triangles_b: read_indices::<_, O>(r, b_offset, b_count)?,
+                mcp_room_index: room,
+                map_id,
+                weight,
+            });
+        }
+
+        Ok(Self {
+            big_endian,
+            unk04,
+            nodes,
+            edges,
+            unk18,
+            unk1c,
+        })
+    }
+
+    /// Edges connected to a node, along with the node at their other end.
+    pub fn node_edges(&self, node: usize) -> impl Iterator<Item = (&McgEdge, i32)> {
+        self.edges.iter().filter_map(move |edge| {
+            if edge.node_a == node as i32 {
+                Some((edge, edge.node_b))
+            } else if edge.node_b == node as i32 {
+                Some((edge, edge.node_a))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+impl McgEdge {
+    /// The gate triangles at both ends of the edge, looked up in the NVM of the edge's room.
+    pub fn nvm_triangles<'a>(&'a self, nvm: &'a Nvm) -> impl Iterator<Item = &'a NvmTriangle> {
+        self.triangles_a
+            .iter()
+            .chain(&self.triangles_b)
+            .filter_map(|index| nvm.triangles.get(usize::try_from(*index).ok()?))
+    }
+}
+
+pub(crate) fn read_indices<R: Read + Seek, O: ByteOrder>(
+    r: &mut R,
+    offset: u32,
+    count: u32,
+) -> io::Result<Vec<i32>> {
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+
+    r.seek(SeekFrom::Start(offset as u64))?;
+
+    let mut indices = vec![0; count as usize];
+    r.read_i32_into::<O>(&mut indices)?;
+
+    Ok(indices)
+}
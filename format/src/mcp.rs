@@ -0,0 +1,146 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use byteorder::{ByteOrder, ReadBytesExt, BE, LE};
+use thiserror::Error;
+
+use crate::mcg::read_indices;
+
+#[derive(Debug, Error)]
+pub enum McpError {
+    #[error("Could not read MCP: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unknown MCP version {0:#x}")]
+    UnknownVersion(u32),
+}
+
+const MCP_VERSION: u32 = 2;
+
+/// The rooms of a Dark Souls map's navigation graph (`.mcp`). Each room is the bounds of one
+/// navmesh, and is linked to the rooms it shares gates with.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Mcp {
+    pub big_endian: bool,
+    pub unk04: i32,
+    pub rooms: Vec<McpRoom>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct McpRoom {
+    /// The map containing the room, packed as one byte per part of `mAA_BB_CC_DD`.
+    pub map_id: [u8; 4],
+
+    /// Index of the room's navmesh among the map's navmesh parts.
+    pub local_index: i32,
+    pub bounds_min: [f32; 3],
+    pub bounds_max: [f32; 3],
+    pub connected_rooms: Vec<i32>,
+}
+
+impl Mcp {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, McpError> {
+        let version = r.read_u32::<LE>()?;
+        r.seek(SeekFrom::Start(0))?;
+
+        if version == MCP_VERSION {
+            Self::read::<_, LE>(r, false)
+        } else if version == MCP_VERSION.swap_bytes() {
+            Self::read::<_, BE>(r, true)
+        } else {
+            Err(McpError::UnknownVersion(version))
+        }
+    }
+
+    fn read<R: Read + Seek, O: ByteOrder>(r: &mut R, big_endian: bool) -> Result<Self, McpError> {
+        let _version = r.read_u32::<O>()?;
+        let unk04 = r.read_i32::<O>()?;
+        let room_count = r.read_u32::<O>()?;
+        let room_offset = r.read_u32::<O>()?;
+
+        r.seek(SeekFrom::Start(room_offset as u64))?;
+        let mut headers = Vec::with_capacity(room_count as usize);
+        for _ in 0..room_count {
+            let mut map_id = [0u8; 4];
+            r.read_exact(&mut map_id)?;
+            if !big_endian {
+                map_id.reverse();
+            }
+
+            let local_index = r.read_i32::<O>()?;
+            let connection_count = r.read_u32::<O>()?;
+            let bounds_min = [r.read_f32::<O>()?, r.read_f32::<O>()?, r.read_f32::<O>()?];
+            let bounds_max = [r.read_f32::<O>()?, r.read_f32::<O>()?, r.read_f32::<O>()?];
+            let connections_offset = r.read_u32::<O>()?;
+
+            headers.push((
+                map_id,
+                local_index,
+                bounds_min,
+                bounds_max,
+                connections_offset,
+                connection_count,
+            ));
+        }
+
+        let mut rooms = Vec::with_capacity(headers.len());
+        for (map_id, local_index, bounds_min, bounds_max, offset, count) in headers {
+            rooms.push(McpRoom {
+                map_id,
+                local_index,
+                bounds_min,
+                bounds_max,
+                connected_rooms: read_indices::<_, O>(r, offset, count)?,
+            });
+        }
+
+        Ok(Self {
+            big_endian,
+            unk04,
+            rooms,
+        })
+    }
+
+    /// The room whose bounds contain `point`, if any.
+    pub fn room_at(&self, point: [f32; 3]) -> Option<usize> {
+        self.rooms.iter().position(|room| {
+            (0..3).all(|axis| {
+                room.bounds_min[axis] <= point[axis] && point[axis] <= room.bounds_max[axis]
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::mcp::Mcp;
+
+    #[test]
+    pub fn reads_rooms() {
+        let mut bytes = Vec::new();
+        for value in [2u32, 0, 1, 0x10] {
+            bytes.extend(value.to_le_bytes());
+        }
+
+        bytes.extend([0, 0, 0, 10]);
+        for value in [3u32, 1] {
+            bytes.extend(value.to_le_bytes());
+        }
+        for value in [0.0f32, 0.0, 0.0, 1.0, 1.0, 1.0] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.extend(0x38u32.to_le_bytes());
+        bytes.extend(5i32.to_le_bytes());
+
+        let mcp = Mcp::from_reader(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(mcp.rooms[0].map_id, [10, 0, 0, 0]);
+        assert_eq!(mcp.rooms[0].local_index, 3);
+        assert_eq!(mcp.rooms[0].connected_rooms, vec![5]);
+        assert_eq!(mcp.room_at([0.5, 0.5, 0.5]), Some(0));
+        assert_eq!(mcp.room_at([2.0, 0.5, 0.5]), None);
+    }
+}
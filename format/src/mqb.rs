@@ -0,0 +1,475 @@
+use std::io::{self, Cursor, Read};
+
+use byteorder::{ReadBytesExt, LE};
+use thiserror::Error;
+
+use crate::io_ext::ReadFormatsExt;
+
+#[derive(Debug, Error)]
+pub enum MqbError {
+    #[error("Could not read MQB: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not an MQB file")]
+    InvalidMagic,
+
+    #[error("Big endian MQBs aren't supported")]
+    BigEndian,
+
+    #[error("Unknown MQB version {0:#x}")]
+    UnknownVersion(u32),
+
+    #[error("Unknown MQB parameter type {0}")]
+    UnknownParameterType(u32),
+
+    #[error("MQB {0} count of {1} is larger than the file")]
+    BadCount(&'static str, usize),
+}
+
+const MQB_MAGIC: &[u8; 4] = b"MQB ";
+const NAME_SIZE: usize = 0x40;
+
+/// Size of a sequence point, the smallest record, used to reject counts that can't fit in the
+/// file.
+const MIN_RECORD_SIZE: usize = 0x8;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MqbVersion {
+    DarkSouls2Scholar,
+    Bloodborne,
+
+    /// Dark Souls 3, Sekiro and Elden Ring.
+    DarkSouls3,
+}
+
+impl MqbVersion {
+    fn from_u32(value: u32) -> Option<Self> {
+        match value {
+            0x94 => Some(Self::DarkSouls2Scholar),
+            0xCA => Some(Self::Bloodborne),
+            0xCB => Some(Self::DarkSouls3),
+            _ => None,
+        }
+    }
+}
+
+/// A cutscene (`.mqb`), usually found in a `cutscenebnd`.
+///
+/// Resources are the actors taking part in the cutscene, e.g. characters, map pieces and
+/// cameras. Each cut is a shot made up of timelines, whose dispositions place resources in the
+/// scene for a range of frames and animate them with transforms and parameters.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Mqb {
+    pub version: MqbVersion,
+
+    /// Set from Dark Souls 3 onwards, offsets are 64 bits wide.
+    pub long_format: bool,
+    pub name: String,
+    pub framerate: f32,
+    pub resources: Vec<MqbResource>,
+    pub cuts: Vec<MqbCut>,
+    pub resource_directory: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MqbResource {
+    pub name: String,
+
+    /// Index of the resource this one is attached to, or -1.
+    pub parent_index: i32,
+    pub parameters: Vec<MqbParameter>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MqbCut {
+    pub name: String,
+    pub unk44: i32,
+
+    /// Length of the cut in frames.
+    pub duration: i32,
+    pub timelines: Vec<MqbTimeline>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MqbTimeline {
+    pub unk00: i32,
+    pub dispositions: Vec<MqbDisposition>,
+    pub parameters: Vec<MqbParameter>,
+}
+
+/// A resource's presence in a timeline.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MqbDisposition {
+    pub id: i32,
+
+    /// Index into [`Mqb::resources`].
+    pub resource_index: i32,
+    pub unk08: i32,
+    pub start_frame: i32,
+    pub duration: i32,
+    pub unk14: i32,
+    pub unk18: i32,
+    pub unk1c: i32,
+    pub transforms: Vec<MqbTransform>,
+    pub parameters: Vec<MqbParameter>,
+}
+
+/// A keyframe of a disposition's placement, relative to the start of the cut.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MqbTransform {
+    pub frame: f32,
+    pub translation: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+/// A named value attached to a resource, timeline or disposition, e.g. the animation a character
+/// plays or a camera's field of view.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MqbParameter {
+    pub name: String,
+    pub unk44: i32,
+    pub value: MqbValue,
+
+    /// Keyframed values that override [`Self::value`] over the course of the cut.
+    pub sequences: Vec<MqbSequence>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MqbValue {
+    Bool(bool),
+    SByte(i8),
+    Byte(u8),
+    Short(i16),
+    Int(i32),
+    UInt(u32),
+    Float(f32),
+    String(String),
+    Color([u8; 4]),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MqbSequence {
+    /// Which component of the parameter's value is keyframed, e.g. a color channel.
+    pub value_index: i32,
+    pub points: Vec<MqbPoint>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MqbPoint {
+    pub frame: i32,
+    pub value: f32,
+}
+
+impl Mqb {
+    pub fn from_reader(r: &mut impl Read) -> Result<Self, MqbError> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+
+        let mut r = MqbReader {
+            length: bytes.len(),
+            cursor: Cursor::new(&bytes[..]),
+        };
+        r.cursor
+            .read_magic(MQB_MAGIC)
+            .map_err(|_| MqbError::InvalidMagic)?;
+
+        let big_endian = r.cursor.read_u8()? != 0;
+        if big_endian {
+            return Err(MqbError::BigEndian);
+        }
+        r.cursor.read_u8()?;
+        let long_format = r.cursor.read_u8()? != 0;
+        r.cursor.read_u8()?;
+
+        let version = r.cursor.read_u32::<LE>()?;
+        let version = MqbVersion::from_u32(version).ok_or(MqbError::UnknownVersion(version))?;
+        r.cursor.read_u32::<LE>()?;
+        let resource_directory_offset = if long_format {
+            r.cursor.read_u64::<LE>()?
+        } else {
+            r.cursor.read_u32::<LE>()? as u64
+        };
+
+        let name = r.name()?;
+        let framerate = r.cursor.read_f32::<LE>()?;
+        let resource_count = r.count("resource")?;
+        let cut_count = r.count("cut")?;
+        r.cursor.read_padding(0xC)?;
+
+        let resources = (0..resource_count)
+            .map(|_| r.resource())
+            .collect::<Result<Vec<_>, _>>()?;
+        let cuts = (0..cut_count)
+            .map(|_| r.cut())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let resource_directory = if resource_directory_offset != 0 {
+            r.cursor.set_position(resource_directory_offset);
+            Some(r.cursor.read_utf16::<LE>()?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            version,
+            long_format,
+            name,
+            framerate,
+            resources,
+            cuts,
+            resource_directory,
+        })
+    }
+
+    /// Every disposition of a resource across all cuts, with the cut it appears in.
+    pub fn dispositions_of(
+        &self,
+        resource_index: i32,
+    ) -> impl Iterator<Item = (&MqbCut, &MqbDisposition)> {
+        self.cuts.iter().flat_map(move |cut| {
+            cut.timelines
+                .iter()
+                .flat_map(|timeline| &timeline.dispositions)
+                .filter(move |disposition| disposition.resource_index == resource_index)
+                .map(move |disposition| (cut, disposition))
+        })
+    }
+
+    /// Length of the cutscene in frames.
+    pub fn duration(&self) -> i32 {
+        self.cuts.iter().map(|cut| cut.duration).sum()
+    }
+}
+
+struct MqbReader<'a> {
+    length: usize,
+    cursor: Cursor<&'a [u8]>,
+}
+
+impl MqbReader<'_> {
+    fn name(&mut self) -> Result<String, MqbError> {
+        let mut name = [0u16; NAME_SIZE / 2];
+        self.cursor.read_u16_into::<LE>(&mut name)?;
+        let length = name.iter().position(|c| *c == 0).unwrap_or(name.len());
+
+        Ok(String::from_utf16_lossy(&name[..length]))
+    }
+
+    /// Read a record count, which can't exceed what's left of the file in a valid one.
+    fn count(&mut self, kind: &'static str) -> Result<usize, MqbError> {
+        let count = self.cursor.read_u32::<LE>()? as usize;
+        let remaining = self.length.saturating_sub(self.cursor.position() as usize);
+        if count.saturating_mul(MIN_RECORD_SIZE) > remaining {
+            return Err(MqbError::BadCount(kind, count));
+        }
+
+        Ok(count)
+    }
+
+    fn parameters(&mut self, count: usize) -> Result<Vec<MqbParameter>, MqbError> {
+        (0..count).map(|_| self.parameter()).collect()
+    }
+
+    fn resource(&mut self) -> Result<MqbResource, MqbError> {
+        let name = self.name()?;
+        let parent_index = self.cursor.read_i32::<LE>()?;
+        self.cursor.read_padding(4)?;
+        let parameter_count = self.count("parameter")?;
+        self.cursor.read_padding(4)?;
+
+        Ok(MqbResource {
+            name,
+            parent_index,
+            parameters: self.parameters(parameter_count)?,
+        })
+    }
+
+    fn cut(&mut self) -> Result<MqbCut, MqbError> {
+        let name = self.name()?;
+        let _disposition_count = self.cursor.read_u32::<LE>()?;
+        let unk44 = self.cursor.read_i32::<LE>()?;
+        let duration = self.cursor.read_i32::<LE>()?;
+        let timeline_count = self.count("timeline")?;
+
+        Ok(MqbCut {
+            name,
+            unk44,
+            duration,
+            timelines: (0..timeline_count)
+                .map(|_| self.timeline())
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    fn timeline(&mut self) -> Result<MqbTimeline, MqbError> {
+        let unk00 = self.cursor.read_i32::<LE>()?;
+        let disposition_count = self.count("disposition")?;
+        let parameter_count = self.count("parameter")?;
+        self.cursor.read_padding(4)?;
+
+        Ok(MqbTimeline {
+            unk00,
+            dispositions: (0..disposition_count)
+                .map(|_| self.disposition())
+                .collect::<Result<_, _>>()?,
+            parameters: self.parameters(parameter_count)?,
+        })
+    }
+
+    fn disposition(&mut self) -> Result<MqbDisposition, MqbError> {
+        let mut values = [0i32; 8];
+        self.cursor.read_i32_into::<LE>(&mut values)?;
+        let [id, resource_index, unk08, start_frame, duration, unk14, unk18, unk1c] = values;
+        let transform_count = self.count("transform")?;
+        let parameter_count = self.count("parameter")?;
+        self.cursor.read_padding(8)?;
+
+        let transforms = (0..transform_count)
+            .map(|_| {
+                let mut values = [0f32; 10];
+                self.cursor.read_f32_into::<LE>(&mut values)?;
+                let [frame, tx, ty, tz, rx, ry, rz, sx, sy, sz] = values;
+
+                Ok(MqbTransform {
+                    frame,
+                    translation: [tx, ty, tz],
+                    rotation: [rx, ry, rz],
+                    scale: [sx, sy, sz],
+                })
+            })
+            .collect::<Result<_, MqbError>>()?;
+
+        Ok(MqbDisposition {
+            id,
+            resource_index,
+            unk08,
+            start_frame,
+            duration,
+            unk14,
+            unk18,
+            unk1c,
+            transforms,
+            parameters: self.parameters(parameter_count)?,
+        })
+    }
+
+    fn parameter(&mut self) -> Result<MqbParameter, MqbError> {
+        let name = self.name()?;
+        let value_type = self.cursor.read_u32::<LE>()?;
+        let unk44 = self.cursor.read_i32::<LE>()?;
+        let sequence_count = self.count("sequence")?;
+        self.cursor.read_padding(4)?;
+
+        // Values are stored in 4 bytes, apart from strings which are stored inline as a name.
+        let value = match value_type {
+            1 => MqbValue::Bool(self.cursor.read_u32::<LE>()? != 0),
+            2 => MqbValue::SByte(self.cursor.read_i32::<LE>()? as i8),
+            3 => MqbValue::Byte(self.cursor.read_u32::<LE>()? as u8),
+            4 => MqbValue::Short(self.cursor.read_i32::<LE>()? as i16),
+            7 => MqbValue::Int(self.cursor.read_i32::<LE>()?),
+            8 => MqbValue::UInt(self.cursor.read_u32::<LE>()?),
+            9 => MqbValue::Float(self.cursor.read_f32::<LE>()?),
+            0x13 => MqbValue::String(self.name()?),
+            0x15 => {
+                let mut color = [0u8; 4];
+                self.cursor.read_exact(&mut color)?;
+                MqbValue::Color(color)
+            }
+            _ => return Err(MqbError::UnknownParameterType(value_type)),
+        };
+
+        let sequences = (0..sequence_count)
+            .map(|_| {
+                let value_index = self.cursor.read_i32::<LE>()?;
+                let point_count = self.count("point")?;
+                let points = (0..point_count)
+                    .map(|_| {
+                        Ok(MqbPoint {
+                            frame: self.cursor.read_i32::<LE>()?,
+                            value: self.cursor.read_f32::<LE>()?,
+                        })
+                    })
+                    .collect::<Result<_, MqbError>>()?;
+
+                Ok(MqbSequence {
+                    value_index,
+                    points,
+                })
+            })
+            .collect::<Result<_, MqbError>>()?;
+
+        Ok(MqbParameter {
+            name,
+            unk44,
+            value,
+            sequences,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::mqb::{Mqb, MqbValue, MqbVersion};
+
+    fn name(out: &mut Vec<u8>, name: &str) {
+        let mut field = name.encode_utf16().collect::<Vec<_>>();
+        field.resize(0x20, 0);
+        out.extend(field.iter().flat_map(|c| c.to_le_bytes()));
+    }
+
+    fn ints(out: &mut Vec<u8>, values: &[i32]) {
+        out.extend(values.iter().flat_map(|value| value.to_le_bytes()));
+    }
+
+    #[test]
+    pub fn reads_cut_dispositions() {
+        let mut bytes = b"MQB \0\0\0\0".to_vec();
+        ints(&mut bytes, &[0xCB, 0x14, 0]);
+        name(&mut bytes, "s10_00");
+        bytes.extend(30f32.to_le_bytes());
+        ints(&mut bytes, &[1, 1, 0, 0, 0]);
+
+        // A resource, with no parameters.
+        name(&mut bytes, "c0000");
+        ints(&mut bytes, &[-1, 0, 0, 0]);
+
+        // A cut with one timeline holding one disposition.
+        name(&mut bytes, "cut_0");
+        ints(&mut bytes, &[1, 0, 120, 1]);
+        ints(&mut bytes, &[0, 1, 0, 0]);
+        ints(&mut bytes, &[10, 0, 0, 5, 60, 0, 0, 0, 1, 1, 0, 0]);
+        bytes.extend(
+            [0f32, 1., 2., 3., 0., 0., 0., 1., 1., 1.]
+                .map(f32::to_le_bytes)
+                .concat(),
+        );
+        name(&mut bytes, "AnimId");
+        ints(&mut bytes, &[7, 0, 0, 0, 3000]);
+
+        let mqb = Mqb::from_reader(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(mqb.version, MqbVersion::DarkSouls3);
+        assert_eq!(mqb.name, "s10_00");
+        assert_eq!(mqb.resources[0].name, "c0000");
+        assert_eq!(mqb.duration(), 120);
+
+        let (cut, disposition) = mqb.dispositions_of(0).next().unwrap();
+        assert_eq!(cut.name, "cut_0");
+        assert_eq!(disposition.start_frame, 5);
+        assert_eq!(disposition.transforms[0].translation, [1., 2., 3.]);
+        assert_eq!(disposition.parameters[0].value, MqbValue::Int(3000));
+    }
+}
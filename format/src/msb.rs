@@ -0,0 +1,299 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use byteorder::{ReadBytesExt, LE};
+use thiserror::Error;
+
+use crate::io_ext::ReadFormatsExt;
+
+#[derive(Debug, Error)]
+pub enum MsbError {
+    #[error("Could not read MSB: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unsupported MSB version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("Param list at {0:#x} has no entries, not even the offset of the next list")]
+    EmptyParam(u64),
+}
+
+const MSB_VERSION: u32 = 1;
+const HEADER_SIZE: u32 = 0x10;
+
+/// The param lists that are read, the others (events, regions, routes and layers) are skipped.
+const MODEL_PARAM: &str = "MODEL_PARAM_ST";
+const PARTS_PARAM: &str = "PARTS_PARAM_ST";
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MsbModelType {
+    MapPiece,
+    Enemy,
+    Player,
+    Collision,
+    Asset,
+    Other(u32),
+}
+
+impl MsbModelType {
+    fn from_u32(value: u32) -> Self {
+        match value {
+            0 => Self::MapPiece,
+            2 => Self::Enemy,
+            4 => Self::Player,
+            5 => Self::Collision,
+            10 => Self::Asset,
+            value => Self::Other(value),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MsbPartType {
+    MapPiece,
+    Enemy,
+    Player,
+    Collision,
+    DummyAsset,
+    DummyEnemy,
+    ConnectCollision,
+    Asset,
+    Other(u32),
+}
+
+impl MsbPartType {
+    fn from_u32(value: u32) -> Self {
+        match value {
+            0 => Self::MapPiece,
+            2 => Self::Enemy,
+            4 => Self::Player,
+            5 => Self::Collision,
+            9 => Self::DummyAsset,
+            10 => Self::DummyEnemy,
+            11 => Self::ConnectCollision,
+            13 => Self::Asset,
+            value => Self::Other(value),
+        }
+    }
+}
+
+/// The layout of an Elden Ring map (`mXX_XX_XX_XX.msb`): the models it uses and the parts that
+/// place instances of them.
+///
+/// Only the placement of parts is decoded. Events, regions and the type specific data of models
+/// and parts aren't read. Dark Souls 3, Sekiro and Armored Core 6 share the header, but number
+/// their types differently.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Msb {
+    pub models: Vec<MsbModel>,
+    pub parts: Vec<MsbPart>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MsbModel {
+    /// Name of the model, e.g. `m000000` for a map piece or `AEG007_077` for an asset.
+    pub name: String,
+    pub model_type: MsbModelType,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MsbPart {
+    pub name: String,
+    pub instance_id: i32,
+    pub part_type: MsbPartType,
+
+    /// Index into [`Msb::models`], if the part has a model.
+    pub model: Option<usize>,
+    pub position: [f32; 3],
+
+    /// Euler angles in degrees.
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+impl Msb {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, MsbError> {
+        r.read_magic(b"MSB ")?;
+        let version = r.read_u32::<LE>()?;
+        let header_size = r.read_u32::<LE>()?;
+        if version != MSB_VERSION || header_size != HEADER_SIZE {
+            return Err(MsbError::UnsupportedVersion(version));
+        }
+
+        let mut msb = Self {
+            models: Vec::new(),
+            parts: Vec::new(),
+        };
+
+        let mut offset = HEADER_SIZE as u64;
+        while offset != 0 {
+            r.seek(SeekFrom::Start(offset))?;
+            let _version = r.read_u32::<LE>()?;
+            let offset_count = r.read_u32::<LE>()?;
+            let name_offset = r.read_u64::<LE>()?;
+
+            // The last offset is that of the next list, zero after the last one.
+            let mut offsets = (0..offset_count)
+                .map(|_| r.read_u64::<LE>())
+                .collect::<Result<Vec<_>, _>>()?;
+            let next = offsets.pop().ok_or(MsbError::EmptyParam(offset))?;
+
+            r.seek(SeekFrom::Start(name_offset))?;
+            match r.read_utf16::<LE>()?.as_str() {
+                MODEL_PARAM => {
+                    for entry in offsets {
+                        msb.models.push(MsbModel::read(r, entry)?);
+                    }
+                }
+                PARTS_PARAM => {
+                    for entry in offsets {
+                        msb.parts.push(MsbPart::read(r, entry)?);
+                    }
+                }
+                _ => {}
+            }
+
+            offset = next;
+        }
+
+        Ok(msb)
+    }
+
+    /// The model of `part`, if it has one.
+    pub fn part_model(&self, part: &MsbPart) -> Option<&MsbModel> {
+        part.model.and_then(|index| self.models.get(index))
+    }
+}
+
+impl MsbModel {
+    fn read(r: &mut (impl Read + Seek), start: u64) -> Result<Self, MsbError> {
+        r.seek(SeekFrom::Start(start))?;
+        let name_offset = r.read_u64::<LE>()?;
+        let model_type = MsbModelType::from_u32(r.read_u32::<LE>()?);
+
+        Ok(Self {
+            name: read_name(r, start, name_offset)?,
+            model_type,
+        })
+    }
+}
+
+impl MsbPart {
+    fn read(r: &mut (impl Read + Seek), start: u64) -> Result<Self, MsbError> {
+        r.seek(SeekFrom::Start(start))?;
+        let name_offset = r.read_u64::<LE>()?;
+        let instance_id = r.read_i32::<LE>()?;
+        let part_type = MsbPartType::from_u32(r.read_u32::<LE>()?);
+        let _type_index = r.read_i32::<LE>()?;
+        let model = usize::try_from(r.read_i32::<LE>()?).ok();
+        let _file_offset = r.read_u64::<LE>()?;
+
+        let mut vector = || -> io::Result<[f32; 3]> {
+            Ok([
+                r.read_f32::<LE>()?,
+                r.read_f32::<LE>()?,
+                r.read_f32::<LE>()?,
+            ])
+        };
+        let position = vector()?;
+        let rotation = vector()?;
+        let scale = vector()?;
+
+        Ok(Self {
+            name: read_name(r, start, name_offset)?,
+            instance_id,
+            part_type,
+            model,
+            position,
+            rotation,
+            scale,
+        })
+    }
+}
+
+/// Names of entries are UTF-16 and relative to the start of the entry.
+fn read_name(r: &mut (impl Read + Seek), start: u64, offset: u64) -> Result<String, MsbError> {
+    r.seek(SeekFrom::Start(start + offset))?;
+
+    Ok(r.read_utf16::<LE>()?)
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::msb::{Msb, MsbModelType, MsbPartType};
+
+    fn utf16(value: &str) -> Vec<u8> {
+        value
+            .encode_utf16()
+            .chain([0])
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    }
+
+    /// A param list header with `entries` and the offset of the next list, followed by its name.
+    fn param(bytes: &mut Vec<u8>, name: &str, entries: &[u64], next: u64) {
+        let name_offset = bytes.len() as u64 + 0x10 + 8 * (entries.len() as u64 + 1);
+        bytes.extend(0x34u32.to_le_bytes());
+        bytes.extend((entries.len() as u32 + 1).to_le_bytes());
+        bytes.extend(name_offset.to_le_bytes());
+        for offset in entries.iter().chain([&next]) {
+            bytes.extend(offset.to_le_bytes());
+        }
+        bytes.extend(utf16(name));
+        bytes.resize(bytes.len().next_multiple_of(8), 0);
+    }
+
+    #[test]
+    pub fn reads_models_and_parts() {
+        let mut bytes = b"MSB \x01\0\0\0\x10\0\0\0\0\0\x01\xFF".to_vec();
+
+        // The model list, with its one entry straight after it.
+        param(&mut bytes, "MODEL_PARAM_ST", &[0x50], 0x78);
+        assert_eq!(bytes.len(), 0x50);
+        bytes.extend(0x10u64.to_le_bytes());
+        bytes.extend(10u32.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        bytes.extend(utf16("AEG007_077"));
+        bytes.resize(0x78, 0);
+
+        // An empty event list, whose entries are skipped either way.
+        param(&mut bytes, "EVENT_PARAM_ST", &[], 0xB0);
+        assert_eq!(bytes.len(), 0xB0);
+
+        param(&mut bytes, "PARTS_PARAM_ST", &[0xF0], 0x160);
+        assert_eq!(bytes.len(), 0xF0);
+        bytes.extend(0x50u64.to_le_bytes());
+        bytes.extend(1000i32.to_le_bytes());
+        bytes.extend(13u32.to_le_bytes());
+        bytes.extend(0i32.to_le_bytes());
+        bytes.extend(0i32.to_le_bytes());
+        bytes.extend(0u64.to_le_bytes());
+        for value in [1.0f32, 2.0, 3.0, 0.0, 90.0, 0.0, 1.0, 1.0, 1.0] {
+            bytes.extend(value.to_le_bytes());
+        }
+        bytes.resize(0x140, 0);
+        bytes.extend(utf16("AEG007_077_1000"));
+        bytes.resize(0x160, 0);
+
+        param(&mut bytes, "MAPSTUDIO_TREE_ST", &[], 0);
+
+        let msb = Msb::from_reader(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(msb.models.len(), 1);
+        assert_eq!(msb.models[0].name, "AEG007_077");
+        assert_eq!(msb.models[0].model_type, MsbModelType::Asset);
+
+        let part = &msb.parts[0];
+        assert_eq!(part.name, "AEG007_077_1000");
+        assert_eq!(part.instance_id, 1000);
+        assert_eq!(part.part_type, MsbPartType::Asset);
+        assert_eq!(part.position, [1.0, 2.0, 3.0]);
+        assert_eq!(part.rotation, [0.0, 90.0, 0.0]);
+        assert_eq!(msb.part_model(part), Some(&msb.models[0]));
+    }
+}
@@ -0,0 +1,203 @@
+use std::io::{self, Cursor, Read, Seek, Write};
+
+use thiserror::Error;
+
+use crate::{
+    bnd4::BND4,
+    dcx::{DCXError, DCX},
+    fmg::{Fmg, FmgError},
+    round_trip::WriteMode,
+};
+
+#[derive(Debug, Error)]
+pub enum MsgBndError {
+    #[error("Could not read msgbnd: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Could not decompress msgbnd: {0}")]
+    Dcx(#[from] DCXError),
+
+    #[error("Could not parse FMG {name}: {source}")]
+    Fmg { name: String, source: FmgError },
+
+    #[error("No FMG for category {0}")]
+    UnknownCategory(String),
+}
+
+/// The FMGs of a message binder such as `item.msgbnd.dcx` or `menu.msgbnd.dcx`.
+///
+/// Text is looked up by category, the name of an FMG without its extension (e.g. `WeaponName`).
+/// FMGs added by DLC such as `WeaponName_dlc01` are merged into their base category, with later
+/// DLC taking priority.
+pub struct MsgBnd {
+    dcx: Option<DCX>,
+    bnd: BND4,
+    fmgs: Vec<MsgBndFmg>,
+}
+
+struct MsgBndFmg {
+    name: String,
+    file_index: usize,
+    fmg: Fmg,
+    modified: bool,
+}
+
+impl MsgBnd {
+    /// Read a message binder, undoing DCX compression if present.
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, MsgBndError> {
+        let (dcx, bytes) = if DCX::has_magic(r)? {
+            let mut dcx = DCX::from_reader(r)?;
+            let bytes = std::mem::take(&mut dcx.decompressed);
+
+            (Some(dcx), bytes)
+        } else {
+            let mut bytes = Vec::new();
+            r.read_to_end(&mut bytes)?;
+
+            (None, bytes)
+        };
+
+        let bnd = BND4::from_reader(&mut Cursor::new(bytes))?;
+
+        let mut fmgs = Vec::new();
+        for (file_index, entry) in bnd.files.iter().enumerate() {
+            let Some(name) = fmg_name(&entry.path) else {
+                continue;
+            };
+
+            let fmg =
+                Fmg::from_reader(&mut Cursor::new(bnd.file_bytes(entry))).map_err(|source| {
+                    MsgBndError::Fmg {
+                        name: name.to_string(),
+                        source,
+                    }
+                })?;
+
+            fmgs.push(MsgBndFmg {
+                name: name.to_string(),
+                file_index,
+                fmg,
+                modified: false,
+            });
+        }
+
+        // Base FMGs sort before their DLC counterparts, so lookups can scan in reverse.
+        fmgs.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Self { dcx, bnd, fmgs })
+    }
+
+    /// Names of every FMG in the binder, including DLC FMGs.
+    pub fn fmg_names(&self) -> impl Iterator<Item = &str> {
+        self.fmgs.iter().map(|fmg| fmg.name.as_str())
+    }
+
+    /// Base categories of the FMGs in the binder, without any DLC suffix.
+    pub fn categories(&self) -> impl Iterator<Item = &str> {
+        self.fmgs
+            .iter()
+            .map(|fmg| fmg.name.as_str())
+            .filter(|name| base_category(name) == *name)
+    }
+
+    pub fn fmg(&self, name: &str) -> Option<&Fmg> {
+        self.fmgs
+            .iter()
+            .find(|fmg| fmg.name.eq_ignore_ascii_case(name))
+            .map(|fmg| &fmg.fmg)
+    }
+
+    /// The FMG named [name] to edit directly. It's written back by [MsgBnd::write] like the FMGs
+    /// edited through [MsgBnd::set_text].
+    pub fn fmg_mut(&mut self, name: &str) -> Option<&mut Fmg> {
+        let fmg = self
+            .fmgs
+            .iter_mut()
+            .find(|fmg| fmg.name.eq_ignore_ascii_case(name))?;
+        fmg.modified = true;
+
+        Some(&mut fmg.fmg)
+    }
+
+    /// Find the text for an entry of a category, preferring text from the most recent DLC.
+    pub fn text(&self, category: &str, id: i32) -> Option<&str> {
+        self.fmgs
+            .iter()
+            .rev()
+            .filter(|fmg| in_category(&fmg.name, category))
+            .find_map(|fmg| fmg.fmg.get(id))
+    }
+
+    /// Set the text of an entry of a category.
+    ///
+    /// The entry is updated in whichever FMG currently provides its text, so the change is visible
+    /// through [MsgBnd::text]. New entries are added to the base FMG of the category.
+    pub fn set_text(
+        &mut self,
+        category: &str,
+        id: i32,
+        text: Option<String>,
+    ) -> Result<(), MsgBndError> {
+        let candidates = self
+            .fmgs
+            .iter()
+            .enumerate()
+            .filter(|(_, fmg)| in_category(&fmg.name, category))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        let index = candidates
+            .iter()
+            .rev()
+            .find(|&&index| self.fmgs[index].fmg.get(id).is_some())
+            .or_else(|| candidates.first())
+            .copied()
+            .ok_or_else(|| MsgBndError::UnknownCategory(category.to_string()))?;
+
+        let fmg = &mut self.fmgs[index];
+        fmg.fmg.set(id, text);
+        fmg.modified = true;
+
+        Ok(())
+    }
+
+    /// Write the binder back out, recompressing it if it was read from a DCX. Edited FMGs keep the
+    /// layout they were read with, so that only the edited text changes.
+    pub fn write(&mut self, w: &mut impl Write) -> Result<(), MsgBndError> {
+        for fmg in self.fmgs.iter_mut().filter(|fmg| fmg.modified) {
+            self.bnd
+                .replace_file(fmg.file_index, &fmg.fmg.to_bytes_with(WriteMode::Preserve))?;
+            fmg.modified = false;
+        }
+
+        match &mut self.dcx {
+            Some(dcx) => {
+                dcx.decompressed = self.bnd.data.clone();
+                let result = dcx.write(w);
+                dcx.decompressed = Vec::new();
+
+                Ok(result?)
+            }
+            None => Ok(w.write_all(&self.bnd.data)?),
+        }
+    }
+}
+
+fn fmg_name(path: &str) -> Option<&str> {
+    let file_name = path.rsplit(['\\', '/']).next()?;
+
+    file_name
+        .strip_suffix(".fmg")
+        .or_else(|| file_name.strip_suffix(".FMG"))
+}
+
+fn base_category(name: &str) -> &str {
+    match name.rfind("_dlc") {
+        Some(index) if name[index + 4..].chars().all(|ch| ch.is_ascii_digit()) => &name[..index],
+        _ => name,
+    }
+}
+
+fn in_category(name: &str, category: &str) -> bool {
+    base_category(name).eq_ignore_ascii_case(category)
+}
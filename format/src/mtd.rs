@@ -0,0 +1,358 @@
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
+
+use byteorder::{ReadBytesExt, LE};
+use thiserror::Error;
+
+use crate::bnd4::BND4;
+
+#[derive(Debug, Error)]
+pub enum MtdError {
+    #[error("Could not read MTD: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Expected an MTD block of type {expected:#x} at {offset:#x}, found {actual:#x}")]
+    UnexpectedBlock {
+        offset: u64,
+        expected: u32,
+        actual: u32,
+    },
+
+    #[error("Param {name} has an unknown value type {value_type}")]
+    UnknownValueType { name: String, value_type: String },
+}
+
+const FILE_BLOCK: u32 = 0x0;
+const HEADER_BLOCK: u32 = 0x1;
+const DATA_BLOCK: u32 = 0x2;
+const LISTS_BLOCK: u32 = 0x3;
+const PARAM_BLOCK: u32 = 0x1000;
+const TEXTURE_BLOCK: u32 = 0x2000;
+
+/// Texture blocks of this version and later also carry a path and extra floats.
+const TEXTURE_VERSION_WITH_PATH: u32 = 5;
+
+/// A material definition (`.mtd`) from the games before Elden Ring, found in `mtdbnd`s and
+/// referenced by the path of a FLVER material.
+///
+/// MTDs are a tree of length-prefixed blocks. Each block is skipped to its end once read, so
+/// fields that aren't decoded here don't affect the rest of the file.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Mtd {
+    pub shader_path: String,
+    pub description: String,
+    pub params: Vec<MtdParam>,
+    pub textures: Vec<MtdTexture>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MtdValue {
+    Bool(bool),
+    Int(i32),
+    Int2([i32; 2]),
+    Float(f32),
+    Float2([f32; 2]),
+    Float3([f32; 3]),
+    Float4([f32; 4]),
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MtdParam {
+    pub name: String,
+    pub value: MtdValue,
+}
+
+/// A texture slot of the shader, which the textures of a FLVER material are bound to by type.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MtdTexture {
+    pub texture_type: String,
+    pub uv_number: i32,
+    pub shader_data_index: i32,
+
+    /// Texture the slot is filled with when the FLVER material doesn't provide one.
+    pub path: Option<String>,
+    pub unk_floats: Vec<f32>,
+}
+
+/// The header of a block, and where the block ends.
+struct Block {
+    version: u32,
+    end: u64,
+}
+
+impl Block {
+    fn read(r: &mut (impl Read + Seek), expected_type: Option<u32>) -> Result<Self, MtdError> {
+        let offset = r.stream_position()?;
+        let _zero = r.read_u32::<LE>()?;
+        let length = r.read_u32::<LE>()?;
+        let start = r.stream_position()?;
+        let block_type = r.read_u32::<LE>()?;
+        let version = r.read_u32::<LE>()?;
+        let _marker = r.read_u8()?;
+
+        if let Some(expected) = expected_type.filter(|expected| *expected != block_type) {
+            return Err(MtdError::UnexpectedBlock {
+                offset,
+                expected,
+                actual: block_type,
+            });
+        }
+
+        Ok(Self {
+            version,
+            end: start + length as u64,
+        })
+    }
+
+    fn skip_to_end(&self, r: &mut impl Seek) -> io::Result<()> {
+        r.seek(SeekFrom::Start(self.end))?;
+        Ok(())
+    }
+}
+
+impl Mtd {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, MtdError> {
+        Block::read(r, Some(FILE_BLOCK))?;
+        Block::read(r, Some(HEADER_BLOCK))?.skip_to_end(r)?;
+
+        let data = Block::read(r, Some(DATA_BLOCK))?;
+        let shader_path = read_marked_string(r)?;
+        let description = read_marked_string(r)?;
+        let _unk = r.read_u32::<LE>()?;
+
+        Block::read(r, Some(LISTS_BLOCK))?;
+        let _unk = r.read_u32::<LE>()?;
+
+        read_marker(r)?;
+        let param_count = r.read_u32::<LE>()?;
+        let params = (0..param_count)
+            .map(|_| MtdParam::from_reader(r))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        read_marker(r)?;
+        let texture_count = r.read_u32::<LE>()?;
+        let textures = (0..texture_count)
+            .map(|_| MtdTexture::from_reader(r))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        data.skip_to_end(r)?;
+
+        Ok(Self {
+            shader_path,
+            description,
+            params,
+            textures,
+        })
+    }
+
+    /// Find the MTD for a FLVER material in an `mtdbnd`, by the file name of the material's path.
+    pub fn from_bnd(bnd: &BND4, material_path: &str) -> Option<Result<Self, MtdError>> {
+        let entry = bnd.file_descriptor_by_stem(material_path)?;
+
+        Some(Self::from_reader(&mut Cursor::new(bnd.file_bytes(entry))))
+    }
+
+    pub fn param(&self, name: &str) -> Option<&MtdValue> {
+        self.params
+            .iter()
+            .find(|param| param.name == name)
+            .map(|param| &param.value)
+    }
+
+    /// Find a texture slot by type, e.g. `g_Diffuse`.
+    pub fn texture(&self, texture_type: &str) -> Option<&MtdTexture> {
+        self.textures
+            .iter()
+            .find(|texture| texture.texture_type == texture_type)
+    }
+}
+
+impl MtdParam {
+    fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, MtdError> {
+        let block = Block::read(r, Some(PARAM_BLOCK))?;
+        let name = read_marked_string(r)?;
+        let value_type = read_marked_string(r)?;
+        let _unk = r.read_u32::<LE>()?;
+
+        let value_block = Block::read(r, None)?;
+        let _value_count = r.read_u32::<LE>()?;
+
+        let value = match value_type.as_str() {
+            "bool" => MtdValue::Bool(r.read_u8()? != 0),
+            "int" => MtdValue::Int(r.read_i32::<LE>()?),
+            "int2" => MtdValue::Int2([r.read_i32::<LE>()?, r.read_i32::<LE>()?]),
+            "float" => MtdValue::Float(r.read_f32::<LE>()?),
+            "float2" => MtdValue::Float2(floats(r)?),
+            "float3" => MtdValue::Float3(floats(r)?),
+            "float4" => MtdValue::Float4(floats(r)?),
+            _ => return Err(MtdError::UnknownValueType { name, value_type }),
+        };
+
+        value_block.skip_to_end(r)?;
+        block.skip_to_end(r)?;
+
+        Ok(Self { name, value })
+    }
+}
+
+impl MtdTexture {
+    fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, MtdError> {
+        let block = Block::read(r, Some(TEXTURE_BLOCK))?;
+        let texture_type = read_marked_string(r)?;
+        let uv_number = r.read_i32::<LE>()?;
+        read_marker(r)?;
+        let shader_data_index = r.read_i32::<LE>()?;
+
+        let (path, unk_floats) = if block.version >= TEXTURE_VERSION_WITH_PATH {
+            let _unk = r.read_u32::<LE>()?;
+            let path = read_marked_string(r)?;
+            let float_count = r.read_u32::<LE>()?;
+            let unk_floats = (0..float_count)
+                .map(|_| r.read_f32::<LE>())
+                .collect::<io::Result<Vec<_>>>()?;
+
+            (Some(path), unk_floats)
+        } else {
+            (None, Vec::new())
+        };
+
+        block.skip_to_end(r)?;
+
+        Ok(Self {
+            texture_type,
+            uv_number,
+            shader_data_index,
+            path,
+            unk_floats,
+        })
+    }
+}
+
+fn floats<const N: usize>(r: &mut impl Read) -> io::Result<[f32; N]> {
+    let mut values = [0.0; N];
+    r.read_f32_into::<LE>(&mut values)?;
+    Ok(values)
+}
+
+/// Skip a marker byte and the padding that aligns what follows it to 4 bytes.
+fn read_marker(r: &mut (impl Read + Seek)) -> io::Result<()> {
+    let _marker = r.read_u8()?;
+    let position = r.stream_position()?;
+    r.seek(SeekFrom::Start(position.next_multiple_of(4)))?;
+
+    Ok(())
+}
+
+/// Read a length-prefixed Shift-JIS string followed by a marker.
+fn read_marked_string(r: &mut (impl Read + Seek)) -> io::Result<String> {
+    let length = r.read_u32::<LE>()?;
+    let mut bytes = vec![0u8; length as usize];
+    r.read_exact(&mut bytes)?;
+    read_marker(r)?;
+
+    let (string, _, _) = encoding_rs::SHIFT_JIS.decode(&bytes);
+    Ok(string.into_owned())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::mtd::{Mtd, MtdValue};
+
+    struct Writer(Vec<u8>);
+
+    impl Writer {
+        fn u32(&mut self, value: u32) {
+            self.0.extend(value.to_le_bytes());
+        }
+
+        fn marker(&mut self, marker: u8) {
+            self.0.push(marker);
+            self.0.resize(self.0.len().next_multiple_of(4), 0);
+        }
+
+        fn string(&mut self, value: &str, marker: u8) {
+            self.u32(value.len() as u32);
+            self.0.extend(value.as_bytes());
+            self.marker(marker);
+        }
+
+        /// Write a block header, returning where to patch its length.
+        fn begin(&mut self, block_type: u32, version: u32) -> usize {
+            self.u32(0);
+            let length_at = self.0.len();
+            self.u32(0);
+            self.u32(block_type);
+            self.u32(version);
+            self.0.push(0xA3);
+            length_at
+        }
+
+        fn end(&mut self, length_at: usize) {
+            let length = (self.0.len() - length_at - 4) as u32;
+            self.0[length_at..][..4].copy_from_slice(&length.to_le_bytes());
+        }
+    }
+
+    #[test]
+    pub fn reads_params_and_textures() {
+        let mut w = Writer(Vec::new());
+
+        let file = w.begin(0, 3);
+        let header = w.begin(1, 2);
+        w.string("MTD ", 0x34);
+        w.u32(1000);
+        w.end(header);
+
+        let data = w.begin(2, 4);
+        w.string("C[DetailBump]_Alp.spx", 0xA3);
+        w.string("", 0x03);
+        w.u32(1);
+
+        let lists = w.begin(3, 4);
+        w.u32(0);
+        w.marker(0x03);
+        w.u32(1);
+        let param = w.begin(0x1000, 4);
+        w.string("g_DetailBump_BumpPower", 0xA3);
+        w.string("float", 0x04);
+        w.u32(1);
+        let value = w.begin(0x1002, 1);
+        w.u32(1);
+        w.0.extend(0.75f32.to_le_bytes());
+        w.end(value);
+        w.marker(0x04);
+        w.u32(0);
+        w.end(param);
+
+        w.marker(0x03);
+        w.u32(1);
+        let texture = w.begin(0x2000, 5);
+        w.string("g_Diffuse", 0x35);
+        w.u32(1);
+        w.marker(0x35);
+        w.u32(0);
+        w.u32(0xA3);
+        w.string("", 0xBA);
+        w.u32(0);
+        w.end(texture);
+        w.marker(0x04);
+        w.u32(0);
+        w.end(lists);
+        w.end(data);
+        w.end(file);
+
+        let mtd = Mtd::from_reader(&mut Cursor::new(w.0)).unwrap();
+
+        assert_eq!(mtd.shader_path, "C[DetailBump]_Alp.spx");
+        assert_eq!(
+            mtd.param("g_DetailBump_BumpPower"),
+            Some(&MtdValue::Float(0.75))
+        );
+        assert_eq!(mtd.texture("g_Diffuse").unwrap().uv_number, 1);
+    }
+}
@@ -0,0 +1,262 @@
+use std::io::{self, Read, Seek};
+
+use byteorder::{ByteOrder, ReadBytesExt, LE};
+use thiserror::Error;
+
+use crate::io_ext::ReadFormatsExt;
+
+#[derive(Debug, Error)]
+pub enum NvaError {
+    #[error("Could not read NVA: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Section {index} is {length:#x} bytes but holds {count} entries of {entry_size:#x}")]
+    SectionTooShort {
+        index: usize,
+        length: usize,
+        count: u32,
+        entry_size: usize,
+    },
+}
+
+/// Positions of the sections in an NVA, which are stored in a fixed order.
+const SECTION_NAVMESHES: usize = 0;
+const SECTION_CONNECTORS: usize = 4;
+const SECTION_CONNECTOR_POINTS: usize = 5;
+const SECTION_CONNECTOR_CONDITIONS: usize = 6;
+
+const NAVMESH_SIZE: usize = 0x60;
+const CONNECTOR_SIZE: usize = 0x20;
+const CONNECTOR_POINT_SIZE: usize = 0x10;
+const CONNECTOR_CONDITION_SIZE: usize = 0x8;
+
+/// A map's navmesh placement file (`mXX_XX_XX_XX.nva`), used by Dark Souls 3, Sekiro and Elden
+/// Ring.
+///
+/// The navmesh geometry itself lives in Havok `hkaiNavMesh` objects in the map's `.nvmhktbnd`, see
+/// [crate::hkx::HkaiNavMesh]. The NVA places each of those navmeshes in the map and describes how
+/// neighbouring navmeshes connect to each other.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Nva {
+    pub version: u32,
+    pub sections: Vec<NvaSection>,
+}
+
+/// A section of fixed size entries, most of which are kept undecoded.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NvaSection {
+    pub index: i32,
+    pub version: i32,
+    pub count: u32,
+    pub data: Vec<u8>,
+}
+
+/// The placement of a navmesh within the map.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NvaNavmesh {
+    pub position: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+
+    /// ID that connectors and other map data refer to this navmesh by.
+    pub name_id: i32,
+
+    /// ID of the `hkaiNavMesh` model, e.g. 123 for `n000123`.
+    pub model_id: i32,
+    pub vertex_count: i32,
+}
+
+/// A link between two navmeshes, made of shared points and conditions for crossing it.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NvaConnector {
+    pub main_name_id: i32,
+    pub target_name_id: i32,
+    pub point_count: i32,
+    pub condition_count: i32,
+
+    /// Index of the first of this connector's entries in the connector point section.
+    pub points_index: i32,
+
+    /// Index of the first of this connector's entries in the connector condition section.
+    pub conditions_index: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NvaConnectorPoint {
+    pub unk00: i32,
+    pub unk04: i32,
+    pub unk08: i32,
+    pub unk0c: i32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NvaConnectorCondition {
+    pub condition1: i32,
+    pub condition2: i32,
+}
+
+impl Nva {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, NvaError> {
+        r.read_magic(b"NVMA")?;
+
+        let version = r.read_u32::<LE>()?;
+        let _file_size = r.read_u32::<LE>()?;
+        let section_count = r.read_u32::<LE>()?;
+
+        let mut sections = Vec::with_capacity(section_count as usize);
+        for _ in 0..section_count {
+            let index = r.read_i32::<LE>()?;
+            let version = r.read_i32::<LE>()?;
+            let length = r.read_u32::<LE>()?;
+            let count = r.read_u32::<LE>()?;
+
+            let mut data = vec![0u8; length as usize];
+            r.read_exact(&mut data)?;
+
+            sections.push(NvaSection {
+                index,
+                version,
+                count,
+                data,
+            });
+        }
+
+        Ok(Self { version, sections })
+    }
+
+    pub fn navmeshes(&self) -> Result<Vec<NvaNavmesh>, NvaError> {
+        self.decode(SECTION_NAVMESHES, NAVMESH_SIZE, |entry| {
+            let float = |offset: usize| LE::read_f32(&entry[offset..]);
+            let vector = |offset: usize| [float(offset), float(offset + 4), float(offset + 8)];
+
+            NvaNavmesh {
+                position: vector(0x0),
+                rotation: vector(0x10),
+                scale: vector(0x20),
+                name_id: LE::read_i32(&entry[0x30..]),
+                model_id: LE::read_i32(&entry[0x34..]),
+                vertex_count: LE::read_i32(&entry[0x40..]),
+            }
+        })
+    }
+
+    pub fn connectors(&self) -> Result<Vec<NvaConnector>, NvaError> {
+        self.decode(SECTION_CONNECTORS, CONNECTOR_SIZE, |entry| NvaConnector {
+            main_name_id: LE::read_i32(entry),
+            target_name_id: LE::read_i32(&entry[0x4..]),
+            point_count: LE::read_i32(&entry[0x8..]),
+            condition_count: LE::read_i32(&entry[0xC..]),
+            points_index: LE::read_i32(&entry[0x10..]),
+            conditions_index: LE::read_i32(&entry[0x18..]),
+        })
+    }
+
+    pub fn connector_points(&self) -> Result<Vec<NvaConnectorPoint>, NvaError> {
+        self.decode(SECTION_CONNECTOR_POINTS, CONNECTOR_POINT_SIZE, |entry| {
+            NvaConnectorPoint {
+                unk00: LE::read_i32(entry),
+                unk04: LE::read_i32(&entry[0x4..]),
+                unk08: LE::read_i32(&entry[0x8..]),
+                unk0c: LE::read_i32(&entry[0xC..]),
+            }
+        })
+    }
+
+    pub fn connector_conditions(&self) -> Result<Vec<NvaConnectorCondition>, NvaError> {
+        self.decode(
+            SECTION_CONNECTOR_CONDITIONS,
+            CONNECTOR_CONDITION_SIZE,
+            |entry| NvaConnectorCondition {
+                condition1: LE::read_i32(entry),
+                condition2: LE::read_i32(&entry[0x4..]),
+            },
+        )
+    }
+
+    /// The points of a connector, as a slice of all connector points.
+    pub fn points_of<'a>(
+        connector: &NvaConnector,
+        points: &'a [NvaConnectorPoint],
+    ) -> &'a [NvaConnectorPoint] {
+        let start = connector.points_index.max(0) as usize;
+        let end = start + connector.point_count.max(0) as usize;
+
+        points.get(start..end).unwrap_or_default()
+    }
+
+    fn decode<T>(
+        &self,
+        index: usize,
+        entry_size: usize,
+        decode: impl Fn(&[u8]) -> T,
+    ) -> Result<Vec<T>, NvaError> {
+        let Some(section) = self.sections.get(index) else {
+            return Ok(Vec::new());
+        };
+
+        section
+            .entries(entry_size)
+            .ok_or(NvaError::SectionTooShort {
+                index,
+                length: section.data.len(),
+                count: section.count,
+                entry_size,
+            })
+            .map(|entries| entries.map(decode).collect())
+    }
+}
+
+impl NvaSection {
+    /// Split the section into `count` entries of `entry_size` bytes, ignoring any trailing data.
+    pub fn entries(&self, entry_size: usize) -> Option<impl Iterator<Item = &[u8]>> {
+        let length = self.count as usize * entry_size;
+
+        Some(self.data.get(..length)?.chunks_exact(entry_size))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::nva::Nva;
+
+    #[test]
+    pub fn reads_navmeshes_and_connectors() {
+        let mut bytes = b"NVMA".to_vec();
+        bytes.extend([8u32, 0, 5].iter().flat_map(|v| v.to_le_bytes()));
+
+        let mut navmesh = vec![0u8; 0x60];
+        navmesh[0x0..0x4].copy_from_slice(&1.5f32.to_le_bytes());
+        navmesh[0x30..0x34].copy_from_slice(&7i32.to_le_bytes());
+        navmesh[0x34..0x38].copy_from_slice(&123i32.to_le_bytes());
+
+        let mut connector = vec![0u8; 0x20];
+        connector[0x0..0x4].copy_from_slice(&7i32.to_le_bytes());
+        connector[0x4..0x8].copy_from_slice(&8i32.to_le_bytes());
+
+        let sections: [&[u8]; 5] = [&navmesh, &[], &[], &[], &connector];
+        for (index, data) in sections.iter().enumerate() {
+            let count = (!data.is_empty()) as u32;
+            for value in [index as u32, 4, data.len() as u32, count] {
+                bytes.extend(value.to_le_bytes());
+            }
+            bytes.extend(*data);
+        }
+
+        let nva = Nva::from_reader(&mut Cursor::new(bytes)).unwrap();
+        let navmeshes = nva.navmeshes().unwrap();
+        let connectors = nva.connectors().unwrap();
+
+        assert_eq!(navmeshes[0].position, [1.5, 0.0, 0.0]);
+        assert_eq!((navmeshes[0].name_id, navmeshes[0].model_id), (7, 123));
+        assert_eq!(connectors[0].target_name_id, 8);
+        assert!(nva.connector_points().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,189 @@
+use std::io::{self, Read, Seek, SeekFrom};
+
+use byteorder::{ByteOrder, ReadBytesExt, BE, LE};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum NvmError {
+    #[error("Could not read NVM: {0}")]
+    Io(#[from] io::Error),
+}
+
+/// Offsets of the vertices start right after the header, so anything larger than this means the
+/// file is in the other byte order.
+const MAX_HEADER_SIZE: u32 = 0x10000;
+
+/// A Dark Souls navmesh (`.nvm`): a triangle mesh with per-triangle flags and adjacency.
+///
+/// The navigation graph linking navmeshes together is stored separately, in the map's MCG and MCP
+/// files.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Nvm {
+    pub big_endian: bool,
+    pub vertices: Vec<[f32; 3]>,
+    pub triangles: Vec<NvmTriangle>,
+
+    /// Map entities, such as doors and breakable objects, and the triangles they obstruct.
+    pub entities: Vec<NvmEntity>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NvmTriangle {
+    pub vertices: [u32; 3],
+
+    /// The triangle sharing each edge (between vertices 0-1, 1-2 and 2-0), if any.
+    pub neighbours: [Option<u32>; 3],
+    pub flags: NvmTriangleFlags,
+    pub obstacle_count: u16,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NvmEntity {
+    pub entity_id: i32,
+    pub triangle_indices: Vec<i32>,
+}
+
+/// Flags describing how AI may use a triangle.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NvmTriangleFlags(pub u16);
+
+impl NvmTriangleFlags {
+    pub const INSIDE_WALL: u16 = 0x1;
+    pub const BLOCK_GATE: u16 = 0x2;
+    pub const CLOSED_DOOR: u16 = 0x4;
+    pub const DISABLE: u16 = 0x8;
+    pub const EDGE: u16 = 0x10;
+    pub const LARGE_SPACE: u16 = 0x20;
+    pub const LADDER: u16 = 0x40;
+    pub const HOLE: u16 = 0x80;
+    pub const DOOR: u16 = 0x100;
+    pub const WALL_TOUCHING_FLOOR: u16 = 0x200;
+    pub const END_EDGE: u16 = 0x400;
+    pub const GATE: u16 = 0x8000;
+
+    pub fn contains(self, flag: u16) -> bool {
+        self.0 & flag == flag
+    }
+}
+
+impl Nvm {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, NvmError> {
+        r.seek(SeekFrom::Start(4))?;
+        let vertex_offset = r.read_u32::<LE>()?;
+        r.seek(SeekFrom::Start(0))?;
+
+        if vertex_offset < MAX_HEADER_SIZE {
+            Self::read::<_, LE>(r, false)
+        } else {
+            Self::read::<_, BE>(r, true)
+        }
+    }
+
+    fn read<R: Read + Seek, O: ByteOrder>(r: &mut R, big_endian: bool) -> Result<Self, NvmError> {
+        let vertex_count = r.read_u32::<O>()?;
+        let vertex_offset = r.read_u32::<O>()?;
+        let triangle_count = r.read_u32::<O>()?;
+        let triangle_offset = r.read_u32::<O>()?;
+        let _root_box_offset = r.read_u32::<O>()?;
+        let _unk14 = r.read_u32::<O>()?;
+        let entity_count = r.read_u32::<O>()?;
+        let entity_offset = r.read_u32::<O>()?;
+
+        r.seek(SeekFrom::Start(vertex_offset as u64))?;
+        let mut vertices = Vec::with_capacity(vertex_count as usize);
+        for _ in 0..vertex_count {
+            vertices.push([r.read_f32::<O>()?, r.read_f32::<O>()?, r.read_f32::<O>()?]);
+        }
+
+        r.seek(SeekFrom::Start(triangle_offset as u64))?;
+        let mut triangles = Vec::with_capacity(triangle_count as usize);
+        for _ in 0..triangle_count {
+            let vertices = [r.read_u32::<O>()?, r.read_u32::<O>()?, r.read_u32::<O>()?];
+            let neighbours = [r.read_i32::<O>()?, r.read_i32::<O>()?, r.read_i32::<O>()?]
+                .map(|neighbour| u32::try_from(neighbour).ok());
+            let flags = r.read_u32::<O>()?;
+
+            triangles.push(NvmTriangle {
+                vertices,
+                neighbours,
+                flags: NvmTriangleFlags(flags as u16),
+                obstacle_count: ((flags >> 16) & 0x3FFF) as u16,
+            });
+        }
+
+        r.seek(SeekFrom::Start(entity_offset as u64))?;
+        let mut entity_headers = Vec::with_capacity(entity_count as usize);
+        for _ in 0..entity_count {
+            let entity_id = r.read_i32::<O>()?;
+            let indices_offset = r.read_u32::<O>()?;
+            let indices_count = r.read_u32::<O>()?;
+            let _unk0c = r.read_u32::<O>()?;
+
+            entity_headers.push((entity_id, indices_offset, indices_count));
+        }
+
+        let mut entities = Vec::with_capacity(entity_headers.len());
+        for (entity_id, indices_offset, indices_count) in entity_headers {
+            r.seek(SeekFrom::Start(indices_offset as u64))?;
+
+            let mut triangle_indices = vec![0; indices_count as usize];
+            r.read_i32_into::<O>(&mut triangle_indices)?;
+
+            entities.push(NvmEntity {
+                entity_id,
+                triangle_indices,
+            });
+        }
+
+        Ok(Self {
+            big_endian,
+            vertices,
+            triangles,
+            entities,
+        })
+    }
+
+    /// Triangles the given flag is set on, e.g. [NvmTriangleFlags::GATE].
+    pub fn triangles_with_flag(&self, flag: u16) -> impl Iterator<Item = (usize, &NvmTriangle)> {
+        self.triangles
+            .iter()
+            .enumerate()
+            .filter(move |(_, triangle)| triangle.flags.contains(flag))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::nvm::{Nvm, NvmTriangleFlags};
+
+    #[test]
+    pub fn reads_big_endian_triangles() {
+        let mut bytes = Vec::new();
+        for value in [3u32, 0x20, 1, 0x44, 0, 0, 0, 0x60] {
+            bytes.extend(value.to_be_bytes());
+        }
+
+        for value in [0.0f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0] {
+            bytes.extend(value.to_be_bytes());
+        }
+
+        for value in [0i32, 1, 2, -1, -1, 4] {
+            bytes.extend(value.to_be_bytes());
+        }
+        bytes.extend((0x8000u32 | 3 << 16).to_be_bytes());
+
+        let nvm = Nvm::from_reader(&mut Cursor::new(bytes)).unwrap();
+
+        assert!(nvm.big_endian);
+        assert_eq!(nvm.vertices[2], [0.0, 0.0, 1.0]);
+        assert_eq!(nvm.triangles[0].neighbours, [None, None, Some(4)]);
+        assert_eq!(nvm.triangles[0].obstacle_count, 3);
+        assert!(nvm.triangles[0].flags.contains(NvmTriangleFlags::GATE));
+    }
+}
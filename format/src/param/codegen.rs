@@ -0,0 +1,414 @@
+use std::{collections::HashSet, fmt::Write};
+
+use crate::param::{def::ParamFieldLocation, ParamDef, ParamField, ParamFieldType};
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv",
+    "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Generate Rust source for a typed, zero-copy row view of every given paramdef.
+///
+/// Each paramdef becomes a struct borrowing a row's data (e.g. `EquipParamWeapon<'a>`) with one
+/// accessor per field, reading it in the byte order of the paramdef. The output only depends on
+/// `core` so it can be written to `OUT_DIR` from a build script and `include!`d by downstream
+/// crates:
+///
+/// ```ignore
+/// let paramdex = Paramdex::load("Paramdex/ER")?;
+/// let source = format::param::codegen::generate_rows(paramdex.defs());
+/// std::fs::write(out_dir.join("params.rs"), source)?;
+/// ```
+pub fn generate_rows<'a>(defs: impl IntoIterator<Item = &'a ParamDef>) -> String {
+    let mut defs = defs.into_iter().collect::<Vec<_>>();
+    defs.sort_by(|a, b| a.param_type.cmp(&b.param_type));
+
+    let mut out = String::new();
+    out.push_str("// @generated from paramdefs by format::param::codegen\n\n");
+    out.push_str(
+        "#[inline(always)]\nfn row_bytes<const N: usize>(data: &[u8], offset: usize) -> [u8; N] {\n    \
+         let mut bytes = [0u8; N];\n    bytes.copy_from_slice(&data[offset..offset + N]);\n    \
+         bytes\n}\n",
+    );
+
+    for def in defs {
+        generate_row(&mut out, def);
+    }
+
+    out
+}
+
+/// Generate typed row views like [generate_rows], along with [ParamRowView] implementations and a
+/// `GeneratedParams` extension trait for [ParamSet] that exposes one indexed table per param type:
+///
+/// ```ignore
+/// use generated::GeneratedParams;
+///
+/// let params = ParamSet::new(params);
+/// let heavy = params
+///     .equip_param_weapon()
+///     .unwrap()?
+///     .filter(|row| row.weight() > 10.0)
+///     .count();
+/// ```
+///
+/// Unlike [generate_rows] the output depends on this crate.
+///
+/// [ParamRowView]: crate::param::ParamRowView
+/// [ParamSet]: crate::param::ParamSet
+pub fn generate_tables<'a>(defs: impl IntoIterator<Item = &'a ParamDef>) -> String {
+    let mut defs = defs.into_iter().collect::<Vec<_>>();
+    defs.sort_by(|a, b| a.param_type.cmp(&b.param_type));
+
+    let mut out = generate_rows(defs.iter().copied());
+    let mut accessors = String::new();
+
+    for def in &defs {
+        let struct_name = struct_name(&def.param_type);
+        let method_name = field_name(&struct_name);
+
+        let _ = write!(
+            out,
+            "\nimpl<'a> ::format::param::ParamRowView<'a> for {struct_name}<'a> {{\n    \
+             const PARAM_TYPE: &'static str = \"{param_type}\";\n    \
+             const BIG_ENDIAN: bool = {big_endian};\n\n    \
+             fn from_data(data: &'a [u8]) -> Option<Self> {{\n        \
+             Self::new(data)\n    }}\n}}\n",
+            param_type = def.param_type,
+            big_endian = def.big_endian,
+        );
+
+        let _ = write!(
+            accessors,
+            "\n    fn {method_name}(&self) -> Option<Result<::format::param::ParamTable<'_, \
+             {struct_name}<'_>>, ::format::param::ParamError>> {{\n        self.table()\n    }}\n",
+        );
+    }
+
+    out.push_str(
+        "\n/// Typed access to every param with a known paramdef.\npub trait GeneratedParams {",
+    );
+    for def in &defs {
+        let struct_name = struct_name(&def.param_type);
+        let _ = write!(
+            out,
+            "\n    fn {}(&self) -> Option<Result<::format::param::ParamTable<'_, {struct_name}<'_>>, \
+             ::format::param::ParamError>>;",
+            field_name(&struct_name),
+        );
+    }
+    out.push_str("\n}\n\nimpl GeneratedParams for ::format::param::ParamSet {");
+    out.push_str(&accessors);
+    out.push_str("}\n");
+
+    out
+}
+
+fn generate_row(out: &mut String, def: &ParamDef) {
+    let struct_name = struct_name(&def.param_type);
+    let row_size = def.row_size();
+
+    let _ = write!(
+        out,
+        "\n/// A row of a `{param_type}` param.\n\
+         #[derive(Clone, Copy, Debug)]\n\
+         pub struct {struct_name}<'a> {{\n    data: &'a [u8],\n}}\n\n\
+         impl<'a> {struct_name}<'a> {{\n    \
+         pub const PARAM_TYPE: &'static str = \"{param_type}\";\n    \
+         pub const ROW_SIZE: usize = {row_size};\n    \
+         pub const BIG_ENDIAN: bool = {big_endian};\n\n    \
+         /// Create a view over the data of a row, if it is large enough.\n    \
+         pub fn new(data: &'a [u8]) -> Option<Self> {{\n        \
+         (data.len() >= Self::ROW_SIZE).then_some(Self {{ data }})\n    }}\n\n    \
+         pub fn data(&self) -> &'a [u8] {{\n        self.data\n    }}\n",
+        param_type = def.param_type,
+        big_endian = def.big_endian,
+    );
+    let from_bytes = match def.big_endian {
+        true => "from_be_bytes",
+        false => "from_le_bytes",
+    };
+
+    let mut used_names = HashSet::new();
+    for (field, location) in def.fields.iter().zip(def.layout()) {
+        if field.field_type == ParamFieldType::Dummy8 {
+            continue;
+        }
+
+        let mut name = field_name(&field.name);
+        while !used_names.insert(name.clone()) {
+            name.push('_');
+        }
+
+        generate_accessor(out, field, location, &name, from_bytes);
+    }
+
+    out.push_str("}\n");
+}
+
+fn generate_accessor(
+    out: &mut String,
+    field: &ParamField,
+    location: ParamFieldLocation,
+    name: &str,
+    from_bytes: &str,
+) {
+    let offset = location.offset;
+
+    out.push('\n');
+    if let Some(description) = field.display_name.as_ref().or(field.description.as_ref()) {
+        for line in description.lines() {
+            let _ = writeln!(out, "    /// {}", line.trim());
+        }
+    }
+
+    if let (Some(bits), Some(bit_offset)) = (field.bit_size, location.bit_offset) {
+        let (return_type, unit_size) = match field.field_type {
+            ParamFieldType::U16 => ("u16", 2),
+            ParamFieldType::U32 => ("u32", 4),
+            _ => ("u8", 1),
+        };
+        // Masked to the unit, so that the literal fits in the unit's type.
+        let unit_bits = unit_size * 8;
+        let mask = match (bits as u32).min(unit_bits) {
+            0 => 0,
+            bits => u32::MAX >> (32 - bits),
+        };
+        let (return_type, conversion) = if bits == 1 {
+            ("bool", " != 0")
+        } else {
+            (return_type, "")
+        };
+
+        let shifted = match bit_offset {
+            0 => "unit".to_string(),
+            bit_offset => format!("(unit >> {bit_offset})"),
+        };
+
+        let _ = write!(
+            out,
+            "    pub fn {name}(&self) -> {return_type} {{\n        \
+             let unit = {unit_type}::{from_bytes}(row_bytes::<{unit_size}>(self.data, {offset}));\n        \
+             {shifted} & {mask:#x}{conversion}\n    }}\n",
+            unit_type = match unit_size {
+                2 => "u16",
+                4 => "u32",
+                _ => "u8",
+            },
+        );
+
+        return;
+    }
+
+    let size = field.field_type.size();
+    let scalar = match field.field_type {
+        ParamFieldType::S8 => Some("i8"),
+        ParamFieldType::U8 => Some("u8"),
+        ParamFieldType::S16 => Some("i16"),
+        ParamFieldType::U16 => Some("u16"),
+        ParamFieldType::S32 => Some("i32"),
+        ParamFieldType::U32 => Some("u32"),
+        ParamFieldType::F32 | ParamFieldType::Angle32 => Some("f32"),
+        ParamFieldType::F64 => Some("f64"),
+        ParamFieldType::B32 => None,
+        ParamFieldType::Dummy8 | ParamFieldType::FixStr | ParamFieldType::FixStrW => None,
+    };
+
+    match (field.field_type, scalar) {
+        (ParamFieldType::B32, _) if field.array_length == 1 => {
+            let _ = write!(
+                out,
+                "    pub fn {name}(&self) -> bool {{\n        \
+                 u32::{from_bytes}(row_bytes::<4>(self.data, {offset})) != 0\n    }}\n",
+            );
+        }
+        (_, Some(scalar)) if field.array_length == 1 => {
+            let _ = write!(
+                out,
+                "    pub fn {name}(&self) -> {scalar} {{\n        \
+                 {scalar}::{from_bytes}(row_bytes::<{size}>(self.data, {offset}))\n    }}\n",
+            );
+        }
+        _ => {
+            let end = offset + size * field.array_length;
+            let _ = write!(
+                out,
+                "    pub fn {name}(&self) -> &'a [u8] {{\n        \
+                 &self.data[{offset}..{end}]\n    }}\n",
+            );
+        }
+    }
+}
+
+/// Convert a param type such as `EQUIP_PARAM_WEAPON_ST` into a struct name like
+/// `EquipParamWeapon`.
+pub fn struct_name(param_type: &str) -> String {
+    let param_type = param_type.strip_suffix("_ST").unwrap_or(param_type);
+    let mut name = String::with_capacity(param_type.len());
+
+    for word in param_type.split(|ch: char| !ch.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name.extend(chars.map(|ch| ch.to_ascii_lowercase()));
+        }
+    }
+
+    if name.starts_with(|ch: char| ch.is_ascii_digit()) || name.is_empty() {
+        name.insert(0, 'P');
+    }
+
+    name
+}
+
+/// Convert a paramdef field name such as `atkBasePhysics` into a snake case identifier like
+/// `atk_base_physics`.
+pub fn field_name(name: &str) -> String {
+    let mut snake = String::with_capacity(name.len() + 4);
+    let mut previous: Option<char> = None;
+
+    for ch in name.chars() {
+        if !ch.is_ascii_alphanumeric() {
+            if !snake.ends_with('_') {
+                snake.push('_');
+            }
+            previous = Some('_');
+            continue;
+        }
+
+        if ch.is_ascii_uppercase()
+            && previous.is_some_and(|prev| prev.is_ascii_lowercase() || prev.is_ascii_digit())
+        {
+            snake.push('_');
+        }
+
+        snake.push(ch.to_ascii_lowercase());
+        previous = Some(ch);
+    }
+
+    let mut snake = snake.trim_matches('_').to_string();
+    if snake.is_empty() || snake.starts_with(|ch: char| ch.is_ascii_digit()) {
+        snake.insert(0, '_');
+    }
+
+    if KEYWORDS.contains(&snake.as_str()) {
+        snake.push('_');
+    }
+
+    snake
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, process::Command};
+
+    use byteorder::{BE, LE};
+
+    use super::generate_rows;
+    use crate::param::{def::write_value, ParamDef, ParamField, ParamValue};
+
+    fn test_def(big_endian: bool) -> ParamDef {
+        let fields = [
+            "s32 value",
+            "u8 flagA:1",
+            "u8 flagB:3",
+            "u8 full:8",
+            "u16 wide:12",
+            "f32 scale",
+            "b32 enabled",
+        ];
+
+        ParamDef {
+            param_type: "TEST_PARAM_ST".to_string(),
+            data_version: 1,
+            big_endian,
+            unicode: true,
+            format_version: 203,
+            fields: fields
+                .iter()
+                .map(|def| ParamField::from_def(def).unwrap())
+                .collect(),
+        }
+    }
+
+    /// Compile the generated view of a row written in the def's byte order, and check that the
+    /// program reads back what was written.
+    fn compile_and_read(def: &ParamDef) {
+        let values = [
+            ParamValue::S32(-7),
+            ParamValue::U8(1),
+            ParamValue::U8(5),
+            ParamValue::U8(0xff),
+            ParamValue::U16(0xabc),
+            ParamValue::F32(1.5),
+            ParamValue::B32(true),
+        ];
+
+        let mut row = vec![0; def.row_size()];
+        for ((field, location), value) in def.fields.iter().zip(def.layout()).zip(&values) {
+            let write = match def.big_endian {
+                true => write_value::<BE>,
+                false => write_value::<LE>,
+            };
+            write(field, location, value, &mut row).unwrap();
+        }
+
+        let source = format!(
+            "{}\nfn main() {{\n    \
+             let row = TestParam::new(&{row:?}).unwrap();\n    \
+             assert_eq!(row.value(), -7);\n    \
+             assert!(row.flag_a());\n    \
+             assert_eq!(row.flag_b(), 5);\n    \
+             assert_eq!(row.full(), 0xff);\n    \
+             assert_eq!(row.wide(), 0xabc);\n    \
+             assert_eq!(row.scale(), 1.5);\n    \
+             assert!(row.enabled());\n}}\n",
+            generate_rows([def]),
+        );
+
+        let directory = env::temp_dir().join(format!(
+            "param-codegen-{}-{}",
+            std::process::id(),
+            def.big_endian
+        ));
+        fs::create_dir_all(&directory).unwrap();
+        let source_path = directory.join("main.rs");
+        let binary_path = directory.join("main");
+        fs::write(&source_path, source).unwrap();
+
+        let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+        let compiled = Command::new(rustc)
+            .args(["--edition", "2021", "--crate-name", "generated", "-o"])
+            .arg(&binary_path)
+            .arg(&source_path)
+            .output()
+            .unwrap();
+        assert!(
+            compiled.status.success(),
+            "{}",
+            String::from_utf8_lossy(&compiled.stderr)
+        );
+
+        let run = Command::new(&binary_path).output().unwrap();
+        assert!(
+            run.status.success(),
+            "{}",
+            String::from_utf8_lossy(&run.stderr)
+        );
+
+        fs::remove_dir_all(directory).unwrap();
+    }
+
+    #[test]
+    pub fn generated_rows_read_little_endian() {
+        compile_and_read(&test_def(false));
+    }
+
+    #[test]
+    pub fn generated_rows_read_big_endian() {
+        compile_and_read(&test_def(true));
+    }
+}
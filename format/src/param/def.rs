@@ -0,0 +1,769 @@
+use std::{fmt, ops::Range, str::FromStr};
+
+use byteorder::ByteOrder;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ParamDefError {
+    #[error("Could not parse paramdef XML: {0}")]
+    Xml(#[from] roxmltree::Error),
+
+    #[error("Paramdef is missing the <{0}> element")]
+    MissingElement(&'static str),
+
+    #[error("Invalid field definition {0:?}")]
+    InvalidFieldDef(String),
+
+    #[error("Unknown field type {0:?}")]
+    UnknownFieldType(String),
+
+    #[error("Field {field} does not fit in a row of {row_size} bytes")]
+    RowTooShort { field: String, row_size: usize },
+
+    #[error("Value is not valid for field {field} of type {field_type}")]
+    TypeMismatch {
+        field: String,
+        field_type: ParamFieldType,
+    },
+
+    #[error("Could not parse {value:?} as field {field} of type {field_type}")]
+    InvalidValue {
+        field: String,
+        field_type: ParamFieldType,
+        value: String,
+    },
+}
+
+/// The storage type of a single paramdef field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ParamFieldType {
+    S8,
+    U8,
+    S16,
+    U16,
+    S32,
+    U32,
+    F32,
+    F64,
+    B32,
+    Angle32,
+    Dummy8,
+    FixStr,
+    FixStrW,
+}
+
+impl ParamFieldType {
+    /// Size in bytes of a single element of this type.
+    pub fn size(&self) -> usize {
+        match self {
+            Self::S8 | Self::U8 | Self::Dummy8 | Self::FixStr => 1,
+            Self::S16 | Self::U16 | Self::FixStrW => 2,
+            Self::S32 | Self::U32 | Self::F32 | Self::B32 | Self::Angle32 => 4,
+            Self::F64 => 8,
+        }
+    }
+
+    /// Whether fields of this type can be packed into bitfields.
+    pub fn is_bit_type(&self) -> bool {
+        matches!(self, Self::U8 | Self::U16 | Self::U32 | Self::Dummy8)
+    }
+
+    /// The storage type used for the bitfield unit containing fields of this type.
+    fn bit_unit(&self) -> Self {
+        match self {
+            Self::Dummy8 => Self::U8,
+            other => *other,
+        }
+    }
+}
+
+impl FromStr for ParamFieldType {
+    type Err = ParamDefError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "s8" => Self::S8,
+            "u8" => Self::U8,
+            "s16" => Self::S16,
+            "u16" => Self::U16,
+            "s32" => Self::S32,
+            "u32" => Self::U32,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            "b32" => Self::B32,
+            "angle32" => Self::Angle32,
+            "dummy8" => Self::Dummy8,
+            "fixstr" => Self::FixStr,
+            "fixstrW" => Self::FixStrW,
+            _ => return Err(ParamDefError::UnknownFieldType(s.to_string())),
+        })
+    }
+}
+
+impl fmt::Display for ParamFieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::S8 => "s8",
+            Self::U8 => "u8",
+            Self::S16 => "s16",
+            Self::U16 => "u16",
+            Self::S32 => "s32",
+            Self::U32 => "u32",
+            Self::F32 => "f32",
+            Self::F64 => "f64",
+            Self::B32 => "b32",
+            Self::Angle32 => "angle32",
+            Self::Dummy8 => "dummy8",
+            Self::FixStr => "fixstr",
+            Self::FixStrW => "fixstrW",
+        })
+    }
+}
+
+/// A decoded field value from a param row.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ParamValue {
+    S8(i8),
+    U8(u8),
+    S16(i16),
+    U16(u16),
+    S32(i32),
+    U32(u32),
+    F32(f32),
+    F64(f64),
+    B32(bool),
+    Angle32(f32),
+    Dummy(Vec<u8>),
+    FixStr(String),
+    Array(Vec<ParamValue>),
+}
+
+impl fmt::Display for ParamValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::S8(v) => write!(f, "{v}"),
+            Self::U8(v) => write!(f, "{v}"),
+            Self::S16(v) => write!(f, "{v}"),
+            Self::U16(v) => write!(f, "{v}"),
+            Self::S32(v) => write!(f, "{v}"),
+            Self::U32(v) => write!(f, "{v}"),
+            Self::F32(v) | Self::Angle32(v) => write!(f, "{v}"),
+            Self::F64(v) => write!(f, "{v}"),
+            Self::B32(v) => write!(f, "{v}"),
+            Self::Dummy(bytes) => write!(f, "{bytes:02x?}"),
+            Self::FixStr(v) => f.write_str(v),
+            Self::Array(values) => {
+                f.write_str("[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+impl ParamValue {
+    /// Parse the value of `field` from the way [ParamValue] displays it, e.g. `[1, 2]` for arrays
+    /// or `[00, 0a]` for padding.
+    pub fn parse(field: &ParamField, value: &str) -> Result<Self, ParamDefError> {
+        let invalid = || ParamDefError::InvalidValue {
+            field: field.name.clone(),
+            field_type: field.field_type,
+            value: value.to_string(),
+        };
+        let elements = || -> Result<Vec<&str>, ParamDefError> {
+            let inner = value
+                .trim()
+                .strip_prefix('[')
+                .and_then(|inner| inner.strip_suffix(']'))
+                .ok_or_else(invalid)?;
+
+            Ok(inner
+                .split(',')
+                .map(str::trim)
+                .filter(|element| !element.is_empty())
+                .collect::<Vec<_>>())
+        };
+
+        match field.field_type {
+            ParamFieldType::FixStr | ParamFieldType::FixStrW => Ok(Self::FixStr(value.to_string())),
+            // Bitfields are read as their storage type, padding included.
+            ParamFieldType::Dummy8 if field.bit_size.is_some() => {
+                value.trim().parse().map(Self::U8).map_err(|_| invalid())
+            }
+            ParamFieldType::Dummy8 => elements()?
+                .into_iter()
+                .map(|byte| u8::from_str_radix(byte, 16).map_err(|_| invalid()))
+                .collect::<Result<_, _>>()
+                .map(Self::Dummy),
+            field_type if field.array_length > 1 => elements()?
+                .into_iter()
+                .map(|element| parse_scalar(field_type, element).ok_or_else(invalid))
+                .collect::<Result<_, _>>()
+                .map(Self::Array),
+            field_type => parse_scalar(field_type, value.trim()).ok_or_else(invalid),
+        }
+    }
+}
+
+/// A single field of a [ParamDef], as described by the `Def` attribute of a Paramdex field, e.g.
+/// `u8 isEnableRepair:1 = 1` or `fixstr name[32]`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParamField {
+    pub name: String,
+    pub field_type: ParamFieldType,
+    pub array_length: usize,
+    pub bit_size: Option<u8>,
+    pub default: Option<String>,
+    pub display_name: Option<String>,
+    pub enum_name: Option<String>,
+    pub description: Option<String>,
+    pub minimum: Option<f64>,
+    pub maximum: Option<f64>,
+    pub increment: Option<f64>,
+    pub sort_id: Option<i32>,
+}
+
+impl ParamField {
+    /// Parse the compact `type name[length]:bits = default` syntax used by Paramdex.
+    pub fn from_def(def: &str) -> Result<Self, ParamDefError> {
+        let invalid = || ParamDefError::InvalidFieldDef(def.to_string());
+
+        let (declaration, default) = match def.split_once('=') {
+            Some((declaration, default)) => (declaration.trim(), Some(default.trim().to_string())),
+            None => (def.trim(), None),
+        };
+
+        let (field_type, name) = declaration.split_once(' ').ok_or_else(invalid)?;
+        let field_type = field_type.parse::<ParamFieldType>()?;
+        let mut name = name.trim();
+
+        let mut bit_size = None;
+        if let Some((rest, bits)) = name.split_once(':') {
+            let bits = bits.trim().parse::<u8>().map_err(|_| invalid())?;
+            // A bitfield holds at least one bit and fits in the unit it's packed into.
+            if bits == 0 || bits as usize > field_type.size() * 8 {
+                return Err(invalid());
+            }
+
+            bit_size = Some(bits);
+            name = rest.trim();
+        }
+
+        let mut array_length = 1;
+        if let Some((rest, length)) = name.split_once('[') {
+            let length = length.strip_suffix(']').ok_or_else(invalid)?;
+            array_length = length.trim().parse::<usize>().map_err(|_| invalid())?;
+            name = rest.trim();
+        }
+
+        if name.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            field_type,
+            array_length,
+            bit_size,
+            default,
+            display_name: None,
+            enum_name: None,
+            description: None,
+            minimum: None,
+            maximum: None,
+            increment: None,
+            sort_id: None,
+        })
+    }
+
+    /// The human-readable name of this field if one is known, otherwise its internal name.
+    pub fn label(&self) -> &str {
+        self.display_name.as_deref().unwrap_or(&self.name)
+    }
+}
+
+/// Where a field lives inside of a row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParamFieldLocation {
+    pub offset: usize,
+    pub bit_offset: Option<u8>,
+}
+
+/// The layout of a param's rows, loaded from a Paramdex XML definition.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParamDef {
+    pub param_type: String,
+    pub data_version: i16,
+    pub big_endian: bool,
+    pub unicode: bool,
+    pub format_version: i16,
+    pub fields: Vec<ParamField>,
+}
+
+impl ParamDef {
+    /// Load a paramdef from the XML format used by the community Paramdex repository.
+    pub fn from_xml(xml: &str) -> Result<Self, ParamDefError> {
+        let document = roxmltree::Document::parse(xml)?;
+        let root = document.root_element();
+
+        let param_type = child_text(root, "ParamType")
+            .ok_or(ParamDefError::MissingElement("ParamType"))?
+            .to_string();
+        let data_version = child_text(root, "DataVersion")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+        let big_endian = child_text(root, "BigEndian").is_some_and(parse_bool);
+        let unicode = child_text(root, "Unicode").is_some_and(parse_bool);
+        let format_version = child_text(root, "FormatVersion")
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0);
+
+        let fields = root
+            .children()
+            .find(|child| child.has_tag_name("Fields"))
+            .ok_or(ParamDefError::MissingElement("Fields"))?
+            .children()
+            .filter(|child| child.has_tag_name("Field"))
+            .map(|node| {
+                let def = node
+                    .attribute("Def")
+                    .ok_or(ParamDefError::MissingElement("Field Def"))?;
+                let mut field = ParamField::from_def(def)?;
+
+                field.display_name = child_text(node, "DisplayName").map(str::to_string);
+                field.enum_name = child_text(node, "Enum").map(str::to_string);
+                field.description = child_text(node, "Description").map(str::to_string);
+                field.minimum = child_text(node, "Minimum").and_then(|v| v.parse().ok());
+                field.maximum = child_text(node, "Maximum").and_then(|v| v.parse().ok());
+                field.increment = child_text(node, "Increment").and_then(|v| v.parse().ok());
+                field.sort_id = child_text(node, "SortID").and_then(|v| v.parse().ok());
+
+                Ok(field)
+            })
+            .collect::<Result<Vec<_>, ParamDefError>>()?;
+
+        Ok(Self {
+            param_type,
+            data_version,
+            big_endian,
+            unicode,
+            format_version,
+            fields,
+        })
+    }
+
+    pub fn field(&self, name: &str) -> Option<&ParamField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Compute the location of every field in a row, packing consecutive bitfields of the same
+    /// storage type into a shared unit.
+    pub fn layout(&self) -> Vec<ParamFieldLocation> {
+        let mut locations = Vec::with_capacity(self.fields.len());
+        let mut offset = 0;
+        let mut bit_unit: Option<(ParamFieldType, usize, u8)> = None;
+
+        for field in &self.fields {
+            match field.bit_size {
+                Some(bits) if field.field_type.is_bit_type() => {
+                    let unit_type = field.field_type.bit_unit();
+                    let unit_bits = (unit_type.size() * 8) as u8;
+
+                    let (unit_offset, bit_offset) = match bit_unit {
+                        Some((current_type, unit_offset, used))
+                            if current_type == unit_type && used + bits <= unit_bits =>
+                        {
+                            (unit_offset, used)
+                        }
+                        _ => {
+                            let unit_offset = offset;
+                            offset += unit_type.size();
+                            (unit_offset, 0)
+                        }
+                    };
+
+                    bit_unit = Some((unit_type, unit_offset, bit_offset + bits));
+                    locations.push(ParamFieldLocation {
+                        offset: unit_offset,
+                        bit_offset: Some(bit_offset),
+                    });
+                }
+                _ => {
+                    bit_unit = None;
+                    locations.push(ParamFieldLocation {
+                        offset,
+                        bit_offset: None,
+                    });
+                    offset += field.field_type.size() * field.array_length;
+                }
+            }
+        }
+
+        locations
+    }
+
+    /// The size of a single row described by this definition.
+    pub fn row_size(&self) -> usize {
+        self.field_ranges()
+            .into_iter()
+            .map(|range| range.end)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The bytes of a row each field is stored in. Bitfields span the whole of their unit.
+    pub fn field_ranges(&self) -> Vec<Range<usize>> {
+        self.fields
+            .iter()
+            .zip(self.layout())
+            .map(|(field, location)| {
+                let size = match field.bit_size {
+                    Some(_) if field.field_type.is_bit_type() => field.field_type.bit_unit().size(),
+                    _ => field.field_type.size() * field.array_length,
+                };
+
+                location.offset..location.offset + size
+            })
+            .collect()
+    }
+
+    /// Decode all fields of a row's data.
+    pub fn read_row<O: ByteOrder>(&self, data: &[u8]) -> Result<Vec<ParamValue>, ParamDefError> {
+        self.fields
+            .iter()
+            .zip(self.layout())
+            .map(|(field, location)| read_value::<O>(field, location, data))
+            .collect()
+    }
+}
+
+/// Decode a single field from a row's data.
+pub fn read_value<O: ByteOrder>(
+    field: &ParamField,
+    location: ParamFieldLocation,
+    data: &[u8],
+) -> Result<ParamValue, ParamDefError> {
+    let too_short = || ParamDefError::RowTooShort {
+        field: field.name.clone(),
+        row_size: data.len(),
+    };
+
+    if let (Some(bits), Some(bit_offset)) = (field.bit_size, location.bit_offset) {
+        let unit_type = field.field_type.bit_unit();
+        let unit = data
+            .get(location.offset..location.offset + unit_type.size())
+            .ok_or_else(too_short)?;
+        let unit = match unit_type {
+            ParamFieldType::U8 => unit[0] as u32,
+            ParamFieldType::U16 => O::read_u16(unit) as u32,
+            _ => O::read_u32(unit),
+        };
+        let value = (unit >> bit_offset) & (u32::MAX >> (32 - bits as u32));
+
+        return Ok(match field.field_type {
+            ParamFieldType::U16 => ParamValue::U16(value as u16),
+            ParamFieldType::U32 => ParamValue::U32(value),
+            _ => ParamValue::U8(value as u8),
+        });
+    }
+
+    let size = field.field_type.size() * field.array_length;
+    let bytes = data
+        .get(location.offset..location.offset + size)
+        .ok_or_else(too_short)?;
+
+    Ok(match field.field_type {
+        ParamFieldType::Dummy8 => ParamValue::Dummy(bytes.to_vec()),
+        ParamFieldType::FixStr => {
+            let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+            let (value, _, _) = encoding_rs::SHIFT_JIS.decode(&bytes[..end]);
+
+            ParamValue::FixStr(value.into_owned())
+        }
+        ParamFieldType::FixStrW => {
+            let chars = bytes
+                .chunks_exact(2)
+                .map(O::read_u16)
+                .take_while(|ch| *ch != 0)
+                .collect::<Vec<_>>();
+
+            ParamValue::FixStr(String::from_utf16_lossy(&chars))
+        }
+        field_type if field.array_length > 1 => ParamValue::Array(
+            bytes
+                .chunks_exact(field_type.size())
+                .map(|element| read_scalar::<O>(field_type, element))
+                .collect(),
+        ),
+        field_type => read_scalar::<O>(field_type, bytes),
+    })
+}
+
+/// Encode a single field value into a row's data, leaving any neighbouring bitfields untouched.
+pub fn write_value<O: ByteOrder>(
+    field: &ParamField,
+    location: ParamFieldLocation,
+    value: &ParamValue,
+    data: &mut [u8],
+) -> Result<(), ParamDefError> {
+    let row_size = data.len();
+    let too_short = || ParamDefError::RowTooShort {
+        field: field.name.clone(),
+        row_size,
+    };
+    let mismatch = || ParamDefError::TypeMismatch {
+        field: field.name.clone(),
+        field_type: field.field_type,
+    };
+
+    if let (Some(bits), Some(bit_offset)) = (field.bit_size, location.bit_offset) {
+        let value = match value {
+            ParamValue::U8(v) => *v as u32,
+            ParamValue::U16(v) => *v as u32,
+            ParamValue::U32(v) => *v,
+            ParamValue::B32(v) => *v as u32,
+            _ => return Err(mismatch()),
+        };
+
+        let unit_type = field.field_type.bit_unit();
+        let unit_bytes = data
+            .get_mut(location.offset..location.offset + unit_type.size())
+            .ok_or_else(too_short)?;
+        let unit = match unit_type {
+            ParamFieldType::U8 => unit_bytes[0] as u32,
+            ParamFieldType::U16 => O::read_u16(unit_bytes) as u32,
+            _ => O::read_u32(unit_bytes),
+        };
+
+        let mask = (u32::MAX >> (32 - bits as u32)) << bit_offset;
+        let unit = (unit & !mask) | ((value << bit_offset) & mask);
+
+        match unit_type {
+            ParamFieldType::U8 => unit_bytes[0] = unit as u8,
+            ParamFieldType::U16 => O::write_u16(unit_bytes, unit as u16),
+            _ => O::write_u32(unit_bytes, unit),
+        }
+
+        return Ok(());
+    }
+
+    let size = field.field_type.size() * field.array_length;
+    let bytes = data
+        .get_mut(location.offset..location.offset + size)
+        .ok_or_else(too_short)?;
+
+    match (field.field_type, value) {
+        (ParamFieldType::Dummy8, ParamValue::Dummy(value)) => {
+            let length = value.len().min(bytes.len());
+            bytes.fill(0);
+            bytes[..length].copy_from_slice(&value[..length]);
+        }
+        (ParamFieldType::FixStr, ParamValue::FixStr(value)) => {
+            let (encoded, _, _) = encoding_rs::SHIFT_JIS.encode(value);
+            let length = encoded.len().min(bytes.len());
+            bytes.fill(0);
+            bytes[..length].copy_from_slice(&encoded[..length]);
+        }
+        (ParamFieldType::FixStrW, ParamValue::FixStr(value)) => {
+            bytes.fill(0);
+            for (chunk, ch) in bytes.chunks_exact_mut(2).zip(value.encode_utf16()) {
+                O::write_u16(chunk, ch);
+            }
+        }
+        (field_type, ParamValue::Array(values)) if field.array_length > 1 => {
+            if values.len() != field.array_length {
+                return Err(mismatch());
+            }
+
+            if !values.iter().all(|value| value_matches(field_type, value)) {
+                return Err(mismatch());
+            }
+
+            for (chunk, value) in bytes.chunks_exact_mut(field_type.size()).zip(values) {
+                write_scalar::<O>(value, chunk).ok_or_else(mismatch)?;
+            }
+        }
+        (field_type, value) if value_matches(field_type, value) => {
+            write_scalar::<O>(value, bytes).ok_or_else(mismatch)?;
+        }
+        _ => return Err(mismatch()),
+    }
+
+    Ok(())
+}
+
+fn value_matches(field_type: ParamFieldType, value: &ParamValue) -> bool {
+    matches!(
+        (field_type, value),
+        (ParamFieldType::S8, ParamValue::S8(_))
+            | (ParamFieldType::U8, ParamValue::U8(_))
+            | (ParamFieldType::S16, ParamValue::S16(_))
+            | (ParamFieldType::U16, ParamValue::U16(_))
+            | (ParamFieldType::S32, ParamValue::S32(_))
+            | (ParamFieldType::U32, ParamValue::U32(_))
+            | (ParamFieldType::F32, ParamValue::F32(_))
+            | (ParamFieldType::F64, ParamValue::F64(_))
+            | (ParamFieldType::B32, ParamValue::B32(_))
+            | (ParamFieldType::Angle32, ParamValue::Angle32(_))
+    )
+}
+
+fn write_scalar<O: ByteOrder>(value: &ParamValue, bytes: &mut [u8]) -> Option<()> {
+    match value {
+        ParamValue::S8(v) => bytes[0] = *v as u8,
+        ParamValue::U8(v) => bytes[0] = *v,
+        ParamValue::S16(v) => O::write_i16(bytes, *v),
+        ParamValue::U16(v) => O::write_u16(bytes, *v),
+        ParamValue::S32(v) => O::write_i32(bytes, *v),
+        ParamValue::U32(v) => O::write_u32(bytes, *v),
+        ParamValue::F32(v) | ParamValue::Angle32(v) => O::write_f32(bytes, *v),
+        ParamValue::F64(v) => O::write_f64(bytes, *v),
+        ParamValue::B32(v) => O::write_u32(bytes, *v as u32),
+        ParamValue::Dummy(_) | ParamValue::FixStr(_) | ParamValue::Array(_) => return None,
+    }
+
+    Some(())
+}
+
+fn parse_scalar(field_type: ParamFieldType, value: &str) -> Option<ParamValue> {
+    Some(match field_type {
+        ParamFieldType::S8 => ParamValue::S8(value.parse().ok()?),
+        ParamFieldType::U8 => ParamValue::U8(value.parse().ok()?),
+        ParamFieldType::S16 => ParamValue::S16(value.parse().ok()?),
+        ParamFieldType::U16 => ParamValue::U16(value.parse().ok()?),
+        ParamFieldType::S32 => ParamValue::S32(value.parse().ok()?),
+        ParamFieldType::U32 => ParamValue::U32(value.parse().ok()?),
+        ParamFieldType::F32 => ParamValue::F32(value.parse().ok()?),
+        ParamFieldType::F64 => ParamValue::F64(value.parse().ok()?),
+        ParamFieldType::B32 => ParamValue::B32(value.parse().ok()?),
+        ParamFieldType::Angle32 => ParamValue::Angle32(value.parse().ok()?),
+        ParamFieldType::Dummy8 | ParamFieldType::FixStr | ParamFieldType::FixStrW => return None,
+    })
+}
+
+fn read_scalar<O: ByteOrder>(field_type: ParamFieldType, bytes: &[u8]) -> ParamValue {
+    match field_type {
+        ParamFieldType::S8 => ParamValue::S8(bytes[0] as i8),
+        ParamFieldType::U8 => ParamValue::U8(bytes[0]),
+        ParamFieldType::S16 => ParamValue::S16(O::read_i16(bytes)),
+        ParamFieldType::U16 => ParamValue::U16(O::read_u16(bytes)),
+        ParamFieldType::S32 => ParamValue::S32(O::read_i32(bytes)),
+        ParamFieldType::U32 => ParamValue::U32(O::read_u32(bytes)),
+        ParamFieldType::F32 => ParamValue::F32(O::read_f32(bytes)),
+        ParamFieldType::F64 => ParamValue::F64(O::read_f64(bytes)),
+        ParamFieldType::B32 => ParamValue::B32(O::read_u32(bytes) != 0),
+        ParamFieldType::Angle32 => ParamValue::Angle32(O::read_f32(bytes)),
+        ParamFieldType::Dummy8 | ParamFieldType::FixStr | ParamFieldType::FixStrW => {
+            ParamValue::Dummy(bytes.to_vec())
+        }
+    }
+}
+
+fn child_text<'a>(node: roxmltree::Node<'a, '_>, name: &str) -> Option<&'a str> {
+    node.children()
+        .find(|child| child.has_tag_name(name))
+        .and_then(|child| child.text())
+        .map(str::trim)
+}
+
+fn parse_bool(value: &str) -> bool {
+    value.eq_ignore_ascii_case("true")
+}
+
+#[cfg(test)]
+mod test {
+    use byteorder::LE;
+
+    use super::{ParamDef, ParamField, ParamFieldType, ParamValue};
+
+    const DEF: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <DataVersion>1</DataVersion>
+  <BigEndian>False</BigEndian>
+  <Unicode>True</Unicode>
+  <FormatVersion>203</FormatVersion>
+  <Fields>
+    <Field Def="s32 value = 0">
+      <DisplayName>Value</DisplayName>
+    </Field>
+    <Field Def="u8 flagA:1" />
+    <Field Def="u8 flagB:3" />
+    <Field Def="dummy8 pad[3]" />
+    <Field Def="f32 scale = 1" />
+  </Fields>
+</PARAMDEF>"#;
+
+    #[test]
+    pub fn parses_field_defs() {
+        let field = ParamField::from_def("fixstr name[32] = hello").unwrap();
+
+        assert_eq!(field.field_type, ParamFieldType::FixStr);
+        assert_eq!(field.name, "name");
+        assert_eq!(field.array_length, 32);
+        assert_eq!(field.default.as_deref(), Some("hello"));
+    }
+
+    #[test]
+    pub fn rejects_invalid_bit_sizes() {
+        assert!(ParamField::from_def("u8 x:0").is_err());
+        assert!(ParamField::from_def("u8 x:9").is_err());
+        assert!(ParamField::from_def("s32 y:40").is_err());
+
+        let field = ParamField::from_def("u32 z:32").unwrap();
+        assert_eq!(field.bit_size, Some(32));
+    }
+
+    #[test]
+    pub fn reads_bitfields() {
+        let def = ParamDef::from_xml(DEF).unwrap();
+        assert_eq!(def.row_size(), 12);
+
+        let row = [7, 0, 0, 0, 0b1011, 0, 0, 0, 0, 0, 0x80, 0x3f];
+        let values = def.read_row::<LE>(&row).unwrap();
+
+        assert_eq!(
+            values,
+            vec![
+                ParamValue::S32(7),
+                ParamValue::U8(1),
+                ParamValue::U8(5),
+                ParamValue::Dummy(vec![0, 0, 0]),
+                ParamValue::F32(1.0),
+            ]
+        );
+    }
+
+    #[test]
+    pub fn parses_displayed_values() {
+        let def = ParamDef::from_xml(DEF).unwrap();
+        let values = [
+            ParamValue::S32(-7),
+            ParamValue::U8(1),
+            ParamValue::U8(5),
+            ParamValue::Dummy(vec![0, 0x0a, 0xff]),
+            ParamValue::F32(0.1),
+        ];
+
+        for (field, value) in def.fields.iter().zip(values) {
+            assert_eq!(ParamValue::parse(field, &value.to_string()).unwrap(), value);
+        }
+
+        let array = ParamField::from_def("s16 offsets[2]").unwrap();
+        assert_eq!(
+            ParamValue::parse(&array, "[1, -2]").unwrap(),
+            ParamValue::Array(vec![ParamValue::S16(1), ParamValue::S16(-2)])
+        );
+        assert!(ParamValue::parse(&array, "1, 2").is_err());
+    }
+}
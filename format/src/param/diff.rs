@@ -0,0 +1,247 @@
+use std::fmt;
+
+use crate::param::{Param, ParamDef, ParamDefError, ParamValue};
+
+/// The set of row changes between two versions of the same param.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParamDiff {
+    pub rows: Vec<RowDiff>,
+}
+
+impl ParamDiff {
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct RowDiff {
+    pub id: i32,
+    pub name: Option<String>,
+    pub kind: RowDiffKind,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum RowDiffKind {
+    Added,
+    Removed,
+    /// The row was renamed, or some of the fields of the def changed value. Changes in bytes the
+    /// def has no field for are ignored.
+    Modified {
+        rename: Option<NameChange>,
+        fields: Vec<FieldChange>,
+    },
+}
+
+/// The name of a row before and after it was renamed.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NameChange {
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// A single field whose value differs between two versions of a row.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FieldChange {
+    pub field: String,
+    pub old: ParamValue,
+    pub new: ParamValue,
+}
+
+/// Compare two versions of a param, producing a per-row, per-field change list ordered by row ID.
+///
+/// Rows are matched by ID. Params may contain duplicate IDs, in which case rows are matched by
+/// their order of appearance.
+pub fn diff(old: &Param, new: &Param, def: &ParamDef) -> Result<ParamDiff, ParamDefError> {
+    let layout = def.layout();
+    let old_rows = old.rows_by_key();
+    let mut new_rows = new.rows_by_key();
+    let mut rows = Vec::new();
+
+    for (key, old_row) in old_rows {
+        let Some(new_row) = new_rows.remove(&key) else {
+            rows.push(RowDiff {
+                id: old_row.id,
+                name: old_row.name.clone(),
+                kind: RowDiffKind::Removed,
+            });
+            continue;
+        };
+
+        let rename = (old_row.name != new_row.name).then(|| NameChange {
+            old: old_row.name.clone(),
+            new: new_row.name.clone(),
+        });
+
+        let mut changes = Vec::new();
+        let fields = match old_row.data == new_row.data {
+            true => &[][..],
+            false => &def.fields[..],
+        };
+        for (field, location) in fields.iter().zip(&layout) {
+            let old_value = old.read_field(field, *location, &old_row.data)?;
+            let new_value = new.read_field(field, *location, &new_row.data)?;
+
+            if old_value != new_value {
+                changes.push(FieldChange {
+                    field: field.name.clone(),
+                    old: old_value,
+                    new: new_value,
+                });
+            }
+        }
+
+        if rename.is_none() && changes.is_empty() {
+            continue;
+        }
+
+        rows.push(RowDiff {
+            id: new_row.id,
+            name: new_row.name.clone().or_else(|| old_row.name.clone()),
+            kind: RowDiffKind::Modified {
+                rename,
+                fields: changes,
+            },
+        });
+    }
+
+    rows.extend(new_rows.into_values().map(|row| RowDiff {
+        id: row.id,
+        name: row.name.clone(),
+        kind: RowDiffKind::Added,
+    }));
+    rows.sort_by_key(|row| row.id);
+
+    Ok(ParamDiff { rows })
+}
+
+impl fmt::Display for ParamDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in &self.rows {
+            let name = row.name.as_deref().unwrap_or_default();
+
+            match &row.kind {
+                RowDiffKind::Added => writeln!(f, "+ {} {}", row.id, name)?,
+                RowDiffKind::Removed => writeln!(f, "- {} {}", row.id, name)?,
+                RowDiffKind::Modified { rename, fields } => {
+                    writeln!(f, "~ {} {}", row.id, name)?;
+                    if let Some(NameChange { old, new }) = rename {
+                        let (old, new) = (old.as_deref(), new.as_deref());
+                        writeln!(
+                            f,
+                            "    (renamed): {} -> {}",
+                            old.unwrap_or_default(),
+                            new.unwrap_or_default()
+                        )?;
+                    }
+                    for change in fields {
+                        writeln!(f, "    {}: {} -> {}", change.field, change.old, change.new)?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{diff, FieldChange, NameChange, RowDiff, RowDiffKind};
+    use crate::param::{Param, ParamDef, ParamRow, ParamValue};
+
+    // The last byte of each row isn't covered by a field.
+    const DEF: &str = r#"<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <Fields>
+    <Field Def="u8 a" />
+    <Field Def="u8 b" />
+  </Fields>
+</PARAMDEF>"#;
+
+    fn param(rows: &[(i32, Option<&str>, [u8; 3])]) -> Param {
+        Param {
+            param_type: "TEST_PARAM_ST".to_string(),
+            big_endian: false,
+            format_2d: 0,
+            format_2e: 0,
+            paramdef_format_version: 0,
+            unk06: 0,
+            paramdef_data_version: 0,
+            row_size: 3,
+            rows: rows
+                .iter()
+                .map(|(id, name, data)| ParamRow {
+                    id: *id,
+                    name: name.map(str::to_string),
+                    data: data.to_vec(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    pub fn diffs_rows() {
+        let def = ParamDef::from_xml(DEF).unwrap();
+        let old = param(&[
+            (1, None, [0, 0, 0]),
+            (2, Some("Modified"), [0, 0, 0]),
+            (3, Some("Before"), [0, 0, 0]),
+            (4, None, [0, 0, 0]),
+            (5, None, [0, 0, 0]),
+        ]);
+        let new = param(&[
+            (2, Some("Modified"), [0, 7, 0]),
+            (3, Some("After"), [0, 0, 0]),
+            (4, None, [0, 0, 9]),
+            (5, None, [0, 0, 0]),
+            (6, Some("Added"), [0, 0, 0]),
+        ]);
+
+        let diff = diff(&old, &new, &def).unwrap();
+
+        assert_eq!(
+            diff.rows,
+            vec![
+                RowDiff {
+                    id: 1,
+                    name: None,
+                    kind: RowDiffKind::Removed,
+                },
+                RowDiff {
+                    id: 2,
+                    name: Some("Modified".to_string()),
+                    kind: RowDiffKind::Modified {
+                        rename: None,
+                        fields: vec![FieldChange {
+                            field: "b".to_string(),
+                            old: ParamValue::U8(0),
+                            new: ParamValue::U8(7),
+                        }],
+                    },
+                },
+                RowDiff {
+                    id: 3,
+                    name: Some("After".to_string()),
+                    kind: RowDiffKind::Modified {
+                        rename: Some(NameChange {
+                            old: Some("Before".to_string()),
+                            new: Some("After".to_string()),
+                        }),
+                        fields: Vec::new(),
+                    },
+                },
+                RowDiff {
+                    id: 6,
+                    name: Some("Added".to_string()),
+                    kind: RowDiffKind::Added,
+                },
+            ]
+        );
+    }
+}
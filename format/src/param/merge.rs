@@ -0,0 +1,307 @@
+use std::collections::BTreeSet;
+
+use crate::param::{Param, ParamDef, ParamDefError, ParamRow, ParamValue};
+
+/// A change made by both sides of a merge that could not be reconciled. The merged param keeps
+/// the value from `ours` for every conflict.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MergeConflict {
+    /// Both sides changed the same field of a row to different values.
+    Field {
+        id: i32,
+        field: String,
+        base: ParamValue,
+        ours: ParamValue,
+        theirs: ParamValue,
+    },
+
+    /// Both sides renamed a row to different names.
+    Name {
+        id: i32,
+        base: Option<String>,
+        ours: Option<String>,
+        theirs: Option<String>,
+    },
+
+    /// One side removed a row that the other side modified, both sides added different rows
+    /// with the same ID, or both sides changed a byte no field covers to different values.
+    Row { id: i32 },
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MergeResult {
+    pub param: Param,
+    pub conflicts: Vec<MergeConflict>,
+}
+
+impl MergeResult {
+    pub fn has_conflicts(&self) -> bool {
+        !self.conflicts.is_empty()
+    }
+}
+
+/// Three-way merge two modified versions of a param against their common base.
+///
+/// Non-conflicting row additions, removals and field-level changes from both sides are applied,
+/// anything both sides changed differently is reported as a [MergeConflict]. The header of the
+/// merged param is taken from `ours` and rows are ordered by ID as the game expects.
+pub fn merge(
+    base: &Param,
+    ours: &Param,
+    theirs: &Param,
+    def: &ParamDef,
+) -> Result<MergeResult, ParamDefError> {
+    let layout = def.layout();
+    let field_ranges = def.field_ranges();
+    let base_rows = base.rows_by_key();
+    let our_rows = ours.rows_by_key();
+    let their_rows = theirs.rows_by_key();
+
+    let keys = base_rows
+        .keys()
+        .chain(our_rows.keys())
+        .chain(their_rows.keys())
+        .copied()
+        .collect::<BTreeSet<_>>();
+
+    let mut rows = Vec::with_capacity(keys.len());
+    let mut conflicts = Vec::new();
+
+    for key in keys {
+        let base_row = base_rows.get(&key).copied();
+        let our_row = our_rows.get(&key).copied();
+        let their_row = their_rows.get(&key).copied();
+
+        let merged = match (base_row, our_row, their_row) {
+            _ if same_row(our_row, their_row) => our_row.cloned(),
+            _ if same_row(base_row, our_row) => their_row.cloned(),
+            _ if same_row(base_row, their_row) => our_row.cloned(),
+            (Some(base_row), Some(our_row), Some(their_row)) => {
+                let mut merged = our_row.clone();
+                if same_name(base_row, our_row) {
+                    merged.name = their_row.name.clone();
+                } else if !same_name(base_row, their_row) && !same_name(our_row, their_row) {
+                    conflicts.push(MergeConflict::Name {
+                        id: key.0,
+                        base: base_row.name.clone(),
+                        ours: our_row.name.clone(),
+                        theirs: their_row.name.clone(),
+                    });
+                }
+
+                // Bytes no field covers, e.g. padding, are merged byte by byte.
+                let mut covered = vec![false; merged.data.len()];
+                for range in &field_ranges {
+                    let end = range.end.min(covered.len());
+                    let start = range.start.min(end);
+                    covered[start..end].fill(true);
+                }
+
+                let mut bytes_conflict = false;
+                for (i, byte) in merged.data.iter_mut().enumerate() {
+                    let (Some(&base_byte), Some(&their_byte)) =
+                        (base_row.data.get(i), their_row.data.get(i))
+                    else {
+                        continue;
+                    };
+                    if covered[i] || their_byte == base_byte || their_byte == *byte {
+                        continue;
+                    }
+
+                    match *byte == base_byte {
+                        true => *byte = their_byte,
+                        false => bytes_conflict = true,
+                    }
+                }
+                if bytes_conflict {
+                    conflicts.push(MergeConflict::Row { id: key.0 });
+                }
+
+                for (field, location) in def.fields.iter().zip(&layout) {
+                    let base_value = base.read_field(field, *location, &base_row.data)?;
+                    let our_value = ours.read_field(field, *location, &our_row.data)?;
+                    let their_value = theirs.read_field(field, *location, &their_row.data)?;
+
+                    if our_value == their_value || their_value == base_value {
+                        continue;
+                    }
+
+                    if our_value == base_value {
+                        ours.write_field(field, *location, &their_value, &mut merged.data)?;
+                    } else {
+                        conflicts.push(MergeConflict::Field {
+                            id: key.0,
+                            field: field.name.clone(),
+                            base: base_value,
+                            ours: our_value,
+                            theirs: their_value,
+                        });
+                    }
+                }
+
+                Some(merged)
+            }
+            (_, our_row, _) => {
+                conflicts.push(MergeConflict::Row { id: key.0 });
+                our_row.cloned()
+            }
+        };
+
+        rows.extend(merged);
+    }
+
+    Ok(MergeResult {
+        param: Param {
+            param_type: ours.param_type.clone(),
+            big_endian: ours.big_endian,
+            format_2d: ours.format_2d,
+            format_2e: ours.format_2e,
+            paramdef_format_version: ours.paramdef_format_version,
+            unk06: ours.unk06,
+            paramdef_data_version: ours.paramdef_data_version,
+            row_size: ours.row_size,
+            rows,
+        },
+        conflicts,
+    })
+}
+
+fn same_row(a: Option<&ParamRow>, b: Option<&ParamRow>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => a.data == b.data && same_name(a, b),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+fn same_name(a: &ParamRow, b: &ParamRow) -> bool {
+    a.name == b.name
+}
+
+#[cfg(test)]
+mod test {
+    use super::{merge, MergeConflict};
+    use crate::param::{Param, ParamDef, ParamRow, ParamValue};
+
+    const DEF: &str = r#"<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <Fields>
+    <Field Def="u8 a" />
+    <Field Def="u8 b" />
+    <Field Def="u8 c" />
+  </Fields>
+</PARAMDEF>"#;
+
+    // The last byte of each row isn't covered by a field.
+    const PADDED_DEF: &str = r#"<PARAMDEF XmlVersion="3">
+  <ParamType>TEST_PARAM_ST</ParamType>
+  <Fields>
+    <Field Def="u8 a" />
+    <Field Def="u8 b" />
+  </Fields>
+</PARAMDEF>"#;
+
+    fn param(rows: &[(i32, [u8; 3])]) -> Param {
+        Param {
+            param_type: "TEST_PARAM_ST".to_string(),
+            big_endian: false,
+            format_2d: 0,
+            format_2e: 0,
+            paramdef_format_version: 0,
+            unk06: 0,
+            paramdef_data_version: 0,
+            row_size: 3,
+            rows: rows
+                .iter()
+                .map(|(id, data)| ParamRow {
+                    id: *id,
+                    name: None,
+                    data: data.to_vec(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    pub fn merges_fields_and_reports_conflicts() {
+        let def = ParamDef::from_xml(DEF).unwrap();
+        let base = param(&[(1, [0, 0, 0]), (2, [0, 0, 0])]);
+        let ours = param(&[(1, [1, 0, 5]), (2, [0, 0, 0])]);
+        let theirs = param(&[(1, [0, 2, 6]), (3, [3, 3, 3])]);
+
+        let result = merge(&base, &ours, &theirs, &def).unwrap();
+        let rows = result
+            .param
+            .rows
+            .iter()
+            .map(|row| (row.id, row.data.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(rows, vec![(1, vec![1, 2, 5]), (3, vec![3, 3, 3])]);
+        assert_eq!(
+            result.conflicts,
+            vec![MergeConflict::Field {
+                id: 1,
+                field: "c".to_string(),
+                base: ParamValue::U8(0),
+                ours: ParamValue::U8(5),
+                theirs: ParamValue::U8(6),
+            }]
+        );
+    }
+
+    #[test]
+    pub fn merges_renames_and_reports_conflicts() {
+        let def = ParamDef::from_xml(DEF).unwrap();
+        let named = |names: [&str; 2]| {
+            let mut param = param(&[(1, [0, 0, 0]), (2, [0, 0, 0])]);
+            for (row, name) in param.rows.iter_mut().zip(names) {
+                row.name = Some(name.to_string());
+            }
+            param
+        };
+        let base = named(["Base", "Base"]);
+        let ours = named(["Base", "Ours"]);
+        let theirs = named(["Theirs", "Theirs"]);
+
+        let result = merge(&base, &ours, &theirs, &def).unwrap();
+        let names = result
+            .param
+            .rows
+            .iter()
+            .map(|row| row.name.as_deref())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec![Some("Theirs"), Some("Ours")]);
+        assert_eq!(
+            result.conflicts,
+            vec![MergeConflict::Name {
+                id: 2,
+                base: Some("Base".to_string()),
+                ours: Some("Ours".to_string()),
+                theirs: Some("Theirs".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    pub fn merges_bytes_outside_fields() {
+        let def = ParamDef::from_xml(PADDED_DEF).unwrap();
+        let base = param(&[(1, [0, 0, 0]), (2, [0, 0, 0])]);
+        let ours = param(&[(1, [1, 0, 0]), (2, [0, 0, 5])]);
+        let theirs = param(&[(1, [0, 0, 4]), (2, [0, 2, 6])]);
+
+        let result = merge(&base, &ours, &theirs, &def).unwrap();
+        let rows = result
+            .param
+            .rows
+            .iter()
+            .map(|row| (row.id, row.data.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(rows, vec![(1, vec![1, 0, 4]), (2, vec![0, 2, 5])]);
+        assert_eq!(result.conflicts, vec![MergeConflict::Row { id: 2 }]);
+    }
+}
@@ -0,0 +1,478 @@
+#[cfg(not(feature = "std"))]
+use alloc::{boxed::Box, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::{
+    collections::HashMap,
+    io::{self, Read, Seek, SeekFrom, Write},
+};
+
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+#[cfg(feature = "std")]
+use byteorder::{BE, LE};
+use thiserror::Error;
+
+#[cfg(feature = "std")]
+use crate::param::def::{read_value, write_value, ParamFieldLocation};
+use crate::{error::FormatError, io_ext::ByteReader};
+
+#[cfg(feature = "std")]
+pub mod codegen;
+#[cfg(feature = "std")]
+pub mod def;
+#[cfg(feature = "std")]
+pub mod diff;
+#[cfg(feature = "std")]
+pub mod merge;
+#[cfg(feature = "std")]
+pub mod paramdex;
+#[cfg(feature = "std")]
+pub mod query;
+#[cfg(feature = "std")]
+pub mod regulation;
+
+#[cfg(feature = "std")]
+pub use self::{
+    def::{ParamDef, ParamDefError, ParamField, ParamFieldType, ParamValue},
+    diff::{diff, FieldChange, NameChange, ParamDiff, RowDiff, RowDiffKind},
+    merge::{merge, MergeConflict, MergeResult},
+    paramdex::{Paramdex, ParamdexError},
+    query::{ParamRowView, ParamSet, ParamTable, TableRow},
+    regulation::{Regulation, RegulationError},
+};
+
+#[derive(Debug, Error)]
+pub enum ParamError {
+    #[cfg(feature = "std")]
+    #[error("Could not read param: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Could not read param: {0}")]
+    Format(Box<FormatError>),
+
+    #[error("Row {0} has an invalid data offset")]
+    InvalidRowOffset(i32),
+
+    #[error("Param {0} is not in the byte order its row view reads")]
+    ByteOrderMismatch(String),
+}
+
+impl From<FormatError> for ParamError {
+    fn from(value: FormatError) -> Self {
+        Self::Format(Box::new(value))
+    }
+}
+
+/// Flags stored at 0x2D of the param header describing how offsets are encoded.
+pub const FORMAT_FLAG_01: u8 = 0x01;
+pub const FORMAT_FLAG_INT_DATA_OFFSET: u8 = 0x02;
+pub const FORMAT_FLAG_LONG_DATA_OFFSET: u8 = 0x04;
+pub const FORMAT_FLAG_OFFSET_PARAM_TYPE: u8 = 0x80;
+
+/// Flags stored at 0x2E of the param header.
+pub const FORMAT_FLAG_UNICODE_ROW_NAMES: u8 = 0x01;
+
+/// A table of fixed-size rows keyed by ID, typically found in regulation.bin or gameparam.parambnd.
+///
+/// Row data is kept as raw bytes, a [ParamDef] is needed to interpret the fields of each row.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Param {
+    pub param_type: String,
+    pub big_endian: bool,
+    pub format_2d: u8,
+    pub format_2e: u8,
+    pub paramdef_format_version: u8,
+    pub unk06: i16,
+    pub paramdef_data_version: i16,
+    pub row_size: usize,
+    pub rows: Vec<ParamRow>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParamRow {
+    pub id: i32,
+    pub name: Option<String>,
+    pub data: Vec<u8>,
+}
+
+struct ParamRowHeader {
+    id: i32,
+    data_offset: u64,
+    name_offset: u64,
+}
+
+impl Param {
+    /// Read a param of either endianness. Big endian params come from PS3 and Xbox 360 builds.
+    #[cfg(feature = "std")]
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, ParamError> {
+        let mut bytes = Vec::new();
+        r.seek(SeekFrom::Start(0))?;
+        r.read_to_end(&mut bytes)?;
+
+        Self::parse(&bytes)
+    }
+
+    /// Parse a param from its bytes, which works without `std`.
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParamError> {
+        let mut r = ByteReader::new("PARAM", bytes);
+        r.set_position(0x2C);
+        let big_endian = r.read_u8()? == 0xFF;
+
+        if big_endian {
+            Self::read::<BigEndian>(&mut r, big_endian)
+        } else {
+            Self::read::<LittleEndian>(&mut r, big_endian)
+        }
+    }
+
+    fn read<O: ByteOrder>(r: &mut ByteReader<'_>, big_endian: bool) -> Result<Self, ParamError> {
+        let format_2d = r.read_u8()?;
+        let format_2e = r.read_u8()?;
+        let paramdef_format_version = r.read_u8()?;
+
+        r.set_position(0);
+        let strings_offset = r.read_u32::<O>()? as u64;
+        let _data_start = r.read_u16::<O>()?;
+        let unk06 = r.read_i16::<O>()?;
+        let paramdef_data_version = r.read_i16::<O>()?;
+        let row_count = r.read_u16::<O>()?;
+
+        let long_offsets = format_2d & FORMAT_FLAG_LONG_DATA_OFFSET != 0;
+        let param_type = if format_2d & FORMAT_FLAG_OFFSET_PARAM_TYPE != 0 {
+            r.read_bytes(4)?;
+            let param_type_offset = r.read_u64::<O>()?;
+            r.read_bytes(0x14)?;
+
+            let current = r.position();
+            r.set_position(param_type_offset);
+            let param_type = String::from_utf8_lossy(r.read_null_terminated()?).into_owned();
+            r.set_position(current);
+
+            param_type
+        } else {
+            fixed_ascii(r.read_bytes(0x20)?)
+        };
+
+        // Endianness and format flags, already read above.
+        r.read_bytes(4)?;
+
+        if format_2d & FORMAT_FLAG_01 != 0 && format_2d & FORMAT_FLAG_INT_DATA_OFFSET != 0 {
+            let _data_start = r.read_u32::<O>()?;
+            r.read_bytes(0xC)?;
+        } else if long_offsets {
+            let _data_start = r.read_u64::<O>()?;
+            r.read_bytes(0x8)?;
+        }
+
+        r.set_section("row header");
+        let mut headers = Vec::with_capacity(row_count as usize);
+        for _ in 0..row_count {
+            let id = r.read_i32::<O>()?;
+            let (data_offset, name_offset) = if long_offsets {
+                // Usually zero, but some DS2 SotFS params have garbage here.
+                let _unk04 = r.read_u32::<O>()?;
+                (r.read_u64::<O>()?, r.read_u64::<O>()?)
+            } else {
+                (r.read_u32::<O>()? as u64, r.read_u32::<O>()? as u64)
+            };
+
+            headers.push(ParamRowHeader {
+                id,
+                data_offset,
+                name_offset,
+            });
+        }
+
+        // The strings offset in the header isn't reliable, so the row size is preferably derived
+        // from the distance between the first two rows.
+        let row_size = match &headers[..] {
+            [first, second, ..] => second.data_offset.saturating_sub(first.data_offset),
+            [first] => headers
+                .iter()
+                .map(|header| header.name_offset)
+                .filter(|offset| *offset > first.data_offset)
+                .min()
+                .unwrap_or(strings_offset)
+                .saturating_sub(first.data_offset),
+            [] => 0,
+        } as usize;
+
+        let unicode = format_2e & FORMAT_FLAG_UNICODE_ROW_NAMES != 0;
+        let mut rows = Vec::with_capacity(headers.len());
+        for header in headers {
+            if header.data_offset == 0 {
+                return Err(ParamError::InvalidRowOffset(header.id));
+            }
+
+            r.set_section("row data");
+            r.set_position(header.data_offset);
+            let data = r.read_bytes(row_size)?.to_vec();
+
+            let name = if header.name_offset != 0 {
+                r.set_section("row name");
+                r.set_position(header.name_offset);
+                let name = if unicode {
+                    r.read_utf16::<O>()?
+                } else {
+                    let (name, _, _) = encoding_rs::SHIFT_JIS.decode(r.read_null_terminated()?);
+                    name.into_owned()
+                };
+
+                Some(name).filter(|name| !name.is_empty())
+            } else {
+                None
+            };
+
+            rows.push(ParamRow {
+                id: header.id,
+                name,
+                data,
+            });
+        }
+
+        Ok(Self {
+            param_type,
+            big_endian,
+            format_2d,
+            format_2e,
+            paramdef_format_version,
+            unk06,
+            paramdef_data_version,
+            row_size,
+            rows,
+        })
+    }
+
+    /// Write the param in the byte order and offset format it was read with. Every row gets a
+    /// name, empty for unnamed rows, as the later games store them.
+    #[cfg(feature = "std")]
+    pub fn write(&self, w: &mut impl Write) -> Result<(), ParamError> {
+        let bytes = if self.big_endian {
+            self.to_bytes_with::<BE>()
+        } else {
+            self.to_bytes_with::<LE>()
+        };
+        w.write_all(&bytes)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "std")]
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ParamError> {
+        let mut out = Vec::new();
+        self.write(&mut out)?;
+
+        Ok(out)
+    }
+
+    #[cfg(feature = "std")]
+    fn to_bytes_with<O: ByteOrder>(&self) -> Vec<u8> {
+        let long_offsets = self.format_2d & FORMAT_FLAG_LONG_DATA_OFFSET != 0;
+        let int_offsets = self.format_2d & FORMAT_FLAG_01 != 0
+            && self.format_2d & FORMAT_FLAG_INT_DATA_OFFSET != 0;
+        let offset_param_type = self.format_2d & FORMAT_FLAG_OFFSET_PARAM_TYPE != 0;
+
+        let mut out = vec![0; 0x30];
+        O::write_i16(&mut out[0x6..], self.unk06);
+        O::write_i16(&mut out[0x8..], self.paramdef_data_version);
+        O::write_u16(&mut out[0xA..], self.rows.len() as u16);
+        if !offset_param_type {
+            let length = self.param_type.len().min(0x20);
+            out[0xC..0xC + length].copy_from_slice(&self.param_type.as_bytes()[..length]);
+        }
+        out[0x2C] = if self.big_endian { 0xFF } else { 0x00 };
+        out[0x2D] = self.format_2d;
+        out[0x2E] = self.format_2e;
+        out[0x2F] = self.paramdef_format_version;
+        if int_offsets || long_offsets {
+            out.resize(0x40, 0);
+        }
+
+        let headers_start = out.len();
+        let header_size = if long_offsets { 0x18 } else { 0xC };
+        out.resize(headers_start + self.rows.len() * header_size, 0);
+        // As the games lay them out, though the padding doesn't seem to matter.
+        if self.format_2d == FORMAT_FLAG_01 {
+            out.resize(out.len() + 0x20, 0);
+        }
+
+        let data_start = out.len();
+        if int_offsets {
+            O::write_u32(&mut out[0x30..], data_start as u32);
+        } else if long_offsets {
+            O::write_u64(&mut out[0x30..], data_start as u64);
+        } else {
+            O::write_u16(&mut out[0x4..], data_start as u16);
+        }
+
+        let mut data_offsets = Vec::with_capacity(self.rows.len());
+        for row in &self.rows {
+            data_offsets.push(out.len() as u64);
+            out.extend(&row.data);
+        }
+
+        let strings_offset = out.len();
+        O::write_u32(&mut out[0x0..], strings_offset as u32);
+        if offset_param_type {
+            O::write_u64(&mut out[0x10..], strings_offset as u64);
+            out.extend(self.param_type.as_bytes());
+            out.push(0);
+        }
+
+        let unicode = self.format_2e & FORMAT_FLAG_UNICODE_ROW_NAMES != 0;
+        for (index, row) in self.rows.iter().enumerate() {
+            let name_offset = out.len() as u64;
+            let name = row.name.as_deref().unwrap_or_default();
+            if unicode {
+                for unit in name.encode_utf16().chain([0]) {
+                    let mut bytes = [0; 2];
+                    O::write_u16(&mut bytes, unit);
+                    out.extend(bytes);
+                }
+            } else {
+                let (name, _, _) = encoding_rs::SHIFT_JIS.encode(name);
+                out.extend(name.iter());
+                out.push(0);
+            }
+
+            let header = &mut out[headers_start + index * header_size..];
+            O::write_i32(header, row.id);
+            if long_offsets {
+                O::write_u64(&mut header[0x8..], data_offsets[index]);
+                O::write_u64(&mut header[0x10..], name_offset);
+            } else {
+                O::write_u32(&mut header[0x4..], data_offsets[index] as u32);
+                O::write_u32(&mut header[0x8..], name_offset as u32);
+            }
+        }
+
+        out
+    }
+
+    pub fn row(&self, id: i32) -> Option<&ParamRow> {
+        self.rows.iter().find(|row| row.id == id)
+    }
+
+    pub fn row_mut(&mut self, id: i32) -> Option<&mut ParamRow> {
+        self.rows.iter_mut().find(|row| row.id == id)
+    }
+
+    /// Decode a single field of a row's data using this param's byte order.
+    #[cfg(feature = "std")]
+    pub fn read_field(
+        &self,
+        field: &ParamField,
+        location: ParamFieldLocation,
+        data: &[u8],
+    ) -> Result<ParamValue, ParamDefError> {
+        if self.big_endian {
+            read_value::<BE>(field, location, data)
+        } else {
+            read_value::<LE>(field, location, data)
+        }
+    }
+
+    /// Encode a single field into a row's data using this param's byte order.
+    #[cfg(feature = "std")]
+    pub fn write_field(
+        &self,
+        field: &ParamField,
+        location: ParamFieldLocation,
+        value: &ParamValue,
+        data: &mut [u8],
+    ) -> Result<(), ParamDefError> {
+        if self.big_endian {
+            write_value::<BE>(field, location, value, data)
+        } else {
+            write_value::<LE>(field, location, value, data)
+        }
+    }
+
+    /// Decode every field of a row's data.
+    #[cfg(feature = "std")]
+    pub fn read_row(&self, def: &ParamDef, data: &[u8]) -> Result<Vec<ParamValue>, ParamDefError> {
+        if self.big_endian {
+            def.read_row::<BE>(data)
+        } else {
+            def.read_row::<LE>(data)
+        }
+    }
+
+    /// Key every row by its ID and the order it appears in amongst rows sharing that ID.
+    #[cfg(feature = "std")]
+    pub(crate) fn rows_by_key(&self) -> HashMap<(i32, usize), &ParamRow> {
+        let mut occurrences = HashMap::<i32, usize>::new();
+
+        self.rows
+            .iter()
+            .map(|row| {
+                let occurrence = occurrences.entry(row.id).or_default();
+                let key = (row.id, *occurrence);
+                *occurrence += 1;
+
+                (key, row)
+            })
+            .collect()
+    }
+}
+
+fn fixed_ascii(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use crate::param::{
+        Param, ParamRow, FORMAT_FLAG_01, FORMAT_FLAG_INT_DATA_OFFSET, FORMAT_FLAG_LONG_DATA_OFFSET,
+        FORMAT_FLAG_OFFSET_PARAM_TYPE, FORMAT_FLAG_UNICODE_ROW_NAMES,
+    };
+
+    #[test]
+    pub fn round_trips_each_offset_format() {
+        let formats = [
+            (false, 0, 0),
+            (true, FORMAT_FLAG_01 | FORMAT_FLAG_INT_DATA_OFFSET, 0),
+            (
+                false,
+                FORMAT_FLAG_01
+                    | FORMAT_FLAG_INT_DATA_OFFSET
+                    | FORMAT_FLAG_LONG_DATA_OFFSET
+                    | FORMAT_FLAG_OFFSET_PARAM_TYPE,
+                FORMAT_FLAG_UNICODE_ROW_NAMES,
+            ),
+        ];
+
+        for (big_endian, format_2d, format_2e) in formats {
+            let param = Param {
+                param_type: "EQUIP_PARAM_WEAPON_ST".to_string(),
+                big_endian,
+                format_2d,
+                format_2e,
+                paramdef_format_version: 203,
+                unk06: 0,
+                paramdef_data_version: 2,
+                row_size: 4,
+                rows: vec![
+                    ParamRow {
+                        id: 1000000,
+                        name: Some("Dagger".to_string()),
+                        data: vec![1, 2, 3, 4],
+                    },
+                    ParamRow {
+                        id: 1010000,
+                        name: None,
+                        data: vec![5, 6, 7, 8],
+                    },
+                ],
+            };
+
+            let read = Param::parse(&param.to_bytes().unwrap()).unwrap();
+            assert_eq!(read.param_type, param.param_type);
+            assert_eq!(read.big_endian, big_endian);
+            assert_eq!(read.row_size, param.row_size);
+            assert_eq!(read.rows, param.rows);
+        }
+    }
+}
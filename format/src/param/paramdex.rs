@@ -0,0 +1,132 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use thiserror::Error;
+
+use crate::param::{Param, ParamDef, ParamDefError};
+
+#[derive(Debug, Error)]
+pub enum ParamdexError {
+    #[error("Could not read paramdex file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Could not parse paramdef {path}: {source}")]
+    Def {
+        path: PathBuf,
+        source: ParamDefError,
+    },
+}
+
+/// Paramdefs and row names loaded from a game directory of the community Paramdex repository.
+///
+/// A game directory contains a `Defs` folder of XML paramdefs (keyed by their param type) and a
+/// `Names` folder of `<param name>.txt` files with one `<row id> <row name>` entry per line.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Paramdex {
+    defs: HashMap<String, ParamDef>,
+    names: HashMap<String, HashMap<i32, String>>,
+}
+
+impl Paramdex {
+    /// Load all defs and row names from a Paramdex game directory, e.g. `Paramdex/ER`.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ParamdexError> {
+        let path = path.as_ref();
+        let mut paramdex = Self::default();
+
+        for def_path in files_with_extension(&path.join("Defs"), "xml")? {
+            let xml = fs::read_to_string(&def_path)?;
+            let def = ParamDef::from_xml(&xml).map_err(|source| ParamdexError::Def {
+                path: def_path,
+                source,
+            })?;
+
+            paramdex.insert_def(def);
+        }
+
+        for names_path in files_with_extension(&path.join("Names"), "txt")? {
+            let Some(param_name) = names_path.file_stem().and_then(|stem| stem.to_str()) else {
+                continue;
+            };
+
+            let contents = fs::read_to_string(&names_path)?;
+            paramdex.insert_row_names(param_name.to_string(), parse_row_names(&contents));
+        }
+
+        Ok(paramdex)
+    }
+
+    pub fn insert_def(&mut self, def: ParamDef) {
+        self.defs.insert(def.param_type.clone(), def);
+    }
+
+    pub fn insert_row_names(&mut self, param_name: String, names: HashMap<i32, String>) {
+        self.names.entry(param_name).or_default().extend(names);
+    }
+
+    /// Find the paramdef for a param type such as `EQUIP_PARAM_WEAPON_ST`.
+    pub fn def(&self, param_type: &str) -> Option<&ParamDef> {
+        self.defs.get(param_type)
+    }
+
+    pub fn defs(&self) -> impl Iterator<Item = &ParamDef> {
+        self.defs.values()
+    }
+
+    /// Find the community name of a row in the param named `param_name` (e.g. `EquipParamWeapon`).
+    pub fn row_name(&self, param_name: &str, id: i32) -> Option<&str> {
+        self.names
+            .get(param_name)
+            .and_then(|names| names.get(&id))
+            .map(String::as_str)
+    }
+
+    /// Fill in the names of any unnamed rows in `param` from the known row names.
+    pub fn apply_row_names(&self, param_name: &str, param: &mut Param) {
+        let Some(names) = self.names.get(param_name) else {
+            return;
+        };
+
+        for row in param.rows.iter_mut().filter(|row| row.name.is_none()) {
+            row.name = names.get(&row.id).cloned();
+        }
+    }
+}
+
+/// Parse a Paramdex row name list, where each line is a row ID optionally followed by a name.
+pub fn parse_row_names(contents: &str) -> HashMap<i32, String> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (id, name) = line.split_once(' ').unwrap_or((line, ""));
+            let name = name.trim();
+
+            match id.parse::<i32>() {
+                Ok(id) if !name.is_empty() => Some((id, name.to_string())),
+                _ => None,
+            }
+        })
+        .collect()
+}
+
+fn files_with_extension(dir: &Path, extension: &str) -> Result<Vec<PathBuf>, io::Error> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths = fs::read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    paths.retain(|path| {
+        path.extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+    });
+    paths.sort();
+
+    Ok(paths)
+}
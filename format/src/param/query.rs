@@ -0,0 +1,212 @@
+use std::{collections::HashMap, marker::PhantomData, ops::Deref};
+
+use crate::param::{Param, ParamError, ParamRow};
+
+/// A typed view over the data of a single param row, such as the structs produced by
+/// [generate_tables](crate::param::codegen::generate_tables).
+pub trait ParamRowView<'a>: Sized + 'a {
+    /// The param type whose rows this view can decode, e.g. `EQUIP_PARAM_WEAPON_ST`.
+    const PARAM_TYPE: &'static str;
+
+    /// Whether the view reads rows big-endian, like the params of PS3 and Xbox 360 games.
+    const BIG_ENDIAN: bool;
+
+    fn from_data(data: &'a [u8]) -> Option<Self>;
+}
+
+/// A collection of params keyed by their param type.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ParamSet {
+    params: HashMap<String, Param>,
+}
+
+impl ParamSet {
+    pub fn new(params: impl IntoIterator<Item = Param>) -> Self {
+        Self {
+            params: params
+                .into_iter()
+                .map(|param| (param.param_type.clone(), param))
+                .collect(),
+        }
+    }
+
+    pub fn insert(&mut self, param: Param) -> Option<Param> {
+        self.params.insert(param.param_type.clone(), param)
+    }
+
+    pub fn param(&self, param_type: &str) -> Option<&Param> {
+        self.params.get(param_type)
+    }
+
+    pub fn param_mut(&mut self, param_type: &str) -> Option<&mut Param> {
+        self.params.get_mut(param_type)
+    }
+
+    pub fn params(&self) -> impl Iterator<Item = &Param> {
+        self.params.values()
+    }
+
+    /// Open the param with the type decoded by `T` as an indexed, typed table, if the set has it.
+    pub fn table<'a, T: ParamRowView<'a>>(
+        &'a self,
+    ) -> Option<Result<ParamTable<'a, T>, ParamError>> {
+        self.param(T::PARAM_TYPE).map(ParamTable::new)
+    }
+}
+
+/// A param with an index from row ID to row, yielding typed views of each row.
+pub struct ParamTable<'a, T> {
+    param: &'a Param,
+    index: HashMap<i32, usize>,
+    _row: PhantomData<T>,
+}
+
+/// A row yielded from a [ParamTable], dereferencing to its typed view.
+#[derive(Clone, Copy, Debug)]
+pub struct TableRow<'a, T> {
+    pub id: i32,
+    pub name: Option<&'a str>,
+    pub view: T,
+}
+
+impl<'a, T> Deref for TableRow<'a, T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.view
+    }
+}
+
+impl<'a, T: ParamRowView<'a>> ParamTable<'a, T> {
+    /// Index the rows of `param`, which must be in the byte order `T` reads.
+    pub fn new(param: &'a Param) -> Result<Self, ParamError> {
+        if param.big_endian != T::BIG_ENDIAN {
+            return Err(ParamError::ByteOrderMismatch(param.param_type.clone()));
+        }
+
+        // With duplicate IDs the game resolves to the first row, so keep the first occurrence.
+        let mut index = HashMap::with_capacity(param.rows.len());
+        for (position, row) in param.rows.iter().enumerate() {
+            index.entry(row.id).or_insert(position);
+        }
+
+        Ok(Self {
+            param,
+            index,
+            _row: PhantomData,
+        })
+    }
+
+    pub fn param(&self) -> &'a Param {
+        self.param
+    }
+
+    pub fn len(&self) -> usize {
+        self.param.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.param.rows.is_empty()
+    }
+
+    pub fn contains(&self, id: i32) -> bool {
+        self.index.contains_key(&id)
+    }
+
+    /// Look up a row by ID without scanning the param.
+    pub fn get(&self, id: i32) -> Option<TableRow<'a, T>> {
+        let row = &self.param.rows[*self.index.get(&id)?];
+        Self::table_row(row)
+    }
+
+    pub fn raw(&self, id: i32) -> Option<&'a ParamRow> {
+        Some(&self.param.rows[*self.index.get(&id)?])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = TableRow<'a, T>> + 'a {
+        self.param.rows.iter().filter_map(Self::table_row)
+    }
+
+    pub fn filter<P>(&self, predicate: P) -> impl Iterator<Item = TableRow<'a, T>> + 'a
+    where
+        P: FnMut(&TableRow<'a, T>) -> bool + 'a,
+    {
+        self.iter().filter(predicate)
+    }
+
+    /// Build a secondary index from a key computed for each row to the IDs of rows sharing it.
+    pub fn group_by<K, F>(&self, mut key: F) -> HashMap<K, Vec<i32>>
+    where
+        K: std::hash::Hash + Eq,
+        F: FnMut(&TableRow<'a, T>) -> K,
+    {
+        let mut groups = HashMap::<K, Vec<i32>>::new();
+        for row in self.iter() {
+            groups.entry(key(&row)).or_default().push(row.id);
+        }
+
+        groups
+    }
+
+    fn table_row(row: &'a ParamRow) -> Option<TableRow<'a, T>> {
+        Some(TableRow {
+            id: row.id,
+            name: row.name.as_deref(),
+            view: T::from_data(&row.data)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ParamRowView, ParamSet};
+    use crate::param::{Param, ParamError, ParamRow};
+
+    struct TestRow<'a>(&'a [u8]);
+
+    impl<'a> ParamRowView<'a> for TestRow<'a> {
+        const PARAM_TYPE: &'static str = "TEST_PARAM_ST";
+        const BIG_ENDIAN: bool = false;
+
+        fn from_data(data: &'a [u8]) -> Option<Self> {
+            Some(Self(data))
+        }
+    }
+
+    fn param(big_endian: bool) -> Param {
+        Param {
+            param_type: "TEST_PARAM_ST".to_string(),
+            big_endian,
+            format_2d: 0,
+            format_2e: 0,
+            paramdef_format_version: 0,
+            unk06: 0,
+            paramdef_data_version: 0,
+            row_size: 4,
+            rows: vec![ParamRow {
+                id: 10,
+                name: None,
+                data: vec![1, 0, 0, 0],
+            }],
+        }
+    }
+
+    #[test]
+    pub fn opens_params_in_the_view_byte_order() {
+        let params = ParamSet::new([param(false)]);
+        let table = params.table::<TestRow>().unwrap().unwrap();
+
+        assert_eq!(table.get(10).unwrap().0, &[1, 0, 0, 0]);
+    }
+
+    #[test]
+    pub fn rejects_params_in_another_byte_order() {
+        let params = ParamSet::new([param(true)]);
+
+        assert!(matches!(
+            params.table::<TestRow>(),
+            Some(Err(ParamError::ByteOrderMismatch(_)))
+        ));
+    }
+}
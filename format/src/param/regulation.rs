@@ -0,0 +1,203 @@
+use std::io::{self, Cursor};
+
+use aes::{
+    cipher::{block_padding::NoPadding, BlockDecryptMut, BlockEncryptMut, KeyIvInit, StreamCipher},
+    Aes128, Aes256,
+};
+use thiserror::Error;
+
+use crate::{
+    bnd4::BND4,
+    param::{Param, ParamError},
+};
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+type Aes256CbcDec = cbc::Decryptor<Aes256>;
+type Aes256CbcEnc = cbc::Encryptor<Aes256>;
+
+/// AES key used to encrypt `enc_regulation.bnd.dcx` in Dark Souls II and Scholar of the First Sin.
+pub const DS2_REGULATION_KEY: [u8; 16] = [
+    0x40, 0x17, 0x81, 0x30, 0xDF, 0x0A, 0x94, 0x54, 0x33, 0x09, 0xE1, 0x71, 0xEC, 0xBF, 0x25, 0x4C,
+];
+
+/// AES key used to encrypt `Data0.bdt`, the regulation of Dark Souls III.
+pub const DS3_REGULATION_KEY: [u8; 32] = *b"ds3#jn/8_7(rsY9pg55GFN7VFL#+3n/)";
+
+/// AES key used to encrypt the `regulation.bin` of Elden Ring.
+pub const ER_REGULATION_KEY: [u8; 32] = [
+    0x99, 0xBF, 0xFC, 0x36, 0x6A, 0x6B, 0xC8, 0xC6, 0xF5, 0x82, 0x7D, 0x09, 0x36, 0x02, 0xD6, 0x76,
+    0xC4, 0x28, 0x92, 0xA0, 0x1C, 0x20, 0x7F, 0xB0, 0x24, 0xD3, 0xAF, 0x4E, 0x49, 0x3F, 0xEF, 0x99,
+];
+
+const DS2_REGULATION_HEADER_SIZE: usize = 32;
+const IV_SIZE: usize = 16;
+
+#[derive(Debug, Error)]
+pub enum RegulationError {
+    #[error("Could not read regulation binder: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Regulation file is too short to contain an encryption header")]
+    TooShort,
+
+    #[error("Encrypted regulation is {0:#x} bytes, which isn't a whole number of AES blocks")]
+    Unaligned(usize),
+
+    #[error("Could not write param {name}: {source}")]
+    Write { name: String, source: ParamError },
+
+    #[error("Could not parse param {name}: {source}")]
+    Param { name: String, source: ParamError },
+
+    #[error("Param {0} was not found in the regulation")]
+    NotFound(String),
+}
+
+/// The binder of params shipped as a game's regulation file.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Regulation {
+    bnd: BND4,
+}
+
+impl Regulation {
+    /// Read an unencrypted regulation binder.
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Self, RegulationError> {
+        let bnd = BND4::from_reader(&mut Cursor::new(bytes))?;
+
+        Ok(Self { bnd })
+    }
+
+    /// Decrypt and read the contents of a Dark Souls II `enc_regulation.bnd.dcx`.
+    pub fn from_ds2_encrypted(bytes: &[u8]) -> Result<Self, RegulationError> {
+        Self::from_bytes(decrypt_ds2_regulation(bytes)?)
+    }
+
+    /// Names of every param in this regulation, without their `.param` extension.
+    pub fn param_names(&self) -> impl Iterator<Item = &str> {
+        self.bnd
+            .files
+            .iter()
+            .filter_map(|entry| param_name(&entry.path))
+    }
+
+    /// Parse the param named `name`, e.g. `ItemParam`.
+    pub fn param(&self, name: &str) -> Result<Param, RegulationError> {
+        let entry = self
+            .bnd
+            .files
+            .iter()
+            .find(|entry| param_name(&entry.path).is_some_and(|n| n.eq_ignore_ascii_case(name)))
+            .ok_or_else(|| RegulationError::NotFound(name.to_string()))?;
+
+        Param::from_reader(&mut Cursor::new(self.bnd.file_bytes(entry))).map_err(|source| {
+            RegulationError::Param {
+                name: name.to_string(),
+                source,
+            }
+        })
+    }
+
+    /// Replace the param named `name` with `param`, moving the params after it as needed.
+    pub fn replace_param(&mut self, name: &str, param: &Param) -> Result<(), RegulationError> {
+        let index = self
+            .bnd
+            .files
+            .iter()
+            .position(|entry| param_name(&entry.path).is_some_and(|n| n.eq_ignore_ascii_case(name)))
+            .ok_or_else(|| RegulationError::NotFound(name.to_string()))?;
+
+        let bytes = param.to_bytes().map_err(|source| RegulationError::Write {
+            name: name.to_string(),
+            source,
+        })?;
+        self.bnd.replace_file(index, &bytes)?;
+
+        Ok(())
+    }
+
+    /// The bytes of the regulation binder, before any compression or encryption.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bnd.data
+    }
+}
+
+/// Decrypt a Dark Souls II regulation file.
+///
+/// The file starts with a 32 byte header whose first 11 bytes seed the AES-CTR counter, the
+/// remainder is the encrypted BND4.
+pub fn decrypt_ds2_regulation(bytes: &[u8]) -> Result<Vec<u8>, RegulationError> {
+    if bytes.len() < DS2_REGULATION_HEADER_SIZE {
+        return Err(RegulationError::TooShort);
+    }
+
+    let mut iv = [0u8; 16];
+    iv[0] = 0x80;
+    iv[1..12].copy_from_slice(&bytes[..11]);
+    iv[15] = 1;
+
+    let mut data = bytes[DS2_REGULATION_HEADER_SIZE..].to_vec();
+    let mut cipher = Aes128Ctr::new(&DS2_REGULATION_KEY.into(), &iv.into());
+    cipher.apply_keystream(&mut data);
+
+    Ok(data)
+}
+
+/// Decrypt a `regulation.bin` from Dark Souls III onwards, which is DCX compressed once decrypted.
+///
+/// The file starts with the IV, the remainder is encrypted with AES-256-CBC and padded to a whole
+/// number of blocks.
+pub fn decrypt_regulation(bytes: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, RegulationError> {
+    if bytes.len() < IV_SIZE {
+        return Err(RegulationError::TooShort);
+    }
+
+    let (iv, encrypted) = bytes.split_at(IV_SIZE);
+    if encrypted.len() % IV_SIZE != 0 {
+        return Err(RegulationError::Unaligned(encrypted.len()));
+    }
+
+    let mut data = encrypted.to_vec();
+    Aes256CbcDec::new(key.into(), iv.into())
+        .decrypt_padded_mut::<NoPadding>(&mut data)
+        .expect("data is a whole number of blocks");
+
+    Ok(data)
+}
+
+/// Encrypt `data` as a `regulation.bin`, padding it with zeroes, the reverse of
+/// [decrypt_regulation].
+pub fn encrypt_regulation(data: &[u8], key: &[u8; 32], iv: [u8; 16]) -> Vec<u8> {
+    let mut encrypted = data.to_vec();
+    encrypted.resize(data.len().next_multiple_of(IV_SIZE), 0);
+
+    let length = encrypted.len();
+    Aes256CbcEnc::new(key.into(), &iv.into())
+        .encrypt_padded_mut::<NoPadding>(&mut encrypted, length)
+        .expect("data is a whole number of blocks");
+
+    [&iv[..], &encrypted].concat()
+}
+
+fn param_name(path: &str) -> Option<&str> {
+    let file_name = path.rsplit(['\\', '/']).next()?;
+
+    file_name
+        .strip_suffix(".param")
+        .or_else(|| file_name.strip_suffix(".PARAM"))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::param::regulation::{decrypt_regulation, encrypt_regulation, ER_REGULATION_KEY};
+
+    #[test]
+    pub fn round_trips_encryption() {
+        let data = b"DCX\0 and then some compressed data";
+        let encrypted = encrypt_regulation(data, &ER_REGULATION_KEY, [7; 16]);
+        assert_eq!(encrypted.len(), 16 + 48);
+
+        let decrypted = decrypt_regulation(&encrypted, &ER_REGULATION_KEY).unwrap();
+        assert_eq!(&decrypted[..data.len()], data);
+        assert!(decrypted[data.len()..].iter().all(|byte| *byte == 0));
+    }
+}
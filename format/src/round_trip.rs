@@ -0,0 +1,66 @@
+use alloc::format;
+
+use crate::error::FormatError;
+
+/// How writers lay out a structure that was read from a file.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WriteMode {
+    /// Rebuild the layout from scratch, ordering and packing the data the way the games do.
+    #[default]
+    Canonical,
+
+    /// Reuse the ordering, padding and unknown values of the file the structure was read from, so
+    /// that writing an untouched file reproduces it byte for byte. Edited parts that no longer fit
+    /// the original layout are laid out canonically.
+    Preserve,
+}
+
+/// Check that `written` reproduces `original` byte for byte, reporting the first difference.
+pub fn verify(format: &'static str, original: &[u8], written: &[u8]) -> Result<(), FormatError> {
+    let mismatch = original
+        .iter()
+        .zip(written)
+        .position(|(expected, actual)| expected != actual);
+
+    match mismatch {
+        Some(offset) => Err(FormatError::malformed(
+            format,
+            "round trip",
+            offset as u64,
+            format!(
+                "wrote {:#04x}, expected {:#04x}",
+                written[offset], original[offset]
+            ),
+        )),
+        None if original.len() != written.len() => Err(FormatError::malformed(
+            format,
+            "round trip",
+            original.len().min(written.len()) as u64,
+            format!(
+                "wrote {:#x} bytes, expected {:#x}",
+                written.len(),
+                original.len()
+            ),
+        )),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{error::FormatError, round_trip::verify};
+
+    #[test]
+    pub fn reports_first_difference() {
+        assert!(verify("FMG", &[1, 2, 3], &[1, 2, 3]).is_ok());
+
+        match verify("FMG", &[1, 2, 3], &[1, 4, 3]) {
+            Err(FormatError::Malformed { offset, .. }) => assert_eq!(offset, 1),
+            result => panic!("unexpected result {result:?}"),
+        }
+        match verify("FMG", &[1, 2, 3], &[1, 2]) {
+            Err(FormatError::Malformed { offset, .. }) => assert_eq!(offset, 2),
+            result => panic!("unexpected result {result:?}"),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use byteorder::{ByteOrder, LE};
+
+use crate::save::{SaveEntry, SaveError, SaveGame};
+
+/// Offset of the item handle map in an Elden Ring character slot, after its version, map ID and
+/// some unknown data.
+const ER_GA_ITEMS_OFFSET: usize = 0x20;
+const ER_GA_ITEM_COUNT: usize = 0x1400;
+
+/// Item handles are sized by their category, in the upper nibble.
+const ER_HANDLE_CATEGORY_MASK: u32 = 0xF000_0000;
+const ER_HANDLE_WEAPON: u32 = 0x8000_0000;
+const ER_HANDLE_ARMOR: u32 = 0x9000_0000;
+const ER_GA_ITEM_SIZE: usize = 0x8;
+const ER_GA_ITEM_WEAPON_SIZE: usize = 0x15;
+const ER_GA_ITEM_ARMOR_SIZE: usize = 0x10;
+
+/// Offsets in the player data following the item handle map.
+const ER_HEALTH: usize = 0x8;
+const ER_FP: usize = 0x14;
+const ER_STAMINA: usize = 0x24;
+const ER_ATTRIBUTES: usize = 0x34;
+const ER_LEVEL: usize = 0x60;
+const ER_RUNES: usize = 0x64;
+const ER_RUNES_MEMORY: usize = 0x68;
+const ER_NAME: usize = 0x94;
+const ER_NAME_LENGTH: usize = 0x10;
+const ER_PLAYER_DATA_SIZE: usize = ER_NAME + ER_NAME_LENGTH * 2;
+
+/// The typed contents of a character slot.
+///
+/// Only the fields whose offsets are known are exposed, everything else in the slot is left as it
+/// was when the character is written back.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Character {
+    pub name: String,
+    pub level: u32,
+
+    /// Runes held, and every rune the character has ever acquired.
+    pub runes: u32,
+    pub runes_memory: u32,
+
+    pub health: CharacterPool,
+    pub fp: CharacterPool,
+    pub stamina: CharacterPool,
+    pub attributes: CharacterAttributes,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CharacterPool {
+    pub current: u32,
+    pub max: u32,
+
+    /// The maximum before equipment and effects are applied.
+    pub base_max: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CharacterAttributes {
+    pub vigor: u32,
+    pub mind: u32,
+    pub endurance: u32,
+    pub strength: u32,
+    pub dexterity: u32,
+    pub intelligence: u32,
+    pub faith: u32,
+    pub arcane: u32,
+}
+
+impl CharacterPool {
+    fn read(data: &[u8]) -> Self {
+        Self {
+            current: LE::read_u32(data),
+            max: LE::read_u32(&data[0x4..]),
+            base_max: LE::read_u32(&data[0x8..]),
+        }
+    }
+
+    fn write(&self, data: &mut [u8]) {
+        LE::write_u32_into(&[self.current, self.max, self.base_max], &mut data[..0xC]);
+    }
+}
+
+impl CharacterAttributes {
+    fn read(data: &[u8]) -> Self {
+        let mut values = [0; 8];
+        LE::read_u32_into(&data[..0x20], &mut values);
+        let [vigor, mind, endurance, strength, dexterity, intelligence, faith, arcane] = values;
+
+        Self {
+            vigor,
+            mind,
+            endurance,
+            strength,
+            dexterity,
+            intelligence,
+            faith,
+            arcane,
+        }
+    }
+
+    fn write(&self, data: &mut [u8]) {
+        let values = [
+            self.vigor,
+            self.mind,
+            self.endurance,
+            self.strength,
+            self.dexterity,
+            self.intelligence,
+            self.faith,
+            self.arcane,
+        ];
+        LE::write_u32_into(&values, &mut data[..0x20]);
+    }
+}
+
+impl SaveEntry {
+    /// Read the character held by this slot.
+    pub fn character(&self, game: SaveGame) -> Result<Character, SaveError> {
+        let data = &self.data[self.player_data(game)?..];
+
+        let name = data[ER_NAME..ER_PLAYER_DATA_SIZE]
+            .chunks_exact(2)
+            .map(LE::read_u16)
+            .take_while(|c| *c != 0)
+            .collect::<Vec<_>>();
+
+        Ok(Character {
+            name: String::from_utf16_lossy(&name),
+            level: LE::read_u32(&data[ER_LEVEL..]),
+            runes: LE::read_u32(&data[ER_RUNES..]),
+            runes_memory: LE::read_u32(&data[ER_RUNES_MEMORY..]),
+            health: CharacterPool::read(&data[ER_HEALTH..]),
+            fp: CharacterPool::read(&data[ER_FP..]),
+            stamina: CharacterPool::read(&data[ER_STAMINA..]),
+            attributes: CharacterAttributes::read(&data[ER_ATTRIBUTES..]),
+        })
+    }
+
+    /// Write a character into this slot, its checksum is regenerated when the save is written.
+    pub fn set_character(
+        &mut self,
+        game: SaveGame,
+        character: &Character,
+    ) -> Result<(), SaveError> {
+        let name = character.name.encode_utf16().collect::<Vec<_>>();
+        if name.len() > ER_NAME_LENGTH {
+            return Err(SaveError::NameTooLong(character.name.clone()));
+        }
+
+        let offset = self.player_data(game)?;
+        let data = &mut self.data[offset..];
+
+        let mut name_field = [0u16; ER_NAME_LENGTH];
+        name_field[..name.len()].copy_from_slice(&name);
+        LE::write_u16_into(&name_field, &mut data[ER_NAME..ER_PLAYER_DATA_SIZE]);
+
+        LE::write_u32(&mut data[ER_LEVEL..], character.level);
+        LE::write_u32(&mut data[ER_RUNES..], character.runes);
+        LE::write_u32(&mut data[ER_RUNES_MEMORY..], character.runes_memory);
+        character.health.write(&mut data[ER_HEALTH..]);
+        character.fp.write(&mut data[ER_FP..]);
+        character.stamina.write(&mut data[ER_STAMINA..]);
+        character.attributes.write(&mut data[ER_ATTRIBUTES..]);
+
+        Ok(())
+    }
+
+    /// Find the player data of a character slot, which follows a map of variably sized item
+    /// handles.
+    fn player_data(&self, game: SaveGame) -> Result<usize, SaveError> {
+        if game != SaveGame::EldenRing {
+            return Err(SaveError::UnsupportedGame(game));
+        }
+        if self.slot_index().is_none() {
+            return Err(SaveError::NotACharacter(self.name.clone()));
+        }
+
+        let mut offset = ER_GA_ITEMS_OFFSET;
+        for _ in 0..ER_GA_ITEM_COUNT {
+            let handle = self
+                .data
+                .get(offset..offset + 4)
+                .map(LE::read_u32)
+                .ok_or_else(|| SaveError::TooShort(self.name.clone()))?;
+
+            offset += match handle & ER_HANDLE_CATEGORY_MASK {
+                ER_HANDLE_WEAPON => ER_GA_ITEM_WEAPON_SIZE,
+                ER_HANDLE_ARMOR => ER_GA_ITEM_ARMOR_SIZE,
+                _ => ER_GA_ITEM_SIZE,
+            };
+        }
+
+        if self.data.len() < offset + ER_PLAYER_DATA_SIZE {
+            return Err(SaveError::TooShort(self.name.clone()));
+        }
+
+        Ok(offset)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::save::{
+        character::{ER_GA_ITEMS_OFFSET, ER_GA_ITEM_COUNT, ER_HANDLE_WEAPON},
+        SaveEntry, SaveGame,
+    };
+
+    #[test]
+    pub fn edits_elden_ring_character() {
+        // One weapon handle, the rest empty.
+        let mut data = vec![0u8; 0x10000];
+        data[ER_GA_ITEMS_OFFSET..][..4].copy_from_slice(&(ER_HANDLE_WEAPON | 1).to_le_bytes());
+        let player_data = ER_GA_ITEMS_OFFSET + 0x15 + (ER_GA_ITEM_COUNT - 1) * 8;
+        data[player_data + 0x60..][..4].copy_from_slice(&12u32.to_le_bytes());
+
+        let mut entry = SaveEntry {
+            name: "USER_DATA000".to_string(),
+            data,
+            iv: [0; 16],
+            checksum_valid: true,
+        };
+
+        let mut character = entry.character(SaveGame::EldenRing).unwrap();
+        assert_eq!(character.level, 12);
+
+        character.name = "Tarnished".to_string();
+        character.runes = 50000;
+        character.attributes.arcane = 99;
+        entry
+            .set_character(SaveGame::EldenRing, &character)
+            .unwrap();
+
+        assert_eq!(entry.character(SaveGame::EldenRing).unwrap(), character);
+        assert_eq!(
+            entry.data[player_data + 0x64..][..4],
+            50000u32.to_le_bytes()
+        );
+        assert!(entry.character(SaveGame::DarkSouls3).is_err());
+    }
+}
@@ -0,0 +1,288 @@
+use std::io::{self, Cursor, Read, Write};
+
+use aes::{
+    cipher::{block_padding::NoPadding, BlockDecryptMut, BlockEncryptMut, KeyIvInit},
+    Aes128,
+};
+use md5::{Digest, Md5};
+use thiserror::Error;
+
+pub use self::{
+    character::{Character, CharacterAttributes, CharacterPool},
+    profile::SlotSummary,
+};
+use crate::bnd4::BND4;
+
+mod character;
+mod profile;
+
+type Aes128CbcDec = cbc::Decryptor<Aes128>;
+type Aes128CbcEnc = cbc::Encryptor<Aes128>;
+
+/// AES key used to encrypt the entries of `DS30000.sl2`.
+pub const DS3_SAVE_KEY: [u8; 16] = [
+    0xFD, 0x46, 0x4D, 0x69, 0x5E, 0x69, 0xA3, 0x9A, 0x10, 0xE3, 0x19, 0xA7, 0xAC, 0xE8, 0xB7, 0xFA,
+];
+
+const CHECKSUM_SIZE: usize = 0x10;
+const IV_SIZE: usize = 0x10;
+const AES_BLOCK_SIZE: usize = 0x10;
+
+/// Entries from `USER_DATA000` up to this one hold characters, the rest hold menu and profile
+/// data shared between them.
+const CHARACTER_SLOT_COUNT: usize = 10;
+const ENTRY_NAME_PREFIX: &str = "USER_DATA";
+
+#[derive(Debug, Error)]
+pub enum SaveError {
+    #[error("Could not read save file: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Save entry {0} is too short to hold its checksum")]
+    TooShort(String),
+
+    #[error("Save entry {name} is {length:#x} bytes, which can't be encrypted as whole blocks")]
+    Unaligned { name: String, length: usize },
+
+    #[error("Character slots of {0:?} saves can't be edited")]
+    UnsupportedGame(SaveGame),
+
+    #[error("Save entry {0} doesn't hold a character")]
+    NotACharacter(String),
+
+    #[error("Character slot {0} was not found")]
+    NoSlot(usize),
+
+    #[error("Character name {0} is too long")]
+    NameTooLong(String),
+
+    #[error("Save has no {0} entry")]
+    MissingEntry(String),
+}
+
+/// Games whose saves are a BND4 of checksummed entries.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SaveGame {
+    /// Entries are an MD5 checksum, an IV and the data encrypted with AES-128-CBC.
+    DarkSouls3,
+
+    /// Entries are an MD5 checksum followed by the data in the clear.
+    Sekiro,
+    EldenRing,
+}
+
+impl SaveGame {
+    /// The key entries are encrypted with, if they are encrypted at all.
+    pub fn key(self) -> Option<[u8; 16]> {
+        match self {
+            Self::DarkSouls3 => Some(DS3_SAVE_KEY),
+            Self::Sekiro | Self::EldenRing => None,
+        }
+    }
+}
+
+/// A PC save file (`.sl2`).
+///
+/// Entries are exposed decrypted, and are encrypted and checksummed afresh when the save is
+/// written, so that edited or corrupted entries come out with valid checksums.
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Sl2 {
+    game: SaveGame,
+    bnd: BND4,
+    entries: Vec<SaveEntry>,
+}
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SaveEntry {
+    /// Name of the entry in the binder, e.g. `USER_DATA000`.
+    pub name: String,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub data: Vec<u8>,
+    iv: [u8; IV_SIZE],
+    checksum_valid: bool,
+}
+
+impl SaveEntry {
+    /// Whether the checksum stored with the entry matched its contents when it was read.
+    pub fn checksum_valid(&self) -> bool {
+        self.checksum_valid
+    }
+
+    /// Index of the character slot held by this entry, if it holds one.
+    pub fn slot_index(&self) -> Option<usize> {
+        let index = self.name.strip_prefix(ENTRY_NAME_PREFIX)?.parse().ok()?;
+
+        (index < CHARACTER_SLOT_COUNT).then_some(index)
+    }
+
+    fn decode(name: String, bytes: &[u8], game: SaveGame) -> Result<Self, SaveError> {
+        if bytes.len() < CHECKSUM_SIZE {
+            return Err(SaveError::TooShort(name));
+        }
+
+        let (checksum, checksummed) = bytes.split_at(CHECKSUM_SIZE);
+        let checksum_valid = Md5::digest(checksummed)[..] == *checksum;
+
+        let mut iv = [0u8; IV_SIZE];
+        let data = match game.key() {
+            Some(key) => {
+                let encrypted = checksummed
+                    .get(IV_SIZE..)
+                    .ok_or_else(|| SaveError::TooShort(name.clone()))?;
+                iv.copy_from_slice(&checksummed[..IV_SIZE]);
+
+                if encrypted.len() % AES_BLOCK_SIZE != 0 {
+                    return Err(SaveError::Unaligned {
+                        name,
+                        length: encrypted.len(),
+                    });
+                }
+
+                let mut data = encrypted.to_vec();
+                Aes128CbcDec::new(&key.into(), &iv.into())
+                    .decrypt_padded_mut::<NoPadding>(&mut data)
+                    .expect("data is a whole number of blocks");
+
+                data
+            }
+            None => checksummed.to_vec(),
+        };
+
+        Ok(Self {
+            name,
+            data,
+            iv,
+            checksum_valid,
+        })
+    }
+
+    fn encode(&self, game: SaveGame) -> Result<Vec<u8>, SaveError> {
+        let checksummed = match game.key() {
+            Some(key) => {
+                if self.data.len() % AES_BLOCK_SIZE != 0 {
+                    return Err(SaveError::Unaligned {
+                        name: self.name.clone(),
+                        length: self.data.len(),
+                    });
+                }
+
+                let mut encrypted = self.data.clone();
+                Aes128CbcEnc::new(&key.into(), &self.iv.into())
+                    .encrypt_padded_mut::<NoPadding>(&mut encrypted, self.data.len())
+                    .expect("data is a whole number of blocks");
+
+                [&self.iv[..], &encrypted].concat()
+            }
+            None => self.data.clone(),
+        };
+
+        Ok([&Md5::digest(&checksummed)[..], &checksummed].concat())
+    }
+}
+
+impl Sl2 {
+    pub fn from_reader(r: &mut impl Read, game: SaveGame) -> Result<Self, SaveError> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+
+        let bnd = BND4::from_reader(&mut Cursor::new(bytes))?;
+        let entries = bnd
+            .files
+            .iter()
+            .map(|entry| SaveEntry::decode(entry.path.clone(), bnd.file_bytes(entry), game))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { game, bnd, entries })
+    }
+
+    pub fn game(&self) -> SaveGame {
+        self.game
+    }
+
+    /// Every entry of the save, in the order they are stored.
+    pub fn entries(&self) -> &[SaveEntry] {
+        &self.entries
+    }
+
+    pub fn entries_mut(&mut self) -> &mut [SaveEntry] {
+        &mut self.entries
+    }
+
+    /// The entries holding characters.
+    pub fn slots(&self) -> impl Iterator<Item = &SaveEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.slot_index().is_some())
+    }
+
+    pub fn slot(&self, index: usize) -> Option<&SaveEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.slot_index() == Some(index))
+    }
+
+    pub fn slot_mut(&mut self, index: usize) -> Option<&mut SaveEntry> {
+        self.entries
+            .iter_mut()
+            .find(|entry| entry.slot_index() == Some(index))
+    }
+
+    /// Read the character in a slot, see [`SaveEntry::character`].
+    pub fn character(&self, index: usize) -> Result<Character, SaveError> {
+        self.slot(index)
+            .ok_or(SaveError::NoSlot(index))?
+            .character(self.game)
+    }
+
+    /// Write a character into a slot, see [`SaveEntry::set_character`].
+    pub fn set_character(&mut self, index: usize, character: &Character) -> Result<(), SaveError> {
+        let game = self.game;
+        self.slot_mut(index)
+            .ok_or(SaveError::NoSlot(index))?
+            .set_character(game, character)
+    }
+
+    /// Entries whose stored checksum didn't match when the save was read.
+    pub fn invalid_entries(&self) -> impl Iterator<Item = &SaveEntry> {
+        self.entries.iter().filter(|entry| !entry.checksum_valid)
+    }
+
+    /// Write the save back out, re-encrypting every entry and fixing its checksum.
+    pub fn write(&mut self, w: &mut impl Write) -> Result<(), SaveError> {
+        for (index, entry) in self.entries.iter_mut().enumerate() {
+            self.bnd.replace_file(index, &entry.encode(self.game)?)?;
+            entry.checksum_valid = true;
+        }
+
+        Ok(w.write_all(&self.bnd.data)?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::save::{SaveEntry, SaveGame};
+
+    #[test]
+    pub fn round_trips_entries() {
+        for game in [SaveGame::DarkSouls3, SaveGame::EldenRing] {
+            let entry = SaveEntry {
+                name: "USER_DATA003".to_string(),
+                data: (0..0x40).collect(),
+                iv: [7; 16],
+                checksum_valid: true,
+            };
+
+            let mut encoded = entry.encode(game).unwrap();
+            let decoded = SaveEntry::decode(entry.name.clone(), &encoded, game).unwrap();
+
+            assert_eq!(decoded.data, entry.data);
+            assert_eq!(decoded.slot_index(), Some(3));
+            assert!(decoded.checksum_valid());
+
+            *encoded.last_mut().unwrap() ^= 1;
+            let corrupted = SaveEntry::decode(entry.name.clone(), &encoded, game).unwrap();
+            assert!(!corrupted.checksum_valid());
+        }
+    }
+}
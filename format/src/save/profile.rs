@@ -0,0 +1,191 @@
+use byteorder::{ByteOrder, LE};
+
+use crate::save::{SaveError, SaveGame, Sl2, CHARACTER_SLOT_COUNT};
+
+/// Entry holding the settings and what the load menu shows of each character slot.
+const ER_PROFILE_ENTRY: &str = "USER_DATA010";
+
+/// Offset of a flag per slot saying whether it holds a character, after the version, Steam ID,
+/// settings and menu state. The summaries of the slots follow.
+const ER_ACTIVE_SLOTS: usize = 0x1954;
+const ER_SUMMARIES: usize = ER_ACTIVE_SLOTS + CHARACTER_SLOT_COUNT;
+const ER_SUMMARY_SIZE: usize = 0x24C;
+const ER_SUMMARY_NAME_LENGTH: usize = 0x11;
+const ER_SUMMARY_LEVEL: usize = 0x22;
+const ER_SUMMARY_SECONDS_PLAYED: usize = 0x26;
+
+/// What the load menu shows of a character slot.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SlotSummary {
+    pub index: usize,
+
+    /// Whether the slot holds a character, the rest of the summary is stale otherwise.
+    pub active: bool,
+    pub name: String,
+    pub level: u32,
+    pub seconds_played: u32,
+}
+
+impl Sl2 {
+    /// The summary of every character slot, as shown on the load menu.
+    pub fn slot_summaries(&self) -> Result<Vec<SlotSummary>, SaveError> {
+        let data = self.profile()?;
+
+        Ok((0..CHARACTER_SLOT_COUNT)
+            .map(|index| {
+                let summary = &data[ER_SUMMARIES + index * ER_SUMMARY_SIZE..];
+                let name = summary[..ER_SUMMARY_NAME_LENGTH * 2]
+                    .chunks_exact(2)
+                    .map(LE::read_u16)
+                    .take_while(|c| *c != 0)
+                    .collect::<Vec<_>>();
+
+                SlotSummary {
+                    index,
+                    active: data[ER_ACTIVE_SLOTS + index] != 0,
+                    name: String::from_utf16_lossy(&name),
+                    level: LE::read_u32(&summary[ER_SUMMARY_LEVEL..]),
+                    seconds_played: LE::read_u32(&summary[ER_SUMMARY_SECONDS_PLAYED..]),
+                }
+            })
+            .collect())
+    }
+
+    /// Copy the character in slot `from` of `source` into slot `to`, along with its summary so that
+    /// it shows up on the load menu.
+    ///
+    /// Characters are tied to the Steam account of the save they were made in, so copying between
+    /// the saves of different accounts gives a character the game refuses to load.
+    pub fn copy_slot(&mut self, source: &Sl2, from: usize, to: usize) -> Result<(), SaveError> {
+        let data = source
+            .slot(from)
+            .ok_or(SaveError::NoSlot(from))?
+            .data
+            .clone();
+        let summary = source.profile()?
+            [ER_SUMMARIES + from * ER_SUMMARY_SIZE..ER_SUMMARIES + (from + 1) * ER_SUMMARY_SIZE]
+            .to_vec();
+        let active = source.profile()?[ER_ACTIVE_SLOTS + from];
+
+        // Checked before anything is changed, so a failed copy leaves the save as it was.
+        self.profile()?;
+        self.slot_mut(to).ok_or(SaveError::NoSlot(to))?.data = data;
+
+        let profile = self.profile_mut()?;
+        profile[ER_ACTIVE_SLOTS + to] = active;
+        profile[ER_SUMMARIES + to * ER_SUMMARY_SIZE..ER_SUMMARIES + (to + 1) * ER_SUMMARY_SIZE]
+            .copy_from_slice(&summary);
+
+        Ok(())
+    }
+
+    fn profile(&self) -> Result<&[u8], SaveError> {
+        if self.game != SaveGame::EldenRing {
+            return Err(SaveError::UnsupportedGame(self.game));
+        }
+
+        let entry = self
+            .entries
+            .iter()
+            .find(|entry| entry.name == ER_PROFILE_ENTRY)
+            .ok_or_else(|| SaveError::MissingEntry(ER_PROFILE_ENTRY.to_string()))?;
+        if entry.data.len() < ER_SUMMARIES + CHARACTER_SLOT_COUNT * ER_SUMMARY_SIZE {
+            return Err(SaveError::TooShort(entry.name.clone()));
+        }
+
+        Ok(&entry.data)
+    }
+
+    fn profile_mut(&mut self) -> Result<&mut [u8], SaveError> {
+        self.profile()?;
+
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.name == ER_PROFILE_ENTRY)
+            .expect("profile was found");
+
+        Ok(&mut entry.data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bnd4::BND4,
+        save::{
+            profile::{ER_ACTIVE_SLOTS, ER_SUMMARIES, ER_SUMMARY_LEVEL, ER_SUMMARY_SIZE},
+            SaveEntry, SaveGame, Sl2,
+        },
+    };
+
+    /// A save of `entries`, whose binder is only needed to write it.
+    fn save(entries: Vec<SaveEntry>) -> Sl2 {
+        let bnd = BND4 {
+            unk04: 0,
+            unk05: 0,
+            unk0a: 0,
+            file_count: 0,
+            file_headers_offset: 0,
+            version: 0,
+            file_header_size: 0,
+            file_headers_end: 0,
+            unicode: true,
+            raw_format: 0,
+            extended: 0,
+            buckets_offset: 0,
+            files: Vec::new(),
+            data: Vec::new(),
+        };
+
+        Sl2 {
+            game: SaveGame::EldenRing,
+            bnd,
+            entries,
+        }
+    }
+
+    fn entry(name: &str, data: Vec<u8>) -> SaveEntry {
+        SaveEntry {
+            name: name.to_string(),
+            data,
+            iv: [0; 16],
+            checksum_valid: true,
+        }
+    }
+
+    #[test]
+    pub fn copies_slots_with_their_summaries() {
+        let mut profile = vec![0u8; ER_SUMMARIES + 10 * ER_SUMMARY_SIZE];
+        profile[ER_ACTIVE_SLOTS] = 1;
+        profile[ER_SUMMARIES..ER_SUMMARIES + 4].copy_from_slice(&[b'T', 0, b'a', 0]);
+        profile[ER_SUMMARIES + ER_SUMMARY_LEVEL] = 42;
+
+        let entries = vec![
+            entry("USER_DATA000", vec![1; 4]),
+            entry("USER_DATA001", vec![0; 4]),
+            entry("USER_DATA010", profile),
+        ];
+        let source = save(
+            entries
+                .iter()
+                .map(|e| entry(&e.name, e.data.clone()))
+                .collect(),
+        );
+        let mut save = save(entries);
+
+        let summaries = save.slot_summaries().unwrap();
+        assert!(summaries[0].active && !summaries[1].active);
+        assert_eq!(summaries[0].name, "Ta");
+        assert_eq!(summaries[0].level, 42);
+
+        save.copy_slot(&source, 0, 1).unwrap();
+        assert_eq!(save.slot(1).unwrap().data, [1; 4]);
+
+        let summaries = save.slot_summaries().unwrap();
+        assert!(summaries[1].active);
+        assert_eq!(summaries[1].name, "Ta");
+        assert!(save.copy_slot(&source, 0, 5).is_err());
+    }
+}
@@ -0,0 +1,601 @@
+use std::{
+    collections::{HashMap, HashSet},
+    io::{self, Read},
+};
+
+use byteorder::{ByteOrder, LE};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum SoundError {
+    #[error("Could not read soundbank: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not a Wwise soundbank")]
+    InvalidMagic,
+
+    #[error("Soundbank chunk {0} is truncated")]
+    Truncated(String),
+
+    #[error("Embedded WEM {id} lies outside of the soundbank's data")]
+    WemOutOfBounds { id: u32 },
+
+    #[error("Not a WEM: {0}")]
+    InvalidWem(&'static str),
+
+    #[error("WEMs encoded with {0:?} can't be converted to WAV")]
+    UnsupportedCodec(WemCodec),
+}
+
+const BKHD_MAGIC: &[u8; 4] = b"BKHD";
+const CHUNK_HEADER_SIZE: usize = 0x8;
+const DIDX_ENTRY_SIZE: usize = 0xC;
+
+/// `HIRC` object type of a sound, the only type that refers to media directly.
+const HIRC_SOUND: u8 = 2;
+const HIRC_ACTION: u8 = 3;
+const HIRC_EVENT: u8 = 4;
+
+/// Type of the actions that play their target, as opposed to stopping or pausing it.
+const ACTION_PLAY: u16 = 0x0403;
+
+/// A Wwise soundbank (`.bnk`), found in the `sd` archive.
+///
+/// Short sounds are embedded in the bank itself, while longer ones are streamed from loose WEM
+/// files that the bank's sound objects refer to by ID, see [`wem_path`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Bnk {
+    /// Wwise bank version, e.g. `0x8C` in Elden Ring.
+    pub version: u32,
+    pub id: u32,
+
+    /// Media embedded in the bank, in the order of its `DIDX` chunk.
+    pub wems: Vec<BnkWem>,
+
+    /// Every object of the bank's `HIRC` chunk, i.e. its sounds, events, actions and containers.
+    pub objects: Vec<BnkObject>,
+
+    #[cfg_attr(feature = "serde", serde(skip))]
+    data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BnkWem {
+    pub id: u32,
+    offset: usize,
+    size: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BnkObject {
+    pub object_type: u8,
+    pub id: u32,
+
+    /// The rest of the object, whose layout depends on its type and the bank version.
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum BnkStreamType {
+    /// The media is in the bank's `DATA` chunk.
+    Embedded,
+    Streamed,
+
+    /// Streamed, with the start of the media embedded to hide the latency of streaming it in.
+    Prefetched,
+}
+
+/// The media played by a sound object.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct BnkSoundSource {
+    pub sound_id: u32,
+
+    /// Identifies the codec, e.g. `0x40001` for Vorbis.
+    pub plugin_id: u32,
+    pub stream_type: BnkStreamType,
+
+    /// ID of the WEM holding the media, either embedded or loose.
+    pub wem_id: u32,
+}
+
+impl Bnk {
+    pub fn from_reader(r: &mut impl Read) -> Result<Self, SoundError> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+
+        if bytes.get(..4) != Some(&BKHD_MAGIC[..]) {
+            return Err(SoundError::InvalidMagic);
+        }
+
+        let mut bnk = Self {
+            version: 0,
+            id: 0,
+            wems: Vec::new(),
+            objects: Vec::new(),
+            data: Vec::new(),
+        };
+
+        let mut offset = 0;
+        while let Some(header) = bytes.get(offset..offset + CHUNK_HEADER_SIZE) {
+            let tag = &header[..4];
+            let truncated = || SoundError::Truncated(String::from_utf8_lossy(tag).into_owned());
+            let size = LE::read_u32(&header[4..]) as usize;
+            let start = offset + CHUNK_HEADER_SIZE;
+            let chunk = start
+                .checked_add(size)
+                .and_then(|end| bytes.get(start..end))
+                .ok_or_else(truncated)?;
+
+            match tag {
+                b"BKHD" => {
+                    let header = chunk.get(..8).ok_or_else(truncated)?;
+                    bnk.version = LE::read_u32(header);
+                    bnk.id = LE::read_u32(&header[4..]);
+                }
+                b"DIDX" => {
+                    bnk.wems = chunk
+                        .chunks_exact(DIDX_ENTRY_SIZE)
+                        .map(|entry| BnkWem {
+                            id: LE::read_u32(entry),
+                            offset: LE::read_u32(&entry[4..]) as usize,
+                            size: LE::read_u32(&entry[8..]) as usize,
+                        })
+                        .collect();
+                }
+                b"DATA" => bnk.data = chunk.to_vec(),
+                b"HIRC" => bnk.objects = read_objects(chunk).ok_or_else(truncated)?,
+                _ => {}
+            }
+
+            offset = start + size;
+        }
+
+        if let Some(wem) = bnk
+            .wems
+            .iter()
+            .find(|wem| wem.offset + wem.size > bnk.data.len())
+        {
+            return Err(SoundError::WemOutOfBounds { id: wem.id });
+        }
+
+        Ok(bnk)
+    }
+
+    /// Bytes of an embedded WEM, a RIFF file that can be written out as is.
+    pub fn wem_bytes(&self, wem: &BnkWem) -> &[u8] {
+        &self.data[wem.offset..wem.offset + wem.size]
+    }
+
+    pub fn wem(&self, id: u32) -> Option<&[u8]> {
+        self.wems
+            .iter()
+            .find(|wem| wem.id == id)
+            .map(|wem| self.wem_bytes(wem))
+    }
+
+    /// The media of every sound object in the bank.
+    pub fn sources(&self) -> impl Iterator<Item = BnkSoundSource> + '_ {
+        self.objects.iter().filter_map(sound_source)
+    }
+
+    /// The media played by event `id`, following its play actions down through the containers
+    /// they target, or `None` if the bank has no such event.
+    ///
+    /// The layout of containers changes with every Wwise version, so rather than decoding their
+    /// children, any ID of another object of the bank found in a container counts as one.
+    pub fn event_sources(&self, id: u32) -> Option<Vec<BnkSoundSource>> {
+        let objects = self
+            .objects
+            .iter()
+            .map(|object| (object.id, object))
+            .collect::<HashMap<_, _>>();
+        let event = objects.get(&id).filter(|o| o.object_type == HIRC_EVENT)?;
+
+        let mentioned = |object: &BnkObject| {
+            object
+                .data
+                .windows(4)
+                .map(LE::read_u32)
+                .filter(|id| *id != object.id)
+                .filter_map(|id| objects.get(&id).copied())
+                .collect::<Vec<_>>()
+        };
+
+        let mut pending = mentioned(event)
+            .into_iter()
+            .filter(|action| action.object_type == HIRC_ACTION)
+            .filter(|action| action.data.get(..2).map(LE::read_u16) == Some(ACTION_PLAY))
+            .filter_map(|action| objects.get(&LE::read_u32(action.data.get(2..6)?)).copied())
+            .collect::<Vec<_>>();
+
+        let mut visited = HashSet::new();
+        let mut sources = Vec::new();
+        while let Some(object) = pending.pop() {
+            if !visited.insert(object.id) {
+                continue;
+            }
+
+            match object.object_type {
+                HIRC_SOUND => sources.extend(sound_source(object)),
+                HIRC_ACTION | HIRC_EVENT => {}
+                _ => pending.extend(mentioned(object)),
+            }
+        }
+        sources.sort_by_key(|source| source.sound_id);
+
+        Some(sources)
+    }
+
+    /// IDs of the loose WEMs this bank streams, which have to be resolved through the `sd`
+    /// archive with [`wem_path`].
+    pub fn streamed_wems(&self) -> Vec<u32> {
+        let mut ids = self
+            .sources()
+            .filter(|source| source.stream_type != BnkStreamType::Embedded)
+            .map(|source| source.wem_id)
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+
+        ids
+    }
+}
+
+fn sound_source(object: &BnkObject) -> Option<BnkSoundSource> {
+    if object.object_type != HIRC_SOUND {
+        return None;
+    }
+
+    let data = object.data.get(..9)?;
+    let stream_type = match data[4] {
+        0 => BnkStreamType::Embedded,
+        1 => BnkStreamType::Streamed,
+        2 => BnkStreamType::Prefetched,
+        _ => return None,
+    };
+
+    Some(BnkSoundSource {
+        sound_id: object.id,
+        plugin_id: LE::read_u32(data),
+        stream_type,
+        wem_id: LE::read_u32(&data[5..]),
+    })
+}
+
+fn read_objects(chunk: &[u8]) -> Option<Vec<BnkObject>> {
+    let count = LE::read_u32(chunk.get(..4)?) as usize;
+
+    let mut objects = Vec::new();
+    let mut offset = 4;
+    for _ in 0..count {
+        let object_type = *chunk.get(offset)?;
+        let size = LE::read_u32(chunk.get(offset + 1..offset + 5)?) as usize;
+
+        // The size covers the ID but not the type and size themselves.
+        let body = chunk.get(offset + 5..(offset + 5).checked_add(size)?)?;
+        objects.push(BnkObject {
+            object_type,
+            id: LE::read_u32(body.get(..4)?),
+            data: body[4..].to_vec(),
+        });
+
+        offset += 5 + size;
+    }
+
+    Some(objects)
+}
+
+/// Path of a loose WEM in the `sd` archive, e.g. `/wem/10/1001000.wem`.
+///
+/// Voiced lines are localized and found under a language directory such as `enus` instead.
+pub fn wem_path(id: u32, language: Option<&str>) -> String {
+    let id = id.to_string();
+    let prefix = &id[..id.len().min(2)];
+
+    match language {
+        Some(language) => format!("/{language}/wem/{prefix}/{id}.wem"),
+        None => format!("/wem/{prefix}/{id}.wem"),
+    }
+}
+
+/// The ID Wwise gives a name such as an event's, the 32-bit FNV-1 hash of its lowercase bytes.
+pub fn wwise_id(name: &str) -> u32 {
+    name.to_ascii_lowercase()
+        .bytes()
+        .fold(0x811C9DC5, |hash: u32, byte| {
+            hash.wrapping_mul(0x01000193) ^ byte as u32
+        })
+}
+
+/// How the media of a WEM is encoded, from the format tag of its `fmt ` chunk.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum WemCodec {
+    Pcm,
+
+    /// IMA ADPCM, in blocks of 64 samples per channel after an uncompressed one.
+    ImaAdpcm,
+
+    /// Vorbis without its headers, which reference codebooks shipped with Wwise.
+    Vorbis,
+    Opus,
+    Other(u16),
+}
+
+impl WemCodec {
+    fn from_format_tag(tag: u16) -> Self {
+        match tag {
+            0x0001 | 0xFFFE => Self::Pcm,
+            0x0002 => Self::ImaAdpcm,
+            0xFFFF => Self::Vorbis,
+            0x3040 | 0x3041 => Self::Opus,
+            tag => Self::Other(tag),
+        }
+    }
+}
+
+/// The format and media of a WEM, a RIFF file with a Wwise specific `fmt ` chunk.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wem<'a> {
+    pub codec: WemCodec,
+    pub channels: u16,
+    pub sample_rate: u32,
+    pub block_align: u16,
+    pub bits_per_sample: u16,
+    pub data: &'a [u8],
+}
+
+/// Size of the block of each channel in a Wwise IMA ADPCM frame: the first sample and step index
+/// followed by 64 samples of 4 bits.
+const IMA_BLOCK_SIZE: usize = 0x24;
+
+const IMA_INDEX_TABLE: [i32; 8] = [-1, -1, -1, -1, 2, 4, 6, 8];
+const IMA_STEP_TABLE: [i32; 89] = [
+    7, 8, 9, 10, 11, 12, 13, 14, 16, 17, 19, 21, 23, 25, 28, 31, 34, 37, 41, 45, 50, 55, 60, 66,
+    73, 80, 88, 97, 107, 118, 130, 143, 157, 173, 190, 209, 230, 253, 279, 307, 337, 371, 408, 449,
+    494, 544, 598, 658, 724, 796, 876, 963, 1060, 1166, 1282, 1411, 1552, 1707, 1878, 2066, 2272,
+    2499, 2749, 3024, 3327, 3660, 4026, 4428, 4871, 5358, 5894, 6484, 7132, 7845, 8630, 9493,
+    10442, 11487, 12635, 13899, 15289, 16818, 18500, 20350, 22385, 24623, 27086, 29794, 32767,
+];
+
+impl<'a> Wem<'a> {
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, SoundError> {
+        if bytes.get(..4) != Some(b"RIFF") || bytes.get(8..12) != Some(b"WAVE") {
+            return Err(SoundError::InvalidWem("no RIFF header"));
+        }
+
+        let (mut format, mut data) = (None, None);
+        let mut offset = 12;
+        while let Some(header) = bytes.get(offset..offset + CHUNK_HEADER_SIZE) {
+            let size = LE::read_u32(&header[4..]) as usize;
+            let start = offset + CHUNK_HEADER_SIZE;
+            let chunk = start
+                .checked_add(size)
+                .and_then(|end| bytes.get(start..end));
+
+            match &header[..4] {
+                b"fmt " => format = chunk,
+                b"data" => data = chunk,
+                _ => {}
+            }
+
+            // Chunks are padded to an even size.
+            offset = start + size + size % 2;
+        }
+
+        let format = format
+            .filter(|format| format.len() >= 16)
+            .ok_or(SoundError::InvalidWem("no format chunk"))?;
+
+        Ok(Self {
+            codec: WemCodec::from_format_tag(LE::read_u16(format)),
+            channels: LE::read_u16(&format[2..]),
+            sample_rate: LE::read_u32(&format[4..]),
+            block_align: LE::read_u16(&format[12..]),
+            bits_per_sample: LE::read_u16(&format[14..]),
+            data: data.ok_or(SoundError::InvalidWem("no data chunk"))?,
+        })
+    }
+
+    /// Decode the media as 16-bit PCM and write it as a WAV file, for the codecs that can be
+    /// decoded without Wwise's own data.
+    pub fn to_wav(&self) -> Result<Vec<u8>, SoundError> {
+        let samples = match (self.codec, self.bits_per_sample) {
+            (WemCodec::Pcm, 16) => self.data.to_vec(),
+            (WemCodec::ImaAdpcm, _) => self
+                .decode_ima()?
+                .into_iter()
+                .flat_map(i16::to_le_bytes)
+                .collect(),
+            _ => return Err(SoundError::UnsupportedCodec(self.codec)),
+        };
+
+        let block_align = self.channels as u32 * 2;
+        let mut wav = Vec::with_capacity(44 + samples.len());
+        wav.extend(b"RIFF");
+        wav.extend((36 + samples.len() as u32).to_le_bytes());
+        wav.extend(b"WAVEfmt ");
+        wav.extend(16u32.to_le_bytes());
+        wav.extend(1u16.to_le_bytes());
+        wav.extend(self.channels.to_le_bytes());
+        wav.extend(self.sample_rate.to_le_bytes());
+        wav.extend((self.sample_rate * block_align).to_le_bytes());
+        wav.extend((block_align as u16).to_le_bytes());
+        wav.extend(16u16.to_le_bytes());
+        wav.extend(b"data");
+        wav.extend((samples.len() as u32).to_le_bytes());
+        wav.extend(samples);
+
+        Ok(wav)
+    }
+
+    /// Interleaved samples of IMA ADPCM media, whose frames hold a block per channel in turn.
+    fn decode_ima(&self) -> Result<Vec<i16>, SoundError> {
+        let channels = self.channels as usize;
+        if channels == 0 || self.block_align as usize != IMA_BLOCK_SIZE * channels {
+            return Err(SoundError::InvalidWem("unexpected IMA ADPCM block size"));
+        }
+
+        let mut samples = Vec::new();
+        let mut block = vec![[0i16; 65]; channels];
+        for frame in self.data.chunks_exact(IMA_BLOCK_SIZE * channels) {
+            for (channel, data) in frame.chunks_exact(IMA_BLOCK_SIZE).enumerate() {
+                let mut predictor = LE::read_i16(data) as i32;
+                let mut index = (data[2] as i32).min(88);
+                block[channel][0] = predictor as i16;
+
+                let nibbles = data[4..].iter().flat_map(|byte| [byte & 0xF, byte >> 4]);
+                for (sample, nibble) in nibbles.enumerate() {
+                    let step = IMA_STEP_TABLE[index as usize];
+                    let mut diff = step >> 3;
+                    if nibble & 1 != 0 {
+                        diff += step >> 2;
+                    }
+                    if nibble & 2 != 0 {
+                        diff += step >> 1;
+                    }
+                    if nibble & 4 != 0 {
+                        diff += step;
+                    }
+                    if nibble & 8 != 0 {
+                        diff = -diff;
+                    }
+
+                    predictor = (predictor + diff).clamp(i16::MIN as i32, i16::MAX as i32);
+                    index = (index + IMA_INDEX_TABLE[(nibble & 7) as usize]).clamp(0, 88);
+                    block[channel][sample + 1] = predictor as i16;
+                }
+            }
+
+            for sample in 0..65 {
+                samples.extend(block.iter().map(|channel| channel[sample]));
+            }
+        }
+
+        Ok(samples)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::sound::{wem_path, wwise_id, Bnk, BnkStreamType, Wem, WemCodec};
+
+    fn chunk(out: &mut Vec<u8>, tag: &[u8; 4], data: &[u8]) {
+        out.extend(tag);
+        out.extend((data.len() as u32).to_le_bytes());
+        out.extend(data);
+    }
+
+    fn sound(id: u32, stream_type: u8, wem_id: u32) -> Vec<u8> {
+        let mut object = vec![2];
+        object.extend(13u32.to_le_bytes());
+        object.extend(id.to_le_bytes());
+        object.extend(0x40001u32.to_le_bytes());
+        object.push(stream_type);
+        object.extend(wem_id.to_le_bytes());
+
+        object
+    }
+
+    #[test]
+    pub fn reads_embedded_and_streamed_wems() {
+        let mut bytes = Vec::new();
+        chunk(&mut bytes, b"BKHD", &[0x8C, 0, 0, 0, 1, 0, 0, 0]);
+        chunk(&mut bytes, b"DIDX", &[7, 0, 0, 0, 0, 0, 0, 0, 4, 0, 0, 0]);
+        chunk(&mut bytes, b"DATA", b"RIFF");
+
+        let mut hirc = 2u32.to_le_bytes().to_vec();
+        hirc.extend(sound(100, 0, 7));
+        hirc.extend(sound(101, 1, 1001000));
+        chunk(&mut bytes, b"HIRC", &hirc);
+
+        let bnk = Bnk::from_reader(&mut Cursor::new(bytes)).unwrap();
+        assert_eq!(bnk.version, 0x8C);
+        assert_eq!(bnk.wem(7), Some(&b"RIFF"[..]));
+
+        let sources = bnk.sources().collect::<Vec<_>>();
+        assert_eq!(sources[0].stream_type, BnkStreamType::Embedded);
+        assert_eq!(bnk.streamed_wems(), [1001000]);
+        assert_eq!(wem_path(1001000, None), "/wem/10/1001000.wem");
+    }
+
+    fn object(object_type: u8, id: u32, data: &[u8]) -> Vec<u8> {
+        let mut object = vec![object_type];
+        object.extend((data.len() as u32 + 4).to_le_bytes());
+        object.extend(id.to_le_bytes());
+        object.extend(data);
+
+        object
+    }
+
+    #[test]
+    pub fn follows_events_to_their_sounds() {
+        let event = wwise_id("Play_Test");
+        assert_eq!(wwise_id("a"), 0x050C5D7E);
+
+        let mut play = 0x0403u16.to_le_bytes().to_vec();
+        play.extend(300u32.to_le_bytes());
+        let mut stop = 0x0102u16.to_le_bytes().to_vec();
+        stop.extend(102u32.to_le_bytes());
+        let mut container = vec![0; 3];
+        container.extend([2, 0, 0, 0, 100, 0, 0, 0, 101, 0, 0, 0]);
+
+        let mut hirc = 7u32.to_le_bytes().to_vec();
+        hirc.extend(sound(100, 0, 7));
+        hirc.extend(sound(101, 1, 1001000));
+        hirc.extend(sound(102, 1, 1002000));
+        hirc.extend(object(5, 300, &container));
+        hirc.extend(object(3, 200, &play));
+        hirc.extend(object(3, 201, &stop));
+        hirc.extend(object(4, event, &[2, 200, 0, 0, 0, 201, 0, 0, 0]));
+
+        let mut bytes = Vec::new();
+        chunk(&mut bytes, b"BKHD", &[0x8C, 0, 0, 0, 1, 0, 0, 0]);
+        chunk(&mut bytes, b"HIRC", &hirc);
+
+        let bnk = Bnk::from_reader(&mut Cursor::new(bytes)).unwrap();
+        let sources = bnk.event_sources(event).unwrap();
+        let wems = sources.iter().map(|s| s.wem_id).collect::<Vec<_>>();
+        assert_eq!(wems, [7, 1001000]);
+        assert_eq!(bnk.event_sources(300), None);
+    }
+
+    #[test]
+    pub fn decodes_ima_adpcm_to_wav() {
+        let mut format = 0x0002u16.to_le_bytes().to_vec();
+        format.extend(1u16.to_le_bytes());
+        format.extend(48000u32.to_le_bytes());
+        format.extend(0u32.to_le_bytes());
+        format.extend(0x24u16.to_le_bytes());
+        format.extend(4u16.to_le_bytes());
+
+        // A first sample of 100, then steps of +1 and -1 at the smallest step size.
+        let mut block = 100i16.to_le_bytes().to_vec();
+        block.extend([0, 0]);
+        block.extend([0x91; 32]);
+
+        let mut bytes = b"RIFF\0\0\0\0WAVE".to_vec();
+        chunk(&mut bytes, b"fmt ", &format);
+        chunk(&mut bytes, b"data", &block);
+
+        let wem = Wem::parse(&bytes).unwrap();
+        assert_eq!(wem.codec, WemCodec::ImaAdpcm);
+
+        let wav = wem.to_wav().unwrap();
+        assert_eq!(&wav[..4], b"RIFF");
+        assert_eq!(wav.len(), 44 + 65 * 2);
+
+        let samples = wav[44..]
+            .chunks_exact(2)
+            .map(|s| i16::from_le_bytes([s[0], s[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!(samples[..3], [100, 101, 100]);
+    }
+}
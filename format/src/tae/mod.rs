@@ -0,0 +1,553 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use byteorder::{ByteOrder, ReadBytesExt, WriteBytesExt, LE};
+use thiserror::Error;
+
+use crate::io_ext::ReadFormatsExt;
+
+pub mod template;
+
+pub use self::template::{
+    TaeEventType, TaeParam, TaeParamType, TaeTemplate, TaeTemplateError, TaeValue,
+};
+
+#[derive(Debug, Error)]
+pub enum TaeError {
+    #[error("Could not read TAE: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Unsupported TAE version {0:#x}, only 64-bit TAE3 files are supported")]
+    UnsupportedVersion(u32),
+}
+
+/// Version used by Dark Souls 3.
+pub const TAE_VERSION_DS3: u32 = 0x1000C;
+
+/// Version used by Sekiro, Elden Ring and Armored Core 6.
+pub const TAE_VERSION_SDT: u32 = 0x1000D;
+
+/// A time-act file, associating each animation of a character or object with timed events such as
+/// hitboxes, sounds and invincibility frames.
+///
+/// Event parameters are kept as raw bytes, their layout is described per event type by a
+/// [TaeTemplate].
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Tae {
+    pub version: u32,
+    pub id: i32,
+
+    /// The bank of event types used by this file, selecting which template types apply.
+    pub event_bank: i64,
+    pub flags: [u8; 8],
+    pub skeleton_name: String,
+    pub sib_name: String,
+    pub animations: Vec<TaeAnimation>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TaeAnimation {
+    pub id: i64,
+
+    /// The animation file header, describing whether the animation imports another.
+    pub anim_file: [u8; ANIM_FILE_SIZE],
+    pub events: Vec<TaeEvent>,
+    pub groups: Vec<TaeEventGroup>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TaeEvent {
+    pub start_time: f32,
+    pub end_time: f32,
+    pub event_type: i32,
+    pub params: Vec<u8>,
+}
+
+/// A set of events grouped together in editors, referencing events by index.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TaeEventGroup {
+    pub group_type: i64,
+    pub events: Vec<usize>,
+}
+
+const EVENT_HEADER_SIZE: u64 = 0x18;
+const EVENT_GROUP_SIZE: u64 = 0x20;
+const ANIMATION_HEADER_SIZE: u64 = 0x10;
+const ANIMATION_SIZE: u64 = 0x30;
+const ANIM_FILE_SIZE: usize = 0x10;
+const ANIM_FILE_TYPE_IMPORT_OTHER: u32 = 1;
+
+struct EventHeader {
+    start_time_offset: u64,
+    end_time_offset: u64,
+    data_offset: u64,
+}
+
+impl Tae {
+    pub fn from_reader(r: &mut (impl Read + Seek)) -> Result<Self, TaeError> {
+        r.read_magic(b"TAE ")?;
+
+        let big_endian = r.read_bool()?;
+        r.read_padding(2)?;
+        let long_format = r.read_u8()? == 0xFF;
+        let version = r.read_u32::<LE>()?;
+
+        if big_endian || !long_format || !matches!(version, TAE_VERSION_DS3 | TAE_VERSION_SDT) {
+            return Err(TaeError::UnsupportedVersion(version));
+        }
+
+        let file_size = r.read_u32::<LE>()? as u64;
+
+        r.seek(SeekFrom::Start(0x30))?;
+        let event_bank = r.read_i64::<LE>()?;
+        let _unk38 = r.read_i64::<LE>()?;
+        let mut flags = [0u8; 8];
+        r.read_exact(&mut flags)?;
+        let _unk48 = r.read_i64::<LE>()?;
+        let id = r.read_i32::<LE>()?;
+        let animation_count = r.read_i32::<LE>()? as u64;
+        let animations_offset = r.read_u64::<LE>()?;
+
+        r.seek(SeekFrom::Start(0xB0))?;
+        let skeleton_name_offset = r.read_u64::<LE>()?;
+        let sib_name_offset = r.read_u64::<LE>()?;
+
+        r.seek(SeekFrom::Start(skeleton_name_offset))?;
+        let skeleton_name = r.read_utf16::<LE>()?;
+        r.seek(SeekFrom::Start(sib_name_offset))?;
+        let sib_name = r.read_utf16::<LE>()?;
+
+        let mut animations = Vec::with_capacity(animation_count as usize);
+        for index in 0..animation_count {
+            r.seek(SeekFrom::Start(
+                animations_offset + index * ANIMATION_HEADER_SIZE,
+            ))?;
+            animations.push(TaeAnimation::read(r, file_size)?);
+        }
+
+        Ok(Self {
+            version,
+            id,
+            event_bank,
+            flags,
+            skeleton_name,
+            sib_name,
+            animations,
+        })
+    }
+
+    pub fn animation(&self, id: i64) -> Option<&TaeAnimation> {
+        self.animations.iter().find(|anim| anim.id == id)
+    }
+
+    pub fn animation_mut(&mut self, id: i64) -> Option<&mut TaeAnimation> {
+        self.animations.iter_mut().find(|anim| anim.id == id)
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> io::Result<()> {
+        w.write_all(&self.to_bytes())
+    }
+
+    /// Serialize this TAE, with animations ordered by ID.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut animations = self.animations.iter().collect::<Vec<_>>();
+        animations.sort_by_key(|anim| anim.id);
+
+        // Writing to a Vec can't fail, so the results of the writes below are ignored.
+        let count = animations.len() as u64;
+        out.extend_from_slice(b"TAE ");
+        out.extend_from_slice(&[0, 0, 0, 0xFF]);
+        let _ = out.write_u32::<LE>(self.version);
+        let _ = out.write_u32::<LE>(0);
+        for value in [0x40, 1, 0x50, 0x80] {
+            let _ = out.write_u64::<LE>(value);
+        }
+        let _ = out.write_i64::<LE>(self.event_bank);
+        let _ = out.write_u64::<LE>(0);
+        out.extend_from_slice(&self.flags);
+        let _ = out.write_u64::<LE>(1);
+        let _ = out.write_i32::<LE>(self.id);
+        let _ = out.write_u32::<LE>(count as u32);
+        let _ = out.write_u64::<LE>(0);
+        let _ = out.write_u64::<LE>(0);
+        let _ = out.write_u64::<LE>(0xA0);
+        let _ = out.write_u64::<LE>(count);
+        let _ = out.write_u64::<LE>(0);
+        let _ = out.write_u64::<LE>(1);
+        let _ = out.write_u64::<LE>(0x90);
+        let _ = out.write_i32::<LE>(self.id);
+        let _ = out.write_i32::<LE>(self.id);
+        let _ = out.write_u64::<LE>(0x50);
+        let _ = out.write_u64::<LE>(0);
+        let _ = out.write_u64::<LE>(0xB0);
+        out.resize(0xD0, 0);
+
+        patch_position(&mut out, 0xB0);
+        write_utf16(&mut out, &self.skeleton_name);
+        patch_position(&mut out, 0xB8);
+        write_utf16(&mut out, &self.sib_name);
+        pad(&mut out, 0x10);
+
+        let animations_offset = out.len() as u64;
+        patch_u64(&mut out, 0x58, animations_offset);
+        for anim in &animations {
+            let _ = out.write_i64::<LE>(anim.id);
+            let _ = out.write_u64::<LE>(0);
+        }
+
+        // Runs of consecutive animation IDs are grouped, referencing their first animation header.
+        let mut anim_groups: Vec<(i64, i64, usize)> = Vec::new();
+        for (index, anim) in animations.iter().enumerate() {
+            match anim_groups.last_mut() {
+                Some((_, last, _)) if anim.id.checked_sub(1) == Some(*last) => *last = anim.id,
+                _ => anim_groups.push((anim.id, anim.id, index)),
+            }
+        }
+
+        patch_position(&mut out, 0x60);
+        let _ = out.write_u64::<LE>(anim_groups.len() as u64);
+        let groups_start = out.len() as u64 + 8;
+        let _ = out.write_u64::<LE>(if anim_groups.is_empty() {
+            0
+        } else {
+            groups_start
+        });
+        for (first, last, index) in &anim_groups {
+            let _ = out.write_i32::<LE>(*first as i32);
+            let _ = out.write_i32::<LE>(*last as i32);
+            let _ = out.write_u64::<LE>(animations_offset + *index as u64 * ANIMATION_HEADER_SIZE);
+        }
+
+        let bodies_offset = out.len() as u64;
+        patch_u64(&mut out, 0x78, bodies_offset);
+        for (index, anim) in animations.iter().enumerate() {
+            let header = animations_offset + index as u64 * ANIMATION_HEADER_SIZE;
+            patch_position(&mut out, header + 8);
+
+            out.resize(out.len() + ANIMATION_SIZE as usize, 0);
+            let body = out.len() - ANIMATION_SIZE as usize;
+            LE::write_u32(&mut out[body + 0x20..], anim.events.len() as u32);
+            LE::write_u32(&mut out[body + 0x24..], anim.groups.len() as u32);
+        }
+
+        for (index, anim) in animations.iter().enumerate() {
+            anim.write_body(&mut out, bodies_offset + index as u64 * ANIMATION_SIZE);
+        }
+
+        let file_size = out.len() as u32;
+        LE::write_u32(&mut out[0x0C..], file_size);
+
+        out
+    }
+}
+
+impl TaeAnimation {
+    fn read(r: &mut (impl Read + Seek), file_size: u64) -> Result<Self, TaeError> {
+        let id = r.read_i64::<LE>()?;
+        let offset = r.read_u64::<LE>()?;
+
+        r.seek(SeekFrom::Start(offset))?;
+        let event_headers_offset = r.read_u64::<LE>()?;
+        let event_groups_offset = r.read_u64::<LE>()?;
+        let times_offset = r.read_u64::<LE>()?;
+        let anim_file_offset = r.read_u64::<LE>()?;
+        let event_count = r.read_u32::<LE>()? as u64;
+        let event_group_count = r.read_u32::<LE>()? as u64;
+
+        let mut headers = Vec::with_capacity(event_count as usize);
+        for index in 0..event_count {
+            r.seek(SeekFrom::Start(
+                event_headers_offset + index * EVENT_HEADER_SIZE,
+            ))?;
+
+            headers.push(EventHeader {
+                start_time_offset: r.read_u64::<LE>()?,
+                end_time_offset: r.read_u64::<LE>()?,
+                data_offset: r.read_u64::<LE>()?,
+            });
+        }
+
+        // Parameter data has no stored length, so it is taken to run until the next structure
+        // that follows it.
+        let mut boundaries = headers
+            .iter()
+            .map(|header| header.data_offset)
+            .chain([
+                event_headers_offset,
+                event_groups_offset,
+                times_offset,
+                anim_file_offset,
+                file_size,
+            ])
+            .collect::<Vec<_>>();
+        boundaries.sort_unstable();
+
+        let mut events = Vec::with_capacity(headers.len());
+        for header in &headers {
+            r.seek(SeekFrom::Start(header.start_time_offset))?;
+            let start_time = r.read_f32::<LE>()?;
+            r.seek(SeekFrom::Start(header.end_time_offset))?;
+            let end_time = r.read_f32::<LE>()?;
+
+            r.seek(SeekFrom::Start(header.data_offset))?;
+            let event_type = r.read_i32::<LE>()?;
+            let _unk04 = r.read_i32::<LE>()?;
+            let params_offset = r.read_u64::<LE>()?;
+
+            let params_end = boundaries
+                .iter()
+                .copied()
+                .find(|boundary| *boundary > params_offset)
+                .unwrap_or(params_offset);
+
+            r.seek(SeekFrom::Start(params_offset))?;
+            let mut params = vec![0u8; (params_end - params_offset) as usize];
+            r.read_exact(&mut params)?;
+
+            events.push(TaeEvent {
+                start_time,
+                end_time,
+                event_type,
+                params,
+            });
+        }
+
+        let mut groups = Vec::with_capacity(event_group_count as usize);
+        for index in 0..event_group_count {
+            r.seek(SeekFrom::Start(
+                event_groups_offset + index * EVENT_GROUP_SIZE,
+            ))?;
+            let group_event_count = r.read_u64::<LE>()?;
+            let indices_offset = r.read_u64::<LE>()?;
+            let group_data_offset = r.read_u64::<LE>()?;
+
+            r.seek(SeekFrom::Start(group_data_offset))?;
+            let group_type = r.read_i64::<LE>()?;
+
+            r.seek(SeekFrom::Start(indices_offset))?;
+            let events = (0..group_event_count)
+                .map(|_| {
+                    let header_offset = r.read_u64::<LE>()?;
+                    Ok(
+                        (header_offset.saturating_sub(event_headers_offset) / EVENT_HEADER_SIZE)
+                            as usize,
+                    )
+                })
+                .collect::<Result<Vec<_>, io::Error>>()?;
+
+            groups.push(TaeEventGroup { group_type, events });
+        }
+
+        r.seek(SeekFrom::Start(anim_file_offset))?;
+        let mut anim_file = [0u8; ANIM_FILE_SIZE];
+        r.read_exact(&mut anim_file)?;
+
+        Ok(Self {
+            id,
+            anim_file,
+            events,
+            groups,
+        })
+    }
+}
+
+impl TaeAnimation {
+    /// The ID of the animation this one imports, if it imports another.
+    pub fn import_anim_id(&self) -> Option<i32> {
+        (LE::read_u32(&self.anim_file) == ANIM_FILE_TYPE_IMPORT_OTHER)
+            .then(|| LE::read_i32(&self.anim_file[4..]))
+    }
+
+    fn write_body(&self, out: &mut Vec<u8>, body: u64) {
+        patch_position(out, body + 0x18);
+        out.extend_from_slice(&self.anim_file);
+
+        let mut times = Vec::<f32>::new();
+        for event in &self.events {
+            for time in [event.start_time, event.end_time] {
+                if !times.iter().any(|t| t.to_bits() == time.to_bits()) {
+                    times.push(time);
+                }
+            }
+        }
+
+        let times_offset = out.len() as u64;
+        patch_u64(out, body + 0x10, times_offset);
+        LE::write_u32(&mut out[body as usize + 0x28..], times.len() as u32);
+        for time in &times {
+            let _ = out.write_f32::<LE>(*time);
+        }
+        pad(out, 0x10);
+
+        let time_offset = |time: f32| {
+            let index = times.iter().position(|t| t.to_bits() == time.to_bits());
+            times_offset + index.unwrap_or_default() as u64 * 4
+        };
+
+        let headers_offset = out.len() as u64;
+        patch_u64(out, body, headers_offset);
+        for event in &self.events {
+            let _ = out.write_u64::<LE>(time_offset(event.start_time));
+            let _ = out.write_u64::<LE>(time_offset(event.end_time));
+            let _ = out.write_u64::<LE>(0);
+        }
+
+        for (index, event) in self.events.iter().enumerate() {
+            let data_offset = out.len() as u64;
+            patch_u64(
+                out,
+                headers_offset + index as u64 * EVENT_HEADER_SIZE + 0x10,
+                data_offset,
+            );
+
+            let _ = out.write_i32::<LE>(event.event_type);
+            let _ = out.write_i32::<LE>(0);
+            let _ = out.write_u64::<LE>(data_offset + 0x10);
+            out.extend_from_slice(&event.params);
+            pad(out, 0x10);
+        }
+
+        let groups_offset = out.len() as u64;
+        patch_u64(out, body + 8, groups_offset);
+        out.resize(out.len() + self.groups.len() * EVENT_GROUP_SIZE as usize, 0);
+
+        for (index, group) in self.groups.iter().enumerate() {
+            let group_header = groups_offset + index as u64 * EVENT_GROUP_SIZE;
+
+            patch_position(out, group_header + 0x10);
+            let _ = out.write_i64::<LE>(group.group_type);
+            let _ = out.write_u64::<LE>(0);
+
+            patch_u64(out, group_header, group.events.len() as u64);
+            patch_position(out, group_header + 8);
+            for event in &group.events {
+                let _ = out.write_u64::<LE>(headers_offset + *event as u64 * EVENT_HEADER_SIZE);
+            }
+            pad(out, 0x10);
+        }
+    }
+}
+
+impl TaeEvent {
+    /// Create an event of the given type with its parameters set to their template defaults.
+    pub fn new(
+        event_type: &TaeEventType,
+        start_time: f32,
+        end_time: f32,
+    ) -> Result<Self, TaeTemplateError> {
+        Ok(Self {
+            start_time,
+            end_time,
+            event_type: event_type.id,
+            params: event_type.encode(&[])?,
+        })
+    }
+
+    /// Set a single named parameter using the layout of its type in `template`, leaving the other
+    /// parameters untouched.
+    pub fn set_param(
+        &mut self,
+        event_type: &TaeEventType,
+        name: &str,
+        value: TaeValue,
+    ) -> Result<(), TaeTemplateError> {
+        event_type.write_param(&mut self.params, name, value)
+    }
+
+    /// Decode the parameters of this event using the layout of its type in `template`.
+    ///
+    /// Returns `None` if the template has no definition for this event type.
+    pub fn decode(
+        &self,
+        template: &TaeTemplate,
+        bank: i64,
+    ) -> Option<Result<Vec<(String, TaeValue)>, TaeTemplateError>> {
+        let event_type = template.event_type(bank, self.event_type)?;
+
+        Some(event_type.decode(&self.params))
+    }
+}
+
+fn patch_u64(out: &mut [u8], offset: u64, value: u64) {
+    LE::write_u64(&mut out[offset as usize..], value);
+}
+
+/// Fill in a placeholder offset with the current end of the output.
+fn patch_position(out: &mut [u8], offset: u64) {
+    let position = out.len() as u64;
+    patch_u64(out, offset, position);
+}
+
+fn write_utf16(out: &mut Vec<u8>, value: &str) {
+    for unit in value.encode_utf16().chain([0]) {
+        out.extend_from_slice(&unit.to_le_bytes());
+    }
+}
+
+fn pad(out: &mut Vec<u8>, alignment: usize) {
+    out.resize(out.len().next_multiple_of(alignment), 0);
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::tae::{Tae, TaeAnimation, TaeEvent, TaeEventGroup, TaeTemplate, TaeValue};
+
+    #[test]
+    pub fn writes_edited_events() {
+        let template = TaeTemplate::from_xml(
+            r#"<template><bank id="20"><type id="1" name="Attack">
+                <s32 name="BehaviorJudgeID" default="-1" />
+                <assert type="u32" value="0" />
+            </type></bank></template>"#,
+        )
+        .unwrap();
+        let attack = template.event_type(20, 1).unwrap();
+
+        let mut event = TaeEvent::new(attack, 0.1, 0.5).unwrap();
+        event
+            .set_param(attack, "BehaviorJudgeID", TaeValue::Signed(100))
+            .unwrap();
+
+        let tae = Tae {
+            version: 0x1000D,
+            id: 2000,
+            event_bank: 20,
+            flags: [1, 0, 1, 2, 2, 1, 1, 1],
+            skeleton_name: "skeleton.hkt".to_string(),
+            sib_name: "c2000.sib".to_string(),
+            animations: vec![TaeAnimation {
+                id: 3000,
+                anim_file: [0; 16],
+                events: vec![event],
+                groups: vec![TaeEventGroup {
+                    group_type: 1,
+                    events: vec![0],
+                }],
+            }],
+        };
+
+        let bytes = tae.to_bytes();
+        let read = Tae::from_reader(&mut Cursor::new(&bytes)).unwrap();
+        assert_eq!(read.to_bytes(), bytes);
+
+        let anim = read.animation(3000).unwrap();
+        assert_eq!(anim.groups[0].events, [0]);
+        assert_eq!(
+            (anim.events[0].start_time, anim.events[0].end_time),
+            (0.1, 0.5)
+        );
+        assert_eq!(
+            anim.events[0]
+                .decode(&template, read.event_bank)
+                .unwrap()
+                .unwrap(),
+            vec![("BehaviorJudgeID".to_string(), TaeValue::Signed(100))]
+        );
+    }
+}
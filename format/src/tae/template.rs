@@ -0,0 +1,444 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use byteorder::{ByteOrder, LE};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum TaeTemplateError {
+    #[error("Could not read template: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Could not parse template XML: {0}")]
+    Xml(#[from] roxmltree::Error),
+
+    #[error("Template element is missing attribute {0}")]
+    MissingAttribute(&'static str),
+
+    #[error("Template attribute {0} is not a valid number")]
+    InvalidNumber(String),
+
+    #[error("Unknown template parameter type {0}")]
+    UnknownParamType(String),
+
+    #[error("Template value {value} is not a valid {param_type:?}")]
+    InvalidValue {
+        value: String,
+        param_type: TaeParamType,
+    },
+
+    #[error("Event type {event} has no parameter named {name}")]
+    UnknownParam { event: String, name: String },
+
+    #[error("Value {value} can't be stored in parameter {name}")]
+    TypeMismatch { name: String, value: TaeValue },
+
+    #[error("Event parameters are {len} bytes, template {event} expects at least {expected}")]
+    ParamsTooShort {
+        event: String,
+        len: usize,
+        expected: usize,
+    },
+}
+
+/// Event parameter layouts in the XML format used by DS Anim Studio
+/// (e.g. `TAE.Template.ER.xml`), keyed by event bank and event type.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TaeTemplate {
+    types: HashMap<(i64, i32), TaeEventType>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TaeEventType {
+    pub id: i32,
+    pub name: String,
+    pub params: Vec<TaeParam>,
+}
+
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TaeParam {
+    /// Empty for asserted values, which aren't exposed when decoding.
+    pub name: String,
+    pub param_type: TaeParamType,
+
+    /// The value written for new events, or the required value of an asserted parameter.
+    pub default: Option<TaeValue>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TaeParamType {
+    Bool,
+    U8,
+    S8,
+    X8,
+    U16,
+    S16,
+    X16,
+    U32,
+    S32,
+    X32,
+    U64,
+    S64,
+    X64,
+    F32,
+    F64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TaeValue {
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Hex(u64),
+    Float(f64),
+}
+
+impl TaeParamType {
+    pub fn size(self) -> usize {
+        match self {
+            Self::Bool | Self::U8 | Self::S8 | Self::X8 => 1,
+            Self::U16 | Self::S16 | Self::X16 => 2,
+            Self::U32 | Self::S32 | Self::X32 | Self::F32 => 4,
+            Self::U64 | Self::S64 | Self::X64 | Self::F64 => 8,
+        }
+    }
+
+    fn read(self, bytes: &[u8]) -> TaeValue {
+        match self {
+            Self::Bool => TaeValue::Bool(bytes[0] != 0),
+            Self::U8 => TaeValue::Unsigned(bytes[0] as u64),
+            Self::S8 => TaeValue::Signed(bytes[0] as i8 as i64),
+            Self::X8 => TaeValue::Hex(bytes[0] as u64),
+            Self::U16 => TaeValue::Unsigned(LE::read_u16(bytes) as u64),
+            Self::S16 => TaeValue::Signed(LE::read_i16(bytes) as i64),
+            Self::X16 => TaeValue::Hex(LE::read_u16(bytes) as u64),
+            Self::U32 => TaeValue::Unsigned(LE::read_u32(bytes) as u64),
+            Self::S32 => TaeValue::Signed(LE::read_i32(bytes) as i64),
+            Self::X32 => TaeValue::Hex(LE::read_u32(bytes) as u64),
+            Self::U64 => TaeValue::Unsigned(LE::read_u64(bytes)),
+            Self::S64 => TaeValue::Signed(LE::read_i64(bytes)),
+            Self::X64 => TaeValue::Hex(LE::read_u64(bytes)),
+            Self::F32 => TaeValue::Float(LE::read_f32(bytes) as f64),
+            Self::F64 => TaeValue::Float(LE::read_f64(bytes)),
+        }
+    }
+}
+
+impl TaeParamType {
+    fn write(self, value: &TaeValue, bytes: &mut [u8]) -> Option<()> {
+        match (self, value) {
+            (Self::Bool, TaeValue::Bool(v)) => bytes[0] = *v as u8,
+            (Self::U8 | Self::X8, TaeValue::Unsigned(v) | TaeValue::Hex(v)) => {
+                bytes[0] = u8::try_from(*v).ok()?
+            }
+            (Self::S8, TaeValue::Signed(v)) => bytes[0] = i8::try_from(*v).ok()? as u8,
+            (Self::U16 | Self::X16, TaeValue::Unsigned(v) | TaeValue::Hex(v)) => {
+                LE::write_u16(bytes, u16::try_from(*v).ok()?)
+            }
+            (Self::S16, TaeValue::Signed(v)) => LE::write_i16(bytes, i16::try_from(*v).ok()?),
+            (Self::U32 | Self::X32, TaeValue::Unsigned(v) | TaeValue::Hex(v)) => {
+                LE::write_u32(bytes, u32::try_from(*v).ok()?)
+            }
+            (Self::S32, TaeValue::Signed(v)) => LE::write_i32(bytes, i32::try_from(*v).ok()?),
+            (Self::U64 | Self::X64, TaeValue::Unsigned(v) | TaeValue::Hex(v)) => {
+                LE::write_u64(bytes, *v)
+            }
+            (Self::S64, TaeValue::Signed(v)) => LE::write_i64(bytes, *v),
+            (Self::F32, TaeValue::Float(v)) => LE::write_f32(bytes, *v as f32),
+            (Self::F64, TaeValue::Float(v)) => LE::write_f64(bytes, *v),
+            _ => return None,
+        }
+
+        Some(())
+    }
+
+    /// Parse a value of this type from its textual form in a template.
+    pub fn parse_value(self, value: &str) -> Result<TaeValue, TaeTemplateError> {
+        let value = value.trim();
+        let parsed = match self {
+            Self::Bool => match value {
+                "true" | "1" => Some(TaeValue::Bool(true)),
+                "false" | "0" => Some(TaeValue::Bool(false)),
+                _ => None,
+            },
+            Self::U8 | Self::U16 | Self::U32 | Self::U64 => {
+                value.parse().ok().map(TaeValue::Unsigned)
+            }
+            Self::S8 | Self::S16 | Self::S32 | Self::S64 => {
+                value.parse().ok().map(TaeValue::Signed)
+            }
+            Self::X8 | Self::X16 | Self::X32 | Self::X64 => {
+                let digits = value.trim_start_matches("0x").trim_start_matches("0X");
+                u64::from_str_radix(digits, 16).ok().map(TaeValue::Hex)
+            }
+            Self::F32 | Self::F64 => value.parse().ok().map(TaeValue::Float),
+        };
+
+        parsed.ok_or_else(|| TaeTemplateError::InvalidValue {
+            value: value.to_string(),
+            param_type: self,
+        })
+    }
+}
+
+impl FromStr for TaeParamType {
+    type Err = TaeTemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "b" => Self::Bool,
+            "u8" => Self::U8,
+            "s8" => Self::S8,
+            "x8" => Self::X8,
+            "u16" => Self::U16,
+            "s16" => Self::S16,
+            "x16" => Self::X16,
+            "u32" => Self::U32,
+            "s32" => Self::S32,
+            "x32" => Self::X32,
+            "u64" => Self::U64,
+            "s64" => Self::S64,
+            "x64" => Self::X64,
+            "f32" => Self::F32,
+            "f64" => Self::F64,
+            _ => return Err(TaeTemplateError::UnknownParamType(s.to_string())),
+        })
+    }
+}
+
+impl fmt::Display for TaeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(v) => write!(f, "{v}"),
+            Self::Unsigned(v) => write!(f, "{v}"),
+            Self::Signed(v) => write!(f, "{v}"),
+            Self::Hex(v) => write!(f, "{v:#x}"),
+            Self::Float(v) => write!(f, "{v}"),
+        }
+    }
+}
+
+impl TaeTemplate {
+    /// Parse a template of `<bank id="..">` elements, each containing `<type id=".." name="..">`
+    /// elements whose children describe the event parameters in order, e.g. `<f32 name="Speed"/>`.
+    pub fn from_xml(xml: &str) -> Result<Self, TaeTemplateError> {
+        let document = roxmltree::Document::parse(xml)?;
+        let mut template = Self::default();
+
+        for bank in document
+            .descendants()
+            .filter(|node| node.has_tag_name("bank"))
+        {
+            let bank_id = number_attribute::<i64>(bank, "id")?;
+
+            for event_type in bank.children().filter(|node| node.has_tag_name("type")) {
+                let id = number_attribute::<i32>(event_type, "id")?;
+                let name = event_type.attribute("name").unwrap_or_default().to_string();
+
+                let params = event_type
+                    .children()
+                    .filter(|node| node.is_element())
+                    .map(|param| {
+                        // Asserted values are unnamed padding with a type attribute.
+                        let (tag, name, default) = match param.tag_name().name() {
+                            "assert" => (
+                                param
+                                    .attribute("type")
+                                    .ok_or(TaeTemplateError::MissingAttribute("type"))?,
+                                String::new(),
+                                param.attribute("value"),
+                            ),
+                            tag => (
+                                tag,
+                                param.attribute("name").unwrap_or(tag).to_string(),
+                                param.attribute("default"),
+                            ),
+                        };
+
+                        let param_type = tag.parse::<TaeParamType>()?;
+                        let default = default
+                            .map(|value| param_type.parse_value(value))
+                            .transpose()?;
+
+                        Ok(TaeParam {
+                            name,
+                            param_type,
+                            default,
+                        })
+                    })
+                    .collect::<Result<Vec<_>, TaeTemplateError>>()?;
+
+                template
+                    .types
+                    .insert((bank_id, id), TaeEventType { id, name, params });
+            }
+        }
+
+        Ok(template)
+    }
+
+    /// Load a template file, such as the one shipped with DS Anim Studio for a particular game.
+    pub fn load<P: AsRef<std::path::Path>>(path: P) -> Result<Self, TaeTemplateError> {
+        Self::from_xml(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn event_type(&self, bank: i64, id: i32) -> Option<&TaeEventType> {
+        self.types.get(&(bank, id))
+    }
+}
+
+impl TaeEventType {
+    /// Total size of the parameters described by this event type.
+    pub fn size(&self) -> usize {
+        self.params
+            .iter()
+            .map(|param| param.param_type.size())
+            .sum()
+    }
+
+    /// Decode the named parameters of an event, skipping unnamed asserted values.
+    pub fn decode(&self, params: &[u8]) -> Result<Vec<(String, TaeValue)>, TaeTemplateError> {
+        if params.len() < self.size() {
+            return Err(TaeTemplateError::ParamsTooShort {
+                event: self.name.clone(),
+                len: params.len(),
+                expected: self.size(),
+            });
+        }
+
+        let mut offset = 0;
+        let mut values = Vec::with_capacity(self.params.len());
+        for param in &self.params {
+            let size = param.param_type.size();
+            if !param.name.is_empty() {
+                let value = param.param_type.read(&params[offset..offset + size]);
+                values.push((param.name.clone(), value));
+            }
+
+            offset += size;
+        }
+
+        Ok(values)
+    }
+
+    /// Encode parameters for an event of this type. Parameters missing from `values` are set to
+    /// their template default, or zero.
+    pub fn encode(&self, values: &[(String, TaeValue)]) -> Result<Vec<u8>, TaeTemplateError> {
+        let mut params = vec![0u8; self.size()];
+        let mut offset = 0;
+
+        for param in &self.params {
+            let size = param.param_type.size();
+            let value = values
+                .iter()
+                .find(|(name, _)| !param.name.is_empty() && *name == param.name)
+                .map(|(_, value)| value)
+                .or(param.default.as_ref());
+
+            if let Some(value) = value {
+                param
+                    .param_type
+                    .write(value, &mut params[offset..offset + size])
+                    .ok_or_else(|| TaeTemplateError::TypeMismatch {
+                        name: param.name.clone(),
+                        value: value.clone(),
+                    })?;
+            }
+
+            offset += size;
+        }
+
+        Ok(params)
+    }
+
+    /// Overwrite a single named parameter in the encoded parameters of an event.
+    pub fn write_param(
+        &self,
+        params: &mut [u8],
+        name: &str,
+        value: TaeValue,
+    ) -> Result<(), TaeTemplateError> {
+        if params.len() < self.size() {
+            return Err(TaeTemplateError::ParamsTooShort {
+                event: self.name.clone(),
+                len: params.len(),
+                expected: self.size(),
+            });
+        }
+
+        let mut offset = 0;
+        for param in &self.params {
+            let size = param.param_type.size();
+            if !param.name.is_empty() && param.name == name {
+                return param
+                    .param_type
+                    .write(&value, &mut params[offset..offset + size])
+                    .ok_or_else(|| TaeTemplateError::TypeMismatch {
+                        name: param.name.clone(),
+                        value,
+                    });
+            }
+
+            offset += size;
+        }
+
+        Err(TaeTemplateError::UnknownParam {
+            event: self.name.clone(),
+            name: name.to_string(),
+        })
+    }
+}
+
+fn number_attribute<T: FromStr>(
+    node: roxmltree::Node,
+    name: &'static str,
+) -> Result<T, TaeTemplateError> {
+    let value = node
+        .attribute(name)
+        .ok_or(TaeTemplateError::MissingAttribute(name))?;
+
+    value
+        .parse()
+        .map_err(|_| TaeTemplateError::InvalidNumber(value.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tae::{TaeTemplate, TaeValue};
+
+    #[test]
+    pub fn decodes_params_from_template() {
+        let template = TaeTemplate::from_xml(
+            r#"<template>
+                <bank id="20" name="Default">
+                    <type id="795" name="DisableDefaultWeaponTrail">
+                        <s32 name="Unk00" />
+                        <assert type="u8" value="0" />
+                        <b name="Enabled" />
+                        <x16 name="Mask" />
+                        <f32 name="Speed" />
+                    </type>
+                </bank>
+            </template>"#,
+        )
+        .unwrap();
+
+        let event_type = template.event_type(20, 795).unwrap();
+        assert_eq!(event_type.encode(&[]).unwrap(), [0; 12]);
+        let params = [0xFF, 0xFF, 0xFF, 0xFF, 0, 1, 0x34, 0x12, 0, 0, 0x80, 0x3F];
+
+        assert_eq!(
+            event_type.decode(&params).unwrap(),
+            vec![
+                ("Unk00".to_string(), TaeValue::Signed(-1)),
+                ("Enabled".to_string(), TaeValue::Bool(true)),
+                ("Mask".to_string(), TaeValue::Hex(0x1234)),
+                ("Speed".to_string(), TaeValue::Float(1.0)),
+            ]
+        );
+    }
+}
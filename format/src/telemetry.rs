@@ -0,0 +1,167 @@
+use alloc::collections::BTreeMap;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A value of an unknown field, stored as its raw bits.
+pub trait UnknownValue {
+    fn to_bits(self) -> u64;
+}
+
+macro_rules! unknown_value {
+    ($($ty:ty => $bits:ty),*) => {
+        $(impl UnknownValue for $ty {
+            fn to_bits(self) -> u64 {
+                self as $bits as u64
+            }
+        })*
+    };
+}
+
+unknown_value!(
+    u8 => u8,
+    u16 => u16,
+    u32 => u32,
+    u64 => u64,
+    i8 => u8,
+    i16 => u16,
+    i32 => u32,
+    i64 => u64,
+    bool => u8
+);
+
+impl UnknownValue for f32 {
+    fn to_bits(self) -> u64 {
+        f32::to_bits(self) as u64
+    }
+}
+
+/// Distinct values observed for the unknown fields of each format, gathered across any number of
+/// files to help work out what the fields mean.
+///
+/// Parsers record into this through [`crate::diagnostics::Diagnostics::observe`] once recording
+/// is enabled with [`crate::diagnostics::Diagnostics::record_unknown_fields`].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UnknownFields {
+    fields: BTreeMap<(&'static str, &'static str), BTreeMap<u64, usize>>,
+}
+
+/// The values observed for one unknown field.
+#[derive(Clone, Copy, Debug)]
+pub struct FieldSummary<'a> {
+    pub format: &'static str,
+    pub field: &'static str,
+
+    /// How many times each value was observed, by raw bits.
+    pub values: &'a BTreeMap<u64, usize>,
+}
+
+impl FieldSummary<'_> {
+    /// How many times the field was observed in total.
+    pub fn count(&self) -> usize {
+        self.values.values().sum()
+    }
+
+    /// Whether the field only ever held a single value, and is likely padding or a constant.
+    pub fn is_constant(&self) -> bool {
+        self.values.len() == 1
+    }
+}
+
+impl UnknownFields {
+    pub fn record(&mut self, format: &'static str, field: &'static str, value: impl UnknownValue) {
+        *self
+            .fields
+            .entry((format, field))
+            .or_default()
+            .entry(value.to_bits())
+            .or_default() += 1;
+    }
+
+    /// Add the observations of another corpus, e.g. one gathered on another thread.
+    pub fn merge(&mut self, other: &UnknownFields) {
+        for (key, values) in &other.fields {
+            let counts = self.fields.entry(*key).or_default();
+            for (value, count) in values {
+                *counts.entry(*value).or_default() += count;
+            }
+        }
+    }
+
+    pub fn field(&self, format: &'static str, field: &'static str) -> Option<FieldSummary<'_>> {
+        self.fields
+            .get(&(format, field))
+            .map(|values| FieldSummary {
+                format,
+                field,
+                values,
+            })
+    }
+
+    /// Every observed field, ordered by format and then field name.
+    pub fn fields(&self) -> impl Iterator<Item = FieldSummary<'_>> {
+        self.fields
+            .iter()
+            .map(|((format, field), values)| FieldSummary {
+                format,
+                field,
+                values,
+            })
+    }
+}
+
+/// One line per field, listing its values from most to least common.
+impl fmt::Display for UnknownFields {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for summary in self.fields() {
+            let mut values = summary.values.iter().collect::<Vec<_>>();
+            values.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+            write!(
+                f,
+                "{} {}: {} observations, {} distinct:",
+                summary.format,
+                summary.field,
+                summary.count(),
+                values.len()
+            )?;
+            for (value, count) in values {
+                write!(f, " {value:#x} ({count})")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use alloc::format;
+
+    use crate::telemetry::UnknownFields;
+
+    #[test]
+    pub fn counts_distinct_values() {
+        let mut fields = UnknownFields::default();
+        fields.record("BND4", "unk04", 0u8);
+        fields.record("BND4", "unk04", 1u8);
+        fields.record("BND4", "unk04", 1u8);
+        fields.record("FLVER", "unk14", -1i32);
+
+        let mut other = UnknownFields::default();
+        other.record("BND4", "unk05", true);
+        fields.merge(&other);
+
+        let unk04 = fields.field("BND4", "unk04").unwrap();
+        assert_eq!(unk04.count(), 3);
+        assert_eq!(unk04.values.get(&1), Some(&2));
+        assert!(fields.field("BND4", "unk05").unwrap().is_constant());
+        assert_eq!(
+            fields.field("FLVER", "unk14").unwrap().values.keys().next(),
+            Some(&0xFFFFFFFF)
+        );
+        assert_eq!(fields.fields().count(), 3);
+        assert!(format!("{fields}").starts_with("BND4 unk04: 3 observations, 2 distinct: 0x1 (2)"));
+    }
+}
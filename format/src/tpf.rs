@@ -1,59 +1,198 @@
-use std::io::{self, SeekFrom};
+use std::io::{self, SeekFrom, Write};
 
 use byteorder::{ReadBytesExt, LE};
+use thiserror::Error;
+use tracing::{debug, instrument};
 
-use crate::io_ext::ReadFormatsExt;
+use crate::{diagnostics::Diagnostics, error::FormatError, io_ext::ReadFormatsExt};
 
-#[derive(Debug)]
+const TEXTURE_HEADER_SIZE: u64 = 0x14;
+
+#[derive(Debug, Error)]
 pub enum TPFError {
-    IO(io::Error),
+    #[error("Could not read TPF: {0}")]
+    IO(#[from] io::Error),
+
+    #[error("TPF has {textures} textures but {data} were given to write")]
+    DataCount { textures: usize, data: usize },
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TPF {
+    pub platform: u8,
+    pub unk0d: u8,
     pub textures: Vec<Texture>,
 }
 
 impl TPF {
     pub fn from_reader(r: &mut (impl io::Read + io::Seek)) -> Result<Self, io::Error> {
+        Self::from_reader_with(r, &mut Diagnostics::default())
+    }
+
+    /// Read a TPF, recovering from unknown encodings, texture counts that don't fit in the file and
+    /// truncation when `diagnostics` is lenient.
+    #[instrument(name = "tpf", skip_all)]
+    pub fn from_reader_with(
+        r: &mut (impl io::Read + io::Seek),
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self, io::Error> {
         r.read_magic(b"TPF\0")?;
 
         let _data_size = r.read_u32::<LE>()?;
-        let texture_count = r.read_u32::<LE>()?;
-        let _platform = r.read_u8()?;
-        let _unk0d = r.read_u8()?;
-        assert!(r.read_u8()? == 0x1, "Encoding isn't 0x1");
+        let texture_count_offset = r.stream_position()?;
+        let mut texture_count = r.read_u32::<LE>()?;
+        let platform = r.read_u8()?;
+        let unk0d = r.read_u8()?;
+        diagnostics.observe("TPF", "unk0d", unk0d);
+        let encoding = r.read_u8()?;
+        if encoding != 0x1 {
+            let offset = r.stream_position()? - 1;
+            diagnostics.report(FormatError::malformed(
+                "TPF",
+                "header",
+                offset,
+                format!("unsupported encoding {encoding:#x}"),
+            ))?;
+        }
         r.read_padding(1)?;
 
+        let header_end = r.stream_position()?;
+        let length = r.seek(SeekFrom::End(0))?;
+        let fitting_count = (length - header_end) / TEXTURE_HEADER_SIZE;
+        r.seek(SeekFrom::Start(header_end))?;
+        if texture_count as u64 > fitting_count {
+            diagnostics.report(FormatError::malformed(
+                "TPF",
+                "header",
+                texture_count_offset,
+                format!("{texture_count} textures don't fit in the file, reading {fitting_count}"),
+            ))?;
+            texture_count = fitting_count as u32;
+        }
+
+        debug!(texture_count, "read header");
+
         let mut textures = vec![];
         for _ in 0..texture_count {
-            textures.push(Texture::from_reader(r)?);
+            let offset = r.stream_position()?;
+            let texture = match Texture::from_reader_with(r, diagnostics) {
+                Ok(texture) => texture,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                    diagnostics.truncated("TPF", "texture header", offset)?;
+                    break;
+                }
+                Err(e) => return Err(e),
+            };
+
+            // Textures whose data was cut off are left out.
+            if texture.data_offset as u64 + texture.data_size as u64 > length {
+                diagnostics.truncated("TPF", "texture data", texture.data_offset as u64)?;
+            } else {
+                textures.push(texture);
+            }
         }
 
-        Ok(Self { textures })
+        Ok(Self {
+            platform,
+            unk0d,
+            textures,
+        })
+    }
+
+    /// Write the TPF with `data` as the contents of each of its textures, in order. Offsets and
+    /// sizes are laid out from scratch rather than taken from the textures.
+    pub fn write(&self, w: &mut impl Write, data: &[impl AsRef<[u8]>]) -> Result<(), TPFError> {
+        if data.len() != self.textures.len() {
+            return Err(TPFError::DataCount {
+                textures: self.textures.len(),
+                data: data.len(),
+            });
+        }
+
+        let names_start = 0x10 + self.textures.len() * TEXTURE_HEADER_SIZE as usize;
+        let mut names = Vec::new();
+        let mut name_offsets = Vec::with_capacity(self.textures.len());
+        for texture in &self.textures {
+            name_offsets.push((names_start + names.len()) as u32);
+            for unit in texture.name.encode_utf16().chain([0]) {
+                names.extend(unit.to_le_bytes());
+            }
+        }
+
+        let data_start = (names_start + names.len()).next_multiple_of(0x10);
+        names.resize(data_start - names_start, 0);
+
+        let mut contents = Vec::new();
+        let mut headers = Vec::with_capacity(names_start - 0x10);
+        for ((texture, data), name_offset) in self.textures.iter().zip(data).zip(name_offsets) {
+            let data = data.as_ref();
+            headers.extend(((data_start + contents.len()) as u32).to_le_bytes());
+            headers.extend((data.len() as u32).to_le_bytes());
+            headers.extend([
+                texture.format,
+                texture.cubemap,
+                texture.mipmaps,
+                texture.unk0b,
+            ]);
+            headers.extend(name_offset.to_le_bytes());
+            headers.extend(texture.unk10.to_le_bytes());
+
+            contents.extend(data);
+            contents.resize(contents.len().next_multiple_of(0x10), 0);
+        }
+
+        w.write_all(b"TPF\0")?;
+        w.write_all(&(contents.len() as u32).to_le_bytes())?;
+        w.write_all(&(self.textures.len() as u32).to_le_bytes())?;
+        w.write_all(&[self.platform, self.unk0d, 0x1, 0x0])?;
+        w.write_all(&headers)?;
+        w.write_all(&names)?;
+        w.write_all(&contents)?;
+
+        Ok(())
+    }
+
+    pub fn to_bytes(&self, data: &[impl AsRef<[u8]>]) -> Result<Vec<u8>, TPFError> {
+        let mut out = Vec::new();
+        self.write(&mut out, data)?;
+
+        Ok(out)
     }
 }
 
 #[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Texture {
     pub data_offset: u32,
     pub data_size: u32,
     pub format: u8,
     pub cubemap: u8,
     pub mipmaps: u8,
+    pub unk0b: u8,
+    pub unk10: u32,
     pub name: String,
 }
 
 impl Texture {
     pub fn from_reader(r: &mut (impl io::Read + io::Seek)) -> Result<Self, io::Error> {
+        Self::from_reader_with(r, &mut Diagnostics::default())
+    }
+
+    pub fn from_reader_with(
+        r: &mut (impl io::Read + io::Seek),
+        diagnostics: &mut Diagnostics,
+    ) -> Result<Self, io::Error> {
         let data_offset = r.read_u32::<LE>()?;
         let data_size = r.read_u32::<LE>()?;
         let format = r.read_u8()?;
         let cubemap = r.read_u8()?;
         let mipmaps = r.read_u8()?;
-        let _unk0b = r.read_u8()?;
+        let unk0b = r.read_u8()?;
         let name_offset = r.read_u32::<LE>()?;
-        let _unk10 = r.read_u32::<LE>()?;
+        let unk10 = r.read_u32::<LE>()?;
+        diagnostics.observe("TPF", "texture unk0b", unk0b);
+        diagnostics.observe("TPF", "texture unk10", unk10);
 
         let current = r.stream_position()?;
         r.seek(SeekFrom::Start(name_offset as u64))?;
@@ -66,6 +205,8 @@ impl Texture {
             format,
             cubemap,
             mipmaps,
+            unk0b,
+            unk10,
             name,
         })
     }
@@ -77,3 +218,40 @@ impl Texture {
         Ok(buffer)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use crate::tpf::{Texture, TPF};
+
+    #[test]
+    pub fn round_trips_textures() {
+        let texture = |name: &str, format| Texture {
+            data_offset: 0,
+            data_size: 0,
+            format,
+            cubemap: 0,
+            mipmaps: 1,
+            unk0b: 0,
+            unk10: 0,
+            name: name.to_string(),
+        };
+        let tpf = TPF {
+            platform: 0,
+            unk0d: 3,
+            textures: vec![texture("c1000_a", 0), texture("c1000_n", 106)],
+        };
+        let data = [vec![1u8; 8], vec![2u8; 0x18]];
+
+        let mut bytes = Cursor::new(tpf.to_bytes(&data).unwrap());
+        let read = TPF::from_reader(&mut bytes).unwrap();
+
+        assert_eq!(read.unk0d, 3);
+        for ((read, expected), data) in read.textures.iter().zip(&tpf.textures).zip(&data) {
+            assert_eq!(read.name, expected.name);
+            assert_eq!(read.format, expected.format);
+            assert_eq!(&read.bytes(&mut bytes).unwrap(), data);
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use std::mem::size_of_val;
+
+use format::hkx::{CollisionMesh, HkaAnimation, Hkx};
+use souls_vfs::{undo_container_compression, MemoryUsage, ResidentMemory, Vfs};
+use tracing::{debug, instrument};
+
+use crate::{file_name, find_file, open_bnd, LoadError};
+
+/// The pieces of an Elden Ring asset, e.g. a breakable object or a piece of scenery, gathered from
+/// its binders.
+///
+/// The `geombnd` holds the model and any animations, while collision lives in a separate
+/// `geomhkxbnd` per level of detail.
+pub struct Asset {
+    /// ID of the asset, e.g. `aeg099_001`.
+    pub id: String,
+
+    /// The decompressed FLVER, which can be parsed with [`format::flver::Flver::parse`].
+    pub flver: Vec<u8>,
+
+    /// Collision of the high detail variant, which is what the player collides with.
+    pub collision: Vec<CollisionMesh>,
+    pub animations: Vec<HkaAnimation>,
+}
+
+impl Asset {
+    #[instrument(name = "asset", skip(vfs))]
+    pub fn load(vfs: &Vfs, id: &str) -> Result<Self, LoadError> {
+        let id = id.to_ascii_lowercase();
+        let category = id.get(..6).unwrap_or(&id);
+        let directory = format!("/asset/{}/{category}", &id[..id.len().min(3)]);
+
+        let geombnd_path = format!("{directory}/{id}.geombnd.dcx");
+        let geombnd = open_bnd(vfs, &geombnd_path)?;
+
+        let flver = find_file(&geombnd, &format!("{id}.flver"))
+            .ok_or_else(|| LoadError::NoFlver(geombnd_path.clone()))?;
+        let flver = undo_container_compression(flver.to_vec())?;
+
+        let mut animations = Vec::new();
+        for file in &geombnd.files {
+            if file_name(&file.path).ends_with(".hkx") {
+                animations.extend(Hkx::from_bytes(geombnd.file_bytes(file))?.animations()?);
+            }
+        }
+
+        let mut collision = Vec::new();
+        if let Ok(geomhkxbnd) = open_bnd(vfs, &format!("{directory}/{id}_h.geomhkxbnd.dcx")) {
+            for file in &geomhkxbnd.files {
+                let name = file_name(&file.path);
+                if name.ends_with(".hkx") || name.ends_with(".hkx.dcx") {
+                    let hkx = Hkx::from_bytes(geomhkxbnd.file_bytes(file))?;
+                    collision.extend(hkx.collision_meshes()?);
+                }
+            }
+        }
+
+        debug!(
+            flver_size = flver.len(),
+            collision = collision.len(),
+            animations = animations.len(),
+            "loaded asset"
+        );
+
+        Ok(Self {
+            id,
+            flver,
+            collision,
+            animations,
+        })
+    }
+}
+
+/// The FLVER and the vertices, indices and animation tracks decoded from the Havok files.
+impl ResidentMemory for Asset {
+    fn memory_usage(&self) -> MemoryUsage {
+        let collision = self
+            .collision
+            .iter()
+            .map(|mesh| size_of_val(&mesh.vertices[..]) + size_of_val(&mesh.indices[..]))
+            .sum::<usize>();
+        let animations = self
+            .animations
+            .iter()
+            .map(|animation| {
+                size_of_val(&animation.track_to_bone[..])
+                    + animation
+                        .tracks
+                        .iter()
+                        .map(|track| size_of_val(&track[..]))
+                        .sum::<usize>()
+            })
+            .sum::<usize>();
+
+        let mut usage = MemoryUsage::default();
+        usage.add(None, "flver", self.flver.len());
+        usage.add(None, "hkx", collision + animations);
+
+        usage
+    }
+}
@@ -0,0 +1,127 @@
+use std::{io::Cursor, mem::size_of_val};
+
+use format::{
+    bxf4::BXF4,
+    hkx::{HkaSkeleton, Hkx, SimCloth},
+    tpf::TPF,
+};
+use souls_vfs::{undo_container_compression, MemoryUsage, ResidentMemory, Vfs};
+use tracing::{debug, instrument};
+
+use crate::{find_file, open_bnd, read_vfs, LoadError};
+
+/// Everything needed to display a character, gathered from its binders.
+///
+/// The model itself lives in the `chrbnd`, alongside its cloth and, before Elden Ring, a texture
+/// header (`chrtpfbhd`) whose data is in a loose `chrtpfbdt`. Elden Ring moved the textures to a
+/// `texbnd` and the skeleton to the `anibnd`.
+pub struct Character {
+    /// ID of the character, e.g. `c3251`.
+    pub id: String,
+
+    /// The decompressed FLVER, which can be parsed with [`format::flver::Flver::parse`].
+    pub flver: Vec<u8>,
+    pub skeleton: Option<HkaSkeleton>,
+    pub cloth: Vec<SimCloth>,
+
+    /// The character's textures as DDS files, by name.
+    pub textures: Vec<(String, Vec<u8>)>,
+}
+
+impl Character {
+    #[instrument(name = "character", skip(vfs))]
+    pub fn load(vfs: &Vfs, id: &str) -> Result<Self, LoadError> {
+        let chrbnd_path = format!("/chr/{id}.chrbnd.dcx");
+        let chrbnd = open_bnd(vfs, &chrbnd_path)?;
+
+        let flver = find_file(&chrbnd, &format!("{id}.flver"))
+            .ok_or_else(|| LoadError::NoFlver(chrbnd_path.clone()))?;
+        let flver = undo_container_compression(flver.to_vec())?;
+
+        let cloth = match find_file(&chrbnd, &format!("{id}_c.hkx")) {
+            Some(bytes) => match Hkx::from_bytes(bytes)? {
+                Hkx::Tagfile(tagfile) => SimCloth::from_tagfile(&tagfile)?,
+                Hkx::Packfile(_) => Vec::new(),
+            },
+            None => Vec::new(),
+        };
+
+        let skeleton = match find_file(&chrbnd, "skeleton.hkx") {
+            Some(bytes) => Some(bytes.to_vec()),
+            None => open_bnd(vfs, &format!("/chr/{id}.anibnd.dcx"))
+                .ok()
+                .and_then(|anibnd| find_file(&anibnd, "skeleton.hkx").map(<[u8]>::to_vec)),
+        };
+        let skeleton = match skeleton {
+            Some(bytes) => Hkx::from_bytes(&bytes)?.skeletons()?.into_iter().next(),
+            None => None,
+        };
+
+        let mut tpfs = Vec::new();
+        if let Some(bhd) = find_file(&chrbnd, &format!("{id}.chrtpfbhd")) {
+            let bdt = read_vfs(vfs, &format!("/chr/{id}.chrtpfbdt"))?;
+            let binder = BXF4::from_bytes(bhd, bdt)?;
+            tpfs.extend(binder.files.iter().map(|f| binder.file_bytes(f).to_vec()));
+        } else {
+            for path in [
+                format!("/chr/{id}_h.texbnd.dcx"),
+                format!("/chr/{id}.texbnd.dcx"),
+            ] {
+                if let Ok(texbnd) = open_bnd(vfs, &path) {
+                    tpfs.extend(texbnd.files.iter().map(|f| texbnd.file_bytes(f).to_vec()));
+                    break;
+                }
+            }
+        }
+
+        let mut textures = Vec::new();
+        for tpf in tpfs {
+            let mut tpf = Cursor::new(undo_container_compression(tpf)?);
+            for texture in TPF::from_reader(&mut tpf)?.textures {
+                textures.push((texture.name.clone(), texture.bytes(&mut tpf)?));
+            }
+        }
+
+        debug!(
+            flver_size = flver.len(),
+            cloth = cloth.len(),
+            textures = textures.len(),
+            "loaded character"
+        );
+
+        Ok(Self {
+            id: id.to_string(),
+            flver,
+            skeleton,
+            cloth,
+            textures,
+        })
+    }
+}
+
+/// The FLVER, the textures and the skeleton and cloth decoded from the Havok files.
+impl ResidentMemory for Character {
+    fn memory_usage(&self) -> MemoryUsage {
+        let skeleton = self.skeleton.as_ref().map_or(0, |skeleton| {
+            size_of_val(&skeleton.bones[..]) + size_of_val(&skeleton.reference_pose[..])
+        });
+        let cloth = self
+            .cloth
+            .iter()
+            .map(|cloth| {
+                size_of_val(&cloth.particles[..])
+                    + size_of_val(&cloth.fixed_particles[..])
+                    + size_of_val(&cloth.constraint_sets[..])
+            })
+            .sum::<usize>();
+
+        let mut usage = MemoryUsage::default();
+        usage.add(None, "flver", self.flver.len());
+        usage.add(None, "hkx", skeleton + cloth);
+        for (_, texture) in &self.textures {
+            usage.add(None, "dds", texture.len());
+        }
+
+        usage
+    }
+}
@@ -1 +1,66 @@
+use std::io::{self, Cursor, Read};
 
+use format::{bnd4::BND4, dcx::DCXError, hkx::HkxError};
+use souls_vfs::{Vfs, VfsOpenError, VfsReadError};
+use thiserror::Error;
+use tracing::{instrument, trace};
+
+pub mod asset;
+pub mod character;
+pub mod scan;
+
+#[derive(Debug, Error)]
+pub enum LoadError {
+    #[error("Could not open {0}: {1}")]
+    Open(String, VfsOpenError),
+
+    #[error("Could not read {0}: {1}")]
+    Read(String, VfsReadError),
+
+    #[error("Could not read binder: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Could not decompress binder: {0}")]
+    Dcx(#[from] DCXError),
+
+    #[error("Could not parse HKX: {0}")]
+    Hkx(#[from] HkxError),
+
+    #[error("{0} has no FLVER")]
+    NoFlver(String),
+}
+
+#[instrument(level = "trace", skip(vfs))]
+fn read_vfs(vfs: &Vfs, path: &str) -> Result<Vec<u8>, LoadError> {
+    let mut bytes = Vec::new();
+    vfs.open(path)
+        .map_err(|e| LoadError::Open(path.to_string(), e))?
+        .read_to_end(&mut bytes)?;
+    trace!(size = bytes.len(), "read file");
+
+    Ok(bytes)
+}
+
+fn open_bnd(vfs: &Vfs, path: &str) -> Result<BND4, LoadError> {
+    let bytes = vfs
+        .read_decompressed(path)
+        .map_err(|e| LoadError::Read(path.to_string(), e))?;
+
+    Ok(BND4::from_reader(&mut Cursor::new(bytes))?)
+}
+
+/// Find a file in a binder by its file name, ignoring its directory and case.
+fn find_file<'a>(bnd: &'a BND4, name: &str) -> Option<&'a [u8]> {
+    bnd.files
+        .iter()
+        .find(|file| file_name(&file.path).eq_ignore_ascii_case(name))
+        .map(|file| bnd.file_bytes(file))
+}
+
+fn file_name(path: &str) -> String {
+    BND4::normalize_path(path)
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
@@ -0,0 +1,164 @@
+use std::{
+    fs,
+    io::{self, Cursor},
+    path::{Path, PathBuf},
+};
+
+use format::bnd4::BND4;
+use rayon::prelude::*;
+use souls_vfs::{format_of, undo_container_compression, Vfs, VfsOpenError, VfsReadError};
+use tracing::{instrument, trace};
+
+use crate::{file_name, LoadError};
+
+/// Where [`scan`] finds files.
+#[derive(Clone, Copy)]
+pub enum ScanSource<'a> {
+    /// The files of a VFS named by `paths`, usually a dictionary since the archives only hold
+    /// hashes of their paths. Paths the archives don't contain are skipped.
+    Vfs { vfs: &'a Vfs, paths: &'a [String] },
+
+    /// Every file under a directory, e.g. an extracted game or a mod.
+    Directory(&'a Path),
+}
+
+/// What a [`scan`] went through.
+#[derive(Debug, Default)]
+pub struct ScanSummary {
+    /// Files of the requested format that were parsed, including those inside binders.
+    pub parsed: usize,
+
+    /// Files that could not be read or unpacked, by path.
+    pub errors: Vec<(String, LoadError)>,
+}
+
+/// Parse every file of `format` (its extension without DCX, e.g. `flver`) in `source` in
+/// parallel, passing each result to `callback` along with the path of the file.
+///
+/// Binders (any `*bnd` file) are searched too, in which case the path is the binder's followed by
+/// the file name inside it. Both closures are called from many threads at once, so a callback that
+/// gathers results needs a lock or a channel.
+#[instrument(skip_all, fields(format))]
+pub fn scan<T, P, C>(source: ScanSource, format: &str, parse: P, callback: C) -> ScanSummary
+where
+    P: Fn(&[u8]) -> T + Sync,
+    C: Fn(&str, T) + Sync,
+{
+    let scanner = Scanner {
+        format,
+        parse,
+        callback,
+    };
+
+    match source {
+        ScanSource::Vfs { vfs, paths } => paths
+            .par_iter()
+            .filter(|path| scanner.wants(path))
+            .filter_map(|path| match vfs.read_decompressed(path) {
+                Err(VfsReadError::Open(VfsOpenError::NotFound)) => None,
+                Err(e) => Some(scanner.failed(path, LoadError::Read(path.clone(), e))),
+                Ok(bytes) => Some(scanner.visit(path, bytes)),
+            })
+            .reduce(ScanSummary::default, ScanSummary::merge),
+        ScanSource::Directory(directory) => {
+            let mut files = Vec::new();
+            if let Err(e) = walk(directory, &mut files) {
+                return scanner.failed(&directory.display().to_string(), e.into());
+            }
+
+            files
+                .par_iter()
+                .map(|file| {
+                    let path = file.to_string_lossy();
+                    if !scanner.wants(&path) {
+                        return ScanSummary::default();
+                    }
+
+                    match fs::read(file).and_then(|bytes| {
+                        undo_container_compression(bytes).map_err(io::Error::other)
+                    }) {
+                        Ok(bytes) => scanner.visit(&path, bytes),
+                        Err(e) => scanner.failed(&path, e.into()),
+                    }
+                })
+                .reduce(ScanSummary::default, ScanSummary::merge)
+        }
+    }
+}
+
+impl ScanSummary {
+    fn merge(mut self, other: ScanSummary) -> ScanSummary {
+        self.parsed += other.parsed;
+        self.errors.extend(other.errors);
+        self
+    }
+}
+
+struct Scanner<'a, P, C> {
+    format: &'a str,
+    parse: P,
+    callback: C,
+}
+
+impl<'a, T, P, C> Scanner<'a, P, C>
+where
+    P: Fn(&[u8]) -> T + Sync,
+    C: Fn(&str, T) + Sync,
+{
+    fn wants(&self, path: &str) -> bool {
+        let format = format_of(path);
+        format.eq_ignore_ascii_case(self.format) || format.ends_with("bnd")
+    }
+
+    fn failed(&self, path: &str, error: LoadError) -> ScanSummary {
+        ScanSummary {
+            parsed: 0,
+            errors: vec![(path.to_string(), error)],
+        }
+    }
+
+    /// Parse a decompressed file, or search it if it's a binder.
+    fn visit(&self, path: &str, bytes: Vec<u8>) -> ScanSummary {
+        if format_of(path).eq_ignore_ascii_case(self.format) {
+            (self.callback)(path, (self.parse)(&bytes));
+            return ScanSummary {
+                parsed: 1,
+                errors: Vec::new(),
+            };
+        }
+
+        let bnd = match BND4::from_reader(&mut Cursor::new(bytes)) {
+            Ok(bnd) => bnd,
+            Err(e) => return self.failed(path, e.into()),
+        };
+        trace!(path, files = bnd.files.len(), "searching binder");
+
+        bnd.files
+            .par_iter()
+            .map(|file| {
+                let path = format!("{path}/{}", file_name(&file.path));
+                if !self.wants(&path) {
+                    return ScanSummary::default();
+                }
+
+                match undo_container_compression(bnd.file_bytes(file).to_vec()) {
+                    Ok(bytes) => self.visit(&path, bytes),
+                    Err(e) => self.failed(&path, e.into()),
+                }
+            })
+            .reduce(ScanSummary::default, ScanSummary::merge)
+    }
+}
+
+fn walk(directory: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            walk(&entry.path(), files)?;
+        } else {
+            files.push(entry.path());
+        }
+    }
+
+    Ok(())
+}
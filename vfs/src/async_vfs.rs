@@ -0,0 +1,73 @@
+use std::{ops::Range, panic, sync::Arc};
+
+use tokio::task;
+
+use crate::{Vfs, VfsReadError};
+
+/// A [`Vfs`] for async runtimes. Reads run on tokio's blocking thread pool, since decrypting,
+/// decompressing and faulting in pages of the memory mapped archives would otherwise stall the
+/// runtime's workers.
+///
+/// Cloning is cheap, so a handle can be moved into every task that loads assets.
+#[derive(Clone)]
+pub struct AsyncVfs {
+    vfs: Arc<Vfs>,
+}
+
+impl AsyncVfs {
+    pub fn new(vfs: Vfs) -> Self {
+        Self { vfs: Arc::new(vfs) }
+    }
+
+    /// The underlying filesystem, for lookups that don't read any data.
+    pub fn vfs(&self) -> &Vfs {
+        &self.vfs
+    }
+
+    /// See [`Vfs::read`].
+    pub async fn read(&self, path: &str) -> Result<Vec<u8>, VfsReadError> {
+        let path = path.to_string();
+        self.blocking(move |vfs| vfs.read(&path)).await
+    }
+
+    /// See [`Vfs::read_range`].
+    pub async fn read_range(
+        &self,
+        path: &str,
+        range: Range<usize>,
+    ) -> Result<Vec<u8>, VfsReadError> {
+        let path = path.to_string();
+        self.blocking(move |vfs| vfs.read_range(&path, range)).await
+    }
+
+    /// See [`Vfs::read_decompressed`].
+    pub async fn read_decompressed(&self, path: &str) -> Result<Vec<u8>, VfsReadError> {
+        let path = path.to_string();
+        self.blocking(move |vfs| vfs.read_decompressed(&path)).await
+    }
+
+    async fn blocking<T, F>(&self, f: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&Vfs) -> T + Send + 'static,
+    {
+        let vfs = self.vfs.clone();
+        match task::spawn_blocking(move || f(&vfs)).await {
+            Ok(result) => result,
+            // Blocking tasks can't be aborted, so the only error is a panic in `f`.
+            Err(e) => panic::resume_unwind(e.into_panic()),
+        }
+    }
+}
+
+impl From<Vfs> for AsyncVfs {
+    fn from(vfs: Vfs) -> Self {
+        Self::new(vfs)
+    }
+}
+
+impl From<Arc<Vfs>> for AsyncVfs {
+    fn from(vfs: Arc<Vfs>) -> Self {
+        Self { vfs }
+    }
+}
@@ -1,15 +1,18 @@
 use std::{
     collections::HashMap,
     io::{self, Cursor},
+    mem,
 };
 
 use format::{
-    bnd4::BND4,
+    bnd4::{BND4Entry, BND4},
+    bxf4::BXF4,
     dcx::{DCXError, DCX},
 };
 use thiserror::Error;
+use tracing::{debug, instrument};
 
-use crate::{Name, VfsOpenError};
+use crate::{MemoryUsage, Name, ResidentMemory, VfsOpenError};
 
 /// Provides easy access into a collection of BND4 archives.
 #[derive(Default)]
@@ -35,12 +38,54 @@ pub enum BndMountError {
 
 impl BndMountHost {
     pub fn mount(&mut self, name: Name, bytes: &[u8]) -> Result<(), BndMountError> {
+        self.mount_from(name, bytes, None, "bnd")
+    }
+
+    /// Mount a binder, attributing its decompressed data to `archive` and `format` in the
+    /// [`MemoryUsage`] of the host.
+    #[instrument(skip_all, fields(size = bytes.len()))]
+    pub fn mount_from(
+        &mut self,
+        name: Name,
+        bytes: &[u8],
+        archive: Option<&str>,
+        format: &str,
+    ) -> Result<(), BndMountError> {
         let decompressed = undo_container_compression(bytes.to_vec())?;
 
         let mut cursor = Cursor::new(decompressed);
         let bnd = BND4::from_reader(&mut cursor).map_err(BndMountError::Bnd4)?;
 
-        self.entries.extend(bnd.files.iter().map(|f| {
+        self.insert(name, &bnd.files, bnd.data, archive, format);
+        Ok(())
+    }
+
+    /// Mount a split binder, e.g. a `.tpfbhd`, whose files are stored in `bdt`.
+    #[instrument(skip_all, fields(size = bdt.len()))]
+    pub fn mount_split_from(
+        &mut self,
+        name: Name,
+        bhd: &[u8],
+        bdt: Vec<u8>,
+        archive: Option<&str>,
+        format: &str,
+    ) -> Result<(), BndMountError> {
+        let mut bxf = BXF4::from_bytes(bhd, bdt).map_err(BndMountError::Bnd4)?;
+        let files = mem::take(&mut bxf.files);
+
+        self.insert(name, &files, bxf.into_data(), archive, format);
+        Ok(())
+    }
+
+    fn insert(
+        &mut self,
+        name: Name,
+        files: &[BND4Entry],
+        data: Vec<u8>,
+        archive: Option<&str>,
+        format: &str,
+    ) {
+        self.entries.extend(files.iter().map(|f| {
             (
                 Self::extract_file_name(&f.path).to_ascii_lowercase(),
                 BndFileEntry {
@@ -51,9 +96,22 @@ impl BndMountHost {
             )
         }));
 
-        self.mounted.insert(name, BndBytes(bnd.data));
+        debug!(files = files.len(), "mounted binder");
+        self.mounted.insert(
+            name,
+            BndBytes {
+                data,
+                archive: archive.map(str::to_string),
+                format: format.to_string(),
+            },
+        );
+    }
 
-        Ok(())
+    /// Drop a mounted binder and its files, returning whether it was mounted.
+    pub fn unmount(&mut self, name: &Name) -> bool {
+        self.entries.retain(|_, entry| entry.container != *name);
+
+        self.mounted.remove(name).is_some()
     }
 
     fn entry_bytes(&self, entry: &BndFileEntry) -> Result<&[u8], VfsOpenError> {
@@ -61,7 +119,7 @@ impl BndMountHost {
             let start = entry.offset;
             let end = start + entry.size;
 
-            Ok(&mount.0[start..end])
+            Ok(&mount.data[start..end])
         } else {
             Err(VfsOpenError::NotFound)
         }
@@ -88,7 +146,23 @@ impl BndMountHost {
     }
 }
 
-pub struct BndBytes(Vec<u8>);
+pub struct BndBytes {
+    data: Vec<u8>,
+    archive: Option<String>,
+    format: String,
+}
+
+/// The decompressed data of every mounted binder.
+impl ResidentMemory for BndMountHost {
+    fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage::default();
+        for mount in self.mounted.values() {
+            usage.add(mount.archive.as_deref(), &mount.format, mount.data.len());
+        }
+
+        usage
+    }
+}
 
 #[derive(Debug)]
 pub struct BndFileEntry {
@@ -0,0 +1,114 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    process,
+};
+
+use format::game::Game;
+use tracing::{trace, warn};
+
+use crate::{Name, VfsFileEntry};
+
+/// An on-disk cache of decrypted and decompressed archive entries, so that files read on every
+/// launch only go through AES and Kraken once.
+///
+/// Entries are keyed by the hash of their path along with where they lie in the archives, so a
+/// game update that moves or resizes a file misses the cache instead of returning stale data.
+#[derive(Clone, Debug)]
+pub struct EntryCache {
+    directory: PathBuf,
+}
+
+impl EntryCache {
+    /// Use `directory` for the cache, creating it if needed.
+    pub fn open(directory: impl AsRef<Path>) -> io::Result<Self> {
+        let directory = directory.as_ref().to_path_buf();
+        fs::create_dir_all(&directory)?;
+
+        Ok(Self { directory })
+    }
+
+    pub fn directory(&self) -> &Path {
+        &self.directory
+    }
+
+    /// Remove every cached entry.
+    pub fn clear(&self) -> io::Result<()> {
+        fs::remove_dir_all(&self.directory)?;
+        fs::create_dir_all(&self.directory)
+    }
+
+    fn path(&self, game: Game, name: &Name, entry: &VfsFileEntry) -> PathBuf {
+        self.directory.join(game.id()).join(format!(
+            "{:016x}-{:x}-{:x}",
+            name.0, entry.file_offset, entry.file_size
+        ))
+    }
+
+    pub(crate) fn get(&self, game: Game, name: &Name, entry: &VfsFileEntry) -> Option<Vec<u8>> {
+        let bytes = fs::read(self.path(game, name, entry)).ok()?;
+        trace!(name = name.0, size = bytes.len(), "cache hit");
+
+        Some(bytes)
+    }
+
+    /// Store an entry, logging rather than failing when the cache can't be written since the
+    /// caller already has the data.
+    pub(crate) fn insert(&self, game: Game, name: &Name, entry: &VfsFileEntry, bytes: &[u8]) {
+        let path = self.path(game, name, entry);
+
+        // Written under a temporary name first, so that other processes never read a partial file.
+        let temporary = path.with_extension(format!("tmp{}", process::id()));
+        let result = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&temporary, bytes))
+            .and_then(|_| fs::rename(&temporary, &path));
+
+        if let Err(error) = result {
+            warn!(%error, path = %path.display(), "could not cache entry");
+            let _ = fs::remove_file(&temporary);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, process};
+
+    use format::game::Game;
+
+    use super::EntryCache;
+    use crate::{Name, VfsFileEntry};
+
+    #[test]
+    pub fn misses_when_entry_moves() {
+        let directory = env::temp_dir().join(format!("souls-vfs-cache-{}", process::id()));
+        let cache = EntryCache::open(&directory).unwrap();
+
+        let name = Name::from("/regulation.bin");
+        let mut entry = VfsFileEntry {
+            archive: 0,
+            file_size: 4,
+            file_size_with_padding: 0x10,
+            file_offset: 0x100,
+            aes_key: [0; 16],
+            aes_ranges: Vec::new(),
+            digest: None,
+        };
+        assert!(cache.get(Game::EldenRing, &name, &entry).is_none());
+
+        cache.insert(Game::EldenRing, &name, &entry, b"data");
+        assert_eq!(
+            cache.get(Game::EldenRing, &name, &entry).as_deref(),
+            Some(&b"data"[..])
+        );
+        assert!(cache.get(Game::Sekiro, &name, &entry).is_none());
+
+        entry.file_offset = 0x200;
+        assert!(cache.get(Game::EldenRing, &name, &entry).is_none());
+
+        cache.clear().unwrap();
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
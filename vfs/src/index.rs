@@ -0,0 +1,316 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use byteorder::{ReadBytesExt, WriteBytesExt, LE};
+use format::{bnd4::BND4, game::Game};
+use thiserror::Error;
+use tracing::{debug, instrument, warn};
+
+use crate::{format_of, ArchiveKeyProvider, Name, Vfs, VfsFileEntry};
+
+const MAGIC: &[u8; 4] = b"VFSI";
+const VERSION: u32 = 1;
+
+#[derive(Debug, Error)]
+pub enum IndexError {
+    #[error("Could not read or write index: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("Not a VFS index, or one written by another version")]
+    Unsupported,
+
+    #[error("Index is for {0}")]
+    UnknownGame(String),
+
+    #[error("Archive {0} changed since the index was built")]
+    Stale(String),
+}
+
+/// The tables of contents of a game install, saved so that later runs can open its archives
+/// without decrypting and parsing every BHD, see [`Vfs::open_indexed`].
+///
+/// Along with where each file lies, the index keeps the paths of the files found in a
+/// dictionary and which binder each file inside a binder belongs to, so that tools can go from a
+/// file name to the binder to mount without reading any binder headers.
+#[derive(Debug)]
+pub struct VfsIndex {
+    game: Game,
+
+    /// Path of each archive relative to the install directory, without extension, and the size of
+    /// its BDT when the index was built.
+    archives: Vec<(String, u64)>,
+    entries: HashMap<Name, VfsFileEntry>,
+    paths: HashMap<Name, String>,
+
+    /// Lowercase file names inside binders, and the binder holding each.
+    binder_files: HashMap<String, Name>,
+}
+
+impl VfsIndex {
+    /// Index every archive of the game installed in `directory`. The binders among the
+    /// `dictionary` paths are read to index their files, which takes a while but only needs to
+    /// happen once per game update.
+    #[instrument(skip_all, fields(%game))]
+    pub fn build<K: ArchiveKeyProvider>(
+        game: Game,
+        directory: impl AsRef<Path>,
+        key_provider: &K,
+        dictionary: impl IntoIterator<Item = impl AsRef<str>>,
+    ) -> Result<Self, io::Error> {
+        let vfs = Vfs::open_game(game, directory, key_provider)?;
+
+        let mut paths = HashMap::new();
+        let mut binder_files = HashMap::new();
+        for path in dictionary {
+            let path = path.as_ref();
+            let name = Name::new(game, path);
+            if !vfs.entries.contains_key(&name) {
+                continue;
+            }
+
+            if format_of(path).ends_with("bnd") {
+                let bnd = vfs
+                    .read_decompressed(path)
+                    .map_err(|e| e.to_string())
+                    .and_then(|bytes| BND4::parse(bytes).map_err(|e| e.to_string()));
+
+                match bnd {
+                    Ok(bnd) => binder_files.extend(bnd.files.iter().filter_map(|file| {
+                        let file_name = BND4::normalize_path(&file.path)
+                            .rsplit('/')
+                            .next()?
+                            .to_string();
+                        Some((file_name, name.clone()))
+                    })),
+                    Err(error) => warn!(path, %error, "could not index binder"),
+                }
+            }
+
+            paths.insert(name, path.to_string());
+        }
+
+        let archives = game
+            .archives()
+            .iter()
+            .zip(&vfs.archives)
+            .map(|(archive, data)| (archive.to_string(), data.len() as u64))
+            .collect();
+        debug!(
+            entries = vfs.entries.len(),
+            paths = paths.len(),
+            binder_files = binder_files.len(),
+            "built index"
+        );
+
+        Ok(Self {
+            game,
+            archives,
+            entries: vfs.entries,
+            paths,
+            binder_files,
+        })
+    }
+
+    pub fn game(&self) -> Game {
+        self.game
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The path of the file identified by `name`, if it was in the dictionary the index was built
+    /// with.
+    pub fn path(&self, name: &Name) -> Option<&str> {
+        self.paths.get(name).map(String::as_str)
+    }
+
+    /// The path of the binder holding the file named `file_name`, ignoring case.
+    pub fn binder_of(&self, file_name: &str) -> Option<&str> {
+        self.binder_files
+            .get(&file_name.to_lowercase())
+            .and_then(|name| self.path(name))
+    }
+
+    pub(crate) fn archives(&self) -> &[(String, u64)] {
+        &self.archives
+    }
+
+    pub(crate) fn entries(&self) -> &HashMap<Name, VfsFileEntry> {
+        &self.entries
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, IndexError> {
+        Self::read(&mut BufReader::new(File::open(path)?))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), IndexError> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write(&mut w)?;
+
+        Ok(w.flush()?)
+    }
+
+    pub fn read(r: &mut impl Read) -> Result<Self, IndexError> {
+        let mut magic = [0u8; 4];
+        r.read_exact(&mut magic)?;
+        if &magic != MAGIC || r.read_u32::<LE>()? != VERSION {
+            return Err(IndexError::Unsupported);
+        }
+
+        let game = read_string(r)?;
+        let game = game.parse().map_err(|_| IndexError::UnknownGame(game))?;
+
+        let archives = (0..r.read_u32::<LE>()?)
+            .map(|_| Ok((read_string(r)?, r.read_u64::<LE>()?)))
+            .collect::<io::Result<_>>()?;
+
+        let entries = (0..r.read_u32::<LE>()?)
+            .map(|_| {
+                let name = Name(r.read_u64::<LE>()?);
+                let archive = r.read_u32::<LE>()? as usize;
+                let file_size = r.read_u32::<LE>()?;
+                let file_size_with_padding = r.read_u32::<LE>()?;
+                let file_offset = r.read_u64::<LE>()?;
+                let mut aes_key = [0u8; 16];
+                r.read_exact(&mut aes_key)?;
+                let aes_ranges = (0..r.read_u32::<LE>()?)
+                    .map(|_| Ok(r.read_u64::<LE>()?..r.read_u64::<LE>()?))
+                    .collect::<io::Result<_>>()?;
+
+                Ok((
+                    name,
+                    VfsFileEntry {
+                        archive,
+                        file_size,
+                        file_size_with_padding,
+                        file_offset,
+                        aes_key,
+                        aes_ranges,
+                        digest: None,
+                    },
+                ))
+            })
+            .collect::<io::Result<_>>()?;
+
+        let paths = (0..r.read_u32::<LE>()?)
+            .map(|_| Ok((Name(r.read_u64::<LE>()?), read_string(r)?)))
+            .collect::<io::Result<_>>()?;
+
+        let binder_files = (0..r.read_u32::<LE>()?)
+            .map(|_| Ok((read_string(r)?, Name(r.read_u64::<LE>()?))))
+            .collect::<io::Result<_>>()?;
+
+        Ok(Self {
+            game,
+            archives,
+            entries,
+            paths,
+            binder_files,
+        })
+    }
+
+    pub fn write(&self, w: &mut impl Write) -> Result<(), io::Error> {
+        w.write_all(MAGIC)?;
+        w.write_u32::<LE>(VERSION)?;
+        write_string(w, self.game.id())?;
+
+        w.write_u32::<LE>(self.archives.len() as u32)?;
+        for (archive, size) in &self.archives {
+            write_string(w, archive)?;
+            w.write_u64::<LE>(*size)?;
+        }
+
+        w.write_u32::<LE>(self.entries.len() as u32)?;
+        for (name, entry) in &self.entries {
+            w.write_u64::<LE>(name.0)?;
+            w.write_u32::<LE>(entry.archive as u32)?;
+            w.write_u32::<LE>(entry.file_size)?;
+            w.write_u32::<LE>(entry.file_size_with_padding)?;
+            w.write_u64::<LE>(entry.file_offset)?;
+            w.write_all(&entry.aes_key)?;
+            w.write_u32::<LE>(entry.aes_ranges.len() as u32)?;
+            for range in &entry.aes_ranges {
+                w.write_u64::<LE>(range.start)?;
+                w.write_u64::<LE>(range.end)?;
+            }
+        }
+
+        w.write_u32::<LE>(self.paths.len() as u32)?;
+        for (name, path) in &self.paths {
+            w.write_u64::<LE>(name.0)?;
+            write_string(w, path)?;
+        }
+
+        w.write_u32::<LE>(self.binder_files.len() as u32)?;
+        for (file_name, binder) in &self.binder_files {
+            write_string(w, file_name)?;
+            w.write_u64::<LE>(binder.0)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn read_string(r: &mut impl Read) -> io::Result<String> {
+    let mut bytes = vec![0; r.read_u16::<LE>()? as usize];
+    r.read_exact(&mut bytes)?;
+
+    String::from_utf8(bytes).map_err(io::Error::other)
+}
+
+fn write_string(w: &mut impl Write, string: &str) -> io::Result<()> {
+    let length = u16::try_from(string.len()).map_err(io::Error::other)?;
+    w.write_u16::<LE>(length)?;
+    w.write_all(string.as_bytes())
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, io::Cursor};
+
+    use format::game::Game;
+
+    use super::VfsIndex;
+    use crate::{Name, VfsFileEntry};
+
+    #[test]
+    pub fn round_trips() {
+        let name = Name::new(Game::Sekiro, "/chr/c0000.chrbnd.dcx");
+        let index = VfsIndex {
+            game: Game::Sekiro,
+            archives: vec![("Data1".to_string(), 0x1000)],
+            entries: HashMap::from([(
+                name.clone(),
+                VfsFileEntry {
+                    archive: 0,
+                    file_size: 0x20,
+                    file_size_with_padding: 0x30,
+                    file_offset: 0x100,
+                    aes_key: [7; 16],
+                    aes_ranges: vec![0..0x10, 0x20..0x30],
+                    digest: None,
+                },
+            )]),
+            paths: HashMap::from([(name.clone(), "/chr/c0000.chrbnd.dcx".to_string())]),
+            binder_files: HashMap::from([("c0000.flver".to_string(), name.clone())]),
+        };
+
+        let mut bytes = Vec::new();
+        index.write(&mut bytes).unwrap();
+        let read = VfsIndex::read(&mut Cursor::new(bytes)).unwrap();
+
+        assert_eq!(read.game(), Game::Sekiro);
+        assert_eq!(read.archives(), index.archives());
+        assert_eq!(read.entries()[&name].aes_ranges, vec![0..0x10, 0x20..0x30]);
+        assert_eq!(read.binder_of("C0000.FLVER"), Some("/chr/c0000.chrbnd.dcx"));
+    }
+}
@@ -1,27 +1,50 @@
 use std::{fs, path::PathBuf};
 
-use format::bhd::BhdKey;
+use format::{bhd::BhdKey, game::Game};
 
 // TODO: replace Option with Result
 pub trait ArchiveKeyProvider {
     fn get_key(&self, name: &str) -> Result<BhdKey, std::io::Error>;
 }
 
+/// Reads keys from PEM files named after their archive, e.g. `keys/Data0.pem`.
 pub struct FileKeyProvider {
-    key_dir: PathBuf,
+    key_dirs: Vec<PathBuf>,
 }
 
 impl FileKeyProvider {
     pub fn new<P: Into<PathBuf>>(path: P) -> Self {
         Self {
-            key_dir: path.into(),
+            key_dirs: vec![path.into()],
+        }
+    }
+
+    /// Look for keys in a directory named after the game first, e.g. `keys/ds3/Data1.pem`, as
+    /// games share archive names.
+    pub fn for_game<P: Into<PathBuf>>(path: P, game: Game) -> Self {
+        let path = path.into();
+
+        Self {
+            key_dirs: vec![path.join(game.id()), path],
         }
     }
 }
 
 impl ArchiveKeyProvider for FileKeyProvider {
     fn get_key(&self, name: &str) -> Result<BhdKey, std::io::Error> {
-        fs::read_to_string(self.key_dir.join(name).with_extension("pem"))
+        let path = self
+            .key_dirs
+            .iter()
+            .map(|dir| dir.join(name).with_extension("pem"))
+            .find(|path| path.is_file())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("no key found for archive {name}"),
+                )
+            })?;
+
+        fs::read_to_string(path)
             .and_then(|pem| BhdKey::from_pem(&pem).map_err(std::io::Error::other))
     }
 }
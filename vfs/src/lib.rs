@@ -1,23 +1,35 @@
 use std::{
     collections::HashMap,
     fs::File,
-    io::{Error, Read},
+    io::{self, Error, ErrorKind, Read},
     ops::Range,
     path::Path,
 };
 
-use format::bhd::Bhd;
+use format::{bhd::Bhd, dcx::DCXError, game::Game};
 use memmap2::{Advice, Mmap, MmapOptions};
+use sha2::{Digest, Sha256};
 use thiserror::Error;
+use tracing::{debug, instrument};
 
+#[cfg(feature = "tokio")]
+mod async_vfs;
 mod bnd;
+mod cache;
+mod index;
 mod key_provider;
+mod memory;
 mod name;
 mod reader;
 
+#[cfg(feature = "tokio")]
+pub use self::async_vfs::AsyncVfs;
 pub use self::{
-    bnd::{undo_container_compression, BndMountHost},
+    bnd::{undo_container_compression, BndMountError, BndMountHost},
+    cache::EntryCache,
+    index::{IndexError, VfsIndex},
     key_provider::{ArchiveKeyProvider, FileKeyProvider},
+    memory::{format_of, MemoryUsage, ResidentMemory},
     name::Name,
     reader::VfsEntryReader,
 };
@@ -26,33 +38,83 @@ pub use self::{
 pub enum VfsOpenError {
     #[error("Entry was not found")]
     NotFound,
+
+    #[error("Could not read binder: {0}")]
+    Read(Box<VfsReadError>),
+
+    #[error("Could not mount binder: {0}")]
+    Mount(Box<BndMountError>),
+}
+
+/// How a file's storage in the archives is damaged, as found by [`Vfs::verify_name`].
+#[derive(Debug, Error)]
+pub enum VfsVerifyError {
+    #[error(transparent)]
+    Open(#[from] VfsOpenError),
+
+    #[error("Entry ends {missing} bytes past the end of its archive")]
+    Truncated { missing: u64 },
+
+    #[error("Entry doesn't match its SHA-256 digest")]
+    Digest,
+}
+
+#[derive(Debug, Error)]
+pub enum VfsReadError {
+    #[error(transparent)]
+    Open(#[from] VfsOpenError),
+
+    #[error("Could not read entry: {0}")]
+    Io(#[from] Error),
+
+    #[error("Could not decompress entry: {0}")]
+    Dcx(#[from] DCXError),
 }
 
 /// A read-only virtual filesystem layered over the BHD/BDT archives of a FROMSOFTWARE game.
 pub struct Vfs {
+    game: Game,
     archives: Vec<Mmap>,
+
+    /// File stem of each archive, e.g. `Data0`.
+    archive_names: Vec<String>,
     entries: HashMap<Name, VfsFileEntry>,
     mount_host: BndMountHost,
+    cache: Option<EntryCache>,
 }
 
 impl Vfs {
+    #[instrument(skip_all, fields(path = %path.as_ref().display()))]
     fn load_archive<P: AsRef<Path>>(
+        game: Game,
         path: P,
         key_provider: &impl ArchiveKeyProvider,
-    ) -> Result<(Mmap, Bhd), Error> {
+    ) -> Result<(Mmap, Bhd, String), Error> {
+        let format = game
+            .bhd_format()
+            .ok_or_else(|| Error::other(format!("{game} archives are not supported")))?;
+
         let path = path.as_ref();
-        let bhd_file = File::open(path.with_extension("bhd"))?;
-        let bdt_file = File::open(path.with_extension("bdt"))?;
-        let data = unsafe { MmapOptions::new().map_copy_read_only(&bdt_file)? };
+        let bhd_file = File::open(path.with_extension(game.archive_header_extension()))?;
+        let data = map_bdt(path)?;
         let name = path
             .file_stem()
             .and_then(|stem| stem.to_str())
             .ok_or(Error::other("invalid archive path given"))?;
 
-        let key = key_provider.get_key(name)?;
-        let bhd = Bhd::read(bhd_file, key)?;
+        let key = if format.is_encrypted() {
+            Some(key_provider.get_key(name)?)
+        } else {
+            None
+        };
+        let bhd = Bhd::read_format(bhd_file, key, format)?;
+        debug!(
+            data_size = data.len(),
+            entries = bhd.toc.len(),
+            "opened archive"
+        );
 
-        Ok((data, bhd))
+        Ok((data, bhd, name.to_string()))
     }
 
     /// Create a virtual filesystem from the archive files (BHD or BDT) pointed to by
@@ -60,8 +122,31 @@ impl Vfs {
     pub fn create<P: AsRef<Path>, K: ArchiveKeyProvider>(
         archive_paths: impl IntoIterator<Item = P>,
         key_provider: &K,
+    ) -> Result<Self, Error> {
+        Self::create_for_game(Game::EldenRing, archive_paths, key_provider)
+    }
+
+    /// Create a virtual filesystem from every archive of a game installed in [directory].
+    pub fn open_game<K: ArchiveKeyProvider>(
+        game: Game,
+        directory: impl AsRef<Path>,
+        key_provider: &K,
+    ) -> Result<Self, Error> {
+        let directory = directory.as_ref();
+        let archives = game.archives().iter().map(|name| directory.join(name));
+
+        Self::create_for_game(game, archives, key_provider)
+    }
+
+    /// Create a virtual filesystem from archives laid out and hashed the way [game] does.
+    #[instrument(name = "vfs", skip_all, fields(%game))]
+    pub fn create_for_game<P: AsRef<Path>, K: ArchiveKeyProvider>(
+        game: Game,
+        archive_paths: impl IntoIterator<Item = P>,
+        key_provider: &K,
     ) -> Result<Self, Error> {
         let mut archives = Vec::new();
+        let mut archive_names = Vec::new();
         let mut entries = HashMap::new();
 
         archive_paths
@@ -69,9 +154,10 @@ impl Vfs {
             .enumerate()
             .try_for_each(|(index, path)| {
                 let path = path.as_ref();
-                let (data, bhd) = Self::load_archive(path, key_provider)?;
+                let (data, bhd, name) = Self::load_archive(game, path, key_provider)?;
 
                 archives.push(data);
+                archive_names.push(name);
                 entries.extend(bhd.toc.into_iter().map(|entry| {
                     (
                         Name(entry.hash),
@@ -90,6 +176,15 @@ impl Vfs {
                                     (start, end) => Some(start as u64..end as u64),
                                 })
                                 .collect(),
+                            digest: entry.digest.map(|digest| FileDigest {
+                                sha256: digest.sha256,
+                                ranges: digest
+                                    .ranges
+                                    .into_iter()
+                                    .filter(|(start, end)| *start >= 0 && start < end)
+                                    .map(|(start, end)| start as u64..end as u64)
+                                    .collect(),
+                            }),
                         },
                     )
                 }));
@@ -98,15 +193,123 @@ impl Vfs {
             })?;
 
         Ok(Vfs {
+            game,
             archives,
+            archive_names,
             entries,
             mount_host: Default::default(),
+            cache: None,
+        })
+    }
+
+    /// Open the archives of the game installed in [directory] using a prebuilt [`VfsIndex`] in
+    /// place of their headers, which doesn't need the archive keys either.
+    #[instrument(name = "vfs", skip_all, fields(game = %index.game()))]
+    pub fn open_indexed(directory: impl AsRef<Path>, index: &VfsIndex) -> Result<Self, IndexError> {
+        let directory = directory.as_ref();
+        let mut archives = Vec::new();
+        let mut archive_names = Vec::new();
+
+        for (archive, size) in index.archives() {
+            let data = map_bdt(&directory.join(archive))?;
+            if data.len() as u64 != *size {
+                return Err(IndexError::Stale(archive.clone()));
+            }
+
+            archives.push(data);
+            archive_names.push(archive.rsplit('/').next().unwrap_or(archive).to_string());
+        }
+        debug!(entries = index.len(), "opened indexed archives");
+
+        Ok(Vfs {
+            game: index.game(),
+            archives,
+            archive_names,
+            entries: index.entries().clone(),
+            mount_host: Default::default(),
+            cache: None,
         })
     }
 
+    /// Keep the entries read with [`Vfs::read_decompressed`] in `cache`, for later runs.
+    pub fn with_cache(mut self, cache: EntryCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn game(&self) -> Game {
+        self.game
+    }
+
+    /// Whether there is a file at [path].
+    pub fn contains(&self, path: &str) -> bool {
+        self.entries.contains_key(&Name::new(self.game, path))
+    }
+
+    /// The hashes of every file in the archives, in no particular order.
+    pub fn names(&self) -> impl Iterator<Item = &Name> {
+        self.entries.keys()
+    }
+
+    /// Where and how the file at [path] is stored, without reading it.
+    pub fn stat(&self, path: &str) -> Result<VfsFileStat<'_>, VfsOpenError> {
+        self.stat_name(&Name::new(self.game, path))
+    }
+
+    /// Where and how the file identified by [name] is stored, without reading it.
+    pub fn stat_name(&self, name: &Name) -> Result<VfsFileStat<'_>, VfsOpenError> {
+        let entry = self.entries.get(name).ok_or(VfsOpenError::NotFound)?;
+
+        Ok(VfsFileStat {
+            archive: &self.archive_names[entry.archive],
+            offset: entry.file_offset,
+            size: entry.file_size,
+            padded_size: entry.file_size_with_padding,
+            encrypted_ranges: entry.aes_ranges.len(),
+        })
+    }
+
+    /// Check that the file identified by [name] lies within its archive and, if the archive has a
+    /// digest for it, that the file as stored matches the digest. Returns whether a digest was
+    /// checked, which it isn't for archives opened with [`Vfs::open_indexed`].
+    pub fn verify_name(&self, name: &Name) -> Result<bool, VfsVerifyError> {
+        let entry = self.entries.get(name).ok_or(VfsOpenError::NotFound)?;
+        let data = &self.archives[entry.archive];
+
+        let end = entry.file_offset + entry.file_size_with_padding as u64;
+        if end > data.len() as u64 {
+            return Err(VfsVerifyError::Truncated {
+                missing: end - data.len() as u64,
+            });
+        }
+
+        let Some(digest) = entry.digest.as_ref().filter(|d| !d.ranges.is_empty()) else {
+            return Ok(false);
+        };
+
+        let file = &data[entry.file_offset as usize..end as usize];
+        let mut hasher = Sha256::new();
+        for range in &digest.ranges {
+            let bytes = file
+                .get(range.start as usize..range.end as usize)
+                .ok_or(VfsVerifyError::Digest)?;
+            hasher.update(bytes);
+        }
+
+        match hasher.finalize()[..] == digest.sha256 {
+            true => Ok(true),
+            false => Err(VfsVerifyError::Digest),
+        }
+    }
+
+    /// Open a reader to the file at [path], hashed the way the game does.
+    pub fn open(&self, path: &str) -> Result<VfsEntryReader, VfsOpenError> {
+        self.open_name(&Name::new(self.game, path))
+    }
+
     /// Open a reader to the file identified by [name].
-    pub fn open<N: Into<Name>>(&self, name: N) -> Result<VfsEntryReader, VfsOpenError> {
-        match self.entries.get(&name.into()) {
+    pub fn open_name(&self, name: &Name) -> Result<VfsEntryReader, VfsOpenError> {
+        match self.entries.get(name) {
             Some(entry) => {
                 let mmap = &self.archives[entry.archive];
                 let offset = entry.file_offset as usize;
@@ -122,31 +325,269 @@ impl Vfs {
         }
     }
 
-    /// Attaches a bnd4 to the mount host
-    pub fn mount<N: Into<Name>>(&mut self, name: N) -> Result<(), VfsOpenError> {
-        let name = name.into();
+    /// Read the file at [path], decrypted but still compressed.
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, VfsReadError> {
+        let mut reader = self.open(path)?;
+        let mut buffer = Vec::new();
+        reader.read_to_end(&mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    /// Read [range] of the decrypted file at [path], e.g. a single entry of a binder that hasn't
+    /// been compressed as a whole. Fails if the range runs past the end of the file.
+    pub fn read_range(&self, path: &str, range: Range<usize>) -> Result<Vec<u8>, VfsReadError> {
+        let name = Name::new(self.game, path);
+        let entry = self.entries.get(&name).ok_or(VfsOpenError::NotFound)?;
+        if range.start > range.end || range.end > entry.file_size as usize {
+            return Err(Error::from(ErrorKind::UnexpectedEof).into());
+        }
+
+        // The reader decrypts as it goes, so everything before the range still has to be read.
+        let mut reader = self.open_name(&name)?;
+        io::copy(&mut (&mut reader).take(range.start as u64), &mut io::sink())?;
+
+        let mut buffer = vec![0; range.len()];
+        reader.read_exact(&mut buffer)?;
+
+        Ok(buffer)
+    }
+
+    /// Read the file at [path], decrypted and with any DCX compression undone. Goes through the
+    /// [`EntryCache`] when one is set.
+    #[instrument(skip(self))]
+    pub fn read_decompressed(&self, path: &str) -> Result<Vec<u8>, VfsReadError> {
+        self.read_decompressed_name(&Name::new(self.game, path))
+    }
+
+    /// Read the file identified by [name], decrypted and with any DCX compression undone.
+    pub fn read_decompressed_name(&self, name: &Name) -> Result<Vec<u8>, VfsReadError> {
+        let entry = self.entries.get(name).ok_or(VfsOpenError::NotFound)?;
+        if let Some(bytes) = self
+            .cache
+            .as_ref()
+            .and_then(|cache| cache.get(self.game, name, entry))
+        {
+            return Ok(bytes);
+        }
 
-        let mut reader = self.open(name.clone())?;
         let mut buffer = Vec::new();
-        reader.read_to_end(&mut buffer).unwrap();
+        self.open_name(name)?.read_to_end(&mut buffer)?;
+        let bytes = undo_container_compression(buffer)?;
 
-        self.mount_host.mount(name, buffer.as_slice()).unwrap();
+        if let Some(cache) = &self.cache {
+            cache.insert(self.game, name, entry, &bytes);
+        }
 
-        Ok(())
+        Ok(bytes)
+    }
+
+    /// Attaches a bnd4 to the mount host
+    pub fn mount(&mut self, path: &str) -> Result<(), VfsOpenError> {
+        let name = Name::new(self.game, path);
+        let archive = self
+            .entries
+            .get(&name)
+            .map(|entry| self.archive_names[entry.archive].clone());
+
+        let buffer = self.read_decompressed(path).map_err(|e| match e {
+            VfsReadError::Open(e) => e,
+            e => VfsOpenError::Read(Box::new(e)),
+        })?;
+
+        self.mount_host
+            .mount_from(name, buffer.as_slice(), archive.as_deref(), format_of(path))
+            .map_err(|e| VfsOpenError::Mount(Box::new(e)))
+    }
+
+    /// Attaches a split binder, e.g. a `.tpfbhd`, to the mount host along with the `.*bdt` of the
+    /// same name next to it.
+    pub fn mount_split(&mut self, path: &str) -> Result<(), VfsOpenError> {
+        let Some(data_path) = path.strip_suffix("bhd") else {
+            return Err(VfsOpenError::NotFound);
+        };
+        let name = Name::new(self.game, path);
+        let archive = self
+            .entries
+            .get(&name)
+            .map(|entry| self.archive_names[entry.archive].clone());
+
+        let read = |path: &str| {
+            self.read_decompressed(path).map_err(|e| match e {
+                VfsReadError::Open(e) => e,
+                e => VfsOpenError::Read(Box::new(e)),
+            })
+        };
+        let bhd = read(path)?;
+        let bdt = read(&format!("{data_path}bdt"))?;
+
+        self.mount_host
+            .mount_split_from(name, &bhd, bdt, archive.as_deref(), format_of(path))
+            .map_err(|e| VfsOpenError::Mount(Box::new(e)))
+    }
+
+    /// Detaches a bnd4 from the mount host, freeing its data. Returns whether it was mounted.
+    pub fn unmount(&mut self, path: &str) -> bool {
+        self.mount_host.unmount(&Name::new(self.game, path))
     }
 
     pub fn open_from_mounts(&self, name: &str) -> Result<&[u8], VfsOpenError> {
         self.mount_host.bytes_by_file_name(name)
     }
+
+    /// The lowercase file names of the files in the mounted binders.
+    pub fn mounted_files(&self) -> impl Iterator<Item = &str> {
+        self.mount_host.entries.keys().map(String::as_str)
+    }
 }
 
-#[derive(Debug)]
+fn map_bdt(path: &Path) -> Result<Mmap, Error> {
+    let bdt_file = File::open(path.with_extension("bdt"))?;
+
+    unsafe { MmapOptions::new().map_copy_read_only(&bdt_file) }
+}
+
+/// The binders mounted with [`Vfs::mount`]. The archives themselves are memory mapped, and only
+/// take up memory while the OS keeps their pages cached.
+impl ResidentMemory for Vfs {
+    fn memory_usage(&self) -> MemoryUsage {
+        self.mount_host.memory_usage()
+    }
+}
+
+/// Where a file is stored in the archives, as returned by [`Vfs::stat`].
+#[derive(Clone, Debug)]
+pub struct VfsFileStat<'a> {
+    /// File stem of the archive holding the file, e.g. `Data0`.
+    pub archive: &'a str,
+
+    /// Offset of the file in the archive's BDT.
+    pub offset: u64,
+
+    /// Size of the file as stored, which may still be DCX compressed.
+    pub size: u32,
+
+    /// Size of the file including the padding added for encryption.
+    pub padded_size: u32,
+
+    /// Number of ranges of the file encrypted with its AES key, zero if it isn't encrypted.
+    pub encrypted_ranges: usize,
+}
+
+#[derive(Clone, Debug)]
 pub struct VfsFileEntry {
     archive: usize,
-    #[allow(unused)]
     file_size: u32,
     file_size_with_padding: u32,
     file_offset: u64,
     aes_key: [u8; 16],
     aes_ranges: Vec<Range<u64>>,
+
+    /// Only known when read from the archive headers, not from a [`VfsIndex`].
+    digest: Option<FileDigest>,
+}
+
+#[derive(Clone, Debug)]
+struct FileDigest {
+    sha256: [u8; 32],
+    ranges: Vec<Range<u64>>,
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashMap, env, fs, path::Path, process};
+
+    use format::game::Game;
+    use sha2::{Digest, Sha256};
+
+    use crate::{map_bdt, FileDigest, Name, Vfs, VfsFileEntry, VfsOpenError, VfsVerifyError};
+
+    fn entry(file_offset: u64, size: u32, digest: Option<FileDigest>) -> VfsFileEntry {
+        VfsFileEntry {
+            archive: 0,
+            file_size: size,
+            file_size_with_padding: size,
+            file_offset,
+            aes_key: [0; 16],
+            aes_ranges: Vec::new(),
+            digest,
+        }
+    }
+
+    #[test]
+    pub fn verifies_digests_and_bounds() {
+        let path = env::temp_dir().join(format!("souls-vfs-verify-{}.bdt", process::id()));
+        fs::write(&path, b"0123456789abcdef").unwrap();
+
+        let digest = |bytes: &[u8]| FileDigest {
+            sha256: Sha256::digest(bytes).into(),
+            ranges: vec![0..2, 2..bytes.len() as u64],
+        };
+        let vfs = Vfs {
+            game: Game::EldenRing,
+            archives: vec![map_bdt(Path::new(&path)).unwrap()],
+            archive_names: vec!["Data0".to_string()],
+            entries: HashMap::from([
+                (Name(1), entry(0, 4, Some(digest(b"0123")))),
+                (Name(2), entry(4, 4, Some(digest(b"0123")))),
+                (Name(3), entry(12, 8, None)),
+                (Name(4), entry(8, 8, None)),
+            ]),
+            mount_host: Default::default(),
+            cache: None,
+        };
+
+        assert!(matches!(vfs.verify_name(&Name(1)), Ok(true)));
+        assert!(matches!(
+            vfs.verify_name(&Name(2)),
+            Err(VfsVerifyError::Digest)
+        ));
+        assert!(matches!(
+            vfs.verify_name(&Name(3)),
+            Err(VfsVerifyError::Truncated { missing: 4 })
+        ));
+        assert!(matches!(vfs.verify_name(&Name(4)), Ok(false)));
+
+        drop(vfs);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    pub fn mounting_returns_errors() {
+        let path = env::temp_dir().join(format!("souls-vfs-mount-{}.bdt", process::id()));
+        fs::write(&path, b"0123456789abcdef").unwrap();
+
+        let game = Game::EldenRing;
+        let mut vfs = Vfs {
+            game,
+            archives: vec![map_bdt(Path::new(&path)).unwrap()],
+            archive_names: vec!["Data0".to_string()],
+            entries: HashMap::from([
+                (Name::new(game, "/a.tpfbhd"), entry(0, 8, None)),
+                (Name::new(game, "/a.tpfbdt"), entry(8, 8, None)),
+                (Name::new(game, "/b.tpfbhd"), entry(0, 8, None)),
+                (Name::new(game, "/a.bnd"), entry(0, 8, None)),
+            ]),
+            mount_host: Default::default(),
+            cache: None,
+        };
+
+        assert!(matches!(
+            vfs.mount_split("/a.tpfbhd"),
+            Err(VfsOpenError::Mount(_))
+        ));
+        assert!(matches!(
+            vfs.mount_split("/b.tpfbhd"),
+            Err(VfsOpenError::NotFound)
+        ));
+        assert!(matches!(
+            vfs.mount_split("/c.tpfbhd"),
+            Err(VfsOpenError::NotFound)
+        ));
+        assert!(matches!(vfs.mount("/a.bnd"), Err(VfsOpenError::Mount(_))));
+        assert!(matches!(vfs.mount("/b.bnd"), Err(VfsOpenError::NotFound)));
+
+        drop(vfs);
+        fs::remove_file(path).unwrap();
+    }
 }
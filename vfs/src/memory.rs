@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+/// Layers that keep decompressed or parsed data resident, so that callers can watch how much they
+/// hold and evict what they no longer need.
+pub trait ResidentMemory {
+    fn memory_usage(&self) -> MemoryUsage;
+}
+
+/// Bytes of decompressed or parsed data held in memory, by format and by the archive the data was
+/// read from. Data that didn't come straight from an archive only counts towards its format.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MemoryUsage {
+    archives: BTreeMap<String, usize>,
+    formats: BTreeMap<String, usize>,
+}
+
+impl MemoryUsage {
+    pub fn add(&mut self, archive: Option<&str>, format: &str, bytes: usize) {
+        if let Some(archive) = archive {
+            *self.archives.entry(archive.to_string()).or_default() += bytes;
+        }
+        *self.formats.entry(format.to_string()).or_default() += bytes;
+    }
+
+    /// Add the usage of another layer, e.g. the parsed assets on top of the mounted binders.
+    pub fn merge(&mut self, other: &MemoryUsage) {
+        for (archive, bytes) in &other.archives {
+            *self.archives.entry(archive.clone()).or_default() += bytes;
+        }
+        for (format, bytes) in &other.formats {
+            *self.formats.entry(format.clone()).or_default() += bytes;
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.formats.values().sum()
+    }
+
+    pub fn archive(&self, archive: &str) -> usize {
+        self.archives.get(archive).copied().unwrap_or_default()
+    }
+
+    pub fn format(&self, format: &str) -> usize {
+        self.formats.get(format).copied().unwrap_or_default()
+    }
+
+    pub fn archives(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.archives
+            .iter()
+            .map(|(archive, bytes)| (archive.as_str(), *bytes))
+    }
+
+    pub fn formats(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.formats
+            .iter()
+            .map(|(format, bytes)| (format.as_str(), *bytes))
+    }
+}
+
+/// The format of a file by its extension, ignoring DCX compression, e.g. `partsbnd` for
+/// `/parts/wp_a_0210.partsbnd.dcx`.
+pub fn format_of(path: &str) -> &str {
+    let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let file_name = file_name.strip_suffix(".dcx").unwrap_or(file_name);
+
+    file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_of, MemoryUsage};
+
+    #[test]
+    pub fn sums_by_archive_and_format() {
+        let mut usage = MemoryUsage::default();
+        usage.add(Some("Data0"), "partsbnd", 0x100);
+        usage.add(Some("Data1"), "texbnd", 0x200);
+
+        let mut parsed = MemoryUsage::default();
+        parsed.add(None, "partsbnd", 0x10);
+        usage.merge(&parsed);
+
+        assert_eq!(usage.total(), 0x310);
+        assert_eq!(usage.archive("Data0"), 0x100);
+        assert_eq!(usage.format("partsbnd"), 0x110);
+        assert_eq!(usage.archives().count(), 2);
+    }
+
+    #[test]
+    pub fn ignores_dcx_extension() {
+        assert_eq!(format_of("/parts/wp_a_0210.partsbnd.dcx"), "partsbnd");
+        assert_eq!(format_of("N:\\chr\\c0000.flver"), "flver");
+        assert_eq!(format_of("/regulation"), "");
+    }
+}
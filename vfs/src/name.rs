@@ -1,28 +1,19 @@
-// TODO: replace Name with Hasher attached to Archives, support 32 bit hash
+use format::game::Game;
+
+/// The hash of a path, which is all the archives know of their files.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Name(pub u64);
 
+impl Name {
+    pub fn new(game: Game, path: &str) -> Self {
+        Name(game.hash_path(path))
+    }
+}
+
+/// Hashes paths the way Elden Ring does, see [`Name::new`] for other games.
 impl<S: AsRef<str>> From<S> for Name {
     fn from(value: S) -> Self {
-        let value = value.as_ref();
-        let prefix = if !value.starts_with('/') {
-            Some('/')
-        } else {
-            None
-        };
-
-        let hash = prefix
-            .into_iter()
-            .chain(value.chars().map(|ch| ch.to_ascii_lowercase()))
-            .map(|ch| match ch {
-                '\\' => '/',
-                _ => ch,
-            })
-            .fold(0u64, |hash, next| {
-                hash.wrapping_mul(0x85).wrapping_add(next as u64)
-            });
-
-        Name(hash)
+        Name::new(Game::EldenRing, value.as_ref())
     }
 }
 
@@ -0,0 +1,174 @@
+use std::{collections::HashMap, error::Error, io::Cursor};
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt, BoxedFuture, LoadContext},
+    log::warn,
+    prelude::TypePath,
+};
+use format::{
+    bnd4::BND4,
+    hkx::{HkaAnimation, Hkx},
+    tae::Tae,
+};
+use souls_vfs::undo_container_compression;
+
+/// The animations of a character or object, with the names of the bones of the skeleton they
+/// were authored against.
+#[derive(Asset, Debug, TypePath)]
+pub struct AnibndAsset {
+    /// Bones of the binder's `skeleton.hkx`, which the animations' tracks are bound to.
+    pub bones: Vec<String>,
+
+    /// The animations of the binder's TAEs and HKXs, sorted by name.
+    pub animations: Vec<AnibndAnimation>,
+
+    /// The HKX of each animation by name, decoded when it's played.
+    hkx: HashMap<String, Vec<u8>>,
+}
+
+#[derive(Debug)]
+pub struct AnibndAnimation {
+    /// The animation's name from its TAE ID, e.g. `a000_003000`.
+    pub name: String,
+
+    /// The TAE the animation is listed in, e.g. 0 for `a00.tae`.
+    pub tae: u32,
+
+    /// The animation whose HKX this one plays, itself unless it imports another.
+    pub hkx: String,
+}
+
+impl AnibndAsset {
+    pub fn decode(&self, animation: &AnibndAnimation) -> Result<HkaAnimation, Box<dyn Error>> {
+        let bytes = self
+            .hkx
+            .get(&animation.hkx)
+            .ok_or_else(|| format!("{} has no HKX", animation.hkx))?;
+
+        Hkx::from_bytes(bytes)?
+            .animations()?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("{} has no animation", animation.hkx).into())
+    }
+}
+
+/// The name of an animation's HKX from its ID, e.g. `a000_003000` for 3000 in `a00.tae`.
+/// Imports name their animation by the ID without the TAE, which carries in the millions.
+fn animation_name(tae: u32, id: i64) -> String {
+    format!("a{:03}_{:06}", tae as i64 + id / 1_000_000, id % 1_000_000)
+}
+
+/// The number a name ends with after an `a`, e.g. the TAE of `a00.tae`.
+fn name_number(name: &str) -> Option<u32> {
+    name.strip_prefix('a')?.parse().ok()
+}
+
+/// Loads an `.anibnd`: its skeleton, and the animations its TAEs list along with those it only
+/// has HKXs of.
+#[derive(Default)]
+pub struct AnibndLoader;
+
+impl AssetLoader for AnibndLoader {
+    type Asset = AnibndAsset;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _: &'a (),
+        _: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<AnibndAsset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let bnd = BND4::parse(undo_container_compression(bytes)?)?;
+            let mut bones = Vec::new();
+            let mut hkx = HashMap::new();
+            let mut taes = Vec::new();
+            for file in &bnd.files {
+                let path = BND4::normalize_path(&file.path);
+                let file_name = path.rsplit('/').next().unwrap_or_default();
+                let Some((stem, extension)) = file_name.split_once('.') else {
+                    continue;
+                };
+
+                match extension {
+                    "hkx" if stem == "skeleton" => {
+                        let skeletons = Hkx::from_bytes(bnd.file_bytes(file))?.skeletons()?;
+                        let skeleton = skeletons.first().ok_or("skeleton.hkx has no skeleton")?;
+                        bones = skeleton
+                            .bones
+                            .iter()
+                            .map(|bone| bone.name.clone())
+                            .collect();
+                    }
+                    "hkx" => {
+                        hkx.insert(stem.to_string(), bnd.file_bytes(file).to_vec());
+                    }
+                    "tae" => {
+                        let tae = Tae::from_reader(&mut Cursor::new(bnd.file_bytes(file)));
+                        match (name_number(stem), tae) {
+                            (Some(number), Ok(tae)) => taes.push((number, tae)),
+                            (None, _) => warn!("Skipping TAE {} not named after its ID", path),
+                            (_, Err(e)) => warn!("Skipping TAE {}: {}", path, e),
+                        }
+                    }
+                    _ => {}
+                }
+            }
+
+            let mut animations = HashMap::new();
+            for (number, tae) in &taes {
+                for animation in &tae.animations {
+                    let name = animation_name(*number, animation.id);
+                    let hkx = animation
+                        .import_anim_id()
+                        .map_or_else(|| name.clone(), |id| animation_name(0, id as i64));
+
+                    animations.insert(
+                        name.clone(),
+                        AnibndAnimation {
+                            name,
+                            tae: *number,
+                            hkx,
+                        },
+                    );
+                }
+            }
+
+            // Animations can be in the binder without a TAE listing them.
+            for name in hkx.keys() {
+                let tae = name.split('_').next().and_then(name_number);
+                if let (Some(tae), false) = (tae, animations.contains_key(name)) {
+                    animations.insert(
+                        name.clone(),
+                        AnibndAnimation {
+                            name: name.clone(),
+                            tae,
+                            hkx: name.clone(),
+                        },
+                    );
+                }
+            }
+
+            let mut animations = animations
+                .into_values()
+                .filter(|animation| hkx.contains_key(&animation.hkx))
+                .collect::<Vec<_>>();
+            animations.sort_by(|a, b| a.name.cmp(&b.name));
+
+            Ok(AnibndAsset {
+                bones,
+                animations,
+                hkx,
+            })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["anibnd", "anibnd.dcx"]
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::{BTreeMap, HashMap};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use format::hkx::HkaAnimation;
+
+use crate::{
+    anim::asset::{AnibndAsset, AnibndLoader},
+    skeleton::{asset::SkeletonAsset, SkeletonJoints},
+};
+
+pub mod asset;
+
+/// Plays the animations of an `.anibnd` on skinned models, with a timeline to pick, play, pause
+/// and scrub them.
+pub struct AnimationPlaybackPlugin;
+
+impl Plugin for AnimationPlaybackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<AnibndAsset>()
+            .init_asset_loader::<AnibndLoader>()
+            .add_systems(Update, (timeline, play_animations).chain());
+    }
+}
+
+/// Poses the [SkeletonJoints] of a model with an animation of an `.anibnd`, matching the bones
+/// of its skeleton to the animation's by name.
+#[derive(Component)]
+pub struct AnimationPlayback {
+    anibnd: Handle<AnibndAsset>,
+
+    /// The skeleton of the model, whose bind pose bones without a track are left in.
+    skeleton: Handle<SkeletonAsset>,
+
+    /// Index into the `.anibnd`'s animations of the one being played.
+    selected: Option<usize>,
+    animation: Option<HkaAnimation>,
+    time: f32,
+    playing: bool,
+}
+
+impl AnimationPlayback {
+    pub fn new(anibnd: Handle<AnibndAsset>, skeleton: Handle<SkeletonAsset>) -> Self {
+        Self {
+            anibnd,
+            skeleton,
+            selected: None,
+            animation: None,
+            time: 0.0,
+            playing: true,
+        }
+    }
+
+    fn select(&mut self, index: usize, anibnd: &AnibndAsset) {
+        let animation = &anibnd.animations[index];
+        self.selected = Some(index);
+        self.time = 0.0;
+        self.animation = match anibnd.decode(animation) {
+            Ok(decoded) => Some(decoded),
+            Err(e) => {
+                warn!("Could not decode {}: {}", animation.name, e);
+                None
+            }
+        };
+    }
+}
+
+fn timeline(
+    mut contexts: EguiContexts,
+    mut models: Query<(&Name, &mut AnimationPlayback)>,
+    anibnds: Res<Assets<AnibndAsset>>,
+) {
+    for (name, mut playback) in &mut models {
+        let Some(anibnd) = anibnds.get(&playback.anibnd) else {
+            continue;
+        };
+        let playback = &mut *playback;
+
+        egui::Window::new(format!("Animations of {name}")).show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                let label = match playback.playing {
+                    true => "Pause",
+                    false => "Play",
+                };
+                if ui.button(label).clicked() {
+                    playback.playing = !playback.playing;
+                }
+
+                let duration = playback.animation.as_ref().map_or(0.0, |a| a.duration);
+                ui.add(egui::Slider::new(&mut playback.time, 0.0..=duration).suffix("s"));
+            });
+
+            let mut taes = BTreeMap::<_, Vec<_>>::new();
+            for (index, animation) in anibnd.animations.iter().enumerate() {
+                taes.entry(animation.tae)
+                    .or_default()
+                    .push((index, animation));
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (tae, animations) in taes {
+                    egui::CollapsingHeader::new(format!("a{tae:02}.tae")).show(ui, |ui| {
+                        for (index, animation) in animations {
+                            let label = match animation.hkx == animation.name {
+                                true => animation.name.clone(),
+                                false => format!("{} (plays {})", animation.name, animation.hkx),
+                            };
+
+                            let selected = playback.selected == Some(index);
+                            if ui.selectable_label(selected, label).clicked() {
+                                playback.select(index, anibnd);
+                            }
+                        }
+                    });
+                }
+            });
+        });
+    }
+}
+
+fn play_animations(
+    time: Res<Time>,
+    mut models: Query<(&mut AnimationPlayback, &SkeletonJoints)>,
+    anibnds: Res<Assets<AnibndAsset>>,
+    skeletons: Res<Assets<SkeletonAsset>>,
+    mut transforms: Query<&mut Transform>,
+) {
+    for (mut playback, joints) in &mut models {
+        let (Some(anibnd), Some(skeleton)) = (
+            anibnds.get(&playback.anibnd),
+            skeletons.get(&playback.skeleton),
+        ) else {
+            continue;
+        };
+
+        let duration = playback.animation.as_ref().map_or(0.0, |a| a.duration);
+        if playback.playing && duration > 0.0 {
+            playback.time = (playback.time + time.delta_seconds()) % duration;
+        }
+
+        // Bones the animation doesn't move stay in their bind pose.
+        for (bone, joint) in skeleton.bones.iter().zip(&joints.0) {
+            if let Ok(mut transform) = transforms.get_mut(*joint) {
+                *transform = bone.local;
+            }
+        }
+
+        let Some(animation) = &playback.animation else {
+            continue;
+        };
+
+        let bones = skeleton
+            .bones
+            .iter()
+            .enumerate()
+            .map(|(index, bone)| (bone.name.as_str(), index))
+            .collect::<HashMap<_, _>>();
+        let pose = animation.sample(playback.time);
+        for (track, local) in animation.track_to_bone.iter().zip(pose) {
+            let joint = track
+                .and_then(|bone| anibnd.bones.get(bone))
+                .and_then(|name| bones.get(name.as_str()))
+                .and_then(|bone| joints.0.get(*bone));
+            let Some(mut transform) = joint.and_then(|joint| transforms.get_mut(*joint).ok())
+            else {
+                continue;
+            };
+
+            *transform = Transform {
+                translation: Vec3::from_array(local.translation),
+                rotation: Quat::from_array(local.rotation),
+                scale: Vec3::from_array(local.scale),
+            };
+        }
+    }
+}
@@ -0,0 +1,300 @@
+use std::{fs, path::PathBuf};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{formats::tpf::TPFAsset, map::LoadMap, vfs::VfsAssetRepository, AssetCollection};
+
+/// Size textures are previewed at, in logical pixels.
+const PREVIEW_SIZE: f32 = 256.0;
+
+/// Binders that hold a FLVER named after them.
+const MODEL_BINDERS: &[&str] = &[".chrbnd", ".partsbnd", ".geombnd", ".mapbnd"];
+
+/// A side panel listing the files of the archives a dictionary names and the files of the
+/// mounted binders, to search them and load models, textures and maps.
+pub struct BrowserPlugin {
+    /// File name dictionaries, one virtual path per line.
+    pub dictionaries: Vec<PathBuf>,
+}
+
+impl Plugin for BrowserPlugin {
+    fn build(&self, app: &mut App) {
+        let vfs = app.world.resource::<VfsAssetRepository>();
+
+        let mut paths = vfs.mounted_files().map(str::to_string).collect::<Vec<_>>();
+        for dictionary in &self.dictionaries {
+            let contents = match fs::read_to_string(dictionary) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    warn!("Could not read dictionary {}: {}", dictionary.display(), e);
+                    continue;
+                }
+            };
+
+            paths.extend(
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .filter(|path| vfs.contains(path))
+                    .map(str::to_string),
+            );
+        }
+        paths.sort();
+        paths.dedup();
+
+        let entries = paths
+            .into_iter()
+            .map(|path| (EntryKind::of(&path), path))
+            .collect();
+
+        app.insert_resource(Browser {
+            entries,
+            ..Browser::default()
+        })
+        .add_event::<PreviewTpf>()
+        .add_systems(
+            Update,
+            (browser_panel, preview_tpfs, texture_preview).chain(),
+        );
+    }
+}
+
+/// Preview the textures of a TPF by asset path.
+#[derive(Event)]
+pub struct PreviewTpf(pub String);
+
+#[derive(Clone, Copy, PartialEq)]
+enum EntryKind {
+    Flver,
+    Tpf,
+    Msb,
+    Other,
+}
+
+impl EntryKind {
+    const ALL: [Self; 4] = [Self::Flver, Self::Tpf, Self::Msb, Self::Other];
+
+    fn of(path: &str) -> Self {
+        let path = path.strip_suffix(".dcx").unwrap_or(path);
+        if path.ends_with(".flver") || MODEL_BINDERS.iter().any(|binder| path.ends_with(binder)) {
+            Self::Flver
+        } else if path.ends_with(".tpf") {
+            Self::Tpf
+        } else if path.ends_with(".msb") {
+            Self::Msb
+        } else {
+            Self::Other
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Flver => "FLVER",
+            Self::Tpf => "TPF",
+            Self::Msb => "MSB",
+            Self::Other => "Other",
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Browser {
+    entries: Vec<(EntryKind, String)>,
+    search: String,
+
+    /// Whether entries of each [EntryKind::ALL] are listed.
+    shown: [bool; 4],
+
+    /// Indices of the entries matching the search and filters, and what they were matched with.
+    matches: Vec<usize>,
+    matched: Option<(String, [bool; 4])>,
+
+    /// The TPF being previewed, with the images of its textures once it's loaded.
+    preview: Option<(String, Handle<TPFAsset>)>,
+    preview_images: Vec<(String, Handle<Image>)>,
+}
+
+impl Default for Browser {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            search: String::new(),
+            shown: [true; 4],
+            matches: Vec::new(),
+            matched: None,
+            preview: None,
+            preview_images: Vec::new(),
+        }
+    }
+}
+
+impl Browser {
+    fn update_matches(&mut self) {
+        let query = (self.search.to_lowercase(), self.shown);
+        if self.matched.as_ref() == Some(&query) {
+            return;
+        }
+
+        self.matches = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (kind, path))| {
+                let shown = EntryKind::ALL
+                    .iter()
+                    .position(|candidate| candidate == kind)
+                    .is_some_and(|index| query.1[index]);
+                shown && path.to_lowercase().contains(&query.0)
+            })
+            .map(|(index, _)| index)
+            .collect();
+        self.matched = Some(query);
+    }
+}
+
+fn browser_panel(
+    mut contexts: EguiContexts,
+    mut browser: ResMut<Browser>,
+    mut assets: ResMut<AssetCollection>,
+    mut load_map: EventWriter<LoadMap>,
+    mut preview: EventWriter<PreviewTpf>,
+    asset_server: Res<AssetServer>,
+) {
+    let browser = &mut *browser;
+
+    egui::SidePanel::left("browser").show(contexts.ctx_mut(), |ui| {
+        ui.heading("Files");
+        ui.add(egui::TextEdit::singleline(&mut browser.search).hint_text("Search"));
+        ui.horizontal(|ui| {
+            for (kind, shown) in EntryKind::ALL.iter().zip(&mut browser.shown) {
+                ui.checkbox(shown, kind.label());
+            }
+        });
+
+        browser.update_matches();
+        ui.label(format!(
+            "{} of {} files",
+            browser.matches.len(),
+            browser.entries.len()
+        ));
+        ui.separator();
+
+        let row_height = ui.text_style_height(&egui::TextStyle::Body);
+        let mut clicked = None;
+        egui::ScrollArea::vertical().show_rows(
+            ui,
+            row_height,
+            browser.matches.len(),
+            |ui, rows| {
+                for index in &browser.matches[rows] {
+                    let (kind, path) = &browser.entries[*index];
+                    let response = match kind {
+                        EntryKind::Other => ui.label(path),
+                        _ => ui.link(path),
+                    };
+                    if response.clicked() {
+                        clicked = Some(*index);
+                    }
+                }
+            },
+        );
+
+        let Some((kind, path)) = clicked.map(|index| browser.entries[index].clone()) else {
+            return;
+        };
+        match kind {
+            EntryKind::Flver => assets.assets.push(asset_server.load(flver_path(&path))),
+            EntryKind::Tpf => {
+                preview.send(PreviewTpf(path));
+            }
+            EntryKind::Msb => {
+                let file_name = path.rsplit('/').next().unwrap_or_default();
+                let map = file_name.split('.').next().unwrap_or_default();
+                load_map.send(LoadMap(map.to_string()));
+            }
+            EntryKind::Other => {}
+        }
+    });
+}
+
+/// The asset path of a FLVER, or of the FLVER in a binder named after it, e.g.
+/// `/chr/c3660.chrbnd.dcx/c3660.flver`.
+fn flver_path(path: &str) -> String {
+    if path.ends_with(".flver") {
+        return path.to_string();
+    }
+
+    let file_name = path.rsplit('/').next().unwrap_or_default();
+    let stem = file_name.split('.').next().unwrap_or_default();
+
+    format!("{path}/{stem}.flver")
+}
+
+fn preview_tpfs(
+    mut events: EventReader<PreviewTpf>,
+    mut browser: ResMut<Browser>,
+    asset_server: Res<AssetServer>,
+) {
+    if let Some(PreviewTpf(path)) = events.read().last() {
+        browser.preview = Some((path.clone(), asset_server.load(path)));
+        browser.preview_images.clear();
+    }
+}
+
+fn texture_preview(
+    mut contexts: EguiContexts,
+    mut browser: ResMut<Browser>,
+    tpfs: Res<Assets<TPFAsset>>,
+    asset_server: Res<AssetServer>,
+) {
+    let browser = &mut *browser;
+    let Some((path, handle)) = &browser.preview else {
+        return;
+    };
+
+    if browser.preview_images.is_empty() {
+        if let Some(tpf) = tpfs.get(handle) {
+            browser.preview_images = tpf
+                .textures
+                .iter()
+                .map(|texture| {
+                    let name = texture.name.to_lowercase();
+                    let image = asset_server.load(format!("{path}#{name}"));
+                    (name, image)
+                })
+                .collect();
+        }
+    }
+
+    let textures = browser
+        .preview_images
+        .iter()
+        .map(|(name, image)| (name.clone(), contexts.add_image(image.clone_weak())))
+        .collect::<Vec<_>>();
+
+    let mut open = true;
+    egui::Window::new(path.as_str())
+        .open(&mut open)
+        .show(contexts.ctx_mut(), |ui| {
+            if textures.is_empty() {
+                ui.label("Loading");
+            }
+
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                for (name, texture) in textures {
+                    ui.label(name);
+                    ui.image(egui::load::SizedTexture::new(
+                        texture,
+                        [PREVIEW_SIZE, PREVIEW_SIZE],
+                    ));
+                }
+            });
+        });
+
+    if !open {
+        browser.preview = None;
+        browser.preview_images.clear();
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    f32::consts::TAU,
+    fs,
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    prelude::*,
+    render::{
+        camera::{Exposure, RenderTarget},
+        view::screenshot::ScreenshotManager,
+    },
+    window::{WindowRef, WindowResolution},
+};
+use bevy_egui::{egui, EguiContexts};
+use bevy_panorbit_camera::PanOrbitCamera;
+
+const SCREENSHOT_KEY: KeyCode = KeyCode::F12;
+const TURNTABLE_KEY: KeyCode = KeyCode::F11;
+
+/// Frames a capture window is drawn for before it's captured, for its surface to be created and
+/// for the last image to be read back before it's closed.
+const SETTLE_FRAMES: u32 = 3;
+
+/// Saves screenshots, and turntables of the camera orbiting its focus, as PNGs at a resolution of
+/// their own and optionally with a transparent background.
+///
+/// Images are drawn in a window of their own, which is open while they're taken. The system may
+/// shrink a window larger than the screen.
+pub struct CapturePlugin;
+
+impl Plugin for CapturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CaptureSettings>()
+            .add_event::<StartCapture>()
+            .add_systems(Update, (capture_window, start_captures, take_shots).chain());
+    }
+}
+
+/// Start a capture, unless one is being taken already.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StartCapture {
+    Screenshot,
+    Turntable,
+}
+
+#[derive(Resource)]
+struct CaptureSettings {
+    width: u32,
+    height: u32,
+    transparent: bool,
+
+    /// How many images a turntable is made of, evenly spaced around a full turn.
+    turntable_frames: u32,
+
+    /// The directory captures are saved in.
+    directory: PathBuf,
+}
+
+impl Default for CaptureSettings {
+    fn default() -> Self {
+        Self {
+            width: 3840,
+            height: 2160,
+            transparent: true,
+            turntable_frames: 36,
+            directory: PathBuf::from("captures"),
+        }
+    }
+}
+
+/// A capture in progress, on the camera drawing it.
+#[derive(Component)]
+struct Capture {
+    window: Entity,
+
+    /// Where the camera is for each image, and the path the image is saved to.
+    shots: Vec<(Transform, PathBuf)>,
+    next: usize,
+
+    /// Frames to wait for before the next image is taken, or the capture is finished.
+    settle: u32,
+}
+
+fn capture_window(
+    mut contexts: EguiContexts,
+    mut settings: ResMut<CaptureSettings>,
+    mut start: EventWriter<StartCapture>,
+    keys: Res<ButtonInput<KeyCode>>,
+    captures: Query<&Capture>,
+) {
+    let ctx = contexts.ctx_mut();
+    if !ctx.wants_keyboard_input() {
+        if keys.just_pressed(SCREENSHOT_KEY) {
+            start.send(StartCapture::Screenshot);
+        }
+        if keys.just_pressed(TURNTABLE_KEY) {
+            start.send(StartCapture::Turntable);
+        }
+    }
+
+    egui::Window::new("Capture").show(ctx, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Size");
+            ui.add(egui::DragValue::new(&mut settings.width).clamp_range(1..=16384));
+            ui.label("x");
+            ui.add(egui::DragValue::new(&mut settings.height).clamp_range(1..=16384));
+        });
+        ui.checkbox(&mut settings.transparent, "Transparent background");
+        ui.horizontal(|ui| {
+            ui.label("Turntable frames");
+            ui.add(egui::DragValue::new(&mut settings.turntable_frames).clamp_range(1..=720));
+        });
+
+        if let Ok(capture) = captures.get_single() {
+            ui.label(format!(
+                "Capturing {} of {}",
+                capture.next.min(capture.shots.len() - 1) + 1,
+                capture.shots.len()
+            ));
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            if ui
+                .button(format!("Screenshot ({SCREENSHOT_KEY:?})"))
+                .clicked()
+            {
+                start.send(StartCapture::Screenshot);
+            }
+            if ui
+                .button(format!("Turntable ({TURNTABLE_KEY:?})"))
+                .clicked()
+            {
+                start.send(StartCapture::Turntable);
+            }
+        });
+    });
+}
+
+fn start_captures(
+    mut commands: Commands,
+    mut events: EventReader<StartCapture>,
+    settings: Res<CaptureSettings>,
+    cameras: Query<(&Transform, &Exposure, &PanOrbitCamera), Without<Capture>>,
+    captures: Query<(), With<Capture>>,
+    clear_color: Res<ClearColor>,
+) {
+    let Some(kind) = events.read().last().copied() else {
+        return;
+    };
+    if !captures.is_empty() {
+        warn!("Already capturing, ignoring {:?}", kind);
+        return;
+    }
+    let Ok((transform, exposure, orbit)) = cameras.get_single() else {
+        return;
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let shots = match kind {
+        StartCapture::Screenshot => {
+            let path = settings
+                .directory
+                .join(format!("screenshot-{timestamp}.png"));
+            vec![(*transform, path)]
+        }
+        StartCapture::Turntable => {
+            let directory = settings.directory.join(format!("turntable-{timestamp}"));
+            let frames = settings.turntable_frames.max(1);
+
+            (0..frames)
+                .map(|frame| {
+                    let angle = TAU * frame as f32 / frames as f32;
+                    let mut transform = *transform;
+                    transform.rotate_around(orbit.focus, Quat::from_rotation_y(angle));
+
+                    (transform, directory.join(format!("frame_{frame:04}.png")))
+                })
+                .collect()
+        }
+    };
+    if let Some(directory) = shots.first().and_then(|(_, path)| path.parent()) {
+        if let Err(e) = fs::create_dir_all(directory) {
+            warn!("Could not create {}: {}", directory.display(), e);
+            return;
+        }
+    }
+
+    let window = commands
+        .spawn(Window {
+            title: "Capturing".to_string(),
+            resolution: WindowResolution::new(settings.width as f32, settings.height as f32)
+                .with_scale_factor_override(1.0),
+            transparent: settings.transparent,
+            ..default()
+        })
+        .id();
+    let background = match settings.transparent {
+        true => Color::NONE,
+        false => clear_color.0,
+    };
+
+    commands.spawn((
+        Camera3dBundle {
+            camera: Camera {
+                target: RenderTarget::Window(WindowRef::Entity(window)),
+                clear_color: ClearColorConfig::Custom(background),
+                ..default()
+            },
+            transform: shots[0].0,
+            exposure: *exposure,
+            ..default()
+        },
+        Capture {
+            window,
+            shots,
+            next: 0,
+            settle: SETTLE_FRAMES,
+        },
+    ));
+}
+
+fn take_shots(
+    mut commands: Commands,
+    mut captures: Query<(Entity, &mut Capture, &mut Transform)>,
+    mut screenshots: ResMut<ScreenshotManager>,
+) {
+    for (entity, mut capture, mut transform) in &mut captures {
+        if capture.settle > 0 {
+            capture.settle -= 1;
+            continue;
+        }
+
+        let Some((shot, path)) = capture.shots.get(capture.next).cloned() else {
+            info!("Finished capturing {} images", capture.shots.len());
+            commands.entity(entity).despawn();
+            commands.entity(capture.window).despawn();
+            continue;
+        };
+
+        *transform = shot;
+        let saved = screenshots.take_screenshot(capture.window, move |image| {
+            let saved = image
+                .try_into_dynamic()
+                .map_err(|e| e.to_string())
+                .and_then(|image| image.to_rgba8().save(&path).map_err(|e| e.to_string()));
+            match saved {
+                Ok(()) => info!("Saved {}", path.display()),
+                Err(e) => warn!("Could not save {}: {}", path.display(), e),
+            }
+        });
+        if saved.is_err() {
+            continue;
+        }
+
+        capture.next += 1;
+        if capture.next == capture.shots.len() {
+            capture.settle = SETTLE_FRAMES;
+        }
+    }
+}
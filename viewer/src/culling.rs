@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+use bevy::{
+    prelude::*,
+    render::{
+        primitives::Aabb,
+        view::{VisibilitySystems, VisibleEntities},
+    },
+};
+use bevy_egui::{egui, EguiContexts};
+use bevy_panorbit_camera::PanOrbitCamera;
+
+use crate::flver::FlverMesh;
+
+/// Stops drawing FLVER meshes further from the camera than a draw distance, on top of the
+/// frustum culling every mesh with bounds gets.
+///
+/// Meshes are left out of what each camera draws rather than hidden, so that they don't fight
+/// with the layers and visibility toggles over [Visibility].
+pub struct CullingPlugin;
+
+impl Plugin for CullingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DrawDistance>()
+            .add_systems(Update, culling_window)
+            .add_systems(
+                PostUpdate,
+                cull_distant_meshes.after(VisibilitySystems::CheckVisibility),
+            );
+    }
+}
+
+/// How far from the camera meshes are drawn, in meters, if there's a limit.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct DrawDistance(pub Option<f32>);
+
+impl Default for DrawDistance {
+    fn default() -> Self {
+        Self(Some(DrawDistance::DEFAULT))
+    }
+}
+
+impl DrawDistance {
+    const DEFAULT: f32 = 1000.0;
+}
+
+fn culling_window(
+    mut contexts: EguiContexts,
+    mut draw_distance: ResMut<DrawDistance>,
+    cameras: Query<&VisibleEntities, With<PanOrbitCamera>>,
+    meshes: Query<(&Handle<Mesh>, &Handle<StandardMaterial>), With<FlverMesh>>,
+) {
+    egui::Window::new("Culling").show(contexts.ctx_mut(), |ui| {
+        let mut distance = *draw_distance;
+        let mut limited = distance.0.is_some();
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut limited, "Draw distance");
+            let mut meters = distance.0.unwrap_or(DrawDistance::DEFAULT);
+            ui.add_enabled(
+                limited,
+                egui::DragValue::new(&mut meters)
+                    .clamp_range(1.0..=f32::MAX)
+                    .suffix(" m"),
+            );
+            distance.0 = limited.then_some(meters);
+        });
+
+        // Meshes sharing a mesh and material are drawn together, instanced.
+        if let Ok(visible) = cameras.get_single() {
+            let drawn = meshes.iter_many(visible.iter()).collect::<Vec<_>>();
+            let batches = drawn.iter().copied().collect::<HashSet<_>>().len();
+            ui.label(format!(
+                "{} of {} meshes drawn, in {} batches",
+                drawn.len(),
+                meshes.iter().count(),
+                batches
+            ));
+        }
+
+        // Only flag the resource as changed when the distance is edited.
+        if distance != *draw_distance {
+            *draw_distance = distance;
+        }
+    });
+}
+
+fn cull_distant_meshes(
+    draw_distance: Res<DrawDistance>,
+    mut cameras: Query<(&GlobalTransform, &mut VisibleEntities), With<Camera3d>>,
+    meshes: Query<(&GlobalTransform, &Aabb), With<FlverMesh>>,
+) {
+    let Some(max_distance) = draw_distance.0 else {
+        return;
+    };
+
+    for (camera, mut visible) in &mut cameras {
+        let camera = camera.translation();
+        visible.entities.retain(|entity| {
+            let Ok((transform, aabb)) = meshes.get(*entity) else {
+                return true;
+            };
+
+            // The distance to the sphere around the mesh's bounds.
+            let (scale, _, _) = transform.to_scale_rotation_translation();
+            let center = transform.transform_point(aabb.center.into());
+            let radius = Vec3::from(aabb.half_extents).length() * scale.abs().max_element();
+            camera.distance(center) - radius <= max_distance
+        });
+    }
+}
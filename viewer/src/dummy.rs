@@ -0,0 +1,130 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{flver::asset::FlverAsset, skeleton::SkeletonJoints};
+
+/// Length of the arrows showing which way a dummy point faces.
+const AXIS_LENGTH: f32 = 0.1;
+
+const POINT_RADIUS: f32 = 0.01;
+const POINT_COLOR: Color = Color::CYAN;
+const FORWARD_COLOR: Color = Color::BLUE;
+const UP_COLOR: Color = Color::GREEN;
+const LABEL_COLOR: egui::Color32 = egui::Color32::from_rgb(0, 255, 255);
+
+/// Draws the dummy points of FLVERs, which effects and weapons are attached to, as arrows facing
+/// their forward and up directions, labelled with their reference IDs.
+pub struct DummyPlugin;
+
+impl Plugin for DummyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DummyFilter>()
+            .add_systems(Update, (dummy_window, draw_dummies).chain());
+    }
+}
+
+/// The dummy points of the FLVER of a model, drawn in the space of the entity.
+#[derive(Component)]
+pub struct DummyPoints(pub Handle<FlverAsset>);
+
+/// Which dummy points are drawn, by reference ID.
+#[derive(Resource)]
+struct DummyFilter {
+    visible: bool,
+    min: u16,
+    max: u16,
+}
+
+impl Default for DummyFilter {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            min: 0,
+            max: u16::MAX,
+        }
+    }
+}
+
+impl DummyFilter {
+    fn shows(&self, ref_id: u16) -> bool {
+        self.visible && (self.min..=self.max).contains(&ref_id)
+    }
+}
+
+fn dummy_window(mut contexts: EguiContexts, mut filter: ResMut<DummyFilter>) {
+    egui::Window::new("Dummy points").show(contexts.ctx_mut(), |ui| {
+        ui.checkbox(&mut filter.visible, "Show");
+        ui.horizontal(|ui| {
+            ui.label("Reference IDs");
+            ui.add(egui::DragValue::new(&mut filter.min));
+            ui.label("to");
+            ui.add(egui::DragValue::new(&mut filter.max));
+        });
+    });
+}
+
+fn draw_dummies(
+    mut contexts: EguiContexts,
+    mut gizmos: Gizmos,
+    filter: Res<DummyFilter>,
+    models: Query<(
+        &DummyPoints,
+        &GlobalTransform,
+        Option<&SkeletonJoints>,
+        &InheritedVisibility,
+    )>,
+    joint_transforms: Query<&GlobalTransform>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    flvers: Res<Assets<FlverAsset>>,
+) {
+    if !filter.visible {
+        return;
+    }
+
+    let camera = cameras.get_single().ok();
+    let painter = contexts.ctx_mut().layer_painter(egui::LayerId::new(
+        egui::Order::Background,
+        egui::Id::new("dummy_labels"),
+    ));
+
+    for (points, transform, joints, visibility) in &models {
+        let Some(flver) = flvers.get(&points.0).filter(|_| visibility.get()) else {
+            continue;
+        };
+
+        for dummy in flver.dummies() {
+            if !filter.shows(dummy.ref_id) {
+                continue;
+            }
+
+            // Points follow the pose of the bone they're attached to, if the model is skinned.
+            let attached = dummy.attach.and_then(|(bone, local)| {
+                let joint = joint_transforms.get(*joints?.0.get(bone)?).ok()?;
+                Some(joint.mul_transform(local))
+            });
+            let point = attached.unwrap_or_else(|| transform.mul_transform(dummy.model));
+
+            let position = point.translation();
+            gizmos.sphere(position, Quat::IDENTITY, POINT_RADIUS, POINT_COLOR);
+            gizmos.arrow(
+                position,
+                position + point.forward() * AXIS_LENGTH,
+                FORWARD_COLOR,
+            );
+            gizmos.arrow(position, position + point.up() * AXIS_LENGTH, UP_COLOR);
+
+            let Some(screen) = camera.and_then(|(camera, camera_transform)| {
+                camera.world_to_viewport(camera_transform, position)
+            }) else {
+                continue;
+            };
+            painter.text(
+                egui::pos2(screen.x, screen.y),
+                egui::Align2::LEFT_BOTTOM,
+                dummy.ref_id,
+                egui::FontId::monospace(12.0),
+                LABEL_COLOR,
+            );
+        }
+    }
+}
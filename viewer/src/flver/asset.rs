@@ -2,52 +2,220 @@ use std::{error::Error, io::Cursor};
 
 use bevy::{
     asset::{io::Reader, Asset, AssetLoader, AsyncReadExt, BoxedFuture, Handle, LoadContext},
-    log::warn,
-    prelude::{Mesh, TypePath},
+    log::{debug, info_span, warn},
+    math::{Mat4, Vec3},
+    prelude::{FromWorld, Mesh, StandardMaterial, Transform, TypePath, World},
     render::{
-        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        mesh::{
+            skinning::SkinnedMeshInverseBindposes, Indices, MeshVertexAttribute, PrimitiveTopology,
+            VertexAttributeValues,
+        },
+        primitives::Aabb,
         render_asset::RenderAssetUsages,
+        render_resource::VertexFormat,
     },
 };
 use byteorder::LE;
 use format::flver::{
     accessor::VertexAttributeAccessor,
-    face_set::FaceSetIndices,
+    dummy::Dummy,
+    face_set::{FaceSet, FaceSetIndices},
     mesh::Mesh as FlverMesh,
     reader::{VertexAttributeFormat, VertexAttributeSemantic, FLVER},
     Flver,
 };
+use serde::{Deserialize, Serialize};
 
-#[derive(Default)]
-pub struct FlverLoader;
+use crate::{
+    flver::material::{standard_material, TextureResolver},
+    loose::{LooseFiles, LOOSE_SOURCE},
+    skeleton::asset::SkeletonAsset,
+    vfs::VfsAssetRepository,
+};
+
+/// The least detailed level of detail FLVER face sets have.
+pub const MAX_LOD: u8 = 2;
+
+/// The vertex colors of a FLVER. They're kept out of [Mesh::ATTRIBUTE_COLOR] since the game uses
+/// them for blending rather than tinting, which is how [StandardMaterial] would apply them.
+pub const ATTRIBUTE_VERTEX_COLOR: MeshVertexAttribute =
+    MeshVertexAttribute::new("FlverVertexColor", 0x464c_5652, VertexFormat::Float32x4);
+
+/// Loads FLVERs with their materials, whose textures are looked up in the VFS, or in the files
+/// dropped onto the viewer for FLVERs that were dropped.
+pub struct FlverLoader {
+    vfs: VfsAssetRepository,
+    loose: LooseFiles,
+}
+
+impl FromWorld for FlverLoader {
+    fn from_world(world: &mut World) -> Self {
+        Self {
+            vfs: world.resource::<VfsAssetRepository>().clone(),
+            loose: world.resource::<LooseFiles>().clone(),
+        }
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct FlverSettings {
+    /// Leave the bone weights out of the meshes, so that they're drawn as they are in their bind
+    /// pose. Copies of rigid meshes are drawn instanced, which skinned meshes can't be.
+    pub rigid: bool,
+}
 
 #[derive(Asset, Debug, TypePath)]
 pub struct FlverAsset {
-    meshes: Vec<Handle<Mesh>>,
+    /// Each level of detail of each mesh, from the most detailed.
+    meshes: Vec<Vec<Handle<Mesh>>>,
+
+    /// The material of each mesh.
+    materials: Vec<Handle<StandardMaterial>>,
+
+    skeleton: Handle<SkeletonAsset>,
+
+    /// The inverse of each bone's bind pose, to skin the meshes with.
+    inverse_bindposes: Handle<SkinnedMeshInverseBindposes>,
+
+    /// Whether the meshes are bound to the bones of the skeleton.
+    skinned: bool,
+
+    /// What each mesh is made of, for the inspector.
+    details: Vec<MeshDetails>,
+
+    dummies: Vec<DummyPoint>,
+}
+
+/// The size and material of a mesh, kept since the mesh itself is only in the render world.
+#[derive(Debug)]
+pub struct MeshDetails {
+    pub vertices: usize,
+    pub indices: usize,
+
+    /// The mesh's bounds in its bind pose, to cull and pick it with.
+    pub aabb: Option<Aabb>,
+    pub material: String,
+
+    /// The material definition, an MTD or MATBIN.
+    pub material_path: String,
+
+    /// The type and path of each texture of the material.
+    pub textures: Vec<(String, String)>,
+}
+
+/// A point that effects, weapons and hitboxes are attached to, by its reference ID.
+#[derive(Debug)]
+pub struct DummyPoint {
+    pub ref_id: u16,
+
+    /// Where the point is and which way it faces, relative to the model's origin in its bind
+    /// pose. The point faces -Z, like a [Transform::forward].
+    pub model: Transform,
+
+    /// The bone the point moves with, with the point's transform relative to it.
+    pub attach: Option<(usize, Transform)>,
+}
+
+impl DummyPoint {
+    fn new(dummy: &Dummy<LE>, skeleton: &SkeletonAsset) -> Self {
+        let bone = |index: u16| usize::try_from(index as i16).ok();
+        let bind_pose = |index: Option<usize>| {
+            index
+                .and_then(|index| skeleton.bones.get(index))
+                .map(|bone| bone.model)
+        };
+
+        let position = Vec3::from_array(dummy.position.map(|v| v.get()));
+        let forward = Vec3::from_array(dummy.forward.map(|v| v.get()));
+        let up = match dummy.use_up_vector {
+            0 => Vec3::Y,
+            _ => Vec3::from_array(dummy.up_vector.map(|v| v.get())),
+        };
+
+        // The position and directions are relative to the parent bone.
+        let mut local = Transform::from_translation(position);
+        if forward.length_squared() > 0.0 && forward.cross(up).length_squared() > 0.0 {
+            local.look_to(forward, up);
+        }
+        let parent = bind_pose(bone(dummy.parent_bone_index.get())).unwrap_or(Mat4::IDENTITY);
+        let model = Transform::from_matrix(parent * local.compute_matrix());
+
+        let attach_index = bone(u16::from_le(dummy.attached_bone_index));
+        let attach = attach_index
+            .zip(bind_pose(attach_index))
+            .map(|(index, attach)| {
+                (
+                    index,
+                    Transform::from_matrix(attach.inverse() * model.compute_matrix()),
+                )
+            });
+
+        Self {
+            ref_id: dummy.ref_id.get(),
+            model,
+            attach,
+        }
+    }
 }
 
 impl FlverAsset {
-    pub fn meshes(&self) -> impl Iterator<Item = &Handle<Mesh>> {
-        self.meshes.iter()
+    /// The most detailed level of each mesh, with its material.
+    pub fn meshes(&self) -> impl Iterator<Item = (&Handle<Mesh>, &Handle<StandardMaterial>)> {
+        self.meshes
+            .iter()
+            .filter_map(|lods| lods.first())
+            .zip(&self.materials)
+    }
+
+    /// The mesh at `index` at level of detail `lod`, or at its least detailed level if it has
+    /// fewer.
+    pub fn mesh_lod(&self, index: usize, lod: u8) -> Option<&Handle<Mesh>> {
+        let lods = self.meshes.get(index)?;
+        lods.get(lod as usize).or(lods.last())
+    }
+
+    /// How many levels of detail the mesh at `index` has.
+    pub fn lod_count(&self, index: usize) -> usize {
+        self.meshes.get(index).map_or(0, Vec::len)
+    }
+
+    pub fn skeleton(&self) -> &Handle<SkeletonAsset> {
+        &self.skeleton
+    }
+
+    pub fn inverse_bindposes(&self) -> &Handle<SkinnedMeshInverseBindposes> {
+        &self.inverse_bindposes
+    }
+
+    pub fn is_skinned(&self) -> bool {
+        self.skinned
+    }
+
+    pub fn dummies(&self) -> &[DummyPoint] {
+        &self.dummies
+    }
+
+    pub fn mesh_details(&self, index: usize) -> Option<&MeshDetails> {
+        self.details.get(index)
     }
 }
 
 impl AssetLoader for FlverLoader {
     type Asset = FlverAsset;
-    type Settings = ();
+    type Settings = FlverSettings;
     type Error = Box<dyn Error + Send + Sync>;
 
     fn load<'a>(
         &'a self,
         reader: &'a mut Reader,
-        _: &'a (),
+        settings: &'a FlverSettings,
         load_context: &'a mut LoadContext,
     ) -> BoxedFuture<'a, Result<FlverAsset, Self::Error>> {
         Box::pin(async move {
             let mut bytes = Vec::new();
             reader.read_to_end(&mut bytes).await?;
 
-            FlverLoader::load_flver(&bytes, load_context).await
+            self.load_flver(&bytes, settings, load_context).await
         })
     }
 
@@ -58,32 +226,138 @@ impl AssetLoader for FlverLoader {
 
 impl FlverLoader {
     async fn load_flver<'a, 'data, 'ctx>(
+        &self,
         bytes: &'data [u8],
+        settings: &FlverSettings,
         load_context: &'a mut LoadContext<'ctx>,
     ) -> Result<FlverAsset, Box<dyn Error + Send + Sync>> {
+        let _span = info_span!("load_flver", path = %load_context.path().display()).entered();
+
         let mut reader = Cursor::new(bytes);
         let flver1 = FLVER::from_reader(&mut reader)?;
         let flver = Flver::parse(bytes).expect("failed to parse flver");
 
-        println!("{:#?}", flver1.buffer_layouts);
-        println!(
+        debug!("{:#?}", flver1.buffer_layouts);
+        debug!(
             "{:#?}",
-            flver.vertex_attributes(&flver.vertex_buffer_layouts[0])
+            flver.vertex_attributes(&flver.vertex_buffer_layouts[0])?
         );
+        let mut textures = TextureResolver::new(&self.vfs);
+
+        // FLVERs read from a binder in the archives, e.g. the parts of a map, use the textures
+        // next to them. Characters keep theirs in texture binders of their own too.
+        let path = load_context.path().to_string_lossy().replace('\\', "/");
+        if load_context.asset_path().source().as_str() == Some(LOOSE_SOURCE) {
+            for (tpf, bytes) in self.loose.tpfs() {
+                textures.add_tpf(format!("{LOOSE_SOURCE}://{tpf}"), bytes.to_vec());
+            }
+        } else if let Some((binder, _)) = path.rsplit_once('/') {
+            if binder
+                .strip_suffix(".dcx")
+                .unwrap_or(binder)
+                .ends_with("bnd")
+            {
+                textures.add_binder(binder);
+            }
+            if let Some(character) = binder.strip_suffix(".chrbnd.dcx") {
+                textures.add_binder(&format!("{character}_h.texbnd.dcx"));
+                textures.add_binder(&format!("{character}.texbnd.dcx"));
+            }
+        }
+        let flver_materials = flver
+            .materials()
+            .iter()
+            .enumerate()
+            .map(|(index, material)| {
+                load_context.labeled_asset_scope(format!("material{}", index), |load_context| {
+                    standard_material(&flver, material, &mut textures, load_context)
+                })
+            })
+            .collect::<Vec<_>>();
+
         let mut meshes = Vec::with_capacity(flver.mesh_count());
+        let mut materials = Vec::with_capacity(flver.mesh_count());
+        let mut details = Vec::with_capacity(flver.mesh_count());
+        let skinned = !settings.rigid && !flver.bones().is_empty();
 
         for (index, flver_mesh) in flver.meshes.iter().enumerate() {
-            let mesh_handle = load_context
-                .labeled_asset_scope(format!("mesh{}", index), |_| load_mesh(&flver, flver_mesh));
+            let mesh = load_mesh(&flver, flver_mesh, skinned);
+            details.push(mesh_details(&flver, flver_mesh, &mesh));
 
-            meshes.push(mesh_handle);
+            let lods = lod_meshes(&flver, flver_mesh, &mesh);
+            let mut handles = vec![load_context.add_labeled_asset(format!("mesh{}", index), mesh)];
+            for (lod, mesh) in lods.into_iter().enumerate() {
+                handles.push(
+                    load_context.add_labeled_asset(format!("mesh{}_lod{}", index, lod + 1), mesh),
+                );
+            }
+            meshes.push(handles);
+            materials.push(
+                flver_materials
+                    .get(flver_mesh.material_index.get() as usize)
+                    .cloned()
+                    .unwrap_or_default(),
+            );
         }
 
-        Ok(FlverAsset { meshes })
+        let skeleton = SkeletonAsset::from_flver(&flver);
+        let dummies = flver
+            .dummies()
+            .iter()
+            .map(|dummy| DummyPoint::new(dummy, &skeleton))
+            .collect();
+        let inverse_bindposes =
+            load_context.labeled_asset_scope("inverse_bindposes".to_string(), |_| {
+                SkinnedMeshInverseBindposes::from(
+                    skeleton
+                        .bones
+                        .iter()
+                        .map(|bone| bone.model.inverse())
+                        .collect::<Vec<_>>(),
+                )
+            });
+        let skeleton = load_context.add_labeled_asset("skeleton".to_string(), skeleton);
+
+        Ok(FlverAsset {
+            meshes,
+            materials,
+            skeleton,
+            inverse_bindposes,
+            skinned,
+            details,
+            dummies,
+        })
     }
 }
 
-fn load_mesh(flver: &Flver, flver_mesh: &FlverMesh<LE>) -> Mesh {
+fn mesh_details(flver: &Flver, flver_mesh: &FlverMesh<LE>, mesh: &Mesh) -> MeshDetails {
+    let material = flver.mesh_material(flver_mesh);
+
+    MeshDetails {
+        vertices: mesh.count_vertices(),
+        indices: mesh.indices().map_or(0, Indices::len),
+        aabb: mesh.compute_aabb(),
+        material: material
+            .and_then(|material| flver.material_name(material))
+            .unwrap_or_default(),
+        material_path: material
+            .and_then(|material| flver.material_path(material))
+            .unwrap_or_default(),
+        textures: material
+            .map(|material| flver.material_textures(material))
+            .unwrap_or_default()
+            .iter()
+            .map(|texture| {
+                (
+                    flver.texture_type(texture).unwrap_or_default(),
+                    flver.texture_path(texture).unwrap_or_default(),
+                )
+            })
+            .collect(),
+    }
+}
+
+fn load_mesh(flver: &Flver, flver_mesh: &FlverMesh<LE>, skinned: bool) -> Mesh {
     let mut mesh = Mesh::new(
         PrimitiveTopology::TriangleList,
         RenderAssetUsages::RENDER_WORLD,
@@ -100,14 +374,34 @@ fn load_mesh(flver: &Flver, flver_mesh: &FlverMesh<LE>) -> Mesh {
         .expect("no vertex buffers for FLVER");
 
     let layout = &flver.vertex_buffer_layouts[buffer.layout_index.get() as usize];
-    let layout_members = flver.vertex_attributes(layout);
+    let layout_members = flver
+        .vertex_attributes(layout)
+        .expect("malformed vertex buffer layout");
 
     for member in layout_members {
         use format::flver::reader::VertexAttributeSemantic::*;
 
-        let accessor = flver.vertex_attribute_accessor(buffer, member);
-        let semantic = VertexAttributeSemantic::from(member.semantic_id.get());
-        let format = VertexAttributeFormat::from(member.format_id.get());
+        let (Ok(semantic), Ok(format)) = (
+            VertexAttributeSemantic::try_from(member.semantic_id.get()),
+            VertexAttributeFormat::try_from(member.format_id.get()),
+        ) else {
+            warn!(
+                "Vertex Attribute {:#x} with format {:#x} is unknown",
+                member.semantic_id.get(),
+                member.format_id.get()
+            );
+
+            continue;
+        };
+
+        let accessor = match flver.vertex_attribute_accessor(buffer, member) {
+            Ok(accessor) => accessor,
+            Err(e) => {
+                warn!("Skipping vertex attribute {:#?}: {}", semantic, e);
+
+                continue;
+            }
+        };
 
         let (attribute, values) = match (semantic, accessor) {
             (Position, VertexAttributeAccessor::Float3(it)) => (
@@ -118,10 +412,44 @@ fn load_mesh(flver: &Flver, flver_mesh: &FlverMesh<LE>) -> Mesh {
                 Mesh::ATTRIBUTE_NORMAL,
                 VertexAttributeValues::Float32x3(it.collect()),
             ),
-            (UV, VertexAttributeAccessor::UV(it)) => (
-                Mesh::ATTRIBUTE_UV_0,
+            // Meshes only have attributes for the first two UV channels.
+            (UV, VertexAttributeAccessor::UV(it)) if member.index.get() < 2 => (
+                match member.index.get() {
+                    0 => Mesh::ATTRIBUTE_UV_0,
+                    _ => Mesh::ATTRIBUTE_UV_1,
+                },
                 VertexAttributeValues::Float32x2(it.collect()),
             ),
+            (VertexColor, VertexAttributeAccessor::Float4(it)) if member.index.get() == 0 => (
+                ATTRIBUTE_VERTEX_COLOR,
+                VertexAttributeValues::Float32x4(it.collect()),
+            ),
+            (
+                VertexColor,
+                VertexAttributeAccessor::Byte4A(it) | VertexAttributeAccessor::Byte4C(it),
+            ) if member.index.get() == 0 => (
+                ATTRIBUTE_VERTEX_COLOR,
+                VertexAttributeValues::Float32x4(
+                    it.map(|color| color.map(|c| c as f32 / 255.0)).collect(),
+                ),
+            ),
+            // Elden Ring's vertices index the FLVER's bones rather than a per-mesh list of them.
+            (
+                BoneIndices,
+                VertexAttributeAccessor::Byte4A(it)
+                | VertexAttributeAccessor::Byte4B(it)
+                | VertexAttributeAccessor::Byte4C(it),
+            ) => (
+                Mesh::ATTRIBUTE_JOINT_INDEX,
+                VertexAttributeValues::Uint16x4(it.map(|indices| indices.map(u16::from)).collect()),
+            ),
+            (BoneWeights, VertexAttributeAccessor::Short4ToFloat4A(it)) => (
+                Mesh::ATTRIBUTE_JOINT_WEIGHT,
+                VertexAttributeValues::Float32x4(
+                    it.map(|weights| weights.map(|w| w as i16 as f32 / 32767.0))
+                        .collect(),
+                ),
+            ),
             _ => {
                 warn!(
                     "Vertex Attribute {:#?} and format {:#?} is currently unsupported",
@@ -135,15 +463,79 @@ fn load_mesh(flver: &Flver, flver_mesh: &FlverMesh<LE>) -> Mesh {
         mesh.insert_attribute(attribute, values);
     }
 
-    let indices = match flver.face_set_indices(face_set) {
-        Some(FaceSetIndices::U8(data)) => {
-            Indices::U16(data.iter().map(|index| *index as u16).collect())
-        }
-        Some(FaceSetIndices::U16(data)) => Indices::U16(data.iter().map(|val| val.get()).collect()),
-        Some(FaceSetIndices::U32(data)) => Indices::U32(data.iter().map(|val| val.get()).collect()),
-        _ => unimplemented!(),
-    };
+    mesh.insert_indices(face_set_indices(flver, face_set).expect("main face set has no indices"));
+
+    if skinned {
+        bind_to_bones(&mut mesh, flver_mesh);
+    } else {
+        mesh.remove_attribute(Mesh::ATTRIBUTE_JOINT_INDEX);
+        mesh.remove_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT);
+    }
+
+    // Normal maps need tangents, which are generated rather than decoded from the FLVER's own.
+    if let Err(e) = mesh.generate_tangents() {
+        warn!(
+            "Could not generate tangents, normal maps won't apply: {}",
+            e
+        );
+
+        let count = mesh.count_vertices();
+        mesh.insert_attribute(Mesh::ATTRIBUTE_TANGENT, vec![[1.0, 0.0, 0.0, 1.0]; count]);
+    }
 
-    mesh.insert_indices(indices);
     mesh
 }
+
+fn face_set_indices(flver: &Flver, face_set: &FaceSet<LE>) -> Option<Indices> {
+    match flver.face_set_indices(face_set)? {
+        FaceSetIndices::U8(data) => Some(Indices::U16(
+            data.iter().map(|index| *index as u16).collect(),
+        )),
+        FaceSetIndices::U16(data) => Some(Indices::U16(data.iter().map(|val| val.get()).collect())),
+        FaceSetIndices::U32(data) => Some(Indices::U32(data.iter().map(|val| val.get()).collect())),
+        FaceSetIndices::None => None,
+    }
+}
+
+/// The lower levels of detail of `mesh`, from LOD1 until the first level the FLVER doesn't have.
+/// They share the vertices of the full mesh, with the indices of their own face set.
+fn lod_meshes(flver: &Flver, flver_mesh: &FlverMesh<LE>, mesh: &Mesh) -> Vec<Mesh> {
+    (1..=MAX_LOD)
+        .map_while(|lod| {
+            let face_set = flver
+                .mesh_face_sets(flver_mesh)
+                .find(|set| set.lod() == Some(lod))?;
+            let indices = face_set_indices(flver, face_set)?;
+
+            let mut lod_mesh = mesh.clone();
+            lod_mesh.insert_indices(indices);
+            Some(lod_mesh)
+        })
+        .collect()
+}
+
+/// Make sure every vertex is bound to a bone, so that the mesh can be skinned: vertices without
+/// weights follow their first bone, and meshes without bone indices their default bone.
+fn bind_to_bones(mesh: &mut Mesh, flver_mesh: &FlverMesh<LE>) {
+    let count = mesh.count_vertices();
+    if mesh.attribute(Mesh::ATTRIBUTE_JOINT_INDEX).is_none() {
+        let bone = flver_mesh.default_bone_index.get() as u16;
+        mesh.insert_attribute(
+            Mesh::ATTRIBUTE_JOINT_INDEX,
+            VertexAttributeValues::Uint16x4(vec![[bone, 0, 0, 0]; count]),
+        );
+        mesh.remove_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT);
+    }
+
+    let weights = match mesh.attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT) {
+        Some(VertexAttributeValues::Float32x4(weights)) => weights
+            .iter()
+            .map(|weights| match weights.iter().sum::<f32>() {
+                sum if sum > 0.0 => weights.map(|w| w / sum),
+                _ => [1.0, 0.0, 0.0, 0.0],
+            })
+            .collect(),
+        _ => vec![[1.0, 0.0, 0.0, 0.0]; count],
+    };
+    mesh.insert_attribute(Mesh::ATTRIBUTE_JOINT_WEIGHT, weights);
+}
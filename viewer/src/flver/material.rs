@@ -0,0 +1,192 @@
+use std::{collections::HashMap, io::Cursor};
+
+use bevy::{
+    asset::LoadContext,
+    log::{debug, warn},
+    prelude::{AlphaMode, Image, StandardMaterial},
+};
+use byteorder::LE;
+use format::{
+    bnd4::BND4,
+    flver::{material::Material, Flver},
+    tpf::TPF,
+};
+use souls_vfs::{undo_container_compression, Vfs};
+
+use crate::formats::tpf::METALLIC_ROUGHNESS_SUFFIX;
+
+/// Finds the TPF holding each texture a FLVER's materials use, as an asset path to the texture's
+/// image, e.g. `/asset/aet/aet007/aet007_077.tpf.dcx#aet007_077_a`.
+///
+/// Textures are looked for in the binder holding the FLVER, in the mounted binders, e.g. a
+/// `.partsbnd` or a mounted `.tpfbhd` of a map, and in the archives of the asset textures.
+pub struct TextureResolver<'a> {
+    vfs: &'a Vfs,
+
+    /// The TPF holding each texture in the binders of the FLVER, as the path of the TPF in its
+    /// binder, e.g. `/chr/c3660.chrbnd.dcx/c3660.tpf`.
+    binders: HashMap<String, String>,
+
+    /// The TPF holding each texture in the mounted binders, read once a texture isn't in a TPF
+    /// of its own.
+    mounted: Option<HashMap<String, String>>,
+}
+
+impl<'a> TextureResolver<'a> {
+    pub fn new(vfs: &'a Vfs) -> Self {
+        Self {
+            vfs,
+            binders: HashMap::new(),
+            mounted: None,
+        }
+    }
+
+    /// Look for textures in the TPFs of the binder at `path` in the archives too.
+    pub fn add_binder(&mut self, path: &str) {
+        let Ok(bytes) = self.vfs.read_decompressed(path) else {
+            return;
+        };
+        let Ok(bnd) = BND4::parse(bytes) else {
+            warn!("Could not read binder {}", path);
+            return;
+        };
+
+        for file in &bnd.files {
+            let name = file.path.rsplit(['/', '\\']).next().unwrap_or_default();
+            if name.strip_suffix(".dcx").unwrap_or(name).ends_with(".tpf") {
+                self.add_tpf(format!("{path}/{name}"), bnd.file_bytes(file).to_vec());
+            }
+        }
+    }
+
+    /// Look for textures in the TPF read from `bytes`, loaded from the asset path `tpf`.
+    pub fn add_tpf(&mut self, tpf: String, bytes: Vec<u8>) {
+        let Some(textures) = texture_names(bytes) else {
+            warn!("Could not read TPF {}", tpf);
+            return;
+        };
+
+        for texture in textures {
+            self.binders.insert(texture, tpf.clone());
+        }
+    }
+
+    /// The asset path of the image of the texture at `path`, as a FLVER names it, e.g.
+    /// `N:\GR\data\INTERROOT_win64\parts\...\WP_A_0210_a.tif`.
+    pub fn resolve(&mut self, path: &str) -> Option<String> {
+        let file_name = path.rsplit(['/', '\\']).next()?;
+        let name = file_name.split('.').next()?.to_lowercase();
+        if name.is_empty() {
+            return None;
+        }
+
+        if let Some(tpf) = self.binders.get(&name) {
+            return Some(format!("{tpf}#{name}"));
+        }
+
+        // Map textures are stored in a TPF each, e.g. in a `.tpfbhd`.
+        for tpf in [format!("{name}.tpf.dcx"), format!("{name}.tpf")] {
+            if self.vfs.open_from_mounts(&tpf).is_ok() {
+                return Some(format!("{tpf}#{name}"));
+            }
+        }
+
+        // Asset textures are grouped by their asset, e.g. `aet007_077_a` in `aet007_077.tpf.dcx`.
+        if let Some(archive) = name.get(..10).filter(|_| name.starts_with("aet")) {
+            let tpf = format!("/asset/aet/{}/{archive}.tpf.dcx", &archive[..6]);
+            if self.vfs.contains(&tpf) {
+                return Some(format!("{tpf}#{name}"));
+            }
+        }
+
+        let mounted = self.mounted.get_or_insert_with(|| index_mounted(self.vfs));
+        mounted.get(&name).map(|tpf| format!("{tpf}#{name}"))
+    }
+}
+
+/// The mounted TPF holding each texture of the mounted binders, by lowercase name.
+fn index_mounted(vfs: &Vfs) -> HashMap<String, String> {
+    let mut textures = HashMap::new();
+    for file in vfs.mounted_files() {
+        if !file.strip_suffix(".dcx").unwrap_or(file).ends_with(".tpf") {
+            continue;
+        }
+
+        let names = vfs
+            .open_from_mounts(file)
+            .ok()
+            .and_then(|bytes| texture_names(bytes.to_vec()));
+        let Some(names) = names else {
+            warn!("Could not read mounted TPF {}", file);
+            continue;
+        };
+
+        for texture in names {
+            textures.insert(texture, file.to_string());
+        }
+    }
+
+    textures
+}
+
+/// The lowercase names of the textures of a TPF, which may be DCX compressed.
+fn texture_names(bytes: Vec<u8>) -> Option<Vec<String>> {
+    let bytes = undo_container_compression(bytes).ok()?;
+    let tpf = TPF::from_reader(&mut Cursor::new(bytes)).ok()?;
+
+    Some(
+        tpf.textures
+            .into_iter()
+            .map(|texture| texture.name.to_lowercase())
+            .collect(),
+    )
+}
+
+/// Approximate a FLVER material with a [StandardMaterial]: its albedo, normal and metallic maps
+/// are used as the base color, normal and metallic maps, and the rest of its parameters, which
+/// are in its MATBIN or MTD, are left at defaults.
+pub fn standard_material(
+    flver: &Flver,
+    material: &Material<LE>,
+    textures: &mut TextureResolver,
+    load_context: &mut LoadContext,
+) -> StandardMaterial {
+    let mut texture = |kinds: &[&str]| {
+        let texture = flver.material_textures(material).iter().find(|texture| {
+            let kind = flver
+                .texture_type(texture)
+                .unwrap_or_default()
+                .to_lowercase();
+            kinds.iter().any(|candidate| kind.contains(candidate))
+        })?;
+        let path = flver.texture_path(texture)?;
+
+        let resolved = textures.resolve(&path);
+        if resolved.is_none() {
+            debug!("Texture {} isn't in a mounted binder or the archives", path);
+        }
+
+        resolved
+    };
+
+    let base_color = texture(&["albedo", "diffuse"]);
+    let normal = texture(&["normal", "bumpmap"]);
+    let metallic = texture(&["metallic"]);
+
+    StandardMaterial {
+        base_color_texture: base_color.map(|path| load_context.load::<Image>(path)),
+        normal_map_texture: normal.map(|path| load_context.load::<Image>(path)),
+        // Normal maps follow DirectX's convention of green pointing down.
+        flip_normal_map_y: true,
+        metallic: match metallic.is_some() {
+            true => 1.0,
+            false => 0.0,
+        },
+        metallic_roughness_texture: metallic
+            .map(|path| load_context.load::<Image>(format!("{path}{METALLIC_ROUGHNESS_SUFFIX}"))),
+        perceptual_roughness: 0.7,
+        // Foliage, hair and the like are cut out with the albedo's alpha.
+        alpha_mode: AlphaMode::Mask(0.5),
+        ..StandardMaterial::default()
+    }
+}
@@ -1,8 +1,16 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    render::{mesh::skinning::SkinnedMesh, view::NoFrustumCulling},
+};
 
-use crate::flver::asset::{FlverAsset, FlverLoader};
+use crate::{
+    dummy::DummyPoints,
+    flver::asset::{FlverAsset, FlverLoader},
+    skeleton::{asset::SkeletonAsset, SkeletonJoints, SkeletonOverlay},
+};
 
 pub mod asset;
+mod material;
 
 pub struct FlverPlugin;
 
@@ -12,3 +20,87 @@ impl Plugin for FlverPlugin {
             .init_asset_loader::<FlverLoader>();
     }
 }
+
+/// A mesh of a FLVER, to tell what it is when it's picked.
+#[derive(Component)]
+pub struct FlverMesh {
+    pub flver: Handle<FlverAsset>,
+    pub index: usize,
+}
+
+/// Give `model` the meshes of a FLVER, skinned to a joint entity per bone of its skeleton, as
+/// children so that the model moves and hides as a whole.
+///
+/// Animated models aren't frustum culled, since their vertices can leave the bounds of the bind
+/// pose. Rigid models have no joints, since nothing poses them.
+pub fn spawn_model(
+    commands: &mut Commands,
+    model: Entity,
+    handle: &Handle<FlverAsset>,
+    flver: &FlverAsset,
+    skeleton: &SkeletonAsset,
+    animated: bool,
+) {
+    let bones = match flver.is_skinned() {
+        true => &skeleton.bones[..],
+        false => &[],
+    };
+    let joints = bones
+        .iter()
+        .map(|bone| {
+            commands
+                .spawn((
+                    TransformBundle::from_transform(bone.local),
+                    Name::new(bone.name.clone()),
+                ))
+                .id()
+        })
+        .collect::<Vec<_>>();
+    for (bone, joint) in bones.iter().zip(&joints) {
+        let parent = bone
+            .parent
+            .and_then(|parent| joints.get(parent))
+            .unwrap_or(&model);
+        commands.entity(*parent).add_child(*joint);
+    }
+
+    for (index, (mesh, material)) in flver.meshes().enumerate() {
+        let mut mesh = commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                ..PbrBundle::default()
+            },
+            FlverMesh {
+                flver: handle.clone(),
+                index,
+            },
+        ));
+        // Meshes are only kept in the render world, so their bounds can't be computed from them
+        // once they've been sent there.
+        if let Some(aabb) = flver.mesh_details(index).and_then(|details| details.aabb) {
+            mesh.insert(aabb);
+        }
+        if !joints.is_empty() {
+            mesh.insert(SkinnedMesh {
+                inverse_bindposes: flver.inverse_bindposes().clone(),
+                joints: joints.clone(),
+            });
+        }
+        if animated {
+            mesh.insert(NoFrustumCulling);
+        }
+
+        let mesh = mesh.id();
+        commands.entity(model).add_child(mesh);
+    }
+
+    let mut model = commands.entity(model);
+    model.insert((
+        SkeletonOverlay::new(flver.skeleton().clone()),
+        DummyPoints(handle.clone()),
+    ));
+    if !joints.is_empty() {
+        model.insert(SkeletonJoints(joints));
+    }
+}
@@ -0,0 +1,68 @@
+use byteorder::{ByteOrder, LE};
+
+/// DXGI formats of the single channel textures that can be decoded.
+const DXGI_FORMAT_R8_UNORM: u32 = 61;
+const DXGI_FORMAT_BC4_UNORM: u32 = 80;
+
+/// Decode the top mipmap of a single channel DDS texture, a BC4 or R8 one, into its width,
+/// height and one byte per pixel. Textures in other formats give [None].
+pub fn single_channel(dds: &[u8]) -> Option<(u32, u32, Vec<u8>)> {
+    if dds.get(..4)? != b"DDS " || dds.len() < 128 {
+        return None;
+    }
+
+    let height = LE::read_u32(&dds[12..]);
+    let width = LE::read_u32(&dds[16..]);
+    let (bc4, data) = match &dds[84..88] {
+        b"ATI1" | b"BC4U" => (true, &dds[128..]),
+        b"DX10" => match LE::read_u32(dds.get(128..132)?) {
+            DXGI_FORMAT_BC4_UNORM => (true, &dds[148..]),
+            DXGI_FORMAT_R8_UNORM => (false, &dds[148..]),
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let pixels = (width * height) as usize;
+    if !bc4 {
+        return Some((width, height, data.get(..pixels)?.to_vec()));
+    }
+
+    let blocks_wide = width.div_ceil(4) as usize;
+    let blocks_high = height.div_ceil(4) as usize;
+    let mut decoded = vec![0u8; pixels];
+    for block_y in 0..blocks_high {
+        for block_x in 0..blocks_wide {
+            let offset = (block_y * blocks_wide + block_x) * 8;
+            let block = decode_bc4_block(data.get(offset..offset + 8)?);
+
+            for (index, value) in block.into_iter().enumerate() {
+                let x = block_x * 4 + index % 4;
+                let y = block_y * 4 + index / 4;
+                if x < width as usize && y < height as usize {
+                    decoded[y * width as usize + x] = value;
+                }
+            }
+        }
+    }
+
+    Some((width, height, decoded))
+}
+
+/// The 16 values of a BC4 block, row by row: two endpoints and a 3 bit index per pixel into
+/// the palette between them.
+fn decode_bc4_block(block: &[u8]) -> [u8; 16] {
+    let (r0, r1) = (block[0] as u32, block[1] as u32);
+    let mut palette = [r0, r1, 0, 0, 0, 0, 0, 255];
+    match r0 > r1 {
+        true => (1..7).for_each(|i| palette[i + 1] = ((7 - i as u32) * r0 + i as u32 * r1) / 7),
+        false => (1..5).for_each(|i| palette[i + 1] = ((5 - i as u32) * r0 + i as u32 * r1) / 5),
+    }
+
+    let indices = block[2..8]
+        .iter()
+        .rev()
+        .fold(0u64, |bits, byte| bits << 8 | *byte as u64);
+
+    std::array::from_fn(|pixel| palette[(indices >> (pixel * 3) & 0b111) as usize] as u8)
+}
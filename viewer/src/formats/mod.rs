@@ -2,6 +2,7 @@ use bevy::app::{PluginGroup, PluginGroupBuilder};
 
 use crate::flver::FlverPlugin;
 
+mod dds;
 pub mod tpf;
 
 #[derive(Default)]
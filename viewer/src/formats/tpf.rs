@@ -5,6 +5,7 @@ use bevy::{
     prelude::*,
     render::{
         render_asset::RenderAssetUsages,
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
         texture::{
             CompressedImageFormats, ImageAddressMode, ImageFormat, ImageSampler,
             ImageSamplerDescriptor, ImageType, TextureError,
@@ -16,7 +17,11 @@ use format::tpf::TPF;
 use souls_vfs::undo_container_compression;
 use thiserror::Error;
 
-use crate::formats::TpfPlugin;
+use crate::formats::{dds, TpfPlugin};
+
+/// Suffix of the label of the image made for [StandardMaterial::metallic_roughness_texture] from
+/// a single channel texture, e.g. `am_m_1000_m.metallic_roughness`.
+pub const METALLIC_ROUGHNESS_SUFFIX: &str = ".metallic_roughness";
 
 #[derive(Asset, Deref, TypePath, Debug)]
 pub struct TPFAsset(TPF);
@@ -55,8 +60,24 @@ impl AssetLoader for TPFAssetLoader {
             let tpf = TPF::from_reader(&mut cursor)?;
             for texture in tpf.textures.iter() {
                 let bytes = texture.bytes(&mut cursor)?;
+                let sampler = ImageSampler::Descriptor(ImageSamplerDescriptor {
+                    label: Some(texture.name.clone()),
+                    address_mode_u: ImageAddressMode::Repeat,
+                    address_mode_v: ImageAddressMode::Repeat,
+                    ..Default::default()
+                });
 
-                load_context.labeled_asset_scope(texture.name.clone(), |_| {
+                // Labelled in lowercase, since FLVERs don't always spell texture names the same
+                // way as their TPFs.
+                let label = texture.name.to_lowercase();
+                if let Some((width, height, channel)) = dds::single_channel(&bytes) {
+                    load_context
+                        .labeled_asset_scope(format!("{label}{METALLIC_ROUGHNESS_SUFFIX}"), |_| {
+                            metallic_roughness(width, height, &channel, sampler.clone())
+                        });
+                }
+
+                load_context.labeled_asset_scope(label, |_| {
                     Image::from_buffer(
                         #[cfg(debug_assertions)]
                         texture.name.clone(),
@@ -64,13 +85,8 @@ impl AssetLoader for TPFAssetLoader {
                         ImageType::Format(ImageFormat::Dds),
                         CompressedImageFormats::BC,
                         false,
-                        ImageSampler::Descriptor(ImageSamplerDescriptor {
-                            label: Some(texture.name.clone()),
-                            address_mode_u: ImageAddressMode::Repeat,
-                            address_mode_v: ImageAddressMode::Repeat,
-                            ..Default::default()
-                        }),
-                        RenderAssetUsages::MAIN_WORLD,
+                        sampler,
+                        RenderAssetUsages::RENDER_WORLD,
                     )
                     .unwrap()
                 });
@@ -85,6 +101,31 @@ impl AssetLoader for TPFAssetLoader {
     }
 }
 
+/// A single channel texture, e.g. a metallic map, laid out the way [StandardMaterial] reads
+/// metallic and roughness: metallic in blue and roughness in green, where it's left at full so
+/// that the material's own roughness applies.
+fn metallic_roughness(width: u32, height: u32, channel: &[u8], sampler: ImageSampler) -> Image {
+    let data = channel
+        .iter()
+        .flat_map(|value| [0, u8::MAX, *value, u8::MAX])
+        .collect();
+
+    let mut image = Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8Unorm,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = sampler;
+
+    image
+}
+
 impl Plugin for TpfPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<TPFAsset>()
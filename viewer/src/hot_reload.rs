@@ -0,0 +1,164 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use bevy::{asset::AssetPath, prelude::*};
+
+use crate::{flver::asset::FlverAsset, formats::tpf::TPFAsset};
+
+/// How often watched files are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Reloads models and textures in place when the files they were read from change on disk: the
+/// files dropped onto the viewer, and the files of an overlay directory laid over the archives.
+///
+/// Files are polled for their modification time rather than watched by the OS.
+pub struct HotReloadPlugin {
+    /// Directory of files read in place of the archives', like a mod's.
+    pub overlay: Option<PathBuf>,
+}
+
+impl Plugin for HotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        let mut watcher = Watcher {
+            overlay: self.overlay.clone(),
+            modified: HashMap::new(),
+            timer: Timer::new(POLL_INTERVAL, TimerMode::Repeating),
+        };
+        // Files already in the overlay when the viewer starts are read as they are.
+        watcher.changed_files();
+
+        app.insert_resource(watcher)
+            .add_event::<WatchFile>()
+            .add_event::<FileChanged>()
+            .add_systems(
+                Update,
+                (watch_files, poll_files, reload_overlay_files).chain(),
+            );
+    }
+}
+
+/// Send [FileChanged] when the file at this path on disk changes.
+#[derive(Event)]
+pub struct WatchFile(pub PathBuf);
+
+/// A watched file, or a file in the overlay, was written since it was last checked.
+#[derive(Event)]
+pub struct FileChanged(pub PathBuf);
+
+#[derive(Resource)]
+struct Watcher {
+    overlay: Option<PathBuf>,
+
+    /// When each watched file was last written, as of the last time it was checked.
+    modified: HashMap<PathBuf, Option<SystemTime>>,
+    timer: Timer,
+}
+
+impl Watcher {
+    /// The watched files and files of the overlay that were written since they were last checked,
+    /// including files added to the overlay.
+    fn changed_files(&mut self) -> Vec<PathBuf> {
+        let mut paths = self.modified.keys().cloned().collect::<HashSet<_>>();
+        if let Some(overlay) = &self.overlay {
+            list_files(overlay, &mut paths);
+        }
+
+        paths
+            .into_iter()
+            .filter(|path| {
+                let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+                let previous = self.modified.insert(path.clone(), modified);
+
+                modified.is_some() && previous.map_or(false, |previous| previous != modified)
+            })
+            .collect()
+    }
+}
+
+/// Add every file under `directory` to `paths`.
+fn list_files(directory: &Path, paths: &mut HashSet<PathBuf>) {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => list_files(&path, paths),
+            Ok(_) => {
+                paths.insert(path);
+            }
+            Err(_) => {}
+        }
+    }
+}
+
+fn watch_files(mut events: EventReader<WatchFile>, mut watcher: ResMut<Watcher>) {
+    for WatchFile(path) in events.read() {
+        let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        watcher.modified.insert(path.clone(), modified);
+    }
+}
+
+fn poll_files(
+    time: Res<Time>,
+    mut watcher: ResMut<Watcher>,
+    mut changed: EventWriter<FileChanged>,
+) {
+    if !watcher.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    for path in watcher.changed_files() {
+        info!("{} changed", path.display());
+        changed.send(FileChanged(path));
+    }
+}
+
+/// Reload the models and textures read from a changed file of the overlay, or from a binder
+/// that is.
+fn reload_overlay_files(
+    mut events: EventReader<FileChanged>,
+    watcher: Res<Watcher>,
+    flvers: Res<Assets<FlverAsset>>,
+    tpfs: Res<Assets<TPFAsset>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(overlay) = &watcher.overlay else {
+        return;
+    };
+
+    // The paths of the files in the archives, e.g. `/chr/c3660.chrbnd.dcx`.
+    let changed = events
+        .read()
+        .filter_map(|FileChanged(path)| path.strip_prefix(overlay).ok())
+        .map(|path| format!("/{}", path.to_string_lossy().replace('\\', "/")).to_lowercase())
+        .collect::<Vec<_>>();
+    if changed.is_empty() {
+        return;
+    }
+
+    let loaded = flvers
+        .ids()
+        .filter_map(|id| asset_server.get_path(id))
+        .chain(tpfs.ids().filter_map(|id| asset_server.get_path(id)))
+        .filter(|path| path.label().is_none() && path.source().as_str().is_none())
+        .map(AssetPath::into_owned)
+        .collect::<HashSet<_>>();
+
+    for path in loaded {
+        let asset_path = path.path().to_string_lossy().to_lowercase();
+        let is_changed = changed
+            .iter()
+            .any(|file| asset_path == *file || asset_path.starts_with(&format!("{file}/")));
+
+        if is_changed {
+            info!("Reloading {}", path);
+            asset_server.reload(path);
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::{collections::HashSet, error::Error, io::Cursor};
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt, BoxedFuture, LoadContext},
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_asset::RenderAssetUsages,
+    },
+};
+use format::{
+    bnd4::BND4,
+    hkx::{HkaiNavMesh, HkxCompendium, HkxTagfile},
+    hkxbhd::MapCollision,
+    nva::Nva,
+    nvm::Nvm,
+};
+use souls_vfs::undo_container_compression;
+
+/// Geometry drawn over a map's models rather than as part of them: its navmeshes or its
+/// collision.
+#[derive(Asset, Debug, TypePath)]
+pub struct LayerAsset {
+    pub meshes: Vec<LayerMesh>,
+}
+
+#[derive(Debug)]
+pub struct LayerMesh {
+    /// The navmesh or collision cell the mesh was read from, e.g. `h60_42_36_00_423600`.
+    pub name: String,
+
+    /// Where the mesh is placed within its map.
+    pub transform: Transform,
+
+    /// The mesh's triangles, and a line list of their edges to draw it as a wireframe.
+    pub surface: Handle<Mesh>,
+    pub edges: Handle<Mesh>,
+}
+
+/// Loads the navmeshes of an Elden Ring map from its `.nvmhktbnd`, placed by the NVA next to it,
+/// or the navmesh of a Dark Souls `.nvm`.
+#[derive(Default)]
+pub struct NavmeshLoader;
+
+impl AssetLoader for NavmeshLoader {
+    type Asset = LayerAsset;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<LayerAsset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            let bytes = undo_container_compression(bytes)?;
+
+            let path = load_context.path().to_string_lossy().into_owned();
+            let Some(stem) = path
+                .strip_suffix(".nvmhktbnd.dcx")
+                .or_else(|| path.strip_suffix(".nvmhktbnd"))
+            else {
+                let nvm = Nvm::from_reader(&mut Cursor::new(bytes))?;
+                let triangles = nvm.triangles.iter().map(|triangle| triangle.vertices);
+                let name = path.rsplit('/').next().unwrap_or_default().to_string();
+                let mesh = add_layer_mesh(
+                    load_context,
+                    0,
+                    name,
+                    Transform::IDENTITY,
+                    nvm.vertices.clone(),
+                    triangles.collect(),
+                );
+
+                return Ok(LayerAsset { meshes: vec![mesh] });
+            };
+
+            let placements = read_placements(load_context, &format!("{stem}.nva.dcx")).await;
+            let bnd = BND4::parse(bytes)?;
+            let file_name = |path: &str| {
+                let path = BND4::normalize_path(path);
+                path.rsplit('/').next().unwrap_or_default().to_string()
+            };
+
+            // Like map collision, the navmeshes can leave out their types for a compendium.
+            let compendium = bnd
+                .files
+                .iter()
+                .find(|file| file_name(&file.path).contains(".compendium"))
+                .map(|file| {
+                    let bytes = undo_container_compression(bnd.file_bytes(file).to_vec())?;
+                    Ok::<_, Self::Error>(HkxCompendium::from_bytes(&bytes)?)
+                })
+                .transpose()?;
+
+            let mut meshes = Vec::new();
+            for file in &bnd.files {
+                let name = file_name(&file.path);
+                let Some(stem) = name
+                    .strip_suffix(".hkx.dcx")
+                    .or_else(|| name.strip_suffix(".hkx"))
+                else {
+                    continue;
+                };
+
+                let bytes = undo_container_compression(bnd.file_bytes(file).to_vec())?;
+                let navmeshes = HkxTagfile::from_bytes_with_compendium(&bytes, compendium.as_ref())
+                    .and_then(|tagfile| HkaiNavMesh::from_tagfile(&tagfile));
+                let navmeshes = match navmeshes {
+                    Ok(navmeshes) => navmeshes,
+                    Err(e) => {
+                        warn!("Skipping navmesh {}: {}", name, e);
+                        continue;
+                    }
+                };
+
+                // Navmeshes are named after the ID of their model, which the NVA places.
+                let model_id = stem.rsplit('_').next().and_then(|id| id.parse().ok());
+                let mut transforms = placements
+                    .iter()
+                    .filter(|(id, _)| Some(*id) == model_id)
+                    .map(|(_, transform)| *transform)
+                    .collect::<Vec<_>>();
+                if transforms.is_empty() {
+                    transforms.push(Transform::IDENTITY);
+                }
+
+                for navmesh in &navmeshes {
+                    let triangles = navmesh
+                        .triangles()
+                        .into_iter()
+                        .map(|triangle| triangle.map(|index| index as u32))
+                        .collect::<Vec<_>>();
+
+                    for transform in &transforms {
+                        meshes.push(add_layer_mesh(
+                            load_context,
+                            meshes.len(),
+                            stem.to_string(),
+                            *transform,
+                            navmesh.vertices.clone(),
+                            triangles.clone(),
+                        ));
+                    }
+                }
+            }
+
+            Ok(LayerAsset { meshes })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["nvmhktbnd", "nvmhktbnd.dcx", "nvm"]
+    }
+}
+
+/// The model ID and placement of each navmesh in an NVA, or none if the map doesn't have one.
+async fn read_placements(load_context: &mut LoadContext<'_>, path: &str) -> Vec<(i32, Transform)> {
+    let nva = match load_context.read_asset_bytes(path.to_string()).await {
+        Ok(bytes) => undo_container_compression(bytes)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| Nva::from_reader(&mut Cursor::new(bytes)).map_err(|e| e.to_string()))
+            .and_then(|nva| nva.navmeshes().map_err(|e| e.to_string())),
+        Err(e) => Err(e.to_string()),
+    };
+
+    match nva {
+        Ok(navmeshes) => navmeshes
+            .into_iter()
+            .map(|navmesh| {
+                let [rx, ry, rz] = navmesh.rotation.map(f32::to_radians);
+                let transform = Transform {
+                    translation: Vec3::from_array(navmesh.position),
+                    // Navmeshes rotate like the parts of an MSB.
+                    rotation: Quat::from_euler(EulerRot::YZX, ry, rz, rx),
+                    scale: Vec3::from_array(navmesh.scale),
+                };
+
+                (navmesh.model_id, transform)
+            })
+            .collect(),
+        Err(e) => {
+            warn!("Placing navmeshes without {}: {}", path, e);
+            Vec::new()
+        }
+    }
+}
+
+/// Loads the collision of a map from its `.hkxbhd` and the `.hkxbdt` next to it, a mesh per cell.
+#[derive(Default)]
+pub struct CollisionLoader;
+
+impl AssetLoader for CollisionLoader {
+    type Asset = LayerAsset;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _: &'a (),
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<LayerAsset, Self::Error>> {
+        Box::pin(async move {
+            let mut bhd = Vec::new();
+            reader.read_to_end(&mut bhd).await?;
+
+            let bdt_path = load_context.path().with_extension("hkxbdt");
+            let bdt = load_context.read_asset_bytes(bdt_path).await?;
+            let collision = MapCollision::from_bytes(&bhd, bdt)?;
+
+            let meshes = collision
+                .cells
+                .into_iter()
+                .enumerate()
+                .map(|(index, cell)| {
+                    let mut vertices = Vec::new();
+                    let mut triangles = Vec::new();
+                    for mesh in cell.meshes {
+                        let offset = vertices.len() as u32;
+                        triangles.extend(
+                            mesh.indices
+                                .chunks_exact(3)
+                                .map(|triangle| [0, 1, 2].map(|i| triangle[i] + offset)),
+                        );
+                        vertices.extend(mesh.vertices);
+                    }
+
+                    add_layer_mesh(
+                        load_context,
+                        index,
+                        cell.name,
+                        Transform::IDENTITY,
+                        vertices,
+                        triangles,
+                    )
+                })
+                .collect();
+
+            Ok(LayerAsset { meshes })
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["hkxbhd"]
+    }
+}
+
+/// Add the surface and edges of a mesh as labeled assets, numbered by `index`.
+fn add_layer_mesh(
+    load_context: &mut LoadContext,
+    index: usize,
+    name: String,
+    transform: Transform,
+    vertices: Vec<[f32; 3]>,
+    triangles: Vec<[u32; 3]>,
+) -> LayerMesh {
+    // Each edge shared by two triangles is only drawn once.
+    let edges = triangles
+        .iter()
+        .flat_map(|[a, b, c]| [(*a, *b), (*b, *c), (*c, *a)])
+        .map(|(a, b)| (a.min(b), a.max(b)))
+        .collect::<HashSet<_>>();
+
+    let surface = Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertices.clone())
+    .with_inserted_indices(Indices::U32(triangles.into_iter().flatten().collect()));
+    let edges = Mesh::new(PrimitiveTopology::LineList, RenderAssetUsages::RENDER_WORLD)
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, vertices)
+        .with_inserted_indices(Indices::U32(
+            edges.into_iter().flat_map(|(a, b)| [a, b]).collect(),
+        ));
+
+    LayerMesh {
+        surface: load_context.add_labeled_asset(format!("surface{index}"), surface),
+        edges: load_context.add_labeled_asset(format!("edges{index}"), edges),
+        name,
+        transform,
+    }
+}
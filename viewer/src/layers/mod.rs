@@ -0,0 +1,231 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    layers::asset::{CollisionLoader, LayerAsset, NavmeshLoader},
+    map::Map,
+};
+
+pub mod asset;
+
+/// Toggleable overlays of the navmeshes and collision of the loaded maps, drawn as translucent
+/// surfaces with wireframe edges over the models.
+pub struct LayersPlugin;
+
+impl Plugin for LayersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<LayerAsset>()
+            .init_asset_loader::<NavmeshLoader>()
+            .init_asset_loader::<CollisionLoader>()
+            .init_resource::<Layers>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (layers_window, load_layers, spawn_layers, show_layers).chain(),
+            );
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Layer {
+    Navmesh,
+    Collision,
+}
+
+impl Layer {
+    const ALL: [Self; 2] = [Self::Navmesh, Self::Collision];
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Navmesh => "Navmesh",
+            Self::Collision => "Collision",
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Navmesh => Color::rgb(0.2, 0.9, 0.3),
+            Self::Collision => Color::rgb(1.0, 0.5, 0.1),
+        }
+    }
+
+    /// The asset path of the layer of a map, e.g. `m60_42_36_00`.
+    fn path(self, map: &str) -> Option<String> {
+        let area = map.get(..3)?;
+        match self {
+            Self::Navmesh => Some(format!("/map/{area}/{map}/{map}.nvmhktbnd.dcx")),
+            Self::Collision => Some(format!("/map/{area}/{map}/h{}.hkxbhd", map.get(1..)?)),
+        }
+    }
+}
+
+/// Which layers are drawn. Each is only read for a map once it's first shown.
+#[derive(Resource, Default)]
+pub struct Layers {
+    pub navmesh: bool,
+    pub collision: bool,
+}
+
+impl Layers {
+    fn shown(&self, layer: Layer) -> bool {
+        match layer {
+            Layer::Navmesh => self.navmesh,
+            Layer::Collision => self.collision,
+        }
+    }
+
+    fn set_shown(&mut self, layer: Layer, shown: bool) {
+        match layer {
+            Layer::Navmesh => self.navmesh = shown,
+            Layer::Collision => self.collision = shown,
+        }
+    }
+}
+
+/// The surface and edge materials of each of [Layer::ALL].
+#[derive(Resource)]
+struct LayerMaterials([(Handle<StandardMaterial>, Handle<StandardMaterial>); 2]);
+
+/// The layer of a map, whose meshes are spawned as its children once it's read.
+#[derive(Component)]
+struct MapLayer {
+    layer: Layer,
+    asset: Handle<LayerAsset>,
+    spawned: bool,
+}
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<StandardMaterial>>) {
+    let materials = Layer::ALL.map(|layer| {
+        // Biased towards the camera so that layers aren't hidden by the models they trace.
+        let surface = StandardMaterial {
+            base_color: layer.color().with_a(0.25),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            depth_bias: 4.0,
+            ..default()
+        };
+        let edges = StandardMaterial {
+            base_color: layer.color(),
+            unlit: true,
+            depth_bias: 8.0,
+            ..default()
+        };
+
+        (materials.add(surface), materials.add(edges))
+    });
+
+    commands.insert_resource(LayerMaterials(materials));
+}
+
+fn layers_window(mut contexts: EguiContexts, mut layers: ResMut<Layers>) {
+    egui::Window::new("Layers").show(contexts.ctx_mut(), |ui| {
+        for layer in Layer::ALL {
+            // Only flag the resource as changed when a box is ticked, for show_layers.
+            let mut shown = layers.shown(layer);
+            if ui.checkbox(&mut shown, layer.label()).changed() {
+                layers.set_shown(layer, shown);
+            }
+        }
+    });
+}
+
+fn load_layers(
+    mut commands: Commands,
+    layers: Res<Layers>,
+    maps: Query<(Entity, &Name, Option<&Children>), With<Map>>,
+    map_layers: Query<&MapLayer>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, name, children) in &maps {
+        for layer in Layer::ALL {
+            if !layers.shown(layer) {
+                continue;
+            }
+
+            let loaded = children.is_some_and(|children| {
+                map_layers
+                    .iter_many(children.iter())
+                    .any(|map_layer| map_layer.layer == layer)
+            });
+            let Some(path) = layer.path(name.as_str()).filter(|_| !loaded) else {
+                continue;
+            };
+
+            let map_layer = commands
+                .spawn((
+                    SpatialBundle::default(),
+                    Name::new(layer.label()),
+                    MapLayer {
+                        layer,
+                        asset: asset_server.load(path),
+                        spawned: false,
+                    },
+                ))
+                .id();
+            commands.entity(entity).add_child(map_layer);
+        }
+    }
+}
+
+fn spawn_layers(
+    mut commands: Commands,
+    mut map_layers: Query<(Entity, &mut MapLayer)>,
+    assets: Res<Assets<LayerAsset>>,
+    materials: Res<LayerMaterials>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, mut map_layer) in &mut map_layers {
+        if map_layer.spawned {
+            continue;
+        }
+
+        let Some(asset) = assets.get(&map_layer.asset) else {
+            if asset_server.get_load_state(&map_layer.asset) == Some(LoadState::Failed) {
+                warn!("Could not read the {:?} layer", map_layer.layer);
+                map_layer.spawned = true;
+            }
+
+            continue;
+        };
+
+        let (surface_material, edge_material) = &materials.0[map_layer.layer as usize];
+        for mesh in &asset.meshes {
+            commands.entity(entity).with_children(|parent| {
+                parent
+                    .spawn((
+                        PbrBundle {
+                            mesh: mesh.surface.clone(),
+                            material: surface_material.clone(),
+                            transform: mesh.transform,
+                            ..default()
+                        },
+                        Name::new(mesh.name.clone()),
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(PbrBundle {
+                            mesh: mesh.edges.clone(),
+                            material: edge_material.clone(),
+                            ..default()
+                        });
+                    });
+            });
+        }
+
+        map_layer.spawned = true;
+    }
+}
+
+fn show_layers(layers: Res<Layers>, mut map_layers: Query<(&MapLayer, &mut Visibility)>) {
+    if !layers.is_changed() {
+        return;
+    }
+
+    for (map_layer, mut visibility) in &mut map_layers {
+        *visibility = match layers.shown(map_layer.layer) {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+    }
+}
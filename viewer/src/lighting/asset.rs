@@ -0,0 +1,74 @@
+use std::{error::Error, io::Cursor};
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt, BoxedFuture, LoadContext},
+    prelude::{Deref, TypePath},
+};
+use format::{btl::Btl, gparam::Gparam};
+use souls_vfs::undo_container_compression;
+
+/// The point, spot and directional lights of a map.
+#[derive(Asset, Deref, Debug, TypePath)]
+pub struct BtlAsset(Btl);
+
+#[derive(Default)]
+pub struct BtlLoader;
+
+impl AssetLoader for BtlLoader {
+    type Asset = BtlAsset;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _: &'a (),
+        _: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<BtlAsset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let bytes = undo_container_compression(bytes)?;
+
+            Ok(BtlAsset(Btl::from_reader(&mut Cursor::new(bytes))?))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["btl", "btl.dcx"]
+    }
+}
+
+/// The draw settings of an area, e.g. its sun, ambient light and tone mapping.
+#[derive(Asset, Deref, Debug, TypePath)]
+pub struct GparamAsset(Gparam);
+
+#[derive(Default)]
+pub struct GparamLoader;
+
+impl AssetLoader for GparamLoader {
+    type Asset = GparamAsset;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _: &'a (),
+        _: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<GparamAsset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let bytes = undo_container_compression(bytes)?;
+
+            Ok(GparamAsset(Gparam::from_reader(&mut Cursor::new(bytes))?))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["gparam", "gparam.dcx"]
+    }
+}
@@ -0,0 +1,268 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{asset::LoadState, prelude::*, render::camera::Exposure};
+use format::{
+    btl::{BtlLight, BtlLightType},
+    gparam::{Gparam, GparamValue},
+};
+
+use crate::{
+    lighting::asset::{BtlAsset, BtlLoader, GparamAsset, GparamLoader},
+    map::Map,
+    vfs::VfsAssetRepository,
+};
+
+pub mod asset;
+
+/// How many BTLs a map is looked for, e.g. `m60_42_36_00_0000.btl.dcx` to `_0003`.
+const BTL_VARIANTS: u32 = 4;
+
+/// Scale from the unitless power of BTL and GPARAM lights to Bevy's physical units. Picked to
+/// look right at the camera's default exposure rather than measured.
+const LUMENS_PER_POWER: f32 = 100_000.0;
+const LUX_PER_POWER: f32 = light_consts::lux::OVERCAST_DAY;
+const AMBIENT_PER_POWER: f32 = 80.0;
+
+/// Lights loaded maps like the game does: the point and spot lights of their BTLs are spawned
+/// into the map, and the sun, ambient light and exposure are set from the GPARAM of its area.
+///
+/// GPARAM params are matched by name loosely, since they're named differently between games.
+pub struct LightingPlugin;
+
+impl Plugin for LightingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<BtlAsset>()
+            .init_asset_loader::<BtlLoader>()
+            .init_asset::<GparamAsset>()
+            .init_asset_loader::<GparamLoader>()
+            .add_systems(Update, (load_lighting, spawn_lights, apply_gparams).chain());
+    }
+}
+
+/// The directional light that a map's GPARAM sets as the sun.
+#[derive(Component)]
+pub struct SunLight;
+
+/// The light files of a map, which are let go of once they're applied.
+#[derive(Component)]
+struct MapLighting {
+    btls: Vec<Handle<BtlAsset>>,
+    gparam: Option<Handle<GparamAsset>>,
+}
+
+/// The asset paths of the BTLs of a map that are in the archives, e.g. `m60_42_36_00`.
+fn btl_paths(map: &str, vfs: &VfsAssetRepository) -> Vec<String> {
+    let Some(area) = map.get(..3) else {
+        return Vec::new();
+    };
+
+    (0..BTL_VARIANTS)
+        .map(|variant| format!("/map/{area}/{map}/{map}_{variant:04}.btl.dcx"))
+        .filter(|path| vfs.contains(path))
+        .collect()
+}
+
+/// The asset path of the GPARAM of a map's area, e.g. `/param/drawparam/m60_00_0000.gparam.dcx`
+/// for the tiles of `m60`.
+fn gparam_path(map: &str, vfs: &VfsAssetRepository) -> Option<String> {
+    let area = map.get(..3)?;
+    let block = map.get(4..6)?;
+
+    [
+        format!("/param/drawparam/{map}_0000.gparam.dcx"),
+        format!("/param/drawparam/{area}_{block}_0000.gparam.dcx"),
+    ]
+    .into_iter()
+    .find(|path| vfs.contains(path))
+}
+
+fn load_lighting(
+    mut commands: Commands,
+    maps: Query<(Entity, &Name), Added<Map>>,
+    vfs: Res<VfsAssetRepository>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, name) in &maps {
+        let btls = btl_paths(name.as_str(), &vfs);
+        let gparam = gparam_path(name.as_str(), &vfs);
+        if btls.is_empty() && gparam.is_none() {
+            info!("{} has no BTL or GPARAM, its lighting won't change", name);
+            continue;
+        }
+
+        let lighting = commands
+            .spawn((
+                SpatialBundle::default(),
+                Name::new("Lights"),
+                MapLighting {
+                    btls: btls
+                        .into_iter()
+                        .map(|path| asset_server.load(path))
+                        .collect(),
+                    gparam: gparam.map(|path| asset_server.load(path)),
+                },
+            ))
+            .id();
+        commands.entity(entity).add_child(lighting);
+    }
+}
+
+fn spawn_lights(
+    mut commands: Commands,
+    mut lightings: Query<(Entity, &mut MapLighting)>,
+    btls: Res<Assets<BtlAsset>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, mut lighting) in &mut lightings {
+        lighting.btls.retain(|handle| {
+            let Some(btl) = btls.get(handle) else {
+                if asset_server.get_load_state(handle) == Some(LoadState::Failed) {
+                    warn!("Could not read BTL {:?}", handle.path());
+                    return false;
+                }
+
+                return true;
+            };
+
+            commands.entity(entity).with_children(|parent| {
+                for light in &btl.lights {
+                    spawn_light(parent, light);
+                }
+            });
+
+            false
+        });
+    }
+}
+
+fn spawn_light(parent: &mut ChildBuilder, light: &BtlLight) {
+    let [r, g, b, _] = light.diffuse_color;
+    let color = Color::rgb_u8(r, g, b);
+    let [rx, ry, rz] = light.rotation.map(f32::to_radians);
+    let transform = Transform {
+        translation: Vec3::from_array(light.position),
+        // Rotated like the parts of a map.
+        rotation: Quat::from_euler(EulerRot::YZX, ry, rz, rx),
+        ..default()
+    };
+    let name = Name::new(light.name.clone());
+
+    match light.light_type {
+        BtlLightType::Point => parent.spawn((
+            PointLightBundle {
+                point_light: PointLight {
+                    color,
+                    intensity: light.diffuse_power * LUMENS_PER_POWER,
+                    range: light.radius,
+                    shadows_enabled: light.cast_shadows,
+                    ..default()
+                },
+                transform,
+                ..default()
+            },
+            name,
+        )),
+        BtlLightType::Spot => parent.spawn((
+            SpotLightBundle {
+                spot_light: SpotLight {
+                    color,
+                    intensity: light.diffuse_power * LUMENS_PER_POWER,
+                    range: light.radius,
+                    shadows_enabled: light.cast_shadows,
+                    outer_angle: (light.cone_angle / 2.0).to_radians().clamp(0.0, FRAC_PI_2),
+                    ..default()
+                },
+                transform,
+                ..default()
+            },
+            name,
+        )),
+        BtlLightType::Directional => parent.spawn((
+            DirectionalLightBundle {
+                directional_light: DirectionalLight {
+                    color,
+                    illuminance: light.diffuse_power * LUX_PER_POWER,
+                    shadows_enabled: light.cast_shadows,
+                    ..default()
+                },
+                transform,
+                ..default()
+            },
+            name,
+        )),
+    };
+}
+
+/// The first value of the first param in a group starting with `group` whose name contains each
+/// of `words`, ignoring case.
+fn find_value<'a>(gparam: &'a Gparam, group: &str, words: &[&str]) -> Option<&'a GparamValue> {
+    gparam
+        .groups
+        .iter()
+        .filter(|candidate| candidate.name.starts_with(group))
+        .flat_map(|candidate| &candidate.params)
+        .find(|param| {
+            let name = param.name.to_lowercase();
+            words.iter().all(|word| name.contains(word))
+        })?
+        .values
+        .first()
+}
+
+/// A color and the intensity it's scaled by, which RGBA floats keep in their alpha.
+fn color(value: &GparamValue) -> Option<(Color, f32)> {
+    match *value {
+        GparamValue::Float3([r, g, b]) => Some((Color::rgb(r, g, b), 1.0)),
+        GparamValue::Float4([r, g, b, a]) => Some((Color::rgb(r, g, b), a)),
+        GparamValue::Byte4([r, g, b, _]) => Some((Color::rgb_u8(r, g, b), 1.0)),
+        _ => None,
+    }
+}
+
+fn apply_gparams(
+    mut lightings: Query<&mut MapLighting>,
+    mut suns: Query<&mut DirectionalLight, With<SunLight>>,
+    mut exposures: Query<&mut Exposure, With<Camera3d>>,
+    mut ambient: ResMut<AmbientLight>,
+    gparams: Res<Assets<GparamAsset>>,
+    asset_server: Res<AssetServer>,
+) {
+    for mut lighting in &mut lightings {
+        let Some(handle) = lighting.gparam.clone() else {
+            continue;
+        };
+        let Some(gparam) = gparams.get(&handle) else {
+            if asset_server.get_load_state(&handle) == Some(LoadState::Failed) {
+                warn!("Could not read GPARAM {:?}", handle.path());
+                lighting.gparam = None;
+            }
+
+            continue;
+        };
+        lighting.gparam = None;
+
+        if let Some((color, intensity)) =
+            find_value(gparam, "LightSet", &["dirlight", "diff"]).and_then(color)
+        {
+            for mut sun in &mut suns {
+                sun.color = color;
+                sun.illuminance = intensity * LUX_PER_POWER;
+            }
+        }
+
+        if let Some((color, intensity)) =
+            find_value(gparam, "LightSet", &["amb", "upper"]).and_then(color)
+        {
+            ambient.color = color;
+            ambient.brightness = intensity * AMBIENT_PER_POWER;
+        }
+
+        // Compensation in stops, brightening the image as it grows.
+        if let Some(GparamValue::Float(compensation)) = find_value(gparam, "ToneMap", &["exposure"])
+        {
+            for mut exposure in &mut exposures {
+                exposure.ev100 = Exposure::EV100_BLENDER - compensation;
+            }
+        }
+    }
+}
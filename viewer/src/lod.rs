@@ -0,0 +1,127 @@
+use bevy::{prelude::*, render::primitives::Aabb};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::flver::{
+    asset::{FlverAsset, MAX_LOD},
+    FlverMesh,
+};
+
+/// Picks the level of detail FLVER meshes are drawn at, either one for every mesh or by how far
+/// each mesh is from the camera.
+pub struct LodPlugin;
+
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LodSelection>()
+            .add_systems(Update, (lod_window, apply_lods).chain());
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub enum LodSelection {
+    Fixed(u8),
+
+    /// Drawn at LOD1 from the first distance from the camera, and at LOD2 from the second.
+    Distance([f32; MAX_LOD as usize]),
+}
+
+impl Default for LodSelection {
+    fn default() -> Self {
+        Self::Fixed(0)
+    }
+}
+
+impl LodSelection {
+    const DEFAULT_DISTANCES: [f32; MAX_LOD as usize] = [50.0, 150.0];
+
+    fn label(self) -> String {
+        match self {
+            Self::Fixed(lod) => format!("LOD{lod}"),
+            Self::Distance(_) => "By distance".to_string(),
+        }
+    }
+
+    /// The level of detail of a mesh `distance` from the camera.
+    fn lod(self, distance: f32) -> u8 {
+        match self {
+            Self::Fixed(lod) => lod,
+            Self::Distance(distances) => distances
+                .iter()
+                .take_while(|threshold| distance >= **threshold)
+                .count() as u8,
+        }
+    }
+}
+
+fn lod_window(mut contexts: EguiContexts, mut selection: ResMut<LodSelection>) {
+    egui::Window::new("Level of detail").show(contexts.ctx_mut(), |ui| {
+        let mut selected = *selection;
+        let choices = (0..=MAX_LOD)
+            .map(LodSelection::Fixed)
+            .chain([LodSelection::Distance(LodSelection::DEFAULT_DISTANCES)]);
+        ui.horizontal(|ui| {
+            for choice in choices {
+                let checked = match (selected, choice) {
+                    (LodSelection::Distance(_), LodSelection::Distance(_)) => true,
+                    _ => selected == choice,
+                };
+                if ui.radio(checked, choice.label()).clicked() && !checked {
+                    selected = choice;
+                }
+            }
+        });
+
+        if let LodSelection::Distance(distances) = &mut selected {
+            for (lod, distance) in distances.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("LOD{} from", lod + 1));
+                    ui.add(
+                        egui::DragValue::new(distance)
+                            .clamp_range(0.0..=f32::MAX)
+                            .suffix(" m"),
+                    );
+                });
+            }
+        }
+
+        // Only flag the resource as changed when the selection is edited.
+        if selected != *selection {
+            *selection = selected;
+        }
+    });
+}
+
+fn apply_lods(
+    selection: Res<LodSelection>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    mut meshes: Query<(Entity, Ref<FlverMesh>, &mut Handle<Mesh>, &GlobalTransform)>,
+    bounds: Query<&Aabb>,
+    flvers: Res<Assets<FlverAsset>>,
+) {
+    let camera = cameras.get_single().ok().map(GlobalTransform::translation);
+    let by_distance = matches!(*selection, LodSelection::Distance(_));
+
+    for (entity, flver_mesh, mut mesh, transform) in &mut meshes {
+        // Fixed levels only change when they're picked, or for meshes spawned since.
+        if !by_distance && !selection.is_changed() && !flver_mesh.is_added() {
+            continue;
+        }
+
+        let center = bounds
+            .get(entity)
+            .map_or(Vec3::ZERO, |aabb| aabb.center.into());
+        let distance = camera.map_or(0.0, |camera| {
+            camera.distance(transform.transform_point(center))
+        });
+        let Some(lod_mesh) = flvers
+            .get(&flver_mesh.flver)
+            .and_then(|flver| flver.mesh_lod(flver_mesh.index, selection.lod(distance)))
+        else {
+            continue;
+        };
+
+        if *mesh != *lod_mesh {
+            *mesh = lod_mesh.clone();
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use std::{
+    collections::HashSet,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::io::AssetSource, prelude::*};
+use format::bnd4::BND4;
+use souls_vfs::undo_container_compression;
+
+pub use self::reader::LooseFiles;
+use crate::{
+    browser::PreviewTpf,
+    hot_reload::{FileChanged, WatchFile},
+    map::Map,
+    AssetCollection,
+};
+
+mod reader;
+
+/// The asset source dropped files are read from, e.g. `loose://c3660.chrbnd/c3660.flver`.
+pub const LOOSE_SOURCE: &str = "loose";
+
+/// Loads files dropped onto the viewer, to look at files that aren't in the game's archives.
+/// Binders are extracted, and the models, textures and maps in them are loaded.
+///
+/// Must be added before the [AssetPlugin], which reads the asset sources when it's built.
+pub struct LooseFilesPlugin;
+
+impl Plugin for LooseFilesPlugin {
+    fn build(&self, app: &mut App) {
+        let files = LooseFiles::default();
+
+        app.insert_resource(files.clone())
+            .init_resource::<DroppedFiles>()
+            .register_asset_source(
+                LOOSE_SOURCE,
+                AssetSource::build().with_reader(move || Box::new(files.clone())),
+            )
+            .add_systems(
+                Update,
+                (
+                    load_dropped_files,
+                    watch_dropped_files,
+                    reload_dropped_files,
+                ),
+            );
+    }
+}
+
+/// The files dropped onto the viewer, on disk.
+#[derive(Resource, Default)]
+struct DroppedFiles(HashSet<PathBuf>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LooseKind {
+    Flver,
+    Tpf,
+    Msb,
+}
+
+impl LooseKind {
+    /// The kind of file `bytes` are by their magic, and the extension its loader is picked by.
+    fn of(bytes: &[u8]) -> Option<(Self, &'static str)> {
+        match bytes.get(..4)? {
+            b"FLVE" => Some((Self::Flver, "flver")),
+            b"TPF\0" => Some((Self::Tpf, "tpf")),
+            b"MSB " => Some((Self::Msb, "msb")),
+            _ => None,
+        }
+    }
+}
+
+/// Store the file at `path` decompressed, or the files in it if it's a binder, and list the ones
+/// that can be loaded.
+fn extract(
+    files: &LooseFiles,
+    path: &str,
+    bytes: Vec<u8>,
+    extracted: &mut Vec<(LooseKind, String)>,
+) -> Result<(), Box<dyn Error>> {
+    let bytes = undo_container_compression(bytes)?;
+    let path = path.strip_suffix(".dcx").unwrap_or(path);
+
+    if bytes.starts_with(b"BND4") {
+        let bnd = BND4::parse(bytes)?;
+        for file in &bnd.files {
+            let name = BND4::normalize_path(&file.path);
+            let name = name.rsplit('/').next().unwrap_or_default();
+            let file_path = format!("{path}/{name}");
+
+            // One unreadable file shouldn't stop the rest of the binder from loading.
+            if let Err(e) = extract(files, &file_path, bnd.file_bytes(file).to_vec(), extracted) {
+                warn!("Could not extract {}: {}", file_path, e);
+            }
+        }
+
+        return Ok(());
+    }
+
+    let Some((kind, extension)) = LooseKind::of(&bytes) else {
+        files.insert(path.to_string(), bytes);
+        return Ok(());
+    };
+
+    // Loaders are picked by extension, which a dropped file needn't have.
+    let path = match path.ends_with(&format!(".{extension}")) {
+        true => path.to_string(),
+        false => format!("{path}.{extension}"),
+    };
+    files.insert(path.clone(), bytes);
+    extracted.push((kind, path));
+
+    Ok(())
+}
+
+/// Read a file from disk into `files`, listing the files in it that can be loaded.
+fn read_dropped(
+    files: &LooseFiles,
+    path: &Path,
+) -> Result<Vec<(LooseKind, String)>, Box<dyn Error>> {
+    let name = path.file_name().ok_or("no file name")?;
+    let mut extracted = Vec::new();
+    extract(
+        files,
+        &name.to_string_lossy().to_lowercase(),
+        fs::read(path)?,
+        &mut extracted,
+    )?;
+
+    Ok(extracted)
+}
+
+fn load_dropped_files(
+    mut commands: Commands,
+    mut events: EventReader<FileDragAndDrop>,
+    mut assets: ResMut<AssetCollection>,
+    mut previews: EventWriter<PreviewTpf>,
+    files: Res<LooseFiles>,
+    asset_server: Res<AssetServer>,
+) {
+    for event in events.read() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = event else {
+            continue;
+        };
+
+        let extracted = match read_dropped(&files, path_buf) {
+            Ok(extracted) => extracted,
+            Err(e) => {
+                warn!("Could not read {}: {}", path_buf.display(), e);
+                continue;
+            }
+        };
+        if extracted.is_empty() {
+            warn!("{} has no models, textures or maps", path_buf.display());
+        }
+
+        for (kind, path) in extracted {
+            let asset_path = format!("{LOOSE_SOURCE}://{path}");
+            match kind {
+                LooseKind::Flver => assets.assets.push(asset_server.load(asset_path)),
+                LooseKind::Tpf => {
+                    // The textures of a binder are for its models, rather than to be previewed.
+                    if !path.contains('/') {
+                        previews.send(PreviewTpf(asset_path));
+                    }
+                }
+                LooseKind::Msb => {
+                    let file_name = path.rsplit('/').next().unwrap_or_default();
+                    let map = file_name.split('.').next().unwrap_or_default();
+
+                    commands.spawn((
+                        SpatialBundle::default(),
+                        Name::new(map.to_string()),
+                        Map::new(asset_server.load(asset_path)),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn watch_dropped_files(
+    mut events: EventReader<FileDragAndDrop>,
+    mut watch: EventWriter<WatchFile>,
+    mut dropped: ResMut<DroppedFiles>,
+) {
+    for event in events.read() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = event {
+            if dropped.0.insert(path_buf.clone()) {
+                watch.send(WatchFile(path_buf.clone()));
+            }
+        }
+    }
+}
+
+/// Read dropped files again when they change, reloading the models and textures in them in
+/// place.
+fn reload_dropped_files(
+    mut events: EventReader<FileChanged>,
+    dropped: Res<DroppedFiles>,
+    files: Res<LooseFiles>,
+    asset_server: Res<AssetServer>,
+) {
+    for FileChanged(path_buf) in events.read() {
+        if !dropped.0.contains(path_buf) {
+            continue;
+        }
+
+        let extracted = match read_dropped(&files, path_buf) {
+            Ok(extracted) => extracted,
+            Err(e) => {
+                warn!("Could not read {}: {}", path_buf.display(), e);
+                continue;
+            }
+        };
+        for (kind, path) in extracted {
+            if kind != LooseKind::Msb {
+                asset_server.reload(format!("{LOOSE_SOURCE}://{path}"));
+            }
+        }
+    }
+}
@@ -0,0 +1,83 @@
+use std::{
+    collections::HashMap,
+    path::Path,
+    sync::{Arc, RwLock},
+};
+
+use bevy::{
+    asset::{
+        io::{AssetReader, AssetReaderError, PathStream, Reader},
+        BoxedFuture,
+    },
+    prelude::Resource,
+    tasks::futures_lite::io::Cursor,
+};
+
+/// Files dropped onto the viewer and the files extracted from them, by lowercase path, e.g.
+/// `c3660.chrbnd/c3660.flver` for the FLVER of a dropped `c3660.chrbnd.dcx`.
+#[derive(Clone, Default, Resource)]
+pub struct LooseFiles(Arc<RwLock<HashMap<String, Arc<[u8]>>>>);
+
+impl LooseFiles {
+    pub fn insert(&self, path: String, bytes: Vec<u8>) {
+        self.0
+            .write()
+            .expect("loose files lock poisoned")
+            .insert(path, bytes.into());
+    }
+
+    pub fn get(&self, path: &str) -> Option<Arc<[u8]>> {
+        self.0
+            .read()
+            .expect("loose files lock poisoned")
+            .get(path)
+            .cloned()
+    }
+
+    /// Every loose TPF, with its path.
+    pub fn tpfs(&self) -> Vec<(String, Arc<[u8]>)> {
+        self.0
+            .read()
+            .expect("loose files lock poisoned")
+            .iter()
+            .filter(|(path, _)| path.ends_with(".tpf"))
+            .map(|(path, bytes)| (path.clone(), bytes.clone()))
+            .collect()
+    }
+}
+
+impl AssetReader for LooseFiles {
+    fn read<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            let path_str = path.to_string_lossy().replace('\\', "/");
+
+            self.get(&path_str)
+                .map(|bytes| Box::new(Cursor::new(bytes)) as Box<Reader>)
+                .ok_or_else(|| AssetReaderError::NotFound(path.to_path_buf()))
+        })
+    }
+
+    fn read_meta<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move { Err(AssetReaderError::NotFound(path.to_path_buf())) })
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        Box::pin(async move { Err(AssetReaderError::NotFound(path.to_path_buf())) })
+    }
+
+    fn is_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<bool, AssetReaderError>> {
+        Box::pin(async move { Err(AssetReaderError::NotFound(path.to_path_buf())) })
+    }
+}
@@ -1,32 +1,73 @@
-use std::{f32::consts::PI, io, path::PathBuf};
+use std::{collections::HashSet, f32::consts::PI, io, path::PathBuf};
 
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    render::{
+        settings::{WgpuFeatures, WgpuSettings},
+        RenderPlugin,
+    },
+};
 use bevy_inspector_egui::quick::WorldInspectorPlugin;
 use bevy_panorbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin};
 use clap::Parser;
-use souls_vfs::{FileKeyProvider, Vfs};
+use format::game::Game;
+use souls_vfs::{EntryCache, FileKeyProvider, Vfs};
 use vfs::VfsAssetRepositoryPlugin;
 
-use crate::{flver::asset::FlverAsset, formats::FormatsPlugins};
+use crate::{
+    anim::{asset::AnibndAsset, AnimationPlayback, AnimationPlaybackPlugin},
+    browser::BrowserPlugin,
+    capture::CapturePlugin,
+    culling::CullingPlugin,
+    dummy::DummyPlugin,
+    flver::{asset::FlverAsset, spawn_model},
+    formats::FormatsPlugins,
+    hot_reload::HotReloadPlugin,
+    layers::LayersPlugin,
+    lighting::{LightingPlugin, SunLight},
+    lod::LodPlugin,
+    loose::LooseFilesPlugin,
+    map::{LoadMap, MapPlugin},
+    outliner::OutlinerPlugin,
+    picking::PickingPlugin,
+    skeleton::{asset::SkeletonAsset, SkeletonOverlay, SkeletonPlugin},
+    view_mode::ViewModePlugin,
+};
 
+mod anim;
+mod browser;
+mod capture;
+mod culling;
+mod dummy;
 pub mod flver;
 mod formats;
+mod hot_reload;
+mod layers;
+mod lighting;
+mod lod;
+mod loose;
+mod map;
+mod outliner;
+mod picking;
+mod skeleton;
 mod vfs;
+mod view_mode;
+
+/// Where loaded models are placed.
+const MODEL_ORIGIN: Vec3 = Vec3::new(0.0, 5.0, 0.0);
 
 fn main() {
     let args = Args::parse();
-    let er_path = args.erpath.expect("no path to Elden Ring game provided");
-
-    let keys = FileKeyProvider::new("keys");
-    let archives = [
-        er_path.join("Data0"),
-        er_path.join("Data1"),
-        er_path.join("Data2"),
-        er_path.join("Data3"),
-        er_path.join("sd/sd"),
-    ];
+    let er_path = args
+        .erpath
+        .clone()
+        .expect("no path to Elden Ring game provided");
 
-    let mut vfs = Vfs::create(archives.clone(), &keys).expect("unable to create vfs");
+    let keys = FileKeyProvider::for_game("keys", Game::EldenRing);
+    let mut vfs = Vfs::open_game(Game::EldenRing, er_path, &keys).expect("unable to create vfs");
+    if let Some(cache) = &args.cache {
+        vfs = vfs.with_cache(EntryCache::open(cache).expect("unable to open cache"));
+    }
 
     vfs.mount("/parts/wp_a_0210.partsbnd.dcx")
         .expect("Could not mount bnd");
@@ -34,18 +75,58 @@ fn main() {
     vfs.mount("/chr/c3660_l.texbnd.dcx")
         .expect("Could not mount bnd");
 
+    for path in &args.mount {
+        vfs.mount(path).expect("Could not mount bnd");
+    }
+
+    let mut repository = VfsAssetRepositoryPlugin::new(vfs);
+    if let Some(overlay) = &args.overlay {
+        repository = repository.with_overlay(overlay.clone());
+    }
+
     App::new()
-        .add_plugins((VfsAssetRepositoryPlugin::new(vfs), DefaultPlugins))
+        .add_plugins((
+            repository,
+            LooseFilesPlugin,
+            // Line polygons are needed to draw wireframes.
+            DefaultPlugins.set(RenderPlugin {
+                render_creation: WgpuSettings {
+                    features: WgpuFeatures::POLYGON_MODE_LINE,
+                    ..default()
+                }
+                .into(),
+                ..default()
+            }),
+        ))
         .add_plugins(FormatsPlugins)
         .add_plugins(WorldInspectorPlugin::new())
         .add_plugins(PanOrbitCameraPlugin)
+        .add_plugins(SkeletonPlugin)
+        .add_plugins(DummyPlugin)
+        .add_plugins(AnimationPlaybackPlugin)
+        .add_plugins(MapPlugin)
+        .add_plugins(LayersPlugin)
+        .add_plugins(LightingPlugin)
+        .add_plugins(PickingPlugin)
+        .add_plugins(ViewModePlugin)
+        .add_plugins(LodPlugin)
+        .add_plugins(CullingPlugin)
+        .add_plugins(OutlinerPlugin)
+        .add_plugins(CapturePlugin)
+        .add_plugins(HotReloadPlugin {
+            overlay: args.overlay.clone(),
+        })
+        .add_plugins(BrowserPlugin {
+            dictionaries: args.dictionary.clone(),
+        })
+        .insert_resource(args)
         .init_resource::<AssetCollection>()
         .add_systems(Startup, setup)
         .add_systems(Update, spawn_flvers)
         .run();
 }
 
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Resource)]
 #[command(version, about, long_about = None)]
 struct Args {
     #[arg(long)]
@@ -53,6 +134,41 @@ struct Args {
 
     #[arg(long)]
     erpath: Option<PathBuf>,
+
+    /// Directory to keep decompressed files in, to speed up later launches.
+    #[arg(long)]
+    cache: Option<PathBuf>,
+
+    /// Directory of files to read in place of the archives', laid out like them, e.g.
+    /// `chr/c3660.chrbnd.dcx`. Models and textures are reloaded when its files change.
+    #[arg(long)]
+    overlay: Option<PathBuf>,
+
+    /// Binders to mount, e.g. `/chr/c3660.chrbnd.dcx` for the FLVER of a character. May be
+    /// repeated.
+    #[arg(long)]
+    mount: Vec<String>,
+
+    /// FLVER to load, from a mounted binder.
+    #[arg(long, default_value = "wp_a_0210.flver")]
+    model: String,
+
+    /// Animations to play on the model, e.g. `/chr/c3660.anibnd.dcx`.
+    #[arg(long)]
+    anibnd: Option<String>,
+
+    /// File name dictionary naming the files to list in the browser, one virtual path per line.
+    /// May be repeated.
+    #[arg(long)]
+    dictionary: Vec<PathBuf>,
+
+    /// Map to load, e.g. `m60_42_36_00`. Others can be loaded from the viewer.
+    #[arg(long)]
+    map: Option<String>,
+
+    /// Havok skeleton to draw over the model, e.g. a `skeleton.hkx` in a mounted `.anibnd`.
+    #[arg(long)]
+    skeleton: Option<String>,
 }
 
 #[derive(Debug)]
@@ -64,6 +180,18 @@ pub enum AssetLoadError {
 #[derive(Resource, Default)]
 pub struct AssetCollection {
     assets: Vec<Handle<FlverAsset>>,
+    anibnd: Option<Handle<AnibndAsset>>,
+
+    /// The FLVERs of `assets` that have been spawned as a model.
+    spawned: HashSet<AssetId<FlverAsset>>,
+}
+
+impl AssetCollection {
+    /// Let go of a FLVER whose model was removed, so that it's spawned again if it's loaded again.
+    pub fn remove(&mut self, id: AssetId<FlverAsset>) {
+        self.assets.retain(|handle| handle.id() != id);
+        self.spawned.remove(&id);
+    }
 }
 
 fn setup(
@@ -72,13 +200,32 @@ fn setup(
     mut materials: ResMut<Assets<StandardMaterial>>,
     mut assets: ResMut<AssetCollection>,
     asset_server: Res<AssetServer>,
+    args: Res<Args>,
+    mut load_map: EventWriter<LoadMap>,
 ) {
-    let flver: Handle<FlverAsset> = asset_server.load("wp_a_0210.flver");
+    let flver: Handle<FlverAsset> = asset_server.load(args.model.clone());
 
     assets.assets.push(flver);
+    assets.anibnd = args
+        .anibnd
+        .as_ref()
+        .map(|anibnd| asset_server.load(anibnd.clone()));
+
+    if let Some(map) = &args.map {
+        load_map.send(LoadMap(map.clone()));
+    }
+
+    if let Some(skeleton) = &args.skeleton {
+        commands.spawn((
+            SpatialBundle::from_transform(Transform::from_translation(MODEL_ORIGIN)),
+            Name::new(format!("Skeleton {skeleton}")),
+            SkeletonOverlay::new(asset_server.load(skeleton.clone())),
+        ));
+    }
+
     // From mounted BND
     {
-        let texture: Handle<Image> = asset_server.load("wp_a_0210.tpf#WP_A_0210_a");
+        let texture: Handle<Image> = asset_server.load("wp_a_0210.tpf#wp_a_0210_a");
         let material_handle = materials.add(StandardMaterial {
             base_color_texture: Some(texture.clone()),
             alpha_mode: AlphaMode::Blend,
@@ -96,7 +243,7 @@ fn setup(
     // From DCX'd TPF
     {
         let texture: Handle<Image> =
-            asset_server.load("/asset/aet/aet230/aet230_557.tpf.dcx#AET230_557_a");
+            asset_server.load("/asset/aet/aet230/aet230_557.tpf.dcx#aet230_557_a");
         let material_handle = materials.add(StandardMaterial {
             base_color_texture: Some(texture.clone()),
             alpha_mode: AlphaMode::Blend,
@@ -112,19 +259,22 @@ fn setup(
         });
     }
 
-    commands.spawn(DirectionalLightBundle {
-        directional_light: DirectionalLight {
-            illuminance: light_consts::lux::OVERCAST_DAY,
-            shadows_enabled: false,
-            ..default()
-        },
-        transform: Transform {
-            translation: Vec3::new(0.0, 2.0, 0.0),
-            rotation: Quat::from_rotation_x(-PI / 4.),
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                illuminance: light_consts::lux::OVERCAST_DAY,
+                shadows_enabled: false,
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(0.0, 2.0, 0.0),
+                rotation: Quat::from_rotation_x(-PI / 4.),
+                ..default()
+            },
             ..default()
         },
-        ..default()
-    });
+        SunLight,
+    ));
 
     commands.spawn((
         Camera3dBundle {
@@ -139,19 +289,57 @@ fn setup(
 pub fn spawn_flvers(
     mut commands: Commands,
     mut events: EventReader<AssetEvent<FlverAsset>>,
+    mut assets: ResMut<AssetCollection>,
     flvers: Res<Assets<FlverAsset>>,
+    skeletons: Res<Assets<SkeletonAsset>>,
+    asset_server: Res<AssetServer>,
 ) {
     for ev in events.read() {
-        if let AssetEvent::LoadedWithDependencies { id } = ev {
-            let flver = flvers.get(*id).expect("flver wasn't loaded");
-
-            for mesh in flver.meshes() {
-                commands.spawn(PbrBundle {
-                    mesh: mesh.clone(),
-                    transform: Transform::from_xyz(0.0, 5.0, 0.0),
-                    ..PbrBundle::default()
-                });
-            }
+        let AssetEvent::LoadedWithDependencies { id } = ev else {
+            continue;
+        };
+        // Other FLVERs, e.g. the parts of a map, are placed by whatever loaded them.
+        let Some(handle) = assets
+            .assets
+            .iter()
+            .find(|handle| handle.id() == *id)
+            .cloned()
+        else {
+            continue;
+        };
+        // Reloaded FLVERs update the model they were spawned as in place.
+        if !assets.spawned.insert(*id) {
+            continue;
+        }
+
+        let flver = flvers.get(*id).expect("flver wasn't loaded");
+        let skeleton = skeletons
+            .get(flver.skeleton())
+            .expect("flver skeleton wasn't loaded");
+
+        let name = asset_server
+            .get_path(*id)
+            .map_or_else(|| "FLVER".to_string(), |path| path.to_string());
+        let model = commands
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_translation(MODEL_ORIGIN)),
+                Name::new(name),
+            ))
+            .id();
+        spawn_model(
+            &mut commands,
+            model,
+            &handle,
+            flver,
+            skeleton,
+            assets.anibnd.is_some(),
+        );
+
+        if let Some(anibnd) = &assets.anibnd {
+            commands.entity(model).insert(AnimationPlayback::new(
+                anibnd.clone(),
+                flver.skeleton().clone(),
+            ));
         }
     }
 }
@@ -0,0 +1,40 @@
+use std::{error::Error, io::Cursor};
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt, BoxedFuture, LoadContext},
+    prelude::{Deref, TypePath},
+};
+use format::msb::Msb;
+use souls_vfs::undo_container_compression;
+
+#[derive(Asset, Deref, Debug, TypePath)]
+pub struct MsbAsset(Msb);
+
+#[derive(Default)]
+pub struct MsbLoader;
+
+impl AssetLoader for MsbLoader {
+    type Asset = MsbAsset;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _: &'a (),
+        _: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<MsbAsset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let bytes = undo_container_compression(bytes)?;
+
+            Ok(MsbAsset(Msb::from_reader(&mut Cursor::new(bytes))?))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["msb", "msb.dcx"]
+    }
+}
@@ -0,0 +1,283 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_egui::{egui, EguiContexts};
+use format::msb::{MsbModel, MsbModelType, MsbPart, MsbPartType};
+
+use crate::{
+    flver::{
+        asset::{FlverAsset, FlverSettings},
+        spawn_model,
+    },
+    map::asset::{MsbAsset, MsbLoader},
+    skeleton::{asset::SkeletonAsset, SkeletonOverlay},
+};
+
+pub mod asset;
+
+/// How many parts are placed each frame, so that a map streams in rather than stalling the
+/// viewer until all of its models are read.
+const PARTS_PER_FRAME: usize = 16;
+
+/// Loads Elden Ring maps from their MSB: the map pieces, assets and characters it places are
+/// read from their binders in the archives, nearest to the camera first.
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<MsbAsset>()
+            .init_asset_loader::<MsbLoader>()
+            .add_event::<LoadMap>()
+            .add_systems(
+                Update,
+                (map_window, load_maps, read_msbs, stream_parts, place_parts).chain(),
+            );
+    }
+}
+
+/// Load a map by name, e.g. `m60_42_36_00`.
+#[derive(Event)]
+pub struct LoadMap(pub String);
+
+/// A map being loaded, whose parts are spawned as its children.
+#[derive(Component)]
+pub struct Map {
+    msb: Handle<MsbAsset>,
+
+    /// Parts left to spawn, the furthest from the camera first, once the MSB is read.
+    pending: Option<Vec<PendingPart>>,
+}
+
+impl Map {
+    pub fn new(msb: Handle<MsbAsset>) -> Self {
+        Self { msb, pending: None }
+    }
+}
+
+struct PendingPart {
+    info: MsbPartInfo,
+    transform: Transform,
+
+    /// Asset path of the part's FLVER, in its binder.
+    model: String,
+
+    /// Whether the FLVER is loaded without its bone weights, to be drawn instanced.
+    rigid: bool,
+}
+
+/// The MSB entry a part of a map was placed from, and the name of its model.
+#[derive(Component, Clone)]
+pub struct MsbPartInfo {
+    pub part: MsbPart,
+    pub model: String,
+}
+
+/// A part of a map waiting for its FLVER to load.
+#[derive(Component)]
+struct MapPart(Handle<FlverAsset>);
+
+fn map_window(
+    mut contexts: EguiContexts,
+    mut name: Local<String>,
+    mut load: EventWriter<LoadMap>,
+    maps: Query<&Map>,
+    loading: Query<(), With<MapPart>>,
+) {
+    egui::Window::new("Maps").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            ui.add(egui::TextEdit::singleline(&mut *name).hint_text("m60_42_36_00"));
+            if ui.button("Load map").clicked() {
+                load.send(LoadMap(name.trim().to_string()));
+            }
+        });
+
+        let pending = maps
+            .iter()
+            .filter_map(|map| map.pending.as_ref())
+            .map(Vec::len)
+            .sum::<usize>();
+        let loading = loading.iter().count();
+        if pending + loading > 0 {
+            ui.label(format!(
+                "{pending} parts left to place, {loading} models loading"
+            ));
+        }
+    });
+}
+
+fn load_maps(
+    mut commands: Commands,
+    mut events: EventReader<LoadMap>,
+    asset_server: Res<AssetServer>,
+) {
+    for LoadMap(name) in events.read() {
+        let map = name.to_lowercase();
+        let is_map_name = map.len() == 12
+            && map.starts_with('m')
+            && map[1..]
+                .split('_')
+                .all(|part| part.len() == 2 && part.bytes().all(|b| b.is_ascii_digit()));
+        if !is_map_name {
+            warn!("{} is not a map name like m60_42_36_00", name);
+            continue;
+        }
+
+        commands.spawn((
+            SpatialBundle::default(),
+            Name::new(map.clone()),
+            Map::new(asset_server.load(format!("/map/mapstudio/{map}.msb.dcx"))),
+        ));
+    }
+}
+
+/// The asset path of the FLVER of a model placed by `map`, in its binder.
+fn model_path(map: &str, model: &MsbModel) -> Option<String> {
+    let name = model.name.to_lowercase();
+    let area = map.get(..3)?;
+
+    match model.model_type {
+        // Map pieces are named after their map, e.g. `m000000` of `m60_42_36_00`.
+        MsbModelType::MapPiece => {
+            let stem = format!("{map}_{}", name.get(1..)?);
+            Some(format!("/map/{area}/{map}/{stem}.mapbnd.dcx/{stem}.flver"))
+        }
+        MsbModelType::Asset => {
+            let category = name.get(..6)?;
+            Some(format!(
+                "/asset/aeg/{category}/{name}.geombnd.dcx/{name}.flver"
+            ))
+        }
+        MsbModelType::Enemy => Some(format!("/chr/{name}.chrbnd.dcx/{name}.flver")),
+        _ => None,
+    }
+}
+
+fn read_msbs(
+    mut maps: Query<(&Name, &mut Map)>,
+    msbs: Res<Assets<MsbAsset>>,
+    cameras: Query<&GlobalTransform, With<Camera3d>>,
+    asset_server: Res<AssetServer>,
+) {
+    let camera = cameras
+        .get_single()
+        .map_or(Vec3::ZERO, GlobalTransform::translation);
+
+    for (name, mut map) in &mut maps {
+        if map.pending.is_some() {
+            continue;
+        }
+
+        let Some(msb) = msbs.get(&map.msb) else {
+            if asset_server.get_load_state(&map.msb) == Some(LoadState::Failed) {
+                warn!("Could not read the MSB of {}", name);
+                map.pending = Some(Vec::new());
+            }
+
+            continue;
+        };
+
+        // Dummies and collisions are placeholders the game doesn't draw.
+        let mut pending = msb
+            .parts
+            .iter()
+            .filter(|part| {
+                matches!(
+                    part.part_type,
+                    MsbPartType::MapPiece | MsbPartType::Asset | MsbPartType::Enemy
+                )
+            })
+            .filter_map(|part| {
+                let msb_model = msb.part_model(part)?;
+                let model = model_path(name.as_str(), msb_model)?;
+                let [rx, ry, rz] = part.rotation.map(f32::to_radians);
+
+                Some(PendingPart {
+                    info: MsbPartInfo {
+                        part: part.clone(),
+                        model: msb_model.name.clone(),
+                    },
+                    transform: Transform {
+                        translation: Vec3::from_array(part.position),
+                        // Parts rotate about Y, then Z, then X, like the bones of a FLVER.
+                        rotation: Quat::from_euler(EulerRot::YZX, ry, rz, rx),
+                        scale: Vec3::from_array(part.scale),
+                    },
+                    model,
+                    // Map pieces and assets are placed many times over and never posed.
+                    rigid: matches!(
+                        msb_model.model_type,
+                        MsbModelType::MapPiece | MsbModelType::Asset
+                    ),
+                })
+            })
+            .collect::<Vec<_>>();
+        pending.sort_by(|a, b| {
+            let distance = |part: &PendingPart| part.transform.translation.distance(camera);
+            distance(b).total_cmp(&distance(a))
+        });
+
+        info!("Placing {} parts of {}", pending.len(), name);
+        map.pending = Some(pending);
+    }
+}
+
+fn stream_parts(
+    mut commands: Commands,
+    mut maps: Query<(Entity, &mut Map)>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, mut map) in &mut maps {
+        let Some(pending) = &mut map.pending else {
+            continue;
+        };
+
+        for part in pending.drain(pending.len().saturating_sub(PARTS_PER_FRAME)..) {
+            let rigid = part.rigid;
+            let model = asset_server
+                .load_with_settings(part.model, move |settings: &mut FlverSettings| {
+                    settings.rigid = rigid
+                });
+            let part = commands
+                .spawn((
+                    SpatialBundle::from_transform(part.transform),
+                    Name::new(part.info.part.name.clone()),
+                    part.info,
+                    MapPart(model),
+                ))
+                .id();
+            commands.entity(entity).add_child(part);
+        }
+    }
+}
+
+fn place_parts(
+    mut commands: Commands,
+    parts: Query<(Entity, &Name, &MapPart)>,
+    flvers: Res<Assets<FlverAsset>>,
+    skeletons: Res<Assets<SkeletonAsset>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (entity, name, MapPart(handle)) in &parts {
+        if asset_server.get_load_state(handle) == Some(LoadState::Failed) {
+            warn!("Could not load the model of {}", name);
+            commands.entity(entity).remove::<MapPart>();
+            continue;
+        }
+        if !asset_server.is_loaded_with_dependencies(handle) {
+            continue;
+        }
+
+        let flver = flvers.get(handle).expect("flver wasn't loaded");
+        let skeleton = skeletons
+            .get(flver.skeleton())
+            .expect("flver skeleton wasn't loaded");
+        spawn_model(&mut commands, entity, handle, flver, skeleton, false);
+
+        // A map has far too many skeletons to show them all by default.
+        commands
+            .entity(entity)
+            .remove::<MapPart>()
+            .insert(SkeletonOverlay {
+                skeleton: flver.skeleton().clone(),
+                visible: false,
+            });
+    }
+}
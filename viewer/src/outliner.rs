@@ -0,0 +1,204 @@
+use std::collections::HashSet;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    dummy::DummyPoints,
+    map::{Map, MsbPartInfo},
+    skeleton::SkeletonOverlay,
+    AssetCollection,
+};
+
+/// Lists the loaded models and maps, with the parts of each map under it, to hide, solo and
+/// remove them when comparing several at once.
+///
+/// Soloing shows an entry alone, along with what it's part of and what it's made of.
+pub struct OutlinerPlugin;
+
+impl Plugin for OutlinerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Outliner>()
+            .add_event::<RemoveEntry>()
+            .add_systems(
+                Update,
+                (outliner_window, remove_entries, apply_visibility).chain(),
+            );
+    }
+}
+
+/// The entities listed in the outliner: models, maps and the parts of maps.
+type Entry = Or<(With<Map>, With<MsbPartInfo>, With<SkeletonOverlay>)>;
+type NewEntry = Or<(Added<Map>, Added<MsbPartInfo>, Added<SkeletonOverlay>)>;
+
+/// Despawn an entry of the outliner, with everything under it.
+#[derive(Event)]
+pub struct RemoveEntry(pub Entity);
+
+#[derive(Resource, Default)]
+struct Outliner {
+    hidden: HashSet<Entity>,
+    solo: Option<Entity>,
+}
+
+fn outliner_window(
+    mut contexts: EguiContexts,
+    mut outliner: ResMut<Outliner>,
+    mut remove: EventWriter<RemoveEntry>,
+    roots: Query<(Entity, Option<&Children>), Without<Parent>>,
+    names: Query<&Name, Entry>,
+) {
+    // The soloed entry may have gone with its map, e.g. when it was reloaded.
+    if outliner.solo.is_some_and(|solo| !names.contains(solo)) {
+        outliner.solo = None;
+    }
+
+    let mut edited = Outliner {
+        hidden: outliner.hidden.clone(),
+        solo: outliner.solo,
+    };
+    egui::Window::new("Outliner").show(contexts.ctx_mut(), |ui| {
+        if edited.solo.is_some() && ui.button("Show all").clicked() {
+            edited.solo = None;
+        }
+
+        egui::ScrollArea::vertical()
+            .id_source("outliner")
+            .show(ui, |ui| {
+                for (root, children) in &roots {
+                    // Only models and maps are listed, not e.g. lights and the camera.
+                    let Ok(name) = names.get(root) else {
+                        continue;
+                    };
+                    let parts = children
+                        .into_iter()
+                        .flatten()
+                        .filter(|child| names.contains(**child))
+                        .copied()
+                        .collect::<Vec<_>>();
+                    if parts.is_empty() {
+                        entry_row(ui, root, name, &mut edited, &mut remove);
+                        continue;
+                    }
+
+                    let id = ui.make_persistent_id(root);
+                    egui::collapsing_header::CollapsingState::load_with_default_open(
+                        ui.ctx(),
+                        id,
+                        false,
+                    )
+                    .show_header(ui, |ui| entry_row(ui, root, name, &mut edited, &mut remove))
+                    .body(|ui| {
+                        // Maps have thousands of parts, so only the rows in view are laid out.
+                        let row_height = ui.spacing().interact_size.y;
+                        egui::ScrollArea::vertical()
+                            .id_source(root)
+                            .max_height(row_height * 16.0)
+                            .show_rows(ui, row_height, parts.len(), |ui, rows| {
+                                for part in &parts[rows] {
+                                    if let Ok(name) = names.get(*part) {
+                                        entry_row(ui, *part, name, &mut edited, &mut remove);
+                                    }
+                                }
+                            });
+                    });
+                }
+            });
+    });
+
+    // Only flag the resource as changed when an entry is toggled.
+    if edited.hidden != outliner.hidden || edited.solo != outliner.solo {
+        *outliner = edited;
+    }
+}
+
+fn entry_row(
+    ui: &mut egui::Ui,
+    entity: Entity,
+    name: &Name,
+    outliner: &mut Outliner,
+    remove: &mut EventWriter<RemoveEntry>,
+) {
+    ui.horizontal(|ui| {
+        let mut shown = !outliner.hidden.contains(&entity);
+        if ui.checkbox(&mut shown, name.as_str()).changed() {
+            match shown {
+                true => outliner.hidden.remove(&entity),
+                false => outliner.hidden.insert(entity),
+            };
+        }
+
+        let soloed = outliner.solo == Some(entity);
+        if ui.selectable_label(soloed, "Solo").clicked() {
+            outliner.solo = (!soloed).then_some(entity);
+        }
+        if ui.button("Remove").clicked() {
+            remove.send(RemoveEntry(entity));
+        }
+    });
+}
+
+fn remove_entries(
+    mut commands: Commands,
+    mut events: EventReader<RemoveEntry>,
+    mut outliner: ResMut<Outliner>,
+    mut assets: ResMut<AssetCollection>,
+    models: Query<&DummyPoints, Without<Parent>>,
+    parents: Query<&Parent>,
+) {
+    for RemoveEntry(entity) in events.read() {
+        let Some(removed) = commands.get_entity(*entity) else {
+            continue;
+        };
+        removed.despawn_recursive();
+
+        // Models spawned from the FLVERs the viewer was asked to load are spawned again if
+        // they're loaded again.
+        if let Ok(DummyPoints(flver)) = models.get(*entity) {
+            assets.remove(flver.id());
+        }
+
+        outliner.hidden.remove(entity);
+        let solo_removed = outliner.solo.is_some_and(|solo| {
+            solo == *entity || parents.iter_ancestors(solo).any(|e| e == *entity)
+        });
+        if solo_removed {
+            outliner.solo = None;
+        }
+    }
+}
+
+fn apply_visibility(
+    outliner: Res<Outliner>,
+    mut entries: Query<(Entity, &mut Visibility), Entry>,
+    added: Query<(), NewEntry>,
+    parents: Query<&Parent>,
+) {
+    // Entries spawned while an entry is soloed are hidden too.
+    if !outliner.is_changed() && added.is_empty() {
+        return;
+    }
+
+    // The soloed entry and the entries it's part of.
+    let solo_path = outliner
+        .solo
+        .map(|solo| {
+            parents
+                .iter_ancestors(solo)
+                .chain([solo])
+                .collect::<HashSet<_>>()
+        })
+        .unwrap_or_default();
+
+    for (entity, mut visibility) in &mut entries {
+        let in_solo = outliner.solo.map_or(true, |solo| {
+            solo_path.contains(&entity) || parents.iter_ancestors(entity).any(|e| e == solo)
+        });
+        let shown = match in_solo && !outliner.hidden.contains(&entity) {
+            true => Visibility::Inherited,
+            false => Visibility::Hidden,
+        };
+
+        visibility.set_if_neq(shown);
+    }
+}
@@ -0,0 +1,203 @@
+use bevy::{prelude::*, render::primitives::Aabb, window::PrimaryWindow};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    flver::{asset::FlverAsset, FlverMesh},
+    map::MsbPartInfo,
+};
+
+/// How far the cursor may move between pressing and releasing the mouse for it to still be a
+/// click, rather than the camera being dragged.
+const CLICK_DISTANCE: f32 = 4.0;
+
+const SELECTION_COLOR: Color = Color::YELLOW;
+
+/// Picks the mesh under the cursor on click, outlining it and showing what it is in an
+/// inspector: the file it was read from, its mesh and material, and the MSB part it was placed by.
+pub struct PickingPlugin;
+
+impl Plugin for PickingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>()
+            .add_systems(Update, (pick, draw_selection, inspector).chain());
+    }
+}
+
+/// The picked mesh, if any.
+#[derive(Resource, Default)]
+pub struct Selection(pub Option<Entity>);
+
+fn pick(
+    mut contexts: EguiContexts,
+    mut selection: ResMut<Selection>,
+    mut pressed_at: Local<Option<Vec2>>,
+    buttons: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    meshes: Query<(Entity, &Aabb, &GlobalTransform, &ViewVisibility), With<FlverMesh>>,
+) {
+    let Some(cursor) = windows.get_single().ok().and_then(Window::cursor_position) else {
+        return;
+    };
+
+    if buttons.just_pressed(MouseButton::Left) {
+        *pressed_at = Some(cursor);
+    }
+    if !buttons.just_released(MouseButton::Left) {
+        return;
+    }
+
+    let clicked = pressed_at
+        .take()
+        .is_some_and(|pressed| pressed.distance(cursor) < CLICK_DISTANCE);
+    if !clicked || contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+
+    let Some(ray) = cameras
+        .iter()
+        .find(|(camera, _)| camera.is_active)
+        .and_then(|(camera, transform)| camera.viewport_to_world(transform, cursor))
+    else {
+        return;
+    };
+
+    selection.0 = meshes
+        .iter()
+        .filter(|(.., visibility)| visibility.get())
+        .filter_map(|(entity, aabb, transform, _)| {
+            // Test the ray against the box in the mesh's space, where it's axis aligned.
+            let inverse = transform.affine().inverse();
+            let origin = inverse.transform_point3(ray.origin);
+            let direction = inverse.transform_vector3(*ray.direction);
+
+            intersect_aabb(origin, direction, aabb).map(|distance| (entity, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(entity, _)| entity);
+}
+
+/// How far along the ray from `origin` it first enters `aabb`, unless it starts inside it or
+/// misses it.
+fn intersect_aabb(origin: Vec3, direction: Vec3, aabb: &Aabb) -> Option<f32> {
+    let inverse = direction.recip();
+    let to_min = (Vec3::from(aabb.min()) - origin) * inverse;
+    let to_max = (Vec3::from(aabb.max()) - origin) * inverse;
+
+    let near = to_min.min(to_max).max_element();
+    let far = to_min.max(to_max).min_element();
+
+    (near <= far && near > 0.0).then_some(near)
+}
+
+fn draw_selection(
+    mut gizmos: Gizmos,
+    selection: Res<Selection>,
+    meshes: Query<(&Aabb, &GlobalTransform)>,
+) {
+    let Some((aabb, transform)) = selection.0.and_then(|entity| meshes.get(entity).ok()) else {
+        return;
+    };
+
+    let bounds = Transform::from_translation(aabb.center.into())
+        .with_scale(Vec3::from(aabb.half_extents) * 2.0);
+    gizmos.cuboid(transform.mul_transform(bounds), SELECTION_COLOR);
+}
+
+fn inspector(
+    mut contexts: EguiContexts,
+    mut selection: ResMut<Selection>,
+    meshes: Query<&FlverMesh>,
+    parents: Query<&Parent>,
+    models: Query<(&Name, Option<&MsbPartInfo>)>,
+    flvers: Res<Assets<FlverAsset>>,
+    asset_server: Res<AssetServer>,
+) {
+    let Some(entity) = selection.0 else {
+        return;
+    };
+    // The mesh may have gone with its model, e.g. when a map is reloaded.
+    let Ok(mesh) = meshes.get(entity) else {
+        selection.0 = None;
+        return;
+    };
+
+    let model = parents
+        .iter_ancestors(entity)
+        .find_map(|ancestor| models.get(ancestor).ok().map(|model| (ancestor, model)));
+    let flver = flvers.get(&mesh.flver);
+    let details = flver.and_then(|flver| flver.mesh_details(mesh.index));
+    let source = asset_server
+        .get_path(&mesh.flver)
+        .map_or_else(|| "Unknown".to_string(), |path| path.to_string());
+
+    let mut open = true;
+    egui::SidePanel::right("inspector").show(contexts.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            ui.heading("Selection");
+            open = !ui.button("Deselect").clicked();
+        });
+
+        egui::Grid::new("selection").num_columns(2).show(ui, |ui| {
+            field(ui, "Entity", format!("{entity:?}"));
+            if let Some((model, (name, _))) = model {
+                field(ui, "Model", format!("{name} ({model:?})"));
+            }
+            field(ui, "Source", source);
+            field(ui, "Mesh", mesh.index.to_string());
+            if let Some(flver) = flver {
+                field(
+                    ui,
+                    "Levels of detail",
+                    flver.lod_count(mesh.index).to_string(),
+                );
+            }
+
+            if let Some(details) = details {
+                field(ui, "Vertices", details.vertices.to_string());
+                field(ui, "Indices", details.indices.to_string());
+                field(ui, "Material", details.material.clone());
+                field(ui, "Definition", details.material_path.clone());
+            }
+        });
+
+        if let Some(details) = details.filter(|details| !details.textures.is_empty()) {
+            egui::CollapsingHeader::new("Textures")
+                .default_open(true)
+                .show(ui, |ui| {
+                    egui::Grid::new("textures").num_columns(2).show(ui, |ui| {
+                        for (texture_type, path) in &details.textures {
+                            field(ui, texture_type, path.clone());
+                        }
+                    });
+                });
+        }
+
+        if let Some((_, (_, Some(info)))) = model {
+            let part = &info.part;
+            egui::CollapsingHeader::new("MSB part")
+                .default_open(true)
+                .show(ui, |ui| {
+                    egui::Grid::new("part").num_columns(2).show(ui, |ui| {
+                        field(ui, "Name", part.name.clone());
+                        field(ui, "Instance ID", part.instance_id.to_string());
+                        field(ui, "Type", format!("{:?}", part.part_type));
+                        field(ui, "Model", info.model.clone());
+                        field(ui, "Position", format!("{:?}", part.position));
+                        field(ui, "Rotation", format!("{:?}", part.rotation));
+                        field(ui, "Scale", format!("{:?}", part.scale));
+                    });
+                });
+        }
+    });
+
+    if !open {
+        selection.0 = None;
+    }
+}
+
+fn field(ui: &mut egui::Ui, label: &str, value: String) {
+    ui.label(label);
+    ui.label(value);
+    ui.end_row();
+}
@@ -0,0 +1,136 @@
+use std::error::Error;
+
+use bevy::{
+    asset::{io::Reader, Asset, AssetLoader, AsyncReadExt, BoxedFuture, LoadContext},
+    math::{EulerRot, Mat4, Quat, Vec3},
+    prelude::{Transform, TypePath},
+};
+use format::{
+    flver::Flver,
+    hkx::{HkaSkeleton, Hkx},
+};
+
+/// A bone hierarchy in its bind pose, from a FLVER or a Havok skeleton.
+#[derive(Asset, Debug, TypePath)]
+pub struct SkeletonAsset {
+    pub bones: Vec<SkeletonBone>,
+}
+
+#[derive(Debug)]
+pub struct SkeletonBone {
+    pub name: String,
+    pub parent: Option<usize>,
+
+    /// The bone's transform relative to its parent.
+    pub local: Transform,
+
+    /// The bone's transform relative to the model's origin.
+    pub model: Mat4,
+}
+
+impl SkeletonBone {
+    /// Where the bone's joint is relative to the model's origin.
+    pub fn position(&self) -> Vec3 {
+        self.model.w_axis.truncate()
+    }
+}
+
+impl SkeletonAsset {
+    fn new(bones: impl IntoIterator<Item = (String, Option<usize>, Transform)>) -> Self {
+        let mut bones = bones
+            .into_iter()
+            .map(|(name, parent, local)| SkeletonBone {
+                name,
+                parent,
+                local,
+                model: Mat4::IDENTITY,
+            })
+            .collect::<Vec<_>>();
+
+        for index in 0..bones.len() {
+            let mut model = bones[index].local.compute_affine();
+            let mut parent = bones[index].parent;
+
+            // Bounded by the bone count, in case a malformed hierarchy loops.
+            for _ in 0..bones.len() {
+                let Some(bone) = parent.and_then(|parent| bones.get(parent)) else {
+                    break;
+                };
+
+                model = bone.local.compute_affine() * model;
+                parent = bone.parent;
+            }
+
+            bones[index].model = model.into();
+        }
+
+        Self { bones }
+    }
+
+    pub fn from_flver(flver: &Flver) -> Self {
+        Self::new(flver.bones().iter().map(|bone| {
+            let [rx, ry, rz] = bone.rotation.map(|v| v.get());
+
+            (
+                flver
+                    .string(bone.name_offset.get())
+                    .map(|name| name.to_string())
+                    .unwrap_or_default(),
+                usize::try_from(bone.parent_index.get() as i16).ok(),
+                Transform {
+                    translation: Vec3::from_array(bone.translation.map(|v| v.get())),
+                    // FLVER bones rotate about Y, then Z, then X.
+                    rotation: Quat::from_euler(EulerRot::YZX, ry, rz, rx),
+                    scale: Vec3::from_array(bone.scale.map(|v| v.get())),
+                },
+            )
+        }))
+    }
+
+    pub fn from_havok(skeleton: &HkaSkeleton) -> Self {
+        Self::new(skeleton.bones.iter().enumerate().map(|(index, bone)| {
+            let transform = skeleton
+                .reference_pose
+                .get(index)
+                .map(|pose| Transform {
+                    translation: Vec3::from_array(pose.translation),
+                    rotation: Quat::from_array(pose.rotation),
+                    scale: Vec3::from_array(pose.scale),
+                })
+                .unwrap_or_default();
+
+            (bone.name.clone(), bone.parent, transform)
+        }))
+    }
+}
+
+/// Loads the first skeleton of a Havok file, e.g. the `skeleton.hkx` of an `.anibnd`.
+#[derive(Default)]
+pub struct HkxSkeletonLoader;
+
+impl AssetLoader for HkxSkeletonLoader {
+    type Asset = SkeletonAsset;
+    type Settings = ();
+    type Error = Box<dyn Error + Send + Sync>;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _: &'a (),
+        _: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<SkeletonAsset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let skeletons = Hkx::from_bytes(&bytes)?.skeletons()?;
+            let skeleton = skeletons.first().ok_or("HKX has no skeleton")?;
+
+            Ok(SkeletonAsset::from_havok(skeleton))
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["hkx"]
+    }
+}
@@ -0,0 +1,211 @@
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::skeleton::asset::{HkxSkeletonLoader, SkeletonAsset};
+
+pub mod asset;
+
+/// Key that shows or hides the skeletons of every model at once.
+const TOGGLE_KEY: KeyCode = KeyCode::KeyB;
+
+/// How close the cursor has to be to a joint, in logical pixels, to label it.
+const HOVER_DISTANCE: f32 = 12.0;
+
+const BONE_COLOR: Color = Color::YELLOW;
+const JOINT_COLOR: Color = Color::ORANGE_RED;
+const JOINT_RADIUS: f32 = 0.02;
+
+/// Draws the bones of [SkeletonOverlay]s as lines between their joints, naming the joint under
+/// the cursor.
+pub struct SkeletonPlugin;
+
+impl Plugin for SkeletonPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<SkeletonAsset>()
+            .init_asset_loader::<HkxSkeletonLoader>()
+            .register_type::<SkeletonOverlay>()
+            .add_systems(Startup, setup)
+            .add_systems(
+                Update,
+                (toggle_skeletons, draw_skeletons, label_hovered_bone),
+            );
+    }
+}
+
+/// Draws a skeleton in the space of the entity, e.g. the root of a model. Toggled per model in
+/// the inspector.
+#[derive(Component, Reflect)]
+pub struct SkeletonOverlay {
+    #[reflect(ignore)]
+    pub skeleton: Handle<SkeletonAsset>,
+    pub visible: bool,
+}
+
+impl SkeletonOverlay {
+    pub fn new(skeleton: Handle<SkeletonAsset>) -> Self {
+        Self {
+            skeleton,
+            visible: true,
+        }
+    }
+}
+
+/// The entity posing each bone of the [SkeletonOverlay] on the same entity, e.g. the joints a
+/// skinned model is animated with. Skeletons without them are drawn in their bind pose.
+#[derive(Component)]
+pub struct SkeletonJoints(pub Vec<Entity>);
+
+/// The text naming the joint under the cursor.
+#[derive(Component)]
+struct BoneLabel;
+
+fn setup(mut commands: Commands, mut config_store: ResMut<GizmoConfigStore>) {
+    // Bones are inside the mesh they move, so they're drawn over it.
+    let (config, _) = config_store.config_mut::<DefaultGizmoConfigGroup>();
+    config.depth_bias = -1.0;
+
+    commands.spawn((
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.0,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            ..default()
+        })
+        .with_background_color(Color::rgba(0.0, 0.0, 0.0, 0.6)),
+        Visibility::Hidden,
+        BoneLabel,
+    ));
+}
+
+fn toggle_skeletons(keys: Res<ButtonInput<KeyCode>>, mut overlays: Query<&mut SkeletonOverlay>) {
+    if !keys.just_pressed(TOGGLE_KEY) {
+        return;
+    }
+
+    // Show them all unless they're all shown already.
+    let visible = overlays.iter().any(|overlay| !overlay.visible);
+    for mut overlay in &mut overlays {
+        overlay.visible = visible;
+    }
+}
+
+/// The joints of a visible skeleton of a visible model in world space, posed by its joint
+/// entities if it has them.
+fn joint_positions<'a>(
+    (overlay, transform, joints, visibility): (
+        &SkeletonOverlay,
+        &GlobalTransform,
+        Option<&SkeletonJoints>,
+        &InheritedVisibility,
+    ),
+    skeletons: &'a Assets<SkeletonAsset>,
+    joint_transforms: &Query<&GlobalTransform>,
+) -> Option<(&'a SkeletonAsset, Vec<Vec3>)> {
+    let skeleton = skeletons
+        .get(&overlay.skeleton)
+        .filter(|_| overlay.visible && visibility.get())?;
+    let positions = skeleton
+        .bones
+        .iter()
+        .enumerate()
+        .map(|(index, bone)| {
+            joints
+                .and_then(|joints| joint_transforms.get(*joints.0.get(index)?).ok())
+                .map_or_else(
+                    || transform.transform_point(bone.position()),
+                    GlobalTransform::translation,
+                )
+        })
+        .collect();
+
+    Some((skeleton, positions))
+}
+
+fn draw_skeletons(
+    mut gizmos: Gizmos,
+    overlays: Query<(
+        &SkeletonOverlay,
+        &GlobalTransform,
+        Option<&SkeletonJoints>,
+        &InheritedVisibility,
+    )>,
+    joint_transforms: Query<&GlobalTransform>,
+    skeletons: Res<Assets<SkeletonAsset>>,
+) {
+    for overlay in &overlays {
+        let Some((skeleton, positions)) = joint_positions(overlay, &skeletons, &joint_transforms)
+        else {
+            continue;
+        };
+
+        for (bone, position) in skeleton.bones.iter().zip(&positions) {
+            gizmos.sphere(*position, Quat::IDENTITY, JOINT_RADIUS, JOINT_COLOR);
+
+            if let Some(parent) = bone.parent.and_then(|parent| positions.get(parent)) {
+                gizmos.line(*parent, *position, BONE_COLOR);
+            }
+        }
+    }
+}
+
+fn label_hovered_bone(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    overlays: Query<(
+        &SkeletonOverlay,
+        &GlobalTransform,
+        Option<&SkeletonJoints>,
+        &InheritedVisibility,
+    )>,
+    joint_transforms: Query<&GlobalTransform>,
+    skeletons: Res<Assets<SkeletonAsset>>,
+    mut labels: Query<(&mut Text, &mut Style, &mut Visibility), With<BoneLabel>>,
+) {
+    let Ok((mut text, mut style, mut visibility)) = labels.get_single_mut() else {
+        return;
+    };
+    *visibility = Visibility::Hidden;
+
+    let (Ok(window), Ok((camera, camera_transform))) = (windows.get_single(), cameras.get_single())
+    else {
+        return;
+    };
+    let Some(cursor) = window.cursor_position() else {
+        return;
+    };
+
+    let mut hovered: Option<(&str, f32)> = None;
+    for overlay in &overlays {
+        let Some((skeleton, positions)) = joint_positions(overlay, &skeletons, &joint_transforms)
+        else {
+            continue;
+        };
+
+        for (bone, position) in skeleton.bones.iter().zip(positions) {
+            let Some(distance) = camera
+                .world_to_viewport(camera_transform, position)
+                .map(|point| point.distance(cursor))
+            else {
+                continue;
+            };
+
+            if distance <= HOVER_DISTANCE && hovered.map_or(true, |(_, nearest)| distance < nearest)
+            {
+                hovered = Some((&bone.name, distance));
+            }
+        }
+    }
+    let Some((name, _)) = hovered else {
+        return;
+    };
+
+    text.sections[0].value = name.to_string();
+    style.left = Val::Px(cursor.x + HOVER_DISTANCE);
+    style.top = Val::Px(cursor.y + HOVER_DISTANCE);
+    *visibility = Visibility::Visible;
+}
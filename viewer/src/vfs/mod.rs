@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc};
 
 use bevy::{
     asset::io::{AssetSource, AssetSourceId},
@@ -6,30 +6,49 @@ use bevy::{
 };
 use souls_vfs::Vfs;
 
-use self::reader::VfsAssetRepository;
+use self::overlay::OverlayReader;
+pub use self::reader::VfsAssetRepository;
 
+mod overlay;
 mod reader;
 
 pub struct VfsAssetRepositoryPlugin {
     repository: VfsAssetRepository,
+
+    /// Directory of files read in place of the archives', like a mod's.
+    overlay: Option<PathBuf>,
 }
 
 impl VfsAssetRepositoryPlugin {
     pub fn new(vfs: Vfs) -> Self {
         Self {
             repository: VfsAssetRepository(Arc::new(vfs)),
+            overlay: None,
         }
     }
+
+    /// Read files from `directory` before the archives, laid out as they are in the archives.
+    pub fn with_overlay(mut self, directory: PathBuf) -> Self {
+        self.overlay = Some(directory);
+        self
+    }
 }
 
 impl Plugin for VfsAssetRepositoryPlugin {
     fn build(&self, app: &mut App) {
         let repository = self.repository.clone();
+        let overlay = self.overlay.clone();
 
         app.insert_resource(repository.clone());
         app.register_asset_source(
             AssetSourceId::Default,
-            AssetSource::build().with_reader(move || Box::new(repository.clone())),
+            AssetSource::build().with_reader(move || match &overlay {
+                Some(directory) => Box::new(OverlayReader {
+                    directory: directory.clone(),
+                    vfs: repository.clone(),
+                }),
+                None => Box::new(repository.clone()),
+            }),
         );
     }
 }
@@ -0,0 +1,78 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::{
+    asset::{
+        io::{AssetReader, AssetReaderError, PathStream, Reader},
+        BoxedFuture,
+    },
+    tasks::futures_lite::io::Cursor,
+};
+use souls_vfs::undo_container_compression;
+
+use crate::vfs::{reader::file_in_binder, VfsAssetRepository};
+
+/// Reads files from a directory laid over the archives, like a mod's, before looking in the
+/// archives. Files are laid out as they are in the archives, e.g. `chr/c3660.chrbnd.dcx`.
+pub struct OverlayReader {
+    pub directory: PathBuf,
+    pub vfs: VfsAssetRepository,
+}
+
+impl OverlayReader {
+    /// Read the file at `path` from the overlay, or from a binder in the overlay for files named
+    /// by the binder's path and their name, e.g. `/chr/c3660.chrbnd.dcx/c3660.flver`.
+    fn read_overlay(&self, path: &Path) -> Option<Vec<u8>> {
+        let relative = path.strip_prefix("/").unwrap_or(path);
+        let file = self.directory.join(relative);
+        if file.is_file() {
+            return fs::read(file).ok();
+        }
+
+        let binder = self.directory.join(relative.parent()?);
+        let file_name = relative.file_name()?.to_str()?;
+        if !binder.is_file() {
+            return None;
+        }
+
+        file_in_binder(
+            undo_container_compression(fs::read(binder).ok()?).ok()?,
+            file_name,
+        )
+    }
+}
+
+impl AssetReader for OverlayReader {
+    fn read<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        match self.read_overlay(path) {
+            Some(bytes) => Box::pin(async move { Ok(Box::new(Cursor::new(bytes)) as Box<Reader>) }),
+            None => self.vfs.read(path),
+        }
+    }
+
+    fn read_meta<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        self.vfs.read_meta(path)
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        self.vfs.read_directory(path)
+    }
+
+    fn is_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<bool, AssetReaderError>> {
+        self.vfs.is_directory(path)
+    }
+}
@@ -14,6 +14,7 @@ use bevy::{
     prelude::{Deref, DerefMut, Resource},
     tasks::futures_lite::{io::Cursor, AsyncRead},
 };
+use format::bnd4::BND4;
 use souls_vfs::{Vfs, VfsEntryReader as VfsEntryReaderImpl, VfsOpenError};
 
 #[derive(Clone, Deref, DerefMut, Resource)]
@@ -34,8 +35,14 @@ impl AssetReader for VfsAssetRepository {
                         .open_from_mounts(&path_str)
                         .map(|r| Box::new(Cursor::new(r)))?)
                 })
+                .or_else(|_: VfsOpenError| {
+                    read_from_binder(self, &path_str)
+                        .map(|bytes| Box::new(Cursor::new(bytes)) as Box<Reader>)
+                        .ok_or(VfsOpenError::NotFound)
+                })
                 .map_err(|e| match e {
                     VfsOpenError::NotFound => AssetReaderError::NotFound(path.to_path_buf()),
+                    e => AssetReaderError::Io(Arc::new(io::Error::other(e.to_string()))),
                 })
         })
     }
@@ -62,6 +69,34 @@ impl AssetReader for VfsAssetRepository {
     }
 }
 
+/// Read a file of a binder in the archives, named by the binder's path and the file's name, e.g.
+/// `/chr/c3660.chrbnd.dcx/c3660.flver`, without the binder having to be mounted.
+fn read_from_binder(vfs: &Vfs, path: &str) -> Option<Vec<u8>> {
+    let (binder, file_name) = path.rsplit_once('/')?;
+    if !binder
+        .strip_suffix(".dcx")
+        .unwrap_or(binder)
+        .ends_with("bnd")
+    {
+        return None;
+    }
+
+    file_in_binder(vfs.read_decompressed(binder).ok()?, file_name)
+}
+
+/// Read the file named `file_name` from the decompressed binder in `bytes`.
+pub(super) fn file_in_binder(bytes: Vec<u8>, file_name: &str) -> Option<Vec<u8>> {
+    let bnd = BND4::parse(bytes).ok()?;
+    let file = bnd.files.iter().find(|file| {
+        file.path
+            .rsplit(['/', '\\'])
+            .next()
+            .is_some_and(|name| name.eq_ignore_ascii_case(file_name))
+    })?;
+
+    Some(bnd.file_bytes(file).to_vec())
+}
+
 struct VfsEntryReader<'a>(VfsEntryReaderImpl<'a>);
 
 impl<'a> AsyncRead for VfsEntryReader<'a> {
@@ -0,0 +1,71 @@
+use bevy::{
+    pbr::{MaterialPipeline, MaterialPipelineKey},
+    prelude::*,
+    render::{
+        mesh::MeshVertexBufferLayout,
+        render_resource::{
+            AsBindGroup, RenderPipelineDescriptor, ShaderRef, SpecializedMeshPipelineError,
+        },
+    },
+};
+
+use crate::flver::asset::ATTRIBUTE_VERTEX_COLOR;
+
+pub const VIEW_MODE_SHADER: Handle<Shader> =
+    Handle::weak_from_u128(0x5d1c_3a8e_f04b_4b2d_9e61_27a0_c8d4_7f13);
+
+/// Draws a mesh as one of the debug [super::ViewMode]s rather than lit.
+#[derive(Asset, AsBindGroup, Clone, Debug, TypePath)]
+pub struct ViewModeMaterial {
+    /// Which attribute the shader shows.
+    #[uniform(0)]
+    pub mode: u32,
+}
+
+impl Material for ViewModeMaterial {
+    fn vertex_shader() -> ShaderRef {
+        VIEW_MODE_SHADER.into()
+    }
+
+    fn fragment_shader() -> ShaderRef {
+        VIEW_MODE_SHADER.into()
+    }
+
+    /// Binds the vertex attributes the shader reads, which include FLVER vertex colors that the
+    /// mesh pipeline doesn't know about.
+    fn specialize(
+        _: &MaterialPipeline<Self>,
+        descriptor: &mut RenderPipelineDescriptor,
+        layout: &MeshVertexBufferLayout,
+        _: MaterialPipelineKey<Self>,
+    ) -> Result<(), SpecializedMeshPipelineError> {
+        let mut attributes = vec![Mesh::ATTRIBUTE_POSITION.at_shader_location(0)];
+        let optional = [
+            (Mesh::ATTRIBUTE_NORMAL, 1),
+            (Mesh::ATTRIBUTE_UV_0, 2),
+            (Mesh::ATTRIBUTE_UV_1, 3),
+            (ATTRIBUTE_VERTEX_COLOR, 8),
+        ];
+        for (attribute, location) in optional {
+            if layout.contains(attribute.clone()) {
+                attributes.push(attribute.at_shader_location(location));
+            }
+        }
+
+        // Skinning is decided by the mesh pipeline, which also needs the mesh to have joints.
+        if descriptor.vertex.shader_defs.contains(&"SKINNED".into()) {
+            attributes.push(Mesh::ATTRIBUTE_JOINT_INDEX.at_shader_location(6));
+            attributes.push(Mesh::ATTRIBUTE_JOINT_WEIGHT.at_shader_location(7));
+        }
+
+        if layout.contains(ATTRIBUTE_VERTEX_COLOR) {
+            descriptor
+                .vertex
+                .shader_defs
+                .push("FLVER_VERTEX_COLORS".into());
+        }
+        descriptor.vertex.buffers = vec![layout.get_layout(&attributes)?];
+
+        Ok(())
+    }
+}
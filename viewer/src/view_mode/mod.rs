@@ -0,0 +1,157 @@
+use bevy::{
+    asset::load_internal_asset,
+    pbr::wireframe::{WireframeConfig, WireframePlugin},
+    prelude::*,
+};
+use bevy_egui::{egui, EguiContexts};
+
+use crate::{
+    flver::FlverMesh,
+    view_mode::material::{ViewModeMaterial, VIEW_MODE_SHADER},
+};
+
+mod material;
+
+/// Switches how models are drawn between lit, wireframe, and views of their normals, UVs and
+/// vertex colors, to diagnose how they were read.
+///
+/// Wireframes need the renderer to be created with
+/// [bevy::render::settings::WgpuFeatures::POLYGON_MODE_LINE].
+pub struct ViewModePlugin;
+
+impl Plugin for ViewModePlugin {
+    fn build(&self, app: &mut App) {
+        load_internal_asset!(app, VIEW_MODE_SHADER, "view_mode.wgsl", Shader::from_wgsl);
+
+        app.add_plugins((
+            WireframePlugin,
+            MaterialPlugin::<ViewModeMaterial>::default(),
+        ))
+        .init_resource::<ViewMode>()
+        .add_systems(Startup, setup)
+        .add_systems(Update, (view_mode_window, apply_view_mode).chain());
+    }
+}
+
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ViewMode {
+    #[default]
+    Lit,
+    Wireframe,
+    FaceNormals,
+    VertexNormals,
+    UvChecker(u32),
+    VertexColor,
+}
+
+impl ViewMode {
+    const ALL: [Self; 7] = [
+        Self::Lit,
+        Self::Wireframe,
+        Self::FaceNormals,
+        Self::VertexNormals,
+        Self::UvChecker(0),
+        Self::UvChecker(1),
+        Self::VertexColor,
+    ];
+
+    fn label(self) -> String {
+        match self {
+            Self::Lit => "Lit".to_string(),
+            Self::Wireframe => "Wireframe".to_string(),
+            Self::FaceNormals => "Face normals".to_string(),
+            Self::VertexNormals => "Vertex normals".to_string(),
+            Self::UvChecker(channel) => format!("UV checker (UV{channel})"),
+            Self::VertexColor => "Vertex color".to_string(),
+        }
+    }
+
+    /// The mode of `view_mode.wgsl` that draws this view, unless models are drawn lit.
+    fn shader_mode(self) -> Option<u32> {
+        match self {
+            Self::Lit | Self::Wireframe => None,
+            Self::FaceNormals => Some(0),
+            Self::VertexNormals => Some(1),
+            Self::UvChecker(channel) => Some(2 + channel),
+            Self::VertexColor => Some(4),
+        }
+    }
+}
+
+/// The material of each shader mode, shared by every mesh.
+#[derive(Resource)]
+struct ViewModeMaterials(Vec<Handle<ViewModeMaterial>>);
+
+/// The material a mesh is lit with, while it's drawn with a [ViewModeMaterial].
+#[derive(Component)]
+struct LitMaterial(Handle<StandardMaterial>);
+
+fn setup(mut commands: Commands, mut materials: ResMut<Assets<ViewModeMaterial>>) {
+    let modes = ViewMode::ALL.iter().filter_map(|mode| mode.shader_mode());
+    let handles = modes
+        .map(|mode| materials.add(ViewModeMaterial { mode }))
+        .collect();
+
+    commands.insert_resource(ViewModeMaterials(handles));
+}
+
+fn view_mode_window(mut contexts: EguiContexts, mut view_mode: ResMut<ViewMode>) {
+    egui::Window::new("View mode").show(contexts.ctx_mut(), |ui| {
+        let mut selected = *view_mode;
+        egui::ComboBox::from_id_source("view_mode")
+            .selected_text(selected.label())
+            .show_ui(ui, |ui| {
+                for mode in ViewMode::ALL {
+                    ui.selectable_value(&mut selected, mode, mode.label());
+                }
+            });
+
+        // Only flag the resource as changed when another mode is picked.
+        if selected != *view_mode {
+            *view_mode = selected;
+        }
+    });
+}
+
+fn apply_view_mode(
+    mut commands: Commands,
+    view_mode: Res<ViewMode>,
+    materials: Res<ViewModeMaterials>,
+    mut wireframe: ResMut<WireframeConfig>,
+    meshes: Query<(Entity, Ref<FlverMesh>, Option<&LitMaterial>)>,
+    lit_materials: Query<&Handle<StandardMaterial>>,
+) {
+    if view_mode.is_changed() {
+        wireframe.global = *view_mode == ViewMode::Wireframe;
+    }
+
+    let material = view_mode
+        .shader_mode()
+        .and_then(|mode| materials.0.get(mode as usize));
+
+    // Meshes spawned since the mode was picked are switched to it too.
+    for (entity, flver_mesh, lit_material) in &meshes {
+        if !view_mode.is_changed() && !flver_mesh.is_added() {
+            continue;
+        }
+
+        let lit = lit_materials.get(entity).ok();
+        let mut entity = commands.entity(entity);
+        match (material, lit_material) {
+            (Some(material), _) => {
+                if let Some(lit) = lit {
+                    entity
+                        .insert(LitMaterial(lit.clone()))
+                        .remove::<Handle<StandardMaterial>>();
+                }
+                entity.insert(material.clone());
+            }
+            (None, Some(LitMaterial(lit))) => {
+                entity
+                    .insert(lit.clone())
+                    .remove::<(LitMaterial, Handle<ViewModeMaterial>)>();
+            }
+            (None, None) => {}
+        }
+    }
+}
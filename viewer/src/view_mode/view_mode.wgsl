@@ -0,0 +1,139 @@
+#import bevy_pbr::{
+    mesh_functions,
+    mesh_view_bindings::view,
+    skinning,
+    view_transformations::position_world_to_clip,
+}
+
+// Kept in sync with ViewMode::shader_mode.
+const MODE_FACE_NORMALS: u32 = 0u;
+const MODE_VERTEX_NORMALS: u32 = 1u;
+const MODE_UV_CHECKER_0: u32 = 2u;
+const MODE_UV_CHECKER_1: u32 = 3u;
+const MODE_VERTEX_COLOR: u32 = 4u;
+
+// Bits of VertexOutput::attributes.
+const HAS_NORMALS: u32 = 1u;
+const HAS_UVS: u32 = 2u;
+const HAS_UVS_B: u32 = 4u;
+const HAS_COLORS: u32 = 8u;
+
+// Drawn where a mode needs an attribute the mesh doesn't have.
+const MISSING: vec4<f32> = vec4<f32>(1.0, 0.0, 1.0, 1.0);
+
+// Checker cells per UV unit.
+const CHECKER_CELLS: f32 = 8.0;
+
+@group(2) @binding(0) var<uniform> mode: u32;
+
+struct Vertex {
+    @builtin(instance_index) instance_index: u32,
+    @location(0) position: vec3<f32>,
+#ifdef VERTEX_NORMALS
+    @location(1) normal: vec3<f32>,
+#endif
+#ifdef VERTEX_UVS
+    @location(2) uv: vec2<f32>,
+#endif
+#ifdef VERTEX_UVS_B
+    @location(3) uv_b: vec2<f32>,
+#endif
+#ifdef SKINNED
+    @location(6) joint_indices: vec4<u32>,
+    @location(7) joint_weights: vec4<f32>,
+#endif
+#ifdef FLVER_VERTEX_COLORS
+    @location(8) color: vec4<f32>,
+#endif
+};
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) world_position: vec4<f32>,
+    @location(1) world_normal: vec3<f32>,
+    @location(2) uv: vec2<f32>,
+    @location(3) uv_b: vec2<f32>,
+    @location(4) color: vec4<f32>,
+    @location(5) @interpolate(flat) attributes: u32,
+};
+
+@vertex
+fn vertex(vertex: Vertex) -> VertexOutput {
+    var out: VertexOutput;
+    out.attributes = 0u;
+
+#ifdef SKINNED
+    let model = skinning::skin_model(vertex.joint_indices, vertex.joint_weights);
+#else
+    let model = mesh_functions::get_model_matrix(vertex.instance_index);
+#endif
+
+    out.world_position = mesh_functions::mesh_position_local_to_world(model, vec4<f32>(vertex.position, 1.0));
+    out.position = position_world_to_clip(out.world_position.xyz);
+
+#ifdef VERTEX_NORMALS
+#ifdef SKINNED
+    out.world_normal = skinning::skin_normals(model, vertex.normal);
+#else
+    out.world_normal = mesh_functions::mesh_normal_local_to_world(vertex.normal, vertex.instance_index);
+#endif
+    out.attributes |= HAS_NORMALS;
+#endif
+
+#ifdef VERTEX_UVS
+    out.uv = vertex.uv;
+    out.attributes |= HAS_UVS;
+#endif
+
+#ifdef VERTEX_UVS_B
+    out.uv_b = vertex.uv_b;
+    out.attributes |= HAS_UVS_B;
+#endif
+
+#ifdef FLVER_VERTEX_COLORS
+    out.color = vertex.color;
+    out.attributes |= HAS_COLORS;
+#endif
+
+    return out;
+}
+
+fn show_normal(normal: vec3<f32>) -> vec4<f32> {
+    return vec4<f32>(normal * 0.5 + 0.5, 1.0);
+}
+
+fn checker(uv: vec2<f32>) -> vec4<f32> {
+    let cell = vec2<i32>(floor(uv * CHECKER_CELLS));
+    let even = (cell.x + cell.y) % 2 == 0;
+
+    // Tinted by the UV so that flipped and rotated islands stand out.
+    let tint = vec3<f32>(fract(uv), 1.0);
+    return vec4<f32>(select(tint * 0.35, tint, even), 1.0);
+}
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    if mode == MODE_FACE_NORMALS {
+        let position = in.world_position.xyz;
+        var normal = normalize(cross(dpdx(position), dpdy(position)));
+        if dot(normal, view.world_position - position) < 0.0 {
+            normal = -normal;
+        }
+        return show_normal(normal);
+    }
+
+    if mode == MODE_VERTEX_NORMALS && (in.attributes & HAS_NORMALS) != 0u {
+        return show_normal(normalize(in.world_normal));
+    }
+    if mode == MODE_UV_CHECKER_0 && (in.attributes & HAS_UVS) != 0u {
+        return checker(in.uv);
+    }
+    if mode == MODE_UV_CHECKER_1 && (in.attributes & HAS_UVS_B) != 0u {
+        return checker(in.uv_b);
+    }
+    if mode == MODE_VERTEX_COLOR && (in.attributes & HAS_COLORS) != 0u {
+        return vec4<f32>(in.color.rgb, 1.0);
+    }
+
+    return MISSING;
+}